@@ -0,0 +1,141 @@
+use std::collections::BTreeMap;
+
+use chrono::SecondsFormat;
+use rusqlite::Connection;
+use tracing::info;
+
+use crate::core::orbit::ric_components;
+use crate::utils::db::{self, DbError, TleAccuracy};
+
+/// Element sets further apart than this are not compared; the difference would
+/// say more about the gap than about the elements.
+pub const MAX_GAP_DAYS: f64 = 30.0;
+
+fn epoch(el: &sgp4::Elements) -> String {
+    el.datetime.and_utc().to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+/// Propagates `previous` to the epoch of `current` and measures how far it is
+/// from `current`'s own state there, in `current`'s RIC frame. `None` when
+/// `current` is not newer, the gap exceeds [`MAX_GAP_DAYS`] or SGP4 fails.
+pub fn compare(previous: &sgp4::Elements, current: &sgp4::Elements, recorded_at: &str) -> Option<TleAccuracy> {
+    let minutes = (current.datetime - previous.datetime).num_milliseconds() as f64 / 60_000.0;
+    if minutes <= 0.0 || minutes > MAX_GAP_DAYS * 1440.0 {
+        return None;
+    }
+    let truth = sgp4::Constants::from_elements(current).ok()?.propagate(0.0).ok()?;
+    let predicted = sgp4::Constants::from_elements(previous).ok()?.propagate(minutes).ok()?;
+    let delta: [f64; 3] = std::array::from_fn(|i| predicted.position[i] - truth.position[i]);
+    let [radial_km, along_track_km, cross_track_km] = ric_components(&truth.position, &truth.velocity, &delta);
+    Some(TleAccuracy {
+        norad_id: current.norad_id,
+        epoch: epoch(current),
+        previous_epoch: epoch(previous),
+        gap_hours: minutes / 60.0,
+        error_km: (radial_km.powi(2) + along_track_km.powi(2) + cross_track_km.powi(2)).sqrt(),
+        radial_km,
+        along_track_km,
+        cross_track_km,
+        recorded_at: recorded_at.to_string(),
+    })
+}
+
+/// Compares every element set that is newer than the archived ones with its
+/// predecessor (archived, or earlier in `elements`) and stores the differences.
+/// Must run before the elements are added to the TLE history; sets already
+/// archived are skipped. Returns the number of new samples.
+pub fn record_accuracy(conn: &Connection, elements: &[sgp4::Elements], recorded_at: &str) -> Result<usize, DbError> {
+    let mut by_id: BTreeMap<u64, Vec<&sgp4::Elements>> = BTreeMap::new();
+    for el in elements {
+        by_id.entry(el.norad_id).or_default().push(el);
+    }
+    let mut samples = Vec::new();
+    for (norad_id, mut sets) in by_id {
+        sets.sort_by_key(|e| e.datetime);
+        let Some(newest) = sets.last() else { continue };
+        let Some(archived) = db::latest_tle_until(conn, norad_id, &epoch(newest))? else {
+            continue;
+        };
+        let Ok(archived) = sgp4::Elements::from_tle(archived.name, archived.line1.as_bytes(), archived.line2.as_bytes()) else {
+            continue;
+        };
+        let mut previous = &archived;
+        for el in sets {
+            if el.datetime <= previous.datetime {
+                continue;
+            }
+            samples.extend(compare(previous, el, recorded_at));
+            previous = el;
+        }
+    }
+    let stored = db::insert_tle_accuracy(conn, &samples)?;
+    if stored > 0 {
+        info!(count = stored, "Recorded element set accuracy");
+    }
+    Ok(stored)
+}
+
+/// Rolling statistics of a satellite's accuracy samples.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccuracyStats {
+    pub samples: usize,
+    pub median_error_km: f64,
+    pub mean_error_km: f64,
+    pub max_error_km: f64,
+    pub median_gap_hours: f64,
+    /// Median of each sample's error divided by its gap in days: how fast a
+    /// prediction from this object's elements typically degrades.
+    pub median_error_km_per_day: f64,
+    pub latest_epoch: String,
+}
+
+fn median(mut values: Vec<f64>) -> f64 {
+    values.sort_by(f64::total_cmp);
+    let n = values.len();
+    if n % 2 == 1 {
+        values[n / 2]
+    } else {
+        (values[n / 2 - 1] + values[n / 2]) / 2.0
+    }
+}
+
+/// Statistics over `samples`, which are taken to be one satellite's, oldest
+/// first; `None` when there are none.
+pub fn summarize(samples: &[TleAccuracy]) -> Option<AccuracyStats> {
+    let latest = samples.last()?;
+    let errors: Vec<f64> = samples.iter().map(|s| s.error_km).collect();
+    Some(AccuracyStats {
+        samples: samples.len(),
+        median_error_km: median(errors.clone()),
+        mean_error_km: errors.iter().sum::<f64>() / errors.len() as f64,
+        max_error_km: errors.iter().copied().fold(0.0, f64::max),
+        median_gap_hours: median(samples.iter().map(|s| s.gap_hours).collect()),
+        median_error_km_per_day: median(samples.iter().map(|s| s.error_km / (s.gap_hours / 24.0)).collect()),
+        latest_epoch: latest.epoch.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_consecutive_sets_and_summarizes() {
+        let current = crate::testing::fixtures::catalog().swap_remove(0);
+        // The same mean elements a day earlier put the satellite elsewhere now
+        let mut previous = crate::testing::fixtures::catalog().swap_remove(0);
+        previous.datetime -= chrono::Duration::days(1);
+        let sample = compare(&previous, &current, "now").unwrap();
+        assert_eq!(sample.gap_hours, 24.0);
+        assert!(sample.error_km > 10.0);
+        assert!((sample.error_km - (sample.radial_km.powi(2) + sample.along_track_km.powi(2) + sample.cross_track_km.powi(2)).sqrt()).abs() < 1e-9);
+        assert!(compare(&current, &previous, "now").is_none());
+        assert!(compare(&current, &current, "now").is_none());
+
+        let with = |error_km: f64, gap_hours: f64, epoch: &str| TleAccuracy { error_km, gap_hours, epoch: epoch.to_string(), ..sample.clone() };
+        let stats = summarize(&[with(1.0, 12.0, "a"), with(4.0, 24.0, "b"), with(2.0, 48.0, "c")]).unwrap();
+        assert_eq!((stats.samples, stats.median_error_km, stats.max_error_km), (3, 2.0, 4.0));
+        assert_eq!((stats.median_gap_hours, stats.median_error_km_per_day, stats.latest_epoch.as_str()), (24.0, 2.0, "c"));
+        assert!(summarize(&[]).is_none());
+    }
+}
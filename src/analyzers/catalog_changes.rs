@@ -0,0 +1,108 @@
+use std::collections::BTreeMap;
+
+use chrono::SecondsFormat;
+use rusqlite::Connection;
+use serde::Serialize;
+use tracing::info;
+
+use crate::utils::db::{self, CatalogChangeRow, CatalogMember, DbError};
+
+pub const ADDED: &str = "added";
+pub const REMOVED: &str = "removed";
+pub const UPDATED: &str = "updated";
+
+/// What changed in one load, as broadcast to stream clients.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CatalogChanges {
+    pub changed_at: String,
+    pub added: Vec<u64>,
+    pub removed: Vec<u64>,
+    /// Objects whose element epoch advanced.
+    pub updated: Vec<u64>,
+}
+
+impl CatalogChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.updated.is_empty()
+    }
+}
+
+fn epoch(el: &sgp4::Elements) -> String {
+    el.datetime.and_utc().to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+/// Catalog members of `elements`, one per NORAD ID (the last one wins).
+pub fn members(elements: &[sgp4::Elements]) -> Vec<CatalogMember> {
+    let by_id: BTreeMap<u64, &sgp4::Elements> = elements.iter().map(|e| (e.norad_id, e)).collect();
+    by_id.into_values().map(|e| CatalogMember { norad_id: e.norad_id, name: e.object_name.clone(), epoch: epoch(e) }).collect()
+}
+
+/// Objects that appeared, disappeared or got a newer epoch between two catalogs.
+/// Epochs share one RFC 3339 format, so they compare as strings.
+pub fn diff(previous: &[CatalogMember], current: &[CatalogMember], changed_at: &str) -> Vec<CatalogChangeRow> {
+    let before: BTreeMap<u64, &CatalogMember> = previous.iter().map(|m| (m.norad_id, m)).collect();
+    let after: BTreeMap<u64, &CatalogMember> = current.iter().map(|m| (m.norad_id, m)).collect();
+    let row = |m: &CatalogMember, kind: &str, previous_epoch: Option<&String>, epoch: Option<&String>| CatalogChangeRow {
+        changed_at: changed_at.to_string(),
+        norad_id: m.norad_id,
+        kind: kind.to_string(),
+        name: m.name.clone(),
+        previous_epoch: previous_epoch.cloned(),
+        epoch: epoch.cloned(),
+    };
+    let mut changes = Vec::new();
+    for (id, m) in &after {
+        match before.get(id) {
+            None => changes.push(row(m, ADDED, None, Some(&m.epoch))),
+            Some(old) if m.epoch > old.epoch => changes.push(row(m, UPDATED, Some(&old.epoch), Some(&m.epoch))),
+            Some(_) => {}
+        }
+    }
+    for (id, m) in &before {
+        if !after.contains_key(id) {
+            changes.push(row(m, REMOVED, Some(&m.epoch), None));
+        }
+    }
+    changes
+}
+
+/// Compares the loaded catalog with the previous load, appends the differences
+/// to the change feed and remembers this catalog. The first load only records
+/// the catalog, since every object would look added.
+pub fn record_changes(conn: &Connection, elements: &[sgp4::Elements], changed_at: &str) -> Result<CatalogChanges, DbError> {
+    let previous = db::list_catalog_members(conn)?;
+    let current = members(elements);
+    let rows = if previous.is_empty() { Vec::new() } else { diff(&previous, &current, changed_at) };
+    db::record_catalog_changes(conn, &rows, &current)?;
+
+    let mut changes = CatalogChanges { changed_at: changed_at.to_string(), ..Default::default() };
+    for r in &rows {
+        match r.kind.as_str() {
+            ADDED => changes.added.push(r.norad_id),
+            REMOVED => changes.removed.push(r.norad_id),
+            _ => changes.updated.push(r.norad_id),
+        }
+    }
+    if !changes.is_empty() {
+        info!(added = changes.added.len(), removed = changes.removed.len(), updated = changes.updated.len(), "Recorded catalog changes");
+    }
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_reports_added_removed_and_advanced_epochs() {
+        let member = |norad_id, epoch: &str| CatalogMember { norad_id, name: None, epoch: epoch.to_string() };
+        let previous = [member(1, "2024-01-01T00:00:00.000000Z"), member(2, "2024-01-01T00:00:00.000000Z"), member(3, "2024-01-02T00:00:00.000000Z")];
+        let current = [member(1, "2024-01-03T00:00:00.000000Z"), member(3, "2024-01-01T00:00:00.000000Z"), member(4, "2024-01-03T00:00:00.000000Z")];
+        let changes: Vec<(u64, String)> = diff(&previous, &current, "now").into_iter().map(|c| (c.norad_id, c.kind)).collect();
+        assert_eq!(changes, vec![(1, UPDATED.to_string()), (4, ADDED.to_string()), (2, REMOVED.to_string())]);
+
+        let mut loaded = crate::testing::fixtures::catalog();
+        loaded.push(crate::testing::fixtures::catalog().swap_remove(0));
+        assert_eq!(members(&loaded).len(), 2);
+    }
+}
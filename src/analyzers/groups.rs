@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+
+use rusqlite::Connection;
+use tracing::{info, warn};
+
+use crate::analyzers::launches::designator_of;
+use crate::analyzers::new_objects::{NEW_TAG, UPLOADED_TAG};
+use crate::collectors::satcat::SatcatEntry;
+use crate::core::catalog::Catalog;
+use crate::core::custom::CUSTOM_ID_BASE;
+use crate::utils::db::{self, DbError};
+
+/// Longest accepted group name, in characters.
+pub const MAX_GROUP_NAME_LEN: usize = 64;
+/// Longest accepted filter, in characters.
+pub const MAX_FILTER_LEN: usize = 512;
+
+/// Which property of an object a filter term matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterField {
+    /// International designator in the `YYYY-NNNP` form, from the elements or
+    /// SATCAT.
+    Designator,
+    /// Object name, case-insensitively.
+    Name,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Term {
+    field: FilterField,
+    pattern: Vec<char>,
+}
+
+/// Membership rule of a dynamic group: comma-separated terms, any of which
+/// must match. A term is a pattern with `*` (any run of characters) and `?`
+/// (one character), matched against the designator, or against the name when
+/// prefixed with `name:`. `1999-025*` is the Fengyun-1C debris cloud,
+/// `1982-092*, name:COSMOS 1408*` adds objects named after Cosmos 1408.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupFilter {
+    terms: Vec<Term>,
+}
+
+impl GroupFilter {
+    pub fn parse(text: &str) -> Result<GroupFilter, String> {
+        if text.chars().count() > MAX_FILTER_LEN {
+            return Err(format!("filter is longer than {} characters", MAX_FILTER_LEN));
+        }
+        let mut terms = Vec::new();
+        for term in text.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+            let (field, pattern) = match term.split_once(':') {
+                Some((field, pattern)) => match field.trim().to_ascii_lowercase().as_str() {
+                    "designator" => (FilterField::Designator, pattern.trim()),
+                    "name" => (FilterField::Name, pattern.trim()),
+                    other => return Err(format!("unknown filter field {:?} (designator, name)", other)),
+                },
+                None => (FilterField::Designator, term),
+            };
+            if pattern.is_empty() {
+                return Err(format!("empty pattern in {:?}", term));
+            }
+            terms.push(Term { field, pattern: pattern.to_uppercase().chars().collect() });
+        }
+        if terms.is_empty() {
+            return Err("filter has no terms".to_string());
+        }
+        Ok(GroupFilter { terms })
+    }
+
+    /// Whether the element set at `index` in `catalog` matches any term.
+    pub fn matches(&self, catalog: &Catalog, index: usize, satcat: &HashMap<u64, SatcatEntry>) -> bool {
+        let mut designator = None;
+        self.terms.iter().any(|term| match term.field {
+            FilterField::Designator => designator
+                .get_or_insert_with(|| designator_of(catalog[index].norad_id, catalog.designator(index), satcat).map(|d| format!("{}{}", d.launch, d.piece)))
+                .as_deref()
+                .is_some_and(|d| glob_match(&term.pattern, d)),
+            FilterField::Name => catalog.name(index).is_some_and(|n| glob_match(&term.pattern, &n.trim().to_uppercase())),
+        })
+    }
+
+    /// NORAD IDs of the matching objects, ascending and without duplicates.
+    pub fn members(&self, catalog: &Catalog, satcat: &HashMap<u64, SatcatEntry>) -> Vec<u64> {
+        let mut ids: Vec<u64> = (0..catalog.len()).filter(|&i| self.matches(catalog, i, satcat)).map(|i| catalog[i].norad_id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+}
+
+/// Whether `text` matches `pattern` in full, `*` and `?` being wildcards.
+fn glob_match(pattern: &[char], text: &str) -> bool {
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Pattern position after the last `*` and the text position it resumes at
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((sp, st)) => {
+                    p = sp;
+                    t = st + 1;
+                    star = Some((sp, st + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Checks a group name: 1 to 64 letters, digits, `-`, `_` or `.`, and not one
+/// of the tags the server applies itself.
+pub fn validate_group_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.chars().count() > MAX_GROUP_NAME_LEN || !name.chars().all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c)) {
+        return Err(format!("group name must have 1 to {} letters, digits, '-', '_' or '.'", MAX_GROUP_NAME_LEN));
+    }
+    if name == NEW_TAG || name == UPLOADED_TAG {
+        return Err(format!("tag {:?} is maintained by the server", name));
+    }
+    Ok(())
+}
+
+/// Tags the current members of a group and untags the objects that no longer
+/// match. Custom element sets are never members, as they are never tagged.
+/// Returns the number of members.
+pub fn update_group(conn: &Connection, group: &db::DynamicGroup, catalog: &Catalog, satcat: &HashMap<u64, SatcatEntry>, now: &str) -> Result<usize, DbError> {
+    let mut members = match GroupFilter::parse(&group.filter) {
+        Ok(filter) => filter.members(catalog, satcat),
+        Err(e) => {
+            warn!(group = %group.name, error = %e, "Invalid dynamic group filter");
+            return Ok(0);
+        }
+    };
+    members.retain(|id| *id < CUSTOM_ID_BASE);
+    db::replace_tag_members(conn, &group.name, group.tenant.as_deref(), &members, now)?;
+    Ok(members.len())
+}
+
+/// Brings every tenant's dynamic groups up to date with a freshly loaded catalog.
+pub fn refresh_dynamic_groups(conn: &Connection, catalog: &Catalog, now: &str) -> Result<(), DbError> {
+    let groups = db::list_dynamic_groups(conn, None)?;
+    if groups.is_empty() {
+        return Ok(());
+    }
+    let satcat = db::satcat_map(conn)?;
+    for group in &groups {
+        let count = update_group(conn, group, catalog, &satcat, now)?;
+        info!(group = %group.name, tenant = group.tenant.as_deref().unwrap_or_default(), count, "Updated dynamic group");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_objects_by_designator_and_name_and_keeps_groups_current() {
+        assert!(glob_match(&"1999-025*".chars().collect::<Vec<_>>(), "1999-025ABC"));
+        assert!(glob_match(&"*ZARYA?".chars().collect::<Vec<_>>(), "ISS (ZARYA)"));
+        assert!(!glob_match(&"1999-025?".chars().collect::<Vec<_>>(), "1999-025AB"));
+        assert!(GroupFilter::parse(" , ").is_err() && GroupFilter::parse("norad:25544").is_err() && GroupFilter::parse("name:").is_err());
+        assert!(validate_group_name("fengyun-1c.debris").is_ok());
+        assert!(validate_group_name("new").is_err() && validate_group_name("a/b").is_err());
+
+        // The ISS is 1998-067A, NOAA 18 is 2005-018A
+        let catalog = Catalog::new(crate::testing::fixtures::catalog());
+        let satcat = HashMap::new();
+        assert_eq!(GroupFilter::parse("1998-067*").unwrap().members(&catalog, &satcat), [25544]);
+        assert_eq!(GroupFilter::parse("2005-018A, name:iss*").unwrap().members(&catalog, &satcat), [25544, 28654]);
+        assert!(GroupFilter::parse("1999-025*").unwrap().members(&catalog, &satcat).is_empty());
+
+        let conn = Connection::open_in_memory().unwrap();
+        db::init_schema(&conn).unwrap();
+        assert!(db::upsert_dynamic_group(&conn, "stations", None, "1998-067*", "2024-01-01T00:00:00Z").unwrap());
+        refresh_dynamic_groups(&conn, &catalog, "2024-01-01T00:00:00Z").unwrap();
+        assert_eq!(db::tagged_norad_ids(&conn, "stations", None).unwrap(), [25544]);
+        assert!(!db::upsert_dynamic_group(&conn, "stations", None, "2005-018*", "2024-01-02T00:00:00Z").unwrap());
+        refresh_dynamic_groups(&conn, &catalog, "2024-01-02T00:00:00Z").unwrap();
+        assert_eq!(db::tagged_norad_ids(&conn, "stations", None).unwrap(), [28654]);
+
+        // Another tenant's group of the same name has its own members
+        assert!(db::upsert_dynamic_group(&conn, "stations", Some("club"), "1998-067*", "2024-01-03T00:00:00Z").unwrap());
+        refresh_dynamic_groups(&conn, &catalog, "2024-01-03T00:00:00Z").unwrap();
+        assert_eq!(db::tagged_norad_ids(&conn, "stations", Some("club")).unwrap(), [25544]);
+        assert_eq!(db::tagged_norad_ids(&conn, "stations", None).unwrap(), [28654]);
+        assert_eq!(db::tag_owner(&conn, "stations", Some("club")).unwrap().as_deref(), Some("club"));
+        assert_eq!(db::tag_owner(&conn, "stations", Some("other")).unwrap(), None);
+        assert_eq!(db::list_dynamic_groups(&conn, Some("other")).unwrap().len(), 1);
+
+        assert!(db::delete_dynamic_group(&conn, "stations", None).unwrap());
+        assert!(db::tagged_norad_ids(&conn, "stations", None).unwrap().is_empty());
+        assert_eq!(db::tagged_norad_ids(&conn, "stations", Some("club")).unwrap(), [25544]);
+    }
+}
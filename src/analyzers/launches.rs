@@ -0,0 +1,88 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::collectors::satcat::SatcatEntry;
+use crate::core::catalog::Catalog;
+
+/// A parsed international (COSPAR) designator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Designator {
+    /// Launch part, `YYYY-NNN`.
+    pub launch: String,
+    /// Piece letters, `A` for the first catalogued object.
+    pub piece: String,
+}
+
+/// Parses `1998-067A` (SATCAT, OMM) or `98067A` (TLE line 1, years from 1957).
+pub fn parse_designator(text: &str) -> Option<Designator> {
+    let text = text.trim().to_ascii_uppercase();
+    let (year, rest) = match text.split_once('-') {
+        Some((year, rest)) if year.len() == 4 => (year.parse::<u32>().ok()?, rest.to_string()),
+        Some(_) => return None,
+        None => {
+            let yy: u32 = text.get(..2)?.parse().ok()?;
+            (if yy >= 57 { 1900 + yy } else { 2000 + yy }, text[2..].to_string())
+        }
+    };
+    let number = rest.get(..3).filter(|n| n.chars().all(|c| c.is_ascii_digit()))?;
+    let piece = &rest[3..];
+    if piece.is_empty() || !piece.chars().all(|c| c.is_ascii_uppercase()) {
+        return None;
+    }
+    Some(Designator { launch: format!("{}-{}", year, number), piece: piece.to_string() })
+}
+
+/// Normalises a launch designator given as `1998-067` or `98067`.
+pub fn parse_launch(text: &str) -> Option<String> {
+    parse_designator(&format!("{}A", text.trim())).map(|d| d.launch)
+}
+
+/// Orders pieces `A`..`Z`, then `AA`..: shorter letter groups first.
+pub fn piece_order(piece: &str) -> (usize, &str) {
+    (piece.len(), piece)
+}
+
+/// Designator of a catalogued object: its own, else from SATCAT.
+pub fn designator_of(norad_id: u64, designator: Option<&str>, satcat: &HashMap<u64, SatcatEntry>) -> Option<Designator> {
+    designator.and_then(parse_designator).or_else(|| satcat.get(&norad_id)?.object_id.as_deref().and_then(parse_designator))
+}
+
+/// A loaded object of one launch.
+pub struct Piece<'a> {
+    pub designator: Designator,
+    pub elements: &'a sgp4::Elements,
+    pub name: Option<&'a str>,
+}
+
+/// The loaded objects grouped by launch, each group in piece order.
+pub fn group_by_launch<'a>(catalog: &'a Catalog, satcat: &HashMap<u64, SatcatEntry>) -> BTreeMap<String, Vec<Piece<'a>>> {
+    let mut groups: BTreeMap<String, Vec<Piece>> = BTreeMap::new();
+    for (i, el) in catalog.iter().enumerate() {
+        if let Some(d) = designator_of(el.norad_id, catalog.designator(i), satcat) {
+            groups.entry(d.launch.clone()).or_default().push(Piece { designator: d, elements: el, name: catalog.name(i) });
+        }
+    }
+    for pieces in groups.values_mut() {
+        pieces.sort_by(|a, b| piece_order(&a.designator.piece).cmp(&piece_order(&b.designator.piece)));
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_both_designator_layouts() {
+        let iss = Designator { launch: "1998-067".to_string(), piece: "A".to_string() };
+        assert_eq!(parse_designator("98067A"), Some(iss.clone()));
+        assert_eq!(parse_designator("1998-067a "), Some(iss));
+        assert_eq!(parse_designator("24043BZ").map(|d| (d.launch, d.piece)), Some(("2024-043".to_string(), "BZ".to_string())));
+        assert_eq!(parse_launch("57001").as_deref(), Some("1957-001"));
+        assert_eq!(parse_launch("2024-043").as_deref(), Some("2024-043"));
+        assert!(parse_designator("98067").is_none() && parse_designator("98-067A").is_none() && parse_designator("").is_none());
+
+        let mut pieces = vec!["AA", "B", "Z", "A"];
+        pieces.sort_by_key(|p| piece_order(p));
+        assert_eq!(pieces, vec!["A", "B", "Z", "AA"]);
+    }
+}
@@ -1 +1,10 @@
-// Pattern and anomaly detection
\ No newline at end of file
+// Pattern and anomaly detection
+pub mod new_objects;
+pub mod population;
+pub mod catalog_changes;
+pub mod snapshot_anomalies;
+pub mod accuracy;
+pub mod launches;
+pub mod shells;
+pub mod groups;
+pub mod query;
@@ -0,0 +1,54 @@
+use std::collections::HashSet;
+
+use rusqlite::Connection;
+use tracing::info;
+
+use crate::core::catalog::Catalog;
+use crate::utils::db::{self, DbError};
+use crate::utils::notify::{self, Alert};
+
+/// Tag applied to NORAD IDs seen for the first time.
+pub const NEW_TAG: &str = "new";
+/// Tag applied to objects whose elements were uploaded by a user.
+pub const UPLOADED_TAG: &str = "uploaded";
+
+/// Tags every element set whose NORAD ID has never been archived before as `new`.
+/// Must run before the elements are added to the TLE history. On an empty history
+/// (first start) nothing is tagged, since every object would look new.
+pub fn detect_and_tag(conn: &Connection, elements: &[sgp4::Elements], tagged_at: &str) -> Result<Vec<u64>, DbError> {
+    let known = db::known_norad_ids(conn)?;
+    if known.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut seen = HashSet::new();
+    let new_ids: Vec<u64> = elements
+        .iter()
+        .map(|e| e.norad_id)
+        .filter(|id| !known.contains(id) && seen.insert(*id))
+        .collect();
+    db::tag_satellites(conn, &new_ids, NEW_TAG, None, tagged_at)?;
+    if !new_ids.is_empty() {
+        info!(count = new_ids.len(), "Tagged newly appeared objects");
+    }
+    Ok(new_ids)
+}
+
+/// Sends one alert listing the newly appeared objects, if any.
+pub async fn notify_new_objects(new_ids: &[u64], catalog: &Catalog) {
+    if new_ids.is_empty() {
+        return;
+    }
+    let objects: Vec<serde_json::Value> = new_ids
+        .iter()
+        .map(|id| {
+            let name = catalog.name_of(*id);
+            serde_json::json!({ "norad_id": id, "name": name })
+        })
+        .collect();
+    notify::send(&Alert {
+        kind: "new_objects",
+        message: format!("{} new object(s) appeared in the catalog", new_ids.len()),
+        payload: serde_json::json!({ "objects": objects }),
+    })
+    .await;
+}
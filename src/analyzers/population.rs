@@ -0,0 +1,91 @@
+use crate::core::orbit::{perigee_apogee_radius_km, EARTH_RADIUS_KM};
+
+/// Orbital quantity a catalog population is binned by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Field {
+    /// Mean altitude, i.e. semi-major axis minus the Earth's radius (km).
+    Altitude,
+    /// Inclination (degrees).
+    Inclination,
+}
+
+impl Field {
+    pub fn unit(self) -> &'static str {
+        match self {
+            Field::Altitude => "km",
+            Field::Inclination => "deg",
+        }
+    }
+
+    pub fn value(self, el: &sgp4::Elements) -> f64 {
+        match self {
+            Field::Altitude => {
+                let (rp, ra) = perigee_apogee_radius_km(el);
+                (rp + ra) / 2.0 - EARTH_RADIUS_KM
+            }
+            Field::Inclination => el.inclination,
+        }
+    }
+}
+
+/// Counts of values in equal-width bins over `[min, max]`; values outside the
+/// range are only counted in `below` / `above`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    pub min: f64,
+    pub max: f64,
+    pub counts: Vec<u64>,
+    pub below: u64,
+    pub above: u64,
+}
+
+impl Histogram {
+    pub fn bin_width(&self) -> f64 {
+        (self.max - self.min) / self.counts.len() as f64
+    }
+}
+
+/// Bins `values` into `bins` bins. Without explicit bounds the range spans the
+/// values; an empty or single-valued range is widened to one unit.
+pub fn histogram(values: &[f64], bins: usize, min: Option<f64>, max: Option<f64>) -> Histogram {
+    let finite = values.iter().copied().filter(|v| v.is_finite());
+    let min = min.unwrap_or_else(|| finite.clone().fold(f64::INFINITY, f64::min));
+    let max = max.unwrap_or_else(|| finite.clone().fold(f64::NEG_INFINITY, f64::max));
+    let (min, max) = if !min.is_finite() || !max.is_finite() {
+        (0.0, 1.0)
+    } else if max <= min {
+        (min, min + 1.0)
+    } else {
+        (min, max)
+    };
+    let mut out = Histogram { min, max, counts: vec![0; bins.max(1)], below: 0, above: 0 };
+    let width = out.bin_width();
+    let last = out.counts.len() - 1;
+    for v in finite {
+        if v < min {
+            out.below += 1;
+        } else if v > max {
+            out.above += 1;
+        } else {
+            // The top edge belongs to the last bin
+            out.counts[(((v - min) / width) as usize).min(last)] += 1;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bins_values_and_counts_outliers() {
+        let h = histogram(&[0.0, 0.5, 1.0, 2.5, 4.0, f64::NAN], 4, None, None);
+        assert_eq!((h.min, h.max, h.bin_width()), (0.0, 4.0, 1.0));
+        assert_eq!(h.counts, vec![2, 1, 1, 1]);
+        let zoomed = histogram(&[100.0, 510.0, 540.0, 35786.0], 2, Some(500.0), Some(600.0));
+        assert_eq!((zoomed.counts.clone(), zoomed.below, zoomed.above), (vec![2, 0], 1, 1));
+        assert_eq!(histogram(&[], 3, None, None).counts, vec![0, 0, 0]);
+    }
+}
@@ -0,0 +1,480 @@
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
+use crate::analyzers::launches::parse_designator;
+use crate::collectors::satcat::{normalize_object_type, SatcatEntry};
+use crate::core::catalog::Catalog;
+use crate::core::orbit::{perigee_apogee_radius_km, EARTH_RADIUS_KM};
+
+/// Longest accepted expression, in characters.
+pub const MAX_QUERY_LEN: usize = 1024;
+/// Deepest nesting of parentheses and `NOT`.
+const MAX_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("filter column {column}: {message}")]
+pub struct QueryError {
+    /// 1-based character position the problem was found at.
+    pub column: usize,
+    pub message: String,
+}
+
+/// A property of a catalogued object a query compares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryField {
+    NoradId,
+    /// Degrees.
+    Inclination,
+    Eccentricity,
+    /// Revolutions per day.
+    MeanMotion,
+    /// Minutes.
+    Period,
+    /// Altitude above the equatorial radius (km).
+    Perigee,
+    Apogee,
+    /// Mean altitude (km).
+    Altitude,
+    /// Right ascension of the ascending node (degrees).
+    Raan,
+    Bstar,
+    /// Days since the element set epoch.
+    Age,
+    /// From the international designator.
+    LaunchYear,
+    Name,
+    /// `YYYY-NNNP`, from the elements or SATCAT.
+    Designator,
+    /// SATCAT owner code.
+    Country,
+    /// SATCAT object type; `=` also takes the Celestrak codes (`DEB`, `R/B`).
+    ObjectType,
+    /// SATCAT launch date, `YYYY-MM-DD`.
+    LaunchDate,
+}
+
+const FIELDS: [(&str, QueryField); 17] = [
+    ("norad_id", QueryField::NoradId),
+    ("inclination", QueryField::Inclination),
+    ("eccentricity", QueryField::Eccentricity),
+    ("mean_motion", QueryField::MeanMotion),
+    ("period", QueryField::Period),
+    ("perigee", QueryField::Perigee),
+    ("apogee", QueryField::Apogee),
+    ("altitude", QueryField::Altitude),
+    ("raan", QueryField::Raan),
+    ("bstar", QueryField::Bstar),
+    ("age", QueryField::Age),
+    ("launch_year", QueryField::LaunchYear),
+    ("name", QueryField::Name),
+    ("designator", QueryField::Designator),
+    ("country", QueryField::Country),
+    ("object_type", QueryField::ObjectType),
+    ("launch_date", QueryField::LaunchDate),
+];
+
+impl QueryField {
+    fn is_text(self) -> bool {
+        matches!(self, QueryField::Name | QueryField::Designator | QueryField::Country | QueryField::ObjectType | QueryField::LaunchDate)
+    }
+
+    fn needs_satcat(self) -> bool {
+        matches!(self, QueryField::Designator | QueryField::LaunchYear | QueryField::Country | QueryField::ObjectType | QueryField::LaunchDate)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+    /// Case-insensitive substring, text fields only.
+    Contains,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    Number(f64),
+    /// Upper case.
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare { field: QueryField, op: CompareOp, operand: Operand },
+}
+
+/// What a query is evaluated against: the catalog entry with its element set
+/// and SATCAT record, when there are any.
+#[derive(Clone, Copy)]
+pub struct Subject<'a> {
+    pub norad_id: u64,
+    pub name: Option<&'a str>,
+    /// International designator as given with the element set.
+    pub designator: Option<&'a str>,
+    pub elements: Option<&'a sgp4::Elements>,
+    pub satcat: Option<&'a SatcatEntry>,
+}
+
+impl<'a> Subject<'a> {
+    /// An element set that carries its own name and designator, as parsed.
+    pub fn of(el: &'a sgp4::Elements, satcat: Option<&'a SatcatEntry>) -> Subject<'a> {
+        Subject { norad_id: el.norad_id, name: el.object_name.as_deref(), designator: el.international_designator.as_deref(), elements: Some(el), satcat }
+    }
+
+    /// The element set at `index` in a catalog, named from it.
+    pub fn in_catalog(catalog: &'a Catalog, index: usize, satcat: Option<&'a SatcatEntry>) -> Subject<'a> {
+        let el = &catalog[index];
+        Subject { norad_id: el.norad_id, name: catalog.name(index), designator: catalog.designator(index), elements: Some(el), satcat }
+    }
+
+    fn number(&self, field: QueryField, now: DateTime<Utc>) -> Option<f64> {
+        if field == QueryField::NoradId {
+            return Some(self.norad_id as f64);
+        }
+        if field == QueryField::LaunchYear {
+            return self.designator().and_then(|d| d.get(..4)?.parse().ok());
+        }
+        let el = self.elements?;
+        let altitudes = || {
+            let (rp, ra) = perigee_apogee_radius_km(el);
+            (rp - EARTH_RADIUS_KM, ra - EARTH_RADIUS_KM)
+        };
+        Some(match field {
+            QueryField::Inclination => el.inclination,
+            QueryField::Eccentricity => el.eccentricity,
+            QueryField::MeanMotion => el.mean_motion,
+            QueryField::Period => 1440.0 / el.mean_motion,
+            QueryField::Perigee => altitudes().0,
+            QueryField::Apogee => altitudes().1,
+            QueryField::Altitude => {
+                let (perigee, apogee) = altitudes();
+                (perigee + apogee) / 2.0
+            }
+            QueryField::Raan => el.right_ascension,
+            QueryField::Bstar => el.drag_term,
+            QueryField::Age => (now.naive_utc() - el.datetime).num_seconds() as f64 / 86_400.0,
+            _ => return None,
+        })
+    }
+
+    fn designator(&self) -> Option<String> {
+        self.designator
+            .and_then(parse_designator)
+            .or_else(|| self.satcat?.object_id.as_deref().and_then(parse_designator))
+            .map(|d| format!("{}{}", d.launch, d.piece))
+    }
+
+    fn text(&self, field: QueryField) -> Option<String> {
+        let text = match field {
+            QueryField::Name => self.name.map(str::to_string),
+            QueryField::Designator => self.designator(),
+            QueryField::Country => self.satcat.map(|s| s.owner.clone()),
+            QueryField::ObjectType => self.satcat.map(|s| s.object_type.clone()),
+            QueryField::LaunchDate => self.satcat.and_then(|s| s.launch_date.clone()),
+            _ => None,
+        };
+        text.map(|t| t.trim().to_uppercase())
+    }
+}
+
+/// A filter over the catalog enriched with SATCAT metadata and derived orbit
+/// quantities, e.g. `inclination>97 AND perigee<600 AND name~"DOVE"`.
+///
+/// Comparisons are `field op value` with `<`, `<=`, `>`, `>=`, `=`, `!=` and
+/// `~` (contains), joined by `AND`, `OR` and `NOT` (any case, `AND` binding
+/// tighter) and grouped with parentheses. Text values are compared without
+/// regard to case and may be quoted; a field an object has no value for (no
+/// SATCAT record, no loaded elements) matches no comparison.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SatelliteQuery {
+    expr: Expr,
+    needs_satcat: bool,
+}
+
+impl SatelliteQuery {
+    pub fn parse(text: &str) -> Result<SatelliteQuery, QueryError> {
+        if text.chars().count() > MAX_QUERY_LEN {
+            return Err(QueryError { column: MAX_QUERY_LEN, message: format!("filter is longer than {} characters", MAX_QUERY_LEN) });
+        }
+        let mut parser = Parser { chars: text.chars().collect(), pos: 0, depth: 0 };
+        let expr = parser.or()?;
+        parser.skip_space();
+        if parser.pos < parser.chars.len() {
+            return Err(parser.error("expected AND, OR or the end of the filter"));
+        }
+        let needs_satcat = expr.fields().iter().any(|f| f.needs_satcat());
+        Ok(SatelliteQuery { expr, needs_satcat })
+    }
+
+    /// Whether any field is read from SATCAT, so callers only load it when needed.
+    pub fn needs_satcat(&self) -> bool {
+        self.needs_satcat
+    }
+
+    /// Evaluates the query at `now`, which only `age` depends on.
+    pub fn matches(&self, subject: &Subject, now: DateTime<Utc>) -> bool {
+        self.expr.eval(subject, now)
+    }
+}
+
+impl std::str::FromStr for SatelliteQuery {
+    type Err = QueryError;
+
+    fn from_str(s: &str) -> Result<SatelliteQuery, QueryError> {
+        SatelliteQuery::parse(s)
+    }
+}
+
+impl Expr {
+    fn fields(&self) -> Vec<QueryField> {
+        match self {
+            Expr::And(a, b) | Expr::Or(a, b) => [a.fields(), b.fields()].concat(),
+            Expr::Not(e) => e.fields(),
+            Expr::Compare { field, .. } => vec![*field],
+        }
+    }
+
+    fn eval(&self, subject: &Subject, now: DateTime<Utc>) -> bool {
+        match self {
+            Expr::And(a, b) => a.eval(subject, now) && b.eval(subject, now),
+            Expr::Or(a, b) => a.eval(subject, now) || b.eval(subject, now),
+            Expr::Not(e) => !e.eval(subject, now),
+            Expr::Compare { field, op, operand: Operand::Number(value) } => {
+                subject.number(*field, now).is_some_and(|v| compare(v.partial_cmp(value), *op))
+            }
+            Expr::Compare { field, op, operand: Operand::Text(value) } => subject.text(*field).is_some_and(|v| match op {
+                CompareOp::Contains => v.contains(value.as_str()),
+                _ => compare(Some(v.as_str().cmp(value)), *op),
+            }),
+        }
+    }
+}
+
+fn compare(ordering: Option<std::cmp::Ordering>, op: CompareOp) -> bool {
+    use std::cmp::Ordering::*;
+    match (ordering, op) {
+        (None, _) => false,
+        (Some(o), CompareOp::Lt) => o == Less,
+        (Some(o), CompareOp::Le) => o != Greater,
+        (Some(o), CompareOp::Gt) => o == Greater,
+        (Some(o), CompareOp::Ge) => o != Less,
+        (Some(o), CompareOp::Eq) => o == Equal,
+        (Some(o), CompareOp::Ne) => o != Equal,
+        (Some(_), CompareOp::Contains) => false,
+    }
+}
+
+/// Recursive descent over the characters of an expression.
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn error(&self, message: &str) -> QueryError {
+        QueryError { column: self.pos + 1, message: message.to_string() }
+    }
+
+    fn skip_space(&mut self) {
+        while self.chars.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    /// Consumes `keyword` (any case) when it is the next word.
+    fn keyword(&mut self, keyword: &str) -> bool {
+        self.skip_space();
+        let end = self.pos + keyword.len();
+        let word: String = self.chars.get(self.pos..end).map(|w| w.iter().collect()).unwrap_or_default();
+        let boundary = self.chars.get(end).is_none_or(|c| !(c.is_alphanumeric() || *c == '_'));
+        if word.eq_ignore_ascii_case(keyword) && boundary {
+            self.pos = end;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, QueryError> {
+        let mut left = self.and()?;
+        while self.keyword("OR") {
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, QueryError> {
+        let mut left = self.unary()?;
+        while self.keyword("AND") {
+            left = Expr::And(Box::new(left), Box::new(self.unary()?));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, QueryError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(self.error("filter is nested too deeply"));
+        }
+        let expr = if self.keyword("NOT") {
+            Expr::Not(Box::new(self.unary()?))
+        } else {
+            self.skip_space();
+            if self.chars.get(self.pos) == Some(&'(') {
+                self.pos += 1;
+                let inner = self.or()?;
+                self.skip_space();
+                if self.chars.get(self.pos) != Some(&')') {
+                    return Err(self.error("expected )"));
+                }
+                self.pos += 1;
+                inner
+            } else {
+                self.comparison()?
+            }
+        };
+        self.depth -= 1;
+        Ok(expr)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, QueryError> {
+        self.skip_space();
+        let start = self.pos;
+        while self.chars.get(self.pos).is_some_and(|c| c.is_ascii_alphanumeric() || *c == '_') {
+            self.pos += 1;
+        }
+        let name: String = self.chars[start..self.pos].iter().collect::<String>().to_ascii_lowercase();
+        let field = match FIELDS.iter().find(|(n, _)| *n == name) {
+            Some((_, field)) => *field,
+            None if name.is_empty() => return Err(self.error("expected a field")),
+            None => {
+                let known: Vec<&str> = FIELDS.iter().map(|(n, _)| *n).collect();
+                return Err(QueryError { column: start + 1, message: format!("unknown field {:?}; use one of {}", name, known.join(", ")) });
+            }
+        };
+        self.skip_space();
+        let op_column = self.pos;
+        let op = self.operator()?;
+        self.skip_space();
+        let value_column = self.pos;
+        let value = self.value()?;
+        let operand = if field.is_text() {
+            let text = value.trim().to_uppercase();
+            let text = match (field, op) {
+                (QueryField::ObjectType, CompareOp::Eq | CompareOp::Ne) => normalize_object_type(&text).map(str::to_string).unwrap_or(text),
+                _ => text,
+            };
+            Operand::Text(text)
+        } else {
+            if op == CompareOp::Contains {
+                return Err(QueryError { column: op_column + 1, message: format!("~ needs a text field, {} is a number", name) });
+            }
+            match value.trim().parse::<f64>() {
+                Ok(v) if v.is_finite() => Operand::Number(v),
+                _ => return Err(QueryError { column: value_column + 1, message: format!("{} needs a number, got {:?}", name, value) }),
+            }
+        };
+        Ok(Expr::Compare { field, op, operand })
+    }
+
+    fn operator(&mut self) -> Result<CompareOp, QueryError> {
+        let next = |p: &Parser, i: usize| p.chars.get(p.pos + i).copied();
+        let (op, len) = match (next(self, 0), next(self, 1)) {
+            (Some('<'), Some('=')) => (CompareOp::Le, 2),
+            (Some('>'), Some('=')) => (CompareOp::Ge, 2),
+            (Some('!'), Some('=')) => (CompareOp::Ne, 2),
+            (Some('='), Some('=')) => (CompareOp::Eq, 2),
+            (Some('<'), _) => (CompareOp::Lt, 1),
+            (Some('>'), _) => (CompareOp::Gt, 1),
+            (Some('='), _) => (CompareOp::Eq, 1),
+            (Some('~'), _) => (CompareOp::Contains, 1),
+            _ => return Err(self.error("expected one of < <= > >= = != ~")),
+        };
+        self.pos += len;
+        Ok(op)
+    }
+
+    /// A double-quoted string (`\"` and `\\` escaped) or a bare word up to the
+    /// next space or parenthesis.
+    fn value(&mut self) -> Result<String, QueryError> {
+        if self.chars.get(self.pos) == Some(&'"') {
+            let start = self.pos;
+            self.pos += 1;
+            let mut out = String::new();
+            loop {
+                match self.chars.get(self.pos) {
+                    None => return Err(QueryError { column: start + 1, message: "unterminated string".to_string() }),
+                    Some('"') => break,
+                    Some('\\') if matches!(self.chars.get(self.pos + 1), Some('"' | '\\')) => {
+                        out.push(self.chars[self.pos + 1]);
+                        self.pos += 2;
+                        continue;
+                    }
+                    Some(&c) => out.push(c),
+                }
+                self.pos += 1;
+            }
+            self.pos += 1;
+            return Ok(out);
+        }
+        let start = self.pos;
+        while self.chars.get(self.pos).is_some_and(|c| !(c.is_whitespace() || *c == '(' || *c == ')')) {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Err(self.error("expected a value"));
+        }
+        Ok(self.chars[start..self.pos].iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_evaluates_filters_over_the_enriched_catalog() {
+        // The ISS flies near 350 km at 51.6°, NOAA 18 near 850 km at 98.9°
+        let catalog = crate::testing::fixtures::catalog();
+        let noaa_satcat = SatcatEntry {
+            norad_id: 28654,
+            object_id: Some("2005-018A".to_string()),
+            object_type: "PAYLOAD".to_string(),
+            owner: "US".to_string(),
+            launch_date: Some("2005-05-20".to_string()),
+            decay_date: None,
+        };
+        let subjects = [Subject::of(&catalog[0], None), Subject::of(&catalog[1], Some(&noaa_satcat))];
+        let now = catalog[0].datetime.and_utc() + chrono::Duration::days(2);
+        let select = |text: &str| -> Vec<u64> {
+            let query = SatelliteQuery::parse(text).unwrap();
+            subjects.iter().filter(|s| query.matches(s, now)).map(|s| s.norad_id).collect()
+        };
+        assert_eq!(select("inclination>97 AND perigee<900 AND name~\"noaa\""), [28654]);
+        assert_eq!(select("altitude < 500 or country=us"), [25544, 28654]);
+        assert_eq!(select("NOT (object_type = PAY) AND period<100"), [25544]);
+        assert_eq!(select("launch_year>=2000 AND designator=2005-018A AND launch_date<\"2006\""), [28654]);
+        assert_eq!(select("age > 1.9 AND age < 2.1 AND norad_id != 28654"), [25544]);
+        // AND binds tighter than OR
+        assert_eq!(select("norad_id=1 AND norad_id=1 OR norad_id=25544"), [25544]);
+        assert!(!SatelliteQuery::parse("inclination>97").unwrap().needs_satcat());
+        assert!(SatelliteQuery::parse("country=PRC").unwrap().needs_satcat());
+
+        let error = |text: &str| SatelliteQuery::parse(text).unwrap_err();
+        assert_eq!(error("inclination>>97").column, 13);
+        assert_eq!(error("colour=red").column, 1);
+        assert!(error("name~\"DOVE").message.contains("unterminated"));
+        assert!(error("perigee~500").message.contains("text field"));
+        assert!(error("(perigee<500").message.contains(')'));
+        assert!(error("perigee<500 name~x").message.contains("AND"));
+        assert!(SatelliteQuery::parse(&format!("{}perigee<1{}", "(".repeat(40), ")".repeat(40))).is_err());
+    }
+}
@@ -0,0 +1,48 @@
+use rusqlite::Connection;
+
+use crate::analyzers::population::{histogram, Field};
+use crate::utils::db::{self, DbError};
+
+/// Width (km) of the altitude shells the catalog is counted in at each fetch.
+pub const SHELL_WIDTH_KM: u32 = 50;
+/// Top of the highest shell (km); objects above it are not counted.
+pub const MAX_SHELL_KM: u32 = 2000;
+
+/// Objects per shell by mean altitude, lowest shell first; shell `i` spans
+/// `[i, i + 1) * SHELL_WIDTH_KM`.
+pub fn shell_counts(elements: &[sgp4::Elements]) -> Vec<u64> {
+    let values: Vec<f64> = elements.iter().map(|el| Field::Altitude.value(el)).collect();
+    let bins = (MAX_SHELL_KM / SHELL_WIDTH_KM) as usize;
+    histogram(&values, bins, Some(0.0), Some(MAX_SHELL_KM as f64)).counts
+}
+
+/// Stores the shell counts of a fetched catalog under its fetch time, so the
+/// congestion of each shell can be followed across fetches.
+pub fn record_shell_counts(conn: &Connection, elements: &[sgp4::Elements], fetched_at: &str) -> Result<(), DbError> {
+    db::insert_shell_counts(conn, fetched_at, SHELL_WIDTH_KM, &shell_counts(elements))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_objects_in_their_shell_and_stores_them_per_fetch() {
+        let catalog = crate::testing::fixtures::catalog();
+        let counts = shell_counts(&catalog);
+        assert_eq!(counts.len(), 40);
+        assert_eq!(counts.iter().sum::<u64>(), 2);
+        // The ISS flies near 350 km in the fixture epoch, NOAA 18 near 850 km
+        let occupied: Vec<usize> = (0..counts.len()).filter(|&i| counts[i] > 0).collect();
+        assert_eq!(occupied, [7, 17]);
+
+        let conn = Connection::open_in_memory().unwrap();
+        db::init_schema(&conn).unwrap();
+        record_shell_counts(&conn, &catalog, "2024-01-01T00:00:00Z").unwrap();
+        record_shell_counts(&conn, &catalog[..1], "2024-01-02T00:00:00Z").unwrap();
+        let rows = db::list_shell_counts(&conn, "2024-01-02T00:00:00Z").unwrap();
+        assert_eq!(rows.len(), 40);
+        assert_eq!(rows.iter().map(|r| r.count).sum::<u64>(), 1);
+        assert_eq!((rows[7].shell_km, rows[7].count), (350, 1));
+    }
+}
@@ -0,0 +1,145 @@
+use chrono::DateTime;
+
+use crate::core::orbit::{dot, EARTH_RADIUS_KM, MU_EARTH_KM3_S2};
+use crate::utils::db::SnapshotRow;
+
+/// What is implausible about a recorded snapshot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnomalyKind {
+    /// The position does not follow from the previous snapshot a short time before.
+    PositionJump,
+    /// The altitude is below the atmosphere's edge, beyond cislunar space or not a number.
+    AltitudeSpike,
+    /// The semi-major axis from position and velocity differs from the previous
+    /// snapshot's by more than osculating variations explain, as after a bad TLE.
+    OrbitJump,
+}
+
+impl AnomalyKind {
+    pub const ALL: [AnomalyKind; 3] = [AnomalyKind::PositionJump, AnomalyKind::AltitudeSpike, AnomalyKind::OrbitJump];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AnomalyKind::PositionJump => "position_jump",
+            AnomalyKind::AltitudeSpike => "altitude_spike",
+            AnomalyKind::OrbitJump => "orbit_jump",
+        }
+    }
+}
+
+/// One finding for a snapshot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Anomaly {
+    pub kind: AnomalyKind,
+    /// Size of the discrepancy (km): the position mismatch, the altitude or the
+    /// change of semi-major axis.
+    pub magnitude_km: f64,
+    pub detail: String,
+}
+
+/// Limits beyond which a snapshot is flagged.
+#[derive(Debug, Clone, Copy)]
+pub struct Thresholds {
+    /// Largest gap (seconds) over which positions are checked for continuity;
+    /// over longer gaps the integration error outgrows `position_jump_km`.
+    pub continuity_gap_s: f64,
+    pub position_jump_km: f64,
+    pub min_alt_km: f64,
+    pub max_alt_km: f64,
+    /// J2 alone moves the osculating semi-major axis of a LEO object by about
+    /// ±10 km over a revolution.
+    pub orbit_jump_km: f64,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self { continuity_gap_s: 300.0, position_jump_km: 50.0, min_alt_km: 80.0, max_alt_km: 500_000.0, orbit_jump_km: 50.0 }
+    }
+}
+
+fn norm(v: &[f64; 3]) -> f64 {
+    dot(v, v).sqrt()
+}
+
+/// Semi-major axis (km) by vis-viva; `None` for an unbound or degenerate state.
+fn semi_major_axis_km(s: &SnapshotRow) -> Option<f64> {
+    let energy = dot(&s.velocity, &s.velocity) / 2.0 - MU_EARTH_KM3_S2 / norm(&s.position);
+    (energy < 0.0).then(|| -MU_EARTH_KM3_S2 / (2.0 * energy))
+}
+
+/// Checks `current` on its own and against `previous`, the satellite's snapshot
+/// before it, if any.
+pub fn check(previous: Option<&SnapshotRow>, current: &SnapshotRow, limits: &Thresholds) -> Vec<Anomaly> {
+    let mut found = Vec::new();
+    let altitude = norm(&current.position) - EARTH_RADIUS_KM;
+    if !(limits.min_alt_km..=limits.max_alt_km).contains(&altitude) || !norm(&current.velocity).is_finite() {
+        found.push(Anomaly { kind: AnomalyKind::AltitudeSpike, magnitude_km: altitude, detail: format!("altitude {:.1} km", altitude) });
+        // Comparisons with a state that bad say nothing more
+        return found;
+    }
+    let Some(previous) = previous else {
+        return found;
+    };
+
+    let times = DateTime::parse_from_rfc3339(&previous.timestamp).ok().zip(DateTime::parse_from_rfc3339(&current.timestamp).ok());
+    if let Some((t0, t1)) = times {
+        let dt = (t1 - t0).num_milliseconds() as f64 / 1000.0;
+        if dt > 0.0 && dt <= limits.continuity_gap_s {
+            // Trapezoidal integration of the velocity; the error grows as dt³
+            let expected: [f64; 3] = std::array::from_fn(|i| previous.position[i] + (previous.velocity[i] + current.velocity[i]) / 2.0 * dt);
+            let miss = norm(&std::array::from_fn(|i| current.position[i] - expected[i]));
+            if miss > limits.position_jump_km {
+                found.push(Anomaly {
+                    kind: AnomalyKind::PositionJump,
+                    magnitude_km: miss,
+                    detail: format!("{:.1} km from the position expected {:.0} s after the previous snapshot", miss, dt),
+                });
+            }
+        }
+    }
+    if let Some((a0, a1)) = semi_major_axis_km(previous).zip(semi_major_axis_km(current)) {
+        if (a1 - a0).abs() > limits.orbit_jump_km {
+            found.push(Anomaly {
+                kind: AnomalyKind::OrbitJump,
+                magnitude_km: (a1 - a0).abs(),
+                detail: format!("semi-major axis changed from {:.1} km to {:.1} km", a0, a1),
+            });
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::orbit::propagate_minutes;
+
+    #[test]
+    fn flags_jumps_but_not_a_smooth_track() {
+        let el = crate::testing::fixtures::catalog().swap_remove(0);
+        let epoch = el.datetime.and_utc();
+        let snapshot = |id: i64, minutes: f64| {
+            let p = propagate_minutes(&el, minutes).unwrap();
+            let t = epoch + chrono::Duration::milliseconds((minutes * 60_000.0) as i64);
+            SnapshotRow { id, norad_id: el.norad_id, timestamp: t.to_rfc3339(), position: p.position, velocity: p.velocity }
+        };
+        let limits = Thresholds::default();
+        let track: Vec<SnapshotRow> = (0..200).map(|k| snapshot(k, k as f64)).collect();
+        assert!(check(None, &track[0], &limits).is_empty());
+        assert!(track.windows(2).all(|w| check(Some(&w[0]), &w[1], &limits).is_empty()));
+
+        // A position off by 200 km along-track, then a state from another orbit
+        let mut shifted = track[1].clone();
+        shifted.position[2] += 200.0;
+        let kinds: Vec<_> = check(Some(&track[0]), &shifted, &limits).into_iter().map(|a| a.kind).collect();
+        assert!(kinds.contains(&AnomalyKind::PositionJump));
+        let mut faster = track[1].clone();
+        faster.velocity = faster.velocity.map(|v| v * 1.05);
+        let kinds: Vec<_> = check(Some(&track[0]), &faster, &limits).into_iter().map(|a| a.kind).collect();
+        assert!(kinds.contains(&AnomalyKind::OrbitJump));
+
+        let mut sunk = track[1].clone();
+        sunk.position = sunk.position.map(|x| x * 0.9);
+        assert_eq!(check(Some(&track[0]), &sunk, &limits)[0].kind, AnomalyKind::AltitudeSpike);
+    }
+}
@@ -0,0 +1,319 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use axum::{extract::{Request, State}, middleware::Next, response::{IntoResponse, Response}, Json};
+use axum::http::{header, Method, StatusCode};
+use tracing::error;
+
+use crate::api::devices;
+use crate::api::readonly::is_mutating;
+use crate::utils::db;
+
+/// API keys and their roles as comma-separated `key:role` pairs, e.g.
+/// `k3y1:admin,k3y2:viewer`; `key:role@tenant` confines a key to one tenant's
+/// stations, observations and protected assets. Unset, the API is open to everyone.
+const API_KEYS_ENV: &str = "STFCM_API_KEYS";
+
+/// Writes that change the catalog or start background jobs; other writes
+/// (stations, observations, assets, aliases) need the operator role.
+const ADMIN_WRITES: [&str; 4] = ["/tle/upload", "/conjunctions/screen", "/custom-elements", "/config/import"];
+
+/// What a key may do; each role includes the ones below it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Role {
+    /// Reads and computed queries.
+    Viewer,
+    /// Also manages stations, observations, protected assets and aliases.
+    Operator,
+    /// Also changes the catalog, triggers screening and bulk exports.
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Viewer => "viewer",
+            Role::Operator => "operator",
+            Role::Admin => "admin",
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Role, String> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "viewer" => Ok(Role::Viewer),
+            "operator" => Ok(Role::Operator),
+            "admin" => Ok(Role::Admin),
+            other => Err(format!("unknown role {}", other)),
+        }
+    }
+}
+
+/// Who made a request, as left in the request extensions by [`authorize`].
+#[derive(Debug, Clone)]
+pub struct Caller {
+    pub role: Role,
+    /// Short fingerprint of the key, safe to log.
+    pub key_id: String,
+    /// Station a device token is bound to; `None` for API keys.
+    pub station_id: Option<i64>,
+    /// Tenant the key belongs to; what it creates is owned by this tenant.
+    pub tenant: Option<String>,
+}
+
+impl Caller {
+    /// Tenant whose data the caller is confined to; `None` means every tenant's,
+    /// as for admins and keys without a tenant.
+    pub fn scope(&self) -> Option<&str> {
+        self.tenant.as_deref().filter(|_| self.role < Role::Admin)
+    }
+
+    /// Whether the caller may see and change a station.
+    pub fn can_access(&self, station: &db::Station) -> bool {
+        self.scope().is_none_or(|t| station.tenant.as_deref() == Some(t))
+    }
+
+    /// `role:fingerprint` or `device:station:fingerprint`, e.g. for the audit log.
+    pub fn actor(&self) -> String {
+        match self.station_id {
+            Some(station_id) => format!("device:{}:{}", station_id, self.key_id),
+            None => format!("{}:{}", self.role.as_str(), self.key_id),
+        }
+    }
+}
+
+/// FNV-1a of the key, shortened to 8 hex digits; identifies a key in logs
+/// without revealing it.
+fn fingerprint(key: &str) -> String {
+    let hash = key.bytes().fold(0xcbf2_9ce4_8422_2325u64, |h, b| (h ^ b as u64).wrapping_mul(0x0100_0000_01b3));
+    format!("{:08x}", hash >> 32)
+}
+
+/// Key-to-role (and tenant) table checked by [`authorize`].
+#[derive(Clone, Default)]
+pub struct ApiKeys(Arc<HashMap<String, (Role, Option<String>)>>);
+
+impl ApiKeys {
+    pub fn parse(spec: &str) -> Result<ApiKeys, String> {
+        let mut keys = HashMap::new();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (key, role) = entry.rsplit_once(':').ok_or_else(|| format!("expected key:role, got {}", entry))?;
+            if key.is_empty() {
+                return Err("empty API key".to_string());
+            }
+            let (role, tenant) = match role.split_once('@') {
+                Some((_, "")) => return Err(format!("empty tenant in {}", entry)),
+                Some((role, tenant)) => (role, Some(tenant.to_string())),
+                None => (role, None),
+            };
+            keys.insert(key.to_string(), (role.parse()?, tenant));
+        }
+        Ok(ApiKeys(Arc::new(keys)))
+    }
+
+    /// Keys from `STFCM_API_KEYS`, or `None` when access control is off. An
+    /// invalid value locks the API (no key matches) rather than opening it.
+    pub fn from_env() -> Option<ApiKeys> {
+        let spec = std::env::var(API_KEYS_ENV).ok()?;
+        Some(ApiKeys::parse(&spec).unwrap_or_else(|e| {
+            error!(error = %e, "Invalid {}; rejecting every API key", API_KEYS_ENV);
+            ApiKeys::default()
+        }))
+    }
+
+    fn role(&self, key: &str) -> Option<(Role, Option<String>)> {
+        self.0.get(key).cloned()
+    }
+}
+
+/// Role needed for a request, by method and path (relative to the API root).
+fn required_role(method: &Method, path: &str) -> Role {
+    if !is_mutating(method, path) {
+        // Bulk exports are the backup path
+        let admin = path.starts_with("/export/") || path == "/audit-log" || path == "/config/export" || path == "/snapshots/anomalies";
+        return if admin {
+            Role::Admin
+        } else if path.starts_with("/stations/") && path.ends_with("/tokens") {
+            Role::Operator
+        } else {
+            Role::Viewer
+        };
+    }
+    let overrides = path.starts_with("/satellites/") && (path.ends_with("/elements") || path.ends_with("/elements/revert"));
+    if overrides || ADMIN_WRITES.iter().any(|p| path == *p || path.starts_with(&format!("{}/", p))) {
+        Role::Admin
+    } else {
+        Role::Operator
+    }
+}
+
+/// Whether a device token bound to `station_id` may make a request: reading its
+/// station and that station's pass schedule, and reporting telemetry.
+fn device_allowed(method: &Method, path: &str, query: Option<&str>, station_id: i64) -> bool {
+    let own = format!("/stations/{}", station_id);
+    let own_path = |suffix: &str| path == format!("{}{}", own, suffix);
+    match *method {
+        Method::GET | Method::HEAD => {
+            if path == own || ["/horizon", "/exclusions", "/favorites", "/summary", "/telemetry"].iter().any(|s| own_path(s)) {
+                return true;
+            }
+            // Schedules only with this station's ID and no other
+            let mut stations = query.unwrap_or_default().split('&').filter_map(|p| p.strip_prefix("station_id="));
+            let for_station = stations.next().is_some_and(|s| s == station_id.to_string()) && stations.all(|s| s == station_id.to_string());
+            let schedule = ["/passes", "/passes/trackfile", "/passes/profile"].contains(&path)
+                || (path.starts_with("/satellites/") && (path.ends_with("/passes") || path.ends_with("/next")));
+            for_station && schedule
+        }
+        Method::POST => own_path("/telemetry"),
+        _ => false,
+    }
+}
+
+/// Stations a request refers to by `/stations/{id}` or `station_id=`. An ID
+/// that is not a plain integer is the `Err`: the extractors would still decode
+/// e.g. `%35` to a station, so it cannot be skipped.
+fn referenced_stations(path: &str, query: Option<&str>) -> Result<Vec<i64>, String> {
+    let in_path = path.strip_prefix("/stations/").and_then(|rest| rest.split('/').next());
+    let in_query = query.unwrap_or_default().split('&').filter_map(|p| p.strip_prefix("station_id="));
+    in_path.into_iter().chain(in_query).map(|id| id.parse().map_err(|_| id.to_string())).collect()
+}
+
+/// Whether `caller` may use station `id`. Only tenant-scoped callers are
+/// limited; to them a missing station is as invisible as another tenant's.
+pub fn station_visible(caller: Option<&Caller>, id: i64) -> bool {
+    let Some(caller) = caller.filter(|c| c.scope().is_some()) else {
+        return true;
+    };
+    db::open_or_init().and_then(|c| db::get_station(&c, id)).is_ok_and(|s| caller.can_access(&s))
+}
+
+/// Response for a station the caller may not see: reported missing rather than
+/// forbidden, so other tenants' station IDs do not leak.
+pub fn station_not_found() -> (StatusCode, Json<serde_json::Value>) {
+    (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "station not found"})))
+}
+
+/// Key sent as `Authorization: Bearer <key>`, `X-API-Key: <key>` or, for
+/// WebSocket clients that cannot set headers, `?api_key=<key>`.
+fn presented_key(request: &Request) -> Option<String> {
+    let headers = request.headers();
+    if let Some(key) = headers.get(header::AUTHORIZATION).and_then(|v| v.to_str().ok()).and_then(|v| v.strip_prefix("Bearer ")) {
+        return Some(key.trim().to_string());
+    }
+    if let Some(key) = headers.get("x-api-key").and_then(|v| v.to_str().ok()) {
+        return Some(key.trim().to_string());
+    }
+    request
+        .uri()
+        .query()?
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("api_key="))
+        .map(|k| k.to_string())
+}
+
+/// Middleware enforcing API key roles: `401` without a known key, `403` when
+/// the key's role is too low. `/health` stays open for load balancers. The
+/// [`Caller`] is left in the request extensions.
+pub async fn authorize(State(keys): State<ApiKeys>, mut request: Request, next: Next) -> Response {
+    let path = request.uri().path().to_string();
+    if path == "/health" {
+        return next.run(request).await;
+    }
+    let key = presented_key(&request);
+    if let Some(token) = key.as_deref().filter(|k| devices::is_device_token(k)) {
+        let Some(station_id) = devices::station_for_token(token) else {
+            let body = Json(serde_json::json!({"error": "unknown or revoked device token"}));
+            return (StatusCode::UNAUTHORIZED, [(header::WWW_AUTHENTICATE, "Bearer")], body).into_response();
+        };
+        if !device_allowed(request.method(), &path, request.uri().query(), station_id) {
+            let error = format!("device token is limited to the schedule and telemetry of station {}", station_id);
+            return (StatusCode::FORBIDDEN, Json(serde_json::json!({ "error": error }))).into_response();
+        }
+        let key_id = fingerprint(token);
+        request.extensions_mut().insert(Caller { role: Role::Viewer, key_id, station_id: Some(station_id), tenant: None });
+        return next.run(request).await;
+    }
+    let Some((role, tenant)) = key.as_deref().and_then(|k| keys.role(k)) else {
+        let body = Json(serde_json::json!({"error": "missing or unknown API key"}));
+        return (StatusCode::UNAUTHORIZED, [(header::WWW_AUTHENTICATE, "Bearer")], body).into_response();
+    };
+    let required = required_role(request.method(), &path);
+    if role < required {
+        return (StatusCode::FORBIDDEN, Json(serde_json::json!({"error": format!("requires the {} role", required.as_str())}))).into_response();
+    }
+    let key_id = fingerprint(key.as_deref().unwrap_or_default());
+    let caller = Caller { role, key_id, station_id: None, tenant };
+    let stations = match referenced_stations(&path, request.uri().query()) {
+        Ok(ids) => ids,
+        Err(id) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": format!("invalid station ID {:?}", id)}))).into_response(),
+    };
+    if stations.iter().any(|id| !station_visible(Some(&caller), *id)) {
+        return station_not_found().into_response();
+    }
+    request.extensions_mut().insert(caller);
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roles_gate_endpoints() {
+        let keys = ApiKeys::parse("v:viewer, o:operator,a:ADMIN,c:operator@club").unwrap();
+        assert_eq!(keys.role("o"), Some((Role::Operator, None)));
+        assert_eq!(keys.role("c"), Some((Role::Operator, Some("club".to_string()))));
+        assert!(ApiKeys::parse("nokey").is_err());
+        assert!(ApiKeys::parse("k:root").is_err());
+        assert!(ApiKeys::parse("k:viewer@").is_err());
+
+        assert_eq!(required_role(&Method::GET, "/stations"), Role::Viewer);
+        assert_eq!(required_role(&Method::POST, "/predict/passes"), Role::Viewer);
+        assert_eq!(required_role(&Method::POST, "/stations"), Role::Operator);
+        assert_eq!(required_role(&Method::PUT, "/satellites/25544/aliases"), Role::Operator);
+        assert_eq!(required_role(&Method::POST, "/tle/upload"), Role::Admin);
+        assert_eq!(required_role(&Method::DELETE, "/custom-elements/3"), Role::Admin);
+        assert_eq!(required_role(&Method::POST, "/satellites/25544/elements/revert"), Role::Admin);
+        assert_eq!(required_role(&Method::GET, "/export/parquet/snapshots"), Role::Admin);
+        assert_eq!(required_role(&Method::GET, "/audit-log"), Role::Admin);
+        assert_eq!(required_role(&Method::GET, "/config/export"), Role::Admin);
+        assert_eq!(required_role(&Method::GET, "/stations/4/tokens"), Role::Operator);
+        assert_eq!(fingerprint("k3y"), fingerprint("k3y"));
+        assert_ne!(fingerprint("k3y"), fingerprint("k3z"));
+    }
+
+    #[test]
+    fn device_tokens_reach_only_their_station() {
+        assert!(device_allowed(&Method::GET, "/passes", Some("station_id=4&duration=60"), 4));
+        assert!(device_allowed(&Method::GET, "/satellites/25544/passes", Some("station_id=4"), 4));
+        assert!(device_allowed(&Method::POST, "/stations/4/telemetry", None, 4));
+        assert!(device_allowed(&Method::GET, "/stations/4", None, 4));
+        assert!(!device_allowed(&Method::GET, "/passes", Some("station_id=5"), 4));
+        assert!(!device_allowed(&Method::GET, "/passes", Some("lat=1&lon=2"), 4));
+        assert!(!device_allowed(&Method::GET, "/passes", Some("station_id=4&station_id=5"), 4));
+        assert!(!device_allowed(&Method::PUT, "/stations/4", None, 4));
+        assert!(!device_allowed(&Method::GET, "/stations", None, 4));
+        assert!(!device_allowed(&Method::GET, "/stations/44", None, 4));
+    }
+
+    #[test]
+    fn tenants_reach_only_their_stations() {
+        let caller = |role, tenant: Option<&str>| Caller { role, key_id: String::new(), station_id: None, tenant: tenant.map(str::to_string) };
+        let station = |tenant: Option<&str>| db::Station { id: 1, name: None, lat: 0.0, lon: 0.0, alt_m: 0.0, tenant: tenant.map(str::to_string) };
+        let member = caller(Role::Operator, Some("club"));
+        assert!(member.can_access(&station(Some("club"))));
+        assert!(!member.can_access(&station(Some("corp"))) && !member.can_access(&station(None)));
+        assert!(caller(Role::Admin, Some("club")).can_access(&station(Some("corp"))));
+        assert!(caller(Role::Viewer, None).can_access(&station(Some("corp"))));
+
+        assert_eq!(referenced_stations("/stations/4/horizon", Some("station_id=5")), Ok(vec![4, 5]));
+        assert_eq!(referenced_stations("/stations", None), Ok(Vec::new()));
+        // Encoded IDs reach the handlers decoded, so they are refused here
+        assert_eq!(referenced_stations("/stations/%35", None), Err("%35".to_string()));
+        assert_eq!(referenced_stations("/passes", Some("station_id=5&station_id=x")), Err("x".to_string()));
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::BTreeMap;
+
+use axum::{extract::{Path, Query, State}, response::IntoResponse, Json};
+use axum::http::StatusCode;
+use chrono::{Duration, SecondsFormat};
+use serde::Deserialize;
+
+use crate::analyzers::accuracy::{summarize, AccuracyStats};
+use crate::api::server::AppState;
+use crate::api::types::{AccuracySampleDto, AccuracyStatsDto, SatelliteAccuracyDto};
+use crate::predictors::passes::SortOrder;
+use crate::utils::db::{self, TleAccuracy};
+
+#[derive(Debug, Deserialize)]
+pub struct AccuracyQuery {
+    #[serde(default = "default_days")]
+    days: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AccuracyListQuery {
+    #[serde(default = "default_days")]
+    days: i64,
+    #[serde(default = "default_min_samples")]
+    min_samples: usize,
+    /// By median error per day of propagation; ascending (most trustworthy
+    /// first) by default.
+    #[serde(default)]
+    order: Option<SortOrder>,
+    #[serde(default = "default_limit")]
+    limit: usize,
+}
+
+fn default_days() -> i64 { 30 }
+fn default_min_samples() -> usize { 1 }
+fn default_limit() -> usize { 100 }
+/// Longest look-back for the rolling statistics.
+const MAX_DAYS: i64 = 365;
+const MAX_LIMIT: usize = 1000;
+
+impl From<AccuracyStats> for AccuracyStatsDto {
+    fn from(s: AccuracyStats) -> Self {
+        AccuracyStatsDto {
+            samples: s.samples,
+            median_error_km: s.median_error_km,
+            mean_error_km: s.mean_error_km,
+            max_error_km: s.max_error_km,
+            median_gap_hours: s.median_gap_hours,
+            median_error_km_per_day: s.median_error_km_per_day,
+            latest_epoch: s.latest_epoch,
+        }
+    }
+}
+
+/// Samples with an element epoch within the last `days`, or the error response.
+fn load(norad_id: Option<u64>, days: i64) -> Result<Vec<TleAccuracy>, (StatusCode, Json<serde_json::Value>)> {
+    if !(1..=MAX_DAYS).contains(&days) {
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": format!("days must be within [1, {}]", MAX_DAYS)}))));
+    }
+    let since = (chrono::Utc::now() - Duration::days(days)).to_rfc3339_opts(SecondsFormat::Micros, true);
+    db::open_or_init()
+        .and_then(|c| db::list_tle_accuracy(&c, norad_id, &since))
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))))
+}
+
+fn name_of(state: &AppState, norad_id: u64) -> Option<String> {
+    state.catalog().name_of(norad_id).map(str::to_string)
+}
+
+/// How well each element set of a satellite predicted its successor: every
+/// comparison of the last `days` plus rolling statistics over them.
+pub async fn get_accuracy(Path(norad_id): Path<u64>, Query(q): Query<AccuracyQuery>, State(state): State<AppState>) -> impl IntoResponse {
+    let samples = match load(Some(norad_id), q.days) {
+        Ok(s) => s,
+        Err(response) => return response,
+    };
+    let out = SatelliteAccuracyDto {
+        norad_id,
+        name: name_of(&state, norad_id),
+        days: q.days,
+        stats: summarize(&samples).map(AccuracyStatsDto::from),
+        samples: Some(
+            samples
+                .into_iter()
+                .map(|s| AccuracySampleDto {
+                    epoch: s.epoch,
+                    previous_epoch: s.previous_epoch,
+                    gap_hours: s.gap_hours,
+                    error_km: s.error_km,
+                    radial_km: s.radial_km,
+                    along_track_km: s.along_track_km,
+                    cross_track_km: s.cross_track_km,
+                })
+                .collect(),
+        ),
+    };
+    (StatusCode::OK, Json(serde_json::json!(out)))
+}
+
+/// Rolling accuracy statistics of every satellite with samples in the last
+/// `days`, ranked by how fast their predictions degrade.
+pub async fn list_accuracy(Query(q): Query<AccuracyListQuery>, State(state): State<AppState>) -> impl IntoResponse {
+    let samples = match load(None, q.days) {
+        Ok(s) => s,
+        Err(response) => return response,
+    };
+    let mut by_id: BTreeMap<u64, Vec<TleAccuracy>> = BTreeMap::new();
+    for s in samples {
+        by_id.entry(s.norad_id).or_default().push(s);
+    }
+    let mut ranked: Vec<(u64, AccuracyStats)> = by_id
+        .into_iter()
+        .filter_map(|(id, list)| summarize(&list).map(|s| (id, s)))
+        .filter(|(_, s)| s.samples >= q.min_samples)
+        .collect();
+    ranked.sort_by(|a, b| a.1.median_error_km_per_day.total_cmp(&b.1.median_error_km_per_day));
+    if q.order == Some(SortOrder::Desc) {
+        ranked.reverse();
+    }
+    let out: Vec<SatelliteAccuracyDto> = ranked
+        .into_iter()
+        .take(q.limit.min(MAX_LIMIT))
+        .map(|(norad_id, stats)| SatelliteAccuracyDto { norad_id, name: name_of(&state, norad_id), days: q.days, stats: Some(stats.into()), samples: None })
+        .collect();
+    (StatusCode::OK, Json(serde_json::json!(out)))
+}
@@ -0,0 +1,148 @@
+use axum::{extract::{Path, Query, State}, response::IntoResponse, Json};
+use axum::http::StatusCode;
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use serde::Deserialize;
+
+use crate::api::server::AppState;
+use crate::api::types::{AoiAccessDto, AoiDto, AoiRequestDto, IntervalDto};
+use crate::core::aoi::AoiGeometry;
+use crate::predictors::access::aoi_access;
+use crate::utils::db::{self, Aoi};
+
+#[derive(Debug, Deserialize)]
+pub struct AccessQuery {
+    norad_id: u64,
+    /// Half-angle (degrees) of the nadir-pointing sensor.
+    half_angle: f64,
+    #[serde(default)]
+    start: Option<DateTime<Utc>>,
+    #[serde(default = "default_hours")]
+    hours: i64,
+    #[serde(default = "default_step")]
+    step: i64,
+}
+
+fn default_hours() -> i64 { 24 }
+fn default_step() -> i64 { 10 }
+/// Longest access search (one week).
+const MAX_ACCESS_HOURS: i64 = 168;
+/// Longest accepted name, in characters.
+const MAX_NAME_LEN: usize = 128;
+
+fn aoi_dto(row: Aoi) -> AoiDto {
+    AoiDto {
+        id: row.id,
+        name: row.name,
+        geometry: serde_json::from_str(&row.geometry).unwrap_or_default(),
+        created_at: row.created_at,
+        updated_at: row.updated_at,
+    }
+}
+
+/// Trimmed name and normalised GeoJSON of a request.
+fn validate(body: &AoiRequestDto) -> Result<(String, String), String> {
+    let name = body.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+        return Err(format!("name must have 1 to {} characters", MAX_NAME_LEN));
+    }
+    let geometry = AoiGeometry::from_geojson(&body.geometry).map_err(|e| format!("invalid geometry: {}", e))?;
+    Ok((name.to_string(), geometry.to_geojson().to_string()))
+}
+
+pub async fn list_aois() -> impl IntoResponse {
+    match db::open_or_init().and_then(|c| db::list_aois(&c)) {
+        Ok(rows) => (StatusCode::OK, Json(serde_json::json!(rows.into_iter().map(aoi_dto).collect::<Vec<_>>()))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+/// Stores a named area of interest.
+pub async fn create_aoi(Json(body): Json<AoiRequestDto>) -> impl IntoResponse {
+    let (name, geometry) = match validate(&body) {
+        Ok(v) => v,
+        Err(e) => return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": e}))),
+    };
+    let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let created = db::open_or_init().and_then(|c| {
+        let id = db::insert_aoi(&c, &name, &geometry, &now)?;
+        db::get_aoi(&c, id)
+    });
+    match created {
+        Ok(row) => (StatusCode::CREATED, Json(serde_json::json!(aoi_dto(row)))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+pub async fn get_aoi(Path(id): Path<i64>) -> impl IntoResponse {
+    match db::open_or_init().and_then(|c| db::get_aoi(&c, id)) {
+        Ok(row) => (StatusCode::OK, Json(serde_json::json!(aoi_dto(row)))),
+        Err(_) => (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "aoi not found"}))),
+    }
+}
+
+/// Replaces the name and geometry of an area.
+pub async fn update_aoi(Path(id): Path<i64>, Json(body): Json<AoiRequestDto>) -> impl IntoResponse {
+    let (name, geometry) = match validate(&body) {
+        Ok(v) => v,
+        Err(e) => return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": e}))),
+    };
+    let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let updated = db::open_or_init().and_then(|c| {
+        if !db::update_aoi(&c, id, &name, &geometry, &now)? {
+            return Ok(None);
+        }
+        db::get_aoi(&c, id).map(Some)
+    });
+    match updated {
+        Ok(Some(row)) => (StatusCode::OK, Json(serde_json::json!(aoi_dto(row)))),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "aoi not found"}))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+pub async fn delete_aoi(Path(id): Path<i64>) -> impl IntoResponse {
+    match db::open_or_init().and_then(|c| db::delete_aoi(&c, id)) {
+        Ok(true) => (StatusCode::NO_CONTENT, Json(serde_json::json!({}))),
+        Ok(false) => (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "aoi not found"}))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+/// When the footprint of a nadir-pointing sensor on `norad_id` overlaps the
+/// area, for tasking feasibility.
+pub async fn get_access(Path(id): Path<i64>, Query(q): Query<AccessQuery>, State(state): State<AppState>) -> impl IntoResponse {
+    if !(q.half_angle > 0.0 && q.half_angle < 90.0) {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": "half_angle must be within (0, 90) degrees"})));
+    }
+    if !(1..=MAX_ACCESS_HOURS).contains(&q.hours) || q.step <= 0 {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": format!("hours must be within [1, {}] and step positive", MAX_ACCESS_HOURS)})));
+    }
+    let catalog = state.catalog();
+    let Some(el) = catalog.get(q.norad_id) else {
+        return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "norad_id not found in loaded TLEs"})));
+    };
+    let row = match db::open_or_init().and_then(|c| db::get_aoi(&c, id)) {
+        Ok(r) => r,
+        Err(_) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "aoi not found"}))),
+    };
+    let geometry = match serde_json::from_str(&row.geometry).map_err(|e| e.to_string()).and_then(|v| AoiGeometry::from_geojson(&v)) {
+        Ok(g) => g,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("stored geometry is invalid: {}", e)}))),
+    };
+    let start = q.start.unwrap_or_else(|| state.clock.now());
+    let end = start + Duration::hours(q.hours);
+    match aoi_access(el, &geometry, q.half_angle, start, end, q.step) {
+        Ok(intervals) => {
+            let out = AoiAccessDto {
+                aoi_id: id,
+                norad_id: q.norad_id,
+                half_angle_deg: q.half_angle,
+                start,
+                end,
+                intervals: intervals.into_iter().map(|(start, end)| IntervalDto { start, end }).collect(),
+            };
+            (StatusCode::OK, Json(serde_json::json!(out)))
+        }
+        Err(e) => (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": format!("prediction error: {}", e)}))),
+    }
+}
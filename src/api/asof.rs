@@ -0,0 +1,63 @@
+use std::ops::Deref;
+use std::sync::Arc;
+
+use axum::{http::StatusCode, Json};
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use crate::api::server::AppState;
+use crate::core::catalog::Catalog;
+use crate::utils::db::{self, DbError};
+
+/// Archived element sets nearest `as_of`, one per satellite (or only `norad_id`),
+/// for running the position and pass endpoints at a past time (`?as_of=`).
+pub fn archived_elements(as_of: DateTime<Utc>, norad_id: Option<u64>) -> Result<Vec<sgp4::Elements>, DbError> {
+    let conn = db::open_or_init()?;
+    let rows = db::list_tle_nearest(&conn, &as_of.to_rfc3339_opts(SecondsFormat::Micros, true), norad_id)?;
+    Ok(rows
+        .into_iter()
+        .filter_map(|r| sgp4::Elements::from_tle(r.name, r.line1.as_bytes(), r.line2.as_bytes()).ok())
+        .collect())
+}
+
+/// A loaded element set, by its index in the catalog, or an archived one.
+pub enum ElementSet {
+    Loaded(Arc<Catalog>, usize),
+    Archived(sgp4::Elements),
+}
+
+impl ElementSet {
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            ElementSet::Loaded(catalog, index) => catalog.name(*index),
+            ElementSet::Archived(el) => el.object_name.as_deref(),
+        }
+    }
+}
+
+impl Deref for ElementSet {
+    type Target = sgp4::Elements;
+
+    fn deref(&self) -> &sgp4::Elements {
+        match self {
+            ElementSet::Loaded(catalog, index) => &catalog[*index],
+            ElementSet::Archived(el) => el,
+        }
+    }
+}
+
+/// Element set of one satellite: the loaded one, or with `as_of` the archived
+/// one nearest that time. Errors are ready-made API responses.
+pub fn element_set(state: &AppState, norad_id: u64, as_of: Option<DateTime<Utc>>) -> Result<ElementSet, (StatusCode, Json<serde_json::Value>)> {
+    let Some(as_of) = as_of else {
+        let catalog = state.catalog();
+        return match catalog.index_of(norad_id) {
+            Some(index) => Ok(ElementSet::Loaded(catalog, index)),
+            None => Err((StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "norad_id not found in loaded TLEs"})))),
+        };
+    };
+    match archived_elements(as_of, Some(norad_id)) {
+        Ok(mut found) if !found.is_empty() => Ok(ElementSet::Archived(found.swap_remove(0))),
+        Ok(_) => Err((StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "no archived TLE for norad_id"})))),
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)})))),
+    }
+}
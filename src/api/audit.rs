@@ -0,0 +1,139 @@
+use axum::{body::{to_bytes, Body}, extract::Request, middleware::Next, response::{IntoResponse, Response}, Json};
+use axum::http::StatusCode;
+use chrono::{SecondsFormat, Utc};
+use serde_json::{json, Map, Value};
+
+use crate::api::access::Caller;
+use crate::api::readonly::is_mutating;
+use crate::api::types::{AzimuthSectorDto, StationDto};
+use crate::utils::db;
+
+/// Longest request body kept in the log; longer ones (e.g. TLE uploads) are cut.
+const MAX_PAYLOAD_BYTES: usize = 64 * 1024;
+/// Largest body buffered for a write, matching axum's default extractor limit.
+const MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// Current state of the resource a request path names, for the resources that
+/// can be read back; compared before and after a write.
+fn snapshot(path: &str) -> Option<Value> {
+    let conn = db::open_or_init().ok()?;
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    match segments.as_slice() {
+        ["stations", id] => db::get_station(&conn, id.parse().ok()?).ok().map(|s| json!(StationDto::from(s))),
+        ["stations", id, "exclusions"] => {
+            let sectors: Vec<AzimuthSectorDto> = db::list_station_exclusions(&conn, id.parse().ok()?)
+                .ok()?
+                .into_iter()
+                .map(|(start_deg, end_deg)| AzimuthSectorDto { start_deg, end_deg })
+                .collect();
+            Some(json!({ "sectors": sectors }))
+        }
+        ["stations", id, "favorites"] => {
+            let norad_ids = db::list_station_favorites(&conn, id.parse().ok()?).ok()?;
+            Some(json!({ "norad_ids": norad_ids }))
+        }
+        ["satellites", id, "aliases"] => {
+            let names = db::get_satellite_aliases(&conn, id.parse().ok()?).ok()?;
+            Some(json!({ "display_name": names.display_name, "aliases": names.aliases }))
+        }
+        ["satellites", id, "elements", ..] => {
+            let history = db::list_element_overrides(&conn, id.parse().ok()?).ok()?;
+            let active = history.into_iter().find(|o| o.reverted_at.is_none());
+            Some(json!({ "active_override": active.map(|o| json!({ "id": o.id, "source": o.source, "tle": o.tle, "omm": o.omm })) }))
+        }
+        ["custom-elements", id] => db::get_custom_elements(&conn, id.parse().ok()?)
+            .ok()
+            .map(|c| json!({ "name": c.name, "tle": c.tle, "omm": c.omm })),
+        ["conjunctions", "assets", id] => {
+            let norad_id: u64 = id.parse().ok()?;
+            let asset = db::list_protected_assets(&conn, None).ok()?.into_iter().find(|a| a.norad_id == norad_id);
+            asset.map(|a| json!({ "norad_id": a.norad_id, "alert_threshold_km": a.alert_threshold_km }))
+        }
+        _ => None,
+    }
+}
+
+/// Top-level fields that differ as `{ field: { from, to } }`; a missing
+/// resource counts as an empty object.
+fn diff(before: Option<&Value>, after: Option<&Value>) -> Value {
+    let empty = Map::new();
+    let fields = |v: Option<&Value>| v.and_then(Value::as_object).cloned().unwrap_or_else(|| empty.clone());
+    let (before, after) = (fields(before), fields(after));
+    let mut out = Map::new();
+    for key in before.keys().chain(after.keys().filter(|k| !before.contains_key(*k))) {
+        let (from, to) = (before.get(key).unwrap_or(&Value::Null), after.get(key).unwrap_or(&Value::Null));
+        if from != to {
+            out.insert(key.clone(), json!({ "from": from, "to": to }));
+        }
+    }
+    Value::Object(out)
+}
+
+/// Request body as JSON, or as a (possibly cut) string when it is not JSON.
+fn payload(bytes: &[u8]) -> Option<Value> {
+    if bytes.is_empty() {
+        return None;
+    }
+    if bytes.len() <= MAX_PAYLOAD_BYTES {
+        if let Ok(v) = serde_json::from_slice(bytes) {
+            return Some(v);
+        }
+    }
+    let text = String::from_utf8_lossy(&bytes[..bytes.len().min(MAX_PAYLOAD_BYTES)]).into_owned();
+    Some(Value::String(text))
+}
+
+/// Middleware recording every mutating request in the `audit_log` table: who
+/// sent it, the outcome, the body and, where the resource can be read back,
+/// what changed. Failures to log are only warned about.
+pub async fn record(request: Request, next: Next) -> Response {
+    if !is_mutating(request.method(), request.uri().path()) {
+        return next.run(request).await;
+    }
+    let (parts, body) = request.into_parts();
+    let Ok(bytes) = to_bytes(body, MAX_BODY_BYTES).await else {
+        return (StatusCode::PAYLOAD_TOO_LARGE, Json(json!({"error": "request body too large or unreadable"}))).into_response();
+    };
+    let path = parts.uri.path().to_string();
+    let actor = parts.extensions.get::<Caller>().map(Caller::actor).unwrap_or_else(|| "anonymous".to_string());
+    let method = parts.method.to_string();
+    let before = snapshot(&path);
+    let payload = payload(&bytes);
+
+    let response = next.run(Request::from_parts(parts, Body::from(bytes))).await;
+
+    let after = snapshot(&path);
+    let entry = db::AuditEntry {
+        id: 0,
+        at: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+        actor,
+        method,
+        path,
+        status: response.status().as_u16(),
+        payload: payload.map(|p| p.to_string()),
+        diff: (before.is_some() || after.is_some()).then(|| diff(before.as_ref(), after.as_ref()).to_string()),
+    };
+    if let Err(e) = db::open_or_init().and_then(|c| db::insert_audit_entry(&c, &entry)) {
+        tracing::warn!(error = %e, "Failed to write audit log entry");
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_lists_changed_fields() {
+        let before = json!({ "name": "Old", "lat": 1.0, "lon": 2.0 });
+        let after = json!({ "name": "New", "lat": 1.0, "lon": 2.0, "alt_m": 5.0 });
+        assert_eq!(
+            diff(Some(&before), Some(&after)),
+            json!({ "name": { "from": "Old", "to": "New" }, "alt_m": { "from": null, "to": 5.0 } })
+        );
+        assert_eq!(diff(Some(&before), None)["lat"], json!({ "from": 1.0, "to": null }));
+        assert_eq!(payload(br#"{"name":"ESOC"}"#), Some(json!({ "name": "ESOC" })));
+        assert_eq!(payload(b"ISS (ZARYA)"), Some(json!("ISS (ZARYA)")));
+        assert_eq!(payload(b""), None);
+    }
+}
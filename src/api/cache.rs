@@ -0,0 +1,142 @@
+use std::time::Duration;
+
+use axum::{body::{Body, HttpBody}, extract::{Request, State}, middleware::Next, response::{IntoResponse, Response}};
+use axum::http::{header, HeaderValue, Method, StatusCode};
+use chrono::{DateTime, Utc};
+
+use crate::api::server::AppState;
+
+/// Largest response body stored; bigger ones are served uncached.
+const MAX_CACHED_BYTES: usize = 8 * 1024 * 1024;
+/// How long an element set is assumed to stay current after its epoch.
+const ELEMENT_SET_LIFETIME: chrono::Duration = chrono::Duration::days(3);
+const MIN_TTL: Duration = Duration::from_secs(300);
+const MAX_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+/// Window granularity of requests that start "now": they share an entry for a minute.
+const NOW_BUCKET_S: i64 = 60;
+
+/// NORAD ID a request is about: the path segment after `satellites`, or the
+/// `norad_id` query parameter.
+fn norad_id_of(request: &Request) -> Option<u64> {
+    let mut segments = request.uri().path().split('/');
+    if segments.by_ref().any(|s| s == "satellites") {
+        if let Some(id) = segments.next().and_then(|s| s.parse().ok()) {
+            return Some(id);
+        }
+    }
+    request.uri().query()?.split('&').find_map(|kv| kv.strip_prefix("norad_id=")?.parse().ok())
+}
+
+/// Stored station a request observes from, as its `station_id` query parameter.
+fn station_id_of(request: &Request) -> Option<&str> {
+    request.uri().query()?.split('&').find_map(|kv| kv.strip_prefix("station_id="))
+}
+
+/// Version of a stored station; `None` when it does not exist or cannot be read.
+fn station_version(id: i64) -> Option<i64> {
+    crate::utils::db::open_or_init().and_then(|c| crate::utils::db::station_version(&c, id)).ok().flatten()
+}
+
+/// Cache key and TTL for a request on a satellite with the given TLE epoch,
+/// observed from a station at `station_version` if any. Both are part of the
+/// key, so a new element set or an edited station never hits an old entry.
+fn cache_entry(path: &str, query: Option<&str>, norad_id: u64, epoch: DateTime<Utc>, station_version: Option<i64>, now: DateTime<Utc>) -> (String, Duration) {
+    let query = query.unwrap_or("");
+    let path = match station_version {
+        Some(version) => format!("{}@v{}", path, version),
+        None => path.to_string(),
+    };
+    let epoch_ttl = (epoch + ELEMENT_SET_LIFETIME - now).to_std().unwrap_or_default().clamp(MIN_TTL, MAX_TTL);
+    let relative = !query.split('&').any(|kv| kv.starts_with("start=") || kv.starts_with("as_of="));
+    if relative {
+        let bucket = now.timestamp().div_euclid(NOW_BUCKET_S);
+        let ttl = Duration::from_secs(NOW_BUCKET_S as u64);
+        (format!("{}:{}:{}?{}@{}", norad_id, epoch.timestamp(), path, query, bucket), ttl)
+    } else {
+        (format!("{}:{}:{}?{}", norad_id, epoch.timestamp(), path, query), epoch_ttl)
+    }
+}
+
+/// Serves GET responses of the wrapped routes from Redis when `STFCM_REDIS_URL`
+/// is set, storing successful ones with a TTL tied to the satellite's TLE epoch.
+/// Marks responses with `x-cache: hit|miss`. Requests from a stored station
+/// bypass the cache while passes are mirrored to a time-series database, so
+/// every prediction is mirrored; the pass cache still spares the work.
+pub async fn cached(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let Some(cache) = state.cache.clone() else {
+        return next.run(request).await;
+    };
+    let catalog = state.catalog();
+    let epoch = norad_id_of(&request)
+        .and_then(|id| catalog.get(id))
+        .map(|el| (el.norad_id, el.datetime.and_utc()));
+    let (Some((norad_id, epoch)), true) = (epoch, request.method() == Method::GET) else {
+        return next.run(request).await;
+    };
+    let station_version = match station_id_of(&request) {
+        Some(_) if state.tsdb.is_some() => return next.run(request).await,
+        // An unknown station is the handler's to report, uncached
+        Some(id) => match id.parse().ok().and_then(station_version) {
+            Some(version) => Some(version),
+            None => return next.run(request).await,
+        },
+        None => None,
+    };
+    let (key, ttl) = cache_entry(request.uri().path(), request.uri().query(), norad_id, epoch, station_version, state.clock.now());
+
+    if let Some(stored) = cache.get(&key).await {
+        if let Some(split) = stored.iter().position(|&b| b == b'\n') {
+            let mut response = Body::from(stored[split + 1..].to_vec()).into_response();
+            if let Ok(ct) = HeaderValue::from_bytes(&stored[..split]) {
+                response.headers_mut().insert(header::CONTENT_TYPE, ct);
+            }
+            response.headers_mut().insert("x-cache", HeaderValue::from_static("hit"));
+            return response;
+        }
+    }
+
+    let response = next.run(request).await;
+    if response.status() != StatusCode::OK {
+        return response;
+    }
+    let (mut parts, body) = response.into_parts();
+    parts.headers.insert("x-cache", HeaderValue::from_static("miss"));
+    // Bodies of unknown or too large a size are streamed through unstored
+    if body.size_hint().exact().is_none_or(|n| n > MAX_CACHED_BYTES as u64) {
+        return Response::from_parts(parts, body);
+    }
+    let bytes = match axum::body::to_bytes(body, MAX_CACHED_BYTES).await {
+        Ok(b) => b,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, format!("failed to read response body: {}", e)).into_response(),
+    };
+    let mut stored = parts.headers.get(header::CONTENT_TYPE).map(|v| v.as_bytes().to_vec()).unwrap_or_default();
+    stored.push(b'\n');
+    stored.extend_from_slice(&bytes);
+    cache.put(&key, &stored, ttl).await;
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn keys_follow_epoch_and_window() {
+        let epoch = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let now = epoch + chrono::Duration::hours(12);
+        let (k1, ttl) = cache_entry("/api/v1/satellites/25544/passes", Some("lat=1&lon=2"), 25544, epoch, None, now);
+        assert_eq!(ttl, Duration::from_secs(60));
+        let (k2, _) = cache_entry("/api/v1/satellites/25544/passes", Some("lat=1&lon=2"), 25544, epoch, None, now + chrono::Duration::seconds(61));
+        assert_ne!(k1, k2);
+        let (k3, ttl) = cache_entry("/api/v1/satellites/25544/groundtrack", Some("start=2024-01-01T12:00:00Z"), 25544, epoch, None, now);
+        assert_eq!(ttl, MAX_TTL);
+        let (k4, _) = cache_entry("/api/v1/satellites/25544/groundtrack", Some("start=2024-01-01T12:00:00Z"), 25544, epoch + chrono::Duration::hours(1), None, now);
+        assert_ne!(k3, k4);
+
+        // An edited station gets new entries
+        let (s1, _) = cache_entry("/api/v1/passes", Some("norad_id=25544&station_id=3"), 25544, epoch, Some(1), now);
+        let (s2, _) = cache_entry("/api/v1/passes", Some("norad_id=25544&station_id=3"), 25544, epoch, Some(2), now);
+        assert_ne!(s1, s2);
+    }
+}
@@ -0,0 +1,118 @@
+use axum::{extract::{Query, State}, response::IntoResponse, Extension, Json};
+use axum::http::StatusCode;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Deserialize;
+
+use crate::analyzers::accuracy;
+use crate::analyzers::catalog_changes;
+use crate::analyzers::new_objects::{self, NEW_TAG, UPLOADED_TAG};
+use crate::api::access::Caller;
+use crate::api::server::AppState;
+use crate::api::types::{CatalogChangeDto, ScreeningReportDto, TaggedSatelliteDto, TleUploadResultDto};
+use crate::core::catalog::Catalog;
+
+#[derive(Debug, Deserialize)]
+pub struct NewObjectsQuery {
+    #[serde(default)]
+    since: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CatalogChangesQuery {
+    #[serde(default)]
+    since: Option<DateTime<Utc>>,
+    /// `added`, `removed` or `updated`.
+    #[serde(default)]
+    kind: Option<String>,
+}
+
+/// Objects that appeared in, disappeared from or got newer elements in the
+/// catalog at each load; defaults to the changes of the latest load.
+pub async fn list_changes(Query(q): Query<CatalogChangesQuery>) -> impl IntoResponse {
+    if q.kind.as_deref().is_some_and(|k| ![catalog_changes::ADDED, catalog_changes::REMOVED, catalog_changes::UPDATED].contains(&k)) {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": "kind must be added, removed or updated"})));
+    }
+    let since = q.since.map(|t| t.to_rfc3339_opts(SecondsFormat::Secs, true));
+    match crate::utils::db::open_or_init().and_then(|c| crate::utils::db::list_catalog_changes(&c, since.as_deref(), q.kind.as_deref())) {
+        Ok(rows) => {
+            let out: Vec<CatalogChangeDto> = rows
+                .into_iter()
+                .map(|r| CatalogChangeDto {
+                    changed_at: r.changed_at,
+                    norad_id: r.norad_id,
+                    kind: r.kind,
+                    name: r.name,
+                    previous_epoch: r.previous_epoch,
+                    epoch: r.epoch,
+                })
+                .collect();
+            (StatusCode::OK, Json(serde_json::json!(out)))
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+/// Lists objects tagged `new`; defaults to the ones that appeared in the latest fetch.
+pub async fn list_new_objects(Query(q): Query<NewObjectsQuery>) -> impl IntoResponse {
+    let since = q.since.map(|t| t.to_rfc3339_opts(SecondsFormat::Secs, true));
+    match crate::utils::db::open_or_init().and_then(|c| crate::utils::db::list_tagged(&c, NEW_TAG, None, since.as_deref())) {
+        Ok(rows) => {
+            let out: Vec<TaggedSatelliteDto> = rows
+                .into_iter()
+                .map(|r| TaggedSatelliteDto { norad_id: r.norad_id, name: r.name, tagged_at: r.tagged_at })
+                .collect();
+            (StatusCode::OK, Json(serde_json::json!(out)))
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+/// Accepts TLE text (2- or 3-line), archives it, and tags the objects as `uploaded`
+/// (plus `new` for NORAD IDs never seen before). The `uploaded` tag belongs to the
+/// caller's tenant. Uploaded objects join the in-memory catalog on the next load.
+pub async fn upload_tle(caller: Option<Extension<Caller>>, body: String) -> impl IntoResponse {
+    let report = crate::core::tle::parse_tle_report(&body);
+    let (records, elements) = (&report.records, &report.elements);
+    if elements.is_empty() {
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(serde_json::json!({"error": "no valid TLE records in body", "rejected_records": report.rejected})),
+        );
+    }
+
+    let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let ids: Vec<u64> = elements.iter().map(|e| e.norad_id).collect();
+    let tenant = caller.as_deref().and_then(|c| c.tenant.as_deref());
+    let stored = crate::utils::db::open_or_init().and_then(|c| {
+        let new_ids = new_objects::detect_and_tag(&c, elements, &now)?;
+        crate::utils::db::tag_satellites(&c, &ids, UPLOADED_TAG, tenant, &now)?;
+        accuracy::record_accuracy(&c, elements, &now)?;
+        crate::utils::db::insert_tle_history(&c, records, &now)?;
+        crate::utils::db::insert_fetch_log(&c, &now, "upload", elements.len(), &report.rejected, None)?;
+        Ok(new_ids)
+    });
+    let new_ids = match stored {
+        Ok(ids) => ids,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    };
+    let uploaded = Catalog::new(report.elements);
+    new_objects::notify_new_objects(&new_ids, &uploaded).await;
+
+    let out = TleUploadResultDto {
+        accepted: uploaded.len(),
+        rejected: report.rejected.len(),
+        rejected_records: report.rejected,
+        new_norad_ids: new_ids,
+    };
+    (StatusCode::CREATED, Json(serde_json::json!(out)))
+}
+
+/// Element sets left out of (or flagged in) the loaded catalog because they are
+/// stale, decayed or cannot be propagated.
+pub async fn get_screening(State(state): State<AppState>) -> impl IntoResponse {
+    let screening = state.screening.load();
+    let (limits, objects) = screening.as_ref();
+    let rejected = objects.iter().filter(|s| !s.kept).count();
+    let dto = ScreeningReportDto { limits: *limits, rejected, flagged: objects.len() - rejected, objects: objects.clone() };
+    (StatusCode::OK, Json(serde_json::json!(dto)))
+}
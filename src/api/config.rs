@@ -0,0 +1,63 @@
+use axum::{extract::Query, response::{IntoResponse, Response}, Json};
+use axum::http::{header, HeaderMap, StatusCode};
+use chrono::{SecondsFormat, Utc};
+use serde::Deserialize;
+
+use crate::core::bundle::{self, BundleError, BundleFormat};
+use crate::utils::db;
+
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    /// `json` (default) or `yaml`.
+    #[serde(default)]
+    format: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportQuery {
+    /// Clears the existing configuration before importing.
+    #[serde(default)]
+    replace: bool,
+}
+
+/// Stations, tags, aliases, alert subscriptions and custom element sets as one
+/// bundle, for `POST /config/import` or `STfCM import-config` on another deployment.
+pub async fn export_config(Query(q): Query<ExportQuery>) -> Response {
+    let format = match q.format.as_deref().map(BundleFormat::parse) {
+        None => BundleFormat::Json,
+        Some(Some(f)) => f,
+        Some(None) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": "format must be json or yaml"}))).into_response(),
+    };
+    let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let bundle = match db::open_or_init().and_then(|c| bundle::export_bundle(&c, &now)) {
+        Ok(b) => b,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))).into_response(),
+    };
+    match format {
+        BundleFormat::Json => (StatusCode::OK, Json(serde_json::json!(bundle))).into_response(),
+        BundleFormat::Yaml => match format.encode(&bundle) {
+            Ok(text) => (StatusCode::OK, [(header::CONTENT_TYPE, "application/yaml")], text).into_response(),
+            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": e}))).into_response(),
+        },
+    }
+}
+
+/// Restores a bundle in one transaction; a YAML body is recognised by its
+/// `Content-Type`. Nothing is written when any part of it is invalid.
+pub async fn import_config(Query(q): Query<ImportQuery>, headers: HeaderMap, body: String) -> impl IntoResponse {
+    let content_type = headers.get(header::CONTENT_TYPE).and_then(|v| v.to_str().ok()).unwrap_or("");
+    let format = if content_type.contains("yaml") { BundleFormat::Yaml } else { BundleFormat::Json };
+    let bundle = match format.decode(&body) {
+        Ok(b) => b,
+        Err(e) => return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": format!("invalid bundle: {}", e)}))),
+    };
+    let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let imported = db::open_or_init()
+        .map_err(BundleError::from)
+        .and_then(|c| bundle::import_bundle(&c, &bundle, q.replace, &now));
+    match imported {
+        Ok(summary) => (StatusCode::OK, Json(serde_json::json!(summary))),
+        Err(e @ BundleError::Invalid(_)) => (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": e.to_string()}))),
+        Err(BundleError::Db(e)) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
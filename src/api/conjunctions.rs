@@ -0,0 +1,99 @@
+use axum::{extract::{Path, Query, State}, response::IntoResponse, Extension, Json};
+use axum::http::StatusCode;
+use serde::Deserialize;
+
+use crate::api::access::Caller;
+use crate::api::server::AppState;
+use crate::api::types::{ConjunctionDto, CreateProtectedAssetDto, PositionSigmaDto, ProtectedAssetDto};
+
+/// Alert threshold applied when an asset is registered without one.
+const DEFAULT_ALERT_THRESHOLD_KM: f64 = 1.0;
+
+#[derive(Debug, Deserialize)]
+pub struct ConjunctionQuery {
+    #[serde(default)]
+    norad_id: Option<u64>,
+    #[serde(default = "default_limit")]
+    limit: usize,
+}
+
+fn default_limit() -> usize { 100 }
+
+pub async fn list_conjunctions(State(state): State<AppState>, caller: Option<Extension<Caller>>, Query(q): Query<ConjunctionQuery>) -> impl IntoResponse {
+    let scope = caller.as_deref().and_then(Caller::scope);
+    match crate::utils::db::open_or_init().and_then(|c| crate::utils::db::list_conjunctions(&c, q.norad_id, scope, q.limit)) {
+        Ok(rows) => {
+            let uncertainty = state.uncertainty.load();
+            let out: Vec<ConjunctionDto> = rows
+                .into_iter()
+                .map(|r| {
+                    let asset_sigma = uncertainty.get(&r.asset_norad_id);
+                    let secondary_sigma = uncertainty.get(&r.secondary_norad_id);
+                    let combined_sigma_km = match (asset_sigma, secondary_sigma) {
+                        (Some(a), Some(b)) => Some((a.total_km().powi(2) + b.total_km().powi(2)).sqrt()),
+                        _ => None,
+                    };
+                    ConjunctionDto {
+                        id: r.id,
+                        asset_norad_id: r.asset_norad_id,
+                        secondary_norad_id: r.secondary_norad_id,
+                        secondary_name: r.secondary_name,
+                        tca: r.tca,
+                        miss_distance_km: r.miss_distance_km,
+                        relative_speed_km_s: r.relative_speed_km_s,
+                        screened_at: r.screened_at,
+                        asset_sigma: asset_sigma.map(PositionSigmaDto::from),
+                        secondary_sigma: secondary_sigma.map(PositionSigmaDto::from),
+                        combined_sigma_km,
+                    }
+                })
+                .collect();
+            (StatusCode::OK, Json(serde_json::json!(out)))
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+pub async fn list_assets(caller: Option<Extension<Caller>>) -> impl IntoResponse {
+    let scope = caller.as_deref().and_then(Caller::scope);
+    match crate::utils::db::open_or_init().and_then(|c| crate::utils::db::list_protected_assets(&c, scope)) {
+        Ok(assets) => {
+            let out: Vec<ProtectedAssetDto> = assets
+                .into_iter()
+                .map(|a| ProtectedAssetDto { norad_id: a.norad_id, alert_threshold_km: a.alert_threshold_km, tenant: a.tenant })
+                .collect();
+            (StatusCode::OK, Json(serde_json::json!(out)))
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+pub async fn create_asset(State(state): State<AppState>, caller: Option<Extension<Caller>>, Json(body): Json<CreateProtectedAssetDto>) -> impl IntoResponse {
+    let threshold = body.alert_threshold_km.unwrap_or(DEFAULT_ALERT_THRESHOLD_KM);
+    if threshold.is_nan() || threshold <= 0.0 {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": "alert_threshold_km must be positive"})));
+    }
+    if state.catalog().get(body.norad_id).is_none() {
+        return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "norad_id not found in loaded TLEs"})));
+    }
+    let tenant = caller.as_deref().and_then(|c| c.tenant.clone());
+    match crate::utils::db::open_or_init().and_then(|c| crate::utils::db::upsert_protected_asset(&c, body.norad_id, threshold, tenant.as_deref())) {
+        Ok(()) => (StatusCode::CREATED, Json(serde_json::json!(ProtectedAssetDto { norad_id: body.norad_id, alert_threshold_km: threshold, tenant }))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+/// Ends the caller's subscription; admins and keys without a tenant end everyone's.
+pub async fn delete_asset(caller: Option<Extension<Caller>>, Path(norad_id): Path<u64>) -> impl IntoResponse {
+    let scope = caller.as_deref().and_then(Caller::scope);
+    match crate::utils::db::open_or_init().and_then(|c| crate::utils::db::delete_protected_asset(&c, norad_id, scope)) {
+        Ok(()) => (StatusCode::NO_CONTENT, Json(serde_json::json!({}))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+/// Triggers a screening run in the background and returns immediately.
+pub async fn trigger_screening(State(state): State<AppState>) -> impl IntoResponse {
+    tokio::spawn(crate::scheduler::conjunctions::run_once(state.catalog()));
+    (StatusCode::ACCEPTED, Json(serde_json::json!({"status": "screening started"})))
+}
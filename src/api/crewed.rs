@@ -0,0 +1,208 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use axum::{extract::{Query, State}, response::IntoResponse, Extension, Json};
+use axum::http::StatusCode;
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use tracing::warn;
+
+use crate::api::access::Caller;
+use crate::api::passes::{self, Prediction, PredictionParams, ResolvedObserver};
+use crate::api::server::AppState;
+use crate::api::types::{CrewedVehicleDto, PassWindowDto};
+use crate::core::coords::{ecef_to_geodetic_height, eci_to_ecef, gmst};
+use crate::core::orbit::{minutes_since_epoch, propagate_minutes};
+use crate::core::sun::shadow_margin_km;
+use crate::core::terrain::HorizonMask;
+use crate::predictors::passes::{ExclusionMode, Lighting, PassFilter, PassWindow};
+use crate::predictors::revolution::orbit_number_at;
+
+/// Environment variable with the comma-separated NORAD IDs listed by
+/// `/satellites/crewed`.
+const IDS_ENV: &str = "STFCM_CREWED_NORAD_IDS";
+/// ISS (ZARYA) and the Tiangong core module (TIANHE).
+const DEFAULT_IDS: [u64; 2] = [25544, 48274];
+/// How far ahead passes are searched. Visible passes come in spells a few
+/// days apart, so a day or two is often not enough.
+const PASS_SEARCH_HOURS: i64 = 168;
+/// Sampling step (s) of the pass search.
+const PASS_STEP_S: i64 = 15;
+/// How long a search that found no pass is reused.
+const EMPTY_SEARCH_MINUTES: i64 = 10;
+/// Entries kept before the pass cache is cleared.
+const MAX_CACHE_ENTRIES: usize = 1024;
+
+#[derive(Debug, Deserialize)]
+pub struct CrewedQuery {
+    #[serde(default)]
+    station_id: Option<i64>,
+    #[serde(default)]
+    lat: Option<f64>,
+    #[serde(default)]
+    lon: Option<f64>,
+    #[serde(default)]
+    alt_m: Option<f64>,
+    #[serde(default = "default_min_el")]
+    min_el: f64,
+}
+
+fn default_min_el() -> f64 { 10.0 }
+
+/// NORAD ID, element epoch, then observer latitude, longitude, altitude and
+/// minimum elevation as bits.
+type CacheKey = (u64, i64, [u64; 4]);
+
+/// Next pass and next visible pass of one vehicle over one observer.
+#[derive(Debug, Clone)]
+struct UpcomingPasses {
+    next: Option<PassWindow>,
+    next_visible: Option<PassWindow>,
+    /// The first of the passes ends here, after which the search is repeated.
+    valid_until: DateTime<Utc>,
+}
+
+/// Vehicles listed by `/satellites/crewed` and their cached pass searches. The
+/// cache lives as long as the loaded catalog.
+#[derive(Debug)]
+pub struct CrewedVehicles {
+    pub norad_ids: Vec<u64>,
+    passes: Mutex<BTreeMap<CacheKey, UpcomingPasses>>,
+}
+
+impl CrewedVehicles {
+    pub fn new(norad_ids: Vec<u64>) -> CrewedVehicles {
+        CrewedVehicles { norad_ids, passes: Mutex::new(BTreeMap::new()) }
+    }
+
+    /// Vehicles from `STFCM_CREWED_NORAD_IDS`; the ISS and Tiangong when unset
+    /// or invalid.
+    pub fn from_env() -> CrewedVehicles {
+        let Ok(value) = std::env::var(IDS_ENV) else {
+            return CrewedVehicles::new(DEFAULT_IDS.to_vec());
+        };
+        match value.split(',').map(|s| s.trim().parse::<u64>()).collect::<Result<Vec<_>, _>>() {
+            Ok(ids) if !ids.is_empty() => CrewedVehicles::new(ids),
+            _ => {
+                warn!(value = value.as_str(), "Ignoring invalid {}; use a comma-separated list of NORAD IDs", IDS_ENV);
+                CrewedVehicles::new(DEFAULT_IDS.to_vec())
+            }
+        }
+    }
+
+    fn cached(&self, key: &CacheKey, now: DateTime<Utc>) -> Option<UpcomingPasses> {
+        let passes = self.passes.lock().unwrap_or_else(|e| e.into_inner());
+        passes.get(key).filter(|p| now < p.valid_until).cloned()
+    }
+
+    fn store(&self, key: CacheKey, upcoming: UpcomingPasses) {
+        let mut passes = self.passes.lock().unwrap_or_else(|e| e.into_inner());
+        if passes.len() >= MAX_CACHE_ENTRIES {
+            passes.clear();
+        }
+        passes.insert(key, upcoming);
+    }
+}
+
+/// Whether the vehicle can be seen with the eye at closest approach: the
+/// observer is past civil twilight while the vehicle is still sunlit.
+fn is_visible(el: &sgp4::Elements, pass: &PassWindow, observer: &ResolvedObserver) -> bool {
+    let dark = PassFilter { lighting: Some(Lighting::Night), ..Default::default() };
+    dark.accepts(pass, &observer.position)
+        && propagate_minutes(el, minutes_since_epoch(el, pass.tca)).is_ok_and(|p| shadow_margin_km(&p.position, pass.tca) >= 0.0)
+}
+
+/// The next pass and next visible pass within a week, from the cache when a
+/// search for this element set and observer is still current.
+fn upcoming_passes(
+    vehicles: &CrewedVehicles,
+    el: &sgp4::Elements,
+    observer: &ResolvedObserver,
+    horizon: Option<&HorizonMask>,
+    min_el: f64,
+    now: DateTime<Utc>,
+) -> Result<UpcomingPasses, passes::PassError> {
+    let position = observer.position;
+    let key = (el.norad_id, el.datetime.and_utc().timestamp(), [position.lat_deg, position.lon_deg, position.alt_km, min_el].map(f64::to_bits));
+    if let Some(upcoming) = vehicles.cached(&key, now) {
+        return Ok(upcoming);
+    }
+    let params = PredictionParams {
+        start: now,
+        duration_min: PASS_SEARCH_HOURS * 60,
+        step_s: PASS_STEP_S,
+        min_el,
+        terrain: true,
+        refraction: false,
+        light_time: false,
+        exclusion_mode: ExclusionMode::default(),
+        merge_gap_s: 0,
+        min_peak_el: None,
+    };
+    let windows = match passes::run_prediction(el, None, observer, horizon, &params, None)? {
+        Prediction::Passes(windows) => windows,
+        Prediction::Geo(_) => Vec::new(),
+    };
+    let next_visible = windows.iter().find(|w| is_visible(el, w, observer)).cloned();
+    let next = windows.into_iter().next();
+    let valid_until = next.as_ref().map(|w| w.end).unwrap_or(now + Duration::minutes(EMPTY_SEARCH_MINUTES));
+    let upcoming = UpcomingPasses { next, next_visible, valid_until };
+    vehicles.store(key, upcoming.clone());
+    Ok(upcoming)
+}
+
+/// Where the crewed vehicles (`STFCM_CREWED_NORAD_IDS`) are now, their orbit
+/// numbers and, for a station or `lat`/`lon`, their next pass and next visible
+/// pass. Vehicles missing from the loaded catalog are left out.
+pub async fn list_crewed(Query(q): Query<CrewedQuery>, caller: Option<Extension<Caller>>, State(state): State<AppState>) -> impl IntoResponse {
+    let wants_passes = q.station_id.is_some() || q.lat.is_some() || q.lon.is_some();
+    let observer = match wants_passes.then(|| passes::resolve_observer(q.station_id, q.lat, q.lon, q.alt_m, caller.as_deref())).transpose() {
+        Ok(o) => o,
+        Err(response) => return response,
+    };
+    if !(-90.0..=90.0).contains(&q.min_el) {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": "min_el must be within [-90, 90]"})));
+    }
+    let horizon = match &observer {
+        Some(observer) => passes::observer_horizon(observer, true).await,
+        None => None,
+    };
+    let now = state.clock.now();
+    let mut out = Vec::new();
+    for id in &state.crewed.norad_ids {
+        let catalog = state.catalog();
+        let Some(el) = catalog.get(*id) else {
+            continue;
+        };
+        let pred = match propagate_minutes(el, minutes_since_epoch(el, now)) {
+            Ok(p) => p,
+            Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": format!("prediction error: {}", e)}))),
+        };
+        let (x, y, z) = eci_to_ecef(&pred.position, gmst(now));
+        let (lat, lon, alt_km) = ecef_to_geodetic_height(x, y, z);
+        let upcoming = match &observer {
+            Some(observer) => match upcoming_passes(&state.crewed, el, observer, horizon.as_deref(), q.min_el, now) {
+                Ok(u) => Some(u),
+                Err(response) => return response,
+            },
+            None => None,
+        };
+        let (next_pass, next_visible_pass) = upcoming.map(|u| (u.next, u.next_visible)).unwrap_or_default();
+        out.push(CrewedVehicleDto {
+            norad_id: el.norad_id,
+            name: catalog.name_of(*id).map(str::to_string),
+            time: now,
+            element_epoch: el.datetime.and_utc(),
+            lat,
+            lon,
+            alt_km,
+            speed_km_s: pred.velocity.iter().map(|v| v * v).sum::<f64>().sqrt(),
+            orbit_number: orbit_number_at(el, now, &pred.position, &pred.velocity),
+            in_eclipse: shadow_margin_km(&pred.position, now) < 0.0,
+            station_id: q.station_id,
+            next_pass: next_pass.map(PassWindowDto::from),
+            next_visible_pass: next_visible_pass.map(PassWindowDto::from),
+        });
+    }
+    (StatusCode::OK, Json(serde_json::json!(out)))
+}
@@ -0,0 +1,106 @@
+use axum::{extract::{Path, State}, response::IntoResponse, Json};
+use axum::http::StatusCode;
+use chrono::{SecondsFormat, Utc};
+
+use crate::api::server::AppState;
+use crate::api::types::{CustomElementsDto, CustomElementsRequestDto};
+use crate::core::custom::{synthetic_id, to_elements};
+use crate::utils::db::{self, CustomElements};
+
+fn custom_dto(state: &AppState, row: CustomElements) -> CustomElementsDto {
+    let norad_id = synthetic_id(row.id);
+    let epoch = to_elements(&row).ok().map(|el| el.datetime);
+    let loaded = epoch.is_some_and(|epoch| state.catalog().get(norad_id).is_some_and(|e| e.datetime == epoch));
+    CustomElementsDto {
+        id: row.id,
+        norad_id,
+        name: row.name,
+        epoch: epoch.map(|e| e.and_utc()),
+        tle: row.tle,
+        omm: row.omm.and_then(|o| serde_json::from_str(&o).ok()),
+        created_at: row.created_at,
+        updated_at: row.updated_at,
+        loaded,
+    }
+}
+
+/// Columns stored for a request.
+struct Submitted {
+    name: Option<String>,
+    tle: Option<String>,
+    omm: Option<String>,
+}
+
+/// Checks that the element set of a request parses.
+fn validate(body: CustomElementsRequestDto) -> Result<Submitted, String> {
+    let omm = body.elements.omm.as_ref().map(|o| o.to_string());
+    let tle = body.elements.tle.clone();
+    crate::core::tle::parse_tle_or_omm(body.elements.tle.as_deref(), body.elements.omm)?;
+    let name = body.name.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+    Ok(Submitted { name, tle, omm })
+}
+
+pub async fn list_custom_elements(State(state): State<AppState>) -> impl IntoResponse {
+    match db::open_or_init().and_then(|c| db::list_custom_elements(&c)) {
+        Ok(rows) => {
+            let out: Vec<CustomElementsDto> = rows.into_iter().map(|r| custom_dto(&state, r)).collect();
+            (StatusCode::OK, Json(serde_json::json!(out)))
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+/// Stores an element set for an object that is in no public catalog (e.g. a
+/// cubesat before its NORAD ID is assigned). It joins the catalog under a
+/// synthetic NORAD ID on the next TLE load.
+pub async fn create_custom_elements(State(state): State<AppState>, Json(body): Json<CustomElementsRequestDto>) -> impl IntoResponse {
+    let Submitted { name, tle, omm } = match validate(body) {
+        Ok(v) => v,
+        Err(e) => return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": e}))),
+    };
+    let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let created = db::open_or_init().and_then(|c| {
+        let id = db::insert_custom_elements(&c, name.as_deref(), tle.as_deref(), omm.as_deref(), &now)?;
+        db::get_custom_elements(&c, id)
+    });
+    match created {
+        Ok(row) => (StatusCode::CREATED, Json(serde_json::json!(custom_dto(&state, row)))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+pub async fn get_custom_elements(Path(id): Path<i64>, State(state): State<AppState>) -> impl IntoResponse {
+    match db::open_or_init().and_then(|c| db::get_custom_elements(&c, id)) {
+        Ok(row) => (StatusCode::OK, Json(serde_json::json!(custom_dto(&state, row)))),
+        Err(_) => (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "custom element set not found"}))),
+    }
+}
+
+/// Replaces the element set (and name) of a custom satellite; its synthetic
+/// NORAD ID stays the same.
+pub async fn update_custom_elements(Path(id): Path<i64>, State(state): State<AppState>, Json(body): Json<CustomElementsRequestDto>) -> impl IntoResponse {
+    let Submitted { name, tle, omm } = match validate(body) {
+        Ok(v) => v,
+        Err(e) => return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": e}))),
+    };
+    let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let updated = db::open_or_init().and_then(|c| {
+        if !db::update_custom_elements(&c, id, name.as_deref(), tle.as_deref(), omm.as_deref(), &now)? {
+            return Ok(None);
+        }
+        db::get_custom_elements(&c, id).map(Some)
+    });
+    match updated {
+        Ok(Some(row)) => (StatusCode::OK, Json(serde_json::json!(custom_dto(&state, row)))),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "custom element set not found"}))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+pub async fn delete_custom_elements(Path(id): Path<i64>) -> impl IntoResponse {
+    match db::open_or_init().and_then(|c| db::delete_custom_elements(&c, id)) {
+        Ok(true) => (StatusCode::NO_CONTENT, Json(serde_json::json!({}))),
+        Ok(false) => (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "custom element set not found"}))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
@@ -0,0 +1,185 @@
+use axum::{extract::{Path, Query, State}, response::IntoResponse, Extension, Json};
+use axum::http::StatusCode;
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+
+use crate::api::access::{self, Caller};
+use crate::api::horizon;
+use crate::api::server::AppState;
+use crate::api::types::{AlertStatusDto, EquatorialDto, PassWindowDto, SatellitePassDto, StationDto, StationSummaryDto, VisibleSatelliteDto};
+use crate::core::coords::gmst;
+use crate::core::orbit::{minutes_since_epoch, propagate_minutes};
+use crate::predictors::geo::is_geosynchronous;
+use crate::predictors::passes::{predict_passes_with_options, topocentric_motion, LookOptions, Observer, ObserverPosition, PassWindow};
+
+#[derive(Debug, Deserialize)]
+pub struct SummaryQuery {
+    /// Upcoming favorite passes to list.
+    #[serde(default = "default_passes")]
+    passes: usize,
+    #[serde(default = "default_min_el")]
+    min_el: f64,
+    /// Add topocentric RA/Dec to the visible satellites.
+    #[serde(default)]
+    radec: bool,
+}
+
+fn default_passes() -> usize { 5 }
+fn default_min_el() -> f64 { 10.0 }
+/// Most upcoming passes one summary lists.
+const MAX_SUMMARY_PASSES: usize = 50;
+/// Most favorites one station may follow.
+const MAX_FAVORITES: usize = 200;
+/// Sampling step (s) of the favorites' pass search.
+const SUMMARY_STEP_S: i64 = 15;
+
+pub async fn get_favorites(caller: Option<Extension<Caller>>, Path(id): Path<i64>) -> impl IntoResponse {
+    if !access::station_visible(caller.as_deref(), id) {
+        return access::station_not_found();
+    }
+    match crate::utils::db::open_or_init().and_then(|c| crate::utils::db::list_station_favorites(&c, id)) {
+        Ok(norad_ids) => (StatusCode::OK, Json(serde_json::json!(norad_ids))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+/// Replaces the NORAD IDs a station's dashboard follows.
+pub async fn set_favorites(caller: Option<Extension<Caller>>, Path(id): Path<i64>, Json(mut body): Json<Vec<u64>>) -> impl IntoResponse {
+    if !access::station_visible(caller.as_deref(), id) {
+        return access::station_not_found();
+    }
+    body.sort_unstable();
+    body.dedup();
+    if body.len() > MAX_FAVORITES {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": format!("at most {} favorites per station", MAX_FAVORITES)})));
+    }
+    let conn = match crate::utils::db::open_or_init() {
+        Ok(c) => c,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    };
+    if crate::utils::db::get_station(&conn, id).is_err() {
+        return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "station not found"})));
+    }
+    match crate::utils::db::set_station_favorites(&conn, id, &body) {
+        Ok(()) => (StatusCode::OK, Json(serde_json::json!(body))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+/// Minutes of `passes` that fall within `[from, to)`.
+fn contact_minutes(passes: &[(u64, PassWindow)], from: DateTime<Utc>, to: DateTime<Utc>) -> f64 {
+    let seconds: i64 = passes
+        .iter()
+        .map(|(_, w)| (w.end.min(to) - w.start.max(from)).num_seconds().max(0))
+        .sum();
+    seconds as f64 / 60.0
+}
+
+/// The station's home page in one request: satellites above `min_el` now, the
+/// next `passes` passes of its favorites (in progress ones included), the
+/// favorites' contact time over the current UTC day and the alert status.
+pub async fn get_summary(caller: Option<Extension<Caller>>, Path(id): Path<i64>, Query(q): Query<SummaryQuery>, State(state): State<AppState>) -> impl IntoResponse {
+    if !access::station_visible(caller.as_deref(), id) {
+        return access::station_not_found();
+    }
+    if q.passes > MAX_SUMMARY_PASSES {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": format!("passes must be at most {}", MAX_SUMMARY_PASSES)})));
+    }
+    let conn = match crate::utils::db::open_or_init() {
+        Ok(c) => c,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    };
+    let Ok(station) = crate::utils::db::get_station(&conn, id) else {
+        return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "station not found"})));
+    };
+    let (favorites, assets) = match (crate::utils::db::list_station_favorites(&conn, id), crate::utils::db::list_protected_assets(&conn, station.tenant.as_deref())) {
+        (Ok(f), Ok(a)) => (f, a),
+        (Err(e), _) | (_, Err(e)) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    };
+
+    let now = state.clock.now();
+    let position = ObserverPosition { lat_deg: station.lat, lon_deg: station.lon, alt_km: station.alt_m / 1000.0 };
+    let gmst_now = gmst(now);
+    let mut visible: Vec<VisibleSatelliteDto> = state
+        .catalog()
+        .named()
+        .filter_map(|(el, name)| {
+            let pred = propagate_minutes(el, minutes_since_epoch(el, now)).ok()?;
+            let motion = topocentric_motion(&pred.position, &pred.velocity, gmst_now, &position);
+            (motion.el_deg >= q.min_el).then(|| VisibleSatelliteDto {
+                equatorial: q.radec.then(|| EquatorialDto::new(&position, motion.az_deg, motion.el_deg, now)),
+                ..VisibleSatelliteDto::new(el.norad_id, name.map(str::to_string), motion)
+            })
+        })
+        .collect();
+    visible.sort_by(|a, b| b.el_deg.total_cmp(&a.el_deg));
+
+    // From the start of the UTC day, so today's finished passes count as well
+    let today = now.date_naive().and_hms_opt(0, 0, 0).expect("midnight").and_utc();
+    let end = now + Duration::hours(24);
+    let horizon = horizon::load_horizon(Some(id), station.lat, station.lon).await;
+    let exclusions = horizon::exclusions_for(id);
+    let options = LookOptions { horizon: horizon.as_deref(), exclusions: &exclusions, ..Default::default() };
+    let mut passes: Vec<(u64, PassWindow)> = Vec::new();
+    for el in state.catalog().iter().filter(|e| favorites.contains(&e.norad_id) && !is_geosynchronous(e)) {
+        match predict_passes_with_options(el, &Observer::Fixed(position), &options, today, (end - today).num_minutes(), SUMMARY_STEP_S, q.min_el) {
+            Ok(wins) => passes.extend(wins.into_iter().map(|w| (el.norad_id, w))),
+            Err(e) => tracing::warn!(norad_id = el.norad_id, error = %e, "Skipping favorite in station summary"),
+        }
+    }
+    passes.sort_by_key(|(_, w)| w.start);
+    let contact_minutes_today = contact_minutes(&passes, today, today + Duration::days(1));
+
+    let name_of = |norad_id: u64| state.catalog().name_of(norad_id).map(str::to_string);
+    let next_passes = passes
+        .into_iter()
+        .filter(|(_, w)| w.end > now)
+        .take(q.passes)
+        .map(|(norad_id, w)| SatellitePassDto {
+            norad_id,
+            name: name_of(norad_id),
+            pass: PassWindowDto::from(w),
+        })
+        .collect();
+    let alerts = AlertStatusDto {
+        webhook_configured: crate::utils::notify::webhook_configured(),
+        protected_favorites: favorites.iter().copied().filter(|id| assets.iter().any(|a| a.norad_id == *id)).collect(),
+    };
+    let dto = StationSummaryDto {
+        station: StationDto::from(station),
+        time: now,
+        visible,
+        favorites,
+        next_passes,
+        contact_minutes_today,
+        alerts,
+    };
+    (StatusCode::OK, Json(serde_json::json!(dto)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn contact_time_is_clipped_to_the_day() {
+        let day = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+        let pass = |start: DateTime<Utc>, minutes: i64| PassWindow {
+            start,
+            end: start + Duration::minutes(minutes),
+            tca: start,
+            max_elevation_deg: 45.0,
+            orbit_number: 0,
+            aos_az_deg: 0.0,
+            los_az_deg: 0.0,
+            blocked: Vec::new(),
+        };
+        let passes = vec![
+            (1, pass(day - Duration::minutes(4), 10)),
+            (2, pass(day + Duration::hours(12), 8)),
+            (1, pass(day + Duration::hours(24), 10)),
+        ];
+        assert_eq!(contact_minutes(&passes, day, day + Duration::days(1)), 14.0);
+    }
+}
@@ -0,0 +1,58 @@
+use axum::{extract::{Request, State}, middleware::Next, response::Response};
+use axum::http::{header, HeaderValue};
+use chrono::{DateTime, TimeZone, Utc};
+
+/// Deprecation notice attached to a group of routes.
+#[derive(Debug, Clone, Copy)]
+pub struct Deprecation {
+    /// When the routes were deprecated (`Deprecation` header, RFC 9745).
+    pub since: DateTime<Utc>,
+    /// When the routes stop being served, if decided (`Sunset` header, RFC 8594).
+    pub sunset: Option<DateTime<Utc>>,
+    /// Prefix of the replacement routes, linked as `successor-version`.
+    pub successor_prefix: Option<&'static str>,
+}
+
+/// The unversioned routes kept as aliases of `/api/v1`.
+pub fn legacy_api() -> Deprecation {
+    Deprecation {
+        since: Utc.with_ymd_and_hms(2026, 10, 16, 0, 0, 0).unwrap(),
+        sunset: Some(Utc.with_ymd_and_hms(2027, 4, 16, 0, 0, 0).unwrap()),
+        successor_prefix: Some("/api/v1"),
+    }
+}
+
+fn http_date(t: DateTime<Utc>) -> String {
+    t.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// Middleware adding the deprecation headers to every response of the routes it wraps:
+/// `router.layer(axum::middleware::from_fn_with_state(notice, deprecated))`.
+pub async fn deprecated(State(notice): State<Deprecation>, request: Request, next: Next) -> Response {
+    let successor = notice.successor_prefix.map(|prefix| {
+        let path = request.uri().path_and_query().map(|p| p.as_str()).unwrap_or("/");
+        format!("<{}{}>; rel=\"successor-version\"", prefix, path)
+    });
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    if let Ok(v) = HeaderValue::from_str(&format!("@{}", notice.since.timestamp())) {
+        headers.insert("deprecation", v);
+    }
+    if let Some(v) = notice.sunset.and_then(|t| HeaderValue::from_str(&http_date(t)).ok()) {
+        headers.insert("sunset", v);
+    }
+    if let Some(v) = successor.and_then(|l| HeaderValue::from_str(&l).ok()) {
+        headers.append(header::LINK, v);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sunset_uses_http_date() {
+        assert_eq!(http_date(Utc.with_ymd_and_hms(2027, 4, 16, 0, 0, 0).unwrap()), "Fri, 16 Apr 2027 00:00:00 GMT");
+    }
+}
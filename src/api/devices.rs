@@ -0,0 +1,124 @@
+use axum::{extract::Path, response::IntoResponse, Extension, Json};
+use axum::http::StatusCode;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use chrono::{SecondsFormat, Utc};
+use sha2::{Digest, Sha256};
+
+use crate::api::access::{self, Caller};
+use crate::api::types::{CreateDeviceTokenDto, DeviceTokenDto, TelemetryDto};
+use crate::utils::db::{self, DeviceToken, TelemetrySample};
+
+/// Prefix of device tokens, so they are recognisable in configs and logs.
+const TOKEN_PREFIX: &str = "stfcm_dev_";
+/// Most telemetry samples accepted per request.
+const MAX_TELEMETRY_BATCH: usize = 10_000;
+
+/// Hex SHA-256 of a token, as stored.
+pub fn token_hash(token: &str) -> String {
+    Sha256::digest(token.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Whether `key` looks like a device token rather than an API key.
+pub fn is_device_token(key: &str) -> bool {
+    key.starts_with(TOKEN_PREFIX)
+}
+
+/// Station a device token is bound to, if it is known and not revoked.
+pub fn station_for_token(token: &str) -> Option<i64> {
+    db::open_or_init().and_then(|c| db::device_token_station(&c, &token_hash(token))).ok().flatten()
+}
+
+fn token_dto(t: DeviceToken, token: Option<String>) -> DeviceTokenDto {
+    DeviceTokenDto { id: t.id, station_id: t.station_id, name: t.name, created_at: t.created_at, revoked_at: t.revoked_at, token }
+}
+
+/// Issues a token bound to one station for a rotator or rig client. The secret
+/// is only in this response.
+pub async fn create_token(caller: Option<Extension<Caller>>, Path(id): Path<i64>, Json(body): Json<CreateDeviceTokenDto>) -> impl IntoResponse {
+    if !access::station_visible(caller.as_deref(), id) {
+        return access::station_not_found();
+    }
+    let mut secret = [0u8; 24];
+    if let Err(e) = getrandom::fill(&mut secret) {
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("no randomness: {}", e)})));
+    }
+    let token = format!("{}{}", TOKEN_PREFIX, URL_SAFE_NO_PAD.encode(secret));
+    let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let name = body.name.filter(|n| !n.trim().is_empty());
+    let conn = match db::open_or_init() {
+        Ok(c) => c,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    };
+    if db::get_station(&conn, id).is_err() {
+        return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "station not found"})));
+    }
+    match db::insert_device_token(&conn, id, name.as_deref(), &token_hash(&token), &now) {
+        Ok(token_id) => {
+            let t = DeviceToken { id: token_id, station_id: id, name, created_at: now, revoked_at: None };
+            (StatusCode::CREATED, Json(serde_json::json!(token_dto(t, Some(token)))))
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+pub async fn list_tokens(caller: Option<Extension<Caller>>, Path(id): Path<i64>) -> impl IntoResponse {
+    if !access::station_visible(caller.as_deref(), id) {
+        return access::station_not_found();
+    }
+    match db::open_or_init().and_then(|c| db::list_device_tokens(&c, id)) {
+        Ok(tokens) => {
+            let out: Vec<DeviceTokenDto> = tokens.into_iter().map(|t| token_dto(t, None)).collect();
+            (StatusCode::OK, Json(serde_json::json!(out)))
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+pub async fn revoke_token(caller: Option<Extension<Caller>>, Path((id, token_id)): Path<(i64, i64)>) -> impl IntoResponse {
+    if !access::station_visible(caller.as_deref(), id) {
+        return access::station_not_found();
+    }
+    let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    match db::open_or_init().and_then(|c| db::revoke_device_token(&c, id, token_id, &now)) {
+        Ok(true) => (StatusCode::NO_CONTENT, Json(serde_json::json!({}))),
+        Ok(false) => (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "active token not found"}))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+/// Stores antenna pointing samples reported by a station's tracking client.
+pub async fn report_telemetry(caller: Option<Extension<Caller>>, Path(id): Path<i64>, Json(body): Json<Vec<TelemetryDto>>) -> impl IntoResponse {
+    if !access::station_visible(caller.as_deref(), id) {
+        return access::station_not_found();
+    }
+    if body.len() > MAX_TELEMETRY_BATCH {
+        return (StatusCode::PAYLOAD_TOO_LARGE, Json(serde_json::json!({"error": format!("at most {} samples per request", MAX_TELEMETRY_BATCH)})));
+    }
+    if body.iter().any(|s| !(0.0..=360.0).contains(&s.az_deg) || !(-90.0..=90.0).contains(&s.el_deg)) {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": "az_deg must be within 0..360 and el_deg within -90..90"})));
+    }
+    let received_at = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+    let samples: Vec<TelemetrySample> = body
+        .into_iter()
+        .map(|s| TelemetrySample {
+            id: 0,
+            norad_id: s.norad_id,
+            time: s.time.to_rfc3339_opts(SecondsFormat::Millis, true),
+            az_deg: s.az_deg,
+            el_deg: s.el_deg,
+            signal_dbm: s.signal_dbm,
+            received_at: received_at.clone(),
+        })
+        .collect();
+    let conn = match db::open_or_init() {
+        Ok(c) => c,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    };
+    if db::get_station(&conn, id).is_err() {
+        return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "station not found"})));
+    }
+    match db::insert_telemetry(&conn, id, &samples) {
+        Ok(n) => (StatusCode::CREATED, Json(serde_json::json!({"accepted": n}))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
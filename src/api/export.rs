@@ -0,0 +1,213 @@
+use axum::{extract::{Path, Query, State}, response::{IntoResponse, Response}, Json};
+use axum::http::{header, StatusCode};
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+
+use crate::api::server::AppState;
+use crate::predictors::passes::{predict_passes_for_observer, Observer, ObserverPosition};
+use crate::utils::db::{self, DbError};
+use crate::utils::parquet::{rfc3339_micros, timestamp_micros, Column, ParquetTable};
+
+/// Rows read from SQLite and written per Parquet row group.
+const ROW_GROUP_SIZE: usize = 50_000;
+const MAX_PASS_HOURS: i64 = 168;
+
+const SNAPSHOTS_SCHEMA: &str = "message snapshots {
+    REQUIRED INT64 id;
+    REQUIRED INT64 norad_id;
+    OPTIONAL INT64 timestamp (TIMESTAMP(MICROS,true));
+    REQUIRED DOUBLE pos_x_km;
+    REQUIRED DOUBLE pos_y_km;
+    REQUIRED DOUBLE pos_z_km;
+    REQUIRED DOUBLE vel_x_km_s;
+    REQUIRED DOUBLE vel_y_km_s;
+    REQUIRED DOUBLE vel_z_km_s;
+}";
+
+const TLE_HISTORY_SCHEMA: &str = "message tle_history {
+    REQUIRED INT64 id;
+    REQUIRED INT64 norad_id;
+    OPTIONAL INT64 epoch (TIMESTAMP(MICROS,true));
+    OPTIONAL BYTE_ARRAY name (UTF8);
+    REQUIRED BYTE_ARRAY line1 (UTF8);
+    REQUIRED BYTE_ARRAY line2 (UTF8);
+    OPTIONAL INT64 fetched_at (TIMESTAMP(MICROS,true));
+}";
+
+const PASSES_SCHEMA: &str = "message passes {
+    REQUIRED INT64 norad_id;
+    REQUIRED INT64 station_id;
+    OPTIONAL BYTE_ARRAY station_name (UTF8);
+    REQUIRED INT64 start (TIMESTAMP(MICROS,true));
+    REQUIRED INT64 tca (TIMESTAMP(MICROS,true));
+    REQUIRED INT64 end (TIMESTAMP(MICROS,true));
+    REQUIRED DOUBLE max_elevation_deg;
+    REQUIRED INT64 duration_s;
+}";
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Dataset {
+    Snapshots,
+    TleHistory,
+    Passes,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    /// Restricts the rows to one satellite; required for `passes`.
+    #[serde(default)]
+    norad_id: Option<u64>,
+    /// Start of the pass prediction window (default: now).
+    #[serde(default)]
+    start: Option<DateTime<Utc>>,
+    /// Length of the pass prediction window in hours.
+    #[serde(default = "default_hours")]
+    hours: i64,
+    #[serde(default = "default_min_el")]
+    min_el: f64,
+}
+
+fn default_hours() -> i64 { 24 }
+fn default_min_el() -> f64 { 10.0 }
+
+#[derive(Debug, thiserror::Error)]
+enum ExportError {
+    #[error("db error: {0}")]
+    Db(#[from] DbError),
+    #[error("parquet error: {0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
+}
+
+/// Snapshots, archived TLEs or predicted passes as a Parquet file, for offline
+/// analysis with pandas/Spark. NORAD IDs are INT64 and times are UTC
+/// `TIMESTAMP(MICROS)` columns.
+pub async fn export_parquet(
+    Path(dataset): Path<Dataset>,
+    Query(q): Query<ExportQuery>,
+    State(state): State<AppState>,
+) -> Response {
+    let result = match dataset {
+        Dataset::Snapshots => tokio::task::spawn_blocking(move || export_snapshots(q.norad_id)).await,
+        Dataset::TleHistory => tokio::task::spawn_blocking(move || export_tle_history(q.norad_id)).await,
+        Dataset::Passes => {
+            let Some(norad_id) = q.norad_id else {
+                return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": "norad_id is required for passes"}))).into_response();
+            };
+            if !(1..=MAX_PASS_HOURS).contains(&q.hours) {
+                return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": format!("hours must be within 1..={}", MAX_PASS_HOURS)}))).into_response();
+            }
+            let catalog = state.catalog();
+            let Some(index) = catalog.index_of(norad_id) else {
+                return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "norad_id not found in loaded TLEs"}))).into_response();
+            };
+            let start = q.start.unwrap_or_else(Utc::now);
+            tokio::task::spawn_blocking(move || export_passes(&catalog[index], start, Duration::hours(q.hours), q.min_el)).await
+        }
+    };
+    let bytes = match result {
+        Ok(Ok(b)) => b,
+        Ok(Err(e)) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": e.to_string()}))).into_response(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("export task failed: {}", e)}))).into_response(),
+    };
+    let name = match dataset {
+        Dataset::Snapshots => "snapshots",
+        Dataset::TleHistory => "tle_history",
+        Dataset::Passes => "passes",
+    };
+    let filename = format!("{}-{}.parquet", name, Utc::now().format("%Y%m%dT%H%M%SZ"));
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "application/vnd.apache.parquet".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        bytes,
+    )
+        .into_response()
+}
+
+/// Pages through a table by row ID and writes each page as a row group.
+fn export_pages<R>(
+    schema: &str,
+    mut fetch: impl FnMut(&rusqlite::Connection, i64) -> Result<Vec<R>, DbError>,
+    id_of: impl Fn(&R) -> i64,
+    to_columns: impl Fn(&[R]) -> Vec<Column>,
+) -> Result<Vec<u8>, ExportError> {
+    let conn = db::open_or_init()?;
+    let mut table = ParquetTable::new(schema)?;
+    let mut after_id = 0;
+    loop {
+        let rows = fetch(&conn, after_id)?;
+        let Some(last) = rows.last() else { break };
+        after_id = id_of(last);
+        table.write_row_group(&to_columns(&rows))?;
+        if rows.len() < ROW_GROUP_SIZE {
+            break;
+        }
+    }
+    Ok(table.finish()?)
+}
+
+fn export_snapshots(norad_id: Option<u64>) -> Result<Vec<u8>, ExportError> {
+    export_pages(
+        SNAPSHOTS_SCHEMA,
+        |c, after_id| db::list_snapshots_page(c, norad_id, after_id, ROW_GROUP_SIZE),
+        |r| r.id,
+        |rows| {
+            let mut columns = vec![
+                Column::Int64(rows.iter().map(|r| Some(r.id)).collect()),
+                Column::Int64(rows.iter().map(|r| Some(r.norad_id as i64)).collect()),
+                Column::Int64(rows.iter().map(|r| rfc3339_micros(&r.timestamp)).collect()),
+            ];
+            columns.extend((0..3).map(|i| Column::Double(rows.iter().map(|r| Some(r.position[i])).collect())));
+            columns.extend((0..3).map(|i| Column::Double(rows.iter().map(|r| Some(r.velocity[i])).collect())));
+            columns
+        },
+    )
+}
+
+fn export_tle_history(norad_id: Option<u64>) -> Result<Vec<u8>, ExportError> {
+    export_pages(
+        TLE_HISTORY_SCHEMA,
+        |c, after_id| db::list_tle_history_page(c, norad_id, after_id, ROW_GROUP_SIZE),
+        |r| r.id,
+        |rows| {
+            vec![
+                Column::Int64(rows.iter().map(|r| Some(r.id)).collect()),
+                Column::Int64(rows.iter().map(|r| Some(r.norad_id as i64)).collect()),
+                Column::Int64(rows.iter().map(|r| rfc3339_micros(&r.epoch)).collect()),
+                Column::Text(rows.iter().map(|r| r.name.clone()).collect()),
+                Column::Text(rows.iter().map(|r| Some(r.line1.clone())).collect()),
+                Column::Text(rows.iter().map(|r| Some(r.line2.clone())).collect()),
+                Column::Int64(rows.iter().map(|r| rfc3339_micros(&r.fetched_at)).collect()),
+            ]
+        },
+    )
+}
+
+/// Passes of one satellite over every stored station; stations where
+/// prediction fails are skipped.
+fn export_passes(el: &sgp4::Elements, start: DateTime<Utc>, window: Duration, min_el: f64) -> Result<Vec<u8>, ExportError> {
+    let conn = db::open_or_init()?;
+    let mut rows = Vec::new();
+    for station in db::list_stations(&conn)? {
+        let observer = Observer::Fixed(ObserverPosition { lat_deg: station.lat, lon_deg: station.lon, alt_km: station.alt_m / 1000.0 });
+        match predict_passes_for_observer(el, &observer, start, window.num_minutes(), 15, min_el) {
+            Ok(passes) => rows.extend(passes.into_iter().map(|p| (station.id, station.name.clone(), p))),
+            Err(e) => tracing::warn!(error = %e, station = station.id, "Pass prediction failed during export"),
+        }
+    }
+    let mut table = ParquetTable::new(PASSES_SCHEMA)?;
+    table.write_row_group(&[
+        Column::Int64(rows.iter().map(|_| Some(el.norad_id as i64)).collect()),
+        Column::Int64(rows.iter().map(|(id, _, _)| Some(*id)).collect()),
+        Column::Text(rows.iter().map(|(_, name, _)| name.clone()).collect()),
+        Column::Int64(rows.iter().map(|(_, _, p)| Some(timestamp_micros(p.start))).collect()),
+        Column::Int64(rows.iter().map(|(_, _, p)| Some(timestamp_micros(p.tca))).collect()),
+        Column::Int64(rows.iter().map(|(_, _, p)| Some(timestamp_micros(p.end))).collect()),
+        Column::Double(rows.iter().map(|(_, _, p)| Some(p.max_elevation_deg)).collect()),
+        Column::Int64(rows.iter().map(|(_, _, p)| Some(p.duration_s())).collect()),
+    ])?;
+    Ok(table.finish()?)
+}
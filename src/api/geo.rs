@@ -0,0 +1,85 @@
+use axum::{extract::{Query, State}, response::IntoResponse, Json};
+use axum::http::StatusCode;
+use serde::Deserialize;
+
+use crate::api::asof::archived_elements;
+use crate::api::server::AppState;
+use crate::api::types::{GeoBeltDto, GeoBinDto, GeoLookDto, GeoObjectDto};
+use crate::core::catalog::Catalog;
+use crate::predictors::geo::{geo_state, is_geosynchronous};
+use crate::predictors::passes::{pointing_track, LookOptions, ObserverPosition};
+
+#[derive(Debug, Deserialize)]
+pub struct GeoQuery {
+    #[serde(default = "default_bin")]
+    bin: f64,
+    /// Belt occupancy at this time from the archived TLEs nearest it.
+    #[serde(default)]
+    as_of: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+fn default_bin() -> f64 { 5.0 }
+
+/// Subsatellite point and drift of a geosynchronous object, `None` on propagation errors.
+pub fn geo_object(el: &sgp4::Elements, name: Option<&str>, t: chrono::DateTime<chrono::Utc>) -> Option<GeoObjectDto> {
+    let state = geo_state(el, t).ok()?;
+    Some(GeoObjectDto {
+        norad_id: el.norad_id,
+        name: name.map(str::to_string),
+        longitude_deg: state.longitude_deg,
+        latitude_deg: state.latitude_deg,
+        drift_deg_per_day: state.drift_deg_per_day,
+    })
+}
+
+/// Constant look angle from a station to a geosynchronous object, used by the
+/// pass endpoints instead of scanning for passes; `None` on propagation errors.
+pub fn geo_look(
+    el: &sgp4::Elements,
+    name: Option<&str>,
+    position: &ObserverPosition,
+    options: &LookOptions<'_>,
+    min_el: f64,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Option<GeoLookDto> {
+    let sample = pointing_track(el, position, now, now, 1, options).ok()?.into_iter().next()?;
+    Some(GeoLookDto {
+        geo: geo_object(el, name, now)?,
+        az_deg: sample.az_deg,
+        el_deg: sample.el_deg,
+        visible: sample.el_deg >= min_el,
+    })
+}
+
+/// GEO belt occupancy: every geosynchronous object by longitude, plus counts per
+/// longitude bin.
+pub async fn list_geo(State(state): State<AppState>, Query(q): Query<GeoQuery>) -> impl IntoResponse {
+    if !(q.bin > 0.0 && q.bin <= 180.0) {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": "bin must be within (0, 180]"})));
+    }
+    let now = q.as_of.unwrap_or_else(|| state.clock.now());
+    let archived = match q.as_of.map(|t| archived_elements(t, None).map(Catalog::new)).transpose() {
+        Ok(a) => a,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    };
+    let current = state.catalog();
+    let mut objects: Vec<GeoObjectDto> = archived
+        .as_ref()
+        .unwrap_or(&current)
+        .named()
+        .filter(|(el, _)| is_geosynchronous(el))
+        .filter_map(|(el, name)| geo_object(el, name, now))
+        .collect();
+    objects.sort_by(|a, b| a.longitude_deg.total_cmp(&b.longitude_deg));
+
+    let n_bins = (360.0 / q.bin).ceil() as usize;
+    let mut bins: Vec<GeoBinDto> = (0..n_bins)
+        .map(|i| GeoBinDto { lon_start_deg: -180.0 + i as f64 * q.bin, count: 0 })
+        .collect();
+    for o in &objects {
+        let idx = (((o.longitude_deg + 180.0) / q.bin) as usize).min(n_bins - 1);
+        bins[idx].count += 1;
+    }
+    let out = GeoBeltDto { bin_width_deg: q.bin, bins, objects };
+    (StatusCode::OK, Json(serde_json::json!(out)))
+}
@@ -0,0 +1,230 @@
+use std::fmt::Write as _;
+
+use axum::{extract::{Path, Query, State}, response::{IntoResponse, Response}, Json};
+use axum::http::{header, StatusCode};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Deserialize;
+
+use crate::api::server::AppState;
+use crate::predictors::groundtrack::{ground_track, swath_polygons, GroundTrackPoint, SwathPolygon};
+use crate::predictors::saa::{saa_crossings, SAA_BOUNDARY};
+
+#[derive(Debug, Deserialize)]
+pub struct GroundTrackQuery {
+    #[serde(default)]
+    format: GroundTrackFormat,
+    /// Start of the track (default: now).
+    #[serde(default)]
+    start: Option<DateTime<Utc>>,
+    #[serde(default = "default_revolutions")]
+    revolutions: f64,
+    /// Seconds between track points.
+    #[serde(default = "default_step")]
+    step: i64,
+    /// Half-angle (degrees) of a nadir-pointing sensor; adds its swath.
+    #[serde(default)]
+    swath_half_angle: Option<f64>,
+    /// Add the South Atlantic Anomaly and the parts of the track inside it.
+    #[serde(default)]
+    saa: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroundTrackFormat {
+    #[default]
+    Kml,
+    Czml,
+}
+
+fn default_revolutions() -> f64 { 1.0 }
+fn default_step() -> i64 { 30 }
+
+fn rfc3339(t: DateTime<Utc>) -> String {
+    t.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Time-stamped ground track as KML (`gx:Track`) or CZML, optionally with the
+/// swath of a nadir-pointing sensor as polygons valid while the satellite is over them
+/// and with its South Atlantic Anomaly crossings.
+pub async fn get_groundtrack(
+    Path(norad_id): Path<u64>,
+    Query(q): Query<GroundTrackQuery>,
+    State(state): State<AppState>,
+) -> Response {
+    let catalog = state.catalog();
+    let Some(el) = catalog.get(norad_id) else {
+        return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "norad_id not found in loaded TLEs"}))).into_response();
+    };
+    if q.swath_half_angle.is_some_and(|a| !(a > 0.0 && a < 90.0)) {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": "swath_half_angle must be within (0, 90) degrees"}))).into_response();
+    }
+    let start = q.start.unwrap_or_else(Utc::now);
+    let period_s = 86_400.0 / el.mean_motion;
+    let end = start + chrono::Duration::seconds((period_s * q.revolutions.clamp(0.1, 16.0)) as i64);
+    let track = match ground_track(el, start, end, q.step.max(1)) {
+        Ok(t) => t,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": format!("prediction error: {}", e)}))).into_response(),
+    };
+    let swath = q.swath_half_angle.map(|a| (a, swath_polygons(&track, a)));
+    let saa = q.saa.then(|| saa_crossings(&track));
+    let name = catalog.name_of(norad_id).map_or_else(|| norad_id.to_string(), str::to_string);
+
+    let (body, content_type, ext) = match q.format {
+        GroundTrackFormat::Kml => (to_kml(&name, &track, swath.as_ref(), saa.as_deref()), "application/vnd.google-earth.kml+xml", "kml"),
+        GroundTrackFormat::Czml => (to_czml(norad_id, &name, &track, swath.as_ref(), saa.as_deref(), period_s), "application/json", "czml"),
+    };
+    let filename = format!("{}-{}.{}", norad_id, start.format("%Y%m%dT%H%M%SZ"), ext);
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+/// Points of `track` within a crossing.
+fn crossing_points(track: &[GroundTrackPoint], (start, end): (DateTime<Utc>, DateTime<Utc>)) -> impl Iterator<Item = &GroundTrackPoint> {
+    track.iter().filter(move |p| p.time >= start && p.time <= end)
+}
+
+fn to_kml(name: &str, track: &[GroundTrackPoint], swath: Option<&(f64, Vec<SwathPolygon>)>, saa: Option<&[(DateTime<Utc>, DateTime<Utc>)]>) -> String {
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<kml xmlns=\"http://www.opengis.net/kml/2.2\" xmlns:gx=\"http://www.google.com/kml/ext/2.2\">\n<Document>\n",
+    );
+    let _ = writeln!(out, "<name>{}</name>", xml_escape(name));
+    out.push_str("<Style id=\"track\"><LineStyle><color>ff00ffff</color><width>2</width></LineStyle></Style>\n");
+    out.push_str("<Style id=\"swath\"><LineStyle><color>ff00a5ff</color></LineStyle><PolyStyle><color>5000a5ff</color></PolyStyle></Style>\n");
+    out.push_str("<Style id=\"saa\"><LineStyle><color>ff0000ff</color><width>4</width></LineStyle><PolyStyle><color>300000ff</color></PolyStyle></Style>\n");
+    out.push_str("<Placemark><name>Ground track</name><styleUrl>#track</styleUrl><gx:Track><altitudeMode>clampToGround</altitudeMode>\n");
+    for p in track {
+        let _ = writeln!(out, "<when>{}</when>", rfc3339(p.time));
+    }
+    for p in track {
+        let _ = writeln!(out, "<gx:coord>{:.5} {:.5} 0</gx:coord>", p.lon_deg, p.lat_deg);
+    }
+    out.push_str("</gx:Track></Placemark>\n");
+    if let Some((half_angle, polygons)) = swath {
+        for poly in polygons {
+            let _ = write!(
+                out,
+                "<Placemark><name>Swath ({:.1}°)</name><styleUrl>#swath</styleUrl><TimeSpan><begin>{}</begin><end>{}</end></TimeSpan><Polygon><tessellate>1</tessellate><outerBoundaryIs><LinearRing><coordinates>",
+                half_angle,
+                rfc3339(poly.start),
+                rfc3339(poly.end)
+            );
+            for [lon, lat] in &poly.ring {
+                let _ = write!(out, "{:.5},{:.5},0 ", lon, lat);
+            }
+            out.push_str("</coordinates></LinearRing></outerBoundaryIs></Polygon></Placemark>\n");
+        }
+    }
+    if let Some(crossings) = saa {
+        out.push_str("<Placemark><name>South Atlantic Anomaly</name><styleUrl>#saa</styleUrl><Polygon><outerBoundaryIs><LinearRing><coordinates>");
+        for [lon, lat] in &SAA_BOUNDARY {
+            let _ = write!(out, "{:.1},{:.1},0 ", lon, lat);
+        }
+        out.push_str("</coordinates></LinearRing></outerBoundaryIs></Polygon></Placemark>\n");
+        for &crossing in crossings {
+            let _ = write!(
+                out,
+                "<Placemark><name>SAA crossing</name><styleUrl>#saa</styleUrl><TimeSpan><begin>{}</begin><end>{}</end></TimeSpan><LineString><tessellate>1</tessellate><coordinates>",
+                rfc3339(crossing.0),
+                rfc3339(crossing.1)
+            );
+            for p in crossing_points(track, crossing) {
+                let _ = write!(out, "{:.5},{:.5},0 ", p.lon_deg, p.lat_deg);
+            }
+            out.push_str("</coordinates></LineString></Placemark>\n");
+        }
+    }
+    out.push_str("</Document>\n</kml>\n");
+    out
+}
+
+fn to_czml(
+    norad_id: u64,
+    name: &str,
+    track: &[GroundTrackPoint],
+    swath: Option<&(f64, Vec<SwathPolygon>)>,
+    saa: Option<&[(DateTime<Utc>, DateTime<Utc>)]>,
+    period_s: f64,
+) -> String {
+    let (Some(first), Some(last)) = (track.first(), track.last()) else {
+        return "[]".to_string();
+    };
+    let interval = format!("{}/{}", rfc3339(first.time), rfc3339(last.time));
+    let positions: Vec<f64> = track
+        .iter()
+        .flat_map(|p| [(p.time - first.time).num_milliseconds() as f64 / 1000.0, p.lon_deg, p.lat_deg, p.alt_km * 1000.0])
+        .collect();
+    let mut packets = vec![
+        serde_json::json!({
+            "id": "document",
+            "name": name,
+            "version": "1.0",
+            "clock": { "interval": interval, "currentTime": rfc3339(first.time), "multiplier": 60 },
+        }),
+        serde_json::json!({
+            "id": format!("sat-{}", norad_id),
+            "name": name,
+            "availability": interval,
+            "position": { "epoch": rfc3339(first.time), "cartographicDegrees": positions },
+            "point": { "pixelSize": 6, "color": { "rgba": [255, 255, 0, 255] } },
+            "path": {
+                "width": 2,
+                "leadTime": 0,
+                "trailTime": period_s,
+                "material": { "solidColor": { "color": { "rgba": [255, 255, 0, 255] } } },
+            },
+        }),
+    ];
+    if let Some((_, polygons)) = swath {
+        for (i, poly) in polygons.iter().enumerate() {
+            let ring: Vec<f64> = poly.ring.iter().flat_map(|[lon, lat]| [*lon, *lat, 0.0]).collect();
+            packets.push(serde_json::json!({
+                "id": format!("swath-{}-{}", norad_id, i),
+                "name": format!("{} swath", name),
+                "availability": format!("{}/{}", rfc3339(poly.start), rfc3339(poly.end)),
+                "polygon": {
+                    "positions": { "cartographicDegrees": ring },
+                    "material": { "solidColor": { "color": { "rgba": [255, 165, 0, 80] } } },
+                    "outline": true,
+                },
+            }));
+        }
+    }
+    if let Some(crossings) = saa {
+        let outline: Vec<f64> = SAA_BOUNDARY.iter().flat_map(|[lon, lat]| [*lon, *lat, 0.0]).collect();
+        packets.push(serde_json::json!({
+            "id": "saa",
+            "name": "South Atlantic Anomaly",
+            "polygon": {
+                "positions": { "cartographicDegrees": outline },
+                "material": { "solidColor": { "color": { "rgba": [255, 0, 0, 48] } } },
+                "outline": true,
+            },
+        }));
+        for (i, &crossing) in crossings.iter().enumerate() {
+            let positions: Vec<f64> = crossing_points(track, crossing).flat_map(|p| [p.lon_deg, p.lat_deg, p.alt_km * 1000.0]).collect();
+            packets.push(serde_json::json!({
+                "id": format!("saa-{}-{}", norad_id, i),
+                "name": format!("{} SAA crossing", name),
+                "availability": format!("{}/{}", rfc3339(crossing.0), rfc3339(crossing.1)),
+                "polyline": {
+                    "positions": { "cartographicDegrees": positions },
+                    "width": 4,
+                    "material": { "solidColor": { "color": { "rgba": [255, 0, 0, 255] } } },
+                },
+            }));
+        }
+    }
+    serde_json::Value::Array(packets).to_string()
+}
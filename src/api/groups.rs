@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+
+use axum::{extract::{Path, Query, State}, response::IntoResponse, Extension, Json};
+use axum::http::StatusCode;
+use chrono::{SecondsFormat, Utc};
+use serde::Deserialize;
+
+use crate::analyzers::groups::{update_group, validate_group_name, GroupFilter};
+use crate::api::access::Caller;
+use crate::api::server::AppState;
+use crate::api::types::{GroupDetailDto, GroupDto, GroupRequestDto, TaggedSatelliteDto};
+use crate::utils::db::{self, DbError};
+
+#[derive(Debug, Default, Deserialize)]
+pub struct GroupQuery {
+    /// Tenant whose group to address; only for callers not confined to one.
+    #[serde(default)]
+    tenant: Option<String>,
+}
+
+fn db_error(e: DbError) -> (StatusCode, Json<serde_json::Value>) {
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)})))
+}
+
+/// Tenant whose groups a request creates and changes: the key's tenant, or
+/// `?tenant=` for callers that may manage every tenant's data.
+fn tenant_of(caller: Option<&Caller>, q: &GroupQuery) -> Option<String> {
+    let requested = q.tenant.clone().filter(|t| !t.is_empty());
+    match caller {
+        Some(c) if c.scope().is_some() => c.tenant.clone(),
+        Some(c) => requested.or_else(|| c.tenant.clone()),
+        None => requested,
+    }
+}
+
+/// Every satellite tag with its member count, plus the dynamic groups that
+/// have no members yet. Tenant keys see their own and the shared ones.
+pub async fn list_groups(caller: Option<Extension<Caller>>) -> impl IntoResponse {
+    let scope = caller.as_deref().and_then(Caller::scope);
+    let listed = db::open_or_init().and_then(|c| Ok((db::tag_counts(&c, scope)?, db::list_dynamic_groups(&c, scope)?)));
+    let (counts, dynamic) = match listed {
+        Ok(v) => v,
+        Err(e) => return db_error(e),
+    };
+    let mut groups: BTreeMap<(String, Option<String>), GroupDto> = counts
+        .into_iter()
+        .map(|(tenant, name, members)| ((name.clone(), tenant.clone()), GroupDto { name, tenant, filter: None, members }))
+        .collect();
+    for group in dynamic {
+        groups
+            .entry((group.name.clone(), group.tenant.clone()))
+            .or_insert_with(|| GroupDto { name: group.name, tenant: group.tenant, filter: None, members: 0 })
+            .filter = Some(group.filter);
+    }
+    (StatusCode::OK, Json(serde_json::json!(groups.into_values().collect::<Vec<_>>())))
+}
+
+fn group_detail(conn: &rusqlite::Connection, name: &str, tenant: Option<&str>) -> Result<Option<GroupDetailDto>, DbError> {
+    let dynamic = db::get_dynamic_group(conn, name, tenant)?;
+    let mut members = db::list_tagged(conn, name, tenant, Some(""))?;
+    if dynamic.is_none() && members.is_empty() {
+        return Ok(None);
+    }
+    members.sort_by_key(|m| m.norad_id);
+    Ok(Some(GroupDetailDto {
+        name: name.to_string(),
+        tenant: tenant.map(str::to_string),
+        filter: dynamic.as_ref().map(|g| g.filter.clone()),
+        updated_at: dynamic.map(|g| g.updated_at),
+        members: members
+            .into_iter()
+            .map(|r| TaggedSatelliteDto { norad_id: r.norad_id, name: r.name, tagged_at: r.tagged_at })
+            .collect(),
+    }))
+}
+
+/// Members of a tag or dynamic group, by NORAD ID. The tenant's own group of
+/// that name, or else the shared one.
+pub async fn get_group(Path(name): Path<String>, Query(q): Query<GroupQuery>, caller: Option<Extension<Caller>>) -> impl IntoResponse {
+    let tenant = tenant_of(caller.as_deref(), &q);
+    let found = db::open_or_init().and_then(|c| {
+        let owner = db::tag_owner(&c, &name, tenant.as_deref())?;
+        group_detail(&c, &name, owner.as_deref())
+    });
+    match found {
+        Ok(Some(group)) => (StatusCode::OK, Json(serde_json::json!(group))),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "group not found"}))),
+        Err(e) => db_error(e),
+    }
+}
+
+/// Defines a dynamic group of the caller's tenant, or replaces its filter, and
+/// tags its members in the loaded catalog right away. Each TLE load updates
+/// the members again.
+pub async fn put_group(
+    Path(name): Path<String>,
+    Query(q): Query<GroupQuery>,
+    State(state): State<AppState>,
+    caller: Option<Extension<Caller>>,
+    Json(body): Json<GroupRequestDto>,
+) -> impl IntoResponse {
+    if let Err(e) = validate_group_name(&name) {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": e})));
+    }
+    if let Err(e) = GroupFilter::parse(&body.filter) {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": format!("invalid filter: {}", e)})));
+    }
+    let tenant = tenant_of(caller.as_deref(), &q);
+    let conn = match db::open_or_init() {
+        Ok(c) => c,
+        Err(e) => return db_error(e),
+    };
+    let existing = db::get_dynamic_group(&conn, &name, tenant.as_deref()).and_then(|g| Ok((g, db::tagged_norad_ids(&conn, &name, tenant.as_deref())?)));
+    match existing {
+        Ok((None, tagged)) if !tagged.is_empty() => {
+            return (StatusCode::CONFLICT, Json(serde_json::json!({"error": format!("tag {:?} is in use and not a dynamic group", name)})));
+        }
+        Ok(_) => {}
+        Err(e) => return db_error(e),
+    }
+    let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let catalog = state.catalog();
+    let group = db::DynamicGroup {
+        name: name.clone(),
+        tenant,
+        filter: body.filter.trim().to_string(),
+        created_at: now.clone(),
+        updated_at: now.clone(),
+    };
+    let stored = (|| -> Result<_, DbError> {
+        let created = db::upsert_dynamic_group(&conn, &group.name, group.tenant.as_deref(), &group.filter, &now)?;
+        update_group(&conn, &group, &catalog, &db::satcat_map(&conn)?, &now)?;
+        Ok((created, group_detail(&conn, &name, group.tenant.as_deref())?))
+    })();
+    match stored {
+        Ok((created, Some(group))) => {
+            let status = if created { StatusCode::CREATED } else { StatusCode::OK };
+            (status, Json(serde_json::json!(group)))
+        }
+        Ok((_, None)) => (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "group not found"}))),
+        Err(e) => db_error(e),
+    }
+}
+
+/// Deletes a dynamic group of the caller's tenant and untags its members.
+pub async fn delete_group(Path(name): Path<String>, Query(q): Query<GroupQuery>, caller: Option<Extension<Caller>>) -> impl IntoResponse {
+    let tenant = tenant_of(caller.as_deref(), &q);
+    match db::open_or_init().and_then(|c| db::delete_dynamic_group(&c, &name, tenant.as_deref())) {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "dynamic group not found"}))).into_response(),
+        Err(e) => db_error(e).into_response(),
+    }
+}
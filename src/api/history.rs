@@ -0,0 +1,297 @@
+use std::fmt::Write as _;
+
+use axum::{extract::{Path, Query}, response::{IntoResponse, Response}, Extension, Json};
+use axum::http::{header, StatusCode};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::api::access::{self, Caller};
+use crate::api::types::{AuditEntryDto, FetchLogDto, TelemetryDto, PageDto, PositionHistoryDto, PositionSampleDto, SnapshotAnomalyDto, SnapshotDto, TleHistoryDto};
+use crate::core::tle::to_omm;
+
+const DEFAULT_PAGE_SIZE: usize = 100;
+const MAX_PAGE_SIZE: usize = 1000;
+const DEFAULT_MAX_POINTS: usize = 2000;
+const MAX_POINTS: usize = 20_000;
+
+#[derive(Debug, Deserialize)]
+pub struct PageQuery {
+    #[serde(default)]
+    cursor: Option<String>,
+    #[serde(default)]
+    limit: Option<usize>,
+    #[serde(default)]
+    norad_id: Option<u64>,
+}
+
+/// Opaque page cursor wrapping the last row ID returned.
+fn encode_cursor(id: i64) -> String {
+    URL_SAFE_NO_PAD.encode(format!("v1:{}", id))
+}
+
+fn decode_cursor(cursor: &str) -> Option<i64> {
+    let raw = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+    std::str::from_utf8(&raw).ok()?.strip_prefix("v1:")?.parse().ok()
+}
+
+/// Validates the query, runs `fetch(norad_id, after_id, limit + 1)` and builds the page.
+fn paginate<R, T: Serialize>(
+    q: PageQuery,
+    fetch: impl FnOnce(&rusqlite::Connection, Option<u64>, i64, usize) -> Result<Vec<R>, crate::utils::db::DbError>,
+    id_of: impl Fn(&R) -> i64,
+    to_dto: impl Fn(R) -> T,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let after_id = match q.cursor.as_deref() {
+        None => 0,
+        Some(c) => match decode_cursor(c) {
+            Some(id) => id,
+            None => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": "invalid cursor"}))),
+        },
+    };
+    let limit = q.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let mut rows = match crate::utils::db::open_or_init().and_then(|c| fetch(&c, q.norad_id, after_id, limit + 1)) {
+        Ok(rows) => rows,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    };
+    let has_more = rows.len() > limit;
+    rows.truncate(limit);
+    let last_id = rows.last().map(&id_of).unwrap_or(after_id);
+    let page = PageDto {
+        next_cursor: (last_id > 0).then(|| encode_cursor(last_id)),
+        has_more,
+        items: rows.into_iter().map(to_dto).collect::<Vec<T>>(),
+    };
+    (StatusCode::OK, Json(serde_json::json!(page)))
+}
+
+pub async fn list_snapshots(Query(q): Query<PageQuery>) -> impl IntoResponse {
+    paginate(q, crate::utils::db::list_snapshots_page, |r| r.id, |r| SnapshotDto {
+        norad_id: r.norad_id,
+        timestamp: r.timestamp,
+        position_km: r.position,
+        velocity_km_s: r.velocity,
+    })
+}
+
+/// Snapshots the background analyzer flagged, oldest first.
+pub async fn list_snapshot_anomalies(Query(q): Query<PageQuery>) -> impl IntoResponse {
+    paginate(q, crate::utils::db::list_snapshot_anomalies_page, |r| r.id, |r| SnapshotAnomalyDto {
+        norad_id: r.norad_id,
+        timestamp: r.timestamp,
+        kind: r.kind,
+        magnitude_km: r.magnitude_km,
+        detail: r.detail,
+        detected_at: r.detected_at,
+    })
+}
+
+pub async fn list_tle_history(Query(q): Query<PageQuery>) -> impl IntoResponse {
+    paginate(q, crate::utils::db::list_tle_history_page, |r| r.id, |r| TleHistoryDto {
+        norad_id: r.norad_id,
+        epoch: r.epoch,
+        name: r.name,
+        line1: r.line1,
+        line2: r.line2,
+        fetched_at: r.fetched_at,
+    })
+}
+
+pub async fn list_fetch_log(Query(q): Query<PageQuery>) -> impl IntoResponse {
+    paginate(
+        q,
+        |c, _, after_id, limit| crate::utils::db::list_fetch_log_page(c, after_id, limit),
+        |r| r.id,
+        |r| FetchLogDto { fetched_at: r.fetched_at, source: r.source, records: r.records, rejected: r.rejected, error: r.error },
+    )
+}
+
+pub async fn list_audit_log(Query(q): Query<PageQuery>) -> impl IntoResponse {
+    let json = |s: Option<String>| s.and_then(|s| serde_json::from_str(&s).ok());
+    paginate(
+        q,
+        |c, _, after_id, limit| crate::utils::db::list_audit_log_page(c, after_id, limit),
+        |r| r.id,
+        |r| AuditEntryDto { at: r.at, actor: r.actor, method: r.method, path: r.path, status: r.status, payload: json(r.payload), diff: json(r.diff) },
+    )
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TleHistoryRangeQuery {
+    /// Earliest element epoch (default: the first archived).
+    #[serde(default)]
+    from: Option<DateTime<Utc>>,
+    /// Latest element epoch (default: the newest archived).
+    #[serde(default)]
+    to: Option<DateTime<Utc>>,
+    #[serde(default)]
+    format: ElementFormat,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ElementFormat {
+    /// Three-line TLE text (two lines when no name was archived).
+    #[default]
+    Tle,
+    /// A JSON array of CCSDS OMM objects in Celestrak's layout.
+    Omm,
+}
+
+/// Every archived element set of one satellite with an epoch in `[from, to]`,
+/// oldest first, for orbit determination tools that work from the archive.
+pub async fn get_tle_history_range(Path(norad_id): Path<u64>, Query(q): Query<TleHistoryRangeQuery>) -> Response {
+    if q.from.zip(q.to).is_some_and(|(from, to)| from > to) {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": "from must not be after to"}))).into_response();
+    }
+    let bound = |t: Option<DateTime<Utc>>| t.map(|t| t.to_rfc3339_opts(SecondsFormat::Micros, true));
+    let (from, to) = (bound(q.from), bound(q.to));
+    let rows = match crate::utils::db::open_or_init().and_then(|c| crate::utils::db::list_tle_history_range(&c, norad_id, from.as_deref(), to.as_deref())) {
+        Ok(rows) => rows,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))).into_response(),
+    };
+    match q.format {
+        ElementFormat::Tle => {
+            let mut text = String::new();
+            for r in &rows {
+                if let Some(name) = &r.name {
+                    let _ = writeln!(text, "{}", name);
+                }
+                let _ = writeln!(text, "{}\n{}", r.line1, r.line2);
+            }
+            (StatusCode::OK, [(header::CONTENT_TYPE, "text/plain; charset=utf-8")], text).into_response()
+        }
+        ElementFormat::Omm => {
+            let omm: Vec<serde_json::Value> = rows
+                .iter()
+                .filter_map(|r| sgp4::Elements::from_tle(r.name.clone(), r.line1.as_bytes(), r.line2.as_bytes()).ok())
+                .map(|el| to_omm(&el))
+                .collect();
+            (StatusCode::OK, Json(serde_json::Value::Array(omm))).into_response()
+        }
+    }
+}
+
+/// Telemetry reported by a station's tracking client, oldest first; `norad_id`
+/// narrows it to one satellite.
+pub async fn list_station_telemetry(caller: Option<Extension<Caller>>, Path(id): Path<i64>, Query(q): Query<PageQuery>) -> impl IntoResponse {
+    if !access::station_visible(caller.as_deref(), id) {
+        return access::station_not_found();
+    }
+    paginate(
+        q,
+        |c, norad_id, after_id, limit| crate::utils::db::list_telemetry_page(c, id, norad_id, after_id, limit),
+        |r| r.id,
+        |r| TelemetryDto {
+            time: DateTime::parse_from_rfc3339(&r.time).map(|t| t.with_timezone(&Utc)).unwrap_or_default(),
+            norad_id: r.norad_id,
+            az_deg: r.az_deg,
+            el_deg: r.el_deg,
+            signal_dbm: r.signal_dbm,
+            received_at: Some(r.received_at),
+        },
+    )
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PositionHistoryQuery {
+    /// Start of the range (default: 24 h before `end`).
+    #[serde(default)]
+    start: Option<DateTime<Utc>>,
+    /// End of the range (default: now).
+    #[serde(default)]
+    end: Option<DateTime<Utc>>,
+    #[serde(default)]
+    resolution: HistoryResolution,
+    #[serde(default)]
+    max_points: Option<usize>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum HistoryResolution {
+    /// Finest resolution that fits in `max_points`.
+    #[default]
+    #[serde(rename = "auto")]
+    Auto,
+    #[serde(rename = "raw")]
+    Raw,
+    #[serde(rename = "1m")]
+    Minute,
+    #[serde(rename = "1h")]
+    Hour,
+}
+
+/// Rollup width in seconds (`None` for raw snapshots) used for `auto`, given the
+/// raw snapshot count and an upper bound on the 1-minute bucket count.
+fn pick_resolution(raw_count: u64, minute_buckets: u64, max_points: usize) -> Option<i64> {
+    if raw_count <= max_points as u64 {
+        None
+    } else if minute_buckets <= max_points as u64 {
+        Some(60)
+    } else {
+        Some(3600)
+    }
+}
+
+/// Stored position history of one satellite, served from the raw snapshots or
+/// from the 1-minute/1-hour rollups so long ranges never scan every snapshot.
+pub async fn get_position_history(Path(norad_id): Path<u64>, Query(q): Query<PositionHistoryQuery>) -> impl IntoResponse {
+    let end = q.end.unwrap_or_else(Utc::now);
+    let start = q.start.unwrap_or(end - Duration::hours(24));
+    if start > end {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": "start must not be after end"})));
+    }
+    let max_points = q.max_points.unwrap_or(DEFAULT_MAX_POINTS).clamp(1, MAX_POINTS);
+    let (start_s, end_s) = (start.timestamp(), end.timestamp());
+    let result = crate::utils::db::open_or_init().and_then(|conn| {
+        let resolution = match q.resolution {
+            HistoryResolution::Auto => {
+                let (raw, minutes) = crate::utils::db::history_density(&conn, norad_id, start_s, end_s)?;
+                pick_resolution(raw, minutes, max_points)
+            }
+            HistoryResolution::Raw => None,
+            HistoryResolution::Minute => Some(60),
+            HistoryResolution::Hour => Some(3600),
+        };
+        let points = crate::utils::db::list_position_history(&conn, norad_id, start_s, end_s, resolution, max_points + 1)?;
+        Ok((resolution, points))
+    });
+    let (resolution, mut points) = match result {
+        Ok(r) => r,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    };
+    let truncated = points.len() > max_points;
+    points.truncate(max_points);
+    let dto = PositionHistoryDto {
+        norad_id,
+        resolution: match resolution {
+            None => "raw",
+            Some(60) => "1m",
+            Some(_) => "1h",
+        },
+        points: points
+            .into_iter()
+            .map(|p| PositionSampleDto { timestamp: p.timestamp, samples: p.samples, position_km: p.position, velocity_km_s: p.velocity })
+            .collect(),
+        truncated,
+    };
+    (StatusCode::OK, Json(serde_json::json!(dto)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_round_trips_and_rejects_garbage() {
+        assert_eq!(decode_cursor(&encode_cursor(12345)), Some(12345));
+        assert_eq!(decode_cursor("not-a-cursor"), None);
+    }
+
+    #[test]
+    fn auto_resolution_is_finest_that_fits() {
+        assert_eq!(pick_resolution(1_500, 1_500, 2_000), None);
+        assert_eq!(pick_resolution(86_400, 1_440, 2_000), Some(60));
+        assert_eq!(pick_resolution(31_536_000, 525_600, 2_000), Some(3600));
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use axum::{extract::Path, response::IntoResponse, Extension, Json};
+use axum::http::StatusCode;
+use chrono::SecondsFormat;
+
+use crate::api::access::{self, Caller};
+use crate::api::types::{AzimuthSectorDto, StationHorizonDto};
+use crate::core::terrain::{station_horizon, HorizonMask};
+use crate::predictors::passes::AzimuthSector;
+
+/// Masks by station and station version, or by coordinates for observers
+/// that are not stored stations. `None` is cached too: a flat horizon.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum MaskKey {
+    Station(i64, i64),
+    Point(u64, u64),
+}
+
+/// Most masks kept; the cache is emptied when it fills.
+const MAX_CACHED_MASKS: usize = 1024;
+
+#[derive(Default)]
+struct MaskCache(Mutex<HashMap<MaskKey, Option<Arc<HorizonMask>>>>);
+
+impl MaskCache {
+    /// The cached mask for `key`, else the one `compute` returns. The lock is
+    /// not held while computing; two misses for one key both compute.
+    fn get_or_compute(&self, key: MaskKey, compute: impl FnOnce() -> Option<HorizonMask>) -> Option<Arc<HorizonMask>> {
+        if let Some(mask) = self.0.lock().unwrap_or_else(|e| e.into_inner()).get(&key) {
+            return mask.clone();
+        }
+        let mask = compute().map(Arc::new);
+        let mut masks = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        if masks.len() >= MAX_CACHED_MASKS {
+            masks.clear();
+        }
+        masks.insert(key, mask.clone());
+        mask
+    }
+}
+
+static MASKS: OnceLock<MaskCache> = OnceLock::new();
+
+/// Stored horizon profile of a station, falling back to one computed from the
+/// DEM. `None` when neither is available (flat horizon). Cached per station
+/// until the station is edited, so reads the database and DEM tiles only on a
+/// miss; async callers use [`load_horizon`].
+pub fn horizon_for(station_id: Option<i64>, lat: f64, lon: f64) -> Option<Arc<HorizonMask>> {
+    let version = station_id.and_then(|id| {
+        let version = crate::utils::db::open_or_init().and_then(|c| crate::utils::db::station_version(&c, id));
+        version.ok().flatten().map(|v| (id, v))
+    });
+    let key = match version {
+        Some((id, version)) => MaskKey::Station(id, version),
+        None => MaskKey::Point(lat.to_bits(), lon.to_bits()),
+    };
+    MASKS.get_or_init(Default::default).get_or_compute(key, || compute_mask(version.map(|(id, _)| id), lat, lon))
+}
+
+/// [`horizon_for`] on the blocking pool.
+pub async fn load_horizon(station_id: Option<i64>, lat: f64, lon: f64) -> Option<Arc<HorizonMask>> {
+    tokio::task::spawn_blocking(move || horizon_for(station_id, lat, lon)).await.ok().flatten()
+}
+
+fn compute_mask(station_id: Option<i64>, lat: f64, lon: f64) -> Option<HorizonMask> {
+    if let Some(id) = station_id {
+        let stored = crate::utils::db::open_or_init()
+            .and_then(|c| crate::utils::db::get_station_horizon(&c, id))
+            .ok()
+            .flatten();
+        if let Some(mask) = stored.and_then(|h| HorizonMask::from_bins(h.profile)) {
+            return Some(mask);
+        }
+    }
+    station_horizon(lat, lon)
+}
+
+fn to_dto(station_id: i64, profile: Vec<f64>, computed_at: String) -> StationHorizonDto {
+    StationHorizonDto {
+        station_id,
+        bin_width_deg: 360.0 / profile.len().max(1) as f64,
+        elevations_deg: profile,
+        computed_at,
+    }
+}
+
+pub async fn get_horizon(caller: Option<Extension<Caller>>, Path(id): Path<i64>) -> impl IntoResponse {
+    if !access::station_visible(caller.as_deref(), id) {
+        return access::station_not_found();
+    }
+    match crate::utils::db::open_or_init().and_then(|c| crate::utils::db::get_station_horizon(&c, id)) {
+        Ok(Some(h)) => (StatusCode::OK, Json(serde_json::json!(to_dto(h.station_id, h.profile, h.computed_at)))),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "no horizon profile for station"}))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+/// Computes the station's horizon profile from the DEM tiles and stores it so
+/// pass predictions for the station reuse it.
+pub async fn compute_horizon(caller: Option<Extension<Caller>>, Path(id): Path<i64>) -> impl IntoResponse {
+    if !access::station_visible(caller.as_deref(), id) {
+        return access::station_not_found();
+    }
+    let conn = match crate::utils::db::open_or_init() {
+        Ok(c) => c,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    };
+    let station = match crate::utils::db::get_station(&conn, id) {
+        Ok(s) => s,
+        Err(_) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "station not found"}))),
+    };
+    let (lat, lon) = (station.lat, station.lon);
+    let mask = match tokio::task::spawn_blocking(move || station_horizon(lat, lon)).await {
+        Ok(mask) => mask,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("horizon task failed: {}", e)}))),
+    };
+    let Some(mask) = mask else {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": "no DEM tile covers the station"})));
+    };
+    let computed_at = chrono::Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    match crate::utils::db::upsert_station_horizon(&conn, id, mask.bins(), &computed_at) {
+        Ok(()) => (StatusCode::OK, Json(serde_json::json!(to_dto(id, mask.bins().to_vec(), computed_at)))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+/// Excluded azimuth sectors of a station; empty when none are stored.
+pub fn exclusions_for(station_id: i64) -> Vec<AzimuthSector> {
+    crate::utils::db::open_or_init()
+        .and_then(|c| crate::utils::db::list_station_exclusions(&c, station_id))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(start_deg, end_deg)| AzimuthSector { start_deg, end_deg })
+        .collect()
+}
+
+pub async fn get_exclusions(caller: Option<Extension<Caller>>, Path(id): Path<i64>) -> impl IntoResponse {
+    if !access::station_visible(caller.as_deref(), id) {
+        return access::station_not_found();
+    }
+    match crate::utils::db::open_or_init().and_then(|c| crate::utils::db::list_station_exclusions(&c, id)) {
+        Ok(rows) => {
+            let out: Vec<AzimuthSectorDto> = rows
+                .into_iter()
+                .map(|(start_deg, end_deg)| AzimuthSectorDto { start_deg, end_deg })
+                .collect();
+            (StatusCode::OK, Json(serde_json::json!(out)))
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+/// Replaces the station's excluded azimuth sectors (clockwise from `start_deg`
+/// to `end_deg`, wrapping through north allowed).
+pub async fn set_exclusions(caller: Option<Extension<Caller>>, Path(id): Path<i64>, Json(body): Json<Vec<AzimuthSectorDto>>) -> impl IntoResponse {
+    if !access::station_visible(caller.as_deref(), id) {
+        return access::station_not_found();
+    }
+    if body.iter().any(|s| !(0.0..=360.0).contains(&s.start_deg) || !(0.0..=360.0).contains(&s.end_deg)) {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": "azimuths must be within 0..360"})));
+    }
+    let conn = match crate::utils::db::open_or_init() {
+        Ok(c) => c,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    };
+    if crate::utils::db::get_station(&conn, id).is_err() {
+        return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "station not found"})));
+    }
+    let sectors: Vec<(f64, f64)> = body.iter().map(|s| (s.start_deg, s.end_deg)).collect();
+    match crate::utils::db::set_station_exclusions(&conn, id, &sectors) {
+        Ok(()) => (StatusCode::OK, Json(serde_json::json!(body))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks_are_cached_until_the_station_version_changes() {
+        let cache = MaskCache::default();
+        let computed = std::cell::Cell::new(0);
+        let compute = |elevation: f64| {
+            computed.set(computed.get() + 1);
+            HorizonMask::from_bins(vec![elevation; 36])
+        };
+        let first = cache.get_or_compute(MaskKey::Station(7, 0), || compute(2.0));
+        let again = cache.get_or_compute(MaskKey::Station(7, 0), || compute(5.0));
+        assert!(Arc::ptr_eq(first.as_ref().unwrap(), again.as_ref().unwrap()));
+        assert_eq!(computed.get(), 1);
+
+        // An edit bumps the version, so the new profile is read
+        let edited = cache.get_or_compute(MaskKey::Station(7, 1), || compute(5.0)).unwrap();
+        assert_eq!(edited.elevation_at(90.0), 5.0);
+        assert_eq!(computed.get(), 2);
+
+        // A flat horizon is remembered as well
+        assert!(cache.get_or_compute(MaskKey::Point(1, 2), || None).is_none());
+        assert!(cache.get_or_compute(MaskKey::Point(1, 2), || compute(9.0)).is_none());
+        assert_eq!(computed.get(), 2);
+    }
+}
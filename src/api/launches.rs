@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+use axum::{extract::{Path, Query, State}, response::IntoResponse, Json};
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::analyzers::launches::{group_by_launch, parse_launch, Piece};
+use crate::api::server::AppState;
+use crate::api::types::{LaunchDetailDto, LaunchPieceDto, LaunchSummaryDto};
+use crate::collectors::satcat::SatcatEntry;
+use crate::core::coords::{ecef_to_geodetic_height, eci_to_ecef, gmst};
+use crate::core::orbit::{dot, minutes_since_epoch, perigee_apogee_radius_km, propagate_minutes, ric_components, EARTH_RADIUS_KM};
+use crate::predictors::revolution::node_fraction;
+
+#[derive(Debug, Deserialize)]
+pub struct LaunchesQuery {
+    /// Only launches of this year.
+    #[serde(default)]
+    year: Option<u32>,
+    #[serde(default = "default_min_pieces")]
+    min_pieces: usize,
+    #[serde(default = "default_limit")]
+    limit: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LaunchQuery {
+    /// Piece the others are measured from (default: the first catalogued one).
+    #[serde(default)]
+    reference: Option<u64>,
+    #[serde(default)]
+    time: Option<DateTime<Utc>>,
+}
+
+fn default_min_pieces() -> usize { 1 }
+fn default_limit() -> usize { 100 }
+const MAX_LIMIT: usize = 1000;
+
+type Rejection = (StatusCode, Json<serde_json::Value>);
+
+fn satcat() -> Result<HashMap<u64, SatcatEntry>, Rejection> {
+    crate::utils::db::open_or_init()
+        .and_then(|c| crate::utils::db::satcat_map(&c))
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))))
+}
+
+/// Launches with objects in the loaded catalog, newest first, grouped by the
+/// launch part of the international designator.
+pub async fn list_launches(Query(q): Query<LaunchesQuery>, State(state): State<AppState>) -> impl IntoResponse {
+    let satcat = match satcat() {
+        Ok(s) => s,
+        Err(response) => return response,
+    };
+    let launches: Vec<LaunchSummaryDto> = group_by_launch(&state.catalog(), &satcat)
+        .into_iter()
+        .rev()
+        .filter(|(launch, _)| q.year.is_none_or(|y| launch.starts_with(&format!("{}-", y))))
+        .filter(|(_, pieces)| pieces.len() >= q.min_pieces)
+        .take(q.limit.min(MAX_LIMIT))
+        .map(|(launch, pieces)| LaunchSummaryDto {
+            launch_date: pieces.iter().find_map(|p| satcat.get(&p.elements.norad_id)?.launch_date.clone()),
+            pieces: pieces.len(),
+            norad_ids: pieces.iter().map(|p| p.elements.norad_id).collect(),
+            launch,
+        })
+        .collect();
+    (StatusCode::OK, Json(serde_json::json!(launches)))
+}
+
+/// Every loaded piece of one launch (`2024-043` or `24043`) and how far each has
+/// drifted from a reference piece: phase along the orbit, altitude and plane
+/// offsets, and the period difference that drives the along-track drift.
+pub async fn get_launch(Path(launch): Path<String>, Query(q): Query<LaunchQuery>, State(state): State<AppState>) -> impl IntoResponse {
+    let Some(launch) = parse_launch(&launch) else {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": "launch must look like 2024-043 or 24043"})));
+    };
+    let satcat = match satcat() {
+        Ok(s) => s,
+        Err(response) => return response,
+    };
+    let catalog = state.catalog();
+    let Some(pieces) = group_by_launch(&catalog, &satcat).remove(&launch) else {
+        return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "no loaded objects from this launch"})));
+    };
+    let reference = match q.reference {
+        Some(id) => match pieces.iter().find(|p| p.elements.norad_id == id) {
+            Some(p) => p.elements,
+            None => return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": "reference is not a piece of this launch"}))),
+        },
+        None => pieces[0].elements,
+    };
+    let t = q.time.unwrap_or_else(|| state.clock.now());
+    let reference_state = match propagate_minutes(reference, minutes_since_epoch(reference, t)) {
+        Ok(s) => s,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": format!("prediction error: {}", e)}))),
+    };
+    let reference_period_s = 86_400.0 / reference.mean_motion;
+    let reference_fraction = node_fraction(&reference_state.position, &reference_state.velocity);
+    let theta = gmst(t);
+
+    let mut out = Vec::with_capacity(pieces.len());
+    for Piece { designator, elements: el, name } in &pieces {
+        let (rp, ra) = perigee_apogee_radius_km(el);
+        let period_s = 86_400.0 / el.mean_motion;
+        let mut dto = LaunchPieceDto {
+            norad_id: el.norad_id,
+            name: name.map(str::to_string),
+            piece: designator.piece.clone(),
+            object_type: satcat.get(&el.norad_id).map(|s| s.object_type.clone()),
+            element_epoch: el.datetime.and_utc(),
+            perigee_alt_km: rp - EARTH_RADIUS_KM,
+            apogee_alt_km: ra - EARTH_RADIUS_KM,
+            period_s,
+            period_delta_s: period_s - reference_period_s,
+            lat: None,
+            lon: None,
+            alt_km: None,
+            phase_deg: None,
+            along_track_s: None,
+            radial_km: None,
+            cross_track_km: None,
+        };
+        // A piece that no longer propagates (decayed) is listed without a position
+        if let Ok(state) = propagate_minutes(el, minutes_since_epoch(el, t)) {
+            let (x, y, z) = eci_to_ecef(&state.position, theta);
+            let (lat, lon, alt) = ecef_to_geodetic_height(x, y, z);
+            let phase = ((node_fraction(&state.position, &state.velocity) - reference_fraction + 0.5).rem_euclid(1.0) - 0.5) * 360.0;
+            let delta: [f64; 3] = std::array::from_fn(|i| state.position[i] - reference_state.position[i]);
+            let norm = |v: &[f64; 3]| dot(v, v).sqrt();
+            dto.lat = Some(lat);
+            dto.lon = Some(lon);
+            dto.alt_km = Some(alt);
+            dto.phase_deg = Some(phase);
+            dto.along_track_s = Some(phase / 360.0 * reference_period_s);
+            dto.radial_km = Some(norm(&state.position) - norm(&reference_state.position));
+            dto.cross_track_km = Some(ric_components(&reference_state.position, &reference_state.velocity, &delta)[2]);
+        }
+        out.push(dto);
+    }
+    let phases: Vec<f64> = out.iter().filter_map(|p| p.phase_deg).collect();
+    let detail = LaunchDetailDto {
+        launch_date: pieces.iter().find_map(|p| satcat.get(&p.elements.norad_id)?.launch_date.clone()),
+        launch,
+        time: t,
+        reference_norad_id: reference.norad_id,
+        phase_spread_deg: phases.iter().copied().fold(0.0, f64::max) - phases.iter().copied().fold(0.0, f64::min),
+        pieces: out,
+    };
+    (StatusCode::OK, Json(serde_json::json!(detail)))
+}
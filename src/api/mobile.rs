@@ -0,0 +1,146 @@
+use axum::{extract::{Query, State}, response::IntoResponse, Json};
+use axum::http::{header, HeaderMap, StatusCode};
+use serde::Deserialize;
+
+use crate::api::server::AppState;
+use crate::api::types::{MobilePassDto, MobilePassRequestDto, MobileSatellitePassesDto, ObserverLocationDto};
+use crate::collectors::gpx::parse_gpx_track;
+use crate::predictors::passes::{predict_passes_with_options, LookOptions, Observer, ObserverPosition, TrackPoint};
+
+/// Query parameters used when the body is a GPX document; a JSON body carries
+/// the same settings itself.
+#[derive(Debug, Deserialize)]
+pub struct MobilePassQuery {
+    /// Comma-separated NORAD IDs.
+    #[serde(default)]
+    norad_ids: Option<String>,
+    #[serde(default)]
+    step: Option<i64>,
+    #[serde(default)]
+    min_el: Option<f64>,
+    #[serde(default)]
+    refraction: Option<bool>,
+    #[serde(default)]
+    light_time: Option<bool>,
+}
+
+fn is_gpx(headers: &HeaderMap, body: &str) -> bool {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    content_type.contains("xml") || content_type.contains("gpx") || body.trim_start().starts_with('<')
+}
+
+fn location(p: Option<ObserverPosition>) -> Option<ObserverLocationDto> {
+    p.map(|p| ObserverLocationDto { lat: p.lat_deg, lon: p.lon_deg, alt_km: p.alt_km })
+}
+
+/// Pass prediction for a moving observer (ship, vehicle, aircraft) given as a
+/// time-tagged track, either JSON or GPX. Passes are searched over the time span
+/// of the track and report where the observer is at AOS, TCA and LOS.
+pub async fn mobile_passes(
+    State(state): State<AppState>,
+    Query(q): Query<MobilePassQuery>,
+    headers: HeaderMap,
+    body: String,
+) -> impl IntoResponse {
+    let (norad_ids, mut track, step, min_el, refraction, light_time) = if is_gpx(&headers, &body) {
+        let track = match parse_gpx_track(&body) {
+            Ok(t) => t,
+            Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": format!("invalid GPX: {}", e)}))),
+        };
+        let ids: Result<Vec<u64>, _> = q
+            .norad_ids
+            .as_deref()
+            .unwrap_or("")
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::parse)
+            .collect();
+        let Ok(ids) = ids else {
+            return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": "norad_ids must be comma-separated integers"})));
+        };
+        (ids, track, q.step, q.min_el, q.refraction, q.light_time)
+    } else {
+        let req: MobilePassRequestDto = match serde_json::from_str(&body) {
+            Ok(r) => r,
+            Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": format!("invalid JSON: {}", e)}))),
+        };
+        let mut ids = req.norad_ids;
+        ids.extend(req.norad_id);
+        let track: Vec<TrackPoint> = req
+            .track
+            .iter()
+            .map(|p| TrackPoint { time: p.time, position: ObserverPosition { lat_deg: p.lat, lon_deg: p.lon, alt_km: p.alt_km } })
+            .collect();
+        (ids, track, req.step.or(q.step), req.min_el.or(q.min_el), req.refraction.or(q.refraction), req.light_time.or(q.light_time))
+    };
+
+    if norad_ids.is_empty() {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": "at least one norad_id is required"})));
+    }
+    if track.len() < 2 {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": "track needs at least two points"})));
+    }
+    if track
+        .iter()
+        .any(|p| !(-90.0..=90.0).contains(&p.position.lat_deg) || !(-180.0..=180.0).contains(&p.position.lon_deg))
+    {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": "lat/lon out of range"})));
+    }
+
+    track.sort_by_key(|p| p.time);
+    let start = track[0].time;
+    let duration_minutes = (track[track.len() - 1].time - start).num_minutes();
+    let observer = Observer::Track(&track);
+    let step = step.unwrap_or(15);
+    let min_el = min_el.unwrap_or(10.0);
+    let options = LookOptions {
+        refraction: refraction.unwrap_or(false),
+        light_time: light_time.unwrap_or(false),
+        ..Default::default()
+    };
+
+    let out: Vec<MobileSatellitePassesDto> = norad_ids
+        .iter()
+        .map(|&norad_id| {
+            let catalog = state.catalog();
+            let Some(el) = catalog.get(norad_id) else {
+                return MobileSatellitePassesDto {
+                    norad_id,
+                    name: None,
+                    passes: Vec::new(),
+                    error: Some("norad_id not found in loaded TLEs".to_string()),
+                };
+            };
+            match predict_passes_with_options(el, &observer, &options, start, duration_minutes, step, min_el) {
+                Ok(wins) => MobileSatellitePassesDto {
+                    norad_id,
+                    name: catalog.name_of(norad_id).map(str::to_string),
+                    passes: wins
+                        .into_iter()
+                        .map(|w| MobilePassDto {
+                            start: w.start,
+                            end: w.end,
+                            tca: w.tca,
+                            max_elevation_deg: w.max_elevation_deg,
+                            aos_location: location(observer.position_at(w.start)),
+                            tca_location: location(observer.position_at(w.tca)),
+                            los_location: location(observer.position_at(w.end)),
+                        })
+                        .collect(),
+                    error: None,
+                },
+                Err(e) => MobileSatellitePassesDto {
+                    norad_id,
+                    name: catalog.name_of(norad_id).map(str::to_string),
+                    passes: Vec::new(),
+                    error: Some(format!("prediction error: {}", e)),
+                },
+            }
+        })
+        .collect();
+    (StatusCode::OK, Json(serde_json::json!(out)))
+}
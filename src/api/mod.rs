@@ -1,2 +1,41 @@
 pub mod server;
-pub mod types;
\ No newline at end of file
+pub mod types;
+pub mod conjunctions;
+pub mod observations;
+pub mod catalog;
+pub mod satellites;
+pub mod mobile;
+pub mod trackfile;
+pub mod horizon;
+pub mod profile;
+pub mod geo;
+pub mod groundtrack;
+pub mod negotiate;
+pub mod stream;
+pub mod deprecation;
+pub mod history;
+pub mod export;
+pub mod cache;
+pub mod readonly;
+pub mod asof;
+pub mod predict;
+pub mod custom;
+pub mod overrides;
+pub mod access;
+pub mod audit;
+pub mod devices;
+pub mod problem;
+pub mod dashboard;
+pub mod stats;
+pub mod config;
+pub mod passes;
+pub mod aoi;
+pub mod crewed;
+pub mod pass_cache;
+pub mod network;
+pub mod region;
+pub mod accuracy;
+pub mod launches;
+pub mod trains;
+pub mod warmup;
+pub mod groups;
@@ -0,0 +1,170 @@
+use axum::{body::{Body, HttpBody}, extract::Request, middleware::Next, response::{IntoResponse, Response}};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use serde_json::Value;
+
+/// Largest JSON body re-encoded; bigger responses are passed through as JSON.
+const MAX_BODY_BYTES: usize = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Json,
+    Csv,
+    MsgPack,
+}
+
+/// First supported media type in the `Accept` header, honouring q-values.
+fn preferred_format(headers: &HeaderMap) -> Format {
+    let accept = headers.get(header::ACCEPT).and_then(|v| v.to_str().ok()).unwrap_or("");
+    let mut best: Option<(f32, Format)> = None;
+    for item in accept.split(',') {
+        let mut parts = item.split(';').map(str::trim);
+        let format = match parts.next().unwrap_or("") {
+            "application/json" => Format::Json,
+            "text/csv" => Format::Csv,
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => Format::MsgPack,
+            _ => continue,
+        };
+        let q = parts
+            .find_map(|p| p.strip_prefix("q="))
+            .and_then(|q| q.parse::<f32>().ok())
+            .unwrap_or(1.0);
+        if q > 0.0 && best.is_none_or(|(bq, _)| q > bq) {
+            best = Some((q, format));
+        }
+    }
+    best.map(|(_, f)| f).unwrap_or(Format::Json)
+}
+
+fn csv_cell(v: &Value) -> String {
+    match v {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Renders an array of objects (or a single object) as CSV with the union of
+/// top-level keys as columns; nested values are written as JSON.
+fn to_csv(value: &Value) -> Result<Vec<u8>, csv::Error> {
+    let rows: Vec<&Value> = match value {
+        Value::Array(items) => items.iter().collect(),
+        other => vec![other],
+    };
+    let mut columns: Vec<&str> = Vec::new();
+    for row in &rows {
+        if let Value::Object(map) = row {
+            for key in map.keys() {
+                if !columns.contains(&key.as_str()) {
+                    columns.push(key);
+                }
+            }
+        }
+    }
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    if columns.is_empty() {
+        writer.write_record(["value"])?;
+        for row in &rows {
+            writer.write_record([csv_cell(row)])?;
+        }
+    } else {
+        writer.write_record(&columns)?;
+        for row in &rows {
+            writer.write_record(columns.iter().map(|c| row.get(c).map(csv_cell).unwrap_or_default()))?;
+        }
+    }
+    writer.into_inner().map_err(|e| e.into_error().into())
+}
+
+/// Re-encodes JSON responses as CSV or MessagePack when the client asks for them
+/// in `Accept`. Non-JSON responses (exports, static files) are left untouched,
+/// and so are JSON bodies of unknown size or over [`MAX_BODY_BYTES`]. JSON
+/// responses carry `Vary: Accept`, as their encoding depends on it.
+pub async fn negotiate(request: Request, next: Next) -> Response {
+    let format = preferred_format(request.headers());
+    let mut response = next.run(request).await;
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("application/json"));
+    if !is_json {
+        return response;
+    }
+    response.headers_mut().append(header::VARY, HeaderValue::from_static("accept"));
+    if format == Format::Json || response.body().size_hint().exact().is_none_or(|n| n > MAX_BODY_BYTES as u64) {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, MAX_BODY_BYTES).await {
+        Ok(b) => b,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, format!("failed to read response body: {}", e)).into_response(),
+    };
+    let Ok(value) = serde_json::from_slice::<Value>(&bytes) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+    let (encoded, content_type) = match format {
+        Format::Csv => (to_csv(&value).map_err(|e| e.to_string()), "text/csv"),
+        Format::MsgPack => (rmp_serde::to_vec_named(&value).map_err(|e| e.to_string()), "application/msgpack"),
+        Format::Json => unreachable!(),
+    };
+    match encoded {
+        Ok(out) => {
+            parts.headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+            parts.headers.remove(header::CONTENT_LENGTH);
+            Response::from_parts(parts, Body::from(out))
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("failed to encode response: {}", e)).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_highest_quality_supported_type() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, HeaderValue::from_static("text/html, application/json;q=0.5, text/csv;q=0.9"));
+        assert_eq!(preferred_format(&headers), Format::Csv);
+        headers.insert(header::ACCEPT, HeaderValue::from_static("*/*"));
+        assert_eq!(preferred_format(&headers), Format::Json);
+    }
+
+    #[test]
+    fn csv_uses_union_of_keys() {
+        let v = serde_json::json!([{"a": 1, "b": "x"}, {"a": 2, "c": [1, 2]}]);
+        let out = String::from_utf8(to_csv(&v).unwrap()).unwrap();
+        assert_eq!(out, "a,b,c\n1,x,\n2,,\"[1,2]\"\n");
+    }
+
+    #[tokio::test]
+    async fn converts_json_and_passes_oversize_bodies_through() {
+        use tower::ServiceExt;
+
+        async fn small() -> axum::Json<Value> {
+            axum::Json(serde_json::json!([{"a": 1}]))
+        }
+        async fn large() -> Response {
+            let body = format!("[\"{}\"]", "x".repeat(MAX_BODY_BYTES));
+            ([(header::CONTENT_TYPE, "application/json")], body).into_response()
+        }
+        let app = axum::Router::new()
+            .route("/small", axum::routing::get(small))
+            .route("/large", axum::routing::get(large))
+            .layer(axum::middleware::from_fn(negotiate));
+        let get = |uri: &str| Request::builder().uri(uri).header(header::ACCEPT, "text/csv").body(Body::empty()).unwrap();
+
+        let response = app.clone().oneshot(get("/small")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "text/csv");
+        assert_eq!(response.headers()[header::VARY], "accept");
+        assert_eq!(axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap(), "a\n1\n");
+
+        let response = app.oneshot(get("/large")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+        assert_eq!(response.headers()[header::VARY], "accept");
+        assert_eq!(axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap().len(), MAX_BODY_BYTES + 4);
+    }
+}
@@ -0,0 +1,237 @@
+use axum::{extract::{Query, State}, response::IntoResponse, Extension, Json};
+use axum::http::StatusCode;
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+
+use crate::api::access::Caller;
+use crate::api::passes::{self, PassError, Prediction, PredictionParams, ResolvedObserver};
+use crate::api::server::AppState;
+use crate::api::types::{CommonVisibilityDto, CommonWindowDto, HandoverDto, HandoverPlanDto};
+use crate::core::coords::gmst;
+use crate::core::orbit::{minutes_since_epoch, propagate_minutes};
+use crate::predictors::network::{common_windows, plan_handover, Interval};
+use crate::predictors::passes::{topocentric_look_deg, ExclusionMode};
+
+/// Most stations in one network request.
+const MAX_STATIONS: usize = 16;
+/// Longest search window (one week).
+const MAX_DURATION_MIN: i64 = 10_080;
+
+#[derive(Debug, Deserialize)]
+pub struct CommonQuery {
+    norad_id: u64,
+    /// Comma-separated station IDs.
+    station_ids: String,
+    #[serde(default = "default_min_stations")]
+    min_stations: usize,
+    #[serde(default)]
+    start: Option<DateTime<Utc>>,
+    #[serde(default = "default_duration")]
+    duration: i64,
+    #[serde(default = "default_step")]
+    step: i64,
+    #[serde(default = "default_min_el")]
+    min_el: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HandoverQuery {
+    norad_id: u64,
+    /// Comma-separated station IDs.
+    station_ids: String,
+    #[serde(default)]
+    start: Option<DateTime<Utc>>,
+    #[serde(default = "default_duration")]
+    duration: i64,
+    #[serde(default = "default_step")]
+    step: i64,
+    #[serde(default = "default_min_el")]
+    min_el: f64,
+    /// Degree-minutes of elevation a station has to gain to take over.
+    #[serde(default = "default_switch_penalty")]
+    switch_penalty: f64,
+}
+
+fn default_min_stations() -> usize { 2 }
+fn default_switch_penalty() -> f64 { 60.0 }
+/// Most elevation samples in one handover plan.
+const MAX_SAMPLES: i64 = 50_000;
+fn default_duration() -> i64 { 1440 }
+fn default_step() -> i64 { 15 }
+fn default_min_el() -> f64 { 10.0 }
+
+fn prediction_params(start: DateTime<Utc>, duration_min: i64, step_s: i64, min_el: f64) -> Result<PredictionParams, PassError> {
+    if duration_min > MAX_DURATION_MIN {
+        return Err(error(StatusCode::UNPROCESSABLE_ENTITY, format!("duration must be at most {} minutes", MAX_DURATION_MIN)));
+    }
+    Ok(PredictionParams {
+        start,
+        duration_min,
+        step_s,
+        min_el,
+        terrain: true,
+        refraction: false,
+        light_time: false,
+        exclusion_mode: ExclusionMode::default(),
+        merge_gap_s: 0,
+        min_peak_el: None,
+    })
+}
+
+fn error(status: StatusCode, msg: impl Into<String>) -> PassError {
+    (status, Json(serde_json::json!({"error": msg.into()})))
+}
+
+/// Parses `station_ids` and resolves each one; at least two and at most
+/// [`MAX_STATIONS`] distinct stations.
+fn resolve_stations(ids: &str, caller: Option<&Caller>) -> Result<Vec<ResolvedObserver>, PassError> {
+    let mut parsed = Vec::new();
+    for id in ids.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        let id: i64 = id.parse().map_err(|_| error(StatusCode::UNPROCESSABLE_ENTITY, format!("invalid station id {}", id)))?;
+        if !parsed.contains(&id) {
+            parsed.push(id);
+        }
+    }
+    if !(2..=MAX_STATIONS).contains(&parsed.len()) {
+        return Err(error(StatusCode::UNPROCESSABLE_ENTITY, format!("station_ids must name 2 to {} stations", MAX_STATIONS)));
+    }
+    parsed.into_iter().map(|id| passes::resolve_observer(Some(id), None, None, None, caller)).collect()
+}
+
+/// Visibility intervals of `el` over each station, with the stored horizons
+/// and excluded sectors. A GEO object above a station's mask is visible for the
+/// whole window.
+pub async fn station_intervals(
+    state: &AppState,
+    el: &sgp4::Elements,
+    stations: &[ResolvedObserver],
+    params: &PredictionParams,
+) -> Result<Vec<Vec<Interval>>, PassError> {
+    let end = params.start + Duration::minutes(params.duration_min);
+    let mut intervals = Vec::with_capacity(stations.len());
+    for observer in stations {
+        let horizon = passes::observer_horizon(observer, params.terrain).await;
+        intervals.push(match passes::run_prediction(el, None, observer, horizon.as_deref(), params, Some(&state.pass_cache))? {
+            Prediction::Passes(windows) => windows.into_iter().map(|w| (w.start, w.end)).collect(),
+            Prediction::Geo(look) if look.visible => vec![(params.start, end)],
+            Prediction::Geo(_) => Vec::new(),
+        });
+    }
+    Ok(intervals)
+}
+
+/// When `norad_id` is above the horizon of at least `min_stations` of the given
+/// stations at once, for bistatic observation, handovers or interferometry.
+pub async fn get_common_visibility(Query(q): Query<CommonQuery>, caller: Option<Extension<Caller>>, State(state): State<AppState>) -> impl IntoResponse {
+    let catalog = state.catalog();
+    let Some(el) = catalog.get(q.norad_id) else {
+        return error(StatusCode::NOT_FOUND, "norad_id not found in loaded TLEs");
+    };
+    let stations = match resolve_stations(&q.station_ids, caller.as_deref()) {
+        Ok(s) => s,
+        Err(response) => return response,
+    };
+    if !(2..=stations.len()).contains(&q.min_stations) {
+        return error(StatusCode::UNPROCESSABLE_ENTITY, format!("min_stations must be within [2, {}]", stations.len()));
+    }
+    let params = match prediction_params(q.start.unwrap_or_else(|| state.clock.now()), q.duration, q.step, q.min_el) {
+        Ok(p) => p,
+        Err(response) => return response,
+    };
+    let intervals = match station_intervals(&state, el, &stations, &params).await {
+        Ok(i) => i,
+        Err(response) => return response,
+    };
+    let station_id = |i: usize| stations[i].station_id.unwrap_or_default();
+    let out = CommonVisibilityDto {
+        norad_id: q.norad_id,
+        start: params.start,
+        end: params.start + Duration::minutes(params.duration_min),
+        min_stations: q.min_stations,
+        windows: common_windows(&intervals, q.min_stations)
+            .into_iter()
+            .map(|w| CommonWindowDto {
+                start: w.start,
+                end: w.end,
+                duration_s: (w.end - w.start).num_seconds(),
+                station_ids: w.stations.into_iter().map(station_id).collect(),
+            })
+            .collect(),
+    };
+    (StatusCode::OK, Json(serde_json::json!(out)))
+}
+
+/// Which of the given stations should track `norad_id` when: one station at a
+/// time, favouring high elevation and few switches. Visibility follows each
+/// station's passes (horizon, excluded sectors, `min_el`); elevations are
+/// sampled every `step` seconds.
+pub async fn get_handover(Query(q): Query<HandoverQuery>, caller: Option<Extension<Caller>>, State(state): State<AppState>) -> impl IntoResponse {
+    let catalog = state.catalog();
+    let Some(el) = catalog.get(q.norad_id) else {
+        return error(StatusCode::NOT_FOUND, "norad_id not found in loaded TLEs");
+    };
+    let stations = match resolve_stations(&q.station_ids, caller.as_deref()) {
+        Ok(s) => s,
+        Err(response) => return response,
+    };
+    let params = match prediction_params(q.start.unwrap_or_else(|| state.clock.now()), q.duration, q.step, q.min_el) {
+        Ok(p) => p,
+        Err(response) => return response,
+    };
+    if q.step > 0 && q.duration * 60 / q.step > MAX_SAMPLES {
+        return error(StatusCode::UNPROCESSABLE_ENTITY, format!("duration / step gives more than {} samples", MAX_SAMPLES));
+    }
+    if !(q.switch_penalty.is_finite() && q.switch_penalty >= 0.0) {
+        return error(StatusCode::UNPROCESSABLE_ENTITY, "switch_penalty must not be negative");
+    }
+    let intervals = match station_intervals(&state, el, &stations, &params).await {
+        Ok(i) => i,
+        Err(response) => return response,
+    };
+
+    let end = params.start + Duration::minutes(params.duration_min);
+    let step = Duration::seconds(params.step_s);
+    let times: Vec<DateTime<Utc>> = std::iter::successors(Some(params.start), |t| Some(*t + step)).take_while(|t| *t < end).collect();
+    let mut elevations = Vec::with_capacity(times.len());
+    for &t in &times {
+        let visible: Vec<bool> = intervals.iter().map(|list| list.iter().any(|(s, e)| *s <= t && t < *e)).collect();
+        if !visible.contains(&true) {
+            elevations.push(vec![None; stations.len()]);
+            continue;
+        }
+        let position = match propagate_minutes(el, minutes_since_epoch(el, t)) {
+            Ok(p) => p.position,
+            Err(e) => return error(StatusCode::BAD_REQUEST, format!("prediction error: {}", e)),
+        };
+        let theta = gmst(t);
+        elevations.push(
+            stations
+                .iter()
+                .zip(&visible)
+                .map(|(observer, &v)| v.then(|| topocentric_look_deg(&position, theta, &observer.position).0))
+                .collect(),
+        );
+    }
+    let plan = plan_handover(&times, &elevations, params.step_s as f64 / 60.0, q.switch_penalty);
+
+    let station_id = |i: usize| stations[i].station_id.unwrap_or_default();
+    let tracked_s: i64 = plan.iter().map(|a| (a.end.min(end) - a.start).num_seconds()).sum();
+    let out = HandoverPlanDto {
+        norad_id: q.norad_id,
+        start: params.start,
+        end,
+        tracked_s,
+        handovers: plan.iter().filter(|a| a.handover).count(),
+        assignments: plan
+            .into_iter()
+            .map(|a| HandoverDto {
+                station_id: station_id(a.station),
+                start: a.start,
+                end: a.end.min(end),
+                max_elevation_deg: a.max_elevation_deg,
+                handover: a.handover,
+            })
+            .collect(),
+    };
+    (StatusCode::OK, Json(serde_json::json!(out)))
+}
@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+
+use axum::{extract::{Path, Query, State}, response::IntoResponse, Extension, Json};
+use axum::http::StatusCode;
+use serde::Deserialize;
+
+use crate::api::access::Caller;
+use crate::api::server::AppState;
+use crate::api::types::{
+    CandidateFitDto, CatalogMatchDto, CreateObservationDto, IdentifyRequestDto, IdentifyResultDto, IodElementsDto, IodRequestDto, IodResultDto, ObservationDto,
+};
+use crate::core::orbit::SPEED_OF_LIGHT_KM_S;
+use crate::predictors::identify::{rank_candidates, Measurement, Sighting, Weights};
+use crate::predictors::iod::{gauss, rank_catalog, AngleObservation};
+use crate::predictors::passes::ObserverPosition;
+
+#[derive(Debug, Deserialize)]
+pub struct ObservationQuery {
+    #[serde(default)]
+    station_id: Option<i64>,
+    #[serde(default)]
+    norad_id: Option<u64>,
+    #[serde(default = "default_limit")]
+    limit: usize,
+}
+
+fn default_limit() -> usize { 500 }
+fn default_candidates() -> usize { 5 }
+/// Most candidates and sightings one identification compares.
+const MAX_CANDIDATES: usize = 500;
+const MAX_SIGHTINGS: usize = 5000;
+
+type Rejection = (StatusCode, Json<serde_json::Value>);
+
+fn error(status: StatusCode, msg: impl Into<String>) -> Rejection {
+    (status, Json(serde_json::json!({"error": msg.into()})))
+}
+
+pub async fn list_observations(caller: Option<Extension<Caller>>, Query(q): Query<ObservationQuery>) -> impl IntoResponse {
+    let scope = caller.as_deref().and_then(Caller::scope);
+    match crate::utils::db::open_or_init().and_then(|c| crate::utils::db::list_observations(&c, q.station_id, q.norad_id, scope, q.limit)) {
+        Ok(rows) => {
+            let out: Vec<ObservationDto> = rows
+                .into_iter()
+                .map(|o| ObservationDto {
+                    id: o.id,
+                    station_id: o.station_id,
+                    norad_id: o.norad_id,
+                    observed_at: o.observed_at,
+                    az_deg: o.az_deg,
+                    el_deg: o.el_deg,
+                })
+                .collect();
+            (StatusCode::OK, Json(serde_json::json!(out)))
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+pub async fn create_observation(caller: Option<Extension<Caller>>, Json(body): Json<CreateObservationDto>) -> impl IntoResponse {
+    if !((0.0..=360.0).contains(&body.az_deg) && (-90.0..=90.0).contains(&body.el_deg)) {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": "az/el out of range"})));
+    }
+    let conn = match crate::utils::db::open_or_init() {
+        Ok(c) => c,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    };
+    let visible = crate::utils::db::get_station(&conn, body.station_id).is_ok_and(|s| caller.as_deref().is_none_or(|c| c.can_access(&s)));
+    if !visible {
+        return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "station_id not found"})));
+    }
+    let observed_at = body.observed_at.to_rfc3339();
+    match crate::utils::db::insert_observation(&conn, body.station_id, body.norad_id, &observed_at, body.az_deg, body.el_deg) {
+        Ok(id) => (StatusCode::CREATED, Json(serde_json::json!({"id": id}))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+pub async fn delete_observation(caller: Option<Extension<Caller>>, Path(id): Path<i64>) -> impl IntoResponse {
+    let conn = match crate::utils::db::open_or_init() {
+        Ok(c) => c,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    };
+    // Another tenant's observation is left alone as if it did not exist
+    if let Some(caller) = caller.as_deref().filter(|c| c.scope().is_some()) {
+        let owned = crate::utils::db::get_observations_by_ids(&conn, &[id])
+            .ok()
+            .and_then(|rows| crate::utils::db::get_station(&conn, rows.first()?.station_id).ok())
+            .is_some_and(|s| caller.can_access(&s));
+        if !owned {
+            return (StatusCode::NO_CONTENT, Json(serde_json::json!({})));
+        }
+    }
+    match crate::utils::db::delete_observation(&conn, id) {
+        Ok(()) => (StatusCode::NO_CONTENT, Json(serde_json::json!({}))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+/// Runs Gauss IOD on the selected observations and ranks catalog objects near the solution.
+pub async fn run_iod(State(state): State<AppState>, caller: Option<Extension<Caller>>, Json(body): Json<IodRequestDto>) -> impl IntoResponse {
+    let conn = match crate::utils::db::open_or_init() {
+        Ok(c) => c,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    };
+    let rows = match crate::utils::db::get_observations_by_ids(&conn, &body.observation_ids) {
+        Ok(r) => r,
+        Err(_) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "observation not found"}))),
+    };
+
+    let mut stations = HashMap::new();
+    let mut observations = Vec::with_capacity(rows.len());
+    for row in rows {
+        let station = match stations.get(&row.station_id) {
+            Some(s) => s,
+            None => match crate::utils::db::get_station(&conn, row.station_id) {
+                Ok(s) if caller.as_deref().is_none_or(|c| c.can_access(&s)) => stations.entry(row.station_id).or_insert(s),
+                Ok(_) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "observation not found"}))),
+                Err(_) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "station_id not found"}))),
+            },
+        };
+        let time = match chrono::DateTime::parse_from_rfc3339(&row.observed_at) {
+            Ok(t) => t.with_timezone(&chrono::Utc),
+            Err(_) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": "invalid stored timestamp"}))),
+        };
+        observations.push(AngleObservation {
+            time,
+            lat_deg: station.lat,
+            lon_deg: station.lon,
+            height_km: station.alt_m / 1000.0,
+            az_deg: row.az_deg,
+            el_deg: row.el_deg,
+        });
+    }
+
+    let solution = match gauss(&observations) {
+        Ok(s) => s,
+        Err(e) => return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": format!("iod error: {}", e)}))),
+    };
+    let candidates = rank_catalog(&solution, &state.catalog(), body.candidates.unwrap_or_else(default_candidates))
+        .into_iter()
+        .map(|m| CatalogMatchDto { norad_id: m.norad_id, name: m.name, distance_km: m.distance_km })
+        .collect();
+    let el = &solution.elements;
+    let (tle_line1, tle_line2) = el.to_tle_lines();
+    let out = IodResultDto {
+        epoch: solution.epoch,
+        position_km: solution.position_km,
+        velocity_km_s: solution.velocity_km_s,
+        elements: IodElementsDto {
+            epoch: solution.epoch,
+            inclination_deg: el.inclination_deg,
+            raan_deg: el.raan_deg,
+            eccentricity: el.eccentricity,
+            arg_perigee_deg: el.arg_perigee_deg,
+            mean_anomaly_deg: el.mean_anomaly_deg,
+            mean_motion_rev_day: el.mean_motion_rev_day,
+            tle_line1,
+            tle_line2,
+        },
+        candidates,
+    };
+    (StatusCode::OK, Json(serde_json::json!(out)))
+}
+
+/// Looks a station up once per request, hiding stations the caller may not use.
+fn station_observer(conn: &rusqlite::Connection, caller: Option<&Caller>, cache: &mut HashMap<i64, ObserverPosition>, id: i64) -> Result<ObserverPosition, Rejection> {
+    if let Some(observer) = cache.get(&id) {
+        return Ok(*observer);
+    }
+    let station = match crate::utils::db::get_station(conn, id) {
+        Ok(s) if caller.is_none_or(|c| c.can_access(&s)) => s,
+        _ => return Err(error(StatusCode::NOT_FOUND, "station_id not found")),
+    };
+    let observer = ObserverPosition { lat_deg: station.lat, lon_deg: station.lon, alt_km: station.alt_m / 1000.0 };
+    Ok(*cache.entry(id).or_insert(observer))
+}
+
+/// Ranks candidate objects, typically the pieces of a fresh launch, by how
+/// well their elements explain logged az/el observations and received Doppler
+/// frequencies. The best match comes first.
+pub async fn identify_objects(State(state): State<AppState>, caller: Option<Extension<Caller>>, Json(body): Json<IdentifyRequestDto>) -> impl IntoResponse {
+    match identify(&state, caller.as_deref(), body) {
+        Ok(out) => (StatusCode::OK, Json(serde_json::json!(out))),
+        Err(response) => response,
+    }
+}
+
+fn identify(state: &AppState, caller: Option<&Caller>, body: IdentifyRequestDto) -> Result<IdentifyResultDto, Rejection> {
+    let weights = Weights {
+        angle_sigma_deg: body.angle_sigma_deg.unwrap_or(1.0),
+        range_rate_sigma_km_s: body.range_rate_sigma_m_s.unwrap_or(50.0) / 1000.0,
+        fit_frequency_offset: body.fit_frequency_offset.unwrap_or(true),
+    };
+    if !(weights.angle_sigma_deg > 0.0 && weights.range_rate_sigma_km_s > 0.0) {
+        return Err(error(StatusCode::UNPROCESSABLE_ENTITY, "sigmas must be positive"));
+    }
+    let sighting_count = body.observation_ids.len() + body.doppler.len();
+    if sighting_count == 0 || sighting_count > MAX_SIGHTINGS {
+        return Err(error(StatusCode::UNPROCESSABLE_ENTITY, format!("give between 1 and {} observations and Doppler samples", MAX_SIGHTINGS)));
+    }
+    if body.doppler.iter().any(|d| !(d.frequency_hz > 0.0 && d.nominal_hz > 0.0)) {
+        return Err(error(StatusCode::UNPROCESSABLE_ENTITY, "frequencies must be positive"));
+    }
+
+    let catalog = state.catalog();
+    let (candidates, missing): (Vec<(&sgp4::Elements, Option<&str>)>, Vec<u64>) = match (&body.norad_ids, &body.launch) {
+        (Some(ids), None) => {
+            let mut found: Vec<(&sgp4::Elements, Option<&str>)> = Vec::new();
+            let mut missing = Vec::new();
+            for &id in ids {
+                match catalog.get(id) {
+                    Some(el) if !found.iter().any(|(e, _)| e.norad_id == id) => found.push((el, catalog.name_of(id))),
+                    Some(_) => {}
+                    None => missing.push(id),
+                }
+            }
+            (found, missing)
+        }
+        (None, Some(launch)) => {
+            let launch = crate::analyzers::launches::parse_launch(launch).ok_or_else(|| error(StatusCode::UNPROCESSABLE_ENTITY, "launch must look like 2024-043 or 24043"))?;
+            let satcat = crate::utils::db::open_or_init()
+                .and_then(|c| crate::utils::db::satcat_map(&c))
+                .map_err(|e| error(StatusCode::INTERNAL_SERVER_ERROR, format!("db error: {}", e)))?;
+            let pieces = crate::analyzers::launches::group_by_launch(&catalog, &satcat).remove(&launch).unwrap_or_default();
+            (pieces.into_iter().map(|p| (p.elements, p.name)).collect(), Vec::new())
+        }
+        _ => return Err(error(StatusCode::BAD_REQUEST, "give either norad_ids or launch")),
+    };
+    if candidates.is_empty() {
+        return Err(error(StatusCode::NOT_FOUND, "no candidate is in the loaded TLEs"));
+    }
+    if candidates.len() > MAX_CANDIDATES {
+        return Err(error(StatusCode::UNPROCESSABLE_ENTITY, format!("at most {} candidates", MAX_CANDIDATES)));
+    }
+
+    let conn = crate::utils::db::open_or_init().map_err(|e| error(StatusCode::INTERNAL_SERVER_ERROR, format!("db error: {}", e)))?;
+    let rows = crate::utils::db::get_observations_by_ids(&conn, &body.observation_ids).map_err(|_| error(StatusCode::NOT_FOUND, "observation not found"))?;
+    let mut stations = HashMap::new();
+    let mut sightings = Vec::with_capacity(sighting_count);
+    for row in rows {
+        let observer = station_observer(&conn, caller, &mut stations, row.station_id).map_err(|_| error(StatusCode::NOT_FOUND, "observation not found"))?;
+        let time = chrono::DateTime::parse_from_rfc3339(&row.observed_at)
+            .map_err(|_| error(StatusCode::INTERNAL_SERVER_ERROR, "invalid stored timestamp"))?
+            .with_timezone(&chrono::Utc);
+        sightings.push(Sighting { time, observer, measurement: Measurement::Angles { az_deg: row.az_deg, el_deg: row.el_deg } });
+    }
+    let angle_sightings = sightings.len();
+    for d in &body.doppler {
+        let observer = station_observer(&conn, caller, &mut stations, d.station_id)?;
+        sightings.push(Sighting { time: d.time, observer, measurement: Measurement::Doppler { frequency_hz: d.frequency_hz, nominal_hz: d.nominal_hz } });
+    }
+
+    let mean_nominal_hz = body.doppler.iter().map(|d| d.nominal_hz).sum::<f64>() / body.doppler.len().max(1) as f64;
+    let candidates = rank_candidates(&candidates, &sightings, &weights)
+        .into_iter()
+        .enumerate()
+        .map(|(i, fit)| CandidateFitDto {
+            rank: i + 1,
+            norad_id: fit.norad_id,
+            name: fit.name,
+            score: fit.score,
+            angle_rms_deg: fit.angle_rms_deg,
+            range_rate_rms_m_s: fit.range_rate_rms_km_s.map(|r| r * 1000.0),
+            frequency_offset_hz: fit.range_rate_bias_km_s.map(|b| -b / SPEED_OF_LIGHT_KM_S * mean_nominal_hz),
+            below_horizon: fit.below_horizon,
+        })
+        .collect();
+    Ok(IdentifyResultDto { angle_sightings, doppler_sightings: body.doppler.len(), candidates, missing })
+}
@@ -0,0 +1,112 @@
+use axum::{extract::{Path, State}, response::IntoResponse, Json};
+use axum::http::StatusCode;
+use chrono::{SecondsFormat, Utc};
+
+use crate::api::server::AppState;
+use crate::api::types::{ElementOverrideDto, ElementOverrideRequestDto, ElementOverridesDto};
+use crate::core::overrides::{elements_from_state, to_elements};
+use crate::utils::db::{self, ElementOverride};
+
+fn override_dto(o: ElementOverride) -> ElementOverrideDto {
+    ElementOverrideDto {
+        id: o.id,
+        epoch: to_elements(&o).ok().map(|el| el.datetime.and_utc()),
+        source: o.source,
+        tle: o.tle,
+        omm: o.omm.and_then(|s| serde_json::from_str(&s).ok()),
+        reason: o.reason,
+        created_at: o.created_at,
+        reverted_at: o.reverted_at,
+    }
+}
+
+fn overrides_dto(state: &AppState, norad_id: u64, history: Vec<ElementOverride>) -> ElementOverridesDto {
+    let active = history.iter().find(|o| o.reverted_at.is_none()).cloned();
+    let loaded_epoch = state.catalog().get(norad_id).map(|e| e.datetime);
+    // Without an override any loaded element set is the fetched one
+    let loaded = match &active {
+        Some(o) => to_elements(o).ok().map(|el| el.datetime) == loaded_epoch,
+        None => true,
+    };
+    ElementOverridesDto {
+        norad_id,
+        active: active.map(override_dto),
+        loaded,
+        history: history.into_iter().map(override_dto).collect(),
+    }
+}
+
+/// The element set override in effect for a satellite and all earlier versions.
+pub async fn get_overrides(Path(norad_id): Path<u64>, State(state): State<AppState>) -> impl IntoResponse {
+    match db::open_or_init().and_then(|c| db::list_element_overrides(&c, norad_id)) {
+        Ok(history) => (StatusCode::OK, Json(serde_json::json!(overrides_dto(&state, norad_id, history)))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+/// Stores a new override version for a satellite: a TLE or OMM, a TEME state
+/// vector converted to mean elements, or a pin to an archived TLE. It replaces
+/// the fetched elements from the next TLE load until reverted.
+pub async fn create_override(Path(norad_id): Path<u64>, State(state): State<AppState>, Json(body): Json<ElementOverrideRequestDto>) -> impl IntoResponse {
+    let unprocessable = |e: String| (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": e})));
+    let given = [body.elements.tle.is_some(), body.elements.omm.is_some(), body.state.is_some(), body.pin_epoch.is_some()];
+    if given.iter().filter(|g| **g).count() != 1 {
+        return unprocessable("give exactly one of tle, omm, state or pin_epoch".to_string());
+    }
+    let conn = match db::open_or_init() {
+        Ok(c) => c,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    };
+    let (source, tle, omm) = if let Some(sv) = &body.state {
+        let Some(mean) = elements_from_state(norad_id, sv.epoch, &sv.position_km, &sv.velocity_km_s) else {
+            return unprocessable("state is not a bound orbit".to_string());
+        };
+        let (line1, line2) = mean.to_tle_lines();
+        ("state", Some(format!("{}\n{}", line1, line2)), None)
+    } else if let Some(epoch) = body.pin_epoch {
+        let as_of = epoch.to_rfc3339_opts(SecondsFormat::Micros, true);
+        match db::list_tle_nearest(&conn, &as_of, Some(norad_id)) {
+            Ok(mut rows) if !rows.is_empty() => {
+                let r = rows.swap_remove(0);
+                let name = r.name.map(|n| format!("{}\n", n)).unwrap_or_default();
+                ("pin", Some(format!("{}{}\n{}", name, r.line1, r.line2)), None)
+            }
+            Ok(_) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "no archived TLE for norad_id"}))),
+            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+        }
+    } else {
+        let omm = body.elements.omm.as_ref().map(|o| o.to_string());
+        match crate::core::tle::parse_tle_or_omm(body.elements.tle.as_deref(), body.elements.omm) {
+            Ok(el) if el.norad_id != norad_id => return unprocessable(format!("elements are for NORAD ID {}", el.norad_id)),
+            Ok(_) => (if omm.is_some() { "omm" } else { "tle" }, body.elements.tle, omm),
+            Err(e) => return unprocessable(e),
+        }
+    };
+
+    let new = ElementOverride {
+        id: 0,
+        norad_id,
+        source: source.to_string(),
+        tle,
+        omm,
+        reason: body.reason.filter(|r| !r.trim().is_empty()),
+        created_at: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        reverted_at: None,
+    };
+    match db::insert_element_override(&conn, &new).and_then(|_| db::list_element_overrides(&conn, norad_id)) {
+        Ok(history) => (StatusCode::CREATED, Json(serde_json::json!(overrides_dto(&state, norad_id, history)))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+/// Reverts the override in effect, restoring the previous version or, after the
+/// first one, the fetched elements.
+pub async fn revert_override(Path(norad_id): Path<u64>, State(state): State<AppState>) -> impl IntoResponse {
+    let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let reverted = db::open_or_init().and_then(|c| Ok((db::revert_element_override(&c, norad_id, &now)?, db::list_element_overrides(&c, norad_id)?)));
+    match reverted {
+        Ok((Some(_), history)) => (StatusCode::OK, Json(serde_json::json!(overrides_dto(&state, norad_id, history)))),
+        Ok((None, _)) => (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "no override in effect for norad_id"}))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
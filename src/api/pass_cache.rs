@@ -0,0 +1,254 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+
+use crate::api::passes::PredictionParams;
+use crate::core::terrain::HorizonMask;
+use crate::predictors::passes::{AzimuthSector, ObserverPosition, PassWindow};
+use crate::utils::metrics::{self, PassCacheResult};
+
+/// Environment variable with the number of predictions kept in memory (default
+/// 256, 0 turns the memory cache off).
+const SIZE_ENV: &str = "STFCM_PASS_CACHE_SIZE";
+/// Environment variable that, set to `1`, also keeps predictions in the database
+/// so they survive restarts and are shared by instances on one database.
+const DB_ENV: &str = "STFCM_PASS_CACHE_DB";
+const DEFAULT_SIZE: usize = 256;
+/// How much later than a cached prediction a request may start and still be
+/// served from it, so repeated "now" queries hit.
+const REUSE_SECONDS: i64 = 60;
+
+/// Identity of a prediction: everything its windows depend on but the start.
+#[derive(Debug, Clone)]
+pub struct PassKey {
+    digest: String,
+    norad_id: u64,
+    epoch: i64,
+}
+
+impl PassKey {
+    /// Digest of the element set, the observer with its horizon and excluded
+    /// sectors, and the search parameters. Any change to one of them, such as a
+    /// newer element set or an edited horizon, gives a new key.
+    pub fn new(
+        el: &sgp4::Elements,
+        position: &ObserverPosition,
+        params: &PredictionParams,
+        horizon: Option<&HorizonMask>,
+        exclusions: &[AzimuthSector],
+    ) -> PassKey {
+        // The mean elements SGP4 propagates, bit for bit
+        let elements = [
+            el.inclination,
+            el.right_ascension,
+            el.eccentricity,
+            el.argument_of_perigee,
+            el.mean_anomaly,
+            el.mean_motion,
+            el.drag_term,
+        ]
+        .map(f64::to_bits);
+        let identity = format!(
+            "{}|{:?}|{:?}|{:?}|{}|{}|{}|{}|{}|{:?}|{:?}|{:?}",
+            el.norad_id,
+            el.datetime,
+            elements,
+            [position.lat_deg, position.lon_deg, position.alt_km].map(f64::to_bits),
+            params.duration_min,
+            params.step_s,
+            params.min_el.to_bits(),
+            params.refraction,
+            params.light_time,
+            params.exclusion_mode,
+            horizon,
+            exclusions,
+        );
+        let digest = Sha256::digest(identity.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect();
+        PassKey { digest, norad_id: el.norad_id, epoch: el.datetime.and_utc().timestamp() }
+    }
+}
+
+struct Entry {
+    norad_id: u64,
+    epoch: i64,
+    start: DateTime<Utc>,
+    windows: Vec<PassWindow>,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct Entries {
+    map: HashMap<String, Entry>,
+    clock: u64,
+}
+
+/// Pass predictions reused across requests: a least-recently-used map in memory
+/// and, optionally, the `pass_cache` table behind it. Entries are keyed by
+/// [`PassKey`], so a newer element set never hits an old one; [`retain_current`]
+/// drops the superseded ones after each load.
+///
+/// [`retain_current`]: PassCache::retain_current
+pub struct PassCache {
+    capacity: usize,
+    db: bool,
+    entries: Mutex<Entries>,
+}
+
+/// Windows of a cached prediction for a request starting at `start`: those that
+/// have not ended by then.
+fn reuse(cached_start: DateTime<Utc>, windows: &[PassWindow], start: DateTime<Utc>) -> Option<Vec<PassWindow>> {
+    let offset = start - cached_start;
+    if offset < Duration::zero() || offset >= Duration::seconds(REUSE_SECONDS) {
+        return None;
+    }
+    Some(windows.iter().filter(|w| w.end > start).cloned().collect())
+}
+
+impl PassCache {
+    pub fn new(capacity: usize, db: bool) -> PassCache {
+        PassCache { capacity, db, entries: Mutex::new(Entries::default()) }
+    }
+
+    /// Cache sized by `STFCM_PASS_CACHE_SIZE`, backed by the database when
+    /// `STFCM_PASS_CACHE_DB=1` and the database is writable.
+    pub fn from_env(read_only: bool) -> PassCache {
+        let capacity = match std::env::var(SIZE_ENV) {
+            Ok(value) => value.parse().unwrap_or_else(|_| {
+                warn!(value = value.as_str(), "Ignoring invalid {}", SIZE_ENV);
+                DEFAULT_SIZE
+            }),
+            Err(_) => DEFAULT_SIZE,
+        };
+        let db = std::env::var(DB_ENV).is_ok_and(|v| v == "1") && !read_only;
+        if db {
+            info!("Caching pass predictions in the database");
+        }
+        PassCache::new(capacity, db)
+    }
+
+    /// Windows predicted for `key` from up to a minute before `start`.
+    pub fn get(&self, key: &PassKey, start: DateTime<Utc>) -> Option<Vec<PassWindow>> {
+        {
+            let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+            entries.clock += 1;
+            let clock = entries.clock;
+            if let Some(entry) = entries.map.get_mut(&key.digest) {
+                if let Some(windows) = reuse(entry.start, &entry.windows, start) {
+                    entry.last_used = clock;
+                    metrics::record_pass_cache(PassCacheResult::Memory);
+                    return Some(windows);
+                }
+            }
+        }
+        if self.db {
+            let stored = crate::utils::db::open_or_init().and_then(|c| crate::utils::db::get_cached_passes(&c, &key.digest));
+            match stored {
+                Ok(Some((cached_start, json))) => {
+                    let parsed = DateTime::parse_from_rfc3339(&cached_start).ok().zip(serde_json::from_str::<Vec<PassWindow>>(&json).ok());
+                    if let Some((cached_start, windows)) = parsed {
+                        let cached_start = cached_start.with_timezone(&Utc);
+                        if let Some(reused) = reuse(cached_start, &windows, start) {
+                            self.remember(key, cached_start, windows);
+                            metrics::record_pass_cache(PassCacheResult::Database);
+                            return Some(reused);
+                        }
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => warn!(error = %e, "Failed to read cached passes"),
+            }
+        }
+        metrics::record_pass_cache(PassCacheResult::Miss);
+        None
+    }
+
+    /// Stores the windows predicted for `key` from `start`.
+    pub fn put(&self, key: &PassKey, start: DateTime<Utc>, windows: &[PassWindow]) {
+        self.remember(key, start, windows.to_vec());
+        if self.db {
+            let json = serde_json::to_string(windows).unwrap_or_default();
+            let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+            let start = start.to_rfc3339_opts(SecondsFormat::Micros, true);
+            if let Err(e) = crate::utils::db::open_or_init()
+                .and_then(|c| crate::utils::db::put_cached_passes(&c, &key.digest, key.norad_id, key.epoch, &start, &json, &now))
+            {
+                warn!(error = %e, "Failed to store cached passes");
+            }
+        }
+    }
+
+    fn remember(&self, key: &PassKey, start: DateTime<Utc>, windows: Vec<PassWindow>) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.clock += 1;
+        let last_used = entries.clock;
+        if entries.map.len() >= self.capacity && !entries.map.contains_key(&key.digest) {
+            let oldest = entries.map.iter().min_by_key(|(_, e)| e.last_used).map(|(k, _)| k.clone());
+            if let Some(oldest) = oldest {
+                entries.map.remove(&oldest);
+            }
+        }
+        entries.map.insert(key.digest.clone(), Entry { norad_id: key.norad_id, epoch: key.epoch, start, windows, last_used });
+    }
+
+    /// Drops predictions made from element sets other than the loaded ones.
+    pub fn retain_current(&self, elements: &[sgp4::Elements]) {
+        let current: HashSet<(u64, i64)> = elements.iter().map(|e| (e.norad_id, e.datetime.and_utc().timestamp())).collect();
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).map.retain(|_, e| current.contains(&(e.norad_id, e.epoch)));
+        if self.db {
+            match crate::utils::db::open_or_init().and_then(|c| crate::utils::db::prune_pass_cache(&c, &current)) {
+                Ok(0) => {}
+                Ok(n) => info!(count = n, "Dropped cached passes of superseded element sets"),
+                Err(e) => warn!(error = %e, "Failed to prune cached passes"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::predictors::passes::{predict_passes, ExclusionMode};
+
+    #[test]
+    fn reuses_predictions_for_the_same_element_set_only() {
+        let mut catalog = crate::testing::fixtures::catalog();
+        let el = catalog.swap_remove(0);
+        let start = el.datetime.and_utc();
+        let position = ObserverPosition { lat_deg: 40.7, lon_deg: -74.0, alt_km: 0.0 };
+        let params = PredictionParams {
+            start,
+            duration_min: 720,
+            step_s: 15,
+            min_el: 10.0,
+            terrain: false,
+            refraction: false,
+            light_time: false,
+            exclusion_mode: ExclusionMode::default(),
+            merge_gap_s: 0,
+            min_peak_el: None,
+        };
+        let windows = predict_passes(&el, position.lat_deg, position.lon_deg, start, 720, 15, 10.0).unwrap();
+        assert!(!windows.is_empty());
+
+        let cache = PassCache::new(1, false);
+        let key = PassKey::new(&el, &position, &params, None, &[]);
+        assert!(cache.get(&key, start).is_none());
+        cache.put(&key, start, &windows);
+        assert_eq!(cache.get(&key, start + Duration::seconds(30)).map(|w| w.len()), Some(windows.len()));
+        assert!(cache.get(&key, start + Duration::seconds(90)).is_none());
+        // Another observer or search has its own entry, evicting this one
+        let other = PassKey::new(&el, &position, &PredictionParams { min_el: 20.0, ..params }, None, &[]);
+        assert_ne!(other.digest, key.digest);
+        cache.put(&other, start, &[]);
+        assert!(cache.get(&key, start).is_none());
+
+        cache.retain_current(&catalog);
+        assert!(cache.get(&other, start).is_none());
+    }
+}
@@ -0,0 +1,223 @@
+use std::sync::Arc;
+
+use axum::http::StatusCode;
+use axum::Json;
+use chrono::{DateTime, Utc};
+
+use crate::api::access::Caller;
+use crate::api::types::GeoLookDto;
+use crate::api::pass_cache::{PassCache, PassKey};
+use crate::api::{geo, horizon};
+use crate::core::terrain::HorizonMask;
+use crate::predictors::geo::is_geosynchronous;
+use crate::predictors::passes::{merge_passes, predict_passes_with_options, ExclusionMode, LookOptions, Observer, ObserverPosition, PassWindow};
+
+/// Error response of the shared pass services, returned as is by the handlers.
+pub type PassError = (StatusCode, Json<serde_json::Value>);
+
+fn error(status: StatusCode, msg: impl Into<String>) -> PassError {
+    (status, Json(serde_json::json!({"error": msg.into()})))
+}
+
+/// Where passes are predicted for: a stored station or ad-hoc coordinates.
+#[derive(Debug, Clone, Copy)]
+pub struct ResolvedObserver {
+    pub station_id: Option<i64>,
+    pub position: ObserverPosition,
+}
+
+/// Resolves `station_id`, or else `lat`/`lon` (and `alt_m`, default 0), to an
+/// observer. A station of another tenant is reported as not found.
+pub fn resolve_observer(
+    station_id: Option<i64>,
+    lat: Option<f64>,
+    lon: Option<f64>,
+    alt_m: Option<f64>,
+    caller: Option<&Caller>,
+) -> Result<ResolvedObserver, PassError> {
+    if let Some(id) = station_id {
+        return match crate::utils::db::open_or_init().and_then(|c| crate::utils::db::get_station(&c, id)) {
+            Ok(st) if caller.is_none_or(|c| c.can_access(&st)) => Ok(ResolvedObserver {
+                station_id: Some(id),
+                position: ObserverPosition { lat_deg: st.lat, lon_deg: st.lon, alt_km: st.alt_m / 1000.0 },
+            }),
+            _ => Err(error(StatusCode::NOT_FOUND, "station_id not found")),
+        };
+    }
+    let (Some(lat), Some(lon)) = (lat, lon) else {
+        return Err(error(StatusCode::BAD_REQUEST, "missing lat/lon or station_id"));
+    };
+    if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lon) {
+        return Err(error(StatusCode::UNPROCESSABLE_ENTITY, "lat must be within [-90, 90] and lon within [-180, 180]"));
+    }
+    Ok(ResolvedObserver { station_id: None, position: ObserverPosition { lat_deg: lat, lon_deg: lon, alt_km: alt_m.unwrap_or(0.0) / 1000.0 } })
+}
+
+/// Search window and look-angle options of a pass prediction.
+#[derive(Debug, Clone, Copy)]
+pub struct PredictionParams {
+    pub start: DateTime<Utc>,
+    pub duration_min: i64,
+    pub step_s: i64,
+    pub min_el: f64,
+    /// Apply the DEM horizon mask when tiles cover the observer.
+    pub terrain: bool,
+    pub refraction: bool,
+    pub light_time: bool,
+    pub exclusion_mode: ExclusionMode,
+    /// Join passes separated by at most this many seconds below `min_el`.
+    pub merge_gap_s: i64,
+    /// Drop (merged) passes whose maximum elevation stays below this.
+    pub min_peak_el: Option<f64>,
+}
+
+impl PredictionParams {
+    fn validate(&self) -> Result<(), PassError> {
+        if self.duration_min <= 0 || self.step_s <= 0 {
+            return Err(error(StatusCode::UNPROCESSABLE_ENTITY, "duration and step must be positive"));
+        }
+        if self.merge_gap_s < 0 {
+            return Err(error(StatusCode::UNPROCESSABLE_ENTITY, "merge_gap must not be negative"));
+        }
+        if !(-90.0..=90.0).contains(&self.min_el) {
+            return Err(error(StatusCode::UNPROCESSABLE_ENTITY, "min_el must be within [-90, 90]"));
+        }
+        Ok(())
+    }
+
+    /// Merges and drops passes as requested. Runs after the cache, which keeps
+    /// the passes as the scan found them.
+    fn shape(&self, windows: Vec<PassWindow>) -> Vec<PassWindow> {
+        let mut windows = if self.merge_gap_s > 0 { merge_passes(windows, self.merge_gap_s) } else { windows };
+        if let Some(min_peak) = self.min_peak_el {
+            windows.retain(|w| w.max_elevation_deg >= min_peak);
+        }
+        windows
+    }
+}
+
+/// Outcome of `run_prediction`.
+pub enum Prediction {
+    /// A geosynchronous object has no passes, only a constant look angle.
+    Geo(GeoLookDto),
+    Passes(Vec<PassWindow>),
+}
+
+/// Horizon mask of the observer when `terrain` asks for one, loaded on the
+/// blocking pool; pass it to [`run_prediction`].
+pub async fn observer_horizon(observer: &ResolvedObserver, terrain: bool) -> Option<Arc<HorizonMask>> {
+    if !terrain {
+        return None;
+    }
+    horizon::load_horizon(observer.station_id, observer.position.lat_deg, observer.position.lon_deg).await
+}
+
+/// Predicts the passes of `el`, named `name`, over the observer with its
+/// horizon mask (from [`observer_horizon`]; ignored unless `params.terrain`)
+/// and excluded sectors, in time order. With a cache, passes predicted for the
+/// same inputs up to a minute earlier are reused.
+pub fn run_prediction(
+    el: &sgp4::Elements,
+    name: Option<&str>,
+    observer: &ResolvedObserver,
+    horizon: Option<&HorizonMask>,
+    params: &PredictionParams,
+    cache: Option<&PassCache>,
+) -> Result<Prediction, PassError> {
+    params.validate()?;
+    let position = observer.position;
+    let horizon = horizon.filter(|_| params.terrain);
+    let exclusions = observer.station_id.map(horizon::exclusions_for).unwrap_or_default();
+    let options = LookOptions {
+        horizon,
+        refraction: params.refraction,
+        light_time: params.light_time,
+        exclusions: &exclusions,
+        exclusion_mode: params.exclusion_mode,
+    };
+    if is_geosynchronous(el) {
+        return geo::geo_look(el, name, &position, &options, params.min_el, params.start)
+            .map(Prediction::Geo)
+            .ok_or_else(|| error(StatusCode::BAD_REQUEST, "prediction error"));
+    }
+    let key = cache.map(|_| PassKey::new(el, &position, params, horizon, &exclusions));
+    if let Some(windows) = cache.zip(key.as_ref()).and_then(|(c, k)| c.get(k, params.start)) {
+        return Ok(Prediction::Passes(params.shape(windows)));
+    }
+    let windows = predict_passes_with_options(el, &Observer::Fixed(position), &options, params.start, params.duration_min, params.step_s, params.min_el)
+        .map_err(|e| error(StatusCode::BAD_REQUEST, format!("prediction error: {}", e)))?;
+    if let Some((cache, key)) = cache.zip(key.as_ref()) {
+        cache.put(key, params.start, &windows);
+    }
+    Ok(Prediction::Passes(params.shape(windows)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_invalid_observers_and_windows() {
+        let status = |r: Result<ResolvedObserver, PassError>| r.map(|o| o.position.alt_km).map_err(|e| e.0);
+        assert_eq!(status(resolve_observer(None, Some(52.0), Some(13.0), Some(500.0), None)), Ok(0.5));
+        assert_eq!(status(resolve_observer(None, Some(52.0), None, None, None)), Err(StatusCode::BAD_REQUEST));
+        assert_eq!(status(resolve_observer(None, Some(95.0), Some(13.0), None, None)), Err(StatusCode::UNPROCESSABLE_ENTITY));
+
+        let params = PredictionParams {
+            start: Utc::now(),
+            duration_min: 120,
+            step_s: 0,
+            min_el: 10.0,
+            terrain: false,
+            refraction: false,
+            light_time: false,
+            exclusion_mode: ExclusionMode::default(),
+            merge_gap_s: 0,
+            min_peak_el: None,
+        };
+        assert_eq!(params.validate().unwrap_err().0, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(PredictionParams { step_s: 15, ..params }.validate().is_ok());
+        assert!(PredictionParams { step_s: 15, min_el: 91.0, ..params }.validate().is_err());
+    }
+
+    #[test]
+    fn geo_objects_get_one_look_angle_object_instead_of_passes() {
+        // Near-stationary case 28626 of Vallado's SGP4 verification set
+        let text = "GEO\n1 28626U 05008A   06176.46683397 -.00000205  00000-0  10000-3 0  2190\n2 28626   0.0019 286.9433 0000335  13.7918  55.6504  1.00270176  4891\n";
+        let geo = crate::core::tle::parse_tle_records(text)[0].to_elements().unwrap();
+        let start = geo.datetime.and_utc();
+        let params = PredictionParams {
+            start,
+            duration_min: 24 * 60,
+            step_s: 60,
+            min_el: 10.0,
+            terrain: false,
+            refraction: false,
+            light_time: false,
+            exclusion_mode: ExclusionMode::default(),
+            merge_gap_s: 0,
+            min_peak_el: None,
+        };
+        let longitude = geo::geo_object(&geo, None, start).unwrap().longitude_deg;
+        let observer = |lon: f64| ResolvedObserver { station_id: None, position: ObserverPosition { lat_deg: 0.0, lon_deg: lon, alt_km: 0.0 } };
+        let look = |lon: f64| match run_prediction(&geo, Some("GEO"), &observer(lon), None, &params, None) {
+            Ok(Prediction::Geo(look)) => look,
+            _ => panic!("expected a GEO look angle"),
+        };
+
+        let below = look(longitude);
+        assert!(below.el_deg > 89.0 && below.visible);
+        let far_side = look(longitude + 180.0);
+        assert!(far_side.el_deg < 0.0 && !far_side.visible);
+        // The body is an object with the object's state, not an array of passes
+        let body = serde_json::json!(below);
+        let keys: Vec<&str> = body.as_object().unwrap().keys().map(String::as_str).collect();
+        assert_eq!(keys, ["az_deg", "el_deg", "geo", "visible"]);
+        assert_eq!(body["geo"]["norad_id"], 28626);
+        assert_eq!(body["geo"]["name"], "GEO");
+
+        let iss = crate::testing::fixtures::catalog().swap_remove(0);
+        let params = PredictionParams { start: iss.datetime.and_utc(), ..params };
+        assert!(matches!(run_prediction(&iss, None, &observer(0.0), None, &params, None), Ok(Prediction::Passes(_))));
+    }
+}
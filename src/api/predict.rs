@@ -0,0 +1,182 @@
+use axum::{extract::State, response::IntoResponse, Extension, Json};
+use axum::http::StatusCode;
+
+use crate::api::access::Caller;
+use crate::api::server::AppState;
+use crate::api::types::{
+    AdHocElementsDto, CompareEphemeridesRequestDto, EphemerisComparisonDto, EphemerisSourceDto, PassWindowDto, PredictPassesRequestDto,
+    PredictPositionRequestDto, PredictedPositionDto, RicDifferenceDto, RicRmsDto,
+};
+use crate::api::passes::{self, Prediction, PredictionParams};
+use crate::core::coords::{ecef_to_geodetic, eci_to_ecef, gmst};
+use crate::core::orbit::{minutes_since_epoch, propagate_minutes, EARTH_RADIUS_KM};
+use crate::predictors::ephemeris::{compare, Ephemeris, RicDifference};
+use crate::predictors::passes::ExclusionMode;
+
+/// Parses the candidate element set of a request; the message is returned as a 422.
+fn parse_elements(dto: AdHocElementsDto) -> Result<sgp4::Elements, String> {
+    crate::core::tle::parse_tle_or_omm(dto.tle.as_deref(), dto.omm)
+}
+
+/// Pass prediction for an element set given in the body rather than the loaded
+/// catalog, e.g. a candidate orbit under design.
+pub async fn predict_passes(State(state): State<AppState>, caller: Option<Extension<Caller>>, Json(req): Json<PredictPassesRequestDto>) -> impl IntoResponse {
+    let el = match parse_elements(req.elements) {
+        Ok(el) => el,
+        Err(e) => return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": e}))),
+    };
+    let observer = match passes::resolve_observer(req.station_id, req.lat, req.lon, req.alt_m, caller.as_deref()) {
+        Ok(o) => o,
+        Err(response) => return response,
+    };
+    let params = PredictionParams {
+        start: req.start.unwrap_or_else(|| state.clock.now()),
+        duration_min: req.duration.unwrap_or(state.config.passes.duration_min),
+        step_s: req.step.unwrap_or(state.config.passes.step_s),
+        min_el: req.min_el.unwrap_or(state.config.passes.min_el_deg),
+        terrain: true,
+        refraction: req.refraction.unwrap_or(false),
+        light_time: req.light_time.unwrap_or(false),
+        exclusion_mode: ExclusionMode::default(),
+        merge_gap_s: req.merge_gap.unwrap_or(0),
+        min_peak_el: req.min_peak_el,
+    };
+    let horizon = passes::observer_horizon(&observer, params.terrain).await;
+    match passes::run_prediction(&el, el.object_name.as_deref(), &observer, horizon.as_deref(), &params, Some(&state.pass_cache)) {
+        Ok(Prediction::Geo(look)) => (StatusCode::OK, Json(serde_json::json!(look))),
+        Ok(Prediction::Passes(wins)) => {
+            let out: Vec<PassWindowDto> = wins.into_iter().map(PassWindowDto::from).collect();
+            (StatusCode::OK, Json(serde_json::json!(out)))
+        }
+        Err(response) => response,
+    }
+}
+
+/// Position of an element set given in the body at `time` (default now).
+pub async fn predict_position(State(state): State<AppState>, Json(req): Json<PredictPositionRequestDto>) -> impl IntoResponse {
+    let el = match parse_elements(req.elements) {
+        Ok(el) => el,
+        Err(e) => return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": e}))),
+    };
+    let time = req.time.unwrap_or_else(|| state.clock.now());
+    let pred = match propagate_minutes(&el, minutes_since_epoch(&el, time)) {
+        Ok(p) => p,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": format!("prediction error: {}", e)}))),
+    };
+    let (x, y, z) = eci_to_ecef(&pred.position, gmst(time));
+    let (lat, lon) = ecef_to_geodetic(x, y, z);
+    let norm = |v: &[f64; 3]| (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+    let out = PredictedPositionDto {
+        norad_id: el.norad_id,
+        name: el.object_name.clone(),
+        epoch: el.datetime.and_utc(),
+        time,
+        lat,
+        lon,
+        alt_km: norm(&pred.position) - EARTH_RADIUS_KM,
+        speed_km_s: norm(&pred.velocity),
+        position_km: pred.position,
+        velocity_km_s: pred.velocity,
+    };
+    (StatusCode::OK, Json(serde_json::json!(out)))
+}
+
+/// Default comparison span when no `end` is given.
+const DEFAULT_COMPARE_SPAN: chrono::Duration = chrono::Duration::hours(24);
+/// Most samples one comparison may produce.
+const MAX_COMPARE_SAMPLES: i64 = 20_000;
+
+fn resolve_source(state: &AppState, dto: EphemerisSourceDto) -> Result<Ephemeris, String> {
+    let given = [dto.elements.tle.is_some(), dto.elements.omm.is_some(), dto.oem.is_some(), dto.norad_id.is_some()];
+    if given.iter().filter(|g| **g).count() != 1 {
+        return Err("give exactly one of tle, omm, oem or norad_id per element set".to_string());
+    }
+    let ephemeris = if let Some(oem) = dto.oem {
+        return crate::collectors::oem::parse_oem(&oem).map(Ephemeris::Table).map_err(|e| format!("invalid OEM: {}", e));
+    } else if let Some(norad_id) = dto.norad_id {
+        Ephemeris::from_elements(state.catalog().get(norad_id).ok_or("norad_id not found in loaded TLEs")?)
+    } else {
+        Ephemeris::from_elements(&parse_elements(dto.elements)?)
+    };
+    ephemeris.map_err(|e| format!("invalid elements: {}", e))
+}
+
+fn ric_dto(d: &RicDifference) -> RicDifferenceDto {
+    RicDifferenceDto {
+        time: d.time,
+        radial_km: d.radial_km,
+        along_track_km: d.along_track_km,
+        cross_track_km: d.cross_track_km,
+        total_km: d.total_km(),
+    }
+}
+
+/// Propagates two ephemerides (e.g. an old and a new TLE, or a TLE and an
+/// operator OEM) over a span and reports the second's position relative to the
+/// first in the first's RIC frame. Without `start` the span begins where both
+/// have data (the later epoch); without `end` it lasts a day or until an OEM ends.
+pub async fn compare_ephemerides(State(state): State<AppState>, Json(req): Json<CompareEphemeridesRequestDto>) -> impl IntoResponse {
+    let unprocessable = |e: String| (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": e})));
+    let (reference, other) = match (resolve_source(&state, req.reference), resolve_source(&state, req.other)) {
+        (Ok(r), Ok(o)) => (r, o),
+        (Err(e), _) => return unprocessable(format!("reference: {}", e)),
+        (_, Err(e)) => return unprocessable(format!("other: {}", e)),
+    };
+    let spans: Vec<_> = [&reference, &other].iter().filter_map(|e| e.span()).collect();
+    let start = req.start.or_else(|| {
+        let covered = spans.iter().map(|s| s.0).max();
+        covered.or_else(|| reference.epoch().max(other.epoch()))
+    });
+    let Some(start) = start else {
+        return unprocessable("cannot determine start".to_string());
+    };
+    let end = req.end.unwrap_or_else(|| spans.iter().map(|s| s.1).fold(start + DEFAULT_COMPARE_SPAN, |a, b| a.min(b)));
+    let step = req.step.unwrap_or(60);
+    if step <= 0 || end <= start {
+        return unprocessable("step must be positive and end after start".to_string());
+    }
+    if (end - start).num_seconds() / step >= MAX_COMPARE_SAMPLES {
+        return unprocessable(format!("span / step exceeds {} samples", MAX_COMPARE_SAMPLES));
+    }
+
+    let diffs = match compare(&reference, &other, start, end, chrono::Duration::seconds(step)) {
+        Ok(d) if !d.is_empty() => d,
+        Ok(_) => return unprocessable("no samples in span".to_string()),
+        Err(e) => return unprocessable(e.to_string()),
+    };
+    let n = diffs.len() as f64;
+    let rms = |f: fn(&RicDifference) -> f64| (diffs.iter().map(|d| f(d).powi(2)).sum::<f64>() / n).sqrt();
+    let max = diffs.iter().max_by(|a, b| a.total_km().total_cmp(&b.total_km())).map(ric_dto).expect("non-empty");
+    let out = EphemerisComparisonDto {
+        start,
+        end,
+        step_s: step,
+        rms: RicRmsDto {
+            radial_km: rms(|d| d.radial_km),
+            along_track_km: rms(|d| d.along_track_km),
+            cross_track_km: rms(|d| d.cross_track_km),
+            total_km: rms(RicDifference::total_km),
+        },
+        max,
+        samples: diffs.iter().map(ric_dto).collect(),
+    };
+    (StatusCode::OK, Json(serde_json::json!(out)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ISS: &str = "ISS (ZARYA)
+1 25544U 98067A   08264.51782528 -.00002182  00000-0 -11606-4 0  2927
+2 25544  51.6416 247.4627 0006703 130.5360 325.0288 15.72125391563537";
+
+    #[test]
+    fn accepts_exactly_one_element_source() {
+        let tle = |t: &str| AdHocElementsDto { tle: Some(t.to_string()), omm: None };
+        assert_eq!(parse_elements(tle(ISS)).unwrap().norad_id, 25544);
+        assert!(parse_elements(tle("not a tle")).is_err());
+        assert!(parse_elements(AdHocElementsDto { tle: None, omm: None }).is_err());
+        assert!(parse_elements(AdHocElementsDto { tle: Some(ISS.to_string()), omm: Some(serde_json::json!({})) }).is_err());
+    }
+}
@@ -0,0 +1,174 @@
+use axum::{body::Body, extract::Request, middleware::Next, response::Response};
+use axum::http::{header, HeaderName, HeaderValue, StatusCode};
+use serde_json::{Map, Value};
+use tracing::Instrument;
+
+/// Header carrying the request ID in both directions.
+pub static REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+/// Longest client-supplied request ID that is kept; longer ones are replaced.
+const MAX_REQUEST_ID_LEN: usize = 128;
+/// Largest error body rewritten as problem+json; bigger ones pass through.
+const MAX_ERROR_BYTES: u64 = 64 * 1024;
+
+/// ID of the current request, as left in the request extensions by [`request_id`].
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+/// A client's ID is kept if it is short, visible ASCII, so it can be logged and
+/// echoed back as a header as is.
+fn accepted_id(value: &HeaderValue) -> Option<String> {
+    let id = value.to_str().ok()?;
+    let valid = !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN && id.bytes().all(|b| b.is_ascii_graphic());
+    valid.then(|| id.to_string())
+}
+
+/// Random version 4 UUID, or a timestamp if the OS has no randomness to give.
+fn new_id() -> String {
+    let mut b = [0u8; 16];
+    if getrandom::fill(&mut b).is_err() {
+        return format!("t{}", chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default());
+    }
+    b[6] = (b[6] & 0x0f) | 0x40;
+    b[8] = (b[8] & 0x3f) | 0x80;
+    let hex: String = b.iter().map(|x| format!("{:02x}", x)).collect();
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+/// Takes the client's `X-Request-Id` or makes one up, runs the request in a
+/// tracing span carrying it and echoes it on the response.
+pub async fn request_id(mut request: Request, next: Next) -> Response {
+    let id = request.headers().get(&REQUEST_ID).and_then(accepted_id).unwrap_or_else(new_id);
+    request.extensions_mut().insert(RequestId(id.clone()));
+    let span = tracing::info_span!("request", request_id = %id, method = %request.method(), path = %request.uri().path());
+    let mut response = async move {
+        let response = next.run(request).await;
+        let status = response.status();
+        if status.is_server_error() {
+            tracing::warn!(status = status.as_u16(), "request failed");
+        } else {
+            tracing::debug!(status = status.as_u16(), "request finished");
+        }
+        response
+    }
+    .instrument(span)
+    .await;
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(REQUEST_ID.clone(), value);
+    }
+    response
+}
+
+/// RFC 7807 body for an error response. `{"error": ...}` bodies become `detail`
+/// and their other fields stay as extension members; a plain-text body becomes
+/// `detail` as well.
+fn problem_body(status: StatusCode, body: &[u8], is_json: bool, instance: &str, request_id: Option<&str>) -> Value {
+    let mut problem = Map::new();
+    problem.insert("type".to_string(), Value::from("about:blank"));
+    problem.insert("title".to_string(), Value::from(status.canonical_reason().unwrap_or("Error")));
+    problem.insert("status".to_string(), Value::from(status.as_u16()));
+    match serde_json::from_slice::<Value>(body) {
+        Ok(Value::Object(fields)) if is_json => {
+            for (key, value) in fields {
+                match key.as_str() {
+                    "error" => problem.insert("detail".to_string(), value),
+                    "type" | "title" | "status" | "instance" | "request_id" => None,
+                    _ => problem.insert(key, value),
+                };
+            }
+        }
+        Ok(Value::String(detail)) if is_json => {
+            problem.insert("detail".to_string(), Value::from(detail));
+        }
+        _ => {
+            let text = String::from_utf8_lossy(body);
+            if !is_json && !text.trim().is_empty() {
+                problem.insert("detail".to_string(), Value::from(text.trim()));
+            }
+        }
+    }
+    problem.insert("instance".to_string(), Value::from(instance));
+    if let Some(id) = request_id {
+        problem.insert("request_id".to_string(), Value::from(id));
+    }
+    Value::Object(problem)
+}
+
+/// Rewrites every 4xx/5xx response with a JSON, plain-text or empty body as
+/// `application/problem+json`, so all handlers, extractor rejections and
+/// middleware report errors the same way.
+pub async fn problem_json(request: Request, next: Next) -> Response {
+    let instance = request.uri().path().to_string();
+    let request_id = request.extensions().get::<RequestId>().map(|r| r.0.clone());
+    let response = next.run(request).await;
+    let status = response.status();
+    let content_type = response.headers().get(header::CONTENT_TYPE).and_then(|v| v.to_str().ok()).unwrap_or("");
+    let is_json = content_type.starts_with("application/json");
+    let convertible = is_json || content_type.is_empty() || content_type.starts_with("text/plain");
+    let small = axum::body::HttpBody::size_hint(response.body()).upper().is_some_and(|n| n <= MAX_ERROR_BYTES);
+    if !(status.is_client_error() || status.is_server_error()) || !convertible || !small {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let Ok(bytes) = axum::body::to_bytes(body, MAX_ERROR_BYTES as usize).await else {
+        return Response::from_parts(parts, Body::empty());
+    };
+    let problem = problem_body(status, &bytes, is_json, &instance, request_id.as_deref());
+    parts.headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/problem+json"));
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(problem.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_bodies_become_problem_details() {
+        let body = br#"{"error": "station not found", "rejected_records": [], "status": "x"}"#;
+        let problem = problem_body(StatusCode::NOT_FOUND, body, true, "/api/v1/stations/9", Some("abc"));
+        assert_eq!(
+            problem,
+            serde_json::json!({
+                "type": "about:blank",
+                "title": "Not Found",
+                "status": 404,
+                "detail": "station not found",
+                "rejected_records": [],
+                "instance": "/api/v1/stations/9",
+                "request_id": "abc",
+            })
+        );
+        let text = problem_body(StatusCode::UNPROCESSABLE_ENTITY, b"Failed to deserialize", false, "/x", None);
+        assert_eq!(text["detail"], "Failed to deserialize");
+        assert!(problem_body(StatusCode::METHOD_NOT_ALLOWED, b"", false, "/x", None).get("detail").is_none());
+
+        assert_eq!(accepted_id(&HeaderValue::from_static("req-42")).as_deref(), Some("req-42"));
+        assert!(accepted_id(&HeaderValue::from_static("has space")).is_none());
+        let id = new_id();
+        assert_eq!((id.len(), &id[14..15]), (36, "4"));
+    }
+
+    #[tokio::test]
+    async fn rejections_carry_the_callers_request_id() {
+        use axum::{routing::post, Router};
+        use tower::ServiceExt;
+
+        let app = Router::new()
+            .route("/stations", post(|axum::Json(v): axum::Json<Value>| async move { axum::Json(v) }))
+            .layer(axum::middleware::from_fn(problem_json))
+            .layer(axum::middleware::from_fn(request_id));
+        let request = Request::post("/stations")
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(&REQUEST_ID, "bug-123")
+            .body(Body::from("{bad"))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.headers()[&REQUEST_ID], "bug-123");
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/problem+json");
+        let body: Value = serde_json::from_slice(&axum::body::to_bytes(response.into_body(), 4096).await.unwrap()).unwrap();
+        assert_eq!((body["request_id"].as_str(), body["instance"].as_str()), (Some("bug-123"), Some("/stations")));
+        assert!(body["detail"].is_string());
+    }
+}
@@ -0,0 +1,159 @@
+use axum::{extract::{Query, State}, response::IntoResponse, Extension, Json};
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::api::access::Caller;
+use crate::api::passes::resolve_observer;
+use crate::api::server::AppState;
+use crate::api::types::{EquatorialDto, IntervalDto, LinkSummaryDto, PassProfileDto, ProfileSampleDto};
+use crate::predictors::link::{link_budget, LinkParams};
+use crate::predictors::saa::saa_intervals;
+use crate::predictors::passes::{keyhole_intervals, pointing_track, predict_passes_with_options, LookOptions, Observer};
+
+#[derive(Debug, Deserialize)]
+pub struct ProfileQuery {
+    norad_id: u64,
+    #[serde(default)]
+    station_id: Option<i64>,
+    #[serde(default)]
+    lat: Option<f64>,
+    #[serde(default)]
+    lon: Option<f64>,
+    /// The first pass starting at or after this time is profiled (default: now).
+    #[serde(default)]
+    start: Option<DateTime<Utc>>,
+    #[serde(default = "default_search")]
+    search: i64,
+    #[serde(default)]
+    min_el: f64,
+    #[serde(default = "default_step")]
+    step: i64,
+    /// Azimuth slew limit of the rotator (deg/s) used for keyhole detection.
+    #[serde(default = "default_max_az_rate")]
+    max_az_rate: f64,
+    #[serde(default)]
+    refraction: bool,
+    #[serde(default)]
+    light_time: bool,
+    /// Add topocentric RA/Dec to every sample.
+    #[serde(default)]
+    radec: bool,
+    /// Downlink frequency; enables the link budget.
+    #[serde(default)]
+    frequency_mhz: Option<f64>,
+    #[serde(default)]
+    tx_power_dbw: f64,
+    #[serde(default)]
+    tx_gain_dbi: f64,
+    #[serde(default)]
+    rx_gain_dbi: f64,
+    #[serde(default = "default_noise_temp")]
+    noise_temp_k: f64,
+    #[serde(default = "default_bandwidth")]
+    bandwidth_hz: f64,
+    #[serde(default)]
+    misc_losses_db: f64,
+}
+
+impl ProfileQuery {
+    fn link_params(&self) -> Option<LinkParams> {
+        Some(LinkParams {
+            frequency_mhz: self.frequency_mhz?,
+            tx_power_dbw: self.tx_power_dbw,
+            tx_gain_dbi: self.tx_gain_dbi,
+            rx_gain_dbi: self.rx_gain_dbi,
+            noise_temp_k: self.noise_temp_k,
+            bandwidth_hz: self.bandwidth_hz,
+            misc_losses_db: self.misc_losses_db,
+        })
+    }
+}
+
+fn default_search() -> i64 { 1440 }
+fn default_step() -> i64 { 1 }
+fn default_max_az_rate() -> f64 { 3.0 }
+fn default_noise_temp() -> f64 { 290.0 }
+fn default_bandwidth() -> f64 { 10_000.0 }
+
+/// Step used to locate the pass; the profile samples use `step`.
+const SEARCH_STEP_SECONDS: i64 = 10;
+
+/// Detailed az/el profile of the next pass with angular rates, flagging the
+/// zenith keyhole where an az-el rotator cannot keep up so a flip can be planned.
+pub async fn get_profile(State(state): State<AppState>, Query(q): Query<ProfileQuery>, caller: Option<Extension<Caller>>) -> impl IntoResponse {
+    let catalog = state.catalog();
+    let Some(el) = catalog.get(q.norad_id) else {
+        return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "norad_id not found in loaded TLEs"})));
+    };
+    let link_params = q.link_params();
+    if let Some(Err(e)) = link_params.map(|p| p.validate()) {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": e})));
+    }
+    let position = match resolve_observer(q.station_id, q.lat, q.lon, None, caller.as_deref()) {
+        Ok(o) => o.position,
+        Err(response) => return response,
+    };
+
+    let start = q.start.unwrap_or_else(Utc::now);
+    let options = LookOptions { refraction: q.refraction, light_time: q.light_time, ..Default::default() };
+    let pass = match predict_passes_with_options(el, &Observer::Fixed(position), &options, start, q.search, SEARCH_STEP_SECONDS, q.min_el) {
+        Ok(wins) => match wins.into_iter().next() {
+            Some(w) => w,
+            None => return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "no pass in the search window"}))),
+        },
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": format!("prediction error: {}", e)}))),
+    };
+    let samples = match pointing_track(el, &position, pass.start, pass.end, q.step, &options) {
+        Ok(s) => s,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": format!("prediction error: {}", e)}))),
+    };
+
+    let keyholes: Vec<IntervalDto> = keyhole_intervals(&samples, q.max_az_rate)
+        .into_iter()
+        .map(|(start, end)| IntervalDto { start, end })
+        .collect();
+    let saa: Vec<IntervalDto> = match saa_intervals(el, pass.start, pass.end, q.step) {
+        Ok(intervals) => intervals.into_iter().map(|(start, end)| IntervalDto { start, end }).collect(),
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": format!("prediction error: {}", e)}))),
+    };
+    let link = match link_params.map(|p| link_budget(el, &position, &p, samples.iter().map(|s| s.time))).transpose() {
+        Ok(l) => l,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": format!("prediction error: {}", e)}))),
+    };
+    let out = PassProfileDto {
+        norad_id: q.norad_id,
+        start: pass.start,
+        end: pass.end,
+        tca: pass.tca,
+        max_elevation_deg: pass.max_elevation_deg,
+        peak_az_rate_deg_s: samples.iter().map(|s| s.az_rate_deg_s.abs()).fold(0.0, f64::max),
+        keyhole: !keyholes.is_empty(),
+        keyhole_intervals: keyholes,
+        saa_intervals: saa,
+        link: link.as_ref().zip(q.frequency_mhz).map(|(l, frequency_mhz)| LinkSummaryDto {
+            frequency_mhz,
+            max_snr_db: l.iter().map(|s| s.snr_db).fold(f64::NEG_INFINITY, f64::max),
+            min_snr_db: l.iter().map(|s| s.snr_db).fold(f64::INFINITY, f64::min),
+        }),
+        samples: samples
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                let budget = link.as_ref().map(|l| l[i]);
+                ProfileSampleDto {
+                    time: s.time,
+                    az_deg: s.az_deg,
+                    el_deg: s.el_deg,
+                    az_rate_deg_s: s.az_rate_deg_s,
+                    el_rate_deg_s: s.el_rate_deg_s,
+                    equatorial: q.radec.then(|| EquatorialDto::new(&position, s.az_deg, s.el_deg, s.time)),
+                    range_km: budget.map(|b| b.range_km),
+                    path_loss_db: budget.map(|b| b.path_loss_db),
+                    snr_db: budget.map(|b| b.snr_db),
+                }
+            })
+            .collect(),
+    };
+    (StatusCode::OK, Json(serde_json::json!(out)))
+}
@@ -0,0 +1,35 @@
+use axum::{extract::Request, middleware::Next, response::{IntoResponse, Response}, Json};
+use axum::http::{Method, StatusCode};
+
+/// POST routes that only compute a result and never write.
+const QUERY_POSTS: [&str; 6] = ["/passes/mobile", "/iod", "/iod/identify", "/predict/passes", "/predict/position", "/predict/compare"];
+
+pub fn is_mutating(method: &Method, path: &str) -> bool {
+    match *method {
+        Method::GET | Method::HEAD | Method::OPTIONS => false,
+        Method::POST => !QUERY_POSTS.contains(&path),
+        _ => true,
+    }
+}
+
+/// Middleware for read-only mode: answers every mutating request with
+/// `405 Method Not Allowed` instead of running it.
+pub async fn reject_writes(request: Request, next: Next) -> Response {
+    if is_mutating(request.method(), request.uri().path()) {
+        return (StatusCode::METHOD_NOT_ALLOWED, Json(serde_json::json!({"error": "server is in read-only mode"}))).into_response();
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_query_posts_pass() {
+        assert!(!is_mutating(&Method::GET, "/stations"));
+        assert!(!is_mutating(&Method::POST, "/passes/mobile"));
+        assert!(is_mutating(&Method::POST, "/stations"));
+        assert!(is_mutating(&Method::DELETE, "/observations/1"));
+    }
+}
@@ -0,0 +1,103 @@
+use std::sync::Arc;
+
+use axum::{extract::{Query, State}, response::IntoResponse, Json};
+use axum::http::StatusCode;
+use chrono::Duration;
+use serde::Deserialize;
+
+use crate::api::server::AppState;
+use crate::api::types::{IntervalDto, RegionOverflightDto, SatelliteOverRegionDto};
+use crate::core::aoi::AoiGeometry;
+use crate::predictors::access::region_intervals;
+use crate::utils::db;
+
+#[derive(Debug, Deserialize)]
+pub struct OverQuery {
+    /// ISO 3166-1 alpha-2 or alpha-3 code, looked up in the country boundaries.
+    #[serde(default)]
+    country: Option<String>,
+    /// A stored polygon area of interest.
+    #[serde(default)]
+    aoi_id: Option<i64>,
+    /// Also list when each satellite is over the region within this many hours.
+    #[serde(default)]
+    hours: Option<i64>,
+    #[serde(default = "default_step")]
+    step: i64,
+    #[serde(default = "default_limit")]
+    limit: usize,
+}
+
+fn default_step() -> i64 { 30 }
+fn default_limit() -> usize { 500 }
+/// Longest window for entry and exit times.
+const MAX_HOURS: i64 = 24;
+
+type Rejection = (StatusCode, Json<serde_json::Value>);
+
+fn error(status: StatusCode, msg: impl Into<String>) -> Rejection {
+    (status, Json(serde_json::json!({"error": msg.into()})))
+}
+
+/// The polygons named by `country` or `aoi_id`, with a label for the response.
+fn region(state: &AppState, q: &OverQuery) -> Result<(String, Arc<Vec<AoiGeometry>>), Rejection> {
+    match (&q.country, q.aoi_id) {
+        (Some(code), None) => {
+            if state.countries.is_empty() {
+                return Err(error(StatusCode::NOT_FOUND, "no country boundaries are loaded; see STFCM_COUNTRIES_FILE"));
+            }
+            let outline = state.countries.get(code).ok_or_else(|| error(StatusCode::NOT_FOUND, format!("unknown country code {}", code)))?;
+            Ok((format!("country:{}", code.to_uppercase()), outline))
+        }
+        (None, Some(id)) => {
+            let row = db::open_or_init().and_then(|c| db::get_aoi(&c, id)).map_err(|_| error(StatusCode::NOT_FOUND, "aoi not found"))?;
+            let geometry = serde_json::from_str(&row.geometry).map_err(|e| e.to_string()).and_then(|v| AoiGeometry::from_geojson(&v));
+            match geometry {
+                Ok(g @ AoiGeometry::Polygon(_)) => Ok((format!("aoi:{}", id), Arc::new(vec![g]))),
+                Ok(AoiGeometry::Point(_)) => Err(error(StatusCode::UNPROCESSABLE_ENTITY, "the aoi is a point; use a polygon")),
+                Err(e) => Err(error(StatusCode::INTERNAL_SERVER_ERROR, format!("stored geometry is invalid: {}", e))),
+            }
+        }
+        _ => Err(error(StatusCode::BAD_REQUEST, "give either country or aoi_id")),
+    }
+}
+
+/// Satellites whose subsatellite point is inside a country or a stored area
+/// right now, from the shared catalog positions. With `hours`, each one also
+/// gets the intervals it spends over the region from now on, the first ending
+/// at its exit.
+pub async fn list_over_region(Query(q): Query<OverQuery>, State(state): State<AppState>) -> impl IntoResponse {
+    let (label, polygons) = match region(&state, &q) {
+        Ok(r) => r,
+        Err(response) => return response,
+    };
+    if q.hours.is_some_and(|h| !(1..=MAX_HOURS).contains(&h)) || q.step <= 0 {
+        return error(StatusCode::UNPROCESSABLE_ENTITY, format!("hours must be within [1, {}] and step positive", MAX_HOURS));
+    }
+    let now = state.clock.now();
+    let batch = state.positions.batch(&state.catalog(), now);
+    let inside = batch.positions.iter().filter(|p| polygons.iter().any(|g| g.contains(p.lat as f64, p.lon as f64))).take(q.limit);
+
+    let mut satellites = Vec::new();
+    for p in inside {
+        let norad_id = p.norad_id as u64;
+        let catalog = state.catalog();
+        let Some(el) = catalog.get(norad_id) else {
+            continue;
+        };
+        let intervals = match q.hours.map(|h| region_intervals(el, &polygons, now, now + Duration::hours(h), q.step)).transpose() {
+            Ok(i) => i.map(|list| list.into_iter().map(|(start, end)| IntervalDto { start, end }).collect()),
+            Err(e) => return error(StatusCode::BAD_REQUEST, format!("prediction error: {}", e)),
+        };
+        satellites.push(SatelliteOverRegionDto {
+            norad_id,
+            name: catalog.name_of(norad_id).map(str::to_string),
+            lat: p.lat as f64,
+            lon: p.lon as f64,
+            alt_km: p.alt_km as f64,
+            intervals,
+        });
+    }
+    let out = RegionOverflightDto { region: label, time: now, count: satellites.len(), satellites };
+    (StatusCode::OK, Json(serde_json::json!(out)))
+}
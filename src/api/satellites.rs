@@ -0,0 +1,369 @@
+use axum::{extract::{Path, Query, State}, response::IntoResponse, Extension, Json};
+use axum::http::StatusCode;
+use serde::Deserialize;
+
+use crate::api::access::Caller;
+use crate::api::passes::{self, Prediction, PredictionParams};
+use crate::api::server::AppState;
+use crate::api::types::{NextEventsDto, OrbitalEventDto, PassWindowDto, ReentryDto, RepeatCycleDto, SatelliteAliasesDto, SatelliteDetailDto, SatellitePlaneDto};
+use crate::core::orbit::{minutes_since_epoch, perigee_apogee_radius_km, propagate_minutes, EARTH_RADIUS_KM};
+use crate::core::sun::shadow_margin_km;
+use crate::predictors::decay::{estimate_reentry, final_ground_track, DecayEstimate};
+use crate::predictors::events::{eclipse_events, orbital_events, OrbitalEvent, OrbitalEventKind};
+use crate::predictors::passes::ExclusionMode;
+use crate::predictors::plane::{local_time_distance_hours, orbit_plane, parse_local_time};
+use crate::predictors::repeat::{repeat_cycle, revolutions_per_nodal_day};
+use crate::predictors::revolution::orbit_number;
+
+#[derive(Debug, Deserialize)]
+pub struct ReentryQuery {
+    #[serde(default = "default_revolutions")]
+    revolutions: f64,
+    #[serde(default = "default_track_step")]
+    step: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EventsQuery {
+    #[serde(default = "default_event_hours")]
+    hours: i64,
+}
+
+fn default_event_hours() -> i64 { 24 }
+/// Longest window accepted by the events endpoint (two weeks).
+const MAX_EVENT_HOURS: i64 = 336;
+
+#[derive(Debug, Deserialize)]
+pub struct NextQuery {
+    #[serde(default)]
+    station_id: Option<i64>,
+    #[serde(default = "default_next_min_el")]
+    min_el: f64,
+}
+
+fn default_next_min_el() -> f64 { 10.0 }
+/// How far ahead the summary looks for the next pass.
+const NEXT_PASS_HOURS: i64 = 48;
+/// Sampling step (s) of the pass search.
+const NEXT_PASS_STEP_S: i64 = 15;
+
+/// Bounds of the re-entry corridor length in revolutions.
+const MIN_CORRIDOR_REVOLUTIONS: f64 = 0.1;
+const MAX_CORRIDOR_REVOLUTIONS: f64 = 16.0;
+
+fn default_revolutions() -> f64 { 2.0 }
+fn default_track_step() -> i64 { 30 }
+
+#[derive(Debug, Deserialize)]
+pub struct PlanesQuery {
+    /// Only sun-synchronous (`true`) or other (`false`) orbits.
+    #[serde(default)]
+    sso: Option<bool>,
+    /// Local time of the ascending node, `HH:MM` or decimal hours.
+    #[serde(default)]
+    ltan: Option<String>,
+    #[serde(default = "default_ltan_tolerance")]
+    ltan_tolerance_min: f64,
+}
+
+fn default_ltan_tolerance() -> f64 { 30.0 }
+
+/// Orbit planes of the loaded catalog relative to the Sun, in order of local
+/// time of the ascending node, e.g. `?sso=true&ltan=10:30` for the sun-synchronous
+/// orbits crossing the equator northbound around 10:30.
+pub async fn list_planes(Query(q): Query<PlanesQuery>, State(state): State<AppState>) -> impl IntoResponse {
+    let ltan = match q.ltan.as_deref().map(|s| parse_local_time(s).ok_or(s)).transpose() {
+        Ok(l) => l,
+        Err(s) => return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": format!("invalid ltan {}; use HH:MM", s)}))),
+    };
+    if !(0.0..=720.0).contains(&q.ltan_tolerance_min) {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": "ltan_tolerance_min must be within [0, 720]"})));
+    }
+    let now = state.clock.now();
+    let mut planes: Vec<SatellitePlaneDto> = state
+        .catalog()
+        .named()
+        .map(|(el, name)| (el, name, orbit_plane(el, now)))
+        .filter(|(_, _, p)| q.sso.is_none_or(|sso| p.sun_synchronous == sso))
+        .filter(|(_, _, p)| ltan.is_none_or(|l| local_time_distance_hours(p.ltan_hours, l) * 60.0 <= q.ltan_tolerance_min))
+        .map(|(el, name, p)| SatellitePlaneDto { norad_id: el.norad_id, name: name.map(str::to_string), inclination_deg: el.inclination, plane: p.into() })
+        .collect();
+    planes.sort_by(|a, b| a.plane.ltan_hours.total_cmp(&b.plane.ltan_hours));
+    (StatusCode::OK, Json(serde_json::json!(planes)))
+}
+
+/// Mean orbital elements of a loaded satellite, with its repeat ground track
+/// cycle when the orbit closes on itself within a month, and its orbit plane
+/// relative to the Sun.
+pub async fn get_satellite(Path(norad_id): Path<u64>, State(state): State<AppState>) -> impl IntoResponse {
+    let catalog = state.catalog();
+    let Some(el) = catalog.get(norad_id) else {
+        return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "norad_id not found in loaded TLEs"})));
+    };
+    let names = crate::utils::db::open_or_init()
+        .and_then(|c| crate::utils::db::get_satellite_aliases(&c, norad_id))
+        .unwrap_or_default();
+    let (rp, ra) = perigee_apogee_radius_km(el);
+    let revs_per_nodal_day = revolutions_per_nodal_day(el);
+    let now = state.clock.now();
+    let dto = SatelliteDetailDto {
+        norad_id,
+        name: catalog.name_of(norad_id).map(str::to_string),
+        display_name: names.display_name,
+        aliases: names.aliases,
+        element_epoch: el.datetime.and_utc(),
+        inclination_deg: el.inclination,
+        eccentricity: el.eccentricity,
+        mean_motion_rev_per_day: el.mean_motion,
+        period_min: 1440.0 / el.mean_motion,
+        perigee_alt_km: rp - EARTH_RADIUS_KM,
+        apogee_alt_km: ra - EARTH_RADIUS_KM,
+        revs_per_nodal_day,
+        repeat_ground_track: repeat_cycle(revs_per_nodal_day).map(|c| RepeatCycleDto {
+            revolutions: c.revolutions,
+            days: c.days,
+            label: format!("{}/{}", c.revolutions, c.days),
+            shift_deg: c.shift_deg,
+        }),
+        orbit_plane: orbit_plane(el, now).into(),
+        orbit_number: orbit_number(el, now).ok(),
+    };
+    (StatusCode::OK, Json(serde_json::json!(dto)))
+}
+
+/// Longest accepted display name or alias, in characters.
+const MAX_ALIAS_LEN: usize = 128;
+
+pub async fn get_aliases(Path(norad_id): Path<u64>) -> impl IntoResponse {
+    match crate::utils::db::open_or_init().and_then(|c| crate::utils::db::get_satellite_aliases(&c, norad_id)) {
+        Ok(names) => (StatusCode::OK, Json(serde_json::json!(SatelliteAliasesDto { display_name: names.display_name, aliases: names.aliases }))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+/// Replaces the display name and aliases of a satellite. Names are trimmed,
+/// blank ones dropped and duplicates merged; an empty body clears them.
+pub async fn set_aliases(Path(norad_id): Path<u64>, Json(body): Json<SatelliteAliasesDto>) -> impl IntoResponse {
+    let clean = |s: &str| Some(s.trim().to_string()).filter(|s| !s.is_empty());
+    let display_name = body.display_name.as_deref().and_then(clean);
+    let mut aliases: Vec<String> = body.aliases.iter().filter_map(|a| clean(a)).collect();
+    aliases.sort();
+    aliases.dedup();
+    aliases.retain(|a| Some(a) != display_name.as_ref());
+    if display_name.iter().chain(&aliases).any(|a| a.chars().count() > MAX_ALIAS_LEN) {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": format!("names are limited to {} characters", MAX_ALIAS_LEN)})));
+    }
+    let names = crate::utils::db::SatelliteAliases { display_name, aliases };
+    match crate::utils::db::open_or_init().and_then(|c| crate::utils::db::set_satellite_aliases(&c, norad_id, &names)) {
+        Ok(()) => (StatusCode::OK, Json(serde_json::json!(SatelliteAliasesDto { display_name: names.display_name, aliases: names.aliases }))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+/// Predicted re-entry epoch, uncertainty window and the ground track corridor of the
+/// final revolutions (GeoJSON FeatureCollection).
+pub async fn get_reentry(
+    Path(norad_id): Path<u64>,
+    Query(q): Query<ReentryQuery>,
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    let catalog = state.catalog();
+    let Some(el) = catalog.get(norad_id) else {
+        return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "norad_id not found in loaded TLEs"})));
+    };
+    let Some(est) = estimate_reentry(el) else {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": "no re-entry predicted for current elements"})));
+    };
+    if !q.revolutions.is_finite() {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": "revolutions must be a finite number"})));
+    }
+
+    let out = ReentryDto {
+        norad_id,
+        name: catalog.name_of(norad_id).map(str::to_string),
+        element_epoch: est.element_epoch,
+        predicted_reentry: est.reentry,
+        window_start: est.window_start,
+        window_end: est.window_end,
+        lifetime_days: est.lifetime_days,
+        perigee_alt_km: est.perigee_alt_km,
+        corridor: reentry_corridor(el, &est, q.revolutions, q.step.max(1)),
+    };
+    (StatusCode::OK, Json(serde_json::json!(out)))
+}
+
+/// Ground track of the final `revolutions` before the nominal re-entry and the
+/// point where it ends, as a GeoJSON FeatureCollection. `revolutions` is
+/// clamped to the corridor bounds and the track feature echoes it as drawn.
+fn reentry_corridor(el: &sgp4::Elements, est: &DecayEstimate, revolutions: f64, step_seconds: i64) -> serde_json::Value {
+    let revolutions = revolutions.clamp(MIN_CORRIDOR_REVOLUTIONS, MAX_CORRIDOR_REVOLUTIONS);
+    let segments = final_ground_track(el, est.reentry, revolutions, step_seconds);
+    let end_point = segments.last().and_then(|s| s.last()).copied();
+    let mut features = vec![serde_json::json!({
+        "type": "Feature",
+        "properties": { "kind": "final_revolutions", "revolutions": revolutions },
+        "geometry": { "type": "MultiLineString", "coordinates": segments },
+    })];
+    if let Some(p) = end_point {
+        features.push(serde_json::json!({
+            "type": "Feature",
+            "properties": { "kind": "nominal_reentry", "time": est.reentry },
+            "geometry": { "type": "Point", "coordinates": p },
+        }));
+    }
+    serde_json::json!({ "type": "FeatureCollection", "features": features })
+}
+
+/// Perigee/apogee passages and ascending/descending node crossings over the next
+/// `hours`, with the subsatellite point and altitude at each event.
+pub async fn get_events(
+    Path(norad_id): Path<u64>,
+    Query(q): Query<EventsQuery>,
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    let catalog = state.catalog();
+    let Some(el) = catalog.get(norad_id) else {
+        return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "norad_id not found in loaded TLEs"})));
+    };
+    if !(1..=MAX_EVENT_HOURS).contains(&q.hours) {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": format!("hours must be within 1..={}", MAX_EVENT_HOURS)})));
+    }
+    let start = chrono::Utc::now();
+    match orbital_events(el, start, start + chrono::Duration::hours(q.hours)) {
+        Ok(events) => {
+            let out: Vec<OrbitalEventDto> = events
+                .into_iter()
+                .map(|e| OrbitalEventDto {
+                    kind: e.kind,
+                    time: e.time,
+                    latitude_deg: e.latitude_deg,
+                    longitude_deg: e.longitude_deg,
+                    altitude_km: e.altitude_km,
+                })
+                .collect();
+            (StatusCode::OK, Json(serde_json::json!(out)))
+        }
+        Err(e) => (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": format!("prediction error: {}", e)}))),
+    }
+}
+
+fn event_dto(e: &OrbitalEvent) -> OrbitalEventDto {
+    OrbitalEventDto {
+        kind: e.kind,
+        time: e.time,
+        latitude_deg: e.latitude_deg,
+        longitude_deg: e.longitude_deg,
+        altitude_km: e.altitude_km,
+    }
+}
+
+/// One-call summary for dashboards: element set age, the next pass over
+/// `station_id` (if given), the next shadow entry and exit, and the next node
+/// crossing. Events are searched over two revolutions, at most two days.
+pub async fn get_next(
+    Path(norad_id): Path<u64>,
+    Query(q): Query<NextQuery>,
+    caller: Option<Extension<Caller>>,
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    let catalog = state.catalog();
+    let Some(el) = catalog.get(norad_id) else {
+        return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "norad_id not found in loaded TLEs"})));
+    };
+    let observer = match q.station_id.map(|id| passes::resolve_observer(Some(id), None, None, None, caller.as_deref())).transpose() {
+        Ok(o) => o,
+        Err(response) => return response,
+    };
+    let now = state.clock.now();
+    let epoch = el.datetime.and_utc();
+    let prediction_error = |e: sgp4::Error| (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": format!("prediction error: {}", e)})));
+
+    let period = chrono::Duration::milliseconds((86_400_000.0 / el.mean_motion.max(0.1)) as i64);
+    let end = now + (period * 2).min(chrono::Duration::hours(NEXT_PASS_HOURS));
+    let (eclipses, events, position) = match (eclipse_events(el, now, end), orbital_events(el, now, end), propagate_minutes(el, minutes_since_epoch(el, now))) {
+        (Ok(eclipses), Ok(events), Ok(pred)) => (eclipses, events, pred.position),
+        (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => return prediction_error(e),
+    };
+    let first = |events: &[OrbitalEvent], kinds: &[OrbitalEventKind]| events.iter().find(|e| kinds.contains(&e.kind)).map(event_dto);
+
+    let next_pass = match &observer {
+        Some(observer) => {
+            let params = PredictionParams {
+                start: now,
+                duration_min: NEXT_PASS_HOURS * 60,
+                step_s: NEXT_PASS_STEP_S,
+                min_el: q.min_el,
+                terrain: true,
+                refraction: false,
+                light_time: false,
+                exclusion_mode: ExclusionMode::default(),
+                merge_gap_s: 0,
+                min_peak_el: None,
+            };
+            let horizon = passes::observer_horizon(observer, params.terrain).await;
+            match passes::run_prediction(el, None, observer, horizon.as_deref(), &params, Some(&state.pass_cache)) {
+                Ok(Prediction::Passes(wins)) => wins.into_iter().next().map(PassWindowDto::from),
+                // A GEO object has no passes to report
+                Ok(Prediction::Geo(_)) => None,
+                Err(response) => return response,
+            }
+        }
+        None => None,
+    };
+    let dto = NextEventsDto {
+        norad_id,
+        name: catalog.name_of(norad_id).map(str::to_string),
+        time: now,
+        element_epoch: epoch,
+        tle_age_days: (now - epoch).num_seconds() as f64 / 86_400.0,
+        station_id: q.station_id,
+        next_pass,
+        in_eclipse: shadow_margin_km(&position, now) < 0.0,
+        next_eclipse_entry: first(&eclipses, &[OrbitalEventKind::EclipseEntry]),
+        next_eclipse_exit: first(&eclipses, &[OrbitalEventKind::EclipseExit]),
+        next_node: first(&events, &[OrbitalEventKind::AscendingNode, OrbitalEventKind::DescendingNode]),
+    };
+    (StatusCode::OK, Json(serde_json::json!(dto)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::fixtures;
+
+    fn decaying_iss() -> (sgp4::Elements, DecayEstimate) {
+        let mut el = fixtures::catalog().swap_remove(0);
+        el.mean_motion_dot = 0.01;
+        let est = estimate_reentry(&el).expect("decaying orbit");
+        (el, est)
+    }
+
+    #[test]
+    fn corridor_ends_at_the_nominal_reentry_point() {
+        let (el, est) = decaying_iss();
+        let corridor = reentry_corridor(&el, &est, 2.0, 30);
+        assert_eq!(corridor["type"], "FeatureCollection");
+        let features = corridor["features"].as_array().unwrap();
+        assert_eq!(features.len(), 2);
+        assert_eq!(features[0]["properties"]["kind"], "final_revolutions");
+        assert_eq!(features[0]["properties"]["revolutions"], 2.0);
+        let segments = features[0]["geometry"]["coordinates"].as_array().unwrap();
+        let points: usize = segments.iter().map(|s| s.as_array().unwrap().len()).sum();
+        // Two revolutions of about 92 minutes every 30 s, both ends included
+        let period_s = 86_400.0 / el.mean_motion;
+        assert_eq!(points, (2.0 * period_s) as usize / 30 + 1);
+        assert_eq!(features[1]["properties"]["kind"], "nominal_reentry");
+        assert_eq!(features[1]["properties"]["time"], serde_json::json!(est.reentry));
+        let last = segments.last().and_then(|s| s.as_array().unwrap().last()).unwrap();
+        assert_eq!(&features[1]["geometry"]["coordinates"], last);
+    }
+
+    #[test]
+    fn corridor_echoes_the_clamped_revolutions() {
+        let (el, est) = decaying_iss();
+        for (requested, drawn) in [(100.0, MAX_CORRIDOR_REVOLUTIONS), (0.0, MIN_CORRIDOR_REVOLUTIONS), (3.5, 3.5)] {
+            let corridor = reentry_corridor(&el, &est, requested, 60);
+            assert_eq!(corridor["features"][0]["properties"]["revolutions"], drawn);
+        }
+    }
+}
@@ -1,298 +1,626 @@
-use std::net::SocketAddr;
-use std::sync::Arc;
-
-use axum::{extract::{Query, Path}, response::IntoResponse, routing::get, Json, Router};
-use axum::http::StatusCode;
-use tower_http::cors::{CorsLayer, Any};
-use tower_http::services::{ServeDir, ServeFile};
-use serde::Deserialize;
-// use tracing::info;
-
-use crate::api::types::{PassWindowDto, SatelliteDto, StationDto, CreateStationDto};
-use crate::predictors::passes::{predict_passes, PassWindow};
-
-#[derive(Clone)]
-pub struct AppState {
-    pub elements: Arc<Vec<sgp4::Elements>>, // latest parsed elements
-}
-
-#[derive(Debug, Deserialize)]
-struct PassQuery {
-    norad_id: u64,
-    #[serde(default)]
-    station_id: Option<i64>,
-    #[serde(default)]
-    lat: Option<f64>,
-    #[serde(default)]
-    lon: Option<f64>,
-    #[serde(default = "default_duration")] 
-    duration: i64,
-    #[serde(default = "default_step")] 
-    step: i64,
-    #[serde(default = "default_min_el")] 
-    min_el: f64,
-}
-
-fn default_duration() -> i64 { 120 }
-fn default_step() -> i64 { 15 }
-fn default_min_el() -> f64 { 10.0 }
-
-#[derive(Debug, Deserialize)]
-struct SatPosQuery {
-    #[serde(default)]
-    limit: Option<usize>,
-}
-
-pub async fn run_server(state: AppState, addr: SocketAddr) {
-    let app = Router::new()
-        .route("/health", get(health))
-        .route("/stations", get(list_stations).post(create_station))
-        .route("/stations/:id", get(get_station).put(update_station).delete(delete_station))
-        .route("/satellites", get(list_satellites))
-        .route("/satellites/positions", get(list_sat_positions))
-        .route("/passes", get(get_passes))
-        .route("/satellites/:norad_id/passes", get(get_passes_for_satellite))
-        .nest_service("/ui", ServeDir::new("web"))
-        .route_service("/", ServeFile::new("web/index.html"))
-        .with_state(state)
-        .layer(CorsLayer::new().allow_origin(Any).allow_methods(Any).allow_headers(Any));
-
-    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    println!("API server listening on http://{}", addr);
-    axum::serve(listener, app)
-        .await
-        .unwrap();
-}
-
-async fn list_satellites() -> impl IntoResponse {
-    let conn = match crate::utils::db::open_or_init() {
-        Ok(c) => c,
-        Err(e) => {
-            return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)})));
-        }
-    };
-
-    let mut stmt = match conn.prepare("SELECT norad_id, name FROM satellites ORDER BY norad_id") {
-        Ok(s) => s,
-        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
-    };
-
-    let rows = stmt
-        .query_map([], |row| {
-            let name: String = row.get::<_, String>(1)?;
-            Ok(SatelliteDto {
-                norad_id: row.get::<_, i64>(0)? as u64,
-                name,
-            })
-        })
-        .and_then(|iter| -> Result<Vec<SatelliteDto>, rusqlite::Error> { Ok(iter.filter_map(Result::ok).collect()) });
-
-    match rows {
-        Ok(v) => (StatusCode::OK, Json(serde_json::json!(v))),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
-    }
-}
-
-async fn get_passes(Query(q): Query<PassQuery>, axum::extract::State(state): axum::extract::State<AppState>) -> impl IntoResponse {
-    let now = chrono::Utc::now();
-    let maybe_el = state.elements.iter().find(|e| e.norad_id == q.norad_id);
-    let el = match maybe_el {
-        Some(e) => e,
-        None => return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "norad_id not found in loaded TLEs"}))),
-    };
-
-    // Resolve ground station coordinates
-    let (lat, lon) = if let Some(id) = q.station_id {
-        match crate::utils::db::open_or_init().and_then(|c| crate::utils::db::get_station(&c, id)) {
-            Ok(st) => (st.lat, st.lon),
-            Err(_) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "station_id not found"}))),
-        }
-    } else if let (Some(lat), Some(lon)) = (q.lat, q.lon) {
-        (lat, lon)
-    } else {
-        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": "missing lat/lon or station_id"})));
-    };
-
-    match predict_passes(el, lat, lon, now, q.duration, q.step, q.min_el) {
-        Ok(wins) => {
-            let out: Vec<PassWindowDto> = wins
-                .into_iter()
-                .map(|w: PassWindow| PassWindowDto {
-                    start: w.start,
-                    end: w.end,
-                    max_elevation_deg: w.max_elevation_deg,
-                })
-                .collect();
-            (StatusCode::OK, Json(serde_json::json!(out)))
-        }
-        Err(e) => (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": format!("prediction error: {}", e)}))),
-    }
-}
-
-async fn get_passes_for_satellite(Path(norad_id): Path<u64>, Query(q): Query<PassQuery>, axum::extract::State(state): axum::extract::State<AppState>) -> impl IntoResponse {
-    let now = chrono::Utc::now();
-    let maybe_el = state.elements.iter().find(|e| e.norad_id == norad_id);
-    let el = match maybe_el {
-        Some(e) => e,
-        None => return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "norad_id not found in loaded TLEs"}))),
-    };
-
-    let (lat, lon) = if let Some(id) = q.station_id {
-        match crate::utils::db::open_or_init().and_then(|c| crate::utils::db::get_station(&c, id)) {
-            Ok(st) => (st.lat, st.lon),
-            Err(_) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "station_id not found"}))),
-        }
-    } else if let (Some(lat), Some(lon)) = (q.lat, q.lon) {
-        (lat, lon)
-    } else {
-        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": "missing lat/lon or station_id"})));
-    };
-
-    match predict_passes(el, lat, lon, now, q.duration, q.step, q.min_el) {
-        Ok(wins) => {
-            let out: Vec<PassWindowDto> = wins
-                .into_iter()
-                .map(|w: PassWindow| PassWindowDto {
-                    start: w.start,
-                    end: w.end,
-                    max_elevation_deg: w.max_elevation_deg,
-                })
-                .collect();
-            (StatusCode::OK, Json(serde_json::json!(out)))
-        }
-        Err(e) => (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": format!("prediction error: {}", e)}))),
-    }
-}
-
-async fn health(axum::extract::State(state): axum::extract::State<AppState>) -> impl IntoResponse {
-    let count = state.elements.len();
-    let db_ok = crate::utils::db::open_or_init().is_ok();
-    (StatusCode::OK, Json(serde_json::json!({ "status": "ok", "elements": count, "db": db_ok })))
-}
-
-async fn list_stations() -> impl IntoResponse {
-    match crate::utils::db::open_or_init().and_then(|c| crate::utils::db::list_stations(&c)) {
-        Ok(stations) => {
-            let out: Vec<StationDto> = stations
-                .into_iter()
-                .map(|s| StationDto { id: s.id, name: s.name, lat: s.lat, lon: s.lon })
-                .collect();
-            (StatusCode::OK, Json(serde_json::json!(out)))
-        }
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
-    }
-}
-
-async fn list_sat_positions(axum::extract::State(state): axum::extract::State<AppState>, Query(q): Query<SatPosQuery>) -> impl IntoResponse {
-    use chrono::Utc;
-    let now = Utc::now();
-    let gmst_rad = gmst(now);
-    let limit = q.limit.unwrap_or(500);
-
-    let mut out = Vec::with_capacity(limit);
-    for e in state.elements.iter().take(limit) {
-        let minutes_since_epoch = minutes_since_elements_epoch(e, now);
-        match sgp4::Constants::from_elements(e).and_then(|c| c.propagate(minutes_since_epoch)) {
-            Ok(pred) => {
-                let (x, y, z) = eci_to_ecef(&pred.position, gmst_rad);
-                let (lat, lon) = ecef_to_geodetic(x, y, z);
-                let speed_km_s = (pred.velocity[0].powi(2) + pred.velocity[1].powi(2) + pred.velocity[2].powi(2)).sqrt();
-                let radius_km = (pred.position[0].powi(2) + pred.position[1].powi(2) + pred.position[2].powi(2)).sqrt();
-                let alt_km = radius_km - 6378.137f64; // equatorial radius
-                out.push(serde_json::json!({
-                    "norad_id": e.norad_id,
-                    "name": e.object_name.clone().unwrap_or_else(|| "".to_string()),
-                    "lat": lat,
-                    "lon": lon,
-                    "alt_km": alt_km,
-                    "speed_km_s": speed_km_s,
-                    "epoch": e.datetime.to_string()
-                }));
-            }
-            Err(_) => {}
-        }
-    }
-    (StatusCode::OK, Json(serde_json::json!(out)))
-}
-
-fn minutes_since_elements_epoch(elements: &sgp4::Elements, t: chrono::DateTime<chrono::Utc>) -> f64 {
-    let epoch = elements.datetime;
-    let t_naive = t.naive_utc();
-    let diff = t_naive - epoch;
-    diff.num_seconds() as f64 / 60.0
-}
-
-fn gmst(t: chrono::DateTime<chrono::Utc>) -> f64 {
-    use chrono::NaiveDate;
-    let j2000_naive = NaiveDate::from_ymd_opt(2000, 1, 1)
-        .unwrap()
-        .and_hms_opt(12, 0, 0)
-        .unwrap();
-    let secs = (t.naive_utc() - j2000_naive).num_seconds() as f64;
-    let days = secs / 86400.0;
-    let gmst_deg = 280.46061837 + 360.98564736629 * days;
-    (gmst_deg.rem_euclid(360.0)) * std::f64::consts::PI / 180.0
-}
-
-fn eci_to_ecef(pos_eci_km: &[f64; 3], gmst_rad: f64) -> (f64, f64, f64) {
-    let (sin_t, cos_t) = gmst_rad.sin_cos();
-    let x_ecef = cos_t * pos_eci_km[0] + sin_t * pos_eci_km[1];
-    let y_ecef = -sin_t * pos_eci_km[0] + cos_t * pos_eci_km[1];
-    let z_ecef = pos_eci_km[2];
-    (x_ecef, y_ecef, z_ecef)
-}
-
-fn ecef_to_geodetic(x: f64, y: f64, z: f64) -> (f64, f64) {
-    // WGS84
-    let a = 6378.137f64; // km
-    let f = 1.0 / 298.257_223_563;
-    let b = a * (1.0 - f);
-    let e2 = f * (2.0 - f);
-    let ep2 = (a*a - b*b) / (b*b);
-    let p = (x*x + y*y).sqrt();
-    let th = (a * z).atan2(b * p);
-    let sin_th = th.sin();
-    let cos_th = th.cos();
-    let lat = (z + ep2 * b * sin_th.powi(3)).atan2(p - e2 * a * cos_th.powi(3));
-    let lon = y.atan2(x);
-    (lat.to_degrees(), lon.to_degrees())
-}
-
-async fn create_station(Json(body): Json<CreateStationDto>) -> impl IntoResponse {
-    // Basic validation
-    if !(body.lat >= -90.0 && body.lat <= 90.0 && body.lon >= -180.0 && body.lon <= 180.0) {
-        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": "lat/lon out of range"})));
-    }
-
-    match crate::utils::db::open_or_init().and_then(|c| {
-        let id = crate::utils::db::insert_station(&c, body.name.as_deref(), body.lat, body.lon)?;
-        Ok::<i64, crate::utils::db::DbError>(id)
-    }) {
-        Ok(id) => (StatusCode::CREATED, Json(serde_json::json!({"id": id}))),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
-    }
-}
-
-async fn get_station(Path(id): Path<i64>) -> impl IntoResponse {
-    match crate::utils::db::open_or_init().and_then(|c| crate::utils::db::get_station(&c, id)) {
-        Ok(s) => (StatusCode::OK, Json(serde_json::json!(StationDto { id: s.id, name: s.name, lat: s.lat, lon: s.lon }))),
-        Err(_) => (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "station not found"}))),
-    }
-}
-
-async fn update_station(Path(id): Path<i64>, Json(body): Json<CreateStationDto>) -> impl IntoResponse {
-    if !(body.lat >= -90.0 && body.lat <= 90.0 && body.lon >= -180.0 && body.lon <= 180.0) {
-        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": "lat/lon out of range"})));
-    }
-    match crate::utils::db::open_or_init().and_then(|c| crate::utils::db::update_station(&c, id, body.name.as_deref(), body.lat, body.lon)) {
-        Ok(()) => (StatusCode::NO_CONTENT, Json(serde_json::json!({}))),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
-    }
-}
-
-async fn delete_station(Path(id): Path<i64>) -> impl IntoResponse {
-    match crate::utils::db::open_or_init().and_then(|c| crate::utils::db::delete_station(&c, id)) {
-        Ok(()) => (StatusCode::NO_CONTENT, Json(serde_json::json!({}))),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
-    }
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+
+use axum::{extract::{Query, Path, Request}, response::{IntoResponse, Response}, routing::{delete, get, post}, Extension, Json, Router};
+use axum::http::StatusCode;
+use tower_http::cors::{CorsLayer, Any};
+use tower::ServiceExt;
+use tower_http::services::{ServeDir, ServeFile};
+use serde::Deserialize;
+// use tracing::info;
+
+use crate::analyzers::catalog_changes::CatalogChanges;
+use crate::api::crewed::CrewedVehicles;
+use crate::api::stream::PositionCache;
+use crate::core::countries::CountryBoundaries;
+use crate::api::pass_cache::PassCache;
+use crate::api::{access, accuracy, aoi, asof, crewed, audit, cache, catalog, config, conjunctions, custom, dashboard, deprecation, devices, export, geo, groundtrack, groups, history, horizon, launches, mobile, negotiate, network, observations, overrides, passes, predict, problem, profile, readonly, region, satellites, stats, stream, trackfile, trains};
+use crate::api::access::Caller;
+use crate::api::types::{PassWindowDto, SatelliteDto, StationDto, CreateStationDto};
+use crate::api::types::{PositionSigmaDto, PropagationErrorDto};
+use crate::collectors::satcat::SatcatFilter;
+use crate::analyzers::query::{SatelliteQuery, Subject};
+use crate::predictors::geo::is_geosynchronous;
+use crate::predictors::passes::{sort_passes, ExclusionMode, Lighting, PassFilter, PassSort, PassWindow, SortOrder};
+use crate::predictors::revolution::orbit_number_at;
+use crate::predictors::uncertainty::PositionSigma;
+use crate::config::PassDefaults;
+use crate::core::catalog::Catalog;
+use crate::core::clock::Clock;
+use crate::core::screening::{ScreenedElement, ScreeningLimits};
+use crate::core::coords::{ecef_to_geodetic, eci_to_ecef, gmst};
+use crate::scheduler::leader::Leadership;
+use crate::scheduler::snapshot_writer::SnapshotQueue;
+use crate::utils::cache::ResponseCache;
+use crate::utils::metrics::PropagationSource;
+use crate::utils::tsdb::{PassEvent, TsdbSink};
+
+#[derive(Clone)]
+pub struct AppState {
+    pub elements: Arc<ArcSwap<Catalog>>, // latest parsed elements, indexed; swapped by the TLE refresh
+    pub uncertainty: Arc<ArcSwap<HashMap<u64, PositionSigma>>>, // per-satellite sigma from TLE history
+    pub tsdb: Option<Arc<TsdbSink>>, // optional time-series mirror (STFCM_TSDB_URL)
+    pub snapshots: Option<SnapshotQueue>, // write-behind snapshot writer; none on read-only instances
+    pub cache: Option<ResponseCache>, // optional Redis response cache (STFCM_REDIS_URL)
+    pub leadership: Arc<Leadership>, // whether this instance runs fetches and maintenance
+    pub clock: Arc<dyn Clock>, // "now" for predictions and streams (STFCM_CLOCK_RATE / STFCM_CLOCK_START)
+    pub screening: Arc<ArcSwap<(ScreeningLimits, Vec<ScreenedElement>)>>, // element sets that failed screening at load
+    pub catalog_events: tokio::sync::broadcast::Sender<CatalogChanges>, // catalog changes of each load, for stream clients
+    pub pass_cache: Arc<PassCache>, // pass predictions reused across requests and reloads (STFCM_PASS_CACHE_*)
+    pub crewed: Arc<CrewedVehicles>, // vehicles of /satellites/crewed and their cached passes (STFCM_CREWED_NORAD_IDS)
+    pub countries: Arc<CountryBoundaries>, // outlines for /satellites/over?country= (STFCM_COUNTRIES_FILE)
+    pub positions: Arc<PositionCache>, // whole-catalog positions shared by requests within a second
+    pub config: Arc<crate::config::Config>, // settings of the current load (stfcm.toml / STFCM_*)
+}
+
+impl AppState {
+    /// The current catalog; a request keeps the one it started with even when
+    /// a refresh swaps in a newer one meanwhile.
+    pub fn catalog(&self) -> Arc<Catalog> {
+        self.elements.load_full()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PassQuery {
+    /// Required by `GET /passes`; the path carries it otherwise.
+    #[serde(default)]
+    norad_id: Option<u64>,
+    #[serde(default)]
+    station_id: Option<i64>,
+    #[serde(default)]
+    lat: Option<f64>,
+    #[serde(default)]
+    lon: Option<f64>,
+    /// Search window, step and elevation mask; the configured pass defaults
+    /// when unset.
+    #[serde(default)]
+    duration: Option<i64>,
+    #[serde(default)]
+    step: Option<i64>,
+    #[serde(default)]
+    min_el: Option<f64>,
+    /// Apply the DEM horizon mask when tiles cover the station.
+    #[serde(default = "default_terrain")]
+    terrain: bool,
+    /// Compare the refracted (apparent) elevation against `min_el`.
+    #[serde(default)]
+    refraction: bool,
+    /// Apply light-time and aberration corrections to look angles.
+    #[serde(default)]
+    light_time: bool,
+    /// How to treat the station's excluded azimuth sectors.
+    #[serde(default)]
+    exclusions: ExclusionMode,
+    /// Join passes separated by at most this many seconds below `min_el`,
+    /// such as a dip behind a horizon mask notch.
+    #[serde(default)]
+    merge_gap: i64,
+    /// Drop passes shorter than this many seconds.
+    #[serde(default)]
+    min_duration: Option<i64>,
+    /// Keep passes whose maximum elevation lies within these bounds (degrees),
+    /// judged after merging.
+    #[serde(default)]
+    min_max_el: Option<f64>,
+    #[serde(default)]
+    max_max_el: Option<f64>,
+    /// `day` or `night` at the station at TCA (civil twilight boundary).
+    #[serde(default)]
+    lighting: Option<Lighting>,
+    /// `start` (default), `max_el`, `duration` or `score`.
+    #[serde(default)]
+    sort: PassSort,
+    #[serde(default)]
+    order: Option<SortOrder>,
+    #[serde(default)]
+    limit: Option<usize>,
+    /// Predict from this time with the archived TLE nearest it instead of now.
+    #[serde(default)]
+    as_of: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl PassQuery {
+    fn params(&self, start: chrono::DateTime<chrono::Utc>, defaults: &PassDefaults) -> passes::PredictionParams {
+        passes::PredictionParams {
+            start,
+            duration_min: self.duration.unwrap_or(defaults.duration_min),
+            step_s: self.step.unwrap_or(defaults.step_s),
+            min_el: self.min_el.unwrap_or(defaults.min_el_deg),
+            terrain: self.terrain,
+            refraction: self.refraction,
+            light_time: self.light_time,
+            exclusion_mode: self.exclusions,
+            merge_gap_s: self.merge_gap,
+            min_peak_el: self.min_max_el,
+        }
+    }
+
+    fn filter(&self) -> PassFilter {
+        PassFilter {
+            min_duration_s: self.min_duration,
+            max_max_elevation_deg: self.max_max_el,
+            lighting: self.lighting,
+        }
+    }
+}
+
+fn default_terrain() -> bool { true }
+
+#[derive(Debug, Deserialize)]
+struct SatPosQuery {
+    #[serde(default)]
+    limit: Option<usize>,
+    /// Positions at this time from the archived TLEs nearest it.
+    #[serde(default)]
+    as_of: Option<chrono::DateTime<chrono::Utc>>,
+    /// SATCAT owner codes, comma-separated.
+    #[serde(default)]
+    country: Option<String>,
+    /// SATCAT object types, comma-separated.
+    #[serde(default)]
+    object_type: Option<String>,
+    /// Filter expression over the enriched catalog, see [`SatelliteQuery`].
+    #[serde(default)]
+    filter: Option<String>,
+    /// Wrap the positions as `{ positions, errors }` to list the satellites
+    /// whose propagation failed.
+    #[serde(default)]
+    with_errors: bool,
+}
+
+/// First path segments owned by the API; unmatched paths below them are API
+/// 404s rather than frontend routes.
+const API_PREFIXES: [&str; 23] = [
+    "api", "health", "stations", "satellites", "geo", "tle", "passes", "conjunctions", "observations", "iod", "ws", "snapshots", "fetch-log",
+    "predict", "custom-elements", "audit-log", "stats", "config", "metrics", "aois", "catalog", "schedule", "launches",
+];
+
+/// Serves frontend files for paths no route matched, falling back to
+/// `index.html` so client-side routes load the app.
+async fn spa_fallback(spa: ServeDir<ServeFile>, req: Request) -> Response {
+    let first = req.uri().path().trim_start_matches('/').split('/').next().unwrap_or("");
+    if API_PREFIXES.contains(&first) {
+        return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "no such endpoint"}))).into_response();
+    }
+    match spa.oneshot(req).await {
+        Ok(res) => res.into_response(),
+        Err(never) => match never {},
+    }
+}
+
+/// Serves the API until `shutdown` completes, then finishes in-flight requests.
+pub async fn run_server(state: AppState, listener: &std::net::TcpListener, shutdown: impl std::future::Future<Output = ()> + Send + 'static) {
+    let web = state.config.server.web_dir.clone();
+    let index = web.join("index.html");
+    let spa = ServeDir::new(&web).fallback(ServeFile::new(&index));
+    let cached = axum::middleware::from_fn_with_state(state.clone(), cache::cached);
+    let api: Router<AppState> = Router::new()
+        .route("/health", get(health))
+        .route("/stations", get(list_stations).post(create_station))
+        .route("/stations/:id", get(get_station).put(update_station).delete(delete_station))
+        .route("/stations/:id/horizon", get(horizon::get_horizon).post(horizon::compute_horizon))
+        .route("/stations/:id/exclusions", get(horizon::get_exclusions).put(horizon::set_exclusions))
+        .route("/stations/:id/favorites", get(dashboard::get_favorites).put(dashboard::set_favorites))
+        .route("/stations/:id/summary", get(dashboard::get_summary))
+        .route("/stations/:id/tokens", get(devices::list_tokens).post(devices::create_token))
+        .route("/stations/:id/tokens/:token_id", delete(devices::revoke_token))
+        .route("/stations/:id/telemetry", get(history::list_station_telemetry).post(devices::report_telemetry))
+        .route("/satellites", get(list_satellites))
+        .route("/satellites/positions", get(list_sat_positions))
+        .route("/geo", get(geo::list_geo))
+        .route("/stats/histograms", get(stats::get_histogram))
+        .route("/stats/counts", get(stats::get_counts))
+        .route("/stats/shells", get(stats::get_shells))
+        .route("/metrics", get(metrics))
+        .route("/ws/positions", get(stream::ws_positions))
+        .route("/satellites/new", get(catalog::list_new_objects))
+        .route("/groups", get(groups::list_groups))
+        .route("/groups/:name", get(groups::get_group).put(groups::put_group).delete(groups::delete_group))
+        .route("/satellites/planes", get(satellites::list_planes))
+        .route("/satellites/crewed", get(crewed::list_crewed))
+        .route("/satellites/over", get(region::list_over_region))
+        .route("/satellites/accuracy", get(accuracy::list_accuracy))
+        .route("/launches", get(launches::list_launches))
+        .route("/launches/:launch", get(launches::get_launch))
+        .route("/tle/upload", post(catalog::upload_tle))
+        .route("/tle/history", get(history::list_tle_history))
+        .route("/tle/screening", get(catalog::get_screening))
+        .route("/catalog/changes", get(catalog::list_changes))
+        .route("/snapshots", get(history::list_snapshots))
+        .route("/snapshots/anomalies", get(history::list_snapshot_anomalies))
+        .route("/fetch-log", get(history::list_fetch_log))
+        .route("/audit-log", get(history::list_audit_log))
+        .route("/export/parquet/:dataset", get(export::export_parquet))
+        .route("/config/export", get(config::export_config))
+        .route("/config/import", post(config::import_config))
+        .route("/passes", get(get_passes).route_layer(cached.clone()))
+        .route("/passes/mobile", post(mobile::mobile_passes))
+        .route("/passes/trackfile", get(trackfile::get_trackfile))
+        .route("/passes/profile", get(profile::get_profile))
+        .route("/passes/trains", get(trains::get_trains))
+        .route("/passes/common", get(network::get_common_visibility))
+        .route("/schedule/handover", get(network::get_handover))
+        .route("/satellites/:norad_id", get(satellites::get_satellite))
+        .route("/satellites/:norad_id/aliases", get(satellites::get_aliases).put(satellites::set_aliases))
+        .route("/satellites/:norad_id/elements", get(overrides::get_overrides).post(overrides::create_override))
+        .route("/satellites/:norad_id/elements/revert", post(overrides::revert_override))
+        .route("/satellites/:norad_id/passes", get(get_passes_for_satellite).route_layer(cached.clone()))
+        .route("/satellites/:norad_id/reentry", get(satellites::get_reentry))
+        .route("/satellites/:norad_id/events", get(satellites::get_events))
+        .route("/satellites/:norad_id/next", get(satellites::get_next))
+        .route("/satellites/:norad_id/groundtrack", get(groundtrack::get_groundtrack).route_layer(cached))
+        .route("/satellites/:norad_id/history", get(history::get_position_history))
+        .route("/satellites/:norad_id/accuracy", get(accuracy::get_accuracy))
+        .route("/satellites/:norad_id/tle/history", get(history::get_tle_history_range))
+        .route("/conjunctions", get(conjunctions::list_conjunctions))
+        .route("/conjunctions/screen", post(conjunctions::trigger_screening))
+        .route("/conjunctions/assets", get(conjunctions::list_assets).post(conjunctions::create_asset))
+        .route("/conjunctions/assets/:norad_id", delete(conjunctions::delete_asset))
+        .route("/observations", get(observations::list_observations).post(observations::create_observation))
+        .route("/observations/:id", delete(observations::delete_observation))
+        .route("/iod", post(observations::run_iod))
+        .route("/iod/identify", post(observations::identify_objects))
+        .route("/predict/passes", post(predict::predict_passes))
+        .route("/predict/position", post(predict::predict_position))
+        .route("/predict/compare", post(predict::compare_ephemerides))
+        .route("/custom-elements", get(custom::list_custom_elements).post(custom::create_custom_elements))
+        .route(
+            "/custom-elements/:id",
+            get(custom::get_custom_elements).put(custom::update_custom_elements).delete(custom::delete_custom_elements),
+        )
+        .route("/aois", get(aoi::list_aois).post(aoi::create_aoi))
+        .route("/aois/:id", get(aoi::get_aoi).put(aoi::update_aoi).delete(aoi::delete_aoi))
+        .route("/aois/:id/access", get(aoi::get_access));
+    let api = if crate::utils::db::read_only() {
+        api.layer(axum::middleware::from_fn(readonly::reject_writes))
+    } else {
+        api.layer(axum::middleware::from_fn(audit::record))
+    };
+    // Outermost: an unauthenticated write gets 401 rather than the read-only 405
+    let api = match access::ApiKeys::from_env() {
+        Some(keys) => api.layer(axum::middleware::from_fn_with_state(keys, access::authorize)),
+        None => api,
+    };
+    let legacy = api.clone().layer(axum::middleware::from_fn_with_state(deprecation::legacy_api(), deprecation::deprecated));
+
+    let app = Router::new()
+        .nest("/api/v1", api)
+        .merge(legacy)
+        .nest_service("/ui", spa.clone())
+        .route_service("/", ServeFile::new(&index))
+        .fallback(move |req: Request| spa_fallback(spa.clone(), req))
+        .with_state(state)
+        // Inside negotiation, so problem+json errors are not re-encoded as CSV
+        .layer(axum::middleware::from_fn(problem::problem_json))
+        .layer(axum::middleware::from_fn(negotiate::negotiate))
+        .layer(CorsLayer::new().allow_origin(Any).allow_methods(Any).allow_headers(Any).expose_headers([problem::REQUEST_ID.clone()]))
+        .layer(axum::middleware::from_fn(problem::request_id));
+
+    // Bound once at startup and kept across reloads; the warm-up server used it until now
+    let listener = tokio::net::TcpListener::from_std(listener.try_clone().expect("failed to share the listener")).expect("failed to register the listener");
+    // Elements are loaded by now, so systemd may consider the service up.
+    crate::utils::sd_notify::ready();
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown)
+        .await
+        .unwrap();
+}
+
+#[derive(Debug, Deserialize)]
+struct SatelliteSearchQuery {
+    /// Case-insensitive substring of the catalog name, display name or an alias,
+    /// or a NORAD ID or international designator.
+    #[serde(default)]
+    q: Option<String>,
+    /// SATCAT owner codes, comma-separated.
+    #[serde(default)]
+    country: Option<String>,
+    /// SATCAT object types, comma-separated.
+    #[serde(default)]
+    object_type: Option<String>,
+    /// Filter expression over the enriched catalog, see [`SatelliteQuery`].
+    #[serde(default)]
+    filter: Option<String>,
+}
+
+/// Parses the `filter` query parameter; an empty one is no filter.
+fn parse_query(filter: Option<&str>) -> Result<Option<SatelliteQuery>, String> {
+    filter.map(str::trim).filter(|f| !f.is_empty()).map(|f| SatelliteQuery::parse(f).map_err(|e| format!("invalid filter: {}", e))).transpose()
+}
+
+async fn list_satellites(Query(search): Query<SatelliteSearchQuery>, axum::extract::State(state): axum::extract::State<AppState>) -> impl IntoResponse {
+    let filter = match SatcatFilter::parse(search.country.as_deref(), search.object_type.as_deref()) {
+        Ok(f) => f,
+        Err(e) => return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": e}))),
+    };
+    let query = match parse_query(search.filter.as_deref()) {
+        Ok(q) => q,
+        Err(e) => return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": e}))),
+    };
+    let now = state.clock.now();
+    let conn = match crate::utils::db::open_or_init() {
+        Ok(c) => c,
+        Err(e) => {
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)})));
+        }
+    };
+    let mut aliases = match crate::utils::db::list_satellite_aliases(&conn) {
+        Ok(a) => a,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    };
+    let mut satcat = match crate::utils::db::satcat_map(&conn) {
+        Ok(m) => m,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    };
+    let needle = search.q.map(|q| q.trim().to_lowercase()).filter(|q| !q.is_empty());
+    let catalog = state.catalog();
+    let designated = needle.as_deref().and_then(|q| catalog.by_designator(q)).map(|e| e.norad_id);
+
+    let mut stmt = match conn.prepare("SELECT norad_id, name FROM satellites ORDER BY norad_id") {
+        Ok(s) => s,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    };
+
+    let rows = stmt
+        .query_map([], |row| {
+            let name: String = row.get::<_, String>(1)?;
+            Ok((row.get::<_, i64>(0)? as u64, name))
+        })
+        .map(|iter| {
+            iter.filter_map(Result::ok)
+                .filter_map(|(norad_id, name)| {
+                    let meta = satcat.remove(&norad_id);
+                    if filter.as_ref().is_some_and(|f| !f.matches(meta.as_ref())) {
+                        return None;
+                    }
+                    if let Some(query) = &query {
+                        let index = catalog.index_of(norad_id);
+                        let subject = Subject {
+                            norad_id,
+                            name: Some(&name),
+                            designator: index.and_then(|i| catalog.designator(i)),
+                            elements: index.map(|i| &catalog[i]),
+                            satcat: meta.as_ref(),
+                        };
+                        if !query.matches(&subject, now) {
+                            return None;
+                        }
+                    }
+                    let names = aliases.remove(&norad_id).unwrap_or_default();
+                    if let Some(needle) = &needle {
+                        let hit = norad_id.to_string() == *needle
+                            || designated == Some(norad_id)
+                            || name.to_lowercase().contains(needle.as_str())
+                            || names.matches(needle);
+                        if !hit {
+                            return None;
+                        }
+                    }
+                    let (country, object_type) = meta.map(|m| (m.owner, m.object_type)).unzip();
+                    Some(SatelliteDto { norad_id, name, display_name: names.display_name, aliases: names.aliases, country, object_type })
+                })
+                .collect::<Vec<SatelliteDto>>()
+        });
+
+    match rows {
+        Ok(v) => (StatusCode::OK, Json(serde_json::json!(v))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+async fn get_passes(Query(q): Query<PassQuery>, caller: Option<Extension<Caller>>, axum::extract::State(state): axum::extract::State<AppState>) -> impl IntoResponse {
+    let Some(norad_id) = q.norad_id else {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": "missing norad_id"})));
+    };
+    pass_response(&state, norad_id, &q, caller.as_deref()).await
+}
+
+async fn get_passes_for_satellite(
+    Path(norad_id): Path<u64>,
+    Query(q): Query<PassQuery>,
+    caller: Option<Extension<Caller>>,
+    axum::extract::State(state): axum::extract::State<AppState>,
+) -> impl IntoResponse {
+    pass_response(&state, norad_id, &q, caller.as_deref()).await
+}
+
+/// `GET /passes` and `GET /satellites/{noradId}/passes`: filtered, sorted and
+/// truncated passes, or the constant look angle of a GEO object.
+async fn pass_response(state: &AppState, norad_id: u64, q: &PassQuery, caller: Option<&Caller>) -> (StatusCode, Json<serde_json::Value>) {
+    let el = match asof::element_set(state, norad_id, q.as_of) {
+        Ok(e) => e,
+        Err(response) => return response,
+    };
+    let observer = match passes::resolve_observer(q.station_id, q.lat, q.lon, None, caller) {
+        Ok(o) => o,
+        Err(response) => return response,
+    };
+    let params = q.params(q.as_of.unwrap_or_else(|| state.clock.now()), &state.config.passes);
+    let horizon = passes::observer_horizon(&observer, params.terrain).await;
+    match passes::run_prediction(&el, el.name(), &observer, horizon.as_deref(), &params, Some(&state.pass_cache)) {
+        Ok(passes::Prediction::Geo(look)) => (StatusCode::OK, Json(serde_json::json!(look))),
+        Ok(passes::Prediction::Passes(mut wins)) => {
+            let filter = q.filter();
+            wins.retain(|w| filter.accepts(w, &observer.position));
+            mirror_passes(state, norad_id, q.station_id, &wins);
+            sort_passes(&mut wins, q.sort, q.order);
+            wins.truncate(q.limit.unwrap_or(usize::MAX));
+            let out: Vec<PassWindowDto> = wins.into_iter().map(PassWindowDto::from).collect();
+            (StatusCode::OK, Json(serde_json::json!(out)))
+        }
+        Err(response) => response,
+    }
+}
+
+/// Mirrors passes predicted for a stored station to the time-series database, if any,
+/// without delaying the response.
+fn mirror_passes(state: &AppState, norad_id: u64, station_id: Option<i64>, wins: &[PassWindow]) {
+    let (Some(sink), Some(station_id)) = (state.tsdb.clone(), station_id) else {
+        return;
+    };
+    let events: Vec<PassEvent> = wins
+        .iter()
+        .map(|w| PassEvent { norad_id, station_id, start: w.start, tca: w.tca, end: w.end, max_elevation_deg: w.max_elevation_deg })
+        .collect();
+    tokio::spawn(async move {
+        if let Err(e) = sink.write_passes(&events).await {
+            tracing::warn!(error = %e, "Failed to mirror passes to time-series database");
+        }
+    });
+}
+
+/// Counters for Prometheus, in its text exposition format.
+async fn metrics() -> impl IntoResponse {
+    ([(axum::http::header::CONTENT_TYPE, "text/plain; version=0.0.4")], crate::utils::metrics::render())
+}
+
+async fn health(axum::extract::State(state): axum::extract::State<AppState>) -> impl IntoResponse {
+    let count = state.catalog().len();
+    let db_ok = crate::utils::db::open_or_init().is_ok();
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "status": "ok",
+            "elements": count,
+            "db": db_ok,
+            "instance": state.leadership.instance_id(),
+            "leader": state.leadership.is_leader(),
+            "read_only": crate::utils::db::read_only(),
+        })),
+    )
+}
+
+async fn list_stations(caller: Option<Extension<Caller>>) -> impl IntoResponse {
+    match crate::utils::db::open_or_init().and_then(|c| crate::utils::db::list_stations(&c)) {
+        Ok(stations) => {
+            let out: Vec<StationDto> = stations
+                .into_iter()
+                .filter(|s| caller.as_deref().is_none_or(|c| c.can_access(s)))
+                .map(StationDto::from)
+                .collect();
+            (StatusCode::OK, Json(serde_json::json!(out)))
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+async fn list_sat_positions(axum::extract::State(state): axum::extract::State<AppState>, Query(q): Query<SatPosQuery>) -> Response {
+    let now = q.as_of.unwrap_or_else(|| state.clock.now());
+    let gmst_rad = gmst(now);
+    let limit = q.limit.unwrap_or(500);
+    let archived = match q.as_of.map(|t| asof::archived_elements(t, None).map(Catalog::new)).transpose() {
+        Ok(a) => a,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))).into_response(),
+    };
+    let (current, uncertainty) = (state.catalog(), state.uncertainty.load());
+    let elements: &Catalog = archived.as_ref().unwrap_or(&current);
+    let filter = match SatcatFilter::parse(q.country.as_deref(), q.object_type.as_deref()) {
+        Ok(f) => f,
+        Err(e) => return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": e}))).into_response(),
+    };
+    let query = match parse_query(q.filter.as_deref()) {
+        Ok(q) => q,
+        Err(e) => return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": e}))).into_response(),
+    };
+    // The SATCAT is only read when a filter needs it
+    let satcat = if filter.is_some() || query.as_ref().is_some_and(|q| q.needs_satcat()) {
+        match crate::utils::db::open_or_init().and_then(|c| crate::utils::db::satcat_map(&c)) {
+            Ok(satcat) => satcat,
+            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))).into_response(),
+        }
+    } else {
+        HashMap::new()
+    };
+    let selected = elements.iter().enumerate().filter(|(i, e)| {
+        let meta = satcat.get(&e.norad_id);
+        filter.as_ref().is_none_or(|f| f.matches(meta)) && query.as_ref().is_none_or(|q| q.matches(&Subject::in_catalog(elements, *i, meta), now))
+    });
+
+    let mut out = Vec::with_capacity(limit);
+    let mut errors = Vec::new();
+    for (i, e) in selected.take(limit) {
+        match elements.propagate(i, now) {
+            Ok(pred) => {
+                let (x, y, z) = eci_to_ecef(&pred.position, gmst_rad);
+                let (lat, lon) = ecef_to_geodetic(x, y, z);
+                let speed_km_s = (pred.velocity[0].powi(2) + pred.velocity[1].powi(2) + pred.velocity[2].powi(2)).sqrt();
+                let radius_km = (pred.position[0].powi(2) + pred.position[1].powi(2) + pred.position[2].powi(2)).sqrt();
+                let alt_km = radius_km - crate::core::orbit::EARTH_RADIUS_KM;
+                out.push(serde_json::json!({
+                    "norad_id": e.norad_id,
+                    "name": elements.name(i).unwrap_or(""),
+                    "lat": lat,
+                    "lon": lon,
+                    "alt_km": alt_km,
+                    "speed_km_s": speed_km_s,
+                    "orbit_number": orbit_number_at(e, now, &pred.position, &pred.velocity),
+                    "epoch": e.datetime.to_string(),
+                    // Sigmas describe the current element sets only.
+                    "sigma": if archived.is_none() { uncertainty.get(&e.norad_id).map(PositionSigmaDto::from) } else { None },
+                    "geo": if is_geosynchronous(e) { geo::geo_object(e, elements.name(i), now) } else { None }
+                }));
+            }
+            Err(err) => errors.push(PropagationErrorDto { norad_id: e.norad_id, reason: err.to_string() }),
+        }
+    }
+    crate::utils::metrics::record_propagation_errors(PropagationSource::Positions, errors.len());
+    let header = [("x-propagation-errors", errors.len().to_string())];
+    if q.with_errors {
+        (StatusCode::OK, header, Json(serde_json::json!({"positions": out, "errors": errors}))).into_response()
+    } else {
+        (StatusCode::OK, header, Json(serde_json::json!(out))).into_response()
+    }
+}
+
+async fn create_station(caller: Option<Extension<Caller>>, Json(body): Json<CreateStationDto>) -> impl IntoResponse {
+    let (lat, lon, alt_m) = match body.geodetic() {
+        Ok(p) => p,
+        Err(msg) => return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": msg}))),
+    };
+
+    match crate::utils::db::open_or_init().and_then(|c| {
+        let tenant = caller.as_deref().and_then(|c| c.tenant.as_deref());
+        let id = crate::utils::db::insert_station(&c, body.name.as_deref(), lat, lon, alt_m, tenant)?;
+        Ok::<i64, crate::utils::db::DbError>(id)
+    }) {
+        Ok(id) => (StatusCode::CREATED, Json(serde_json::json!({"id": id}))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+async fn get_station(caller: Option<Extension<Caller>>, Path(id): Path<i64>) -> impl IntoResponse {
+    if !access::station_visible(caller.as_deref(), id) {
+        return access::station_not_found();
+    }
+    match crate::utils::db::open_or_init().and_then(|c| crate::utils::db::get_station(&c, id)) {
+        Ok(s) => (StatusCode::OK, Json(serde_json::json!(StationDto::from(s)))),
+        Err(_) => (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "station not found"}))),
+    }
+}
+
+async fn update_station(caller: Option<Extension<Caller>>, Path(id): Path<i64>, Json(body): Json<CreateStationDto>) -> impl IntoResponse {
+    if !access::station_visible(caller.as_deref(), id) {
+        return access::station_not_found();
+    }
+    let (lat, lon, alt_m) = match body.geodetic() {
+        Ok(p) => p,
+        Err(msg) => return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": msg}))),
+    };
+    match crate::utils::db::open_or_init().and_then(|c| crate::utils::db::update_station(&c, id, body.name.as_deref(), lat, lon, alt_m)) {
+        Ok(()) => (StatusCode::NO_CONTENT, Json(serde_json::json!({}))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
+}
+
+async fn delete_station(caller: Option<Extension<Caller>>, Path(id): Path<i64>) -> impl IntoResponse {
+    if !access::station_visible(caller.as_deref(), id) {
+        return access::station_not_found();
+    }
+    match crate::utils::db::open_or_init().and_then(|c| crate::utils::db::delete_station(&c, id)) {
+        Ok(()) => (StatusCode::NO_CONTENT, Json(serde_json::json!({}))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    }
 }
\ No newline at end of file
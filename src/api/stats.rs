@@ -0,0 +1,149 @@
+use std::collections::{BTreeMap, HashMap};
+
+use axum::{extract::{Query, State}, response::IntoResponse, Json};
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use crate::analyzers::population::{histogram, Field};
+use crate::analyzers::shells::{MAX_SHELL_KM, SHELL_WIDTH_KM};
+use crate::api::server::AppState;
+use crate::api::types::{GroupCountDto, GroupCountsDto, HistogramDto, ShellFetchDto, ShellHistoryDto};
+
+#[derive(Debug, Deserialize)]
+pub struct HistogramQuery {
+    field: Field,
+    #[serde(default = "default_bins")]
+    bins: usize,
+    /// Range to bin; defaults to the catalog's extent.
+    #[serde(default)]
+    min: Option<f64>,
+    #[serde(default)]
+    max: Option<f64>,
+}
+
+fn default_bins() -> usize { 50 }
+/// Most bins one histogram may have.
+const MAX_BINS: usize = 1000;
+
+/// Distribution of the loaded catalog over altitude or inclination, e.g. for
+/// LEO shell occupancy plots.
+pub async fn get_histogram(Query(q): Query<HistogramQuery>, State(state): State<AppState>) -> impl IntoResponse {
+    if !(1..=MAX_BINS).contains(&q.bins) {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": format!("bins must be within 1..={}", MAX_BINS)})));
+    }
+    if let (Some(min), Some(max)) = (q.min, q.max) {
+        if max <= min {
+            return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": "max must be greater than min"})));
+        }
+    }
+    let values: Vec<f64> = state.catalog().iter().map(|el| q.field.value(el)).collect();
+    let h = histogram(&values, q.bins, q.min, q.max);
+    let width = h.bin_width();
+    let dto = HistogramDto {
+        field: q.field,
+        unit: q.field.unit(),
+        total: values.len(),
+        min: h.min,
+        max: h.max,
+        bin_width: width,
+        edges: (0..=h.counts.len()).map(|i| h.min + width * i as f64).collect(),
+        counts: h.counts,
+        below: h.below,
+        above: h.above,
+    };
+    (StatusCode::OK, Json(serde_json::json!(dto)))
+}
+
+/// SATCAT attribute the loaded catalog is counted by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupBy {
+    /// Owner code, e.g. `US`, `PRC` or `ESA`.
+    Country,
+    ObjectType,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CountsQuery {
+    group_by: GroupBy,
+}
+
+/// Loaded satellites per owner or object type from the cached SATCAT.
+pub async fn get_counts(Query(q): Query<CountsQuery>, State(state): State<AppState>) -> impl IntoResponse {
+    let satcat = match crate::utils::db::open_or_init().and_then(|c| crate::utils::db::satcat_map(&c)) {
+        Ok(m) => m,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    };
+    let mut groups: HashMap<&str, usize> = HashMap::new();
+    let mut unmatched = 0;
+    for el in state.catalog().iter() {
+        match satcat.get(&el.norad_id) {
+            Some(entry) => {
+                let key = match q.group_by {
+                    GroupBy::Country => entry.owner.as_str(),
+                    GroupBy::ObjectType => entry.object_type.as_str(),
+                };
+                *groups.entry(key).or_default() += 1;
+            }
+            None => unmatched += 1,
+        }
+    }
+    let mut counts: Vec<GroupCountDto> = groups.into_iter().map(|(key, count)| GroupCountDto { key: key.to_string(), count }).collect();
+    counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
+    let dto = GroupCountsDto { group_by: q.group_by, total: state.catalog().len(), counts, unmatched };
+    (StatusCode::OK, Json(serde_json::json!(dto)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ShellQuery {
+    #[serde(default = "default_days")]
+    days: i64,
+    /// Shell width (km), a multiple of the stored 50 km shells.
+    #[serde(default = "default_width")]
+    width: u32,
+    #[serde(default)]
+    min_alt: u32,
+    #[serde(default = "default_max_alt")]
+    max_alt: u32,
+}
+
+fn default_days() -> i64 { 30 }
+fn default_width() -> u32 { SHELL_WIDTH_KM }
+fn default_max_alt() -> u32 { MAX_SHELL_KM }
+/// Longest look-back of the shell history.
+const MAX_DAYS: i64 = 3650;
+
+/// Objects per altitude shell at each catalog fetch of the last `days`, for
+/// charting how crowded the LEO shells become.
+pub async fn get_shells(Query(q): Query<ShellQuery>) -> impl IntoResponse {
+    if !(1..=MAX_DAYS).contains(&q.days) {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": format!("days must be within [1, {}]", MAX_DAYS)})));
+    }
+    if q.width == 0 || q.width % SHELL_WIDTH_KM != 0 {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": format!("width must be a positive multiple of {} km", SHELL_WIDTH_KM)})));
+    }
+    if q.min_alt >= q.max_alt.min(MAX_SHELL_KM) {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": format!("min_alt must be below max_alt and {} km", MAX_SHELL_KM)})));
+    }
+    let since = (chrono::Utc::now() - chrono::Duration::days(q.days)).to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    let rows = match crate::utils::db::open_or_init().and_then(|c| crate::utils::db::list_shell_counts(&c, &since)) {
+        Ok(rows) => rows,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("db error: {}", e)}))),
+    };
+    // Shells of `width` whose lower edge lies in [min_alt, max_alt)
+    let first = q.min_alt / q.width;
+    let shells: Vec<u32> = (first..).map(|i| i * q.width).take_while(|&km| km < q.max_alt.min(MAX_SHELL_KM)).collect();
+    let mut fetches: BTreeMap<String, Vec<u64>> = BTreeMap::new();
+    for row in rows {
+        let counts = fetches.entry(row.fetched_at).or_insert_with(|| vec![0; shells.len()]);
+        if let Some(i) = (row.shell_km / q.width).checked_sub(first).filter(|&i| (i as usize) < shells.len()) {
+            counts[i as usize] += row.count;
+        }
+    }
+    let dto = ShellHistoryDto {
+        width_km: q.width,
+        shells_km: shells,
+        fetches: fetches.into_iter().map(|(fetched_at, counts)| ShellFetchDto { fetched_at, total: counts.iter().sum(), counts }).collect(),
+    };
+    (StatusCode::OK, Json(serde_json::json!(dto)))
+}
@@ -0,0 +1,526 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Query, State};
+use axum::Extension;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+use crate::api::access::Caller;
+use crate::api::server::AppState;
+use crate::core::catalog::Catalog;
+use crate::core::clock::{parse_replay, Clock, SimulatedClock};
+use crate::core::coords::{ecef_to_geodetic, eci_to_ecef, gmst};
+use crate::core::orbit::EARTH_RADIUS_KM;
+use crate::predictors::passes::{topocentric_look_deg, ObserverPosition};
+
+/// One satellite in a stream frame. Mirrors `Position` in `proto/positions.proto`.
+#[derive(Clone, PartialEq, prost::Message, Serialize)]
+pub struct Position {
+    #[prost(uint32, tag = "1")]
+    pub norad_id: u32,
+    #[prost(float, tag = "2")]
+    pub lat: f32,
+    #[prost(float, tag = "3")]
+    pub lon: f32,
+    #[prost(float, tag = "4")]
+    pub alt_km: f32,
+    #[prost(float, tag = "5")]
+    pub speed_km_s: f32,
+}
+
+/// One stream frame. Mirrors `PositionBatch` in `proto/positions.proto`.
+#[derive(Clone, PartialEq, prost::Message, Serialize)]
+pub struct PositionBatch {
+    #[prost(int64, tag = "1")]
+    pub timestamp_ms: i64,
+    #[prost(message, repeated, tag = "2")]
+    pub positions: Vec<Position>,
+}
+
+/// Quantized absolute position in a keyframe. Mirrors `QuantizedPosition` in
+/// `proto/positions.proto`; units are given by the `*_SCALE` constants.
+#[derive(Clone, PartialEq, prost::Message, Serialize)]
+pub struct QuantizedPosition {
+    #[prost(uint32, tag = "1")]
+    pub norad_id: u32,
+    #[prost(sint32, tag = "2")]
+    pub lat: i32,
+    #[prost(sint32, tag = "3")]
+    pub lon: i32,
+    #[prost(sint32, tag = "4")]
+    pub alt: i32,
+    #[prost(sint32, tag = "5")]
+    pub speed: i32,
+}
+
+/// Change of one satellite since the previous frame; unchanged fields are
+/// omitted. Mirrors `PositionDelta` in `proto/positions.proto`.
+#[derive(Clone, PartialEq, prost::Message, Serialize)]
+pub struct PositionDelta {
+    #[prost(uint32, tag = "1")]
+    pub norad_id: u32,
+    #[prost(sint32, optional, tag = "2")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub d_lat: Option<i32>,
+    #[prost(sint32, optional, tag = "3")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub d_lon: Option<i32>,
+    #[prost(sint32, optional, tag = "4")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub d_alt: Option<i32>,
+    #[prost(sint32, optional, tag = "5")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub d_speed: Option<i32>,
+}
+
+/// Keyframe (every satellite in `positions`) or incremental update (`deltas`,
+/// plus `positions` for satellites that started streaming and `removed` for
+/// those that stopped). Mirrors `DeltaFrame` in `proto/positions.proto`.
+#[derive(Clone, PartialEq, prost::Message, Serialize)]
+pub struct DeltaFrame {
+    #[prost(int64, tag = "1")]
+    pub timestamp_ms: i64,
+    #[prost(bool, tag = "2")]
+    pub keyframe: bool,
+    #[prost(message, repeated, tag = "3")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub positions: Vec<QuantizedPosition>,
+    #[prost(message, repeated, tag = "4")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub deltas: Vec<PositionDelta>,
+    /// Satellites no longer streamed (e.g. left a subscribed bounding box).
+    #[prost(uint32, repeated, tag = "5")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed: Vec<u32>,
+}
+
+/// Quantization steps: 1e-4° (about 11 m) for lat/lon, 10 m for altitude and
+/// 1 m/s for speed.
+const LAT_LON_SCALE: f64 = 1e4;
+const ALT_KM_SCALE: f64 = 100.0;
+const SPEED_KM_S_SCALE: f64 = 1000.0;
+/// Quantized units in a full turn of longitude.
+const LON_TURN: i32 = (360.0 * LAT_LON_SCALE) as i32;
+
+fn quantize(p: &Position) -> [i32; 4] {
+    [
+        (p.lat as f64 * LAT_LON_SCALE).round() as i32,
+        (p.lon as f64 * LAT_LON_SCALE).round() as i32,
+        (p.alt_km as f64 * ALT_KM_SCALE).round() as i32,
+        (p.speed_km_s as f64 * SPEED_KM_S_SCALE).round() as i32,
+    ]
+}
+
+/// Turns successive full batches into a keyframe followed by per-satellite
+/// deltas, with a fresh keyframe every `keyframe_every` frames. Longitude deltas
+/// take the short way across the antimeridian, so clients wrap the sum back into ±180°.
+pub struct DeltaEncoder {
+    last: HashMap<u32, [i32; 4]>,
+    keyframe_every: u32,
+    since_keyframe: Option<u32>,
+}
+
+impl DeltaEncoder {
+    pub fn new(keyframe_every: u32) -> Self {
+        DeltaEncoder { last: HashMap::new(), keyframe_every: keyframe_every.max(1), since_keyframe: None }
+    }
+
+    pub fn encode(&mut self, batch: &PositionBatch) -> DeltaFrame {
+        let current: HashMap<u32, [i32; 4]> = batch.positions.iter().map(|p| (p.norad_id, quantize(p))).collect();
+        let keyframe = self.since_keyframe.is_none_or(|n| n + 1 >= self.keyframe_every);
+        let absolute = |norad_id: u32, [lat, lon, alt, speed]: [i32; 4]| QuantizedPosition { norad_id, lat, lon, alt, speed };
+
+        let mut frame = DeltaFrame {
+            timestamp_ms: batch.timestamp_ms,
+            keyframe,
+            positions: Vec::new(),
+            deltas: Vec::new(),
+            removed: Vec::new(),
+        };
+        if keyframe {
+            self.since_keyframe = Some(0);
+            frame.positions = batch.positions.iter().map(|p| absolute(p.norad_id, current[&p.norad_id])).collect();
+        } else {
+            self.since_keyframe = self.since_keyframe.map(|n| n + 1);
+            for p in &batch.positions {
+                let q = current[&p.norad_id];
+                let Some(&prev) = self.last.get(&p.norad_id) else {
+                    frame.positions.push(absolute(p.norad_id, q));
+                    continue;
+                };
+                if q == prev {
+                    continue;
+                }
+                let changed = |d: i32| (d != 0).then_some(d);
+                let d_lon = (q[1] - prev[1] + LON_TURN / 2).rem_euclid(LON_TURN) - LON_TURN / 2;
+                frame.deltas.push(PositionDelta {
+                    norad_id: p.norad_id,
+                    d_lat: changed(q[0] - prev[0]),
+                    d_lon: changed(d_lon),
+                    d_alt: changed(q[2] - prev[2]),
+                    d_speed: changed(q[3] - prev[3]),
+                });
+            }
+            frame.removed = self.last.keys().filter(|id| !current.contains_key(id)).copied().collect();
+            frame.removed.sort_unstable();
+        }
+        self.last = current;
+        frame
+    }
+}
+
+/// What a client asked to receive; every criterion given must match. Sent as a
+/// text frame `{"type": "subscribe", ...}`; `{"type": "unsubscribe"}` goes back
+/// to the whole catalog.
+#[derive(Debug, Default, Deserialize)]
+pub struct SubscribeRequest {
+    #[serde(default)]
+    norad_ids: Vec<u64>,
+    /// `[min_lon, min_lat, max_lon, max_lat]`; `min_lon > max_lon` spans the antimeridian.
+    #[serde(default)]
+    bbox: Option<[f64; 4]>,
+    /// Satellite tag, e.g. `new`: the caller's tenant's own, or else the shared one.
+    #[serde(default)]
+    group: Option<String>,
+    /// Only satellites above `min_el` as seen from this station, which must
+    /// belong to the caller's tenant.
+    #[serde(default)]
+    station_id: Option<i64>,
+    #[serde(default)]
+    min_el: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientMessage {
+    Subscribe(SubscribeRequest),
+    Unsubscribe,
+}
+
+/// A resolved subscription. The default admits everything.
+#[derive(Debug, Default)]
+pub struct StreamFilter {
+    ids: Option<HashSet<u64>>,
+    bbox: Option<[f64; 4]>,
+    overhead: Option<(ObserverPosition, f64)>,
+}
+
+impl StreamFilter {
+    /// Looks up the group members and station a request refers to, as seen by
+    /// `caller`.
+    pub fn resolve(req: SubscribeRequest, caller: Option<&Caller>) -> Result<Self, String> {
+        if let Some([min_lon, min_lat, max_lon, max_lat]) = req.bbox {
+            if min_lat > max_lat || ![min_lon, max_lon].iter().all(|l| (-180.0..=180.0).contains(l)) {
+                return Err("bbox must be [min_lon, min_lat, max_lon, max_lat] in degrees".to_string());
+            }
+        }
+        let mut ids: Option<HashSet<u64>> = (!req.norad_ids.is_empty()).then(|| req.norad_ids.into_iter().collect());
+        let mut overhead = None;
+        if req.group.is_some() || req.station_id.is_some() {
+            let conn = crate::utils::db::open_or_init().map_err(|e| format!("db error: {}", e))?;
+            if let Some(group) = req.group {
+                let tenant = caller.and_then(|c| c.tenant.as_deref());
+                let members: HashSet<u64> = crate::utils::db::tag_owner(&conn, &group, tenant)
+                    .and_then(|owner| crate::utils::db::tagged_norad_ids(&conn, &group, owner.as_deref()))
+                    .map_err(|e| format!("db error: {}", e))?
+                    .into_iter()
+                    .collect();
+                ids = Some(match ids {
+                    Some(ids) => ids.intersection(&members).copied().collect(),
+                    None => members,
+                });
+            }
+            if let Some(id) = req.station_id {
+                let st = crate::utils::db::get_station(&conn, id)
+                    .ok()
+                    .filter(|st| caller.is_none_or(|c| c.can_access(st)))
+                    .ok_or_else(|| "station_id not found".to_string())?;
+                overhead = Some((ObserverPosition { lat_deg: st.lat, lon_deg: st.lon, alt_km: st.alt_m / 1000.0 }, req.min_el));
+            }
+        }
+        Ok(StreamFilter { ids, bbox: req.bbox, overhead })
+    }
+
+    fn admits_id(&self, norad_id: u64) -> bool {
+        self.ids.as_ref().is_none_or(|ids| ids.contains(&norad_id))
+    }
+
+    fn admits_position(&self, pos_eci_km: &[f64; 3], gmst_rad: f64, lat: f64, lon: f64) -> bool {
+        let in_bbox = self.bbox.is_none_or(|[min_lon, min_lat, max_lon, max_lat]| {
+            let lon_ok = if min_lon <= max_lon { (min_lon..=max_lon).contains(&lon) } else { lon >= min_lon || lon <= max_lon };
+            lon_ok && (min_lat..=max_lat).contains(&lat)
+        });
+        in_bbox && self.overhead.as_ref().is_none_or(|(obs, min_el)| topocentric_look_deg(pos_eci_km, gmst_rad, obs).0 >= *min_el)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StreamFormat {
+    #[default]
+    Json,
+    Protobuf,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StreamQuery {
+    #[serde(default)]
+    format: StreamFormat,
+    /// Seconds between frames, clamped to 0.2–60.
+    #[serde(default = "default_interval")]
+    interval: f64,
+    #[serde(default)]
+    limit: Option<usize>,
+    /// Send quantized keyframes and deltas instead of full batches.
+    #[serde(default)]
+    delta: bool,
+    /// Frames between keyframes in delta mode.
+    #[serde(default = "default_keyframe_every")]
+    keyframe_every: u32,
+    /// Replay speed for this connection, e.g. `60` for one simulated minute per second.
+    #[serde(default)]
+    rate: Option<String>,
+    /// Simulated time (RFC 3339) of the first frame when replaying.
+    #[serde(default)]
+    start: Option<String>,
+    /// Also send a `catalog_changes` text frame whenever a load changes the catalog.
+    #[serde(default)]
+    catalog_events: bool,
+}
+
+fn default_interval() -> f64 { 1.0 }
+fn default_keyframe_every() -> u32 { 30 }
+
+impl StreamQuery {
+    /// Time between frames; `NaN` and infinities are rejected.
+    fn period(&self) -> Result<Duration, String> {
+        if !self.interval.is_finite() {
+            return Err("interval must be a number of seconds".to_string());
+        }
+        Ok(Duration::from_secs_f64(self.interval.clamp(0.2, 60.0)))
+    }
+}
+
+fn encode_frame<T: prost::Message + Serialize>(frame: &T, format: StreamFormat) -> Option<Message> {
+    match format {
+        StreamFormat::Json => match serde_json::to_string(frame) {
+            Ok(s) => Some(Message::Text(s)),
+            Err(e) => {
+                tracing::warn!("Failed to encode position frame: {}", e);
+                None
+            }
+        },
+        StreamFormat::Protobuf => Some(Message::Binary(frame.encode_to_vec())),
+    }
+}
+
+/// Propagates the loaded element sets the filter admits to `t`, up to `limit`
+/// of them, skipping (and counting) the ones SGP4 rejects.
+pub fn position_batch(elements: &Catalog, t: DateTime<Utc>, limit: usize, filter: &StreamFilter) -> PositionBatch {
+    let gmst_rad = gmst(t);
+    let mut failed = 0;
+    let positions = elements
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| filter.admits_id(e.norad_id))
+        .filter_map(|(i, e)| {
+            let Ok(pred) = elements.propagate(i, t) else {
+                failed += 1;
+                return None;
+            };
+            let (x, y, z) = eci_to_ecef(&pred.position, gmst_rad);
+            let (lat, lon) = ecef_to_geodetic(x, y, z);
+            if !filter.admits_position(&pred.position, gmst_rad, lat, lon) {
+                return None;
+            }
+            let norm = |v: &[f64; 3]| (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+            Some(Position {
+                norad_id: e.norad_id as u32,
+                lat: lat as f32,
+                lon: lon as f32,
+                alt_km: (norm(&pred.position) - EARTH_RADIUS_KM) as f32,
+                speed_km_s: norm(&pred.velocity) as f32,
+            })
+        })
+        .take(limit)
+        .collect();
+    crate::utils::metrics::record_propagation_errors(crate::utils::metrics::PropagationSource::Stream, failed);
+    PositionBatch { timestamp_ms: t.timestamp_millis(), positions }
+}
+
+/// How long (clock time) a full-catalog position batch is reused.
+const POSITION_CACHE_MS: i64 = 1000;
+
+/// Positions of the whole loaded catalog, shared by requests within
+/// [`POSITION_CACHE_MS`] of each other.
+#[derive(Debug, Default)]
+pub struct PositionCache {
+    latest: Mutex<Option<Arc<PositionBatch>>>,
+}
+
+impl PositionCache {
+    pub fn batch(&self, elements: &Catalog, t: DateTime<Utc>) -> Arc<PositionBatch> {
+        let mut latest = self.latest.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(batch) = latest.as_ref().filter(|b| (t.timestamp_millis() - b.timestamp_ms).abs() < POSITION_CACHE_MS) {
+            return batch.clone();
+        }
+        let batch = Arc::new(position_batch(elements, t, usize::MAX, &StreamFilter::default()));
+        *latest = Some(batch.clone());
+        batch
+    }
+}
+
+/// Live positions over a WebSocket, one frame per `interval`: JSON text frames
+/// by default, or protobuf binary frames (`format=protobuf`) for full-catalog views.
+/// With `delta=true` frames carry quantized keyframes and per-satellite changes.
+/// Clients narrow the stream by sending a [`SubscribeRequest`]; invalid requests
+/// are answered with an `{"error": ...}` text frame and leave the filter unchanged.
+/// `rate` and `start` replay the stream on a simulated clock instead of the server's.
+/// With `catalog_events=true` the catalog changes of each load arrive as
+/// `{"type": "catalog_changes", ...}` text frames.
+pub async fn ws_positions(ws: WebSocketUpgrade, Query(q): Query<StreamQuery>, State(state): State<AppState>, caller: Option<Extension<Caller>>) -> Response {
+    let period = match q.period() {
+        Ok(p) => p,
+        Err(e) => return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": e}))).into_response(),
+    };
+    let clock: Arc<dyn Clock> = match parse_replay(q.rate.as_deref(), q.start.as_deref()) {
+        Ok(Some((start, rate))) => Arc::new(SimulatedClock::new(start, rate)),
+        Ok(None) => state.clock.clone(),
+        Err(e) => return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": e}))).into_response(),
+    };
+    let caller = caller.map(|Extension(c)| c);
+    ws.on_upgrade(move |socket| stream_positions(socket, state, clock, q, period, caller))
+}
+
+async fn stream_positions(mut socket: WebSocket, state: AppState, clock: Arc<dyn Clock>, q: StreamQuery, period: Duration, caller: Option<Caller>) {
+    let limit = q.limit.unwrap_or(usize::MAX);
+    let mut encoder = q.delta.then(|| DeltaEncoder::new(q.keyframe_every));
+    let mut filter = Arc::new(StreamFilter::default());
+    let mut ticker = tokio::time::interval(period);
+    let mut catalog_events = q.catalog_events.then(|| state.catalog_events.subscribe());
+    loop {
+        let catalog_event = async {
+            match catalog_events.as_mut() {
+                Some(rx) => rx.recv().await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            event = catalog_event => match event {
+                Ok(changes) => {
+                    let frame = serde_json::json!({"type": "catalog_changes", "changes": changes});
+                    if socket.send(Message::Text(frame.to_string())).await.is_err() {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => catalog_events = None,
+            },
+            _ = ticker.tick() => {
+                // Propagation is CPU-bound; keep it off the runtime's workers
+                let (catalog, t, tick_filter) = (state.catalog(), clock.now(), filter.clone());
+                let batch = match tokio::task::spawn_blocking(move || position_batch(&catalog, t, limit, &tick_filter)).await {
+                    Ok(batch) => batch,
+                    Err(e) => {
+                        tracing::warn!(error = %e, "Position stream propagation panicked");
+                        break;
+                    }
+                };
+                let msg = match encoder.as_mut() {
+                    Some(encoder) => encode_frame(&encoder.encode(&batch), q.format),
+                    None => encode_frame(&batch, q.format),
+                };
+                let Some(msg) = msg else { continue };
+                if socket.send(msg).await.is_err() {
+                    break;
+                }
+            }
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(Message::Text(text))) => {
+                    let resolved = match serde_json::from_str::<ClientMessage>(&text) {
+                        Ok(ClientMessage::Subscribe(req)) => StreamFilter::resolve(req, caller.as_ref()),
+                        Ok(ClientMessage::Unsubscribe) => Ok(StreamFilter::default()),
+                        Err(e) => Err(format!("invalid message: {}", e)),
+                    };
+                    match resolved {
+                        Ok(f) => {
+                            filter = Arc::new(f);
+                            ticker.reset_immediately();
+                        }
+                        Err(e) => {
+                            if socket.send(Message::Text(serde_json::json!({"error": e}).to_string())).await.is_err() {
+                                break;
+                            }
+                        }
+                    }
+                }
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost::Message as _;
+
+    #[test]
+    fn protobuf_frame_round_trips_and_is_smaller_than_json() {
+        let batch = PositionBatch {
+            timestamp_ms: 1_700_000_000_000,
+            positions: (0..100)
+                .map(|i| Position { norad_id: 25544 + i, lat: 51.2, lon: -12.7, alt_km: 418.3, speed_km_s: 7.66 })
+                .collect(),
+        };
+        let bytes = batch.encode_to_vec();
+        assert_eq!(PositionBatch::decode(bytes.as_slice()).unwrap(), batch);
+        let json = serde_json::to_string(&batch).unwrap();
+        assert!(bytes.len() * 2 < json.len(), "{} vs {}", bytes.len(), json.len());
+    }
+
+    #[test]
+    fn deltas_carry_only_changed_fields_and_wrap_longitude() {
+        let pos = |norad_id, lat, lon| Position { norad_id, lat, lon, alt_km: 500.0, speed_km_s: 7.6 };
+        let mut enc = DeltaEncoder::new(3);
+        let first = enc.encode(&PositionBatch { timestamp_ms: 0, positions: vec![pos(1, 10.0, 179.99), pos(2, 0.0, 0.0)] });
+        assert!(first.keyframe && first.positions.len() == 2);
+
+        let second = enc.encode(&PositionBatch { timestamp_ms: 1000, positions: vec![pos(1, 10.0, -179.99), pos(2, 0.0, 0.0)] });
+        assert!(!second.keyframe);
+        assert_eq!(second.deltas, vec![PositionDelta { norad_id: 1, d_lat: None, d_lon: Some(200), d_alt: None, d_speed: None }]);
+
+        let third = enc.encode(&PositionBatch { timestamp_ms: 2000, positions: vec![pos(1, 10.0, -179.99), pos(3, 1.0, 1.0)] });
+        assert!(!third.keyframe && third.deltas.is_empty());
+        assert_eq!((third.positions.len(), third.removed.clone()), (1, vec![2]));
+        assert!(enc.encode(&PositionBatch { timestamp_ms: 3000, positions: vec![pos(1, 10.0, -179.99)] }).keyframe);
+    }
+
+    #[test]
+    fn interval_must_be_finite() {
+        let query = |q: &str| Query::<StreamQuery>::try_from_uri(&format!("/ws/positions?{}", q).parse().unwrap()).unwrap().0;
+        assert_eq!(query("").period(), Ok(Duration::from_secs(1)));
+        assert_eq!(query("interval=0.01").period(), Ok(Duration::from_millis(200)));
+        assert_eq!(query("interval=1e9").period(), Ok(Duration::from_secs(60)));
+        assert!(query("interval=NaN").period().is_err());
+        assert!(query("interval=inf").period().is_err());
+    }
+
+    #[test]
+    fn bbox_across_antimeridian() {
+        let filter = StreamFilter { bbox: Some([170.0, -10.0, -170.0, 10.0]), ..Default::default() };
+        let pos = [7000.0, 0.0, 0.0];
+        assert!(filter.admits_position(&pos, 0.0, 0.0, 175.0));
+        assert!(filter.admits_position(&pos, 0.0, 5.0, -175.0));
+        assert!(!filter.admits_position(&pos, 0.0, 0.0, 0.0));
+        assert!(!filter.admits_position(&pos, 0.0, 20.0, 175.0));
+    }
+}
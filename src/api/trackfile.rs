@@ -0,0 +1,237 @@
+use std::fmt::Write as _;
+
+use axum::{extract::{Query, State}, response::{IntoResponse, Response}, Extension, Json};
+use axum::http::{header, StatusCode};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Deserialize;
+
+use crate::api::access::Caller;
+use crate::api::passes::resolve_observer;
+use crate::api::server::AppState;
+use crate::predictors::mount::{mount_plan, visible_part, MountSample};
+use crate::predictors::passes::{pointing_track, predict_passes_with_options, LookOptions, Observer, PointingSample};
+
+#[derive(Debug, Deserialize)]
+pub struct TrackFileQuery {
+    norad_id: u64,
+    #[serde(default)]
+    station_id: Option<i64>,
+    #[serde(default)]
+    lat: Option<f64>,
+    #[serde(default)]
+    lon: Option<f64>,
+    /// The first pass starting at or after this time is exported (default: now).
+    #[serde(default)]
+    start: Option<DateTime<Utc>>,
+    /// How far ahead to look for that pass, in minutes.
+    #[serde(default = "default_search")]
+    search: i64,
+    #[serde(default)]
+    min_el: f64,
+    /// Seconds between pointing samples.
+    #[serde(default = "default_cadence")]
+    cadence: i64,
+    #[serde(default)]
+    format: TrackFileFormat,
+    /// Export apparent (refracted) elevations.
+    #[serde(default)]
+    refraction: bool,
+    /// Apply light-time and aberration corrections.
+    #[serde(default)]
+    light_time: bool,
+    /// Export the first pass in which the satellite is sunlit under a dark sky,
+    /// trimmed to the visible part.
+    #[serde(default)]
+    optical: bool,
+    /// INDI device name of the mount.
+    #[serde(default)]
+    device: Option<String>,
+    /// Equinox of the `indi` and `ascom` coordinates.
+    #[serde(default)]
+    epoch: Epoch,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Epoch {
+    /// Equator and equinox of date.
+    #[default]
+    Jnow,
+    J2000,
+}
+
+impl Epoch {
+    fn ra_dec(self, s: &MountSample) -> (f64, f64) {
+        match self {
+            Epoch::Jnow => (s.ra_hours, s.dec_deg),
+            Epoch::J2000 => (s.ra_j2000_hours, s.dec_j2000_deg),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrackFileFormat {
+    #[default]
+    Csv,
+    Easycomm,
+    /// Shell script of timed `indi_setprop` commands.
+    Indi,
+    /// RA/Dec with ASCOM tracking rates, as CSV.
+    Ascom,
+}
+
+impl TrackFileFormat {
+    fn equatorial(self) -> bool {
+        matches!(self, TrackFileFormat::Indi | TrackFileFormat::Ascom)
+    }
+}
+
+fn default_search() -> i64 { 1440 }
+fn default_cadence() -> i64 { 1 }
+
+const DEFAULT_INDI_DEVICE: &str = "Telescope Simulator";
+
+/// Step used to locate the pass; the exported samples use `cadence`.
+const SEARCH_STEP_SECONDS: i64 = 10;
+
+/// Time-stamped az/el pointing file for the next pass, for rotator controllers
+/// that preload a track instead of being driven live.
+pub async fn get_trackfile(State(state): State<AppState>, Query(q): Query<TrackFileQuery>, caller: Option<Extension<Caller>>) -> Response {
+    let catalog = state.catalog();
+    let Some(el) = catalog.get(q.norad_id) else {
+        return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": "norad_id not found in loaded TLEs"}))).into_response();
+    };
+    let device = q.device.as_deref().unwrap_or(DEFAULT_INDI_DEVICE);
+    if device.is_empty() || !device.chars().all(|c| c.is_ascii_alphanumeric() || " _-.".contains(c)) {
+        return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({"error": "device may only contain letters, digits, spaces and _-."}))).into_response();
+    }
+    let position = match resolve_observer(q.station_id, q.lat, q.lon, None, caller.as_deref()) {
+        Ok(o) => o.position,
+        Err(response) => return response.into_response(),
+    };
+
+    let start = q.start.unwrap_or_else(Utc::now);
+    let options = LookOptions { refraction: q.refraction, light_time: q.light_time, ..Default::default() };
+    let prediction_error = |e: sgp4::Error| (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": format!("prediction error: {}", e)}))).into_response();
+    let passes = match predict_passes_with_options(el, &Observer::Fixed(position), &options, start, q.search, SEARCH_STEP_SECONDS, q.min_el) {
+        Ok(wins) => wins,
+        Err(e) => return prediction_error(e),
+    };
+
+    // The first pass, or with `optical` the first one with a visible part
+    let mut selected = None;
+    for pass in passes {
+        let samples = match pointing_track(el, &position, pass.start, pass.end, q.cadence, &options) {
+            Ok(s) => s,
+            Err(e) => return prediction_error(e),
+        };
+        if !q.optical && !q.format.equatorial() {
+            selected = Some((samples, Vec::new()));
+            break;
+        }
+        let plan = match mount_plan(el, &position, &samples) {
+            Ok(p) => p,
+            Err(e) => return prediction_error(e),
+        };
+        if !q.optical {
+            selected = Some((samples, plan));
+            break;
+        }
+        let (first, last) = match visible_part(&plan) {
+            [] => continue,
+            part => (part[0].time, part[part.len() - 1].time),
+        };
+        let keep = |t: DateTime<Utc>| first <= t && t <= last;
+        selected = Some((samples.into_iter().filter(|s| keep(s.time)).collect(), plan.into_iter().filter(|s| keep(s.time)).collect()));
+        break;
+    }
+    let Some((samples, plan)) = selected else {
+        let error = if q.optical { "no visible pass in the search window" } else { "no pass in the search window" };
+        return (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": error}))).into_response();
+    };
+
+    let (body, content_type, ext) = match q.format {
+        TrackFileFormat::Csv => (to_csv(&samples), "text/csv", "csv"),
+        TrackFileFormat::Easycomm => (to_easycomm(q.norad_id, &samples), "text/plain", "txt"),
+        TrackFileFormat::Indi => (to_indi(q.norad_id, device, q.epoch, &plan), "text/x-shellscript", "sh"),
+        TrackFileFormat::Ascom => (to_ascom(q.epoch, &plan), "text/csv", "csv"),
+    };
+    let filename = format!("{}-{}.{}", q.norad_id, samples[0].time.format("%Y%m%dT%H%M%SZ"), ext);
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+fn to_csv(samples: &[PointingSample]) -> String {
+    let mut out = String::from("time,azimuth_deg,elevation_deg\n");
+    for s in samples {
+        let _ = writeln!(out, "{},{:.2},{:.2}", s.time.to_rfc3339_opts(SecondsFormat::Secs, true), s.az_deg, s.el_deg);
+    }
+    out
+}
+
+/// EasyComm II `AZ`/`EL` commands, one per line, prefixed with the UTC time at
+/// which the controller should send them.
+fn to_easycomm(norad_id: u64, samples: &[PointingSample]) -> String {
+    let mut out = format!("# STfCM track for NORAD {}\n# time AZ EL\n", norad_id);
+    for s in samples {
+        let _ = writeln!(
+            out,
+            "{} AZ{:.1} EL{:.1}",
+            s.time.to_rfc3339_opts(SecondsFormat::Secs, true),
+            s.az_deg,
+            s.el_deg.max(0.0)
+        );
+    }
+    out
+}
+
+/// POSIX shell script that slews an INDI mount to each point at its UTC time
+/// through `indi_setprop`: `EQUATORIAL_EOD_COORD` for JNow, `EQUATORIAL_COORD`
+/// for J2000.
+fn to_indi(norad_id: u64, device: &str, epoch: Epoch, plan: &[MountSample]) -> String {
+    let (property, equinox) = match epoch {
+        Epoch::Jnow => ("EQUATORIAL_EOD_COORD", "of date"),
+        Epoch::J2000 => ("EQUATORIAL_COORD", "J2000"),
+    };
+    let mut out = format!(
+        "#!/bin/sh\n# STfCM pointing plan for NORAD {}: topocentric RA (hours) and Dec (degrees), {}\n\
+         DEVICE=\"{}\"\n\
+         wait_until() {{ now=$(date -u +%s); [ \"$1\" -gt \"$now\" ] && sleep $(($1 - now)); }}\n\
+         indi_setprop \"$DEVICE.ON_COORD_SET.TRACK=On\"\n",
+        norad_id, equinox, device
+    );
+    for s in plan {
+        let (ra_hours, dec_deg) = epoch.ra_dec(s);
+        let _ = writeln!(out, "wait_until {}; indi_setprop \"$DEVICE.{}.RA;DEC={:.6};{:.5}\"", s.time.timestamp(), property, ra_hours, dec_deg);
+    }
+    out
+}
+
+/// RA/Dec per time with the ASCOM `RightAscensionRate` and `DeclinationRate`
+/// to set for tracking between points.
+fn to_ascom(epoch: Epoch, plan: &[MountSample]) -> String {
+    let mut out = String::from("time,ra_hours,dec_deg,ra_rate_s_per_sidereal_s,dec_rate_arcsec_s,azimuth_deg,elevation_deg\n");
+    for s in plan {
+        let (ra_hours, dec_deg) = epoch.ra_dec(s);
+        let _ = writeln!(
+            out,
+            "{},{:.6},{:.5},{:.4},{:.3},{:.2},{:.2}",
+            s.time.to_rfc3339_opts(SecondsFormat::Secs, true),
+            ra_hours,
+            dec_deg,
+            s.ra_rate_s_per_sidereal_s,
+            s.dec_rate_arcsec_s,
+            s.az_deg,
+            s.el_deg
+        );
+    }
+    out
+}
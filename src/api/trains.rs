@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+
+use axum::{extract::{Query, State}, response::IntoResponse, Extension, Json};
+use axum::http::StatusCode;
+use chrono::Duration;
+use serde::Deserialize;
+
+use crate::analyzers::launches::{group_by_launch, parse_launch};
+use crate::api::access::Caller;
+use crate::api::passes;
+use crate::api::server::AppState;
+use crate::api::types::{TrainDto, TrainPassesDto};
+use crate::collectors::tle_fetcher;
+use crate::predictors::trains::predict_trains;
+
+/// Celestrak supplemental file with SpaceX's Starlink elements.
+const STARLINK_FILE: &str = "starlink";
+const MAX_HOURS: i64 = 168;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrainSource {
+    /// The loaded catalog, which includes Celestrak's `last-30-days` group.
+    #[default]
+    Catalog,
+    /// Celestrak's supplemental Starlink elements, where a piece has them.
+    Supplemental,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TrainQuery {
+    #[serde(default)]
+    station_id: Option<i64>,
+    #[serde(default)]
+    lat: Option<f64>,
+    #[serde(default)]
+    lon: Option<f64>,
+    #[serde(default)]
+    alt_m: Option<f64>,
+    /// `2024-043` or `24043`; the newest launch with Starlink pieces when unset.
+    #[serde(default)]
+    launch: Option<String>,
+    #[serde(default)]
+    source: TrainSource,
+    #[serde(default = "default_hours")]
+    hours: i64,
+    #[serde(default = "default_min_el")]
+    min_el: f64,
+    /// Longest wait (s) between one satellite appearing and the next.
+    #[serde(default = "default_max_gap")]
+    max_gap: i64,
+    #[serde(default = "default_min_count")]
+    min_count: usize,
+}
+
+fn default_hours() -> i64 { 48 }
+fn default_min_el() -> f64 { 10.0 }
+fn default_max_gap() -> i64 { 120 }
+fn default_min_count() -> usize { 3 }
+
+fn error(status: StatusCode, msg: impl Into<String>) -> (StatusCode, Json<serde_json::Value>) {
+    (status, Json(serde_json::json!({"error": msg.into()})))
+}
+
+/// The newest cached supplemental Starlink set within the refresh interval, or
+/// a fresh download.
+async fn supplemental_elements(config: &crate::config::Config) -> Result<Vec<sgp4::Elements>, String> {
+    let path = match tle_fetcher::fresh_cached(config, &tle_fetcher::supplemental_group(STARLINK_FILE)) {
+        Some(path) => path,
+        None => tle_fetcher::fetch_celestrak_supplemental(config, STARLINK_FILE).await.map_err(|e| e.to_string())?,
+    };
+    crate::core::tle::read_tle_report(&path).map(|r| r.elements).map_err(|e| e.to_string())
+}
+
+/// Visible "trains" of a recent launch: its satellites still fly in a line
+/// shortly after deployment and cross the sky one after another.
+pub async fn get_trains(Query(q): Query<TrainQuery>, caller: Option<Extension<Caller>>, State(state): State<AppState>) -> impl IntoResponse {
+    if !(1..=MAX_HOURS).contains(&q.hours) {
+        return error(StatusCode::UNPROCESSABLE_ENTITY, format!("hours must be within 1..={}", MAX_HOURS));
+    }
+    if !(-90.0..=90.0).contains(&q.min_el) || q.max_gap <= 0 {
+        return error(StatusCode::UNPROCESSABLE_ENTITY, "min_el must be within [-90, 90] and max_gap positive");
+    }
+    let observer = match passes::resolve_observer(q.station_id, q.lat, q.lon, q.alt_m, caller.as_deref()) {
+        Ok(o) => o,
+        Err(response) => return response,
+    };
+    let satcat = match crate::utils::db::open_or_init().and_then(|c| crate::utils::db::satcat_map(&c)) {
+        Ok(s) => s,
+        Err(e) => return error(StatusCode::INTERNAL_SERVER_ERROR, format!("db error: {}", e)),
+    };
+    let catalog = state.catalog();
+    let mut launches = group_by_launch(&catalog, &satcat);
+    let launch = match q.launch.as_deref() {
+        Some(text) => match parse_launch(text) {
+            Some(launch) => launch,
+            None => return error(StatusCode::UNPROCESSABLE_ENTITY, "launch must look like 2024-043 or 24043"),
+        },
+        None => {
+            let starlink = |name: Option<&str>| name.is_some_and(|n| n.trim().to_ascii_uppercase().starts_with("STARLINK"));
+            match launches.iter().rev().find(|(_, pieces)| pieces.iter().any(|p| starlink(p.name))) {
+                Some((launch, _)) => launch.clone(),
+                None => return error(StatusCode::NOT_FOUND, "no Starlink launch in the catalog"),
+            }
+        }
+    };
+    let Some(pieces) = launches.remove(&launch) else {
+        return error(StatusCode::NOT_FOUND, format!("no objects of launch {} in the catalog", launch));
+    };
+
+    let supplemental = match q.source {
+        TrainSource::Catalog => Vec::new(),
+        TrainSource::Supplemental => match supplemental_elements(&state.config).await {
+            Ok(elements) => elements,
+            Err(e) => return error(StatusCode::BAD_GATEWAY, format!("supplemental elements unavailable: {}", e)),
+        },
+    };
+    let by_id: HashMap<u64, &sgp4::Elements> = supplemental.iter().map(|el| (el.norad_id, el)).collect();
+    let elements: Vec<(&sgp4::Elements, Option<&str>)> = pieces.iter().map(|p| (by_id.get(&p.elements.norad_id).copied().unwrap_or(p.elements), p.name)).collect();
+
+    let trains = predict_trains(&elements, &observer.position, state.clock.now(), q.hours * 60, q.min_el, Duration::seconds(q.max_gap), q.min_count);
+    let out = TrainPassesDto {
+        launch,
+        source: match q.source {
+            TrainSource::Catalog => "catalog",
+            TrainSource::Supplemental => "supplemental",
+        },
+        satellites: elements.len(),
+        station_id: observer.station_id,
+        trains: trains.into_iter().map(TrainDto::from).collect(),
+    };
+    (StatusCode::OK, Json(serde_json::json!(out)))
+}
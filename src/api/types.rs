@@ -1,30 +1,1319 @@
-use serde::Serialize;
-use chrono::{DateTime, Utc};
-
-#[derive(Debug, Serialize)]
-pub struct SatelliteDto {
-    pub norad_id: u64,
-    pub name: String,
-}
-
-#[derive(Debug, Serialize)]
-pub struct PassWindowDto {
-    pub start: DateTime<Utc>,
-    pub end: DateTime<Utc>,
-    pub max_elevation_deg: f64,
-}
-
-#[derive(Debug, Serialize)]
-pub struct StationDto {
-    pub id: i64,
-    pub name: Option<String>,
-    pub lat: f64,
-    pub lon: f64,
-}
-
-#[derive(Debug, serde::Deserialize)]
-pub struct CreateStationDto {
-    pub name: Option<String>,
-    pub lat: f64,
-    pub lon: f64,
-}
\ No newline at end of file
+use serde::Serialize;
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Serialize)]
+pub struct SatelliteDto {
+    pub norad_id: u64,
+    pub name: String,
+    /// User-assigned name to show instead of the catalog name.
+    pub display_name: Option<String>,
+    pub aliases: Vec<String>,
+    /// SATCAT owner code and object type, when the SATCAT lists the object.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub object_type: Option<String>,
+}
+
+/// Body of `PUT /satellites/:norad_id/aliases`; replaces all stored names.
+#[derive(Debug, Serialize, serde::Deserialize)]
+pub struct SatelliteAliasesDto {
+    #[serde(default)]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PassWindowDto {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub tca: DateTime<Utc>,
+    pub max_elevation_deg: f64,
+    pub duration_s: i64,
+    pub score: f64,
+    /// Revolution number at TCA.
+    pub orbit_number: i64,
+    pub aos_az_deg: f64,
+    pub los_az_deg: f64,
+    /// Compass points at AOS and LOS, e.g. `NNW→SE`.
+    pub direction: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub blocked: Vec<IntervalDto>,
+}
+
+impl From<crate::predictors::passes::PassWindow> for PassWindowDto {
+    fn from(w: crate::predictors::passes::PassWindow) -> Self {
+        PassWindowDto {
+            start: w.start,
+            end: w.end,
+            tca: w.tca,
+            max_elevation_deg: w.max_elevation_deg,
+            duration_s: w.duration_s(),
+            score: w.score(),
+            orbit_number: w.orbit_number,
+            aos_az_deg: w.aos_az_deg,
+            los_az_deg: w.los_az_deg,
+            direction: w.direction(),
+            blocked: w.blocked.into_iter().map(|(start, end)| IntervalDto { start, end }).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct IntervalDto {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, serde::Deserialize)]
+pub struct AzimuthSectorDto {
+    pub start_deg: f64,
+    pub end_deg: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StationDto {
+    pub id: i64,
+    pub name: Option<String>,
+    pub lat: f64,
+    pub lon: f64,
+    /// Height above the WGS84 ellipsoid (m).
+    pub alt_m: f64,
+    pub ecef_m: [f64; 3],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+}
+
+impl From<crate::utils::db::Station> for StationDto {
+    fn from(s: crate::utils::db::Station) -> Self {
+        let [x, y, z] = crate::core::coords::geodetic_to_ecef(s.lat, s.lon, s.alt_m / 1000.0);
+        StationDto { id: s.id, name: s.name, lat: s.lat, lon: s.lon, alt_m: s.alt_m, ecef_m: [x * 1000.0, y * 1000.0, z * 1000.0], tenant: s.tenant }
+    }
+}
+
+/// A station is given either geodetically (`lat`/`lon` with an ellipsoidal
+/// `alt_m`, or a geoid-referenced `geoid_height_m` plus the local
+/// `geoid_undulation_m`) or as surveyed ECEF coordinates in metres.
+#[derive(Debug, serde::Deserialize)]
+pub struct CreateStationDto {
+    pub name: Option<String>,
+    #[serde(default)]
+    pub lat: Option<f64>,
+    #[serde(default)]
+    pub lon: Option<f64>,
+    #[serde(default)]
+    pub alt_m: Option<f64>,
+    #[serde(default)]
+    pub geoid_height_m: Option<f64>,
+    #[serde(default)]
+    pub geoid_undulation_m: Option<f64>,
+    #[serde(default)]
+    pub ecef_m: Option<[f64; 3]>,
+}
+
+impl CreateStationDto {
+    /// Geodetic latitude, longitude (degrees) and ellipsoidal height (m).
+    pub fn geodetic(&self) -> Result<(f64, f64, f64), &'static str> {
+        let (lat, lon, alt_m) = if let Some([x, y, z]) = self.ecef_m {
+            if self.lat.is_some() || self.lon.is_some() {
+                return Err("give either ecef_m or lat/lon, not both");
+            }
+            let (lat, lon, h_km) = crate::core::coords::ecef_to_geodetic_height(x / 1000.0, y / 1000.0, z / 1000.0);
+            (lat, lon, h_km * 1000.0)
+        } else {
+            let (Some(lat), Some(lon)) = (self.lat, self.lon) else {
+                return Err("missing lat/lon or ecef_m");
+            };
+            let alt_m = match (self.alt_m, self.geoid_height_m, self.geoid_undulation_m) {
+                (Some(_), Some(_), _) => return Err("give either alt_m or geoid_height_m, not both"),
+                (_, Some(_), None) => return Err("geoid_height_m needs geoid_undulation_m"),
+                (_, Some(h), Some(n)) => crate::core::coords::geoid_to_ellipsoidal_height(h, n),
+                (alt, None, _) => alt.unwrap_or(0.0),
+            };
+            (lat, lon, alt_m)
+        };
+        if !((-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&lon)) {
+            return Err("lat/lon out of range");
+        }
+        if !alt_m.is_finite() || !(-1_000.0..=100_000.0).contains(&alt_m) {
+            return Err("height out of range");
+        }
+        Ok((lat, lon, alt_m))
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProtectedAssetDto {
+    pub norad_id: u64,
+    pub alert_threshold_km: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct CreateProtectedAssetDto {
+    pub norad_id: u64,
+    #[serde(default)]
+    pub alert_threshold_km: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConjunctionDto {
+    pub id: i64,
+    pub asset_norad_id: u64,
+    pub secondary_norad_id: u64,
+    pub secondary_name: Option<String>,
+    pub tca: String,
+    pub miss_distance_km: f64,
+    pub relative_speed_km_s: f64,
+    pub screened_at: String,
+    pub asset_sigma: Option<PositionSigmaDto>,
+    pub secondary_sigma: Option<PositionSigmaDto>,
+    /// Root-sum-square of both objects' total sigma, when both are known.
+    pub combined_sigma_km: Option<f64>,
+}
+
+/// One-sigma position uncertainty estimated from TLE history.
+#[derive(Debug, Serialize)]
+pub struct PositionSigmaDto {
+    pub radial_km: f64,
+    pub along_track_km: f64,
+    pub cross_track_km: f64,
+    pub samples: usize,
+    pub reference_epoch: DateTime<Utc>,
+}
+
+impl From<&crate::predictors::uncertainty::PositionSigma> for PositionSigmaDto {
+    fn from(s: &crate::predictors::uncertainty::PositionSigma) -> Self {
+        Self {
+            radial_km: s.radial_km,
+            along_track_km: s.along_track_km,
+            cross_track_km: s.cross_track_km,
+            samples: s.samples,
+            reference_epoch: s.reference_epoch,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ObservationDto {
+    pub id: i64,
+    pub station_id: i64,
+    pub norad_id: Option<u64>,
+    pub observed_at: String,
+    pub az_deg: f64,
+    pub el_deg: f64,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct CreateObservationDto {
+    pub station_id: i64,
+    #[serde(default)]
+    pub norad_id: Option<u64>,
+    pub observed_at: DateTime<Utc>,
+    pub az_deg: f64,
+    pub el_deg: f64,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct IodRequestDto {
+    pub observation_ids: Vec<i64>,
+    #[serde(default)]
+    pub candidates: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IodElementsDto {
+    pub epoch: DateTime<Utc>,
+    pub inclination_deg: f64,
+    pub raan_deg: f64,
+    pub eccentricity: f64,
+    pub arg_perigee_deg: f64,
+    pub mean_anomaly_deg: f64,
+    pub mean_motion_rev_day: f64,
+    pub tle_line1: String,
+    pub tle_line2: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CatalogMatchDto {
+    pub norad_id: u64,
+    pub name: Option<String>,
+    pub distance_km: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IodResultDto {
+    pub epoch: DateTime<Utc>,
+    pub position_km: [f64; 3],
+    pub velocity_km_s: [f64; 3],
+    pub elements: IodElementsDto,
+    pub candidates: Vec<CatalogMatchDto>,
+}
+
+/// A received carrier logged for `POST /iod/identify`.
+#[derive(Debug, serde::Deserialize)]
+pub struct DopplerSampleDto {
+    pub station_id: i64,
+    pub time: DateTime<Utc>,
+    pub frequency_hz: f64,
+    pub nominal_hz: f64,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct IdentifyRequestDto {
+    /// Candidate objects; alternatively every loaded piece of `launch`.
+    #[serde(default)]
+    pub norad_ids: Option<Vec<u64>>,
+    #[serde(default)]
+    pub launch: Option<String>,
+    #[serde(default)]
+    pub observation_ids: Vec<i64>,
+    #[serde(default)]
+    pub doppler: Vec<DopplerSampleDto>,
+    #[serde(default)]
+    pub angle_sigma_deg: Option<f64>,
+    #[serde(default)]
+    pub range_rate_sigma_m_s: Option<f64>,
+    #[serde(default)]
+    pub fit_frequency_offset: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CandidateFitDto {
+    pub rank: usize,
+    pub norad_id: u64,
+    pub name: Option<String>,
+    pub score: f64,
+    pub angle_rms_deg: Option<f64>,
+    pub range_rate_rms_m_s: Option<f64>,
+    /// Transmitter offset from nominal absorbed by the fit, at the mean nominal frequency.
+    pub frequency_offset_hz: Option<f64>,
+    pub below_horizon: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IdentifyResultDto {
+    pub angle_sightings: usize,
+    pub doppler_sightings: usize,
+    pub candidates: Vec<CandidateFitDto>,
+    /// Requested objects that are not in the loaded catalog.
+    pub missing: Vec<u64>,
+}
+
+/// One entry of `GET /catalog/changes`.
+#[derive(Debug, Serialize)]
+pub struct CatalogChangeDto {
+    pub changed_at: String,
+    pub norad_id: u64,
+    /// `added`, `removed` or `updated`.
+    pub kind: String,
+    pub name: Option<String>,
+    pub previous_epoch: Option<String>,
+    pub epoch: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TaggedSatelliteDto {
+    pub norad_id: u64,
+    pub name: Option<String>,
+    pub tagged_at: String,
+}
+
+/// A satellite tag as a group; `filter` is set for dynamic groups. Without a
+/// `tenant` the group is shared.
+#[derive(Debug, Serialize)]
+pub struct GroupDto {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+    pub filter: Option<String>,
+    pub members: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GroupDetailDto {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+    pub filter: Option<String>,
+    /// Dynamic groups only.
+    pub updated_at: Option<String>,
+    pub members: Vec<TaggedSatelliteDto>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct GroupRequestDto {
+    pub filter: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TleUploadResultDto {
+    pub accepted: usize,
+    pub rejected: usize,
+    /// Line number and reason for each rejected entry.
+    pub rejected_records: Vec<crate::core::tle::RejectedTle>,
+    pub new_norad_ids: Vec<u64>,
+}
+
+/// Element sets that failed screening when the catalog was loaded.
+#[derive(Debug, Serialize)]
+pub struct ScreeningReportDto {
+    pub limits: crate::core::screening::ScreeningLimits,
+    /// Left out of the catalog.
+    pub rejected: usize,
+    /// Kept, but outside the limits.
+    pub flagged: usize,
+    pub objects: Vec<crate::core::screening::ScreenedElement>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReentryDto {
+    pub norad_id: u64,
+    pub name: Option<String>,
+    pub element_epoch: DateTime<Utc>,
+    pub predicted_reentry: DateTime<Utc>,
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+    pub lifetime_days: f64,
+    pub perigee_alt_km: f64,
+    /// GeoJSON FeatureCollection with the final revolutions' ground track.
+    pub corridor: serde_json::Value,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct TrackPointDto {
+    pub time: DateTime<Utc>,
+    pub lat: f64,
+    pub lon: f64,
+    #[serde(default)]
+    pub alt_km: f64,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct MobilePassRequestDto {
+    #[serde(default)]
+    pub norad_id: Option<u64>,
+    #[serde(default)]
+    pub norad_ids: Vec<u64>,
+    pub track: Vec<TrackPointDto>,
+    #[serde(default)]
+    pub step: Option<i64>,
+    #[serde(default)]
+    pub min_el: Option<f64>,
+    #[serde(default)]
+    pub refraction: Option<bool>,
+    #[serde(default)]
+    pub light_time: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ObserverLocationDto {
+    pub lat: f64,
+    pub lon: f64,
+    pub alt_km: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MobilePassDto {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub tca: DateTime<Utc>,
+    pub max_elevation_deg: f64,
+    pub aos_location: Option<ObserverLocationDto>,
+    pub tca_location: Option<ObserverLocationDto>,
+    pub los_location: Option<ObserverLocationDto>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MobileSatellitePassesDto {
+    pub norad_id: u64,
+    pub name: Option<String>,
+    pub passes: Vec<MobilePassDto>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StationHorizonDto {
+    pub station_id: i64,
+    pub bin_width_deg: f64,
+    /// Terrain elevation per azimuth bin, starting at north and going clockwise.
+    pub elevations_deg: Vec<f64>,
+    pub computed_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProfileSampleDto {
+    pub time: DateTime<Utc>,
+    pub az_deg: f64,
+    pub el_deg: f64,
+    pub az_rate_deg_s: f64,
+    pub el_rate_deg_s: f64,
+    #[serde(flatten)]
+    pub equatorial: Option<EquatorialDto>,
+    /// Link budget fields, present when link parameters were given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range_km: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path_loss_db: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snr_db: Option<f64>,
+}
+
+/// SNR extremes over a profiled pass.
+#[derive(Debug, Serialize)]
+pub struct LinkSummaryDto {
+    pub frequency_mhz: f64,
+    pub max_snr_db: f64,
+    pub min_snr_db: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PassProfileDto {
+    pub norad_id: u64,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub tca: DateTime<Utc>,
+    pub max_elevation_deg: f64,
+    pub peak_az_rate_deg_s: f64,
+    /// True when the azimuth rate exceeds `max_az_rate` somewhere in the pass.
+    pub keyhole: bool,
+    pub keyhole_intervals: Vec<IntervalDto>,
+    /// Parts of the pass spent in the South Atlantic Anomaly.
+    pub saa_intervals: Vec<IntervalDto>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link: Option<LinkSummaryDto>,
+    pub samples: Vec<ProfileSampleDto>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GeoObjectDto {
+    pub norad_id: u64,
+    pub name: Option<String>,
+    pub longitude_deg: f64,
+    pub latitude_deg: f64,
+    /// Positive eastwards.
+    pub drift_deg_per_day: f64,
+}
+
+/// Returned by pass endpoints instead of pass windows for geosynchronous objects:
+/// the whole body is this object where other objects get an array.
+#[derive(Debug, Serialize)]
+pub struct GeoLookDto {
+    pub geo: GeoObjectDto,
+    pub az_deg: f64,
+    pub el_deg: f64,
+    pub visible: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GeoBinDto {
+    pub lon_start_deg: f64,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GeoBeltDto {
+    pub bin_width_deg: f64,
+    pub bins: Vec<GeoBinDto>,
+    pub objects: Vec<GeoObjectDto>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrbitalEventDto {
+    pub kind: crate::predictors::events::OrbitalEventKind,
+    pub time: DateTime<Utc>,
+    pub latitude_deg: f64,
+    pub longitude_deg: f64,
+    pub altitude_km: f64,
+}
+
+/// Catalog population over one orbital quantity, from `/stats/histograms`.
+#[derive(Debug, Serialize)]
+pub struct HistogramDto {
+    pub field: crate::analyzers::population::Field,
+    pub unit: &'static str,
+    /// Loaded element sets, including those outside `[min, max]`.
+    pub total: usize,
+    pub min: f64,
+    pub max: f64,
+    pub bin_width: f64,
+    /// Bin boundaries, one more than `counts`.
+    pub edges: Vec<f64>,
+    pub counts: Vec<u64>,
+    pub below: u64,
+    pub above: u64,
+}
+
+/// Objects per altitude shell at each catalog fetch, from `/stats/shells`.
+#[derive(Debug, Serialize)]
+pub struct ShellHistoryDto {
+    pub width_km: u32,
+    /// Lower edge of each shell (km).
+    pub shells_km: Vec<u32>,
+    /// Oldest first.
+    pub fetches: Vec<ShellFetchDto>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShellFetchDto {
+    pub fetched_at: String,
+    /// Objects in the returned shells.
+    pub total: u64,
+    /// One per shell of `shells_km`.
+    pub counts: Vec<u64>,
+}
+
+/// Loaded satellites per SATCAT owner or object type, from `/stats/counts`.
+#[derive(Debug, Serialize)]
+pub struct GroupCountsDto {
+    pub group_by: crate::api::stats::GroupBy,
+    pub total: usize,
+    /// Largest group first.
+    pub counts: Vec<GroupCountDto>,
+    /// Loaded objects the SATCAT does not list (e.g. custom element sets).
+    pub unmatched: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GroupCountDto {
+    pub key: String,
+    pub count: usize,
+}
+
+/// A satellite left out of a positions response because SGP4 rejected it.
+#[derive(Debug, Serialize)]
+pub struct PropagationErrorDto {
+    pub norad_id: u64,
+    pub reason: String,
+}
+
+/// Topocentric right ascension and declination of a look direction, added to
+/// look angles with `radec=true`.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct EquatorialDto {
+    /// Mean equator and equinox of date.
+    pub ra_deg: f64,
+    pub dec_deg: f64,
+    pub ra_j2000_deg: f64,
+    pub dec_j2000_deg: f64,
+}
+
+impl EquatorialDto {
+    pub fn new(observer: &crate::predictors::passes::ObserverPosition, az_deg: f64, el_deg: f64, t: DateTime<Utc>) -> Self {
+        use crate::core::coords::{horizontal_to_equatorial, of_date_to_j2000};
+        let (ra_deg, dec_deg) = horizontal_to_equatorial(observer.lat_deg, observer.lon_deg, az_deg, el_deg, t);
+        let (ra_j2000_deg, dec_j2000_deg) = of_date_to_j2000(ra_deg, dec_deg, t);
+        EquatorialDto { ra_deg, dec_deg, ra_j2000_deg, dec_j2000_deg }
+    }
+}
+
+/// A satellite above a station's horizon right now.
+#[derive(Debug, Serialize)]
+pub struct VisibleSatelliteDto {
+    pub norad_id: u64,
+    pub name: Option<String>,
+    pub az_deg: f64,
+    pub el_deg: f64,
+    /// Velocity relative to the station in east/north/up (km/s).
+    pub velocity_enu_km_s: [f64; 3],
+    pub az_rate_deg_s: f64,
+    pub el_rate_deg_s: f64,
+    /// Direction of apparent motion, clockwise from towards the zenith, for
+    /// sky-chart arrows.
+    pub position_angle_deg: f64,
+    pub angular_rate_deg_s: f64,
+    #[serde(flatten)]
+    pub equatorial: Option<EquatorialDto>,
+}
+
+impl VisibleSatelliteDto {
+    pub fn new(norad_id: u64, name: Option<String>, m: crate::predictors::passes::SkyMotion) -> Self {
+        VisibleSatelliteDto {
+            norad_id,
+            name,
+            az_deg: m.az_deg,
+            el_deg: m.el_deg,
+            velocity_enu_km_s: m.velocity_enu_km_s,
+            az_rate_deg_s: m.az_rate_deg_s,
+            el_rate_deg_s: m.el_rate_deg_s,
+            position_angle_deg: m.position_angle_deg,
+            angular_rate_deg_s: m.angular_rate_deg_s,
+            equatorial: None,
+        }
+    }
+}
+
+/// A pass window together with the satellite making it.
+#[derive(Debug, Serialize)]
+pub struct SatellitePassDto {
+    pub norad_id: u64,
+    pub name: Option<String>,
+    #[serde(flatten)]
+    pub pass: PassWindowDto,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AlertStatusDto {
+    /// Whether `STFCM_ALERT_WEBHOOK` forwards alerts beyond the log.
+    pub webhook_configured: bool,
+    /// Favorites that are protected assets, i.e. raise conjunction alerts.
+    pub protected_favorites: Vec<u64>,
+}
+
+/// Everything the home page shows for one station, from `/stations/:id/summary`.
+#[derive(Debug, Serialize)]
+pub struct StationSummaryDto {
+    pub station: StationDto,
+    pub time: DateTime<Utc>,
+    pub visible: Vec<VisibleSatelliteDto>,
+    pub favorites: Vec<u64>,
+    pub next_passes: Vec<SatellitePassDto>,
+    pub contact_minutes_today: f64,
+    pub alerts: AlertStatusDto,
+}
+
+/// Result of `/passes/common`.
+#[derive(Debug, Serialize)]
+pub struct CommonVisibilityDto {
+    pub norad_id: u64,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub min_stations: usize,
+    pub windows: Vec<CommonWindowDto>,
+}
+
+/// A span during which the same stations all see the satellite.
+#[derive(Debug, Serialize)]
+pub struct CommonWindowDto {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub duration_s: i64,
+    pub station_ids: Vec<i64>,
+}
+
+/// Result of `/schedule/handover`.
+#[derive(Debug, Serialize)]
+pub struct HandoverPlanDto {
+    pub norad_id: u64,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Time some station is tracking.
+    pub tracked_s: i64,
+    /// Switches from one station directly to another.
+    pub handovers: usize,
+    pub assignments: Vec<HandoverDto>,
+}
+
+/// One station's turn in a handover plan.
+#[derive(Debug, Serialize)]
+pub struct HandoverDto {
+    pub station_id: i64,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub max_elevation_deg: f64,
+    /// Whether the previous station hands over at `start` (`false` after a gap).
+    pub handover: bool,
+}
+
+/// One crewed vehicle from `/satellites/crewed`.
+#[derive(Debug, Serialize)]
+pub struct CrewedVehicleDto {
+    pub norad_id: u64,
+    pub name: Option<String>,
+    pub time: DateTime<Utc>,
+    pub element_epoch: DateTime<Utc>,
+    pub lat: f64,
+    pub lon: f64,
+    pub alt_km: f64,
+    pub speed_km_s: f64,
+    /// Revolution number, counted from ascending node to ascending node.
+    pub orbit_number: i64,
+    pub in_eclipse: bool,
+    pub station_id: Option<i64>,
+    /// `None` without an observer or when no pass starts within a week.
+    pub next_pass: Option<PassWindowDto>,
+    /// Next pass seen against a dark sky: the observer past civil twilight and
+    /// the vehicle sunlit at closest approach.
+    pub next_visible_pass: Option<PassWindowDto>,
+}
+
+/// Visible trains of one launch over an observer, from `/passes/trains`.
+#[derive(Debug, Serialize)]
+pub struct TrainPassesDto {
+    /// `YYYY-NNN`.
+    pub launch: String,
+    /// `catalog` or `supplemental`: where the element sets came from.
+    pub source: &'static str,
+    /// Pieces of the launch that were predicted.
+    pub satellites: usize,
+    pub station_id: Option<i64>,
+    pub trains: Vec<TrainDto>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrainDto {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub count: usize,
+    pub max_elevation_deg: f64,
+    /// Where the first satellite appears and disappears, e.g. `WSW→NE`.
+    pub direction: String,
+    pub satellites: Vec<TrainMemberDto>,
+}
+
+/// The visible part of one satellite's pass within a train.
+#[derive(Debug, Serialize)]
+pub struct TrainMemberDto {
+    pub norad_id: u64,
+    pub name: Option<String>,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub max_elevation_deg: f64,
+    pub start_az_deg: f64,
+    pub end_az_deg: f64,
+}
+
+impl From<crate::predictors::trains::Train> for TrainDto {
+    fn from(t: crate::predictors::trains::Train) -> Self {
+        TrainDto {
+            start: t.start(),
+            end: t.end(),
+            count: t.satellites.len(),
+            max_elevation_deg: t.max_elevation_deg(),
+            direction: t.direction(),
+            satellites: t
+                .satellites
+                .into_iter()
+                .map(|s| TrainMemberDto {
+                    norad_id: s.norad_id,
+                    name: s.name,
+                    start: s.start,
+                    end: s.end,
+                    max_elevation_deg: s.max_elevation_deg,
+                    start_az_deg: s.start_az_deg,
+                    end_az_deg: s.end_az_deg,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Satellites over a country or area of interest, from `/satellites/over`.
+#[derive(Debug, Serialize)]
+pub struct RegionOverflightDto {
+    /// `country:<ISO>` or `aoi:<id>`.
+    pub region: String,
+    pub time: DateTime<Utc>,
+    pub count: usize,
+    pub satellites: Vec<SatelliteOverRegionDto>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SatelliteOverRegionDto {
+    pub norad_id: u64,
+    pub name: Option<String>,
+    pub lat: f64,
+    pub lon: f64,
+    pub alt_km: f64,
+    /// With `hours`: spans over the region from now on; the first ends at exit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub intervals: Option<Vec<IntervalDto>>,
+}
+
+/// Summary returned by `/satellites/:norad_id/next`.
+#[derive(Debug, Serialize)]
+pub struct NextEventsDto {
+    pub norad_id: u64,
+    pub name: Option<String>,
+    pub time: DateTime<Utc>,
+    pub element_epoch: DateTime<Utc>,
+    pub tle_age_days: f64,
+    pub station_id: Option<i64>,
+    /// `None` without a station, for GEO objects or when no pass starts within two days.
+    pub next_pass: Option<PassWindowDto>,
+    pub in_eclipse: bool,
+    pub next_eclipse_entry: Option<OrbitalEventDto>,
+    pub next_eclipse_exit: Option<OrbitalEventDto>,
+    pub next_node: Option<OrbitalEventDto>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RepeatCycleDto {
+    pub revolutions: u32,
+    pub days: u32,
+    /// e.g. `233/16`.
+    pub label: String,
+    /// Eastward shift of the equator crossings after one cycle.
+    pub shift_deg: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SatelliteDetailDto {
+    pub norad_id: u64,
+    pub name: Option<String>,
+    pub display_name: Option<String>,
+    pub aliases: Vec<String>,
+    pub element_epoch: DateTime<Utc>,
+    pub inclination_deg: f64,
+    pub eccentricity: f64,
+    pub mean_motion_rev_per_day: f64,
+    pub period_min: f64,
+    pub perigee_alt_km: f64,
+    pub apogee_alt_km: f64,
+    pub revs_per_nodal_day: f64,
+    pub repeat_ground_track: Option<RepeatCycleDto>,
+    pub orbit_plane: OrbitPlaneDto,
+    /// Revolution number now; `None` when the elements fail to propagate.
+    pub orbit_number: Option<i64>,
+}
+
+/// Orbit plane relative to the Sun, evaluated now.
+#[derive(Debug, Serialize)]
+pub struct OrbitPlaneDto {
+    pub raan_deg: f64,
+    pub raan_rate_deg_per_day: f64,
+    pub sun_synchronous: bool,
+    /// Local mean solar time of the ascending node, in hours.
+    pub ltan_hours: f64,
+    /// The same as `HH:MM`.
+    pub ltan: String,
+}
+
+impl From<crate::predictors::plane::OrbitPlane> for OrbitPlaneDto {
+    fn from(p: crate::predictors::plane::OrbitPlane) -> Self {
+        OrbitPlaneDto {
+            raan_deg: p.raan_deg,
+            raan_rate_deg_per_day: p.raan_rate_deg_per_day,
+            sun_synchronous: p.sun_synchronous,
+            ltan_hours: p.ltan_hours,
+            ltan: crate::predictors::plane::format_local_time(p.ltan_hours),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SatellitePlaneDto {
+    pub norad_id: u64,
+    pub name: Option<String>,
+    pub inclination_deg: f64,
+    #[serde(flatten)]
+    pub plane: OrbitPlaneDto,
+}
+
+/// One page of a time-series listing. Pass `next_cursor` back as `cursor` to
+/// continue; it is also returned on the last page so clients can poll for rows
+/// added later.
+#[derive(Debug, Serialize)]
+pub struct PageDto<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+    pub has_more: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SnapshotDto {
+    pub norad_id: u64,
+    pub timestamp: String,
+    pub position_km: [f64; 3],
+    pub velocity_km_s: [f64; 3],
+}
+
+/// One launch in `/launches`.
+#[derive(Debug, Serialize)]
+pub struct LaunchSummaryDto {
+    /// `YYYY-NNN`.
+    pub launch: String,
+    /// From SATCAT, when it has been fetched.
+    pub launch_date: Option<String>,
+    pub pieces: usize,
+    pub norad_ids: Vec<u64>,
+}
+
+/// The pieces of one launch from `/launches/{launch}`.
+#[derive(Debug, Serialize)]
+pub struct LaunchDetailDto {
+    pub launch: String,
+    pub launch_date: Option<String>,
+    pub time: DateTime<Utc>,
+    pub reference_norad_id: u64,
+    /// Width of the phase offsets, from the piece furthest behind to the one
+    /// furthest ahead.
+    pub phase_spread_deg: f64,
+    pub pieces: Vec<LaunchPieceDto>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LaunchPieceDto {
+    pub norad_id: u64,
+    pub name: Option<String>,
+    /// Piece letters of the international designator.
+    pub piece: String,
+    pub object_type: Option<String>,
+    pub element_epoch: DateTime<Utc>,
+    pub perigee_alt_km: f64,
+    pub apogee_alt_km: f64,
+    pub period_s: f64,
+    /// Period minus the reference's: positive pieces fall behind it.
+    pub period_delta_s: f64,
+    pub lat: Option<f64>,
+    pub lon: Option<f64>,
+    pub alt_km: Option<f64>,
+    /// Argument of latitude minus the reference's, in (-180, 180]; positive is ahead.
+    pub phase_deg: Option<f64>,
+    /// `phase_deg` as time along the reference orbit.
+    pub along_track_s: Option<f64>,
+    pub radial_km: Option<f64>,
+    pub cross_track_km: Option<f64>,
+}
+
+/// How well a satellite's element sets predict their successors.
+#[derive(Debug, Serialize)]
+pub struct SatelliteAccuracyDto {
+    pub norad_id: u64,
+    pub name: Option<String>,
+    pub days: i64,
+    /// `None` without comparisons in the window.
+    pub stats: Option<AccuracyStatsDto>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub samples: Option<Vec<AccuracySampleDto>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccuracyStatsDto {
+    pub samples: usize,
+    pub median_error_km: f64,
+    pub mean_error_km: f64,
+    pub max_error_km: f64,
+    pub median_gap_hours: f64,
+    pub median_error_km_per_day: f64,
+    pub latest_epoch: String,
+}
+
+/// The previous element set propagated to `epoch`, against the new one there.
+#[derive(Debug, Serialize)]
+pub struct AccuracySampleDto {
+    pub epoch: String,
+    pub previous_epoch: String,
+    pub gap_hours: f64,
+    pub error_km: f64,
+    pub radial_km: f64,
+    pub along_track_km: f64,
+    pub cross_track_km: f64,
+}
+
+/// A recorded snapshot flagged as implausible by the anomaly analyzer.
+#[derive(Debug, Serialize)]
+pub struct SnapshotAnomalyDto {
+    pub norad_id: u64,
+    pub timestamp: String,
+    /// `position_jump`, `altitude_spike` or `orbit_jump`.
+    pub kind: String,
+    pub magnitude_km: f64,
+    pub detail: String,
+    pub detected_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PositionSampleDto {
+    pub timestamp: String,
+    /// Snapshots represented by this point (1 for raw snapshots).
+    pub samples: u64,
+    pub position_km: [f64; 3],
+    pub velocity_km_s: [f64; 3],
+}
+
+#[derive(Debug, Serialize)]
+pub struct PositionHistoryDto {
+    pub norad_id: u64,
+    /// `raw`, `1m` or `1h`.
+    pub resolution: &'static str,
+    pub points: Vec<PositionSampleDto>,
+    /// More points exist in the range than were returned.
+    pub truncated: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TleHistoryDto {
+    pub norad_id: u64,
+    pub epoch: String,
+    pub name: Option<String>,
+    pub line1: String,
+    pub line2: String,
+    pub fetched_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FetchLogDto {
+    pub fetched_at: String,
+    pub source: String,
+    pub records: i64,
+    pub rejected: Vec<crate::core::tle::RejectedTle>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct CreateDeviceTokenDto {
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeviceTokenDto {
+    pub id: i64,
+    pub station_id: i64,
+    pub name: Option<String>,
+    pub created_at: String,
+    pub revoked_at: Option<String>,
+    /// The secret itself; only returned when the token is created.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+/// Antenna pointing reported by a tracking client.
+#[derive(Debug, Serialize, serde::Deserialize)]
+pub struct TelemetryDto {
+    pub time: DateTime<Utc>,
+    #[serde(default)]
+    pub norad_id: Option<u64>,
+    pub az_deg: f64,
+    pub el_deg: f64,
+    #[serde(default)]
+    pub signal_dbm: Option<f64>,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub received_at: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditEntryDto {
+    pub at: String,
+    pub actor: String,
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+    pub payload: Option<serde_json::Value>,
+    /// Changed fields as `{ field: { from, to } }`, for resources that can be
+    /// read back.
+    pub diff: Option<serde_json::Value>,
+}
+
+/// Candidate orbit for the `/predict` endpoints: TLE text (two lines, or three
+/// with a name line) or a CCSDS OMM object in Celestrak's JSON layout.
+#[derive(Debug, serde::Deserialize)]
+pub struct AdHocElementsDto {
+    #[serde(default)]
+    pub tle: Option<String>,
+    #[serde(default)]
+    pub omm: Option<serde_json::Value>,
+}
+
+/// Body of `POST /custom-elements` and `PUT /custom-elements/:id`; `name`
+/// overrides the name in the elements.
+#[derive(Debug, serde::Deserialize)]
+pub struct CustomElementsRequestDto {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(flatten)]
+    pub elements: AdHocElementsDto,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CustomElementsDto {
+    pub id: i64,
+    /// Synthetic NORAD ID the satellite is loaded under.
+    pub norad_id: u64,
+    pub name: Option<String>,
+    pub epoch: Option<DateTime<Utc>>,
+    pub tle: Option<String>,
+    pub omm: Option<serde_json::Value>,
+    pub created_at: String,
+    pub updated_at: String,
+    /// Whether the loaded catalog already has this element set; changes are
+    /// picked up on the next TLE load.
+    pub loaded: bool,
+}
+
+/// TEME state (km, km/s) at `epoch`.
+#[derive(Debug, serde::Deserialize)]
+pub struct StateVectorDto {
+    pub epoch: DateTime<Utc>,
+    pub position_km: [f64; 3],
+    pub velocity_km_s: [f64; 3],
+}
+
+/// Body of `POST /satellites/:norad_id/elements`: exactly one of `tle`, `omm`,
+/// `state` or `pin_epoch` (the archived TLE nearest that time).
+#[derive(Debug, serde::Deserialize)]
+pub struct ElementOverrideRequestDto {
+    #[serde(flatten)]
+    pub elements: AdHocElementsDto,
+    #[serde(default)]
+    pub state: Option<StateVectorDto>,
+    #[serde(default)]
+    pub pin_epoch: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ElementOverrideDto {
+    pub id: i64,
+    pub source: String,
+    pub epoch: Option<DateTime<Utc>>,
+    pub tle: Option<String>,
+    pub omm: Option<serde_json::Value>,
+    pub reason: Option<String>,
+    pub created_at: String,
+    pub reverted_at: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ElementOverridesDto {
+    pub norad_id: u64,
+    /// Version in effect, or `null` when the fetched elements are used.
+    pub active: Option<ElementOverrideDto>,
+    /// Whether the loaded catalog already reflects `active`; changes are picked
+    /// up on the next TLE load.
+    pub loaded: bool,
+    /// Every version, newest first.
+    pub history: Vec<ElementOverrideDto>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct PredictPassesRequestDto {
+    #[serde(flatten)]
+    pub elements: AdHocElementsDto,
+    #[serde(default)]
+    pub station_id: Option<i64>,
+    #[serde(default)]
+    pub lat: Option<f64>,
+    #[serde(default)]
+    pub lon: Option<f64>,
+    #[serde(default)]
+    pub alt_m: Option<f64>,
+    #[serde(default)]
+    pub start: Option<DateTime<Utc>>,
+    /// Minutes.
+    #[serde(default)]
+    pub duration: Option<i64>,
+    /// Seconds.
+    #[serde(default)]
+    pub step: Option<i64>,
+    #[serde(default)]
+    pub min_el: Option<f64>,
+    #[serde(default)]
+    pub refraction: Option<bool>,
+    #[serde(default)]
+    pub light_time: Option<bool>,
+    /// Seconds below `min_el` across which passes are joined.
+    #[serde(default)]
+    pub merge_gap: Option<i64>,
+    #[serde(default)]
+    pub min_peak_el: Option<f64>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct PredictPositionRequestDto {
+    #[serde(flatten)]
+    pub elements: AdHocElementsDto,
+    #[serde(default)]
+    pub time: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PredictedPositionDto {
+    pub norad_id: u64,
+    pub name: Option<String>,
+    pub epoch: DateTime<Utc>,
+    pub time: DateTime<Utc>,
+    pub lat: f64,
+    pub lon: f64,
+    pub alt_km: f64,
+    pub speed_km_s: f64,
+    pub position_km: [f64; 3],
+    pub velocity_km_s: [f64; 3],
+}
+
+/// One side of an ephemeris comparison: an ad-hoc element set, a CCSDS OEM
+/// (KVN text, TEME) or a satellite of the loaded catalog.
+#[derive(Debug, serde::Deserialize)]
+pub struct EphemerisSourceDto {
+    #[serde(flatten)]
+    pub elements: AdHocElementsDto,
+    #[serde(default)]
+    pub oem: Option<String>,
+    #[serde(default)]
+    pub norad_id: Option<u64>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct CompareEphemeridesRequestDto {
+    /// Reference whose RIC frame the differences are expressed in.
+    pub reference: EphemerisSourceDto,
+    pub other: EphemerisSourceDto,
+    #[serde(default)]
+    pub start: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub end: Option<DateTime<Utc>>,
+    /// Seconds.
+    #[serde(default)]
+    pub step: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RicDifferenceDto {
+    pub time: DateTime<Utc>,
+    pub radial_km: f64,
+    pub along_track_km: f64,
+    pub cross_track_km: f64,
+    pub total_km: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RicRmsDto {
+    pub radial_km: f64,
+    pub along_track_km: f64,
+    pub cross_track_km: f64,
+    pub total_km: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EphemerisComparisonDto {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub step_s: i64,
+    /// Root-mean-square of each component over all samples.
+    pub rms: RicRmsDto,
+    /// Sample with the largest total difference.
+    pub max: RicDifferenceDto,
+    pub samples: Vec<RicDifferenceDto>,
+}
+
+/// Body of `POST /aois` and `PUT /aois/:id`: a name and a GeoJSON `Point` or
+/// `Polygon` (or a `Feature` with one).
+#[derive(Debug, serde::Deserialize)]
+pub struct AoiRequestDto {
+    pub name: String,
+    pub geometry: serde_json::Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AoiDto {
+    pub id: i64,
+    pub name: String,
+    pub geometry: serde_json::Value,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Times a sensor footprint overlaps an area of interest.
+#[derive(Debug, Serialize)]
+pub struct AoiAccessDto {
+    pub aoi_id: i64,
+    pub norad_id: u64,
+    pub half_angle_deg: f64,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub intervals: Vec<IntervalDto>,
+}
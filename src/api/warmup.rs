@@ -0,0 +1,92 @@
+use axum::http::{header, StatusCode};
+use axum::{response::IntoResponse, routing::get, Json, Router};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+/// Seconds clients are asked to wait before trying again.
+const RETRY_AFTER_S: &str = "5";
+
+/// Environment variable that, set to `1`, holds back the systemd readiness
+/// notification until the first catalog load has finished.
+const WAIT_ENV: &str = "STFCM_WAIT_FOR_CATALOG";
+
+/// Whether readiness waits for the catalog (`STFCM_WAIT_FOR_CATALOG=1`).
+pub fn wait_for_catalog() -> bool {
+    std::env::var(WAIT_ENV).is_ok_and(|v| v == "1")
+}
+
+async fn loading() -> impl IntoResponse {
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        [(header::RETRY_AFTER, RETRY_AFTER_S)],
+        Json(serde_json::json!({"error": "the satellite catalog is loading; retry shortly"})),
+    )
+}
+
+async fn health() -> impl IntoResponse {
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        [(header::RETRY_AFTER, RETRY_AFTER_S)],
+        Json(serde_json::json!({"status": "starting", "elements": 0, "read_only": crate::utils::db::read_only()})),
+    )
+}
+
+fn router() -> Router {
+    Router::new().route("/health", get(health)).route("/api/v1/health", get(health)).fallback(loading)
+}
+
+/// Answers every request on the listener with `503 Service Unavailable` and a
+/// `Retry-After` while the catalog loads, so clients get a clear answer instead
+/// of a refused connection. Aborted when dropped.
+pub struct Warmup {
+    stop: Option<oneshot::Sender<()>>,
+    task: JoinHandle<()>,
+}
+
+impl Warmup {
+    pub fn spawn(listener: &std::net::TcpListener) -> std::io::Result<Warmup> {
+        let listener = tokio::net::TcpListener::from_std(listener.try_clone()?)?;
+        let (stop, stopped) = oneshot::channel::<()>();
+        let task = tokio::spawn(async move {
+            let shutdown = async move {
+                let _ = stopped.await;
+            };
+            if let Err(e) = axum::serve(listener, router()).with_graceful_shutdown(shutdown).await {
+                tracing::warn!(error = %e, "Warm-up server failed");
+            }
+        });
+        Ok(Warmup { stop: Some(stop), task })
+    }
+
+    /// Stops accepting and lets the requests in flight finish. Connections that
+    /// arrive meanwhile wait in the listen backlog for the API server.
+    pub async fn stop(mut self) {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+        let _ = (&mut self.task).await;
+    }
+}
+
+impl Drop for Warmup {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn every_request_is_asked_to_retry() {
+        for path in ["/health", "/api/v1/satellites/25544/passes", "/stations"] {
+            let response = router().oneshot(Request::get(path).body(Body::empty()).unwrap()).await.unwrap();
+            assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+            assert_eq!(response.headers()[header::RETRY_AFTER], RETRY_AFTER_S);
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use std::path::{Path, PathBuf};
+
+use chrono::{SecondsFormat, Utc};
+use tracing::info;
+
+use crate::core::bundle::{self, BundleFormat};
+
+/// `STfCM export-config` and `STfCM import-config` options.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    /// Bundle file; standard output or input when unset.
+    pub path: Option<PathBuf>,
+    pub format: BundleFormat,
+    /// Import only: clear the existing configuration first.
+    pub replace: bool,
+}
+
+/// `STfCM export-config` arguments.
+#[derive(Debug, Clone, clap::Args)]
+pub struct ExportArgs {
+    /// Bundle file (default: standard output).
+    #[arg(long, value_name = "FILE")]
+    out: Option<PathBuf>,
+    /// json or yaml (default: from the file extension, else json).
+    #[arg(long, value_parser = parse_format)]
+    format: Option<BundleFormat>,
+}
+
+/// `STfCM import-config` arguments.
+#[derive(Debug, Clone, clap::Args)]
+pub struct ImportArgs {
+    /// Bundle file; `-` reads standard input.
+    #[arg(value_name = "FILE")]
+    file: PathBuf,
+    /// Clear the existing configuration first.
+    #[arg(long)]
+    replace: bool,
+    /// json or yaml (default: from the file extension, else json).
+    #[arg(long, value_parser = parse_format)]
+    format: Option<BundleFormat>,
+}
+
+fn parse_format(value: &str) -> Result<BundleFormat, String> {
+    BundleFormat::parse(value).ok_or_else(|| format!("unknown format {}; use json or yaml", value))
+}
+
+impl Options {
+    fn new(path: Option<PathBuf>, format: Option<BundleFormat>, replace: bool) -> Options {
+        let from_extension = path.as_deref().and_then(Path::extension).and_then(|e| e.to_str()).and_then(BundleFormat::parse);
+        let format = format.or(from_extension).unwrap_or(BundleFormat::Json);
+        Options { path: path.filter(|p| p.as_os_str() != "-"), format, replace }
+    }
+}
+
+impl ExportArgs {
+    pub(super) fn into_options(self) -> Options {
+        Options::new(self.out, self.format, false)
+    }
+}
+
+impl ImportArgs {
+    pub(super) fn into_options(self) -> Options {
+        Options::new(Some(self.file), self.format, self.replace)
+    }
+}
+
+/// Writes the configuration bundle of the local database.
+pub fn export(options: &Options) -> Result<(), String> {
+    let conn = crate::utils::db::open_or_init().map_err(|e| e.to_string())?;
+    let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let bundle = bundle::export_bundle(&conn, &now).map_err(|e| e.to_string())?;
+    let text = options.format.encode(&bundle)?;
+    match &options.path {
+        Some(path) => {
+            std::fs::write(path, text).map_err(|e| format!("cannot write {}: {}", path.display(), e))?;
+            info!(path = %path.display(), stations = bundle.stations.len(), custom_elements = bundle.custom_elements.len(), "Exported configuration");
+        }
+        None => print!("{}", text),
+    }
+    Ok(())
+}
+
+/// Restores a configuration bundle into the local database.
+pub fn import(options: &Options) -> Result<(), String> {
+    let text = match &options.path {
+        Some(path) => std::fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?,
+        None => std::io::read_to_string(std::io::stdin()).map_err(|e| e.to_string())?,
+    };
+    let bundle = options.format.decode(&text).map_err(|e| format!("invalid bundle: {}", e))?;
+    let conn = crate::utils::db::open_or_init().map_err(|e| e.to_string())?;
+    let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let summary = bundle::import_bundle(&conn, &bundle, options.replace, &now).map_err(|e| e.to_string())?;
+    info!(
+        replace = options.replace,
+        stations_created = summary.stations_created,
+        stations_updated = summary.stations_updated,
+        tags = summary.tags,
+        groups = summary.groups,
+        aliases = summary.aliases,
+        protected_assets = summary.protected_assets,
+        custom_elements = summary.custom_elements,
+        "Imported configuration"
+    );
+    Ok(())
+}
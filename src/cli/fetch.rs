@@ -0,0 +1,86 @@
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use super::Output;
+use crate::collectors::spacetrack::{self, GpQuery};
+use crate::collectors::tle_fetcher::{self, ACTIVE_GROUP, LAST_30_DAYS_GROUP};
+
+/// `STfCM fetch` options.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    pub groups: Vec<String>,
+    /// Download from Space-Track instead of Celestrak.
+    pub spacetrack: Option<GpQuery>,
+    pub output: Output,
+}
+
+/// `STfCM fetch` arguments.
+#[derive(Debug, Clone, clap::Args)]
+pub struct Args {
+    /// Celestrak groups, e.g. amateur (default: active and last-30-days).
+    #[arg(value_name = "GROUP")]
+    groups: Vec<String>,
+    /// Download GP data from Space-Track with the configured account instead.
+    #[arg(long, conflicts_with = "groups")]
+    spacetrack: bool,
+    /// Space-Track: only this NORAD ID or range, e.g. 25544-25600.
+    #[arg(long, value_name = "RANGE", requires = "spacetrack", value_parser = spacetrack::parse_norad_range)]
+    norad: Option<(u64, u64)>,
+    /// Space-Track: only element sets with an epoch within this many days.
+    #[arg(long, value_name = "DAYS", requires = "spacetrack")]
+    epoch_days: Option<u32>,
+    #[arg(long, value_enum, default_value_t)]
+    format: Output,
+}
+
+impl Args {
+    pub(super) fn into_options(self) -> Options {
+        if self.spacetrack {
+            let query = GpQuery { norad_range: self.norad, epoch_days: self.epoch_days };
+            return Options { groups: Vec::new(), spacetrack: Some(query), output: self.format };
+        }
+        let groups = if self.groups.is_empty() { vec![ACTIVE_GROUP.to_string(), LAST_30_DAYS_GROUP.to_string()] } else { self.groups };
+        Options { groups, spacetrack: None, output: self.format }
+    }
+}
+
+/// One downloaded group.
+#[derive(Debug, Serialize)]
+struct Fetched {
+    group: String,
+    path: PathBuf,
+    records: usize,
+    rejected: usize,
+}
+
+/// Downloads the groups into the TLE directory, where the server and the other
+/// subcommands pick up the newest copy, and prints what each contained.
+pub async fn run(options: Options, config: &crate::config::Config) -> Result<(), String> {
+    let mut fetched = Vec::new();
+    if let Some(query) = &options.spacetrack {
+        let group = format!("{}:{}", spacetrack::SOURCE, query.cache_name());
+        let path = spacetrack::fetch_spacetrack_gp(config, query).await.map_err(|e| e.to_string())?;
+        let report = crate::core::tle::read_tle_report(&path).map_err(|e| format!("{}: {}", group, e))?;
+        fetched.push(Fetched { group, path, records: report.records.len(), rejected: report.rejected.len() });
+    }
+    for group in &options.groups {
+        let path = tle_fetcher::fetch_celestrak_group(config, group).await.map_err(|e| format!("{}: {}", group, e))?;
+        let report = crate::core::tle::read_tle_report(&path).map_err(|e| format!("{}: {}", group, e))?;
+        fetched.push(Fetched { group: group.clone(), path, records: report.records.len(), rejected: report.rejected.len() });
+    }
+    let text = match options.output {
+        Output::Table => {
+            let width = fetched.iter().map(|f| f.group.len()).max().unwrap_or(0).max(16);
+            let mut out = format!("{:<width$}  {:>7}  {:>8}  Path\n", "Group", "Records", "Rejected");
+            for f in &fetched {
+                let _ = writeln!(out, "{:<width$}  {:>7}  {:>8}  {}", f.group, f.records, f.rejected, f.path.display());
+            }
+            out
+        }
+        Output::Json => serde_json::to_string_pretty(&fetched).map_err(|e| e.to_string())? + "\n",
+    };
+    std::io::stdout().lock().write_all(text.as_bytes()).map_err(|e| e.to_string())
+}
@@ -0,0 +1,238 @@
+// Subcommands that run without the API server
+pub mod config;
+pub mod fetch;
+pub mod passes;
+pub mod positions;
+pub mod predict;
+pub mod tui;
+
+use std::path::PathBuf;
+
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, ValueEnum};
+
+use crate::collectors::tle_fetcher::{self, ACTIVE_GROUP, LAST_30_DAYS_GROUP};
+use crate::config::Config;
+use crate::utils::daemon;
+
+/// Satellite tracker: serves the API and web UI, or runs one of the
+/// subcommands without the server.
+#[derive(Debug, Parser)]
+#[command(name = "STfCM", version, about)]
+struct Cli {
+    /// Configuration file (default: $STFCM_CONFIG, else ./stfcm.toml if present).
+    #[arg(long, global = true, value_name = "FILE")]
+    config: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Option<Subcommand>,
+    /// `serve` options, also accepted without the subcommand but not before
+    /// another one.
+    #[command(flatten)]
+    serve: ServeArgs,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum Subcommand {
+    /// Serve the API and web UI (the default).
+    Serve(ServeArgs),
+    /// Download Celestrak groups or Space-Track GP data into the TLE cache.
+    Fetch(fetch::Args),
+    /// Print the passes of satellites over a site.
+    Passes(passes::Args),
+    /// Print where satellites are, once or continuously.
+    Positions(positions::Args),
+    /// Write pass predictions of a group to a file, e.g. from cron.
+    Predict(predict::Args),
+    /// Terminal dashboard of a station.
+    Tui(tui::Args),
+    /// Write the configuration bundle.
+    ExportConfig(config::ExportArgs),
+    /// Restore a configuration bundle.
+    ImportConfig(config::ImportArgs),
+}
+
+#[derive(Debug, Clone, Default, clap::Args)]
+struct ServeArgs {
+    /// Detach from the terminal.
+    #[arg(long)]
+    daemon: bool,
+    /// Write the process ID here while running (default with --daemon: data/stfcm.pid).
+    #[arg(long, value_name = "PATH")]
+    pid_file: Option<PathBuf>,
+    /// Append stdout/stderr here once detached.
+    #[arg(long, value_name = "PATH")]
+    log_file: Option<PathBuf>,
+}
+
+/// `--station <id>` or `--lat <deg> --lon <deg> [--alt-m <m>]`.
+#[derive(Debug, Clone, clap::Args)]
+pub struct SiteArgs {
+    /// Ground station ID.
+    #[arg(long, value_name = "ID", conflicts_with_all = ["lat", "lon"])]
+    station: Option<i64>,
+    /// Latitude of the site (degrees).
+    #[arg(long, value_name = "DEG", requires = "lon", allow_negative_numbers = true)]
+    lat: Option<f64>,
+    /// Longitude of the site (degrees).
+    #[arg(long, value_name = "DEG", requires = "lat", allow_negative_numbers = true)]
+    lon: Option<f64>,
+    /// Height of the site above the ellipsoid (metres).
+    #[arg(long, value_name = "M", default_value_t = 0.0, allow_negative_numbers = true)]
+    alt_m: f64,
+}
+
+impl SiteArgs {
+    fn site(&self) -> Option<predict::Site> {
+        match (self.station, self.lat, self.lon) {
+            (Some(id), _, _) => Some(predict::Site::Station(id)),
+            (None, Some(lat), Some(lon)) => Some(predict::Site::Position { lat, lon, alt_m: self.alt_m }),
+            _ => None,
+        }
+    }
+
+    fn required(&self, subcommand: &str) -> Result<predict::Site, clap::Error> {
+        self.site().ok_or_else(|| usage_error(subcommand, ErrorKind::MissingRequiredArgument, format!("{} needs either --station <id> or --lat <deg> --lon <deg>", subcommand)))
+    }
+}
+
+/// How `fetch`, `passes` and `positions` print their results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Output {
+    /// Aligned columns for reading.
+    #[default]
+    Table,
+    /// JSON for scripts.
+    Json,
+}
+
+/// An error reported with the subcommand's usage, as clap reports its own.
+fn usage_error(subcommand: &str, kind: ErrorKind, message: impl std::fmt::Display) -> clap::Error {
+    let mut cli = Cli::command();
+    cli.build();
+    match cli.find_subcommand_mut(subcommand) {
+        Some(sub) => sub.error(kind, message),
+        None => cli.error(kind, message),
+    }
+}
+
+/// What the process was started to do; serving the API is the default.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Serve(daemon::Options),
+    Fetch(fetch::Options),
+    Passes(passes::Options),
+    Tui(tui::Options),
+    Predict(predict::Options),
+    Positions(positions::Options),
+    ExportConfig(config::Options),
+    ImportConfig(config::Options),
+}
+
+/// The parsed command line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Invocation {
+    /// `--config`; `Config::load` falls back to `STFCM_CONFIG` and `stfcm.toml`.
+    pub config: Option<PathBuf>,
+    pub command: Command,
+}
+
+impl Invocation {
+    /// Parses the arguments after the program name. `--help` and `--version`
+    /// come back as errors that print to standard output when exited with.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Invocation, clap::Error> {
+        let cli = Cli::try_parse_from(std::iter::once("STfCM".to_string()).chain(args))?;
+        // Checked here rather than by clap so that the global --config may precede a subcommand
+        if cli.command.is_some() && (cli.serve.daemon || cli.serve.pid_file.is_some() || cli.serve.log_file.is_some()) {
+            return Err(Cli::command().error(ErrorKind::ArgumentConflict, "--daemon, --pid-file and --log-file go after `serve` or without a subcommand"));
+        }
+        let serve = |a: ServeArgs| daemon::Options::new(a.daemon, a.pid_file, a.log_file);
+        let command = match cli.command {
+            None => Command::Serve(serve(cli.serve)),
+            Some(Subcommand::Serve(a)) => Command::Serve(serve(a)),
+            Some(Subcommand::Fetch(a)) => Command::Fetch(a.into_options()),
+            Some(Subcommand::Passes(a)) => Command::Passes(a.into_options()?),
+            Some(Subcommand::Positions(a)) => Command::Positions(a.into_options()),
+            Some(Subcommand::Predict(a)) => Command::Predict(a.into_options()?),
+            Some(Subcommand::Tui(a)) => Command::Tui(a.into_options()),
+            Some(Subcommand::ExportConfig(a)) => Command::ExportConfig(a.into_options()),
+            Some(Subcommand::ImportConfig(a)) => Command::ImportConfig(a.into_options()),
+        };
+        Ok(Invocation { config: cli.config, command })
+    }
+}
+
+impl Command {
+    /// As [`Invocation::parse`], without the configuration file.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Command, clap::Error> {
+        Invocation::parse(args).map(|i| i.command)
+    }
+}
+
+/// The catalog as the server loads it: the newest cached Celestrak sets (the
+/// active group is downloaded if none is cached), uploaded TLEs screened at
+/// `now`, custom element sets and overrides. Nothing is written to the catalog
+/// tables.
+pub async fn load_catalog(config: &Config, now: chrono::DateTime<chrono::Utc>) -> Result<Vec<sgp4::Elements>, String> {
+    let active = match tle_fetcher::latest_cached(config, ACTIVE_GROUP).map_err(|e| e.to_string())? {
+        Some(path) => path,
+        None => tle_fetcher::fetch_celestrak_group(config, ACTIVE_GROUP).await.map_err(|e| e.to_string())?,
+    };
+    let mut elements = crate::core::tle::read_tle_report(&active).map_err(|e| e.to_string())?.elements;
+    let mut extra = Vec::new();
+    if let Some(recent) = tle_fetcher::latest_cached(config, LAST_30_DAYS_GROUP).ok().flatten() {
+        extra.extend(crate::core::tle::read_tle_report(&recent).map_err(|e| e.to_string())?.records);
+    }
+    let conn = crate::utils::db::open_or_init().map_err(|e| e.to_string())?;
+    extra.extend(
+        crate::utils::db::list_latest_records_with_tag(&conn, crate::analyzers::new_objects::UPLOADED_TAG).map_err(|e| e.to_string())?,
+    );
+    crate::core::tle::merge_records(&mut elements, &extra);
+    crate::core::screening::screen(&mut elements, &crate::core::screening::ScreeningLimits::from_env(), now);
+    elements.extend(crate::core::custom::load_custom_elements(&conn).map_err(|e| e.to_string())?);
+    crate::core::overrides::apply_overrides(&conn, &mut elements).map_err(|e| e.to_string())?;
+    Ok(elements)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serves_unless_a_subcommand_is_given() {
+        let args = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert!(matches!(Command::parse(args(&["--daemon"])), Ok(Command::Serve(o)) if o.daemon));
+        let Ok(Command::Tui(tui)) = Command::parse(args(&["tui", "--station", "4", "--norad", "25544,43013"])) else {
+            panic!("expected the tui subcommand");
+        };
+        assert_eq!((tui.station_id, tui.norad_ids), (4, vec![25544, 43013]));
+        assert!(Command::parse(args(&["tui"])).is_err());
+        let Ok(Command::ImportConfig(import)) = Command::parse(args(&["import-config", "site.yml", "--replace"])) else {
+            panic!("expected the import-config subcommand");
+        };
+        assert_eq!((import.format, import.replace), (crate::core::bundle::BundleFormat::Yaml, true));
+
+        Cli::command().debug_assert();
+        assert!(matches!(Command::parse(args(&["serve", "--daemon"])), Ok(Command::Serve(o)) if o.pid_file.is_some()));
+        assert!(Command::parse(args(&["--daemon", "passes"])).is_err());
+        let Ok(Command::Passes(passes)) = Command::parse(args(&["passes", "--norad", "25544", "--lat", "40.7", "--lon", "-74", "--format", "json"])) else {
+            panic!("expected the passes subcommand");
+        };
+        assert_eq!(passes.search.site, predict::Site::Position { lat: 40.7, lon: -74.0, alt_m: 0.0 });
+        assert_eq!((passes.search.minutes, passes.output), (24 * 60, Output::Json));
+        assert!(Command::parse(args(&["passes", "--norad", "25544"])).is_err());
+        assert!(Command::parse(args(&["passes", "--station", "1", "--lat", "4", "--lon", "5"])).is_err());
+        let Ok(Command::Fetch(fetch)) = Command::parse(args(&["fetch"])) else {
+            panic!("expected the fetch subcommand");
+        };
+        assert_eq!(fetch.groups, [ACTIVE_GROUP, LAST_30_DAYS_GROUP]);
+        let Ok(Command::Fetch(fetch)) = Command::parse(args(&["fetch", "--spacetrack", "--norad", "25544-25600", "--epoch-days", "7"])) else {
+            panic!("expected the fetch subcommand");
+        };
+        assert_eq!(fetch.spacetrack.map(|q| q.cache_name()).as_deref(), Some("gp-25544-25600-7d"));
+        assert!(Command::parse(args(&["fetch", "amateur", "--spacetrack"])).is_err());
+        assert!(Command::parse(args(&["fetch", "--norad", "25544"])).is_err());
+        for a in [&["--config", "site.toml", "fetch"][..], &["fetch", "--config", "site.toml"], &["--config", "site.toml", "--daemon"]] {
+            assert_eq!(Invocation::parse(args(a)).unwrap().config, Some(PathBuf::from("site.toml")));
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use std::fmt::Write as _;
+use std::io::Write as _;
+
+use super::predict::{self, Search, SearchArgs};
+use super::Output;
+use crate::api::types::SatellitePassDto;
+
+/// `STfCM passes` options.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    pub search: Search,
+    pub output: Output,
+}
+
+/// `STfCM passes` arguments.
+#[derive(Debug, Clone, clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    search: SearchArgs,
+    /// Hours to predict from now.
+    #[arg(long, default_value_t = 24, value_parser = clap::value_parser!(i64).range(1..=predict::MAX_DAYS * 24))]
+    hours: i64,
+    #[arg(long, value_enum, default_value_t)]
+    format: Output,
+}
+
+impl Args {
+    pub(super) fn into_options(self) -> Result<Options, clap::Error> {
+        Ok(Options { search: self.search.into_search("passes", self.hours * 60)?, output: self.format })
+    }
+}
+
+fn table(passes: &[SatellitePassDto]) -> String {
+    if passes.is_empty() {
+        return "No passes\n".to_string();
+    }
+    let mut out = format!("{:>6}  {:<24}  {:<19}  {:<8}  {:>6}  {:>5}  {}\n", "NORAD", "Name", "AOS (UTC)", "LOS", "Max el", "Dur", "Path");
+    for p in passes {
+        let name: String = p.name.as_deref().unwrap_or("").chars().take(24).collect();
+        let _ = writeln!(
+            out,
+            "{:>6}  {:<24}  {}  {}  {:>5.1}°  {:>2}:{:02}  {}",
+            p.norad_id,
+            name,
+            p.pass.start.format("%Y-%m-%d %H:%M:%S"),
+            p.pass.end.format("%H:%M:%S"),
+            p.pass.max_elevation_deg,
+            p.pass.duration_s / 60,
+            p.pass.duration_s % 60,
+            p.pass.direction
+        );
+    }
+    out
+}
+
+/// Predicts the passes of the selected satellites and prints them, sorted by AOS.
+pub async fn run(options: Options, config: &crate::config::Config, clock: &dyn crate::core::clock::Clock) -> Result<(), String> {
+    let passes = predict::search(&options.search, config, clock).await?;
+    let text = match options.output {
+        Output::Table => table(&passes),
+        Output::Json => serde_json::to_string_pretty(&passes).map_err(|e| e.to_string())? + "\n",
+    };
+    std::io::stdout().lock().write_all(text.as_bytes()).map_err(|e| e.to_string())
+}
@@ -0,0 +1,217 @@
+use std::io::Write;
+use std::time::Duration as StdDuration;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use super::predict::Site;
+use super::Output;
+use crate::core::clock::Clock;
+use crate::core::catalog::Catalog;
+use crate::core::coords::{ecef_to_geodetic_height, eci_to_ecef, gmst};
+use crate::core::orbit::minutes_since_epoch;
+use crate::predictors::passes::{slant_range_km, topocentric_look_deg, ObserverPosition};
+
+/// Shortest refresh interval of `--watch`.
+const MIN_WATCH: StdDuration = StdDuration::from_millis(100);
+
+/// `STfCM positions` options.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    pub norad_ids: Vec<u64>,
+    /// Adds az/el/range from this site when given.
+    pub site: Option<Site>,
+    /// Print again at this interval until interrupted; once when unset.
+    pub watch: Option<StdDuration>,
+    pub output: Output,
+}
+
+/// Parses `500ms`, `1s`, `2m` or plain seconds.
+fn parse_interval(text: &str) -> Option<StdDuration> {
+    let text = text.trim();
+    let (number, unit) = text.find(|c: char| c.is_ascii_alphabetic()).map_or((text, "s"), |i| text.split_at(i));
+    let value: f64 = number.parse().ok()?;
+    let seconds = match unit {
+        "ms" => value / 1000.0,
+        "s" => value,
+        "m" => value * 60.0,
+        _ => return None,
+    };
+    (seconds.is_finite() && seconds >= 0.0).then(|| StdDuration::from_secs_f64(seconds))
+}
+
+/// `--watch` values: the interval, raised to [`MIN_WATCH`].
+fn parse_watch(value: &str) -> Result<StdDuration, String> {
+    parse_interval(value).map(|i| i.max(MIN_WATCH)).ok_or_else(|| format!("invalid interval {}; use e.g. 1s or 500ms", value))
+}
+
+/// `STfCM positions` arguments.
+#[derive(Debug, Clone, clap::Args)]
+pub struct Args {
+    /// Satellites to show.
+    #[arg(long, value_name = "ID,...", value_delimiter = ',', required = true)]
+    norad: Vec<u64>,
+    #[command(flatten)]
+    site: super::SiteArgs,
+    /// Print again at this interval (e.g. 1s, 500ms) until interrupted.
+    #[arg(long, value_name = "INTERVAL", value_parser = parse_watch)]
+    watch: Option<StdDuration>,
+    /// table redraws in place; json prints one array per update.
+    #[arg(long, value_enum, default_value_t)]
+    format: Output,
+}
+
+impl Args {
+    pub(super) fn into_options(self) -> Options {
+        Options { norad_ids: self.norad, site: self.site.site(), watch: self.watch, output: self.format }
+    }
+}
+
+/// Where one satellite is at one instant.
+struct Position {
+    norad_id: u64,
+    name: String,
+    lat: f64,
+    lon: f64,
+    alt_km: f64,
+    /// Azimuth, elevation and slant range from the site.
+    look: Option<(f64, f64, f64)>,
+}
+
+impl Position {
+    fn line(&self, time: DateTime<Utc>) -> String {
+        let mut line = format!("{}  {:<24} lat {:8.3}°  lon {:9.3}°  alt {:8.1} km", time.format("%H:%M:%S"), self.name, self.lat, self.lon, self.alt_km);
+        if let Some((az, el, range)) = self.look {
+            line.push_str(&format!("  az {:6.2}°  el {:6.2}°  range {:8.1} km", az, el, range));
+        }
+        line
+    }
+
+    fn json(&self, time: DateTime<Utc>) -> serde_json::Value {
+        let mut value = serde_json::json!({
+            "time": time.to_rfc3339_opts(SecondsFormat::Millis, true),
+            "norad_id": self.norad_id,
+            "name": self.name,
+            "lat": self.lat,
+            "lon": self.lon,
+            "alt_km": self.alt_km,
+        });
+        if let Some((az, el, range)) = self.look {
+            value["az_deg"] = az.into();
+            value["el_deg"] = el.into();
+            value["range_km"] = range.into();
+        }
+        value
+    }
+}
+
+/// A selected satellite with its SGP4 constants, initialised once.
+struct Tracked<'a> {
+    elements: &'a sgp4::Elements,
+    name: Option<&'a str>,
+    constants: sgp4::Constants<'static>,
+}
+
+impl Tracked<'_> {
+    fn position(&self, observer: Option<&ObserverPosition>, t: DateTime<Utc>) -> Result<Position, sgp4::Error> {
+        let pred = self.constants.propagate(minutes_since_epoch(self.elements, t))?;
+        let theta = gmst(t);
+        let (x, y, z) = eci_to_ecef(&pred.position, theta);
+        let (lat, lon, alt_km) = ecef_to_geodetic_height(x, y, z);
+        let look = observer.map(|o| {
+            let (el, az) = topocentric_look_deg(&pred.position, theta, o);
+            (az.rem_euclid(360.0), el, slant_range_km(&pred.position, theta, o))
+        });
+        let name = self.name.map_or_else(|| self.elements.norad_id.to_string(), str::to_string);
+        Ok(Position { norad_id: self.elements.norad_id, name, lat, lon, alt_km, look })
+    }
+}
+
+/// Prints the selected satellites' positions, and look angles from the site if
+/// one is given; with `--watch`, redraws them in place until interrupted. JSON
+/// output prints one array per update instead.
+pub fn run(options: Options, catalog: Vec<sgp4::Elements>, clock: &dyn Clock) -> Result<(), String> {
+    let observer = match options.site {
+        Some(Site::Station(id)) => {
+            let conn = crate::utils::db::open_or_init().map_err(|e| e.to_string())?;
+            let st = crate::utils::db::get_station(&conn, id).map_err(|_| format!("station {} not found", id))?;
+            Some(ObserverPosition { lat_deg: st.lat, lon_deg: st.lon, alt_km: st.alt_m / 1000.0 })
+        }
+        Some(Site::Position { lat, lon, alt_m }) => Some(ObserverPosition { lat_deg: lat, lon_deg: lon, alt_km: alt_m / 1000.0 }),
+        None => None,
+    };
+    let catalog = Catalog::new(catalog);
+    let mut tracked = Vec::new();
+    for id in &options.norad_ids {
+        let elements = catalog.get(*id).ok_or_else(|| format!("NORAD {} is not in the catalog", id))?;
+        let constants = sgp4::Constants::from_elements(elements).map_err(|e| format!("NORAD {}: {}", id, e))?;
+        tracked.push(Tracked { elements, name: catalog.name_of(*id), constants });
+    }
+
+    let mut stdout = std::io::stdout().lock();
+    let mut first = true;
+    loop {
+        let now = clock.now();
+        if options.output == Output::Json {
+            let positions: Vec<serde_json::Value> = tracked
+                .iter()
+                .map(|t| match t.position(observer.as_ref(), now) {
+                    Ok(p) => p.json(now),
+                    Err(e) => serde_json::json!({"norad_id": t.elements.norad_id, "error": e.to_string()}),
+                })
+                .collect();
+            writeln!(stdout, "{}", serde_json::Value::Array(positions)).map_err(|e| e.to_string())?;
+        } else {
+            if !first {
+                // Back to the top of the previous block
+                write!(stdout, "\x1b[{}A", tracked.len()).map_err(|e| e.to_string())?;
+            }
+            for t in &tracked {
+                let line = match t.position(observer.as_ref(), now) {
+                    Ok(p) => p.line(now),
+                    Err(e) => format!("{}  NORAD {}: {}", now.format("%H:%M:%S"), t.elements.norad_id, e),
+                };
+                writeln!(stdout, "\x1b[2K{}", line).map_err(|e| e.to_string())?;
+            }
+        }
+        stdout.flush().map_err(|e| e.to_string())?;
+        let Some(interval) = options.watch else {
+            return Ok(());
+        };
+        first = false;
+        std::thread::sleep(interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_options_and_formats_a_position() {
+        let parse = |a: &str| match super::super::Command::parse(format!("positions {}", a).split(' ').map(String::from)) {
+            Ok(super::super::Command::Positions(o)) => Ok(o),
+            Ok(other) => panic!("expected the positions subcommand, got {:?}", other),
+            Err(e) => Err(e),
+        };
+        let o = parse("--norad 25544 --station 1 --watch 1s --format json").unwrap();
+        assert_eq!((o.norad_ids, o.site, o.watch, o.output), (vec![25544], Some(Site::Station(1)), Some(StdDuration::from_secs(1)), Output::Json));
+        assert_eq!(parse("--norad 1,2 --watch 10ms").unwrap().watch, Some(MIN_WATCH));
+        assert_eq!(parse_interval("2m"), Some(StdDuration::from_secs(120)));
+        assert_eq!(parse_interval("3"), Some(StdDuration::from_secs(3)));
+        assert!(parse("--station 1").is_err());
+        assert!(parse("--norad 1 --lat 5").is_err());
+        assert!(parse("--norad 1 --watch 1h").is_err());
+
+        let elements = crate::testing::fixtures::catalog().swap_remove(0);
+        let tracked = Tracked { constants: sgp4::Constants::from_elements(&elements).unwrap(), name: Some("ISS"), elements: &elements };
+        let t = tracked.elements.datetime.and_utc();
+        let p = tracked.position(Some(&crate::testing::fixtures::STATION), t).unwrap();
+        let (_, el, range) = p.look.unwrap();
+        assert!((-90.0..=90.0).contains(&el) && range > 0.0 && p.alt_km > 100.0);
+        let line = p.line(t);
+        assert!(line.contains(" az ") && line.contains(" lat "));
+        assert!(!tracked.position(None, t).unwrap().line(t).contains(" az "));
+        assert_eq!(p.json(t)["norad_id"], tracked.elements.norad_id);
+        assert_eq!(p.name, "ISS");
+    }
+}
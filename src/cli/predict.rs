@@ -0,0 +1,346 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use tracing::{info, warn};
+
+use crate::analyzers::query::{SatelliteQuery, Subject};
+use crate::api::horizon;
+use crate::api::types::{PassWindowDto, SatellitePassDto};
+use crate::collectors::tle_fetcher;
+use crate::config::Config;
+use crate::predictors::geo::is_geosynchronous;
+use crate::predictors::passes::{merge_passes, predict_passes_with_options, LookOptions, Observer, ObserverPosition, PassWindow};
+
+/// Longest span one run predicts.
+pub const MAX_DAYS: i64 = 14;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    Csv,
+    Json,
+    Ics,
+}
+
+impl Format {
+    fn parse(s: &str) -> Option<Format> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" => Some(Format::Csv),
+            "json" => Some(Format::Json),
+            "ics" => Some(Format::Ics),
+            _ => None,
+        }
+    }
+}
+
+/// Where passes are predicted for.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Site {
+    Station(i64),
+    Position { lat: f64, lon: f64, alt_m: f64 },
+}
+
+/// Satellites, site and filters of a pass search, shared by `predict` and `passes`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Search {
+    pub site: Site,
+    /// Celestrak group to predict, e.g. `amateur`; the whole catalog when unset.
+    pub group: Option<String>,
+    /// Only these satellites (within the group, if one is given).
+    pub norad_ids: Vec<u64>,
+    /// Only the satellites matching this expression.
+    pub filter: Option<SatelliteQuery>,
+    /// Length of the window from now (minutes).
+    pub minutes: i64,
+    pub min_el: f64,
+    /// Sampling step of the pass search (seconds).
+    pub step: i64,
+    /// Join passes separated by at most this many seconds below `min_el`.
+    pub merge_gap: i64,
+    /// Drop passes whose maximum elevation stays below this.
+    pub min_peak_el: Option<f64>,
+}
+
+/// `STfCM predict` options.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    pub search: Search,
+    pub out: PathBuf,
+    pub format: Format,
+}
+
+/// Arguments of a pass search except its length.
+#[derive(Debug, Clone, clap::Args)]
+pub struct SearchArgs {
+    #[command(flatten)]
+    site: super::SiteArgs,
+    /// Celestrak group to predict, e.g. amateur (default: the whole catalog).
+    #[arg(long)]
+    group: Option<String>,
+    /// Only these satellites.
+    #[arg(long, value_name = "ID,...", value_delimiter = ',')]
+    norad: Vec<u64>,
+    /// Only the satellites matching a filter expression, e.g. "inclination>97 AND perigee<600".
+    #[arg(long, value_name = "EXPR")]
+    filter: Option<SatelliteQuery>,
+    /// Minimum elevation of a pass (degrees).
+    #[arg(long, value_name = "DEG", default_value_t = 10.0, allow_negative_numbers = true)]
+    min_el: f64,
+    /// Sampling step of the search (seconds).
+    #[arg(long, value_name = "S", default_value_t = 15, value_parser = clap::value_parser!(i64).range(1..))]
+    step: i64,
+    /// Join passes split by dips below --min-el of at most this long (seconds).
+    #[arg(long, value_name = "S", default_value_t = 0, value_parser = clap::value_parser!(i64).range(0..))]
+    merge_gap: i64,
+    /// Drop passes that never climb this high (degrees).
+    #[arg(long, value_name = "DEG", allow_negative_numbers = true)]
+    min_peak_el: Option<f64>,
+}
+
+impl SearchArgs {
+    pub(super) fn into_search(self, subcommand: &str, minutes: i64) -> Result<Search, clap::Error> {
+        Ok(Search {
+            site: self.site.required(subcommand)?,
+            group: self.group,
+            norad_ids: self.norad,
+            filter: self.filter,
+            minutes,
+            min_el: self.min_el,
+            step: self.step,
+            merge_gap: self.merge_gap,
+            min_peak_el: self.min_peak_el,
+        })
+    }
+}
+
+/// `STfCM predict` arguments.
+#[derive(Debug, Clone, clap::Args)]
+pub struct Args {
+    #[command(flatten)]
+    search: SearchArgs,
+    /// Days to predict from now.
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(i64).range(1..=MAX_DAYS))]
+    days: i64,
+    /// File to write.
+    #[arg(long, value_name = "FILE")]
+    out: PathBuf,
+    /// File format (default: from the --out extension).
+    #[arg(long, value_enum, ignore_case = true)]
+    format: Option<Format>,
+}
+
+impl Args {
+    pub(super) fn into_options(self) -> Result<Options, clap::Error> {
+        let format = match self.format {
+            Some(f) => f,
+            None => self.out.extension().and_then(|e| e.to_str()).and_then(Format::parse).ok_or_else(|| {
+                super::usage_error("predict", clap::error::ErrorKind::InvalidValue, "cannot tell the format from the --out extension; give --format csv|json|ics")
+            })?,
+        };
+        Ok(Options { search: self.search.into_search("predict", self.days * 24 * 60)?, out: self.out, format })
+    }
+}
+
+/// Element sets to predict: a Celestrak group (newest cached copy, downloaded
+/// if none is cached) screened at `now` with overrides applied, or the full catalog.
+async fn load_elements(options: &Search, config: &Config, now: DateTime<Utc>) -> Result<Vec<sgp4::Elements>, String> {
+    let mut elements = match &options.group {
+        Some(group) => {
+            let path = match tle_fetcher::latest_cached(config, group).map_err(|e| e.to_string())? {
+                Some(path) => path,
+                None => tle_fetcher::fetch_celestrak_group(config, group).await.map_err(|e| e.to_string())?,
+            };
+            info!(group = group.as_str(), path = %path.display(), "Using TLE set");
+            let mut elements = crate::core::tle::read_tle_report(&path).map_err(|e| e.to_string())?.elements;
+            crate::core::screening::screen(&mut elements, &crate::core::screening::ScreeningLimits::from_env(), now);
+            let conn = crate::utils::db::open_or_init().map_err(|e| e.to_string())?;
+            crate::core::overrides::apply_overrides(&conn, &mut elements).map_err(|e| e.to_string())?;
+            elements
+        }
+        None => super::load_catalog(config, now).await?,
+    };
+    if !options.norad_ids.is_empty() {
+        elements.retain(|e| options.norad_ids.contains(&e.norad_id));
+    }
+    if let Some(filter) = &options.filter {
+        let satcat = if filter.needs_satcat() {
+            crate::utils::db::open_or_init().and_then(|c| crate::utils::db::satcat_map(&c)).map_err(|e| e.to_string())?
+        } else {
+            HashMap::new()
+        };
+        elements.retain(|e| filter.matches(&Subject::of(e, satcat.get(&e.norad_id)), now));
+    }
+    Ok(elements)
+}
+
+fn pass_dto(norad_id: u64, name: Option<String>, w: PassWindow) -> SatellitePassDto {
+    SatellitePassDto {
+        norad_id,
+        name,
+        pass: PassWindowDto::from(w),
+    }
+}
+
+fn to_csv(passes: &[SatellitePassDto]) -> Result<Vec<u8>, csv::Error> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(["norad_id", "name", "start", "end", "tca", "max_elevation_deg", "duration_s", "score", "orbit_number", "aos_az_deg", "los_az_deg", "direction"])?;
+    for p in passes {
+        writer.write_record([
+            p.norad_id.to_string(),
+            p.name.clone().unwrap_or_default(),
+            p.pass.start.to_rfc3339_opts(SecondsFormat::Secs, true),
+            p.pass.end.to_rfc3339_opts(SecondsFormat::Secs, true),
+            p.pass.tca.to_rfc3339_opts(SecondsFormat::Secs, true),
+            format!("{:.2}", p.pass.max_elevation_deg),
+            p.pass.duration_s.to_string(),
+            format!("{:.3}", p.pass.score),
+            p.pass.orbit_number.to_string(),
+            format!("{:.1}", p.pass.aos_az_deg),
+            format!("{:.1}", p.pass.los_az_deg),
+            p.pass.direction.clone(),
+        ])?;
+    }
+    writer.into_inner().map_err(|e| e.into_error().into())
+}
+
+/// Escapes an iCalendar TEXT value.
+fn ics_text(s: &str) -> String {
+    s.replace('\\', "\\\\").replace(';', "\\;").replace(',', "\\,").replace('\n', "\\n")
+}
+
+/// Folds a content line to 75 octets, continuing with a space (RFC 5545 3.1).
+fn ics_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+/// One VEVENT per pass, from AOS to LOS.
+fn to_ics(passes: &[SatellitePassDto], stamp: DateTime<Utc>) -> String {
+    let time = |t: DateTime<Utc>| t.format("%Y%m%dT%H%M%SZ").to_string();
+    let mut out = String::new();
+    for line in ["BEGIN:VCALENDAR", "VERSION:2.0", "PRODID:-//STfCM//Pass predictions//EN", "CALSCALE:GREGORIAN"] {
+        ics_line(&mut out, line);
+    }
+    for p in passes {
+        let name = p.name.clone().unwrap_or_else(|| p.norad_id.to_string());
+        ics_line(&mut out, "BEGIN:VEVENT");
+        ics_line(&mut out, &format!("UID:{}-{}@stfcm", p.norad_id, p.pass.start.timestamp()));
+        ics_line(&mut out, &format!("DTSTAMP:{}", time(stamp)));
+        ics_line(&mut out, &format!("DTSTART:{}", time(p.pass.start)));
+        ics_line(&mut out, &format!("DTEND:{}", time(p.pass.end)));
+        ics_line(&mut out, &format!("SUMMARY:{}", ics_text(&format!("{} pass, max {:.0}°", name, p.pass.max_elevation_deg))));
+        let description = format!(
+            "NORAD {}, TCA {}, max elevation {:.1}°, {}",
+            p.norad_id,
+            p.pass.tca.to_rfc3339_opts(SecondsFormat::Secs, true),
+            p.pass.max_elevation_deg,
+            p.pass.direction
+        );
+        ics_line(&mut out, &format!("DESCRIPTION:{}", ics_text(&description)));
+        ics_line(&mut out, "END:VEVENT");
+    }
+    ics_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+/// Predicts the passes of the selected satellites over the site from now,
+/// sorted by AOS. GEO objects are skipped.
+pub async fn search(options: &Search, config: &Config, clock: &dyn crate::core::clock::Clock) -> Result<Vec<SatellitePassDto>, String> {
+    let (position, station_id) = match options.site {
+        Site::Station(id) => {
+            let conn = crate::utils::db::open_or_init().map_err(|e| e.to_string())?;
+            let st = crate::utils::db::get_station(&conn, id).map_err(|_| format!("station {} not found", id))?;
+            (ObserverPosition { lat_deg: st.lat, lon_deg: st.lon, alt_km: st.alt_m / 1000.0 }, Some(id))
+        }
+        Site::Position { lat, lon, alt_m } => (ObserverPosition { lat_deg: lat, lon_deg: lon, alt_km: alt_m / 1000.0 }, None),
+    };
+    let elements = load_elements(options, config, clock.now()).await?;
+    if elements.is_empty() {
+        return Err("no satellites selected".to_string());
+    }
+
+    let start = clock.now();
+    let horizon = horizon::horizon_for(station_id, position.lat_deg, position.lon_deg);
+    let exclusions = station_id.map(horizon::exclusions_for).unwrap_or_default();
+    let look = LookOptions { horizon: horizon.as_deref(), exclusions: &exclusions, ..Default::default() };
+    let mut passes = Vec::new();
+    for el in elements.iter().filter(|e| !is_geosynchronous(e)) {
+        match predict_passes_with_options(el, &Observer::Fixed(position), &look, start, options.minutes, options.step, options.min_el) {
+            Ok(wins) => passes.extend(
+                merge_passes(wins, options.merge_gap)
+                    .into_iter()
+                    .filter(|w| options.min_peak_el.is_none_or(|m| w.max_elevation_deg >= m))
+                    .map(|w| pass_dto(el.norad_id, el.object_name.clone(), w)),
+            ),
+            Err(e) => warn!(norad_id = el.norad_id, error = %e, "Skipping satellite"),
+        }
+    }
+    passes.sort_by_key(|p| p.pass.start);
+    info!(satellites = elements.len(), passes = passes.len(), "Predicted passes");
+    Ok(passes)
+}
+
+/// Predicts the passes of the selected satellites and writes them to `out`.
+pub async fn run(options: Options, config: &Config, clock: &dyn crate::core::clock::Clock) -> Result<(), String> {
+    let start = clock.now();
+    let passes = search(&options.search, config, clock).await?;
+    let bytes = match options.format {
+        Format::Csv => to_csv(&passes).map_err(|e| e.to_string())?,
+        Format::Json => serde_json::to_vec_pretty(&passes).map_err(|e| e.to_string())?,
+        Format::Ics => to_ics(&passes, start).into_bytes(),
+    };
+    std::fs::write(&options.out, bytes).map_err(|e| format!("cannot write {}: {}", options.out.display(), e))?;
+    info!(passes = passes.len(), out = %options.out.display(), "Wrote pass predictions");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn writes_one_calendar_event_per_pass() {
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let pass = PassWindow {
+            start,
+            end: start + chrono::Duration::minutes(8),
+            tca: start + chrono::Duration::minutes(4),
+            max_elevation_deg: 42.4,
+            orbit_number: 0,
+            aos_az_deg: 337.0,
+            los_az_deg: 134.0,
+            blocked: Vec::new(),
+        };
+        let passes = vec![pass_dto(25544, Some("ISS (ZARYA), crew".to_string()), pass)];
+        let ics = to_ics(&passes, start);
+        assert!(ics.contains("DTSTART:20240301T120000Z\r\nDTEND:20240301T120800Z\r\n"));
+        assert!(ics.contains("SUMMARY:ISS (ZARYA)\\, crew pass\\, max 42°\r\n"));
+        assert!(ics.lines().all(|l| l.trim_end_matches('\r').len() <= 75));
+        let csv = String::from_utf8(to_csv(&passes).unwrap()).unwrap();
+        assert!(csv.lines().nth(1).unwrap().starts_with("25544,\"ISS (ZARYA), crew\",2024-03-01T12:00:00Z"));
+        assert!(csv.lines().nth(1).unwrap().ends_with(",337.0,134.0,NNW→SE"));
+
+        let parse = |a: &str| super::super::Command::parse(format!("predict {}", a).split(' ').map(String::from));
+        let Ok(super::super::Command::Predict(o)) = parse("--station 3 --group amateur --days 3 --out passes.ics") else {
+            panic!("expected the predict subcommand");
+        };
+        assert_eq!((o.search.site, o.format, o.search.minutes), (Site::Station(3), Format::Ics, 3 * 24 * 60));
+        assert!(parse("--station 3 --out passes.txt").is_err());
+        assert!(parse("--lat 1 --out p.csv").is_err());
+        let Ok(super::super::Command::Predict(o)) = parse("--station 3 --filter perigee<600 --out passes.csv") else {
+            panic!("expected the predict subcommand");
+        };
+        assert_eq!(o.search.filter, Some(SatelliteQuery::parse("perigee<600").unwrap()));
+        assert!(parse("--station 3 --filter perigee~600 --out passes.csv").is_err());
+        assert!(parse("--lat 1 --lon 2 --days 15 --out p.csv").is_err());
+    }
+}
@@ -0,0 +1,271 @@
+use std::time::Duration as StdDuration;
+
+use chrono::{DateTime, Duration, Utc};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::symbols::Marker;
+use ratatui::text::Line;
+use ratatui::widgets::canvas::{Canvas, Circle, Line as CanvasLine};
+use ratatui::widgets::{Block, Row, Table};
+use ratatui::Frame;
+
+use crate::core::clock::Clock;
+use crate::core::catalog::Catalog;
+use crate::core::coords::gmst;
+use crate::core::orbit::{minutes_since_epoch, propagate_minutes};
+use crate::predictors::passes::{predict_passes_with_options, slant_range_km, topocentric_look_deg, LookOptions, Observer, ObserverPosition, PassWindow};
+use crate::utils::db::Station;
+
+/// `STfCM tui` options.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    pub station_id: i64,
+    /// Satellites to track; the station's favorites when empty.
+    pub norad_ids: Vec<u64>,
+    /// Elevation a pass must reach to be listed (degrees).
+    pub min_el: f64,
+}
+
+/// `STfCM tui` arguments.
+#[derive(Debug, Clone, clap::Args)]
+pub struct Args {
+    /// Station to watch from.
+    #[arg(long, value_name = "ID")]
+    station: i64,
+    /// Satellites to track (default: the station's favorites).
+    #[arg(long, value_name = "ID,...", value_delimiter = ',')]
+    norad: Vec<u64>,
+    /// Peak elevation a listed pass must reach (degrees).
+    #[arg(long, value_name = "DEG", default_value_t = 10.0, allow_negative_numbers = true)]
+    min_el: f64,
+}
+
+impl Args {
+    pub(super) fn into_options(self) -> Options {
+        Options { station_id: self.station, norad_ids: self.norad, min_el: self.min_el }
+    }
+}
+
+/// Redraw interval; also how long a key press may wait.
+const TICK: StdDuration = StdDuration::from_secs(1);
+/// How often the pass list is predicted again.
+const PASS_REFRESH: Duration = Duration::minutes(1);
+/// How far ahead passes are listed.
+const PASS_HOURS: i64 = 24;
+/// Sampling step (s) of the pass search.
+const PASS_STEP_S: i64 = 15;
+
+/// Where one tracked satellite is as seen from the station.
+struct Look {
+    norad_id: u64,
+    name: String,
+    az_deg: f64,
+    el_deg: f64,
+    range_km: f64,
+}
+
+struct Dashboard {
+    station: Station,
+    observer: ObserverPosition,
+    elements: Catalog,
+    min_el: f64,
+    passes: Vec<(u64, PassWindow)>,
+    passes_at: Option<DateTime<Utc>>,
+}
+
+impl Dashboard {
+    fn name_of(&self, norad_id: u64) -> String {
+        self.elements.name_of(norad_id).map_or_else(|| norad_id.to_string(), str::to_string)
+    }
+
+    fn looks(&self, now: DateTime<Utc>) -> Vec<Look> {
+        let gmst_now = gmst(now);
+        self.elements
+            .iter()
+            .filter_map(|el| {
+                let pred = propagate_minutes(el, minutes_since_epoch(el, now)).ok()?;
+                let (el_deg, az_deg) = topocentric_look_deg(&pred.position, gmst_now, &self.observer);
+                Some(Look {
+                    norad_id: el.norad_id,
+                    name: self.name_of(el.norad_id),
+                    az_deg: az_deg.rem_euclid(360.0),
+                    el_deg,
+                    range_km: slant_range_km(&pred.position, gmst_now, &self.observer),
+                })
+            })
+            .collect()
+    }
+
+    /// Predicts the passes again when the list is older than `PASS_REFRESH`.
+    fn refresh_passes(&mut self, now: DateTime<Utc>) {
+        if self.passes_at.is_some_and(|at| now - at < PASS_REFRESH && now >= at) {
+            return;
+        }
+        let options = LookOptions::default();
+        let mut passes = Vec::new();
+        for el in self.elements.iter() {
+            if let Ok(wins) = predict_passes_with_options(el, &Observer::Fixed(self.observer), &options, now, PASS_HOURS * 60, PASS_STEP_S, self.min_el) {
+                passes.extend(wins.into_iter().map(|w| (el.norad_id, w)));
+            }
+        }
+        passes.sort_by_key(|(_, w)| w.start);
+        self.passes = passes;
+        self.passes_at = Some(now);
+    }
+
+    fn next_aos(&self, norad_id: u64, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.passes.iter().find(|(id, w)| *id == norad_id && w.start > now).map(|(_, w)| w.start)
+    }
+
+    fn draw(&self, frame: &mut Frame, now: DateTime<Utc>) {
+        let [header, middle, bottom, footer] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(10), Constraint::Length(10), Constraint::Length(1)]).areas(frame.area());
+        let [table_area, plot_area] = Layout::horizontal([Constraint::Percentage(55), Constraint::Percentage(45)]).areas(middle);
+
+        let station = self.station.name.clone().unwrap_or_else(|| format!("station {}", self.station.id));
+        let title = format!(
+            " {}  {:.4}°, {:.4}°, {:.0} m   {} UTC",
+            station,
+            self.station.lat,
+            self.station.lon,
+            self.station.alt_m,
+            now.format("%Y-%m-%d %H:%M:%S")
+        );
+        frame.render_widget(Line::from(title).style(Style::default().add_modifier(Modifier::BOLD)), header);
+
+        let looks = self.looks(now);
+        let rows = looks.iter().map(|l| {
+            let status = if l.el_deg >= 0.0 {
+                "up".to_string()
+            } else {
+                self.next_aos(l.norad_id, now).map(|t| t.format("%H:%M:%S").to_string()).unwrap_or_else(|| "-".to_string())
+            };
+            let style = if l.el_deg >= 0.0 { Style::default().fg(Color::Green) } else { Style::default() };
+            Row::new(vec![
+                l.name.clone(),
+                l.norad_id.to_string(),
+                format!("{:6.1}", l.az_deg),
+                format!("{:6.1}", l.el_deg),
+                format!("{:8.0}", l.range_km),
+                status,
+            ])
+            .style(style)
+        });
+        let widths = [
+            Constraint::Min(12),
+            Constraint::Length(9),
+            Constraint::Length(6),
+            Constraint::Length(6),
+            Constraint::Length(8),
+            Constraint::Length(8),
+        ];
+        let table = Table::new(rows, widths)
+            .header(Row::new(vec!["Satellite", "NORAD", "Az°", "El°", "Range km", "Next AOS"]).style(Style::default().add_modifier(Modifier::BOLD)))
+            .block(Block::bordered().title(" Tracked "));
+        frame.render_widget(table, table_area);
+
+        let labels: Vec<(f64, f64, String)> = looks
+            .iter()
+            .filter(|l| l.el_deg >= 0.0)
+            .map(|l| {
+                let (x, y) = polar_xy(l.az_deg, l.el_deg);
+                (x, y, format!("•{}", l.norad_id))
+            })
+            .collect();
+        let plot = Canvas::default()
+            .block(Block::bordered().title(" Sky "))
+            .marker(Marker::Braille)
+            .x_bounds([-100.0, 100.0])
+            .y_bounds([-100.0, 100.0])
+            .paint(move |ctx| {
+                for radius in [30.0, 60.0, 90.0] {
+                    ctx.draw(&Circle { x: 0.0, y: 0.0, radius, color: Color::DarkGray });
+                }
+                ctx.draw(&CanvasLine { x1: -90.0, y1: 0.0, x2: 90.0, y2: 0.0, color: Color::DarkGray });
+                ctx.draw(&CanvasLine { x1: 0.0, y1: -90.0, x2: 0.0, y2: 90.0, color: Color::DarkGray });
+                ctx.layer();
+                for (label, x, y) in [("N", 0.0, 94.0), ("E", 94.0, 0.0), ("S", 0.0, -98.0), ("W", -98.0, 0.0)] {
+                    ctx.print(x, y, label);
+                }
+                for (x, y, label) in &labels {
+                    ctx.print(*x, *y, Line::styled(label.clone(), Style::default().fg(Color::Green)));
+                }
+            });
+        frame.render_widget(plot, plot_area);
+
+        let rows = self.passes.iter().filter(|(_, w)| w.end > now).take(usize::from(bottom.height.saturating_sub(3))).map(|(id, w)| {
+            Row::new(vec![
+                self.name_of(*id),
+                w.start.format("%m-%d %H:%M:%S").to_string(),
+                w.end.format("%H:%M:%S").to_string(),
+                format!("{:5.1}", w.max_elevation_deg),
+                w.direction(),
+                format!("{:3}m{:02}s", w.duration_s() / 60, w.duration_s() % 60),
+            ])
+        });
+        let widths = [Constraint::Min(12), Constraint::Length(14), Constraint::Length(8), Constraint::Length(6), Constraint::Length(9), Constraint::Length(7)];
+        let table = Table::new(rows, widths)
+            .header(Row::new(vec!["Satellite", "AOS", "LOS", "Max°", "Path", "Length"]).style(Style::default().add_modifier(Modifier::BOLD)))
+            .block(Block::bordered().title(format!(" Passes above {}° in the next {} h ", self.min_el, PASS_HOURS)));
+        frame.render_widget(table, bottom);
+
+        frame.render_widget(Line::from(" q quit").style(Style::default().fg(Color::DarkGray)), footer);
+    }
+}
+
+/// Sky plot coordinates: zenith in the centre, the horizon on a radius of 90,
+/// north up and east to the right.
+fn polar_xy(az_deg: f64, el_deg: f64) -> (f64, f64) {
+    let r = 90.0 - el_deg.clamp(0.0, 90.0);
+    let (sin_az, cos_az) = az_deg.to_radians().sin_cos();
+    (r * sin_az, r * cos_az)
+}
+
+/// Live look angles and upcoming passes of the selected satellites from one
+/// station, redrawn every second until `q` or `Esc` is pressed.
+pub fn run(options: Options, catalog: Vec<sgp4::Elements>, clock: &dyn Clock) -> Result<(), String> {
+    let conn = crate::utils::db::open_or_init().map_err(|e| e.to_string())?;
+    let station = crate::utils::db::get_station(&conn, options.station_id).map_err(|_| format!("station {} not found", options.station_id))?;
+    let norad_ids = if options.norad_ids.is_empty() {
+        crate::utils::db::list_station_favorites(&conn, station.id).map_err(|e| e.to_string())?
+    } else {
+        options.norad_ids
+    };
+    let elements = Catalog::new(catalog.into_iter().filter(|e| norad_ids.contains(&e.norad_id)).collect());
+    if elements.is_empty() {
+        return Err("none of the selected satellites is in the catalog; pass --norad or set the station's favorites".to_string());
+    }
+    let observer = ObserverPosition { lat_deg: station.lat, lon_deg: station.lon, alt_km: station.alt_m / 1000.0 };
+    let mut dashboard = Dashboard { station, observer, elements, min_el: options.min_el, passes: Vec::new(), passes_at: None };
+
+    let mut terminal = ratatui::init();
+    let result = loop {
+        let now = clock.now();
+        dashboard.refresh_passes(now);
+        if let Err(e) = terminal.draw(|frame| dashboard.draw(frame, now)) {
+            break Err(e.to_string());
+        }
+        match event::poll(TICK).and_then(|ready| if ready { event::read().map(Some) } else { Ok(None) }) {
+            Ok(Some(Event::Key(key))) if key.kind == KeyEventKind::Press && matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) => break Ok(()),
+            Ok(_) => {}
+            Err(e) => break Err(e.to_string()),
+        }
+    };
+    ratatui::restore();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sky_plot_puts_zenith_in_the_centre_and_north_up() {
+        assert_eq!(polar_xy(123.0, 90.0), (0.0, 0.0));
+        let (x, y) = polar_xy(0.0, 0.0);
+        assert!(x.abs() < 1e-9 && (y - 90.0).abs() < 1e-9);
+        let (x, y) = polar_xy(90.0, 30.0);
+        assert!((x - 60.0).abs() < 1e-9 && y.abs() < 1e-9);
+    }
+}
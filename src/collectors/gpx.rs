@@ -0,0 +1,120 @@
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
+use crate::predictors::passes::{ObserverPosition, TrackPoint};
+
+#[derive(Debug, Error)]
+pub enum GpxError {
+    #[error("track point {index}: missing or invalid {field}")]
+    InvalidPoint { index: usize, field: &'static str },
+    #[error("no track points found")]
+    Empty,
+}
+
+/// Extracts time-tagged points from a GPX document (`trkpt`, falling back to `rtept`).
+/// Elevation (`ele`, metres) is optional and defaults to 0; `time` is required.
+/// This is a minimal reader for the elements STfCM needs, not a full XML parser.
+pub fn parse_gpx_track(xml: &str) -> Result<Vec<TrackPoint>, GpxError> {
+    let mut points = parse_points(xml, "trkpt")?;
+    if points.is_empty() {
+        points = parse_points(xml, "rtept")?;
+    }
+    if points.is_empty() {
+        return Err(GpxError::Empty);
+    }
+    points.sort_by_key(|p| p.time);
+    Ok(points)
+}
+
+fn parse_points(xml: &str, tag: &str) -> Result<Vec<TrackPoint>, GpxError> {
+    let open = format!("<{}", tag);
+    let close = format!("</{}>", tag);
+    let mut points = Vec::new();
+    let mut rest = xml;
+    while let Some(pos) = rest.find(&open) {
+        let index = points.len();
+        rest = &rest[pos + open.len()..];
+        let Some(head_end) = rest.find('>') else { break };
+        let head = &rest[..head_end];
+        let self_closing = head.ends_with('/');
+        let body = if self_closing {
+            ""
+        } else {
+            let end = rest.find(&close).unwrap_or(rest.len());
+            &rest[head_end + 1..end]
+        };
+
+        let lat = attribute(head, "lat").and_then(|v| v.parse::<f64>().ok())
+            .ok_or(GpxError::InvalidPoint { index, field: "lat" })?;
+        let lon = attribute(head, "lon").and_then(|v| v.parse::<f64>().ok())
+            .ok_or(GpxError::InvalidPoint { index, field: "lon" })?;
+        let ele_m = match element_text(body, "ele") {
+            Some(v) => v.parse::<f64>().map_err(|_| GpxError::InvalidPoint { index, field: "ele" })?,
+            None => 0.0,
+        };
+        let time = element_text(body, "time")
+            .and_then(|v| DateTime::parse_from_rfc3339(v).ok())
+            .map(|t| t.with_timezone(&Utc))
+            .ok_or(GpxError::InvalidPoint { index, field: "time" })?;
+
+        points.push(TrackPoint {
+            time,
+            position: ObserverPosition { lat_deg: lat, lon_deg: lon, alt_km: ele_m / 1000.0 },
+        });
+        rest = &rest[head_end..];
+    }
+    Ok(points)
+}
+
+/// Value of `name="..."` (or single-quoted) inside a start tag.
+fn attribute<'a>(head: &'a str, name: &str) -> Option<&'a str> {
+    for quote in ['"', '\''] {
+        let key = format!("{}={}", name, quote);
+        let mut search = head;
+        while let Some(pos) = search.find(&key) {
+            // Make sure we matched a whole attribute name, not a suffix like `xlat`.
+            let preceded_ok = search[..pos].chars().last().is_none_or(|c| c.is_whitespace());
+            let value = &search[pos + key.len()..];
+            if preceded_ok {
+                return value.find(quote).map(|end| &value[..end]);
+            }
+            search = value;
+        }
+    }
+    None
+}
+
+/// Trimmed text content of the first `<name>...</name>` child.
+fn element_text<'a>(body: &'a str, name: &str) -> Option<&'a str> {
+    let open = format!("<{}>", name);
+    let close = format!("</{}>", name);
+    let start = body.find(&open)? + open.len();
+    let end = body[start..].find(&close)? + start;
+    Some(body[start..end].trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_gpx_track;
+
+    #[test]
+    fn parses_track_points_with_elevation() {
+        let gpx = r#"<?xml version="1.0"?>
+            <gpx version="1.1"><trk><trkseg>
+              <trkpt lon="-74.1" lat="40.6"><ele>12.5</ele><time>2024-05-01T10:01:00Z</time></trkpt>
+              <trkpt lat='40.7' lon='-74.0'><time>2024-05-01T10:00:00Z</time></trkpt>
+            </trkseg></trk></gpx>"#;
+        let pts = parse_gpx_track(gpx).unwrap();
+        assert_eq!(pts.len(), 2);
+        // sorted by time
+        assert!((pts[0].position.lat_deg - 40.7).abs() < 1e-9);
+        assert!((pts[1].position.alt_km - 0.0125).abs() < 1e-9);
+        assert!((pts[1].position.lon_deg + 74.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rejects_points_without_time() {
+        let gpx = r#"<gpx><trk><trkseg><trkpt lat="1" lon="2"></trkpt></trkseg></trk></gpx>"#;
+        assert!(parse_gpx_track(gpx).is_err());
+    }
+}
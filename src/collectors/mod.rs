@@ -1 +1,5 @@
-pub mod tle_fetcher;
\ No newline at end of file
+pub mod tle_fetcher;
+pub mod satcat;
+pub mod gpx;
+pub mod oem;
+pub mod spacetrack;
@@ -0,0 +1,121 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use thiserror::Error;
+
+use crate::predictors::ephemeris::StateVector;
+
+#[derive(Debug, Error)]
+pub enum OemError {
+    #[error("line {line}: {message}")]
+    Invalid { line: usize, message: String },
+    #[error("unsupported {key} {value}; only {expected} ephemerides can be compared with SGP4")]
+    Unsupported { key: &'static str, value: String, expected: &'static str },
+    #[error("no ephemeris data lines found")]
+    Empty,
+}
+
+/// Reads the state vectors of a CCSDS OEM in KVN form (km, km/s). SGP4 works in
+/// TEME, so every segment must be Earth-centred TEME in UTC; covariance blocks
+/// and comments are skipped. Acceleration columns, if present, are ignored.
+pub fn parse_oem(text: &str) -> Result<Vec<StateVector>, OemError> {
+    let mut states = Vec::new();
+    let mut in_meta = false;
+    let mut in_covariance = false;
+    for (index, raw) in text.lines().enumerate() {
+        let line = raw.trim();
+        let invalid = |message: String| OemError::Invalid { line: index + 1, message };
+        if line.is_empty() || line.starts_with("COMMENT") {
+            continue;
+        }
+        match line {
+            "META_START" => in_meta = true,
+            "META_STOP" => in_meta = false,
+            "COVARIANCE_START" => in_covariance = true,
+            "COVARIANCE_STOP" => in_covariance = false,
+            _ if in_covariance => {}
+            _ if in_meta || line.contains('=') => {
+                let Some((key, value)) = line.split_once('=') else {
+                    return Err(invalid("expected KEY = value".to_string()));
+                };
+                let value = value.trim();
+                let expect = |key: &'static str, expected: &'static str| {
+                    if value.eq_ignore_ascii_case(expected) {
+                        Ok(())
+                    } else {
+                        Err(OemError::Unsupported { key, value: value.to_string(), expected })
+                    }
+                };
+                match key.trim() {
+                    "REF_FRAME" => expect("REF_FRAME", "TEME")?,
+                    "CENTER_NAME" => expect("CENTER_NAME", "EARTH")?,
+                    "TIME_SYSTEM" => expect("TIME_SYSTEM", "UTC")?,
+                    _ => {}
+                }
+            }
+            _ => {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                if fields.len() < 7 {
+                    return Err(invalid("expected epoch and six state components".to_string()));
+                }
+                let time = parse_epoch(fields[0]).ok_or_else(|| invalid(format!("invalid epoch {}", fields[0])))?;
+                let mut values = [0.0f64; 6];
+                for (v, f) in values.iter_mut().zip(&fields[1..7]) {
+                    *v = f.parse().map_err(|_| invalid(format!("invalid number {}", f)))?;
+                }
+                states.push(StateVector {
+                    time,
+                    position: [values[0], values[1], values[2]],
+                    velocity: [values[3], values[4], values[5]],
+                });
+            }
+        }
+    }
+    if states.is_empty() {
+        return Err(OemError::Empty);
+    }
+    states.sort_by_key(|s| s.time);
+    Ok(states)
+}
+
+/// CCSDS epochs: `YYYY-MM-DDThh:mm:ss[.f]` or day-of-year `YYYY-DDDThh:mm:ss[.f]`,
+/// with an optional trailing `Z`.
+fn parse_epoch(s: &str) -> Option<DateTime<Utc>> {
+    let s = s.trim_end_matches('Z');
+    if let Ok(t) = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f") {
+        return Some(t.and_utc());
+    }
+    let (date, time) = s.split_once('T')?;
+    let (year, doy) = date.split_once('-')?;
+    let day = NaiveDate::from_yo_opt(year.parse().ok()?, doy.parse().ok()?)?;
+    let time = chrono::NaiveTime::parse_from_str(time, "%H:%M:%S%.f").ok()?;
+    Some(day.and_time(time).and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_teme_segments_and_rejects_other_frames() {
+        let oem = "CCSDS_OEM_VERS = 2.0
+META_START
+OBJECT_NAME = TEST
+CENTER_NAME = EARTH
+REF_FRAME = TEME
+TIME_SYSTEM = UTC
+META_STOP
+COMMENT propagated
+2024-03-01T00:01:00.000 7000.0 0.0 0.0 0.0 7.5 0.0
+2024-061T00:00:00 6999.0 -450.0 0.0 0.5 7.5 0.0
+COVARIANCE_START
+EPOCH = 2024-03-01T00:00:00
+1.0
+COVARIANCE_STOP
+";
+        let states = parse_oem(oem).unwrap();
+        assert_eq!(states.len(), 2);
+        assert_eq!(states[0].time.to_rfc3339(), "2024-03-01T00:00:00+00:00");
+        assert_eq!(states[1].position, [7000.0, 0.0, 0.0]);
+        assert!(matches!(parse_oem(&oem.replace("TEME", "EME2000")), Err(OemError::Unsupported { .. })));
+        assert!(matches!(parse_oem("META_START\nMETA_STOP\n"), Err(OemError::Empty)));
+    }
+}
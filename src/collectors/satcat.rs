@@ -0,0 +1,124 @@
+use serde::Deserialize;
+use tracing::{info, warn};
+
+use crate::collectors::tle_fetcher::FetchError;
+use crate::config::CelestrakConfig;
+
+/// Catalog metadata of one object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SatcatEntry {
+    pub norad_id: u64,
+    /// International designator, e.g. `1998-067A`.
+    pub object_id: Option<String>,
+    /// `PAYLOAD`, `ROCKET BODY`, `DEBRIS` or `UNKNOWN`.
+    pub object_type: String,
+    /// Owner code, a country or organisation such as `US`, `PRC` or `ESA`.
+    pub owner: String,
+    pub launch_date: Option<String>,
+    pub decay_date: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+struct SatcatRow {
+    object_id: Option<String>,
+    norad_cat_id: u64,
+    object_type: String,
+    owner: String,
+    launch_date: Option<String>,
+    decay_date: Option<String>,
+}
+
+/// Space-Track style name of an object type given as a Celestrak code (`PAY`,
+/// `R/B`, `DEB`, `UNK`) or in full, in any case; `None` if it is neither.
+pub fn normalize_object_type(s: &str) -> Option<&'static str> {
+    match s.trim().to_ascii_uppercase().as_str() {
+        "PAY" | "PAYLOAD" => Some("PAYLOAD"),
+        "R/B" | "RB" | "ROCKET BODY" | "ROCKET_BODY" => Some("ROCKET BODY"),
+        "DEB" | "DEBRIS" => Some("DEBRIS"),
+        "UNK" | "UNKNOWN" => Some("UNKNOWN"),
+        _ => None,
+    }
+}
+
+/// `country` and `object_type` query filters. Each takes a comma-separated
+/// list; objects without SATCAT metadata never match.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SatcatFilter {
+    owners: Vec<String>,
+    object_types: Vec<&'static str>,
+}
+
+impl SatcatFilter {
+    /// `None` when neither filter is given.
+    pub fn parse(country: Option<&str>, object_type: Option<&str>) -> Result<Option<SatcatFilter>, String> {
+        let list = |s: Option<&str>| s.unwrap_or_default().split(',').map(str::trim).filter(|s| !s.is_empty()).map(str::to_string).collect::<Vec<_>>();
+        let owners: Vec<String> = list(country).into_iter().map(|c| c.to_ascii_uppercase()).collect();
+        let object_types = list(object_type)
+            .iter()
+            .map(|t| normalize_object_type(t).ok_or_else(|| format!("unknown object_type {}; use PAYLOAD, ROCKET BODY, DEBRIS or UNKNOWN", t)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((!owners.is_empty() || !object_types.is_empty()).then_some(SatcatFilter { owners, object_types }))
+    }
+
+    pub fn matches(&self, entry: Option<&SatcatEntry>) -> bool {
+        entry.is_some_and(|e| {
+            (self.owners.is_empty() || self.owners.iter().any(|o| e.owner.eq_ignore_ascii_case(o)))
+                && (self.object_types.is_empty() || self.object_types.contains(&e.object_type.as_str()))
+        })
+    }
+}
+
+/// Parses Celestrak's SATCAT CSV; rows that do not parse are skipped.
+pub fn parse_satcat_csv(text: &str) -> Vec<SatcatEntry> {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(text.as_bytes());
+    let non_empty = |s: Option<String>| s.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+    reader
+        .deserialize::<SatcatRow>()
+        .filter_map(Result::ok)
+        .map(|row| SatcatEntry {
+            norad_id: row.norad_cat_id,
+            object_id: non_empty(row.object_id),
+            object_type: normalize_object_type(&row.object_type).unwrap_or("UNKNOWN").to_string(),
+            owner: row.owner.trim().to_string(),
+            launch_date: non_empty(row.launch_date),
+            decay_date: non_empty(row.decay_date),
+        })
+        .collect()
+}
+
+/// Downloads and parses the current SATCAT.
+pub async fn fetch_satcat(config: &CelestrakConfig) -> Result<Vec<SatcatEntry>, FetchError> {
+    info!("Fetching SATCAT from {}", config.satcat_url);
+    let client = reqwest::Client::builder().gzip(true).brotli(true).deflate(true).build()?;
+    let resp = client.get(&config.satcat_url).send().await?;
+    if !resp.status().is_success() {
+        warn!(status = ?resp.status(), "Non-success response fetching SATCAT");
+    }
+    let body = resp.text().await?;
+    Ok(parse_satcat_csv(&body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_celestrak_satcat_rows() {
+        let csv = "OBJECT_NAME,OBJECT_ID,NORAD_CAT_ID,OBJECT_TYPE,OPS_STATUS_CODE,OWNER,LAUNCH_DATE,LAUNCH_SITE,DECAY_DATE,PERIOD\n\
+                   ISS (ZARYA),1998-067A,25544,PAY,+,ISS,1998-11-20,TYMSC,,92.8\n\
+                   CZ-2C R/B,1999-025B,25731,R/B,D,PRC,1999-05-10,TSC,2001-06-12,\n\
+                   BROKEN,x,not-a-number,DEB,,US,,,,\n";
+        let entries = parse_satcat_csv(csv);
+        assert_eq!(entries.len(), 2);
+        assert_eq!((entries[0].norad_id, entries[0].object_type.as_str(), entries[0].decay_date.as_deref()), (25544, "PAYLOAD", None));
+        assert_eq!((entries[1].owner.as_str(), entries[1].object_type.as_str()), ("PRC", "ROCKET BODY"));
+        assert_eq!(normalize_object_type("payload"), Some("PAYLOAD"));
+        assert_eq!(normalize_object_type("rocket"), None);
+
+        let filter = SatcatFilter::parse(Some("us,prc"), Some("R/B")).unwrap().unwrap();
+        assert!(filter.matches(Some(&entries[1])) && !filter.matches(Some(&entries[0])) && !filter.matches(None));
+        assert_eq!(SatcatFilter::parse(None, Some("")), Ok(None));
+        assert!(SatcatFilter::parse(None, Some("rocket")).is_err());
+    }
+}
@@ -0,0 +1,173 @@
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use reqwest::header::{HeaderMap, COOKIE, SET_COOKIE};
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::collectors::tle_fetcher::{self, FetchError};
+use crate::config::{Config, SpaceTrackConfig};
+
+/// Prefix of the cached Space-Track downloads in the TLE directory.
+pub const SOURCE: &str = "spacetrack";
+
+#[derive(Debug, Error)]
+pub enum SpaceTrackError {
+    #[error("no Space-Track credentials configured (spacetrack.identity and spacetrack.password)")]
+    NoCredentials,
+    #[error("Space-Track login failed: {0}")]
+    Login(String),
+    #[error("Space-Track query failed ({status}): {body}")]
+    Query { status: reqwest::StatusCode, body: String },
+    #[error("network error: {0}")]
+    Network(#[from] reqwest::Error),
+    #[error(transparent)]
+    Cache(#[from] FetchError),
+}
+
+/// GP element sets to download: the newest set of every object still in
+/// orbit, optionally limited to a catalog number range and recent epochs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GpQuery {
+    /// First and last NORAD catalog number, inclusive.
+    pub norad_range: Option<(u64, u64)>,
+    /// Only element sets with an epoch within this many days.
+    pub epoch_days: Option<u32>,
+}
+
+impl GpQuery {
+    /// Query path below the base URL, asking for 3LE text ordered by NORAD ID.
+    pub fn path(&self) -> String {
+        let mut path = "/basicspacedata/query/class/gp".to_string();
+        if let Some((first, last)) = self.norad_range {
+            let _ = write!(path, "/NORAD_CAT_ID/{}--{}", first, last);
+        }
+        if let Some(days) = self.epoch_days {
+            let _ = write!(path, "/EPOCH/%3Enow-{}", days);
+        }
+        path.push_str("/DECAY_DATE/null-val/orderby/NORAD_CAT_ID%20asc/format/3le");
+        path
+    }
+
+    /// Name the download is cached under, e.g. `gp-25544-25600-30d`.
+    pub fn cache_name(&self) -> String {
+        let mut name = "gp".to_string();
+        if let Some((first, last)) = self.norad_range {
+            let _ = write!(name, "-{}-{}", first, last);
+        }
+        if let Some(days) = self.epoch_days {
+            let _ = write!(name, "-{}d", days);
+        }
+        name
+    }
+}
+
+/// Parses `25544` or `25544-25600` into an inclusive range.
+pub fn parse_norad_range(text: &str) -> Result<(u64, u64), String> {
+    let number = |s: &str| s.trim().parse::<u64>().map_err(|_| format!("invalid NORAD ID {:?}", s.trim()));
+    let (first, last) = match text.split_once('-') {
+        Some((first, last)) => (number(first)?, number(last)?),
+        None => (number(text)?, number(text)?),
+    };
+    if first > last {
+        return Err(format!("range {} starts after it ends", text));
+    }
+    Ok((first, last))
+}
+
+/// A logged-in Space-Track session, held in the cookies the login sets.
+pub struct Session {
+    client: reqwest::Client,
+    base_url: String,
+    cookie: String,
+}
+
+impl Session {
+    pub async fn login(config: &SpaceTrackConfig) -> Result<Session, SpaceTrackError> {
+        if !config.has_credentials() {
+            return Err(SpaceTrackError::NoCredentials);
+        }
+        let client = reqwest::Client::builder().gzip(true).build()?;
+        let base_url = config.base_url.trim_end_matches('/').to_string();
+        let resp = client
+            .post(format!("{}/ajaxauth/login", base_url))
+            .form(&[("identity", config.identity.as_str()), ("password", config.password.as_str())])
+            .send()
+            .await?;
+        let status = resp.status();
+        let cookie = session_cookie(resp.headers());
+        let body = resp.text().await?;
+        // Wrong credentials are answered with 200 and {"Login":"Failed"}
+        if !status.is_success() || body.contains("\"Failed\"") || cookie.is_empty() {
+            return Err(SpaceTrackError::Login(format!("{} {}", status, body.trim())));
+        }
+        info!(identity = %config.identity, "Logged in to Space-Track");
+        Ok(Session { client, base_url, cookie })
+    }
+
+    /// Runs a GP query and returns the element sets as 3LE text.
+    pub async fn gp(&self, query: &GpQuery) -> Result<String, SpaceTrackError> {
+        let url = format!("{}{}", self.base_url, query.path());
+        info!("Fetching GP data from {}", url);
+        let resp = self.client.get(&url).header(COOKIE, &self.cookie).send().await?;
+        let status = resp.status();
+        let body = resp.text().await?;
+        if !status.is_success() {
+            return Err(SpaceTrackError::Query { status, body: body.chars().take(200).collect() });
+        }
+        Ok(body)
+    }
+
+    /// Ends the session; failures are only logged.
+    pub async fn logout(self) {
+        let result = self.client.get(format!("{}/ajaxauth/logout", self.base_url)).header(COOKIE, &self.cookie).send().await;
+        if let Err(e) = result {
+            warn!(error = %e, "Failed to log out of Space-Track");
+        }
+    }
+}
+
+/// `name=value` pairs of the cookies a response sets, as a `Cookie` header.
+fn session_cookie(headers: &HeaderMap) -> String {
+    headers
+        .get_all(SET_COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .filter_map(|v| v.split(';').next())
+        .map(str::trim)
+        .filter(|v| v.contains('='))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Logs in, downloads the GP query and caches it in the TLE directory next to
+/// the Celestrak sets as `spacetrack-<name>-<timestamp>.tle`. Returns the path
+/// to the cached file.
+pub async fn fetch_spacetrack_gp(config: &Config, query: &GpQuery) -> Result<PathBuf, SpaceTrackError> {
+    let session = Session::login(&config.spacetrack).await?;
+    let text = session.gp(query).await;
+    session.logout().await;
+    Ok(tle_fetcher::cache_source_text(config, SOURCE, &query.cache_name(), &text?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_queries_and_reads_their_answers() {
+        let query = GpQuery { norad_range: Some(parse_norad_range("25544-25600").unwrap()), epoch_days: Some(30) };
+        assert_eq!(
+            query.path(),
+            "/basicspacedata/query/class/gp/NORAD_CAT_ID/25544--25600/EPOCH/%3Enow-30/DECAY_DATE/null-val/orderby/NORAD_CAT_ID%20asc/format/3le"
+        );
+        assert_eq!((query.cache_name(), GpQuery::default().cache_name()), ("gp-25544-25600-30d".to_string(), "gp".to_string()));
+        assert_eq!(parse_norad_range("25544"), Ok((25544, 25544)));
+        assert!(parse_norad_range("25600-25544").is_err() && parse_norad_range("iss").is_err());
+
+        let mut headers = HeaderMap::new();
+        headers.append(SET_COOKIE, "chocolatechip=abc123; path=/; secure; HttpOnly".parse().unwrap());
+        headers.append(SET_COOKIE, "spacetrack_csrf_cookie=xyz; path=/".parse().unwrap());
+        assert_eq!(session_cookie(&headers), "chocolatechip=abc123; spacetrack_csrf_cookie=xyz");
+    }
+}
@@ -4,7 +4,12 @@ use std::path::PathBuf;
 use thiserror::Error;
 use tracing::{info, warn};
 
-const CELESTRAK_ACTIVE_TLE_URL: &str = "https://celestrak.org/NORAD/elements/gp.php?GROUP=active&format=tle";
+use crate::config::Config;
+
+/// Celestrak group with all active satellites.
+pub const ACTIVE_GROUP: &str = "active";
+/// Celestrak group with objects cataloged in the last 30 days (new launches).
+pub const LAST_30_DAYS_GROUP: &str = "last-30-days";
 
 #[derive(Debug, Error)]
 pub enum FetchError {
@@ -14,19 +19,31 @@ pub enum FetchError {
     Io(#[from] std::io::Error),
 }
 
-/// Fetches the active satellites TLE from Celestrak and caches it under `data/tle/`.
-/// Returns the path to the cached file.
-pub async fn fetch_celestrak_active_tle() -> Result<PathBuf, FetchError> {
-    let dir = PathBuf::from("data/tle");
-    fs::create_dir_all(&dir)?;
+/// Fetches a Celestrak GP group in the configured format and caches it in the
+/// TLE directory (`data/tle/` by default), where the TLE reader detects the
+/// format again. Returns the path to the cached file.
+pub async fn fetch_celestrak_group(config: &Config, group: &str) -> Result<PathBuf, FetchError> {
+    let url = format!("{}?GROUP={}&format={}", config.celestrak.gp_url, group, config.celestrak.gp_format.as_str());
+    let body = download(&url).await?;
+    cache_tle_text(config, group, &body)
+}
 
-    let filename = format!(
-        "celestrak-active-{}.tle",
-        chrono::Utc::now().format("%Y%m%d-%H%M%S")
-    );
-    let path = dir.join(filename);
+/// Celestrak supplemental GP file `file` (e.g. `starlink`) is cached as this group.
+pub fn supplemental_group(file: &str) -> String {
+    format!("sup-{}", file)
+}
+
+/// Fetches a Celestrak supplemental GP file, elements derived from the
+/// operators' own ephemerides, and caches it like a group under
+/// [`supplemental_group`]. Returns the path to the cached file.
+pub async fn fetch_celestrak_supplemental(config: &Config, file: &str) -> Result<PathBuf, FetchError> {
+    let url = format!("{}?FILE={}&FORMAT=tle", config.celestrak.supplemental_url, file);
+    let body = download(&url).await?;
+    cache_tle_text(config, &supplemental_group(file), &body)
+}
 
-    info!("Fetching TLE from {}", CELESTRAK_ACTIVE_TLE_URL);
+async fn download(url: &str) -> Result<String, FetchError> {
+    info!("Fetching TLE from {}", url);
 
     let client = reqwest::Client::builder()
         .gzip(true)
@@ -34,15 +51,60 @@ pub async fn fetch_celestrak_active_tle() -> Result<PathBuf, FetchError> {
         .deflate(true)
         .build()?;
 
-    let resp = client.get(CELESTRAK_ACTIVE_TLE_URL).send().await?;
+    let resp = client.get(url).send().await?;
 
     if !resp.status().is_success() {
         warn!(status = ?resp.status(), "Non-success response fetching TLE");
     }
 
-    let body = resp.text().await?;
-    fs::write(&path, body)?;
+    Ok(resp.text().await?)
+}
+
+/// Most recent cached TLE set of a Celestrak group, if any was downloaded.
+pub fn latest_cached(config: &Config, group: &str) -> Result<Option<PathBuf>, FetchError> {
+    let prefix = format!("celestrak-{}-", group);
+    let entries = match fs::read_dir(&config.data.tle_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    // The timestamp in the name sorts chronologically
+    Ok(entries
+        .filter_map(Result::ok)
+        .map(|e| e.path())
+        .filter(|p| p.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with(&prefix) && n.ends_with(".tle")))
+        .max())
+}
+
+/// The newest cached set of a group when it was written within the refresh
+/// interval, so it need not be downloaded again.
+pub fn fresh_cached(config: &Config, group: &str) -> Option<PathBuf> {
+    let path = latest_cached(config, group).ok().flatten()?;
+    let age = fs::metadata(&path).and_then(|m| m.modified()).ok()?.elapsed().ok()?;
+    (age < config.celestrak.refresh_interval()).then_some(path)
+}
+
+/// Writes a TLE set of a Celestrak group to the TLE directory with a
+/// timestamped name and returns its path.
+pub fn cache_tle_text(config: &Config, group: &str, text: &str) -> Result<PathBuf, FetchError> {
+    cache_source_text(config, "celestrak", group, text)
+}
+
+/// As [`cache_tle_text`] for a TLE set from `source`, cached as
+/// `<source>-<name>-<timestamp>.tle`.
+pub fn cache_source_text(config: &Config, source: &str, name: &str, text: &str) -> Result<PathBuf, FetchError> {
+    let dir = &config.data.tle_dir;
+    fs::create_dir_all(dir)?;
+
+    let filename = format!(
+        "{}-{}-{}.tle",
+        source,
+        name,
+        chrono::Utc::now().format("%Y%m%d-%H%M%S")
+    );
+    let path = dir.join(filename);
+    fs::write(&path, text)?;
     info!(path = %path.display(), "Cached TLE set");
 
     Ok(path)
-}
\ No newline at end of file
+}
@@ -0,0 +1,299 @@
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::core::tle::GpFormat;
+
+/// Environment variable naming the configuration file.
+pub const FILE_ENV: &str = "STFCM_CONFIG";
+/// Read when it exists and neither `--config` nor `STFCM_CONFIG` names a file.
+const DEFAULT_FILE: &str = "stfcm.toml";
+
+const BIND_ENV: &str = "STFCM_BIND";
+const WEB_DIR_ENV: &str = "STFCM_WEB_DIR";
+const TLE_DIR_ENV: &str = "STFCM_TLE_DIR";
+const DB_PATH_ENV: &str = "STFCM_DB_PATH";
+const SNAPSHOT_ENV: &str = "STFCM_SNAPSHOT_SECONDS";
+const GP_URL_ENV: &str = "STFCM_CELESTRAK_URL";
+const GP_FORMAT_ENV: &str = "STFCM_CELESTRAK_FORMAT";
+const SATCAT_URL_ENV: &str = "STFCM_SATCAT_URL";
+const SUPPLEMENTAL_URL_ENV: &str = "STFCM_SUPPLEMENTAL_URL";
+const REFRESH_ENV: &str = "STFCM_TLE_REFRESH_MINUTES";
+const SPACETRACK_URL_ENV: &str = "STFCM_SPACETRACK_URL";
+const SPACETRACK_IDENTITY_ENV: &str = "STFCM_SPACETRACK_IDENTITY";
+const SPACETRACK_PASSWORD_ENV: &str = "STFCM_SPACETRACK_PASSWORD";
+const PASS_DURATION_ENV: &str = "STFCM_PASS_DURATION_MIN";
+const PASS_STEP_ENV: &str = "STFCM_PASS_STEP_S";
+const PASS_MIN_EL_ENV: &str = "STFCM_PASS_MIN_EL";
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("cannot read {path}: {source}")]
+    Io { path: PathBuf, source: std::io::Error },
+    #[error("invalid configuration file {path}: {source}")]
+    Parse { path: PathBuf, source: toml::de::Error },
+    #[error("invalid {name}={value}")]
+    Env { name: &'static str, value: String },
+    #[error("{0}")]
+    Invalid(String),
+}
+
+/// Settings of the server and the subcommands: a TOML file whose sections and
+/// keys are all optional, overridden by `STFCM_*` environment variables.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub data: DataConfig,
+    pub celestrak: CelestrakConfig,
+    pub spacetrack: SpaceTrackConfig,
+    pub passes: PassDefaults,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    /// Address the API listens on.
+    pub bind: SocketAddr,
+    /// Frontend assets.
+    pub web_dir: PathBuf,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig { bind: SocketAddr::from(([127, 0, 0, 1], 3000)), web_dir: PathBuf::from("web") }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DataConfig {
+    /// Cache of downloaded TLE sets.
+    pub tle_dir: PathBuf,
+    /// SQLite database file; its directory is created as needed.
+    pub db_path: PathBuf,
+    /// Interval (seconds) at which the position of every loaded satellite is
+    /// stored as a snapshot. `0` turns recording off.
+    pub snapshot_seconds: u64,
+}
+
+impl Default for DataConfig {
+    fn default() -> Self {
+        DataConfig { tle_dir: PathBuf::from("data/tle"), db_path: PathBuf::from("data/db/tracker.sqlite"), snapshot_seconds: 300 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CelestrakConfig {
+    /// GP query endpoint; `?GROUP=<group>&format=<gp_format>` is appended.
+    pub gp_url: String,
+    /// Format GP groups are downloaded in. Every format is read back the same
+    /// way; CSV is the most compact and OMM formats carry catalog numbers above
+    /// 99999, which TLE cannot.
+    pub gp_format: GpFormat,
+    /// Celestrak's copy of the satellite catalog (SATCAT) as CSV.
+    pub satcat_url: String,
+    /// Supplemental GP query endpoint; `?FILE=<file>&FORMAT=tle` is appended.
+    pub supplemental_url: String,
+    /// Interval of the server's TLE refresh (minutes), which downloads the
+    /// groups again; at startup and on reload a cached group set younger than
+    /// this is used instead of downloading it. Celestrak updates its sets
+    /// about every two hours. `0` turns the refresh off.
+    pub refresh_minutes: u64,
+}
+
+impl Default for CelestrakConfig {
+    fn default() -> Self {
+        CelestrakConfig {
+            gp_url: "https://celestrak.org/NORAD/elements/gp.php".to_string(),
+            gp_format: GpFormat::Tle,
+            satcat_url: "https://celestrak.org/pub/satcat.csv".to_string(),
+            supplemental_url: "https://celestrak.org/NORAD/elements/supplemental/sup-gp.php".to_string(),
+            refresh_minutes: 120,
+        }
+    }
+}
+
+impl CelestrakConfig {
+    pub fn refresh_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.refresh_minutes * 60)
+    }
+}
+
+/// Account on Space-Track.org, the 18th Space Defense Squadron's catalog.
+#[derive(Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SpaceTrackConfig {
+    pub base_url: String,
+    /// Login name, usually the account's e-mail address.
+    pub identity: String,
+    pub password: String,
+}
+
+impl Default for SpaceTrackConfig {
+    fn default() -> Self {
+        SpaceTrackConfig { base_url: "https://www.space-track.org".to_string(), identity: String::new(), password: String::new() }
+    }
+}
+
+impl SpaceTrackConfig {
+    pub fn has_credentials(&self) -> bool {
+        !self.identity.is_empty() && !self.password.is_empty()
+    }
+}
+
+// Keeps the password out of logs
+impl std::fmt::Debug for SpaceTrackConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SpaceTrackConfig")
+            .field("base_url", &self.base_url)
+            .field("identity", &self.identity)
+            .field("password", &if self.password.is_empty() { "" } else { "***" })
+            .finish()
+    }
+}
+
+/// Defaults of the pass searches of `/passes`, `/satellites/{noradId}/passes`
+/// and `POST /predict/passes` for parameters the request leaves out.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PassDefaults {
+    /// Search window (minutes).
+    pub duration_min: i64,
+    /// Sampling step (seconds).
+    pub step_s: i64,
+    pub min_el_deg: f64,
+}
+
+impl Default for PassDefaults {
+    fn default() -> Self {
+        PassDefaults { duration_min: 120, step_s: 15, min_el_deg: 10.0 }
+    }
+}
+
+impl Config {
+    /// Reads `path`, else the file named by `STFCM_CONFIG`, else `stfcm.toml`
+    /// when it exists, and applies the environment overrides. Without a file
+    /// the defaults are used.
+    pub fn load(path: Option<&Path>) -> Result<Config, ConfigError> {
+        let named = path.map(Path::to_path_buf).or_else(|| std::env::var_os(FILE_ENV).map(PathBuf::from));
+        let path = named.or_else(|| Some(PathBuf::from(DEFAULT_FILE)).filter(|p| p.exists()));
+        let mut config = match path {
+            Some(path) => {
+                let text = std::fs::read_to_string(&path).map_err(|source| ConfigError::Io { path: path.clone(), source })?;
+                toml::from_str(&text).map_err(|source| ConfigError::Parse { path, source })?
+            }
+            None => Config::default(),
+        };
+        config.apply_env(|name| std::env::var(name).ok())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Overrides settings with the variables `var` returns; empty values are
+    /// ignored.
+    fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<(), ConfigError> {
+        let var = |name| var(name).filter(|v| !v.is_empty());
+        fn parse<T: FromStr>(name: &'static str, value: String) -> Result<T, ConfigError> {
+            value.parse().map_err(|_| ConfigError::Env { name, value })
+        }
+        if let Some(v) = var(BIND_ENV) {
+            self.server.bind = parse(BIND_ENV, v)?;
+        }
+        if let Some(v) = var(WEB_DIR_ENV) {
+            self.server.web_dir = PathBuf::from(v);
+        }
+        if let Some(v) = var(TLE_DIR_ENV) {
+            self.data.tle_dir = PathBuf::from(v);
+        }
+        if let Some(v) = var(DB_PATH_ENV) {
+            self.data.db_path = PathBuf::from(v);
+        }
+        if let Some(v) = var(SNAPSHOT_ENV) {
+            self.data.snapshot_seconds = parse(SNAPSHOT_ENV, v)?;
+        }
+        if let Some(v) = var(GP_URL_ENV) {
+            self.celestrak.gp_url = v;
+        }
+        if let Some(v) = var(GP_FORMAT_ENV) {
+            self.celestrak.gp_format = parse(GP_FORMAT_ENV, v)?;
+        }
+        if let Some(v) = var(SATCAT_URL_ENV) {
+            self.celestrak.satcat_url = v;
+        }
+        if let Some(v) = var(SUPPLEMENTAL_URL_ENV) {
+            self.celestrak.supplemental_url = v;
+        }
+        if let Some(v) = var(REFRESH_ENV) {
+            self.celestrak.refresh_minutes = parse(REFRESH_ENV, v)?;
+        }
+        if let Some(v) = var(SPACETRACK_URL_ENV) {
+            self.spacetrack.base_url = v;
+        }
+        if let Some(v) = var(SPACETRACK_IDENTITY_ENV) {
+            self.spacetrack.identity = v;
+        }
+        if let Some(v) = var(SPACETRACK_PASSWORD_ENV) {
+            self.spacetrack.password = v;
+        }
+        if let Some(v) = var(PASS_DURATION_ENV) {
+            self.passes.duration_min = parse(PASS_DURATION_ENV, v)?;
+        }
+        if let Some(v) = var(PASS_STEP_ENV) {
+            self.passes.step_s = parse(PASS_STEP_ENV, v)?;
+        }
+        if let Some(v) = var(PASS_MIN_EL_ENV) {
+            self.passes.min_el_deg = parse(PASS_MIN_EL_ENV, v)?;
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        let p = &self.passes;
+        if p.duration_min <= 0 || p.step_s <= 0 {
+            return Err(ConfigError::Invalid("passes.duration_min and passes.step_s must be positive".to_string()));
+        }
+        if !(-90.0..=90.0).contains(&p.min_el_deg) {
+            return Err(ConfigError::Invalid("passes.min_el_deg must be within [-90, 90]".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_values_are_overridden_by_the_environment() {
+        let mut config: Config = toml::from_str(
+            "[server]\nbind = \"0.0.0.0:8080\"\n\n[celestrak]\nrefresh_minutes = 30\ngp_format = \"3le\"\n\n[passes]\nmin_el_deg = 5.0\n",
+        )
+        .unwrap();
+        assert_eq!(config.server.bind.port(), 8080);
+        assert_eq!(config.server.web_dir, PathBuf::from("web"));
+        assert_eq!((config.celestrak.refresh_minutes, config.passes.min_el_deg, config.passes.step_s), (30, 5.0, 15));
+        assert_eq!(config.celestrak.gp_format, GpFormat::ThreeLe);
+
+        let env = |name: &str| match name {
+            DB_PATH_ENV => Some("/var/lib/stfcm/tracker.sqlite".to_string()),
+            PASS_MIN_EL_ENV => Some("15".to_string()),
+            BIND_ENV => Some(String::new()),
+            GP_FORMAT_ENV => Some("CSV".to_string()),
+            _ => None,
+        };
+        config.apply_env(env).unwrap();
+        assert_eq!(config.data.db_path, PathBuf::from("/var/lib/stfcm/tracker.sqlite"));
+        assert_eq!(config.celestrak.gp_format, GpFormat::Csv);
+        assert_eq!((config.passes.min_el_deg, config.server.bind.port()), (15.0, 8080));
+        assert!(config.validate().is_ok());
+
+        assert!(config.apply_env(|name| (name == PASS_STEP_ENV).then(|| "fast".to_string())).is_err());
+        assert!(toml::from_str::<Config>("[server]\nport = 80\n").is_err());
+        assert!(Config { passes: PassDefaults { step_s: 0, ..Default::default() }, ..Default::default() }.validate().is_err());
+    }
+}
@@ -0,0 +1,185 @@
+use serde_json::Value;
+
+use crate::core::orbit::{cross, dot};
+
+/// Most positions accepted in one geometry.
+pub const MAX_AOI_POSITIONS: usize = 1000;
+/// Widest accepted extent in latitude and in longitude (degrees). Wider polygons
+/// are most likely meant to cross the antimeridian, which is not supported.
+pub const MAX_AOI_SPAN_DEG: f64 = 90.0;
+
+/// Geometry of an area of interest. Positions are `[lon, lat]` in degrees, as in
+/// GeoJSON.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AoiGeometry {
+    /// A site, e.g. a target for point access.
+    Point([f64; 2]),
+    /// Closed rings: the exterior first, then any holes.
+    Polygon(Vec<Vec<[f64; 2]>>),
+}
+
+fn position(value: &Value) -> Result<[f64; 2], String> {
+    let coords = value.as_array().filter(|c| c.len() >= 2).ok_or("a position must be [lon, lat]")?;
+    let (Some(lon), Some(lat)) = (coords[0].as_f64(), coords[1].as_f64()) else {
+        return Err("a position must be [lon, lat]".to_string());
+    };
+    if !(-180.0..=180.0).contains(&lon) || !(-90.0..=90.0).contains(&lat) {
+        return Err(format!("position [{}, {}] is outside [-180, 180] x [-90, 90]", lon, lat));
+    }
+    Ok([lon, lat])
+}
+
+fn ring(value: &Value) -> Result<Vec<[f64; 2]>, String> {
+    let ring = value.as_array().ok_or("a polygon ring must be an array of positions")?.iter().map(position).collect::<Result<Vec<_>, _>>()?;
+    if ring.len() < 4 || ring.first() != ring.last() {
+        return Err("a polygon ring needs at least 4 positions and must end where it starts".to_string());
+    }
+    // Shoelace area in the lon/lat plane; zero for rings that enclose nothing
+    let area: f64 = ring.windows(2).map(|e| e[0][0] * e[1][1] - e[1][0] * e[0][1]).sum();
+    if area.abs() < 1e-12 {
+        return Err("a polygon ring must enclose an area".to_string());
+    }
+    Ok(ring)
+}
+
+/// Checks the size limits of a polygon.
+fn check_extent(rings: &[Vec<[f64; 2]>]) -> Result<(), String> {
+    let count: usize = rings.iter().map(Vec::len).sum();
+    if count > MAX_AOI_POSITIONS {
+        return Err(format!("{} positions exceed the limit of {}", count, MAX_AOI_POSITIONS));
+    }
+    let span = |i: usize| {
+        let values = rings[0].iter().map(|p| p[i]);
+        values.clone().fold(f64::NEG_INFINITY, f64::max) - values.fold(f64::INFINITY, f64::min)
+    };
+    if span(0) > MAX_AOI_SPAN_DEG || span(1) > MAX_AOI_SPAN_DEG {
+        return Err(format!("the polygon spans more than {}° in longitude or latitude", MAX_AOI_SPAN_DEG));
+    }
+    Ok(())
+}
+
+impl AoiGeometry {
+    /// Parses a GeoJSON `Point` or `Polygon`, or a `Feature` with one, and checks
+    /// the size limits.
+    pub fn from_geojson(value: &Value) -> Result<AoiGeometry, String> {
+        let geometry = match value.get("type").and_then(Value::as_str) {
+            Some("Feature") => value.get("geometry").ok_or("the Feature has no geometry")?,
+            _ => value,
+        };
+        let coordinates = geometry.get("coordinates").ok_or("the geometry has no coordinates")?;
+        match geometry.get("type").and_then(Value::as_str) {
+            Some("Point") => Ok(AoiGeometry::Point(position(coordinates)?)),
+            Some("Polygon") => {
+                let rings = coordinates.as_array().filter(|r| !r.is_empty()).ok_or("a Polygon needs an exterior ring")?;
+                let rings = rings.iter().map(ring).collect::<Result<Vec<_>, _>>()?;
+                check_extent(&rings)?;
+                Ok(AoiGeometry::Polygon(rings))
+            }
+            Some(other) => Err(format!("unsupported geometry type {}; use Point or Polygon", other)),
+            None => Err("missing geometry type".to_string()),
+        }
+    }
+
+    pub fn to_geojson(&self) -> Value {
+        match self {
+            AoiGeometry::Point(p) => serde_json::json!({"type": "Point", "coordinates": p}),
+            AoiGeometry::Polygon(rings) => serde_json::json!({"type": "Polygon", "coordinates": rings}),
+        }
+    }
+
+    /// Whether a point lies inside the polygon (and none of its holes); never for
+    /// a point geometry.
+    pub fn contains(&self, lat_deg: f64, lon_deg: f64) -> bool {
+        match self {
+            AoiGeometry::Point(_) => false,
+            AoiGeometry::Polygon(rings) => {
+                let inside = |ring: &Vec<[f64; 2]>| contains(ring, lon_deg, lat_deg);
+                rings.first().is_some_and(inside) && !rings[1..].iter().any(inside)
+            }
+        }
+    }
+
+    /// Great-circle distance (degrees) from a point to the area; 0 inside it.
+    pub fn distance_deg(&self, lat_deg: f64, lon_deg: f64) -> f64 {
+        match self {
+            AoiGeometry::Point([lon, lat]) => angle_deg(&unit_vector(lon_deg, lat_deg), &unit_vector(*lon, *lat)),
+            AoiGeometry::Polygon(rings) => {
+                if self.contains(lat_deg, lon_deg) {
+                    return 0.0;
+                }
+                let p = unit_vector(lon_deg, lat_deg);
+                rings
+                    .iter()
+                    .flat_map(|r| r.windows(2))
+                    .map(|edge| arc_distance_deg(&p, &unit_vector(edge[0][0], edge[0][1]), &unit_vector(edge[1][0], edge[1][1])))
+                    .fold(f64::INFINITY, f64::min)
+            }
+        }
+    }
+}
+
+/// Even-odd test in the lon/lat plane; rings are taken not to cross the antimeridian.
+pub(crate) fn contains(ring: &[[f64; 2]], lon: f64, lat: f64) -> bool {
+    let mut inside = false;
+    for edge in ring.windows(2) {
+        let ([x1, y1], [x2, y2]) = (edge[0], edge[1]);
+        if (y1 > lat) != (y2 > lat) && lon < x1 + (lat - y1) / (y2 - y1) * (x2 - x1) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+fn unit_vector(lon_deg: f64, lat_deg: f64) -> [f64; 3] {
+    let (sin_lat, cos_lat) = lat_deg.to_radians().sin_cos();
+    let (sin_lon, cos_lon) = lon_deg.to_radians().sin_cos();
+    [cos_lat * cos_lon, cos_lat * sin_lon, sin_lat]
+}
+
+fn angle_deg(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    dot(&cross(a, b), &cross(a, b)).sqrt().atan2(dot(a, b)).to_degrees()
+}
+
+/// Distance (degrees) from `p` to the great-circle arc from `a` to `b`.
+fn arc_distance_deg(p: &[f64; 3], a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    let n = cross(a, b);
+    let norm = dot(&n, &n).sqrt();
+    if norm > 1e-12 {
+        let n = n.map(|c| c / norm);
+        // `p` projects between the endpoints when it lies on the inner side of both
+        if dot(&cross(a, p), &n) >= 0.0 && dot(&cross(p, b), &n) >= 0.0 {
+            return dot(p, &n).clamp(-1.0, 1.0).asin().abs().to_degrees();
+        }
+    }
+    angle_deg(p, a).min(angle_deg(p, b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distance_is_zero_inside_and_grows_outside() {
+        let square = serde_json::json!({
+            "type": "Feature",
+            "geometry": {"type": "Polygon", "coordinates": [[[10.0, 40.0], [12.0, 40.0], [12.0, 42.0], [10.0, 42.0], [10.0, 40.0]]]},
+        });
+        let aoi = AoiGeometry::from_geojson(&square).unwrap();
+        assert_eq!(aoi.distance_deg(41.0, 11.0), 0.0);
+        // Due south of the bottom edge and beyond a corner
+        assert!((aoi.distance_deg(39.0, 11.0) - 1.0).abs() < 0.01);
+        let corner = aoi.distance_deg(39.0, 13.0);
+        assert!(corner > 1.0 && corner < 1.5, "{}", corner);
+
+        let open = serde_json::json!({"type": "Polygon", "coordinates": [[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0]]]});
+        assert!(AoiGeometry::from_geojson(&open).is_err());
+        assert!(AoiGeometry::from_geojson(&serde_json::json!({"type": "LineString", "coordinates": []})).is_err());
+        let flat = serde_json::json!({"type": "Polygon", "coordinates": [[[0.0, 0.0], [1.0, 0.0], [2.0, 0.0], [0.0, 0.0]]]});
+        assert!(AoiGeometry::from_geojson(&flat).is_err());
+        let wide = serde_json::json!({"type": "Polygon", "coordinates": [[[-170.0, 0.0], [170.0, 0.0], [170.0, 1.0], [-170.0, 0.0]]]});
+        assert!(AoiGeometry::from_geojson(&wide).is_err());
+
+        let site = AoiGeometry::from_geojson(&serde_json::json!({"type": "Point", "coordinates": [11.0, 41.0]})).unwrap();
+        assert!((site.distance_deg(42.0, 11.0) - 1.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,373 @@
+use std::collections::{BTreeMap, HashMap};
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::analyzers::groups::{validate_group_name, GroupFilter};
+use crate::core::custom::synthetic_id;
+use crate::utils::db::{self, DbError, SatelliteAliases};
+
+/// Layout version written by [`export_bundle`]; bundles of other versions are refused.
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// Everything an operator configured on a deployment, to move it to another one:
+/// stations with their exclusions and favorites, satellite tags, dynamic
+/// group filters, aliases, conjunction alert subscriptions and custom element sets. Computed
+/// and recorded data (horizons, device tokens, history) is not included.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigBundle {
+    pub version: u32,
+    pub exported_at: String,
+    #[serde(default)]
+    pub stations: Vec<BundleStation>,
+    #[serde(default)]
+    pub tags: Vec<BundleTag>,
+    #[serde(default)]
+    pub groups: Vec<BundleGroup>,
+    #[serde(default)]
+    pub aliases: Vec<BundleAliases>,
+    #[serde(default)]
+    pub protected_assets: Vec<BundleAsset>,
+    #[serde(default)]
+    pub custom_elements: Vec<BundleCustomElements>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleStation {
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+    pub lat: f64,
+    pub lon: f64,
+    #[serde(default)]
+    pub alt_m: f64,
+    #[serde(default)]
+    pub exclusions: Vec<BundleSector>,
+    #[serde(default)]
+    pub favorites: Vec<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleSector {
+    pub start_deg: f64,
+    pub end_deg: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleTag {
+    pub norad_id: u64,
+    pub tag: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+    pub tagged_at: String,
+}
+
+/// A dynamic group; its members travel as tags.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleGroup {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+    pub filter: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleAliases {
+    pub norad_id: u64,
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleAsset {
+    pub norad_id: u64,
+    pub alert_threshold_km: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+}
+
+/// A custom element set. `id` is its row ID on the exporting deployment; other
+/// sections refer to it by the synthetic NORAD ID derived from it, and both are
+/// renumbered on import.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleCustomElements {
+    pub id: i64,
+    pub name: Option<String>,
+    pub tle: Option<String>,
+    pub omm: Option<serde_json::Value>,
+}
+
+/// What an import wrote.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ImportSummary {
+    pub stations_created: usize,
+    pub stations_updated: usize,
+    pub tags: usize,
+    pub groups: usize,
+    pub aliases: usize,
+    pub protected_assets: usize,
+    pub custom_elements: usize,
+}
+
+#[derive(Debug, Error)]
+pub enum BundleError {
+    #[error("invalid bundle: {0}")]
+    Invalid(String),
+    #[error(transparent)]
+    Db(#[from] DbError),
+}
+
+impl From<rusqlite::Error> for BundleError {
+    fn from(e: rusqlite::Error) -> Self {
+        BundleError::Db(e.into())
+    }
+}
+
+/// Serialization of a bundle file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BundleFormat {
+    Json,
+    Yaml,
+}
+
+impl BundleFormat {
+    pub fn parse(s: &str) -> Option<BundleFormat> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Some(BundleFormat::Json),
+            "yaml" | "yml" => Some(BundleFormat::Yaml),
+            _ => None,
+        }
+    }
+
+    pub fn encode(self, bundle: &ConfigBundle) -> Result<String, String> {
+        match self {
+            BundleFormat::Json => serde_json::to_string_pretty(bundle).map_err(|e| e.to_string()),
+            BundleFormat::Yaml => serde_yaml::to_string(bundle).map_err(|e| e.to_string()),
+        }
+    }
+
+    pub fn decode(self, text: &str) -> Result<ConfigBundle, String> {
+        match self {
+            BundleFormat::Json => serde_json::from_str(text).map_err(|e| e.to_string()),
+            BundleFormat::Yaml => serde_yaml::from_str(text).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// Reads the configuration of this deployment into a bundle.
+pub fn export_bundle(conn: &Connection, exported_at: &str) -> Result<ConfigBundle, DbError> {
+    let mut stations = Vec::new();
+    for s in db::list_stations(conn)? {
+        let exclusions = db::list_station_exclusions(conn, s.id)?
+            .into_iter()
+            .map(|(start_deg, end_deg)| BundleSector { start_deg, end_deg })
+            .collect();
+        let favorites = db::list_station_favorites(conn, s.id)?;
+        stations.push(BundleStation { name: s.name, tenant: s.tenant, lat: s.lat, lon: s.lon, alt_m: s.alt_m, exclusions, favorites });
+    }
+    let tags = db::list_all_tags(conn)?
+        .into_iter()
+        .map(|t| BundleTag { norad_id: t.norad_id, tag: t.tag, tenant: t.tenant, tagged_at: t.created_at })
+        .collect();
+    let groups = db::list_dynamic_groups(conn, None)?
+        .into_iter()
+        .map(|g| BundleGroup { name: g.name, tenant: g.tenant, filter: g.filter })
+        .collect();
+    let aliases = db::list_satellite_aliases(conn)?
+        .into_iter()
+        .collect::<BTreeMap<_, _>>()
+        .into_iter()
+        .map(|(norad_id, names)| BundleAliases { norad_id, display_name: names.display_name, aliases: names.aliases })
+        .collect();
+    let protected_assets = db::list_protected_assets(conn, None)?
+        .into_iter()
+        .map(|a| BundleAsset { norad_id: a.norad_id, alert_threshold_km: a.alert_threshold_km, tenant: a.tenant })
+        .collect();
+    let custom_elements = db::list_custom_elements(conn)?
+        .into_iter()
+        .map(|row| BundleCustomElements {
+            id: row.id,
+            name: row.name,
+            tle: row.tle,
+            omm: row.omm.and_then(|o| serde_json::from_str(&o).ok()),
+        })
+        .collect();
+    Ok(ConfigBundle {
+        version: BUNDLE_VERSION,
+        exported_at: exported_at.to_string(),
+        stations,
+        tags,
+        groups,
+        aliases,
+        protected_assets,
+        custom_elements,
+    })
+}
+
+/// Checks everything an import would write before anything is.
+fn validate(bundle: &ConfigBundle) -> Result<(), String> {
+    if bundle.version != BUNDLE_VERSION {
+        return Err(format!("unsupported bundle version {} (expected {})", bundle.version, BUNDLE_VERSION));
+    }
+    let mut names = std::collections::HashSet::new();
+    for (i, s) in bundle.stations.iter().enumerate() {
+        if !((-90.0..=90.0).contains(&s.lat) && (-180.0..=180.0).contains(&s.lon)) {
+            return Err(format!("stations[{}]: lat/lon out of range", i));
+        }
+        if !s.alt_m.is_finite() || !(-1_000.0..=100_000.0).contains(&s.alt_m) {
+            return Err(format!("stations[{}]: height out of range", i));
+        }
+        if s.exclusions.iter().any(|x| !(x.start_deg.is_finite() && x.end_deg.is_finite())) {
+            return Err(format!("stations[{}]: exclusion sectors must be finite", i));
+        }
+        if let Some(name) = &s.name {
+            if !names.insert((s.tenant.as_deref(), name.as_str())) {
+                return Err(format!("stations[{}]: duplicate name {}", i, name));
+            }
+        }
+    }
+    for (i, a) in bundle.protected_assets.iter().enumerate() {
+        if a.alert_threshold_km.is_nan() || a.alert_threshold_km <= 0.0 {
+            return Err(format!("protected_assets[{}]: alert_threshold_km must be positive", i));
+        }
+    }
+    for (i, t) in bundle.tags.iter().enumerate() {
+        if t.tag.trim().is_empty() {
+            return Err(format!("tags[{}]: empty tag", i));
+        }
+    }
+    for (i, g) in bundle.groups.iter().enumerate() {
+        validate_group_name(&g.name).map_err(|e| format!("groups[{}]: {}", i, e))?;
+        GroupFilter::parse(&g.filter).map_err(|e| format!("groups[{}]: invalid filter: {}", i, e))?;
+    }
+    for (i, c) in bundle.custom_elements.iter().enumerate() {
+        crate::core::tle::parse_tle_or_omm(c.tle.as_deref(), c.omm.clone()).map_err(|e| format!("custom_elements[{}]: {}", i, e))?;
+    }
+    Ok(())
+}
+
+/// Writes a bundle in one transaction. With `replace` the existing configuration
+/// is cleared first; otherwise stations with the same name are updated, and
+/// custom element sets identical to a stored one reuse it. References to custom
+/// satellites are renumbered to their synthetic IDs on this deployment.
+pub fn import_bundle(conn: &Connection, bundle: &ConfigBundle, replace: bool, now: &str) -> Result<ImportSummary, BundleError> {
+    validate(bundle).map_err(BundleError::Invalid)?;
+    let tx = conn.unchecked_transaction()?;
+    if replace {
+        db::clear_configuration(&tx)?;
+    }
+    let mut summary = ImportSummary::default();
+
+    let existing = db::list_custom_elements(&tx)?;
+    let mut renumbered = HashMap::new();
+    for c in &bundle.custom_elements {
+        let omm = c.omm.as_ref().map(|o| o.to_string());
+        let stored = existing.iter().find(|row| {
+            let row_omm = row.omm.as_deref().and_then(|o| serde_json::from_str::<serde_json::Value>(o).ok());
+            row.name == c.name && row.tle == c.tle && row_omm == c.omm
+        });
+        let id = match stored {
+            Some(row) => row.id,
+            None => {
+                summary.custom_elements += 1;
+                db::insert_custom_elements(&tx, c.name.as_deref(), c.tle.as_deref(), omm.as_deref(), now)?
+            }
+        };
+        renumbered.insert(synthetic_id(c.id), synthetic_id(id));
+    }
+    let norad = |id: u64| renumbered.get(&id).copied().unwrap_or(id);
+
+    let stations = db::list_stations(&tx)?;
+    for s in &bundle.stations {
+        let same_name = s.name.as_ref().and_then(|name| stations.iter().find(|e| e.name.as_ref() == Some(name) && e.tenant == s.tenant));
+        let id = match same_name {
+            Some(station) => {
+                db::update_station(&tx, station.id, s.name.as_deref(), s.lat, s.lon, s.alt_m)?;
+                summary.stations_updated += 1;
+                station.id
+            }
+            None => {
+                summary.stations_created += 1;
+                db::insert_station(&tx, s.name.as_deref(), s.lat, s.lon, s.alt_m, s.tenant.as_deref())?
+            }
+        };
+        let sectors: Vec<(f64, f64)> = s.exclusions.iter().map(|x| (x.start_deg, x.end_deg)).collect();
+        db::set_station_exclusions(&tx, id, &sectors)?;
+        let favorites: Vec<u64> = s.favorites.iter().map(|&n| norad(n)).collect();
+        db::set_station_favorites(&tx, id, &favorites)?;
+    }
+    for t in &bundle.tags {
+        db::tag_satellites(&tx, &[norad(t.norad_id)], t.tag.trim(), t.tenant.as_deref(), &t.tagged_at)?;
+    }
+    summary.tags = bundle.tags.len();
+    for g in &bundle.groups {
+        db::upsert_dynamic_group(&tx, &g.name, g.tenant.as_deref(), g.filter.trim(), now)?;
+    }
+    summary.groups = bundle.groups.len();
+    for a in &bundle.aliases {
+        let names = SatelliteAliases { display_name: a.display_name.clone(), aliases: a.aliases.clone() };
+        db::set_satellite_aliases(&tx, norad(a.norad_id), &names)?;
+    }
+    summary.aliases = bundle.aliases.len();
+    for a in &bundle.protected_assets {
+        db::upsert_protected_asset(&tx, norad(a.norad_id), a.alert_threshold_km, a.tenant.as_deref())?;
+    }
+    summary.protected_assets = bundle.protected_assets.len();
+    tx.commit()?;
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundles_round_trip_and_renumber_custom_satellites() {
+        let source = Connection::open_in_memory().unwrap();
+        db::init_schema(&source).unwrap();
+        let now = "2026-01-01T00:00:00Z";
+        // Row 1 is deleted so the exported set has ID 2 and imports as ID 1
+        db::insert_custom_elements(&source, Some("GONE"), Some(crate::testing::fixtures::ISS_TLE), None, now).unwrap();
+        let custom = db::insert_custom_elements(&source, Some("CUBESAT-1"), Some(crate::testing::fixtures::ISS_TLE), None, now).unwrap();
+        db::delete_custom_elements(&source, 1).unwrap();
+        let station = db::insert_station(&source, Some("roof"), 52.0, 4.0, 10.0, Some("club")).unwrap();
+        db::set_station_exclusions(&source, station, &[(350.0, 10.0)]).unwrap();
+        db::set_station_favorites(&source, station, &[25544, synthetic_id(custom)]).unwrap();
+        db::upsert_protected_asset(&source, synthetic_id(custom), 5.0, None).unwrap();
+        db::tag_satellites(&source, &[25544], "ops", Some("club"), now).unwrap();
+        db::upsert_dynamic_group(&source, "fengyun", Some("club"), "1999-025*", now).unwrap();
+
+        let bundle = export_bundle(&source, now).unwrap();
+        let yaml = BundleFormat::Yaml.encode(&bundle).unwrap();
+        assert_eq!(BundleFormat::Yaml.decode(&yaml).unwrap(), bundle);
+
+        let target = Connection::open_in_memory().unwrap();
+        db::init_schema(&target).unwrap();
+        let summary = import_bundle(&target, &bundle, false, now).unwrap();
+        assert_eq!((summary.stations_created, summary.custom_elements, summary.tags), (1, 1, 1));
+        let imported = db::list_stations(&target).unwrap()[0].id;
+        assert_eq!(db::list_station_favorites(&target, imported).unwrap(), vec![25544, synthetic_id(1)]);
+        assert_eq!(db::list_protected_assets(&target, None).unwrap()[0].norad_id, synthetic_id(1));
+        assert_eq!(db::list_stations(&target).unwrap()[0].tenant.as_deref(), Some("club"));
+        assert_eq!(db::tagged_norad_ids(&target, "ops", Some("club")).unwrap(), [25544]);
+        let group = db::get_dynamic_group(&target, "fengyun", Some("club")).unwrap().unwrap();
+        assert_eq!((summary.groups, group.filter.as_str()), (1, "1999-025*"));
+        assert_eq!(db::list_station_exclusions(&target, imported).unwrap(), vec![(350.0, 10.0)]);
+
+        // Importing again updates the station and reuses the custom set
+        let again = import_bundle(&target, &bundle, false, now).unwrap();
+        assert_eq!((again.stations_updated, again.custom_elements), (1, 0));
+        assert_eq!(db::list_custom_elements(&target).unwrap().len(), 1);
+
+        let broken = ConfigBundle { version: 2, ..bundle };
+        assert!(matches!(import_bundle(&target, &broken, true, now), Err(BundleError::Invalid(_))));
+        assert_eq!(db::list_stations(&target).unwrap().len(), 1);
+
+        // Replacing clears the groups the bundle does not name
+        let empty = ConfigBundle { groups: Vec::new(), ..export_bundle(&target, now).unwrap() };
+        import_bundle(&target, &empty, true, now).unwrap();
+        assert!(db::list_dynamic_groups(&target, None).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+use std::ops::Deref;
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Utc};
+
+use crate::analyzers::launches::parse_designator;
+use crate::core::orbit::minutes_since_epoch;
+
+/// The loaded element sets with lookups by NORAD ID and international
+/// designator and the SGP4 constants of each set, initialised on first use and
+/// kept for the catalog's lifetime. Dereferences to the element slice in load
+/// order. A new catalog is built on every load.
+///
+/// Names and designators are moved out of the element sets into one shared
+/// string table, so the sets in the slice carry neither: read them with
+/// [`Catalog::name`] and [`Catalog::designator`].
+pub struct Catalog {
+    elements: Vec<sgp4::Elements>,
+    labels: Vec<Labels>,
+    strings: Strings,
+    by_norad: HashMap<u64, usize>,
+    /// Indices of the element sets with a designator, ordered by it.
+    by_designator: Vec<u32>,
+    /// `None` for element sets SGP4 rejects.
+    constants: Vec<OnceLock<Option<sgp4::Constants<'static>>>>,
+}
+
+/// Name and designator of one element set in the string table.
+#[derive(Clone, Copy)]
+struct Labels {
+    name: Option<Symbol>,
+    designator: Option<Symbol>,
+}
+
+/// A string in [`Strings`]; non-zero so an absent one costs no extra space.
+#[derive(Clone, Copy)]
+struct Symbol(NonZeroU32);
+
+/// Distinct strings stored back to back in one buffer. Debris of one breakup
+/// shares a name, so each distinct string is stored once.
+#[derive(Default)]
+struct Strings {
+    text: String,
+    /// End offset in `text` of each string, in insertion order.
+    ends: Vec<u32>,
+}
+
+impl Strings {
+    fn get(&self, symbol: Symbol) -> &str {
+        let i = symbol.0.get() as usize - 1;
+        let start = if i == 0 { 0 } else { self.ends[i - 1] as usize };
+        &self.text[start..self.ends[i] as usize]
+    }
+
+    fn push(&mut self, s: &str) -> Symbol {
+        self.text.push_str(s);
+        self.ends.push(u32::try_from(self.text.len()).expect("catalog strings fit in 4 GiB"));
+        Symbol(NonZeroU32::new(self.ends.len() as u32).expect("symbols start at 1"))
+    }
+}
+
+/// Builds [`Strings`], handing out the same symbol for equal strings.
+#[derive(Default)]
+struct Interner {
+    strings: Strings,
+    seen: HashMap<String, Symbol>,
+}
+
+impl Interner {
+    fn intern(&mut self, s: String) -> Symbol {
+        if let Some(&symbol) = self.seen.get(&s) {
+            return symbol;
+        }
+        let symbol = self.strings.push(&s);
+        self.seen.insert(s, symbol);
+        symbol
+    }
+
+    fn finish(self) -> Strings {
+        let mut strings = self.strings;
+        strings.text.shrink_to_fit();
+        strings.ends.shrink_to_fit();
+        strings
+    }
+}
+
+/// `YYYY-NNNP` for a designator in either format; other text is kept as is.
+fn normalise_designator(text: String) -> String {
+    match parse_designator(&text) {
+        Some(d) => format!("{}{}", d.launch, d.piece),
+        None => text,
+    }
+}
+
+impl Catalog {
+    pub fn new(mut elements: Vec<sgp4::Elements>) -> Catalog {
+        elements.shrink_to_fit();
+        let mut interner = Interner::default();
+        let labels: Vec<Labels> = elements
+            .iter_mut()
+            .map(|el| Labels {
+                name: el.object_name.take().map(|n| interner.intern(n)),
+                designator: el.international_designator.take().map(|d| interner.intern(normalise_designator(d))),
+            })
+            .collect();
+        let strings = interner.finish();
+        let mut by_norad = HashMap::with_capacity(elements.len());
+        for (i, el) in elements.iter().enumerate() {
+            // The first of duplicate IDs wins, as with a linear search
+            by_norad.entry(el.norad_id).or_insert(i);
+        }
+        let mut by_designator: Vec<u32> = (0..labels.len() as u32)
+            .filter(|&i| labels[i as usize].designator.is_some_and(|d| parse_designator(strings.get(d)).is_some()))
+            .collect();
+        // Stable, so the first of duplicate designators comes first
+        by_designator.sort_by_key(|&i| labels[i as usize].designator.map(|d| strings.get(d)));
+        let constants = elements.iter().map(|_| OnceLock::new()).collect();
+        Catalog { elements, labels, strings, by_norad, by_designator, constants }
+    }
+
+    /// Position of a satellite in the element slice.
+    pub fn index_of(&self, norad_id: u64) -> Option<usize> {
+        self.by_norad.get(&norad_id).copied()
+    }
+
+    pub fn get(&self, norad_id: u64) -> Option<&sgp4::Elements> {
+        self.index_of(norad_id).map(|i| &self.elements[i])
+    }
+
+    /// Object name of the element set at `index`.
+    pub fn name(&self, index: usize) -> Option<&str> {
+        self.labels[index].name.map(|s| self.strings.get(s))
+    }
+
+    /// International designator of the element set at `index`, as `YYYY-NNNP`
+    /// when it parses.
+    pub fn designator(&self, index: usize) -> Option<&str> {
+        self.labels[index].designator.map(|s| self.strings.get(s))
+    }
+
+    /// Object name of a satellite.
+    pub fn name_of(&self, norad_id: u64) -> Option<&str> {
+        self.index_of(norad_id).and_then(|i| self.name(i))
+    }
+
+    /// The element sets in load order, each with its name.
+    pub fn named(&self) -> impl Iterator<Item = (&sgp4::Elements, Option<&str>)> {
+        self.elements.iter().enumerate().map(|(i, el)| (el, self.name(i)))
+    }
+
+    /// Looks up `1998-067A` or `98067A`.
+    pub fn by_designator(&self, designator: &str) -> Option<&sgp4::Elements> {
+        let d = parse_designator(designator)?;
+        let key = format!("{}{}", d.launch, d.piece);
+        let at = self.by_designator.partition_point(|&i| self.designator(i as usize).is_some_and(|d| d < key.as_str()));
+        let &i = self.by_designator.get(at)?;
+        (self.designator(i as usize) == Some(key.as_str())).then(|| &self.elements[i as usize])
+    }
+
+    /// State of the element set at `index` at `t`, from its cached constants.
+    pub fn propagate(&self, index: usize, t: DateTime<Utc>) -> Result<sgp4::Prediction, sgp4::Error> {
+        let el = &self.elements[index];
+        let minutes = minutes_since_epoch(el, t);
+        match self.constants[index].get_or_init(|| sgp4::Constants::from_elements(el).ok()) {
+            Some(constants) => constants.propagate(minutes),
+            // Initialised again only to report why it fails
+            None => sgp4::Constants::from_elements(el).and_then(|c| c.propagate(minutes)),
+        }
+    }
+}
+
+impl Deref for Catalog {
+    type Target = [sgp4::Elements];
+
+    fn deref(&self) -> &[sgp4::Elements] {
+        &self.elements
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::orbit::propagate_minutes;
+
+    #[test]
+    fn looks_up_by_id_and_designator_and_propagates() {
+        let elements = crate::testing::fixtures::catalog();
+        let first = &elements[0];
+        let mut duplicated = crate::testing::fixtures::catalog();
+        let mut shadow = crate::testing::fixtures::catalog().swap_remove(0);
+        shadow.object_name = Some("SHADOW".to_string());
+        duplicated.push(shadow);
+        let catalog = Catalog::new(duplicated);
+
+        assert_eq!(catalog.len(), elements.len() + 1);
+        assert_eq!(catalog.get(first.norad_id).map(|e| e.norad_id), Some(first.norad_id));
+        assert_eq!(catalog.name_of(first.norad_id), first.object_name.as_deref());
+        assert_eq!(catalog.name(elements.len()), Some("SHADOW"));
+        assert!(catalog.get(u64::MAX).is_none());
+        let designator = first.international_designator.clone().unwrap();
+        assert_eq!(catalog.by_designator(&designator).map(|e| e.norad_id), Some(first.norad_id));
+        assert_eq!(catalog.designator(0), Some("1998-067A"));
+        for el in elements.iter().skip(1) {
+            let designator = el.international_designator.as_deref().unwrap();
+            assert_eq!(catalog.by_designator(designator).map(|e| e.norad_id), Some(el.norad_id));
+        }
+        assert!(catalog.by_designator("1900-001A").is_none());
+
+        let t = first.datetime.and_utc() + chrono::Duration::minutes(90);
+        let cached = catalog.propagate(0, t).unwrap();
+        let direct = propagate_minutes(first, 90.0).unwrap();
+        assert_eq!(cached.position, direct.position);
+        // Served from the same constants the second time
+        assert_eq!(catalog.propagate(0, t).unwrap().position, direct.position);
+    }
+
+    #[test]
+    fn equal_names_are_stored_once() {
+        let mut elements = crate::testing::fixtures::catalog();
+        for el in &mut elements {
+            el.object_name = Some("FENGYUN 1C DEB".to_string());
+        }
+        let catalog = Catalog::new(elements);
+        assert!(catalog.named().all(|(_, name)| name == Some("FENGYUN 1C DEB")));
+        assert_eq!(catalog.strings.text.matches("FENGYUN").count(), 1);
+        assert!(catalog.iter().all(|el| el.object_name.is_none() && el.international_designator.is_none()));
+    }
+
+    /// Heap bytes of the names and designators held as owned strings with a
+    /// designator map, against the interned table, for a catalog the size of
+    /// the full GP set in which a third of the objects are debris of a few
+    /// breakups. Run with `cargo test -- --ignored --nocapture catalog_label_footprint`.
+    #[test]
+    #[ignore]
+    fn catalog_label_footprint() {
+        const SIZE: usize = 30_000;
+        let template = crate::testing::fixtures::catalog().swap_remove(0);
+        let elements: Vec<sgp4::Elements> = (0..SIZE)
+            .map(|i| {
+                let mut el = crate::testing::fixtures::catalog().swap_remove(0);
+                el.norad_id = i as u64 + 1;
+                el.object_name = Some(match i % 3 {
+                    0 => format!("{} DEB", ["FENGYUN 1C", "COSMOS 2251", "IRIDIUM 33"][i % 9 / 3]),
+                    _ => format!("STARLINK-{}", i),
+                });
+                el.international_designator = Some(format!("{}-{:03}{}", 1999 + i % 25, i / 25 % 1000, ["A", "B", "C", "AB"][i % 4]));
+                el
+            })
+            .collect();
+        // Each owned string plus the allocator's 16-byte minimum per allocation
+        let allocation = |s: &String| s.capacity().max(16);
+        let owned: usize = elements
+            .iter()
+            .map(|el| {
+                let labels = el.object_name.iter().chain(&el.international_designator).map(allocation).sum::<usize>();
+                // The designator map's normalised key and slot
+                labels + 16 + std::mem::size_of::<(String, usize)>() + 1
+            })
+            .sum();
+        let catalog = Catalog::new(elements);
+        let interned = catalog.strings.text.capacity()
+            + catalog.strings.ends.capacity() * std::mem::size_of::<u32>()
+            + catalog.labels.capacity() * std::mem::size_of::<Labels>()
+            + catalog.by_designator.capacity() * std::mem::size_of::<u32>();
+        println!(
+            "{} element sets: owned labels {} KiB, interned {} KiB ({:.0}% less); Elements {} B and constants {} B each",
+            SIZE,
+            owned / 1024,
+            interned / 1024,
+            100.0 * (1.0 - interned as f64 / owned as f64),
+            std::mem::size_of_val(&template),
+            std::mem::size_of::<OnceLock<Option<sgp4::Constants<'static>>>>()
+        );
+        assert!(interned < owned / 2);
+    }
+}
@@ -0,0 +1,102 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+use tracing::{info, warn};
+
+/// Simulation speed relative to wall time, e.g. `60` for one minute per second.
+const RATE_ENV: &str = "STFCM_CLOCK_RATE";
+/// Simulated time (RFC 3339) at startup; defaults to the current time.
+const START_ENV: &str = "STFCM_CLOCK_START";
+
+/// Source of "now" for the prediction and stream code.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// Wall-clock time.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Time that starts at `start` and runs `rate` times faster than the wall clock,
+/// for replaying orbits in demos and tests.
+pub struct SimulatedClock {
+    start: DateTime<Utc>,
+    rate: f64,
+    origin: Instant,
+}
+
+impl SimulatedClock {
+    pub fn new(start: DateTime<Utc>, rate: f64) -> SimulatedClock {
+        SimulatedClock { start, rate, origin: Instant::now() }
+    }
+
+    /// Simulated time after `elapsed` of wall time.
+    pub fn at(&self, elapsed: std::time::Duration) -> DateTime<Utc> {
+        self.start + chrono::Duration::microseconds((elapsed.as_secs_f64() * self.rate * 1e6) as i64)
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.at(self.origin.elapsed())
+    }
+}
+
+/// Parses a replay configuration: `rate` (default 1, must be positive) and an
+/// optional RFC 3339 `start`.
+pub fn parse_replay(rate: Option<&str>, start: Option<&str>) -> Result<Option<(DateTime<Utc>, f64)>, String> {
+    if rate.is_none() && start.is_none() {
+        return Ok(None);
+    }
+    let rate = match rate {
+        Some(r) => r.parse::<f64>().ok().filter(|r| r.is_finite() && *r > 0.0).ok_or_else(|| format!("invalid clock rate: {}", r))?,
+        None => 1.0,
+    };
+    let start = match start {
+        Some(s) => DateTime::parse_from_rfc3339(s).map_err(|e| format!("invalid clock start {}: {}", s, e))?.with_timezone(&Utc),
+        None => Utc::now(),
+    };
+    Ok(Some((start, rate)))
+}
+
+/// Simulated clock from `STFCM_CLOCK_RATE` / `STFCM_CLOCK_START` when either is
+/// set, otherwise the system clock. Invalid values fall back to the system clock.
+pub fn from_env() -> Arc<dyn Clock> {
+    let rate = std::env::var(RATE_ENV).ok();
+    let start = std::env::var(START_ENV).ok();
+    match parse_replay(rate.as_deref(), start.as_deref()) {
+        Ok(Some((start, rate))) => {
+            info!(%start, rate, "Using simulated clock");
+            Arc::new(SimulatedClock::new(start, rate))
+        }
+        Ok(None) => Arc::new(SystemClock),
+        Err(e) => {
+            warn!(error = %e, "Ignoring simulated clock configuration");
+            Arc::new(SystemClock)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::time::Duration;
+
+    #[test]
+    fn simulated_clock_runs_at_rate() {
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let clock = SimulatedClock::new(start, 60.0);
+        assert_eq!(clock.at(Duration::ZERO), start);
+        assert_eq!(clock.at(Duration::from_millis(1500)), start + chrono::Duration::seconds(90));
+        assert!(parse_replay(Some("0"), None).is_err());
+        assert_eq!(parse_replay(Some("60"), Some("2024-03-01T12:00:00Z")).unwrap(), Some((start, 60.0)));
+        assert_eq!(parse_replay(None, None).unwrap(), None);
+    }
+}
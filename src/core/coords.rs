@@ -0,0 +1,203 @@
+use chrono::{DateTime, NaiveDate, Utc};
+
+/// WGS84 equatorial radius (km).
+pub const WGS84_A_KM: f64 = 6378.137;
+/// WGS84 flattening.
+pub const WGS84_F: f64 = 1.0 / 298.257_223_563;
+
+/// Compute GMST (radians) from UTC time using a simplified expression.
+pub fn gmst(t: DateTime<Utc>) -> f64 {
+    // Seconds since J2000 (2000-01-01 12:00:00 UTC)
+    let j2000_naive = NaiveDate::from_ymd_opt(2000, 1, 1)
+        .unwrap()
+        .and_hms_opt(12, 0, 0)
+        .unwrap();
+    let secs = (t.naive_utc() - j2000_naive).num_milliseconds() as f64 / 1000.0;
+    let days = secs / 86400.0;
+    let gmst_deg = 280.46061837 + 360.98564736629 * days;
+    (gmst_deg.rem_euclid(360.0)) * std::f64::consts::PI / 180.0
+}
+
+/// Rotate an ECI (TEME) vector into ECEF about Z by GMST.
+pub fn eci_to_ecef(pos_eci_km: &[f64; 3], gmst_rad: f64) -> (f64, f64, f64) {
+    let (sin_t, cos_t) = gmst_rad.sin_cos();
+    let x_ecef = cos_t * pos_eci_km[0] + sin_t * pos_eci_km[1];
+    let y_ecef = -sin_t * pos_eci_km[0] + cos_t * pos_eci_km[1];
+    let z_ecef = pos_eci_km[2];
+    (x_ecef, y_ecef, z_ecef)
+}
+
+/// Rotate an ECEF vector into ECI (TEME) about Z by GMST; inverse of [`eci_to_ecef`].
+pub fn ecef_to_eci(pos_ecef_km: &[f64; 3], gmst_rad: f64) -> [f64; 3] {
+    let (sin_t, cos_t) = gmst_rad.sin_cos();
+    [
+        cos_t * pos_ecef_km[0] - sin_t * pos_ecef_km[1],
+        sin_t * pos_ecef_km[0] + cos_t * pos_ecef_km[1],
+        pos_ecef_km[2],
+    ]
+}
+
+/// Geodetic latitude/longitude (degrees) of an ECEF position (km), WGS84.
+pub fn ecef_to_geodetic(x: f64, y: f64, z: f64) -> (f64, f64) {
+    let a = WGS84_A_KM;
+    let f = WGS84_F;
+    let b = a * (1.0 - f);
+    let e2 = f * (2.0 - f);
+    let ep2 = (a*a - b*b) / (b*b);
+    let p = (x*x + y*y).sqrt();
+    let th = (a * z).atan2(b * p);
+    let sin_th = th.sin();
+    let cos_th = th.cos();
+    let lat = (z + ep2 * b * sin_th.powi(3)).atan2(p - e2 * a * cos_th.powi(3));
+    let lon = y.atan2(x);
+    (lat.to_degrees(), lon.to_degrees())
+}
+
+/// ECEF position (km) of a geodetic point (degrees, WGS84 ellipsoidal height in km).
+pub fn geodetic_to_ecef(lat_deg: f64, lon_deg: f64, height_km: f64) -> [f64; 3] {
+    let e2 = WGS84_F * (2.0 - WGS84_F);
+    let (sin_lat, cos_lat) = lat_deg.to_radians().sin_cos();
+    let (sin_lon, cos_lon) = lon_deg.to_radians().sin_cos();
+    let n = WGS84_A_KM / (1.0 - e2 * sin_lat * sin_lat).sqrt();
+    [
+        (n + height_km) * cos_lat * cos_lon,
+        (n + height_km) * cos_lat * sin_lon,
+        (n * (1.0 - e2) + height_km) * sin_lat,
+    ]
+}
+
+/// Geodetic latitude/longitude (degrees) and ellipsoidal height (km) of an ECEF
+/// position (km), WGS84. Iterative, accurate to well below a millimetre for
+/// points near the surface, e.g. surveyed antenna positions.
+pub fn ecef_to_geodetic_height(x: f64, y: f64, z: f64) -> (f64, f64, f64) {
+    let e2 = WGS84_F * (2.0 - WGS84_F);
+    let p = (x * x + y * y).sqrt();
+    let lon = y.atan2(x);
+    let mut lat = z.atan2(p * (1.0 - e2));
+    let mut h = 0.0;
+    for _ in 0..10 {
+        let sin_lat = lat.sin();
+        let n = WGS84_A_KM / (1.0 - e2 * sin_lat * sin_lat).sqrt();
+        h = if lat.cos().abs() > 1e-10 { p / lat.cos() - n } else { z.abs() - n * (1.0 - e2) };
+        lat = z.atan2(p * (1.0 - e2 * n / (n + h)));
+    }
+    (lat.to_degrees(), lon.to_degrees(), h)
+}
+
+/// Ellipsoidal height from a geoid-referenced (orthometric) height and the geoid
+/// undulation N at the site: h = H + N. All in the same unit.
+pub fn geoid_to_ellipsoidal_height(orthometric: f64, undulation: f64) -> f64 {
+    orthometric + undulation
+}
+
+/// Unit line-of-sight vector in ECEF for an azimuth (from north, clockwise) and
+/// elevation (degrees) seen from a geodetic location.
+pub fn az_el_to_ecef_direction(lat_deg: f64, lon_deg: f64, az_deg: f64, el_deg: f64) -> [f64; 3] {
+    let (sin_lat, cos_lat) = lat_deg.to_radians().sin_cos();
+    let (sin_lon, cos_lon) = lon_deg.to_radians().sin_cos();
+    let (sin_az, cos_az) = az_deg.to_radians().sin_cos();
+    let (sin_el, cos_el) = el_deg.to_radians().sin_cos();
+    let east = cos_el * sin_az;
+    let north = cos_el * cos_az;
+    let up = sin_el;
+    [
+        -sin_lon * east - sin_lat * cos_lon * north + cos_lat * cos_lon * up,
+        cos_lon * east - sin_lat * sin_lon * north + cos_lat * sin_lon * up,
+        cos_lat * north + sin_lat * up,
+    ]
+}
+
+/// Topocentric right ascension and declination (degrees, equator and equinox
+/// of date) of an azimuth/elevation seen from a geodetic location at `t`.
+pub fn horizontal_to_equatorial(lat_deg: f64, lon_deg: f64, az_deg: f64, el_deg: f64, t: DateTime<Utc>) -> (f64, f64) {
+    let (sin_lat, cos_lat) = lat_deg.to_radians().sin_cos();
+    let (sin_az, cos_az) = az_deg.to_radians().sin_cos();
+    let (sin_el, cos_el) = el_deg.to_radians().sin_cos();
+    let dec = (sin_lat * sin_el + cos_lat * cos_el * cos_az).clamp(-1.0, 1.0).asin();
+    let hour_angle = (-sin_az * cos_el).atan2(cos_lat * sin_el - sin_lat * cos_el * cos_az);
+    let lst = gmst(t) + lon_deg.to_radians();
+    ((lst - hour_angle).to_degrees().rem_euclid(360.0), dec.to_degrees())
+}
+
+/// Precesses right ascension and declination (degrees) referred to the mean
+/// equator and equinox of `t` to J2000.0, using the IAU 1976 precession angles.
+/// Nutation (under 20″) is neglected.
+pub fn of_date_to_j2000(ra_deg: f64, dec_deg: f64, t: DateTime<Utc>) -> (f64, f64) {
+    let j2000 = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap().and_hms_opt(12, 0, 0).unwrap();
+    let c = (t.naive_utc() - j2000).num_milliseconds() as f64 / 86_400_000.0 / 36_525.0;
+    let arcsec = |a: f64, b: f64, d: f64| ((a + (b + d * c) * c) * c / 3600.0).to_radians();
+    let zeta = arcsec(2306.2181, 0.30188, 0.017998);
+    let z = arcsec(2306.2181, 1.09468, 0.018203);
+    let theta = arcsec(2004.3109, -0.42665, -0.041833);
+    let (sin_h, cos_h) = (ra_deg.to_radians() - z).sin_cos();
+    let (sin_d, cos_d) = dec_deg.to_radians().sin_cos();
+    let (sin_t, cos_t) = theta.sin_cos();
+    let a = cos_d * sin_h;
+    let b = cos_t * cos_d * cos_h + sin_t * sin_d;
+    let dec = (-sin_t * cos_d * cos_h + cos_t * sin_d).clamp(-1.0, 1.0).asin();
+    ((a.atan2(b) - zeta).to_degrees().rem_euclid(360.0), dec.to_degrees())
+}
+
+/// Sub-satellite geodetic latitude/longitude (degrees) of an ECI (TEME) position at time `t`.
+pub fn subsatellite_point(pos_eci_km: &[f64; 3], t: DateTime<Utc>) -> (f64, f64) {
+    let (x, y, z) = eci_to_ecef(pos_eci_km, gmst(t));
+    ecef_to_geodetic(x, y, z)
+}
+
+/// Atmospheric refraction (degrees) to add to a geometric elevation to get the
+/// apparent one, using Sæmundsson's inversion of Bennett's formula for standard
+/// conditions (10 °C, 1010 hPa). Below -1° the value at -1° is used.
+pub fn refraction_deg(true_el_deg: f64) -> f64 {
+    let h = true_el_deg.max(-1.0);
+    let arcmin = 1.02 / (h + 10.3 / (h + 5.11)).to_radians().tan();
+    arcmin.max(0.0) / 60.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ecef_to_geodetic_height, geodetic_to_ecef, gmst, horizontal_to_equatorial, of_date_to_j2000, refraction_deg};
+
+    #[test]
+    fn ecef_geodetic_round_trip_keeps_height() {
+        let [x, y, z] = geodetic_to_ecef(47.3769, 8.5417, 0.4083);
+        let (lat, lon, h) = ecef_to_geodetic_height(x, y, z);
+        assert!((lat - 47.3769).abs() < 1e-9);
+        assert!((lon - 8.5417).abs() < 1e-9);
+        assert!((h - 0.4083).abs() < 1e-9);
+    }
+
+    #[test]
+    fn horizontal_to_equatorial_at_zenith_and_meridian() {
+        let t = chrono::DateTime::parse_from_rfc3339("2024-03-20T22:00:00Z").unwrap().with_timezone(&chrono::Utc);
+        let lst = (gmst(t).to_degrees() + 8.5).rem_euclid(360.0);
+        // The zenith is at the site's latitude on the local meridian
+        let (ra, dec) = horizontal_to_equatorial(47.0, 8.5, 123.0, 90.0, t);
+        assert!((dec - 47.0).abs() < 1e-9 && (ra - lst).abs() < 1e-9);
+        // 30° above the southern horizon crosses the meridian at 47 - 60 = -13°
+        let (ra, dec) = horizontal_to_equatorial(47.0, 8.5, 180.0, 30.0, t);
+        assert!((dec + 13.0).abs() < 1e-9 && (ra - lst).abs() < 1e-9);
+        // Due east on the horizon is on the equator six hours east of the meridian
+        let (ra, dec) = horizontal_to_equatorial(47.0, 8.5, 90.0, 0.0, t);
+        assert!(dec.abs() < 1e-9 && ((ra - lst).rem_euclid(360.0) - 90.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn precession_to_j2000_matches_the_annual_rates() {
+        let t = chrono::DateTime::parse_from_rfc3339("2025-01-01T12:00:00Z").unwrap().with_timezone(&chrono::Utc);
+        // 25 years at 46.1″/yr in RA and 20.0″/yr in Dec at the equinox
+        let (ra, dec) = of_date_to_j2000(0.0, 0.0, t);
+        assert!((ra - (360.0 - 25.0 * 46.1 / 3600.0)).abs() < 0.005);
+        assert!((dec + 25.0 * 20.04 / 3600.0).abs() < 0.005);
+        let j2000 = chrono::DateTime::parse_from_rfc3339("2000-01-01T12:00:00Z").unwrap().with_timezone(&chrono::Utc);
+        let (ra, dec) = of_date_to_j2000(123.4, -56.7, j2000);
+        assert!((ra - 123.4).abs() < 1e-9 && (dec + 56.7).abs() < 1e-9);
+    }
+
+    #[test]
+    fn refraction_matches_standard_values() {
+        // About 29 arcmin at the horizon, 1 arcmin at 45°, nothing at zenith.
+        assert!((refraction_deg(0.0) * 60.0 - 28.9).abs() < 0.5);
+        assert!((refraction_deg(45.0) * 60.0 - 1.0).abs() < 0.05);
+        assert!(refraction_deg(90.0).abs() < 1e-3);
+    }
+}
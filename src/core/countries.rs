@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde_json::Value;
+use tracing::{info, warn};
+
+use crate::core::aoi::AoiGeometry;
+
+/// Environment variable naming the country boundaries file.
+const FILE_ENV: &str = "STFCM_COUNTRIES_FILE";
+const DEFAULT_FILE: &str = "data/countries.geojson";
+/// Feature properties that may hold an ISO 3166-1 code, as in Natural Earth
+/// and the common `datasets/geo-countries` files.
+const CODE_PROPERTIES: [&str; 8] = ["ISO_A2", "ISO_A3", "ISO_A2_EH", "ISO_A3_EH", "iso_a2", "iso_a3", "ISO3166-1-Alpha-2", "ISO3166-1-Alpha-3"];
+
+/// Country outlines by upper-case ISO 3166-1 alpha-2 and alpha-3 code.
+#[derive(Debug, Default)]
+pub struct CountryBoundaries {
+    by_code: HashMap<String, Arc<Vec<AoiGeometry>>>,
+}
+
+fn ring(value: &Value) -> Option<Vec<[f64; 2]>> {
+    value.as_array()?.iter().map(|p| Some([p.get(0)?.as_f64()?, p.get(1)?.as_f64()?])).collect()
+}
+
+fn polygon(value: &Value) -> Option<AoiGeometry> {
+    let rings: Vec<Vec<[f64; 2]>> = value.as_array()?.iter().map(ring).collect::<Option<_>>()?;
+    (!rings.is_empty()).then_some(AoiGeometry::Polygon(rings))
+}
+
+/// Polygons of a `Polygon` or `MultiPolygon` geometry. Unlike stored areas of
+/// interest these are not size-limited; outlines are expected to be split at
+/// the antimeridian, as published boundary datasets are.
+fn polygons(geometry: &Value) -> Option<Vec<AoiGeometry>> {
+    let coordinates = geometry.get("coordinates")?;
+    match geometry.get("type")?.as_str()? {
+        "Polygon" => Some(vec![polygon(coordinates)?]),
+        "MultiPolygon" => coordinates.as_array()?.iter().map(polygon).collect(),
+        _ => None,
+    }
+}
+
+impl CountryBoundaries {
+    /// Parses a GeoJSON FeatureCollection with one feature per country. Features
+    /// without a code or a polygon geometry are skipped.
+    pub fn from_geojson(value: &Value) -> Result<CountryBoundaries, String> {
+        let features = value.get("features").and_then(Value::as_array).ok_or("expected a FeatureCollection")?;
+        let mut by_code = HashMap::new();
+        for feature in features {
+            let Some(outline) = feature.get("geometry").and_then(polygons) else {
+                continue;
+            };
+            let outline = Arc::new(outline);
+            let properties = feature.get("properties");
+            for key in CODE_PROPERTIES {
+                let code = properties.and_then(|p| p.get(key)).and_then(Value::as_str).map(str::to_uppercase);
+                if let Some(code) = code.filter(|c| (2..=3).contains(&c.len()) && c.chars().all(|ch| ch.is_ascii_alphabetic())) {
+                    by_code.entry(code).or_insert_with(|| outline.clone());
+                }
+            }
+        }
+        Ok(CountryBoundaries { by_code })
+    }
+
+    /// Boundaries from `STFCM_COUNTRIES_FILE` (default `data/countries.geojson`);
+    /// empty when the file is missing or invalid (logged).
+    pub fn from_env() -> CountryBoundaries {
+        let path = std::env::var(FILE_ENV).map(PathBuf::from).unwrap_or_else(|_| PathBuf::from(DEFAULT_FILE));
+        let Ok(text) = std::fs::read_to_string(&path) else {
+            return CountryBoundaries::default();
+        };
+        match serde_json::from_str(&text).map_err(|e| e.to_string()).and_then(|v| CountryBoundaries::from_geojson(&v)) {
+            Ok(boundaries) => {
+                info!(path = %path.display(), codes = boundaries.by_code.len(), "Loaded country boundaries");
+                boundaries
+            }
+            Err(e) => {
+                warn!(path = %path.display(), error = %e, "Failed to load country boundaries");
+                CountryBoundaries::default()
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_code.is_empty()
+    }
+
+    /// Outline of a country by ISO 3166-1 alpha-2 or alpha-3 code, in any case.
+    pub fn get(&self, code: &str) -> Option<Arc<Vec<AoiGeometry>>> {
+        self.by_code.get(&code.to_uppercase()).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indexes_outlines_by_both_codes() {
+        let collection = serde_json::json!({
+            "type": "FeatureCollection",
+            "features": [
+                {
+                    "type": "Feature",
+                    "properties": {"ISO_A2": "-99", "ISO_A2_EH": "FR", "ISO_A3": "FRA"},
+                    "geometry": {"type": "MultiPolygon", "coordinates": [
+                        [[[-5.0, 43.0], [8.0, 43.0], [8.0, 51.0], [-5.0, 51.0], [-5.0, 43.0]]],
+                        [[[8.5, 41.4], [9.6, 41.4], [9.6, 43.0], [8.5, 43.0], [8.5, 41.4]]]
+                    ]}
+                },
+                {"type": "Feature", "properties": {"ISO_A2": "XX"}, "geometry": null}
+            ]
+        });
+        let countries = CountryBoundaries::from_geojson(&collection).unwrap();
+        let france = countries.get("fr").unwrap();
+        assert!(Arc::ptr_eq(&france, &countries.get("FRA").unwrap()));
+        // Paris, then Corsica, then Madrid
+        assert!(france.iter().any(|p| p.contains(48.9, 2.35)));
+        assert!(france.iter().any(|p| p.contains(42.0, 9.0)));
+        assert!(!france.iter().any(|p| p.contains(40.4, -3.7)));
+        assert!(countries.get("XX").is_none() && countries.get("-99").is_none());
+    }
+}
@@ -0,0 +1,62 @@
+use tracing::warn;
+
+use crate::utils::db::{self, CustomElements, DbError};
+
+/// First NORAD ID of the synthetic namespace given to user-defined element sets.
+/// It lies far above the Alpha-5 catalog range (up to 339999), so a custom
+/// satellite never shadows a catalogued one.
+pub const CUSTOM_ID_BASE: u64 = 900_000_000;
+
+/// NORAD ID under which the custom element set with row ID `id` is loaded.
+pub fn synthetic_id(id: i64) -> u64 {
+    CUSTOM_ID_BASE + id as u64
+}
+
+/// Elements of a stored custom satellite, renumbered into the synthetic namespace
+/// and renamed when the row has a name.
+pub fn to_elements(row: &CustomElements) -> Result<sgp4::Elements, String> {
+    let omm = row.omm.as_deref().map(serde_json::from_str).transpose().map_err(|e| format!("invalid stored OMM: {}", e))?;
+    let mut el = crate::core::tle::parse_tle_or_omm(row.tle.as_deref(), omm)?;
+    el.norad_id = synthetic_id(row.id);
+    if row.name.is_some() {
+        el.object_name = row.name.clone();
+    }
+    Ok(el)
+}
+
+/// Every stored custom element set that still parses; broken rows are skipped
+/// with a warning.
+pub fn load_custom_elements(conn: &rusqlite::Connection) -> Result<Vec<sgp4::Elements>, DbError> {
+    let rows = db::list_custom_elements(conn)?;
+    Ok(rows
+        .iter()
+        .filter_map(|row| match to_elements(row) {
+            Ok(el) => Some(el),
+            Err(e) => {
+                warn!(id = row.id, error = %e, "Skipping custom element set");
+                None
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn custom_rows_load_under_synthetic_ids() {
+        let row = CustomElements {
+            id: 7,
+            name: Some("CUBESAT-1".to_string()),
+            tle: Some(crate::testing::fixtures::ISS_TLE.to_string()),
+            omm: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+        };
+        let el = to_elements(&row).unwrap();
+        assert_eq!(el.norad_id, 900_000_007);
+        assert_eq!(el.object_name.as_deref(), Some("CUBESAT-1"));
+        assert!(to_elements(&CustomElements { tle: None, ..row }).is_err());
+    }
+}
@@ -1,2 +1,13 @@
 pub mod tle;
-pub mod orbit;
\ No newline at end of file
+pub mod orbit;
+pub mod coords;
+pub mod terrain;
+pub mod sun;
+pub mod clock;
+pub mod custom;
+pub mod overrides;
+pub mod bundle;
+pub mod screening;
+pub mod aoi;
+pub mod countries;
+pub mod catalog;
@@ -1,8 +1,137 @@
+use chrono::{DateTime, Utc};
 use tracing::debug;
 
+/// Earth gravitational parameter (km^3/s^2), WGS72 as used by SGP4.
+pub const MU_EARTH_KM3_S2: f64 = 398_600.8;
+/// Earth equatorial radius (km), WGS84.
+pub const EARTH_RADIUS_KM: f64 = 6378.137;
+/// Speed of light in vacuum (km/s).
+pub const SPEED_OF_LIGHT_KM_S: f64 = 299_792.458;
+/// Earth rotation rate (rad/s).
+pub const EARTH_ROTATION_RAD_S: f64 = 7.292_115e-5;
+
 /// Propagate elements by a given number of minutes using SGP4.
 pub fn propagate_minutes(elements: &sgp4::Elements, minutes: f64) -> Result<sgp4::Prediction, sgp4::Error> {
     let constants = sgp4::Constants::from_elements(elements)?;
     debug!(minutes, "Propagating elements");
     constants.propagate(minutes)
-}
\ No newline at end of file
+}
+
+/// Minutes elapsed between the element set epoch and `t` (negative before epoch).
+pub fn minutes_since_epoch(elements: &sgp4::Elements, t: DateTime<Utc>) -> f64 {
+    let diff = t.naive_utc() - elements.datetime;
+    diff.num_milliseconds() as f64 / 60_000.0
+}
+
+/// Semi-major axis (km) derived from the mean motion in revolutions per day.
+pub fn semi_major_axis_km(elements: &sgp4::Elements) -> f64 {
+    let n_rad_s = elements.mean_motion * 2.0 * std::f64::consts::PI / 86_400.0;
+    (MU_EARTH_KM3_S2 / (n_rad_s * n_rad_s)).cbrt()
+}
+
+/// Perigee and apogee radii (km, geocentric) from the mean elements.
+pub fn perigee_apogee_radius_km(elements: &sgp4::Elements) -> (f64, f64) {
+    let a = semi_major_axis_km(elements);
+    (a * (1.0 - elements.eccentricity), a * (1.0 + elements.eccentricity))
+}
+
+/// Decomposes `delta` into radial, in-track and cross-track (RIC) components of the
+/// frame defined by a reference position and velocity.
+pub fn ric_components(ref_pos: &[f64; 3], ref_vel: &[f64; 3], delta: &[f64; 3]) -> [f64; 3] {
+    let r_hat = normalize(ref_pos);
+    let c_hat = normalize(&cross(ref_pos, ref_vel));
+    let i_hat = cross(&c_hat, &r_hat);
+    [dot(delta, &r_hat), dot(delta, &i_hat), dot(delta, &c_hat)]
+}
+
+pub fn cross(a: &[f64; 3], b: &[f64; 3]) -> [f64; 3] {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+pub fn dot(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub fn normalize(v: &[f64; 3]) -> [f64; 3] {
+    let n = dot(v, v).sqrt();
+    [v[0] / n, v[1] / n, v[2] / n]
+}
+
+/// Classical orbital elements (angles in degrees) of a two-body state.
+#[derive(Debug, Clone, Copy)]
+pub struct OsculatingElements {
+    pub eccentricity: f64,
+    pub inclination_deg: f64,
+    pub raan_deg: f64,
+    pub arg_perigee_deg: f64,
+    pub mean_anomaly_deg: f64,
+    pub mean_motion_rev_day: f64,
+}
+
+/// Converts an inertial position (km) and velocity (km/s) into classical elements.
+/// Returns `None` for non-elliptic or degenerate states. For circular or equatorial
+/// orbits the undefined angles are set to zero and folded into the mean anomaly.
+pub fn osculating_elements(r: &[f64; 3], v: &[f64; 3]) -> Option<OsculatingElements> {
+    use std::f64::consts::TAU;
+    const EPS: f64 = 1e-9;
+
+    let mu = MU_EARTH_KM3_S2;
+    let r_mag = dot(r, r).sqrt();
+    let v2 = dot(v, v);
+    let energy = v2 / 2.0 - mu / r_mag;
+    if !energy.is_finite() || energy >= 0.0 || r_mag <= 0.0 {
+        return None;
+    }
+    let a = -mu / (2.0 * energy);
+
+    let h = cross(r, v);
+    let h_mag = dot(&h, &h).sqrt();
+    if h_mag < EPS {
+        return None;
+    }
+    let node = [-h[1], h[0], 0.0];
+    let node_mag = dot(&node, &node).sqrt();
+    let rv = dot(r, v);
+    let e_vec = [
+        ((v2 - mu / r_mag) * r[0] - rv * v[0]) / mu,
+        ((v2 - mu / r_mag) * r[1] - rv * v[1]) / mu,
+        ((v2 - mu / r_mag) * r[2] - rv * v[2]) / mu,
+    ];
+    let e = dot(&e_vec, &e_vec).sqrt();
+
+    let inc = (h[2] / h_mag).clamp(-1.0, 1.0).acos();
+    let raan = if node_mag > EPS {
+        let raan = (node[0] / node_mag).clamp(-1.0, 1.0).acos();
+        if node[1] < 0.0 { TAU - raan } else { raan }
+    } else {
+        0.0
+    };
+    // Reference direction for the perigee / anomaly: ascending node, or X axis when equatorial.
+    let reference = if node_mag > EPS { normalize(&node) } else { [1.0, 0.0, 0.0] };
+    let angle_from_reference = |vec: &[f64; 3]| {
+        let mag = dot(vec, vec).sqrt();
+        let ang = (dot(&reference, vec) / mag).clamp(-1.0, 1.0).acos();
+        // Measure in the orbit plane, in the direction of motion.
+        if dot(&cross(&reference, vec), &h) < 0.0 { TAU - ang } else { ang }
+    };
+    let (argp, nu) = if e > EPS {
+        let argp = angle_from_reference(&e_vec);
+        let nu = (dot(&e_vec, r) / (e * r_mag)).clamp(-1.0, 1.0).acos();
+        (argp, if rv < 0.0 { TAU - nu } else { nu })
+    } else {
+        (0.0, angle_from_reference(r))
+    };
+
+    let ecc_anomaly = 2.0 * (((1.0 - e) / (1.0 + e)).sqrt() * (nu / 2.0).tan()).atan();
+    let mean_anomaly = (ecc_anomaly - e * ecc_anomaly.sin()).rem_euclid(TAU);
+    let n_rad_s = (mu / a.powi(3)).sqrt();
+
+    Some(OsculatingElements {
+        eccentricity: e,
+        inclination_deg: inc.to_degrees(),
+        raan_deg: raan.to_degrees(),
+        arg_perigee_deg: argp.to_degrees(),
+        mean_anomaly_deg: mean_anomaly.to_degrees(),
+        mean_motion_rev_day: n_rad_s * 86_400.0 / TAU,
+    })
+}
@@ -0,0 +1,89 @@
+use chrono::{DateTime, Utc};
+use tracing::warn;
+
+use crate::core::orbit::osculating_elements;
+use crate::core::tle::MeanElements;
+use crate::utils::db::{self, DbError, ElementOverride};
+
+/// Mean elements approximating a TEME state (km, km/s) at `epoch`, e.g. an
+/// operator-provided state vector. As for IOD the two-body osculating elements
+/// stand in for mean elements, so the result is only good near the epoch.
+pub fn elements_from_state(norad_id: u64, epoch: DateTime<Utc>, position: &[f64; 3], velocity: &[f64; 3]) -> Option<MeanElements> {
+    let osc = osculating_elements(position, velocity)?;
+    Some(MeanElements {
+        norad_id,
+        epoch: epoch.naive_utc(),
+        inclination_deg: osc.inclination_deg,
+        raan_deg: osc.raan_deg,
+        eccentricity: osc.eccentricity,
+        arg_perigee_deg: osc.arg_perigee_deg,
+        mean_anomaly_deg: osc.mean_anomaly_deg,
+        mean_motion_rev_day: osc.mean_motion_rev_day,
+        bstar: 0.0,
+    })
+}
+
+/// Elements of an override version, under the NORAD ID of the satellite it
+/// replaces (TLE lines only hold five digits).
+pub fn to_elements(o: &ElementOverride) -> Result<sgp4::Elements, String> {
+    let omm = o.omm.as_deref().map(serde_json::from_str).transpose().map_err(|e| format!("invalid stored OMM: {}", e))?;
+    let mut el = crate::core::tle::parse_tle_or_omm(o.tle.as_deref(), omm)?;
+    el.norad_id = o.norad_id;
+    Ok(el)
+}
+
+/// Replaces the elements of every satellite with an override in effect; ones
+/// missing from the catalog are added. Returns the number applied.
+pub fn apply_overrides(conn: &rusqlite::Connection, elements: &mut Vec<sgp4::Elements>) -> Result<usize, DbError> {
+    let mut applied = 0;
+    for o in db::active_element_overrides(conn)? {
+        let mut el = match to_elements(&o) {
+            Ok(el) => el,
+            Err(e) => {
+                warn!(norad_id = o.norad_id, id = o.id, error = %e, "Skipping element override");
+                continue;
+            }
+        };
+        match elements.iter_mut().find(|e| e.norad_id == o.norad_id) {
+            Some(current) => {
+                if el.object_name.is_none() {
+                    el.object_name = current.object_name.clone();
+                }
+                *current = el;
+            }
+            None => elements.push(el),
+        }
+        applied += 1;
+    }
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_vector_becomes_an_element_set() {
+        let el = crate::testing::fixtures::catalog().swap_remove(0);
+        let epoch = el.datetime.and_utc();
+        let pred = crate::core::orbit::propagate_minutes(&el, 0.0).unwrap();
+        let mean = elements_from_state(25544, epoch, &pred.position, &pred.velocity).unwrap();
+        let (line1, line2) = mean.to_tle_lines();
+        let o = ElementOverride {
+            id: 1,
+            norad_id: 25544,
+            source: "state".to_string(),
+            tle: Some(format!("{}\n{}", line1, line2)),
+            omm: None,
+            reason: None,
+            created_at: String::new(),
+            reverted_at: None,
+        };
+        let fitted = to_elements(&o).unwrap();
+        let check = crate::core::orbit::propagate_minutes(&fitted, 0.0).unwrap();
+        let miss: f64 = (0..3).map(|i| (check.position[i] - pred.position[i]).powi(2)).sum::<f64>().sqrt();
+        // Osculating elements differ from SGP4 mean elements by short-period terms
+        assert!(miss < 50.0, "{} km", miss);
+        assert!((fitted.inclination - el.inclination).abs() < 0.2);
+    }
+}
@@ -0,0 +1,173 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tracing::{info, warn};
+
+use crate::core::orbit::{perigee_apogee_radius_km, EARTH_RADIUS_KM};
+
+const MAX_AGE_ENV: &str = "STFCM_MAX_ELEMENT_AGE_DAYS";
+const MIN_PERIGEE_ENV: &str = "STFCM_MIN_PERIGEE_KM";
+const MODE_ENV: &str = "STFCM_ELEMENT_SCREENING";
+
+/// What happens to an element set that fails a configurable limit. Element sets
+/// that cannot be propagated at all are always rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScreeningMode {
+    Reject,
+    /// Keep it in the catalog and only report it.
+    Flag,
+}
+
+/// Limits an element set must meet to enter the catalog.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ScreeningLimits {
+    pub max_age_days: f64,
+    /// Lowest perigee altitude (km) above the equatorial radius; below it the
+    /// object has decayed or is about to.
+    pub min_perigee_km: f64,
+    pub mode: ScreeningMode,
+}
+
+impl Default for ScreeningLimits {
+    fn default() -> Self {
+        ScreeningLimits { max_age_days: 30.0, min_perigee_km: 100.0, mode: ScreeningMode::Reject }
+    }
+}
+
+impl ScreeningLimits {
+    /// Limits from `STFCM_MAX_ELEMENT_AGE_DAYS`, `STFCM_MIN_PERIGEE_KM` and
+    /// `STFCM_ELEMENT_SCREENING=reject|flag`; invalid values keep the default.
+    pub fn from_env() -> ScreeningLimits {
+        let mut limits = ScreeningLimits::default();
+        let number = |name: &str| {
+            let value = std::env::var(name).ok()?;
+            let parsed = value.parse::<f64>().ok().filter(|v| v.is_finite() && *v >= 0.0);
+            if parsed.is_none() {
+                warn!(name, value = value.as_str(), "Ignoring invalid screening limit");
+            }
+            parsed
+        };
+        if let Some(days) = number(MAX_AGE_ENV) {
+            limits.max_age_days = days;
+        }
+        if let Some(km) = number(MIN_PERIGEE_ENV) {
+            limits.min_perigee_km = km;
+        }
+        match std::env::var(MODE_ENV).ok().as_deref() {
+            None | Some("reject") => {}
+            Some("flag") => limits.mode = ScreeningMode::Flag,
+            Some(other) => warn!(value = other, "Ignoring invalid {}; use reject or flag", MODE_ENV),
+        }
+        limits
+    }
+}
+
+/// Why an element set was screened out.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ScreeningIssue {
+    /// Eccentricity of 1 or more: not a closed orbit SGP4 can propagate.
+    Unbound { eccentricity: f64 },
+    NonPositiveMeanMotion { mean_motion: f64 },
+    /// Mean motion and eccentricity put the perigee below the limit.
+    Decayed { perigee_km: f64 },
+    Stale { age_days: f64 },
+}
+
+impl ScreeningIssue {
+    /// Whether the element set is unusable regardless of the screening mode.
+    pub fn unpropagatable(self) -> bool {
+        matches!(self, ScreeningIssue::Unbound { .. } | ScreeningIssue::NonPositiveMeanMotion { .. })
+    }
+}
+
+impl std::fmt::Display for ScreeningIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScreeningIssue::Unbound { eccentricity } => write!(f, "eccentricity {:.4} is not below 1", eccentricity),
+            ScreeningIssue::NonPositiveMeanMotion { mean_motion } => write!(f, "mean motion {} rev/day is not positive", mean_motion),
+            ScreeningIssue::Decayed { perigee_km } => write!(f, "perigee altitude {:.0} km implies decay", perigee_km),
+            ScreeningIssue::Stale { age_days } => write!(f, "epoch is {:.1} days old", age_days),
+        }
+    }
+}
+
+/// First issue found with an element set, the unpropagatable ones first.
+pub fn check(el: &sgp4::Elements, limits: &ScreeningLimits, now: DateTime<Utc>) -> Option<ScreeningIssue> {
+    if el.eccentricity.is_nan() || el.eccentricity >= 1.0 {
+        return Some(ScreeningIssue::Unbound { eccentricity: el.eccentricity });
+    }
+    if el.mean_motion.is_nan() || el.mean_motion <= 0.0 {
+        return Some(ScreeningIssue::NonPositiveMeanMotion { mean_motion: el.mean_motion });
+    }
+    let perigee_km = perigee_apogee_radius_km(el).0 - EARTH_RADIUS_KM;
+    if perigee_km < limits.min_perigee_km {
+        return Some(ScreeningIssue::Decayed { perigee_km });
+    }
+    let age_days = (now - el.datetime.and_utc()).num_seconds() as f64 / 86_400.0;
+    (age_days > limits.max_age_days).then_some(ScreeningIssue::Stale { age_days })
+}
+
+/// An element set that failed screening.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScreenedElement {
+    pub norad_id: u64,
+    pub name: Option<String>,
+    pub epoch: DateTime<Utc>,
+    pub issue: ScreeningIssue,
+    /// Kept in the catalog because screening only flags.
+    pub kept: bool,
+}
+
+/// Removes the element sets that fail the limits (only the unpropagatable ones
+/// in flag mode) and reports every failure.
+pub fn screen(elements: &mut Vec<sgp4::Elements>, limits: &ScreeningLimits, now: DateTime<Utc>) -> Vec<ScreenedElement> {
+    let mut report = Vec::new();
+    elements.retain(|el| {
+        let Some(issue) = check(el, limits, now) else {
+            return true;
+        };
+        let kept = limits.mode == ScreeningMode::Flag && !issue.unpropagatable();
+        report.push(ScreenedElement { norad_id: el.norad_id, name: el.object_name.clone(), epoch: el.datetime.and_utc(), issue, kept });
+        kept
+    });
+    if !report.is_empty() {
+        let rejected = report.iter().filter(|s| !s.kept).count();
+        info!(rejected, flagged = report.len() - rejected, "Screened element sets");
+        for s in report.iter().filter(|s| !s.kept) {
+            warn!(norad_id = s.norad_id, reason = %s.issue, "Skipping element set");
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn iss() -> sgp4::Elements {
+        crate::testing::fixtures::catalog().swap_remove(0)
+    }
+
+    #[test]
+    fn rejects_stale_decayed_and_unbound_element_sets() {
+        let epoch = iss().datetime.and_utc();
+        let limits = ScreeningLimits::default();
+        assert_eq!(check(&iss(), &limits, epoch + chrono::Duration::days(2)), None);
+        assert!(matches!(check(&iss(), &limits, epoch + chrono::Duration::days(45)), Some(ScreeningIssue::Stale { .. })));
+
+        let mut decaying = iss();
+        decaying.mean_motion = 17.0;
+        let mut unbound = iss();
+        unbound.eccentricity = 1.2;
+        let mut elements = vec![iss(), decaying, unbound];
+        let flag = ScreeningLimits { mode: ScreeningMode::Flag, ..limits };
+        let report = screen(&mut elements, &flag, epoch);
+        assert_eq!(elements.len(), 2);
+        assert!(matches!(report[0].issue, ScreeningIssue::Decayed { .. }) && report[0].kept);
+        assert!(report[1].issue.unpropagatable() && !report[1].kept);
+
+        screen(&mut elements, &limits, epoch);
+        assert_eq!(elements.len(), 1);
+    }
+}
@@ -0,0 +1,66 @@
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::core::coords::{eci_to_ecef, gmst};
+use crate::core::orbit::EARTH_RADIUS_KM;
+
+/// Astronomical unit (km).
+pub const AU_KM: f64 = 149_597_870.7;
+
+/// Days since J2000.0 (2000-01-01 12:00 UTC).
+fn days_since_j2000(t: DateTime<Utc>) -> f64 {
+    let j2000 = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap().and_hms_opt(12, 0, 0).unwrap();
+    (t.naive_utc() - j2000).num_milliseconds() as f64 / 86_400_000.0
+}
+
+/// Geocentric Sun position in the equatorial inertial frame (km), using the
+/// Astronomical Almanac low-precision formulae (about 0.01° over 1950-2050).
+pub fn sun_position_eci(t: DateTime<Utc>) -> [f64; 3] {
+    let n = days_since_j2000(t);
+    let l = (280.460 + 0.985_647_4 * n).to_radians();
+    let g = (357.528 + 0.985_600_3 * n).to_radians();
+    let lambda = l + (1.915 * g.sin() + 0.020 * (2.0 * g).sin()).to_radians();
+    let eps = (23.439 - 0.000_000_4 * n).to_radians();
+    let r = (1.000_14 - 0.016_71 * g.cos() - 0.000_14 * (2.0 * g).cos()) * AU_KM;
+    [r * lambda.cos(), r * eps.cos() * lambda.sin(), r * eps.sin() * lambda.sin()]
+}
+
+/// Elevation of the Sun's centre (degrees, geometric) above the horizon at a
+/// geodetic location.
+pub fn sun_elevation_deg(t: DateTime<Utc>, lat_deg: f64, lon_deg: f64) -> f64 {
+    let (x, y, z) = eci_to_ecef(&sun_position_eci(t), gmst(t));
+    let r = (x * x + y * y + z * z).sqrt();
+    let (sin_lat, cos_lat) = lat_deg.to_radians().sin_cos();
+    let (sin_lon, cos_lon) = lon_deg.to_radians().sin_cos();
+    let up = (cos_lat * cos_lon * x + cos_lat * sin_lon * y + sin_lat * z) / r;
+    up.asin().to_degrees()
+}
+
+/// Distance (km) of an inertial position outside the Earth's shadow, taken as a
+/// cylinder of Earth radius pointing away from the Sun; negative inside it.
+/// Positions on the day side return their distance from the shadow axis.
+pub fn shadow_margin_km(position: &[f64; 3], t: DateTime<Utc>) -> f64 {
+    let sun = sun_position_eci(t);
+    let r = (sun[0] * sun[0] + sun[1] * sun[1] + sun[2] * sun[2]).sqrt();
+    let along = (position[0] * sun[0] + position[1] * sun[1] + position[2] * sun[2]) / r;
+    let norm2 = position[0] * position[0] + position[1] * position[1] + position[2] * position[2];
+    let off_axis = (norm2 - along * along).max(0.0).sqrt();
+    if along >= 0.0 {
+        off_axis.max(EARTH_RADIUS_KM)
+    } else {
+        off_axis - EARTH_RADIUS_KM
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn sun_is_high_at_noon_and_down_at_midnight_on_the_equinox() {
+        let noon = Utc.with_ymd_and_hms(2024, 3, 20, 12, 0, 0).unwrap();
+        assert!(sun_elevation_deg(noon, 0.0, 0.0) > 85.0);
+        let midnight = Utc.with_ymd_and_hms(2024, 3, 20, 0, 0, 0).unwrap();
+        assert!(sun_elevation_deg(midnight, 0.0, 0.0) < -85.0);
+    }
+}
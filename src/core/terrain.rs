@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::core::orbit::EARTH_RADIUS_KM;
+
+/// SRTM void marker.
+const VOID: i16 = -32768;
+/// Terrain further away than this is ignored when building a horizon mask.
+const MAX_RANGE_M: f64 = 50_000.0;
+/// Effective Earth radius factor for standard atmospheric refraction (k = 4/3).
+const REFRACTION_K: f64 = 4.0 / 3.0;
+
+/// Directory holding SRTM `.hgt` tiles (`STFCM_DEM_DIR`, default `data/dem`).
+pub fn dem_dir() -> PathBuf {
+    std::env::var("STFCM_DEM_DIR").map(PathBuf::from).unwrap_or_else(|_| PathBuf::from("data/dem"))
+}
+
+struct Tile {
+    /// Samples per side (1201 for SRTM3, 3601 for SRTM1).
+    size: usize,
+    /// Row-major heights in metres, first row at the northern edge.
+    heights: Vec<i16>,
+}
+
+/// Lazily loaded SRTM height tiles. Missing tiles are remembered so the disk is
+/// only probed once per tile.
+pub struct Dem {
+    dir: PathBuf,
+    tiles: HashMap<(i32, i32), Option<Tile>>,
+}
+
+impl Dem {
+    pub fn open(dir: impl AsRef<Path>) -> Self {
+        Dem { dir: dir.as_ref().to_path_buf(), tiles: HashMap::new() }
+    }
+
+    /// Terrain height (m) at a point, bilinearly interpolated; `None` outside the
+    /// loaded tiles or on voids.
+    pub fn elevation_m(&mut self, lat_deg: f64, lon_deg: f64) -> Option<f64> {
+        let lon_deg = (lon_deg + 540.0).rem_euclid(360.0) - 180.0;
+        let key = (lat_deg.floor() as i32, lon_deg.floor() as i32);
+        let dir = &self.dir;
+        let tile = self.tiles.entry(key).or_insert_with(|| load_tile(dir, key.0, key.1)).as_ref()?;
+
+        let n = (tile.size - 1) as f64;
+        let row = (key.0 as f64 + 1.0 - lat_deg) * n;
+        let col = (lon_deg - key.1 as f64) * n;
+        let (r0, c0) = (row.floor() as usize, col.floor() as usize);
+        let (r1, c1) = ((r0 + 1).min(tile.size - 1), (c0 + 1).min(tile.size - 1));
+        let (fr, fc) = (row - r0 as f64, col - c0 as f64);
+        let h = |r: usize, c: usize| {
+            let v = tile.heights[r * tile.size + c];
+            (v != VOID).then_some(v as f64)
+        };
+        let top = h(r0, c0)? * (1.0 - fc) + h(r0, c1)? * fc;
+        let bottom = h(r1, c0)? * (1.0 - fc) + h(r1, c1)? * fc;
+        Some(top * (1.0 - fr) + bottom * fr)
+    }
+}
+
+/// `N46E007.hgt` style tile name for the tile whose south-west corner is (lat, lon).
+fn tile_name(lat: i32, lon: i32) -> String {
+    format!(
+        "{}{:02}{}{:03}.hgt",
+        if lat < 0 { 'S' } else { 'N' },
+        lat.abs(),
+        if lon < 0 { 'W' } else { 'E' },
+        lon.abs()
+    )
+}
+
+fn load_tile(dir: &Path, lat: i32, lon: i32) -> Option<Tile> {
+    let bytes = std::fs::read(dir.join(tile_name(lat, lon))).ok()?;
+    let samples = bytes.len() / 2;
+    let size = (samples as f64).sqrt() as usize;
+    if size < 2 || size * size != samples {
+        tracing::warn!("Ignoring DEM tile {} with unexpected size", tile_name(lat, lon));
+        return None;
+    }
+    let heights = bytes.chunks_exact(2).map(|b| i16::from_be_bytes([b[0], b[1]])).collect();
+    Some(Tile { size, heights })
+}
+
+/// Terrain elevation angle of the local horizon per azimuth bin (1° when computed from a DEM).
+#[derive(Debug, Clone)]
+pub struct HorizonMask {
+    bins: Vec<f64>,
+}
+
+impl HorizonMask {
+    /// Wraps a stored profile of equally sized azimuth bins starting at north.
+    pub fn from_bins(bins: Vec<f64>) -> Option<Self> {
+        (!bins.is_empty()).then_some(HorizonMask { bins })
+    }
+
+    pub fn bins(&self) -> &[f64] {
+        &self.bins
+    }
+
+    /// Horizon elevation (degrees) towards an azimuth (degrees from north).
+    pub fn elevation_at(&self, az_deg: f64) -> f64 {
+        let width = 360.0 / self.bins.len() as f64;
+        let idx = ((az_deg.rem_euclid(360.0) / width).round() as usize) % self.bins.len();
+        self.bins[idx]
+    }
+}
+
+/// Builds the terrain horizon seen from a station by marching outwards along each
+/// azimuth and keeping the highest terrain elevation angle, with Earth curvature
+/// and standard refraction. Returns `None` when the DEM does not cover the station.
+pub fn horizon_mask(dem: &mut Dem, lat_deg: f64, lon_deg: f64) -> Option<HorizonMask> {
+    let h0 = dem.elevation_m(lat_deg, lon_deg)?;
+    let effective_radius_m = EARTH_RADIUS_KM * 1000.0 * REFRACTION_K;
+    let (sin_lat, cos_lat) = lat_deg.to_radians().sin_cos();
+
+    let bins = (0..360)
+        .map(|az| {
+            let (sin_az, cos_az) = (az as f64).to_radians().sin_cos();
+            let mut best = f64::NEG_INFINITY;
+            let mut d = 100.0;
+            while d <= MAX_RANGE_M {
+                // Destination point on a sphere.
+                let delta = d / (EARTH_RADIUS_KM * 1000.0);
+                let (sin_d, cos_d) = delta.sin_cos();
+                let lat2 = (sin_lat * cos_d + cos_lat * sin_d * cos_az).asin();
+                let lon2 = lon_deg.to_radians() + (sin_az * sin_d * cos_lat).atan2(cos_d - sin_lat * lat2.sin());
+                if let Some(h) = dem.elevation_m(lat2.to_degrees(), lon2.to_degrees()) {
+                    let drop = d * d / (2.0 * effective_radius_m);
+                    best = best.max(((h - h0 - drop) / d).atan().to_degrees());
+                }
+                d += if d < 2_000.0 { 100.0 } else { 250.0 };
+            }
+            if best.is_finite() { best } else { 0.0 }
+        })
+        .collect();
+    Some(HorizonMask { bins })
+}
+
+/// Loads the horizon mask for a station from the configured DEM directory, if any
+/// tile covers it.
+pub fn station_horizon(lat_deg: f64, lon_deg: f64) -> Option<HorizonMask> {
+    let dir = dem_dir();
+    if !dir.is_dir() {
+        return None;
+    }
+    horizon_mask(&mut Dem::open(dir), lat_deg, lon_deg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tile_names_follow_srtm_convention() {
+        assert_eq!(tile_name(46, 7), "N46E007.hgt");
+        assert_eq!(tile_name(-1, -1), "S01W001.hgt");
+    }
+
+    #[test]
+    fn slope_raises_horizon_uphill_only() {
+        // Terrain rising 1 m per sample (~90 m) towards the east.
+        let size = 1201;
+        let heights = (0..size * size).map(|i| (i % size) as i16).collect();
+        let mut dem = Dem::open("/nonexistent");
+        dem.tiles.insert((46, 7), Some(Tile { size, heights }));
+        let mask = horizon_mask(&mut dem, 46.5, 7.5).unwrap();
+        assert!(mask.elevation_at(90.0) > 0.4, "east {}", mask.elevation_at(90.0));
+        assert!(mask.elevation_at(270.0) < 0.0, "west {}", mask.elevation_at(270.0));
+    }
+}
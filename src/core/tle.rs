@@ -1,6 +1,8 @@
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use tracing::{debug, info, warn};
 
@@ -12,28 +14,557 @@ pub enum TleParseError {
     InvalidPair { line: usize },
     #[error("sgp4 parse error: {0}")]
     Sgp4(#[from] sgp4::Error),
+    #[error("invalid OMM document: {0}")]
+    Omm(String),
 }
 
-/// Parses a TLE file into a vector of `sgp4::Elements`.
+/// Raw TLE record as it appeared in the source file.
+#[derive(Debug, Clone)]
+pub struct TleRecord {
+    pub name: Option<String>,
+    pub line1: String,
+    pub line2: String,
+}
+
+impl TleRecord {
+    pub fn to_elements(&self) -> Result<sgp4::Elements, sgp4::Error> {
+        sgp4::Elements::from_tle(self.name.clone(), self.line1.as_bytes(), self.line2.as_bytes())
+    }
+}
+
+/// A TLE entry that was skipped, with the (1-based) source line it starts on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RejectedTle {
+    pub line: usize,
+    pub reason: String,
+}
+
+/// Outcome of a partial parse: the usable records with their elements (same
+/// order) and everything that was rejected.
+#[derive(Default)]
+pub struct TleParseReport {
+    pub records: Vec<TleRecord>,
+    pub elements: Vec<sgp4::Elements>,
+    pub rejected: Vec<RejectedTle>,
+}
+
+/// Layout of a GP element set document, named as Celestrak's `FORMAT`
+/// parameter names it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GpFormat {
+    /// Line pairs, each optionally after a bare name line.
+    #[default]
+    Tle,
+    /// Line pairs after name lines starting with `0 `.
+    #[serde(rename = "3le")]
+    ThreeLe,
+    /// OMM fields as CSV under a header row.
+    Csv,
+    /// OMM objects as a JSON array.
+    Json,
+    /// OMM messages in a CCSDS NDM/XML document.
+    Xml,
+}
+
+impl GpFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GpFormat::Tle => "tle",
+            GpFormat::ThreeLe => "3le",
+            GpFormat::Csv => "csv",
+            GpFormat::Json => "json",
+            GpFormat::Xml => "xml",
+        }
+    }
+
+    /// Tells the format of a document from its first characters.
+    pub fn detect(content: &str) -> GpFormat {
+        let head = content.trim_start_matches('\u{feff}').trim_start();
+        let first_line = head.lines().next().unwrap_or_default();
+        match head.chars().next() {
+            Some('[' | '{') => GpFormat::Json,
+            Some('<') => GpFormat::Xml,
+            _ if first_line.contains(',') && first_line.to_ascii_uppercase().contains("NORAD_CAT_ID") => GpFormat::Csv,
+            _ if first_line.starts_with("0 ") => GpFormat::ThreeLe,
+            _ => GpFormat::Tle,
+        }
+    }
+}
+
+impl std::str::FromStr for GpFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<GpFormat, String> {
+        [GpFormat::Tle, GpFormat::ThreeLe, GpFormat::Csv, GpFormat::Json, GpFormat::Xml]
+            .into_iter()
+            .find(|f| f.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| format!("unknown GP format {:?}; use tle, 3le, csv, json or xml", s))
+    }
+}
+
+/// Parses an element set given either as TLE text (two lines, or three with a
+/// name line) or as a CCSDS OMM object in Celestrak's JSON layout. The message
+/// is meant for API clients.
+pub fn parse_tle_or_omm(tle: Option<&str>, omm: Option<serde_json::Value>) -> Result<sgp4::Elements, String> {
+    match (tle, omm) {
+        (Some(tle), None) => {
+            let record = parse_tle_records(tle).into_iter().next().ok_or("no TLE record in tle")?;
+            record.to_elements().map_err(|e| format!("invalid TLE: {}", e))
+        }
+        (None, Some(serde_json::Value::Object(omm))) => omm_elements(omm).map_err(|e| format!("invalid OMM: {}", e)),
+        (None, Some(_)) => Err("invalid OMM: expected an object".to_string()),
+        _ => Err("give exactly one of tle or omm".to_string()),
+    }
+}
+
+/// The element set as a CCSDS OMM object in Celestrak's JSON layout, as read
+/// back by [`parse_tle_or_omm`].
+pub fn to_omm(el: &sgp4::Elements) -> serde_json::Value {
+    let classification = match el.classification {
+        sgp4::Classification::Unclassified => "U",
+        sgp4::Classification::Classified => "C",
+        sgp4::Classification::Secret => "S",
+    };
+    serde_json::json!({
+        "OBJECT_NAME": el.object_name,
+        "OBJECT_ID": el.international_designator,
+        "EPOCH": el.datetime.format("%Y-%m-%dT%H:%M:%S%.6f").to_string(),
+        "MEAN_MOTION": el.mean_motion,
+        "ECCENTRICITY": el.eccentricity,
+        "INCLINATION": el.inclination,
+        "RA_OF_ASC_NODE": el.right_ascension,
+        "ARG_OF_PERICENTER": el.argument_of_perigee,
+        "MEAN_ANOMALY": el.mean_anomaly,
+        "EPHEMERIS_TYPE": el.ephemeris_type,
+        "CLASSIFICATION_TYPE": classification,
+        "NORAD_CAT_ID": el.norad_id,
+        "ELEMENT_SET_NO": el.element_set_number,
+        "REV_AT_EPOCH": el.revolution_number,
+        "BSTAR": el.drag_term,
+        "MEAN_MOTION_DOT": el.mean_motion_dot,
+        "MEAN_MOTION_DDOT": el.mean_motion_ddot,
+    })
+}
+
+/// OMM keys holding numbers, the integers first. Space-Track's JSON and every
+/// XML document give them as strings.
+const OMM_NUMERIC_KEYS: [&str; 13] = [
+    "NORAD_CAT_ID",
+    "ELEMENT_SET_NO",
+    "REV_AT_EPOCH",
+    "EPHEMERIS_TYPE",
+    "MEAN_MOTION",
+    "ECCENTRICITY",
+    "INCLINATION",
+    "RA_OF_ASC_NODE",
+    "ARG_OF_PERICENTER",
+    "MEAN_ANOMALY",
+    "BSTAR",
+    "MEAN_MOTION_DOT",
+    "MEAN_MOTION_DDOT",
+];
+/// Number of integer keys at the start of [`OMM_NUMERIC_KEYS`].
+const OMM_INTEGER_KEYS: usize = 4;
+/// The other OMM keys the elements are read from.
+const OMM_TEXT_KEYS: [&str; 4] = ["OBJECT_NAME", "OBJECT_ID", "EPOCH", "CLASSIFICATION_TYPE"];
+
+fn is_omm_key(key: &str) -> bool {
+    OMM_TEXT_KEYS.contains(&key) || OMM_NUMERIC_KEYS.contains(&key)
+}
+
+/// Elements of one OMM object, with numbers given as strings converted first.
+fn omm_elements(mut omm: serde_json::Map<String, serde_json::Value>) -> Result<sgp4::Elements, String> {
+    for (i, key) in OMM_NUMERIC_KEYS.into_iter().enumerate() {
+        let Some(serde_json::Value::String(text)) = omm.get(key) else { continue };
+        // Not JSON numbers: XML writes e.g. `.0006703` and `-.11606E-4`
+        let number = if i < OMM_INTEGER_KEYS {
+            text.trim().parse::<u64>().ok().map(serde_json::Number::from)
+        } else {
+            text.trim().parse::<f64>().ok().and_then(serde_json::Number::from_f64)
+        };
+        let number = number.ok_or_else(|| format!("{} is not a number: {:?}", key, text))?;
+        omm.insert(key.to_string(), serde_json::Value::Number(number));
+    }
+    serde_json::from_value(serde_json::Value::Object(omm)).map_err(|e| e.to_string())
+}
+
+/// Elements of each entry of an OMM document with the (1-based) position it
+/// starts at: the source line, or the object's number in a JSON array.
+type OmmEntries = Vec<(usize, Result<sgp4::Elements, String>)>;
+
+/// Keeps the entries that yield elements and logs the others.
+fn collect_omm(entries: OmmEntries) -> Vec<sgp4::Elements> {
+    let mut elements = Vec::with_capacity(entries.len());
+    for (position, entry) in entries {
+        match entry {
+            Ok(el) => elements.push(el),
+            Err(reason) => warn!(position, reason = %reason, "Skipping OMM entry"),
+        }
+    }
+    info!(count = elements.len(), "Parsed OMM elements");
+    elements
+}
+
+/// Parses the OMM objects of a JSON array (or a single object), as served by
+/// Celestrak and Space-Track with `FORMAT=json`. Objects that do not yield
+/// elements are skipped with a warning; only a malformed document fails.
+pub fn parse_omm_json(content: &str) -> Result<Vec<sgp4::Elements>, TleParseError> {
+    Ok(collect_omm(omm_json_entries(content)?))
+}
+
+fn omm_json_entries(content: &str) -> Result<OmmEntries, TleParseError> {
+    let objects = match serde_json::from_str(content).map_err(|e| TleParseError::Omm(e.to_string()))? {
+        serde_json::Value::Array(values) => values,
+        object @ serde_json::Value::Object(_) => vec![object],
+        _ => return Err(TleParseError::Omm("expected an array of OMM objects".to_string())),
+    };
+    Ok(objects
+        .into_iter()
+        .enumerate()
+        .map(|(index, value)| {
+            let entry = match value {
+                serde_json::Value::Object(omm) => omm_elements(omm),
+                _ => Err("not an object".to_string()),
+            };
+            (index + 1, entry)
+        })
+        .collect())
+}
+
+/// Parses the `<omm>` messages of a CCSDS NDM/XML document, as served by
+/// Celestrak with `FORMAT=xml` and Space-Track with `format/xml`. Only the
+/// keys the elements need are read; like [`parse_omm_json`], messages that do
+/// not yield elements are skipped with a warning. This is a minimal reader for
+/// that layout, not a full XML parser.
+pub fn parse_omm_xml(content: &str) -> Result<Vec<sgp4::Elements>, TleParseError> {
+    Ok(collect_omm(omm_xml_entries(content)?))
+}
+
+fn omm_xml_entries(content: &str) -> Result<OmmEntries, TleParseError> {
+    let mut entries = Vec::new();
+    let mut offset = 0;
+    while let Some(start) = find_start_tag(&content[offset..], "omm") {
+        let line = content[..offset + start].matches('\n').count() + 1;
+        let body = &content[offset + start..];
+        let end = body.find("</omm>").ok_or_else(|| TleParseError::Omm(format!("<omm> on line {} is not closed", line)))?;
+        let message = &body[..end];
+        offset += start + end + "</omm>".len();
+        let mut omm = serde_json::Map::new();
+        for key in OMM_TEXT_KEYS.iter().chain(OMM_NUMERIC_KEYS.iter()) {
+            if let Some(text) = xml_element_text(message, key) {
+                omm.insert(key.to_string(), serde_json::Value::String(xml_unescape(text)));
+            }
+        }
+        entries.push((line, omm_elements(omm)));
+    }
+    if entries.is_empty() {
+        return Err(TleParseError::Omm("no <omm> messages found".to_string()));
+    }
+    Ok(entries)
+}
+
+/// Parses OMM fields written as CSV with a header row naming them, as served
+/// by Celestrak and Space-Track with `FORMAT=csv`. Rows that do not yield
+/// elements are skipped with a warning, like [`parse_omm_json`].
+pub fn parse_omm_csv(content: &str) -> Result<Vec<sgp4::Elements>, TleParseError> {
+    Ok(collect_omm(omm_csv_entries(content)?))
+}
+
+fn omm_csv_entries(content: &str) -> Result<OmmEntries, TleParseError> {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).trim(csv::Trim::All).from_reader(content.as_bytes());
+    let headers = reader.headers().map_err(|e| TleParseError::Omm(e.to_string()))?.clone();
+    if !headers.iter().any(|h| h.eq_ignore_ascii_case("NORAD_CAT_ID")) {
+        return Err(TleParseError::Omm("CSV header has no NORAD_CAT_ID column".to_string()));
+    }
+    let mut entries = Vec::new();
+    for row in reader.records() {
+        let (line, entry) = match row {
+            Ok(row) => {
+                let line = row.position().map_or(0, |p| p.line() as usize);
+                let omm = headers
+                    .iter()
+                    .zip(row.iter())
+                    .map(|(key, value)| (key.to_ascii_uppercase(), value))
+                    .filter(|(key, value)| !value.is_empty() && is_omm_key(key))
+                    .map(|(key, value)| (key, serde_json::Value::String(value.to_string())))
+                    .collect();
+                (line, omm_elements(omm))
+            }
+            Err(e) => (e.position().map_or(0, |p| p.line() as usize), Err(e.to_string())),
+        };
+        entries.push((line, entry));
+    }
+    Ok(entries)
+}
+
+/// Offset just past the next `<name>` or `<name ...>` start tag.
+fn find_start_tag(text: &str, name: &str) -> Option<usize> {
+    let open = format!("<{}", name);
+    let mut offset = 0;
+    while let Some(pos) = text[offset..].find(&open) {
+        let after = offset + pos + open.len();
+        let head_end = text[after..].find('>')? + after;
+        if text[after..].starts_with(|c: char| c == '>' || c.is_whitespace()) {
+            return Some(head_end + 1);
+        }
+        offset = after;
+    }
+    None
+}
+
+/// Trimmed text of the first `<name>...</name>` element; `None` when absent
+/// or empty (`<name/>`).
+fn xml_element_text<'a>(body: &'a str, name: &str) -> Option<&'a str> {
+    let start = find_start_tag(body, name)?;
+    let end = body[start..].find(&format!("</{}>", name))? + start;
+    Some(body[start..end].trim()).filter(|t| !t.is_empty())
+}
+
+fn xml_unescape(text: &str) -> String {
+    text.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&apos;", "'").replace("&amp;", "&")
+}
+
+/// Mean Keplerian elements in TLE/OMM units (degrees, revolutions per day).
+#[derive(Debug, Clone)]
+pub struct MeanElements {
+    pub norad_id: u64,
+    pub epoch: chrono::NaiveDateTime,
+    pub inclination_deg: f64,
+    pub raan_deg: f64,
+    pub eccentricity: f64,
+    pub arg_perigee_deg: f64,
+    pub mean_anomaly_deg: f64,
+    pub mean_motion_rev_day: f64,
+    pub bstar: f64,
+}
+
+impl MeanElements {
+    /// Formats the elements as a TLE line pair (with checksums). Derivative terms are zero.
+    pub fn to_tle_lines(&self) -> (String, String) {
+        use chrono::{Datelike, Timelike};
+        let day_fraction = self.epoch.num_seconds_from_midnight() as f64 / 86_400.0
+            + self.epoch.nanosecond() as f64 / 86_400e9;
+        let line1 = format!(
+            "1 {:05}U {:<8} {:02}{:012.8}  .00000000  00000-0 {} 0  999",
+            self.norad_id % 100_000,
+            "",
+            self.epoch.year() % 100,
+            self.epoch.ordinal() as f64 + day_fraction,
+            format_tle_exponent(self.bstar),
+        );
+        let line2 = format!(
+            "2 {:05} {:8.4} {:8.4} {:07} {:8.4} {:8.4} {:11.8}{:>5}",
+            self.norad_id % 100_000,
+            self.inclination_deg,
+            self.raan_deg.rem_euclid(360.0),
+            ((self.eccentricity * 1e7).round() as u64).min(9_999_999),
+            self.arg_perigee_deg.rem_euclid(360.0),
+            self.mean_anomaly_deg.rem_euclid(360.0),
+            self.mean_motion_rev_day,
+            0,
+        );
+        (with_checksum(line1), with_checksum(line2))
+    }
+}
+
+/// TLE "assumed decimal point" exponent notation, e.g. `-11606-4` for -0.11606e-4.
+fn format_tle_exponent(v: f64) -> String {
+    if v == 0.0 {
+        return " 00000-0".to_string();
+    }
+    let sign = if v < 0.0 { '-' } else { ' ' };
+    let mut exp = v.abs().log10().floor() as i32 + 1;
+    let mut mantissa = (v.abs() / 10f64.powi(exp) * 1e5).round() as u64;
+    if mantissa >= 100_000 {
+        mantissa /= 10;
+        exp += 1;
+    }
+    let exp_sign = if exp < 0 { '-' } else { '+' };
+    format!("{}{:05}{}{}", sign, mantissa, exp_sign, exp.abs().min(9))
+}
+
+/// Appends the modulo-10 checksum (digits summed, '-' counts as 1).
+fn with_checksum(line: String) -> String {
+    let sum: u32 = line
+        .chars()
+        .map(|c| match c {
+            '-' => 1,
+            c => c.to_digit(10).unwrap_or(0),
+        })
+        .sum();
+    format!("{}{}", line, sum % 10)
+}
+
+/// Parses a TLE file, keeping every record that yields valid elements.
 /// Supports both 2-line and 3-line (with name) formats.
-pub fn parse_tle_file_to_elements(path: &Path) -> Result<Vec<sgp4::Elements>, TleParseError> {
+pub fn read_tle_report(path: &Path) -> Result<TleParseReport, TleParseError> {
     let content = fs::read_to_string(path)?;
-    let lines: Vec<String> = content
+    let report = parse_tle_report(&content);
+    for r in &report.rejected {
+        warn!(line = r.line, reason = %r.reason, "Skipping TLE record");
+    }
+    info!(count = report.elements.len(), rejected = report.rejected.len(), "Parsed TLE elements");
+    Ok(report)
+}
+
+/// Splits a GP document into records and parses their elements, collecting a
+/// line number and reason for each entry that cannot be used instead of
+/// failing. The format is detected from the content: TLE or 3LE text, or OMM
+/// as CSV, JSON or XML, whose entries are turned into TLE records so they can
+/// be archived like fetched TLEs. For OMM JSON the "line" is the object's
+/// number in the array.
+pub fn parse_tle_report(content: &str) -> TleParseReport {
+    let (records, mut rejected) = match GpFormat::detect(content) {
+        GpFormat::Tle | GpFormat::ThreeLe => split_records(content),
+        GpFormat::Csv => omm_records(omm_csv_entries(content)),
+        GpFormat::Json => omm_records(omm_json_entries(content)),
+        GpFormat::Xml => omm_records(omm_xml_entries(content)),
+    };
+    let mut report = TleParseReport::default();
+    for (line, rec) in records {
+        match rec.to_elements() {
+            Ok(el) => {
+                report.records.push(rec);
+                report.elements.push(el);
+            }
+            Err(e) => rejected.push(RejectedTle { line, reason: format!("invalid elements: {}", e) }),
+        }
+    }
+    rejected.sort_by_key(|r| r.line);
+    report.rejected = rejected;
+    report
+}
+
+/// Merges extra records into a catalog: unknown NORAD IDs are appended and known
+/// ones are replaced when the record has a newer epoch. Unparseable records are skipped.
+pub fn merge_records(elements: &mut Vec<sgp4::Elements>, records: &[TleRecord]) {
+    let mut index: HashMap<u64, usize> = elements.iter().enumerate().map(|(i, e)| (e.norad_id, i)).collect();
+    for rec in records {
+        let el = match rec.to_elements() {
+            Ok(el) => el,
+            Err(e) => {
+                warn!(error = %e, "Skipping unparseable TLE record");
+                continue;
+            }
+        };
+        match index.get(&el.norad_id) {
+            Some(&i) if elements[i].datetime < el.datetime => elements[i] = el,
+            Some(_) => {}
+            None => {
+                index.insert(el.norad_id, elements.len());
+                elements.push(el);
+            }
+        }
+    }
+}
+
+/// Splits TLE text into records. Supports both 2-line and 3-line (with name) formats.
+pub fn parse_tle_records(content: &str) -> Vec<TleRecord> {
+    let (records, rejected) = split_records(content);
+    for r in &rejected {
+        warn!(line = r.line, reason = %r.reason, "Skipping TLE record");
+    }
+    records.into_iter().map(|(_, rec)| rec).collect()
+}
+
+/// TLE records of the entries of an OMM document, with rejections for the
+/// entries without elements or TLE form; a malformed document is one rejection.
+fn omm_records(entries: Result<OmmEntries, TleParseError>) -> (Vec<(usize, TleRecord)>, Vec<RejectedTle>) {
+    let mut records = Vec::new();
+    let mut rejected = Vec::new();
+    match entries {
+        Ok(entries) => {
+            for (line, entry) in entries {
+                match entry.and_then(|el| tle_record(&el)) {
+                    Ok(record) => records.push((line, record)),
+                    Err(reason) => rejected.push(RejectedTle { line, reason: format!("invalid OMM entry: {}", reason) }),
+                }
+            }
+        }
+        Err(e) => rejected.push(RejectedTle { line: 1, reason: e.to_string() }),
+    }
+    (records, rejected)
+}
+
+/// The element set as a TLE record. Catalog numbers above 99999 have no TLE
+/// form.
+pub fn tle_record(el: &sgp4::Elements) -> Result<TleRecord, String> {
+    use chrono::{Datelike, Timelike};
+    if el.norad_id > 99_999 {
+        return Err(format!("NORAD ID {} does not fit a TLE", el.norad_id));
+    }
+    let classification = match el.classification {
+        sgp4::Classification::Unclassified => 'U',
+        sgp4::Classification::Classified => 'C',
+        sgp4::Classification::Secret => 'S',
+    };
+    // `1998-067A` is written `98067A`
+    let designator = el
+        .international_designator
+        .as_deref()
+        .and_then(crate::analyzers::launches::parse_designator)
+        .map(|d| format!("{}{}{}", &d.launch[2..4], &d.launch[5..], d.piece))
+        .unwrap_or_default();
+    let day = el.datetime.ordinal() as f64 + (el.datetime.num_seconds_from_midnight() as f64 + el.datetime.nanosecond() as f64 * 1e-9) / 86_400.0;
+    let line1 = format!(
+        "1 {:05}{} {:<8} {:02}{:012.8} {} {} {} {} {:>4}",
+        el.norad_id,
+        classification,
+        designator,
+        el.datetime.year() % 100,
+        day,
+        format_tle_decimal(el.mean_motion_dot),
+        format_tle_exponent(el.mean_motion_ddot),
+        format_tle_exponent(el.drag_term),
+        el.ephemeris_type % 10,
+        el.element_set_number % 10_000,
+    );
+    let line2 = format!(
+        "2 {:05} {:8.4} {:8.4} {:07} {:8.4} {:8.4} {:11.8}{:>5}",
+        el.norad_id,
+        el.inclination,
+        el.right_ascension.rem_euclid(360.0),
+        ((el.eccentricity * 1e7).round() as u64).min(9_999_999),
+        el.argument_of_perigee.rem_euclid(360.0),
+        el.mean_anomaly.rem_euclid(360.0),
+        el.mean_motion,
+        el.revolution_number % 100_000,
+    );
+    Ok(TleRecord { name: el.object_name.clone(), line1: with_checksum(line1), line2: with_checksum(line2) })
+}
+
+/// A fraction below 1 as TLE line 1 writes the first derivative, e.g.
+/// `-.00002182`.
+fn format_tle_decimal(v: f64) -> String {
+    let sign = if v < 0.0 { '-' } else { ' ' };
+    let digits = format!("{:.8}", v.abs().min(0.999_999_99));
+    format!("{}{}", sign, &digits[1..])
+}
+
+/// Pairs up line 1 / line 2 entries, keeping the source line number of each
+/// record; unpaired lines are returned as rejections. 3LE name lines lose
+/// their `0 ` prefix.
+fn split_records(content: &str) -> (Vec<(usize, TleRecord)>, Vec<RejectedTle>) {
+    // (1-based source line, trimmed text) of the non-empty lines
+    let lines: Vec<(usize, String)> = content
         .lines()
-        .map(|l| l.trim_end().to_string())
-        .filter(|l| !l.is_empty())
+        .enumerate()
+        .map(|(n, l)| (n + 1, l.trim_end().to_string()))
+        .filter(|(_, l)| !l.is_empty())
         .collect();
 
-    let mut elements = Vec::new();
+    let mut records = Vec::new();
+    let mut rejected = Vec::new();
     let mut i = 0usize;
     while i < lines.len() {
-        let line = &lines[i];
+        let (number, line) = &lines[i];
         if line.starts_with('1') {
             // Optional name on the previous line if it doesn't start with 1 or 2
             let name = if i >= 1 {
-                let prev = &lines[i - 1];
+                let prev = &lines[i - 1].1;
                 if !(prev.starts_with('1') || prev.starts_with('2')) {
-                    Some(prev.clone())
+                    Some(prev.strip_prefix("0 ").unwrap_or(prev).to_string())
                 } else {
                     None
                 }
@@ -41,31 +572,38 @@ pub fn parse_tle_file_to_elements(path: &Path) -> Result<Vec<sgp4::Elements>, Tl
                 None
             };
 
-            if i + 1 >= lines.len() || !lines[i + 1].starts_with('2') {
-                warn!(line = i + 1, "Skipping invalid TLE pair: missing line 2");
+            if i + 1 >= lines.len() || !lines[i + 1].1.starts_with('2') {
+                let err = TleParseError::InvalidPair { line: *number };
+                rejected.push(RejectedTle { line: *number, reason: format!("{}: missing line 2", err) });
                 i += 1;
                 continue;
             }
 
-            let l1 = lines[i].clone();
-            let l2 = lines[i + 1].clone();
-            debug!("Parsing TLE at lines {}, {}", i + 1, i + 2);
-            let elems = sgp4::Elements::from_tle(name, l1.as_bytes(), l2.as_bytes())?;
-            elements.push(elems);
+            debug!("Parsing TLE at lines {}, {}", number, lines[i + 1].0);
+            records.push((
+                *number,
+                TleRecord {
+                    name,
+                    line1: line.clone(),
+                    line2: lines[i + 1].1.clone(),
+                },
+            ));
             i += 2;
+        } else if line.starts_with("2 ") {
+            let err = TleParseError::InvalidPair { line: *number };
+            rejected.push(RejectedTle { line: *number, reason: format!("{}: line 2 without line 1", err) });
+            i += 1;
         } else {
             // Skip non-TLE content or name lines
             i += 1;
         }
     }
-
-    info!(count = elements.len(), "Parsed TLE elements");
-    Ok(elements)
+    (records, rejected)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::parse_tle_file_to_elements;
+    use super::{parse_omm_csv, parse_omm_json, parse_omm_xml, parse_tle_or_omm, parse_tle_report, read_tle_report, tle_record, to_omm, GpFormat, MeanElements};
     use std::io::Write;
     use tempfile::NamedTempFile;
 
@@ -84,8 +622,155 @@ mod tests {
         )
         .unwrap();
 
-        let elems = parse_tle_file_to_elements(file.path()).unwrap();
+        let elems = read_tle_report(file.path()).unwrap().elements;
         assert_eq!(elems.len(), 1);
         assert_eq!(elems[0].norad_id, 25544);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn report_lists_rejected_lines() {
+        let text = "ISS (ZARYA)
+1 25544U 98067A   08264.51782528 -.00002182  00000-0 -11606-4 0  2927
+2 25544  51.6416 247.4627 0006703 130.5360 325.0288 15.72125391563537
+
+BROKEN
+1 25545U 98067A   08264.51782528 -.00002182  00000-0 -11606-4 0  2927
+2 25545  51.6x16 247.4627 0006703 130.5360 325.0288 15.72125391563537
+1 25546U 98067A   08264.51782528 -.00002182  00000-0 -11606-4 0  2927
+";
+        let report = parse_tle_report(text);
+        assert_eq!(report.elements.len(), 1);
+        assert_eq!(report.records.len(), 1);
+        let lines: Vec<usize> = report.rejected.iter().map(|r| r.line).collect();
+        assert_eq!(lines, vec![6, 8]);
+        assert!(report.rejected[0].reason.starts_with("invalid elements"), "{:?}", report.rejected);
+        assert!(report.rejected[1].reason.contains("missing line 2"));
+    }
+
+    #[test]
+    fn mean_elements_round_trip_through_tle() {
+        let me = MeanElements {
+            norad_id: 99999,
+            epoch: chrono::NaiveDate::from_ymd_opt(2024, 2, 14).unwrap().and_hms_opt(6, 0, 0).unwrap(),
+            inclination_deg: 97.4,
+            raan_deg: -10.0,
+            eccentricity: 0.0012345,
+            arg_perigee_deg: 90.0,
+            mean_anomaly_deg: 270.0,
+            mean_motion_rev_day: 15.2,
+            bstar: -0.00011606,
+        };
+        let (l1, l2) = me.to_tle_lines();
+        assert_eq!(l1.len(), 69);
+        assert_eq!(l2.len(), 69);
+
+        let el = sgp4::Elements::from_tle(None, l1.as_bytes(), l2.as_bytes()).unwrap();
+        assert_eq!(el.norad_id, 99999);
+        assert!((el.inclination - 97.4).abs() < 1e-4);
+        assert!((el.right_ascension - 350.0).abs() < 1e-4);
+        assert!((el.mean_motion - 15.2).abs() < 1e-8);
+        assert_eq!(el.datetime, me.epoch);
+    }
+
+    /// OMM epochs carry microseconds, while the TLE day fraction decodes a few
+    /// nanoseconds off them.
+    fn epoch_micros(el: &sgp4::Elements) -> i64 {
+        el.datetime.and_utc().timestamp_micros()
+    }
+
+    #[test]
+    fn omm_round_trips_through_the_parser() {
+        let el = crate::testing::fixtures::catalog().swap_remove(0);
+        let back = parse_tle_or_omm(None, Some(to_omm(&el))).unwrap();
+        assert_eq!((back.norad_id, back.object_name.clone(), epoch_micros(&back)), (el.norad_id, el.object_name.clone(), epoch_micros(&el)));
+        assert_eq!((back.mean_motion, back.eccentricity, back.drag_term), (el.mean_motion, el.eccentricity, el.drag_term));
+    }
+
+    #[test]
+    fn omm_documents_parse_like_tles() {
+        let catalog = crate::testing::fixtures::catalog();
+        let celestrak = serde_json::Value::Array(catalog.iter().map(to_omm).collect()).to_string();
+        let parsed = parse_omm_json(&celestrak).unwrap();
+        assert_eq!(parsed.iter().map(|el| el.norad_id).collect::<Vec<_>>(), [25544, 28654]);
+        assert_eq!((epoch_micros(&parsed[0]), parsed[0].mean_motion), (epoch_micros(&catalog[0]), catalog[0].mean_motion));
+
+        // Space-Track quotes every number; an object missing its epoch is skipped
+        let spacetrack = r#"[{"OBJECT_NAME":"ISS (ZARYA)","OBJECT_ID":"1998-067A","EPOCH":"2008-09-20T12:25:40.104192",
+            "MEAN_MOTION":"15.72125391","ECCENTRICITY":"0.00067030","INCLINATION":"51.6416","RA_OF_ASC_NODE":"247.4627",
+            "ARG_OF_PERICENTER":"130.5360","MEAN_ANOMALY":"325.0288","EPHEMERIS_TYPE":"0","CLASSIFICATION_TYPE":"U",
+            "NORAD_CAT_ID":"25544","ELEMENT_SET_NO":"292","REV_AT_EPOCH":"56353","BSTAR":"-0.000011606",
+            "MEAN_MOTION_DOT":"-0.00002182","MEAN_MOTION_DDOT":"0"},{"NORAD_CAT_ID":"25545"}]"#;
+        let parsed = parse_omm_json(spacetrack).unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!((parsed[0].norad_id, epoch_micros(&parsed[0])), (25544, epoch_micros(&catalog[0])));
+        assert!((parsed[0].drag_term - catalog[0].drag_term).abs() < 1e-12);
+        assert!(parse_omm_json("{").is_err() && parse_omm_json("42").is_err());
+
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ndm xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+<omm id="CCSDS_OMM_VERS" version="2.0">
+<header><CREATION_DATE/><ORIGINATOR/></header>
+<body><segment>
+<metadata><OBJECT_NAME>ISS (ZARYA)</OBJECT_NAME><OBJECT_ID>1998-067A</OBJECT_ID><CENTER_NAME>EARTH</CENTER_NAME>
+<REF_FRAME>TEME</REF_FRAME><TIME_SYSTEM>UTC</TIME_SYSTEM><MEAN_ELEMENT_THEORY>SGP4</MEAN_ELEMENT_THEORY></metadata>
+<data><meanElements><EPOCH>2008-09-20T12:25:40.104192</EPOCH><MEAN_MOTION>15.72125391</MEAN_MOTION>
+<ECCENTRICITY>.0006703</ECCENTRICITY><INCLINATION>51.6416</INCLINATION><RA_OF_ASC_NODE>247.4627</RA_OF_ASC_NODE>
+<ARG_OF_PERICENTER>130.536</ARG_OF_PERICENTER><MEAN_ANOMALY>325.0288</MEAN_ANOMALY></meanElements>
+<tleParameters><EPHEMERIS_TYPE>0</EPHEMERIS_TYPE><CLASSIFICATION_TYPE>U</CLASSIFICATION_TYPE><NORAD_CAT_ID>25544</NORAD_CAT_ID>
+<ELEMENT_SET_NO>292</ELEMENT_SET_NO><REV_AT_EPOCH>56353</REV_AT_EPOCH><BSTAR>-.11606E-4</BSTAR>
+<MEAN_MOTION_DOT>-.00002182</MEAN_MOTION_DOT><MEAN_MOTION_DDOT>0</MEAN_MOTION_DDOT></tleParameters></data>
+</segment></body></omm>
+<omm id="CCSDS_OMM_VERS" version="2.0"><body><segment><metadata><OBJECT_NAME>A &amp; B</OBJECT_NAME></metadata></segment></body></omm>
+</ndm>"#;
+        let parsed = parse_omm_xml(xml).unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!((parsed[0].norad_id, parsed[0].object_name.as_deref()), (25544, Some("ISS (ZARYA)")));
+        assert_eq!((epoch_micros(&parsed[0]), parsed[0].eccentricity), (epoch_micros(&catalog[0]), catalog[0].eccentricity));
+        assert!(parse_omm_xml("<ndm></ndm>").is_err() && parse_omm_xml("<omm>").is_err());
+    }
+
+    #[test]
+    fn every_gp_format_is_detected_and_read_as_tle_records() {
+        use crate::testing::fixtures::{ISS_TLE, NOAA_18_TLE};
+        let catalog = crate::testing::fixtures::catalog();
+        // Element sets written back as TLEs give the source lines
+        for (el, text) in catalog.iter().zip([ISS_TLE, NOAA_18_TLE]) {
+            let record = tle_record(el).unwrap();
+            assert_eq!([record.line1.as_str(), record.line2.as_str()], [text.lines().nth(1).unwrap(), text.lines().nth(2).unwrap()]);
+        }
+
+        let three_le = format!("0 {}", ISS_TLE);
+        assert_eq!(GpFormat::detect(&three_le), GpFormat::ThreeLe);
+        assert_eq!(parse_tle_report(&three_le).records[0].name.as_deref(), Some("ISS (ZARYA)"));
+        assert_eq!(GpFormat::detect(ISS_TLE), GpFormat::Tle);
+
+        let keys = ["OBJECT_NAME", "OBJECT_ID", "EPOCH", "MEAN_MOTION", "ECCENTRICITY", "INCLINATION", "RA_OF_ASC_NODE", "ARG_OF_PERICENTER", "MEAN_ANOMALY",
+            "EPHEMERIS_TYPE", "CLASSIFICATION_TYPE", "NORAD_CAT_ID", "ELEMENT_SET_NO", "REV_AT_EPOCH", "BSTAR", "MEAN_MOTION_DOT", "MEAN_MOTION_DDOT"];
+        let mut csv = keys.join(",") + "\n";
+        for el in &catalog {
+            let omm = to_omm(el);
+            let row: Vec<String> = keys.iter().map(|k| omm[k].to_string().trim_matches('"').to_string()).collect();
+            csv.push_str(&format!("\"{}\"\n", row.join("\",\"")));
+        }
+        csv.push_str("BROKEN,1999-025A,not-a-date\n");
+        assert_eq!(GpFormat::detect(&csv), GpFormat::Csv);
+        assert_eq!(parse_omm_csv(&csv).unwrap().len(), 2);
+        let report = parse_tle_report(&csv);
+        assert_eq!(report.elements.iter().map(|e| e.norad_id).collect::<Vec<_>>(), [25544, 28654]);
+        assert_eq!((report.records[0].line2.as_str(), report.rejected.len(), report.rejected[0].line), (ISS_TLE.lines().nth(2).unwrap(), 1, 4));
+
+        // OMM carries catalog numbers TLEs cannot
+        let mut omm: Vec<serde_json::Value> = catalog.iter().map(to_omm).collect();
+        omm[1]["NORAD_CAT_ID"] = serde_json::json!(270_000);
+        let json = serde_json::Value::Array(omm).to_string();
+        assert_eq!(GpFormat::detect(&json), GpFormat::Json);
+        let report = parse_tle_report(&json);
+        assert_eq!((report.elements.len(), report.rejected[0].line), (1, 2));
+        assert!(report.rejected[0].reason.contains("270000"), "{:?}", report.rejected);
+        assert_eq!(parse_tle_report("[1,").rejected.len(), 1);
+
+        assert_eq!(GpFormat::detect("\u{feff}<?xml version=\"1.0\"?><ndm/>"), GpFormat::Xml);
+        assert_eq!(["tle", "3LE", "csv"].map(|f| f.parse::<GpFormat>()), [Ok(GpFormat::Tle), Ok(GpFormat::ThreeLe), Ok(GpFormat::Csv)]);
+        assert!("kvn".parse::<GpFormat>().is_err());
+    }
+}
@@ -0,0 +1,45 @@
+//! Satellite tracking: fetch two-line element sets, propagate them with SGP4
+//! and predict passes over ground stations, without the HTTP server.
+//!
+//! ```no_run
+//! use stfcm::collectors::tle_fetcher;
+//! use stfcm::config::Config;
+//! use stfcm::core::tle::read_tle_report;
+//! use stfcm::predictors::passes::predict_passes;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let path = tle_fetcher::fetch_celestrak_group(&Config::default(), "stations").await?;
+//! let catalog = read_tle_report(&path)?.elements;
+//! let iss = catalog.iter().find(|e| e.norad_id == 25544).ok_or("ISS not in the group")?;
+//! for pass in predict_passes(iss, 47.37, 8.54, chrono::Utc::now(), 1440, 30, 10.0)? {
+//!     println!("{} {} max {:.0}°", pass.start, pass.direction(), pass.max_elevation_deg);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+/// Element set parsing, SGP4 propagation, coordinate frames and the Sun.
+pub mod core;
+/// Pass, conjunction, ground-track and other predictions from element sets.
+pub mod predictors;
+/// Downloads of element sets and the SATCAT from Celestrak.
+pub mod collectors;
+/// The SQLite store (`utils::db`) and the server's runtime helpers.
+pub mod utils;
+/// Settings from `stfcm.toml` and `STFCM_*` environment variables.
+pub mod config;
+
+// Used by the `STfCM` binary; not a stable API.
+#[doc(hidden)]
+pub mod analyzers;
+#[doc(hidden)]
+pub mod api;
+#[doc(hidden)]
+pub mod cli;
+#[doc(hidden)]
+pub mod scheduler;
+
+/// Fixture TLEs, a manual clock and golden pass windows for regression tests.
+#[cfg(any(test, feature = "testing"))]
+#[allow(dead_code)]
+pub mod testing;
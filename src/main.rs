@@ -1,85 +1,536 @@
-mod utils;
-mod collectors;
-mod core;
-mod predictors;
-mod api;
-use tracing::info;
-
-#[tokio::main]
-async fn main() {
-    utils::logging::init();
-    info!("STfCM initialized");
-
-    let path = match collectors::tle_fetcher::fetch_celestrak_active_tle().await {
-        Ok(path) => {
-            info!(path = %path.display(), "Fetched and cached TLEs");
-            path
-        }
-        Err(e) => {
-            tracing::error!(error = %e, "Failed to fetch TLEs");
-            return;
-        }
-    };
-
-    match core::tle::parse_tle_file_to_elements(&path) {
-        Ok(elements) => {
-            info!(count = elements.len(), "Parsed elements from TLE file");
-            // Initialize DB
-            let conn = match utils::db::open_or_init() {
-                Ok(c) => c,
-                Err(e) => {
-                    tracing::error!(error = %e, "Failed to initialize database");
-                    return;
-                }
-            };
-            for (idx, el) in elements.iter().take(3).enumerate() {
-                let name = el.object_name.as_deref().unwrap_or("<unnamed>");
-                info!(sat_index = idx, norad = el.norad_id, name, "Propagating sample satellite");
-                match core::orbit::propagate_minutes(el, 10.0) {
-                    Ok(pred) => {
-                        info!(
-                            "Pos (km) = [{:.3}, {:.3}, {:.3}], Vel (km/s) = [{:.5}, {:.5}, {:.5}]",
-                            pred.position[0], pred.position[1], pred.position[2],
-                            pred.velocity[0], pred.velocity[1], pred.velocity[2]
-                        );
-                        // Persist snapshot
-                        if let Err(e) = utils::db::upsert_satellite(&conn, el.norad_id, el.object_name.as_deref()) {
-                            tracing::warn!(error = %e, norad = el.norad_id, "Failed to upsert satellite");
-                        }
-                        let ts = chrono::Utc::now().to_rfc3339();
-                        if let Err(e) = utils::db::insert_snapshot(&conn, el.norad_id, &ts, &pred) {
-                            tracing::warn!(error = %e, norad = el.norad_id, "Failed to insert snapshot");
-                        } else {
-                            info!(norad = el.norad_id, "Inserted snapshot");
-                        }
-                    }
-                    Err(e) => tracing::warn!(error = %e, "Propagation failed"),
-                }
-            }
-
-            if let Some(el0) = elements.get(0) {
-                let start = chrono::Utc::now();
-                match predictors::passes::predict_passes(el0, 40.7128, -74.0060, start, 120, 15, 10.0) {
-                    Ok(windows) => {
-                        for (i, w) in windows.iter().take(3).enumerate() {
-                            info!(
-                                "Pass {}: start={} end={} max_el={:.1}°",
-                                i + 1,
-                                w.start.to_rfc3339(),
-                                w.end.to_rfc3339(),
-                                w.max_elevation_deg
-                            );
-                        }
-                    }
-                    Err(e) => tracing::warn!(error = %e, "Pass prediction failed"),
-                }
-            }
-
-            // Start API server with loaded elements
-            let state = api::server::AppState { elements: std::sync::Arc::new(elements) };
-            let addr: std::net::SocketAddr = "127.0.0.1:3000".parse().unwrap();
-            api::server::run_server(state, addr).await;
-        }
-        Err(e) => tracing::error!(error = %e, "Failed to parse TLE file"),
-    }
-}
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+use tracing::info;
+
+use stfcm::{analyzers, api, cli, collectors, core, predictors, scheduler, utils};
+use stfcm::config::Config;
+use scheduler::leader::Leadership;
+use utils::daemon::{Control, Signals};
+
+fn main() {
+    let invocation = match cli::Invocation::parse(std::env::args().skip(1)) {
+        Ok(i) => i,
+        // Prints help and version to stdout, errors with the usage to stderr
+        Err(e) => e.exit(),
+    };
+    let config = match Config::load(invocation.config.as_deref()) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    utils::db::set_path(config.data.db_path.clone());
+    let options = match invocation.command {
+        cli::Command::Serve(o) => o,
+        cli::Command::Fetch(o) => std::process::exit(run_fetch(o, &config)),
+        cli::Command::Passes(o) => std::process::exit(run_passes(o, &config)),
+        cli::Command::Tui(o) => std::process::exit(run_tui(o, &config)),
+        cli::Command::Predict(o) => std::process::exit(run_predict(o, &config)),
+        cli::Command::Positions(o) => std::process::exit(run_positions(o, &config)),
+        cli::Command::ExportConfig(o) => std::process::exit(run_config(cli::config::export, &o)),
+        cli::Command::ImportConfig(o) => std::process::exit(run_config(cli::config::import, &o)),
+    };
+    // Forking is only safe before the runtime starts its threads
+    if options.daemon {
+        if let Err(e) = utils::daemon::daemonize(options.log_file.as_deref()) {
+            eprintln!("Failed to daemonize: {}", e);
+            std::process::exit(1);
+        }
+    }
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("failed to start the Tokio runtime");
+    runtime.block_on(serve(options, config, invocation.config));
+}
+
+/// Runs the terminal dashboard; logging stays off so it does not garble the screen.
+fn run_tui(options: cli::tui::Options, config: &Config) -> i32 {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("failed to start the Tokio runtime");
+    let clock = core::clock::from_env();
+    let result = runtime
+        .block_on(cli::load_catalog(config, clock.now()))
+        .and_then(|catalog| cli::tui::run(options, catalog, clock.as_ref()));
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
+    }
+}
+
+/// Downloads Celestrak groups into the TLE cache. Logging stays off so it does
+/// not mix with the printed summary.
+fn run_fetch(options: cli::fetch::Options, config: &Config) -> i32 {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("failed to start the Tokio runtime");
+    match runtime.block_on(cli::fetch::run(options, config)) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
+    }
+}
+
+/// Prints pass predictions. Logging stays off so it does not mix with the
+/// table or JSON.
+fn run_passes(options: cli::passes::Options, config: &Config) -> i32 {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("failed to start the Tokio runtime");
+    match runtime.block_on(cli::passes::run(options, config, core::clock::from_env().as_ref())) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
+    }
+}
+
+/// Prints satellite positions once or, with `--watch`, until interrupted.
+/// Logging stays off so it does not break up the redrawn lines.
+fn run_positions(options: cli::positions::Options, config: &Config) -> i32 {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("failed to start the Tokio runtime");
+    let clock = core::clock::from_env();
+    let result = runtime
+        .block_on(cli::load_catalog(config, clock.now()))
+        .and_then(|catalog| cli::positions::run(options, catalog, clock.as_ref()));
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
+    }
+}
+
+/// Writes pass predictions to a file and exits, e.g. from cron.
+fn run_predict(options: cli::predict::Options, config: &Config) -> i32 {
+    utils::logging::init();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("failed to start the Tokio runtime");
+    match runtime.block_on(cli::predict::run(options, config, core::clock::from_env().as_ref())) {
+        Ok(()) => 0,
+        Err(e) => {
+            tracing::error!(error = %e, "Prediction failed");
+            1
+        }
+    }
+}
+
+/// Exports or imports the configuration bundle. Logging stays off when the
+/// bundle goes through standard input or output, which it would garble.
+fn run_config(f: fn(&cli::config::Options) -> Result<(), String>, options: &cli::config::Options) -> i32 {
+    if options.path.is_some() {
+        utils::logging::init();
+    }
+    match f(options) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
+    }
+}
+
+/// Serves until a shutdown signal. `config_path` is read again on each reload;
+/// the bind address and database path only change on restart.
+async fn serve(options: utils::daemon::Options, mut config: Config, config_path: Option<std::path::PathBuf>) {
+    utils::logging::init();
+    info!("STfCM initialized");
+
+    let _pid_file = match options.pid_file.as_deref().map(utils::daemon::PidFile::create).transpose() {
+        Ok(p) => p,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to create PID file");
+            return;
+        }
+    };
+    let mut signals = match Signals::install() {
+        Ok(s) => s,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to install signal handlers");
+            return;
+        }
+    };
+
+    let read_only = utils::db::read_only();
+    if read_only {
+        info!("Read-only mode: mutating endpoints and background writers are disabled");
+    }
+    let leadership = Leadership::from_env(!read_only).await;
+    info!(instance = leadership.instance_id(), leader = leadership.is_leader(), "Joined instance group");
+
+    // Bound before the first load so clients get 503s rather than refused connections
+    let addr = config.server.bind;
+    let listener = match std::net::TcpListener::bind(addr).and_then(|l| l.set_nonblocking(true).map(|()| l)) {
+        Ok(l) => l,
+        Err(e) => {
+            tracing::error!(error = %e, %addr, "Failed to bind the API listener");
+            return;
+        }
+    };
+    println!("API server listening on http://{}", addr);
+    if !api::warmup::wait_for_catalog() {
+        utils::sd_notify::ready();
+    }
+
+    let _watchdog = scheduler::watchdog::spawn();
+    // Outlives reloads so stream clients connected before one hear about its changes
+    let (catalog_events, _) = tokio::sync::broadcast::channel(CATALOG_EVENT_BUFFER);
+    // Also outlives reloads; each load drops the predictions of superseded element sets
+    let pass_cache = Arc::new(api::pass_cache::PassCache::from_env(read_only));
+    while run(&config, &leadership, read_only, &listener, &mut signals, &catalog_events, &pass_cache).await == Control::Reload {
+        info!("Reloading configuration and TLEs");
+        utils::sd_notify::reloading();
+        match Config::load(config_path.as_deref()) {
+            Ok(c) => config = c,
+            Err(e) => tracing::warn!(error = %e, "Failed to reload the configuration; keeping the previous one"),
+        }
+    }
+    utils::sd_notify::stopping();
+    info!("STfCM stopped");
+}
+
+/// Loads the TLEs, starts the background jobs and serves the API until a signal
+/// asks for a reload or shutdown. Startup failures end the process.
+async fn run(
+    config: &Config,
+    leadership: &Arc<Leadership>,
+    read_only: bool,
+    listener: &std::net::TcpListener,
+    signals: &mut Signals,
+    catalog_events: &tokio::sync::broadcast::Sender<analyzers::catalog_changes::CatalogChanges>,
+    pass_cache: &Arc<api::pass_cache::PassCache>,
+) -> Control {
+    // Answers with 503 until the API server takes the listener over
+    let warmup = api::warmup::Warmup::spawn(listener).map_err(|e| tracing::warn!(error = %e, "Failed to start the warm-up server")).ok();
+
+    let clock = core::clock::from_env();
+    let Some(loaded) = load_catalog(config, leadership, catalog_events, clock.now(), true).await else {
+        return Control::Shutdown;
+    };
+    let (tsdb, snapshots) = if read_only {
+        (None, None)
+    } else {
+        let snapshot_conn = match utils::db::open_or_init() {
+            Ok(c) => c.into_inner(),
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to open database for the snapshot writer");
+                return Control::Shutdown;
+            }
+        };
+        let tsdb = utils::tsdb::TsdbSink::from_env().await.map(std::sync::Arc::new);
+        let (queue, _writer) = scheduler::snapshot_writer::spawn(snapshot_conn, Default::default(), tsdb.clone());
+        (tsdb, Some(queue))
+    };
+    for (idx, (el, name)) in loaded.catalog.named().take(3).enumerate() {
+        let name = name.unwrap_or("<unnamed>");
+        info!(sat_index = idx, norad = el.norad_id, name, "Propagating sample satellite");
+        match core::orbit::propagate_minutes(el, 10.0) {
+            Ok(pred) => {
+                info!(
+                    "Pos (km) = [{:.3}, {:.3}, {:.3}], Vel (km/s) = [{:.5}, {:.5}, {:.5}]",
+                    pred.position[0], pred.position[1], pred.position[2],
+                    pred.velocity[0], pred.velocity[1], pred.velocity[2]
+                );
+            }
+            Err(e) => tracing::warn!(error = %e, "Propagation failed"),
+        }
+    }
+
+    pass_cache.retain_current(&loaded.catalog);
+
+    // Start API server with loaded elements
+    let state = api::server::AppState {
+        elements: Arc::new(ArcSwap::from_pointee(loaded.catalog)),
+        uncertainty: Arc::new(ArcSwap::from_pointee(loaded.uncertainty)),
+        tsdb,
+        snapshots,
+        cache: utils::cache::ResponseCache::from_env().await,
+        leadership: leadership.clone(),
+        clock,
+        screening: Arc::new(ArcSwap::from_pointee(loaded.screening)),
+        catalog_events: catalog_events.clone(),
+        pass_cache: pass_cache.clone(),
+        crewed: std::sync::Arc::new(api::crewed::CrewedVehicles::from_env()),
+        countries: std::sync::Arc::new(core::countries::CountryBoundaries::from_env()),
+        positions: std::sync::Arc::new(api::stream::PositionCache::default()),
+        config: std::sync::Arc::new(config.clone()),
+    };
+    let screening = scheduler::conjunctions::spawn_daily(state.elements.clone(), leadership.clone());
+    let anomalies = (!read_only).then(|| scheduler::anomalies::spawn(leadership.clone()));
+    let recorder = match (&state.snapshots, config.data.snapshot_seconds) {
+        (Some(queue), seconds) if seconds > 0 => {
+            let period = std::time::Duration::from_secs(seconds);
+            Some(scheduler::snapshot_recorder::spawn(state.elements.clone(), queue.clone(), leadership.clone(), period))
+        }
+        _ => None,
+    };
+    let refresh = (config.celestrak.refresh_minutes > 0).then(|| {
+        let (config, leadership, events, clock) = (config.clone(), leadership.clone(), catalog_events.clone(), state.clock.clone());
+        scheduler::tle_refresh::spawn(config.celestrak.refresh_interval(), state.clone(), move || {
+            let (config, leadership, events, now) = (config.clone(), leadership.clone(), events.clone(), clock.now());
+            async move { load_catalog(&config, &leadership, &events, now, false).await }
+        })
+    });
+    if let Some(warmup) = warmup {
+        warmup.stop().await;
+    }
+    let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+    let server = api::server::run_server(state, listener, async move {
+        let _ = stopped.await;
+    });
+    tokio::pin!(server);
+    let control = tokio::select! {
+        control = signals.recv() => control,
+        _ = &mut server => Control::Shutdown,
+    };
+    info!(?control, "Stopping API server");
+    let _ = stop.send(());
+    server.await;
+    for job in [Some(screening), anomalies, recorder, refresh].into_iter().flatten() {
+        job.abort();
+    }
+    control
+}
+
+/// Fetches and parses the TLEs and prepares them for the API: the active group
+/// plus recent launches and uploads, screened at `now`, then custom element
+/// sets and overrides. The leader also records the catalog bookkeeping.
+/// `reuse_cached` lets startup and reloads use group sets cached within the
+/// refresh interval; the scheduled refresh passes `false` and downloads them.
+/// Failures that leave no catalog are logged and give `None`.
+async fn load_catalog(
+    config: &Config,
+    leadership: &Leadership,
+    catalog_events: &tokio::sync::broadcast::Sender<analyzers::catalog_changes::CatalogChanges>,
+    now: chrono::DateTime<chrono::Utc>,
+    reuse_cached: bool,
+) -> Option<scheduler::tle_refresh::LoadedCatalog> {
+    // Only the leader writes the catalog; followers serve reads from the shared backend
+    let leader = leadership.is_leader();
+    let path = match load_group(config, leadership, collectors::tle_fetcher::ACTIVE_GROUP, reuse_cached).await {
+        Ok(path) => {
+            info!(path = %path.display(), "Fetched and cached TLEs");
+            path
+        }
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to fetch TLEs");
+            record_fetch(leadership, collectors::tle_fetcher::ACTIVE_GROUP, 0, &[], Some(&e.to_string()));
+            return None;
+        }
+    };
+    let report = match core::tle::read_tle_report(&path) {
+        Ok(report) => report,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to parse TLE file");
+            return None;
+        }
+    };
+
+    let mut elements = report.elements;
+    let mut records = report.records;
+    info!(count = elements.len(), "Parsed elements from TLE file");
+    record_fetch(leadership, collectors::tle_fetcher::ACTIVE_GROUP, elements.len(), &report.rejected, None);
+    let mut extra = Vec::new();
+    // Recently launched objects may not be in the active group yet
+    let recent = load_group(config, leadership, collectors::tle_fetcher::LAST_30_DAYS_GROUP, reuse_cached).await;
+    match recent {
+        Ok(p) => match core::tle::read_tle_report(&p) {
+            Ok(recent) => {
+                info!(count = recent.records.len(), "Fetched recent launch TLEs");
+                record_fetch(leadership, collectors::tle_fetcher::LAST_30_DAYS_GROUP, recent.records.len(), &recent.rejected, None);
+                extra.extend(recent.records);
+            }
+            Err(e) => tracing::warn!(error = %e, "Failed to read recent launch TLEs"),
+        },
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to fetch recent launch TLEs");
+            record_fetch(leadership, collectors::tle_fetcher::LAST_30_DAYS_GROUP, 0, &[], Some(&e.to_string()));
+        }
+    }
+    // Initialize DB
+    let conn = match utils::db::open_or_init() {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to initialize database");
+            return None;
+        }
+    };
+    match utils::db::list_latest_records_with_tag(&conn, analyzers::new_objects::UPLOADED_TAG) {
+        Ok(uploaded) => extra.extend(uploaded),
+        Err(e) => tracing::warn!(error = %e, "Failed to load uploaded TLEs"),
+    }
+    core::tle::merge_records(&mut elements, &extra);
+    records.extend(extra);
+    // Screened before the catalog bookkeeping so rejected sets are not stored as satellites
+    let screening_limits = core::screening::ScreeningLimits::from_env();
+    let screened = core::screening::screen(&mut elements, &screening_limits, now);
+
+    let fetched_at = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    let mut new_ids = Vec::new();
+    if leader {
+        match analyzers::new_objects::detect_and_tag(&conn, &elements, &fetched_at) {
+            Ok(ids) => new_ids = ids,
+            Err(e) => tracing::warn!(error = %e, "Failed to detect new objects"),
+        }
+        match analyzers::catalog_changes::record_changes(&conn, &elements, &fetched_at) {
+            Ok(changes) if !changes.is_empty() => {
+                let _ = catalog_events.send(changes);
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!(error = %e, "Failed to record catalog changes"),
+        }
+        let catalog: Vec<(u64, Option<&str>)> = elements.iter().map(|e| (e.norad_id, e.object_name.as_deref())).collect();
+        match utils::db::upsert_satellites(&conn, &catalog) {
+            Ok(n) => info!(count = n, "Stored satellite catalog"),
+            Err(e) => tracing::warn!(error = %e, "Failed to store satellite catalog"),
+        }
+        if let Err(e) = analyzers::accuracy::record_accuracy(&conn, &elements, &fetched_at) {
+            tracing::warn!(error = %e, "Failed to record element set accuracy");
+        }
+        if let Err(e) = analyzers::shells::record_shell_counts(&conn, &elements, &fetched_at) {
+            tracing::warn!(error = %e, "Failed to record altitude shell counts");
+        }
+        match utils::db::insert_tle_history(&conn, &records, &fetched_at) {
+            Ok(n) => info!(new = n, "Archived TLE history"),
+            Err(e) => tracing::warn!(error = %e, "Failed to archive TLE history"),
+        }
+        refresh_satcat(config).await;
+    }
+    // User-defined satellites join after the catalog bookkeeping above so
+    // their synthetic IDs are never archived or tagged as new objects
+    match core::custom::load_custom_elements(&conn) {
+        Ok(custom) => {
+            if !custom.is_empty() {
+                info!(count = custom.len(), "Loaded custom element sets");
+            }
+            elements.extend(custom);
+        }
+        Err(e) => tracing::warn!(error = %e, "Failed to load custom element sets"),
+    }
+    match core::overrides::apply_overrides(&conn, &mut elements) {
+        Ok(0) => {}
+        Ok(n) => info!(count = n, "Applied element set overrides"),
+        Err(e) => tracing::warn!(error = %e, "Failed to apply element set overrides"),
+    }
+    // Propagates every archived element set, so it stays off the async workers
+    let estimate = tokio::task::spawn_blocking(|| utils::db::open_or_init().and_then(|c| predictors::uncertainty::estimate_from_history(&c)));
+    let uncertainty = match estimate.await {
+        Ok(Ok(uncertainty)) => uncertainty,
+        Ok(Err(e)) => {
+            tracing::warn!(error = %e, "Failed to estimate position uncertainty");
+            Default::default()
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "Position uncertainty task panicked");
+            Default::default()
+        }
+    };
+    let catalog = core::catalog::Catalog::new(elements);
+    if leader {
+        // Against the served catalog, which leaves out custom sets by their IDs
+        if let Err(e) = analyzers::groups::refresh_dynamic_groups(&conn, &catalog, &fetched_at) {
+            tracing::warn!(error = %e, "Failed to update dynamic groups");
+        }
+        analyzers::new_objects::notify_new_objects(&new_ids, &catalog).await;
+    }
+    Some(scheduler::tle_refresh::LoadedCatalog {
+        catalog,
+        uncertainty,
+        screening: (screening_limits, screened),
+    })
+}
+
+/// Catalog change events kept for stream clients that fall behind.
+const CATALOG_EVENT_BUFFER: usize = 16;
+
+/// How long a follower waits for the leader to share a TLE set before fetching it itself.
+const FOLLOWER_WAIT: std::time::Duration = std::time::Duration::from_secs(120);
+
+/// Loads a Celestrak group into the TLE directory. The leader downloads it, or
+/// with `reuse_cached` reuses a copy cached within the refresh interval, and
+/// shares it with the other instances; followers use the leader's copy and only
+/// download themselves if none shows up within `FOLLOWER_WAIT`.
+async fn load_group(config: &Config, leadership: &Leadership, group: &str, reuse_cached: bool) -> Result<std::path::PathBuf, collectors::tle_fetcher::FetchError> {
+    let deadline = tokio::time::Instant::now() + FOLLOWER_WAIT;
+    while !leadership.is_leader() && leadership.is_shared() {
+        if let Some(text) = leadership.shared_tle(group).await {
+            info!(group, "Loaded TLE set shared by the leader");
+            return collectors::tle_fetcher::cache_tle_text(config, group, &text);
+        }
+        if tokio::time::Instant::now() >= deadline {
+            tracing::warn!(group, "No TLE set shared by the leader, fetching directly");
+            break;
+        }
+        tokio::time::sleep(scheduler::leader::LEASE_TTL / 6).await;
+    }
+    let path = match collectors::tle_fetcher::fresh_cached(config, group).filter(|_| reuse_cached) {
+        Some(path) => {
+            info!(group, path = %path.display(), "Using the TLE set cached within the refresh interval");
+            path
+        }
+        None => collectors::tle_fetcher::fetch_celestrak_group(config, group).await?,
+    };
+    if leadership.is_leader() {
+        if let Ok(text) = std::fs::read_to_string(&path) {
+            leadership.share_tle(group, &text).await;
+        }
+    }
+    Ok(path)
+}
+
+/// How long a downloaded SATCAT is used before it is fetched again.
+const SATCAT_MAX_AGE: chrono::Duration = chrono::Duration::days(7);
+
+/// Downloads the SATCAT when the stored copy is missing or older than
+/// `SATCAT_MAX_AGE`; failures keep the old copy.
+async fn refresh_satcat(config: &Config) {
+    let fetched_at = utils::db::open_or_init().ok().and_then(|c| utils::db::satcat_fetched_at(&c).ok().flatten());
+    let fresh = fetched_at
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(&t).ok())
+        .is_some_and(|t| chrono::Utc::now() - t.with_timezone(&chrono::Utc) < SATCAT_MAX_AGE);
+    if fresh {
+        return;
+    }
+    match collectors::satcat::fetch_satcat(&config.celestrak).await {
+        Ok(entries) if entries.is_empty() => tracing::warn!("SATCAT download had no rows; keeping the stored copy"),
+        Ok(entries) => {
+            let now = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+            match utils::db::open_or_init().and_then(|c| utils::db::replace_satcat(&c, &entries, &now)) {
+                Ok(n) => info!(count = n, "Stored SATCAT"),
+                Err(e) => tracing::warn!(error = %e, "Failed to store SATCAT"),
+            }
+        }
+        Err(e) => tracing::warn!(error = %e, "Failed to fetch SATCAT"),
+    }
+}
+
+/// Appends a Celestrak group fetch to the fetch log (leader only), including the
+/// TLE entries that were rejected; failures to log are only warned about.
+fn record_fetch(leadership: &Leadership, group: &str, records: usize, rejected: &[core::tle::RejectedTle], error: Option<&str>) {
+    if !leadership.is_leader() {
+        return;
+    }
+    let fetched_at = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    let source = format!("celestrak:{}", group);
+    if let Err(e) = utils::db::open_or_init().and_then(|c| utils::db::insert_fetch_log(&c, &fetched_at, &source, records, rejected, error)) {
+        tracing::warn!(error = %e, "Failed to record fetch");
+    }
+}
@@ -0,0 +1,106 @@
+use chrono::{DateTime, Duration, Utc};
+use sgp4::Elements;
+
+use crate::core::aoi::AoiGeometry;
+use crate::core::coords::{eci_to_ecef, ecef_to_geodetic_height, gmst};
+use crate::core::orbit::minutes_since_epoch;
+use crate::predictors::events::bisect;
+use crate::predictors::groundtrack::swath_half_width_deg;
+
+/// Intervals between `start` and `end` during which the footprint of a
+/// nadir-pointing sensor with the given half-angle overlaps `aoi`. The footprint
+/// is sampled every `step_seconds` and its edges located by bisection, so an
+/// access shorter than the step can be missed.
+pub fn aoi_access(
+    el: &Elements,
+    aoi: &AoiGeometry,
+    half_angle_deg: f64,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    step_seconds: i64,
+) -> sgp4::Result<Vec<(DateTime<Utc>, DateTime<Utc>)>> {
+    // Footprint radius minus the distance from nadir to the area (degrees of arc)
+    intervals_where(el, start, end, step_seconds, |lat, lon, alt| swath_half_width_deg(alt, half_angle_deg) - aoi.distance_deg(lat, lon))
+}
+
+/// Intervals between `start` and `end` during which the subsatellite point lies
+/// inside one of the polygons of `region`, e.g. a country, located like
+/// [`aoi_access`].
+pub fn region_intervals(
+    el: &Elements,
+    region: &[AoiGeometry],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    step_seconds: i64,
+) -> sgp4::Result<Vec<(DateTime<Utc>, DateTime<Utc>)>> {
+    intervals_where(el, start, end, step_seconds, |lat, lon, _| if region.iter().any(|g| g.contains(lat, lon)) { 0.0 } else { -1.0 })
+}
+
+/// Intervals in which `margin(lat, lon, alt_km)` of the subsatellite point is not
+/// negative, sampled every `step_seconds` with the edges located by bisection.
+fn intervals_where(
+    el: &Elements,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    step_seconds: i64,
+    margin: impl Fn(f64, f64, f64) -> f64,
+) -> sgp4::Result<Vec<(DateTime<Utc>, DateTime<Utc>)>> {
+    let constants = sgp4::Constants::from_elements(el)?;
+    let margin = |t: DateTime<Utc>| {
+        constants.propagate(minutes_since_epoch(el, t)).map(|p| {
+            let (x, y, z) = eci_to_ecef(&p.position, gmst(t));
+            let (lat, lon, alt) = ecef_to_geodetic_height(x, y, z);
+            margin(lat, lon, alt)
+        })
+    };
+
+    let step = Duration::seconds(step_seconds.max(1));
+    let mut intervals = Vec::new();
+    let mut t0 = start;
+    let mut since = (margin(start)? >= 0.0).then_some(start);
+    while t0 < end {
+        let t1 = (t0 + step).min(end);
+        let m1 = margin(t1)?;
+        match since {
+            None if m1 >= 0.0 => since = Some(bisect(&margin, t0, t1)?),
+            Some(entry) if m1 < 0.0 => {
+                intervals.push((entry, bisect(&margin, t0, t1)?));
+                since = None;
+            }
+            _ => {}
+        }
+        t0 = t1;
+    }
+    if let Some(entry) = since {
+        intervals.push((entry, end));
+    }
+    Ok(intervals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::predictors::groundtrack::ground_track;
+
+    #[test]
+    fn access_brackets_the_overflight() {
+        let el = crate::testing::fixtures::catalog().swap_remove(0);
+        let start = el.datetime.and_utc();
+        let nadir = ground_track(&el, start + Duration::minutes(20), start + Duration::minutes(20), 1).unwrap()[0];
+        let (lat, lon) = (nadir.lat_deg, nadir.lon_deg);
+        let square = AoiGeometry::Polygon(vec![vec![
+            [lon - 0.5, lat - 0.5],
+            [lon + 0.5, lat - 0.5],
+            [lon + 0.5, lat + 0.5],
+            [lon - 0.5, lat + 0.5],
+            [lon - 0.5, lat - 0.5],
+        ]]);
+        let intervals = aoi_access(&el, &square, 30.0, start, start + Duration::minutes(60), 10).unwrap();
+        assert_eq!(intervals.len(), 1, "{:?}", intervals);
+        let (entry, exit) = intervals[0];
+        assert!(entry < nadir.time && nadir.time < exit);
+        // A 30° cone from the ISS reaches about 2° of arc, so the area is seen for roughly a minute
+        let seconds = (exit - entry).num_seconds();
+        assert!((40..150).contains(&seconds), "{}", seconds);
+    }
+}
@@ -0,0 +1,150 @@
+use chrono::{DateTime, Duration, Utc};
+use sgp4::Elements;
+
+use crate::core::catalog::Catalog;
+use crate::core::orbit::{minutes_since_epoch, perigee_apogee_radius_km};
+
+/// Upper bound on relative speed between two Earth orbiters (km/s), used to size the coarse gate.
+const MAX_RELATIVE_SPEED_KM_S: f64 = 16.0;
+/// Extra radial margin (km) applied to the perigee/apogee pre-filter to absorb SGP4 perturbations.
+const RADIAL_FILTER_MARGIN_KM: f64 = 25.0;
+
+#[derive(Debug, Clone)]
+pub struct ConjunctionEvent {
+    pub asset_norad_id: u64,
+    pub secondary_norad_id: u64,
+    pub secondary_name: Option<String>,
+    pub tca: DateTime<Utc>,
+    pub miss_distance_km: f64,
+    pub relative_speed_km_s: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ScreeningOptions {
+    /// Minutes to screen from the start time.
+    pub window_minutes: i64,
+    /// Coarse sampling step in seconds.
+    pub step_seconds: i64,
+    /// Close approaches below this distance are reported.
+    pub screening_distance_km: f64,
+}
+
+impl Default for ScreeningOptions {
+    fn default() -> Self {
+        Self { window_minutes: 24 * 60, step_seconds: 60, screening_distance_km: 10.0 }
+    }
+}
+
+/// Screen one protected asset against a catalog of element sets.
+/// - Objects whose perigee/apogee shell cannot overlap the asset's are skipped.
+/// - Remaining objects are sampled at `step_seconds`; each local distance minimum
+///   is refined by ternary search to find the time of closest approach (TCA).
+///
+/// Objects that fail to propagate are skipped; an asset that fails is an error.
+pub fn screen_asset(
+    asset: &Elements,
+    catalog: &Catalog,
+    start: DateTime<Utc>,
+    opts: ScreeningOptions,
+) -> sgp4::Result<Vec<ConjunctionEvent>> {
+    let asset_consts = sgp4::Constants::from_elements(asset)?;
+    let steps = (opts.window_minutes * 60 / opts.step_seconds.max(1)).max(1) as usize;
+    let times: Vec<DateTime<Utc>> = (0..=steps)
+        .map(|i| start + Duration::seconds(i as i64 * opts.step_seconds))
+        .collect();
+    let mut asset_pos = Vec::with_capacity(times.len());
+    for t in &times {
+        asset_pos.push(asset_consts.propagate(minutes_since_epoch(asset, *t))?.position);
+    }
+
+    let (asset_rp, asset_ra) = perigee_apogee_radius_km(asset);
+    let margin = opts.screening_distance_km + RADIAL_FILTER_MARGIN_KM;
+    let coarse_gate = opts.screening_distance_km + MAX_RELATIVE_SPEED_KM_S * opts.step_seconds as f64;
+
+    let mut events = Vec::new();
+    for (index, other) in catalog.iter().enumerate() {
+        if other.norad_id == asset.norad_id {
+            continue;
+        }
+        let (rp, ra) = perigee_apogee_radius_km(other);
+        if rp - margin > asset_ra || ra + margin < asset_rp {
+            continue;
+        }
+        let consts = match sgp4::Constants::from_elements(other) {
+            Ok(c) => c,
+            Err(_) => continue,
+        };
+
+        let mut dist = Vec::with_capacity(times.len());
+        for (t, p) in times.iter().zip(asset_pos.iter()) {
+            match consts.propagate(minutes_since_epoch(other, *t)) {
+                Ok(pred) => dist.push(distance(p, &pred.position)),
+                Err(_) => dist.push(f64::INFINITY),
+            }
+        }
+
+        for i in 0..dist.len() {
+            let prev = if i > 0 { dist[i - 1] } else { f64::INFINITY };
+            let next = if i + 1 < dist.len() { dist[i + 1] } else { f64::INFINITY };
+            if !(dist[i] <= prev && dist[i] <= next && dist[i] < coarse_gate) {
+                continue;
+            }
+            let lo = times[i.saturating_sub(1)];
+            let hi = times[(i + 1).min(times.len() - 1)];
+            if let Some(ev) = refine_tca(asset, &asset_consts, other, catalog.name(index), &consts, lo, hi) {
+                if ev.miss_distance_km < opts.screening_distance_km {
+                    events.push(ev);
+                }
+            }
+        }
+    }
+
+    events.sort_by(|a, b| a.miss_distance_km.total_cmp(&b.miss_distance_km));
+    Ok(events)
+}
+
+/// Ternary search for the minimum separation in `[lo, hi]`, down to millisecond resolution.
+fn refine_tca(
+    asset: &Elements,
+    asset_consts: &sgp4::Constants,
+    other: &Elements,
+    other_name: Option<&str>,
+    other_consts: &sgp4::Constants,
+    lo: DateTime<Utc>,
+    hi: DateTime<Utc>,
+) -> Option<ConjunctionEvent> {
+    let state_at = |t: DateTime<Utc>| -> Option<(sgp4::Prediction, sgp4::Prediction)> {
+        let a = asset_consts.propagate(minutes_since_epoch(asset, t)).ok()?;
+        let b = other_consts.propagate(minutes_since_epoch(other, t)).ok()?;
+        Some((a, b))
+    };
+    let sep = |t: DateTime<Utc>| state_at(t).map(|(a, b)| distance(&a.position, &b.position)).unwrap_or(f64::INFINITY);
+
+    let mut lo = lo;
+    let mut hi = hi;
+    while (hi - lo).num_milliseconds() > 2 {
+        let third = (hi - lo) / 3;
+        let m1 = lo + third;
+        let m2 = hi - third;
+        if sep(m1) < sep(m2) {
+            hi = m2;
+        } else {
+            lo = m1;
+        }
+    }
+
+    let tca = lo + (hi - lo) / 2;
+    let (a, b) = state_at(tca)?;
+    Some(ConjunctionEvent {
+        asset_norad_id: asset.norad_id,
+        secondary_norad_id: other.norad_id,
+        secondary_name: other_name.map(str::to_string),
+        tca,
+        miss_distance_km: distance(&a.position, &b.position),
+        relative_speed_km_s: distance(&a.velocity, &b.velocity),
+    })
+}
+
+fn distance(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+}
@@ -0,0 +1,121 @@
+use chrono::{DateTime, Duration, Utc};
+use sgp4::Elements;
+
+use crate::core::coords::subsatellite_point;
+use crate::core::orbit::{minutes_since_epoch, perigee_apogee_radius_km, semi_major_axis_km, EARTH_RADIUS_KM};
+
+/// Relative half-width of the reported re-entry window.
+pub const UNCERTAINTY_FRACTION: f64 = 0.2;
+/// Lifetimes beyond this are reported as "no re-entry predicted".
+const MAX_LIFETIME_DAYS: f64 = 365.0 * 50.0;
+
+/// Atmospheric density scale height (km) by altitude (km), exponential model.
+const SCALE_HEIGHTS: [(f64, f64); 14] = [
+    (150.0, 22.5),
+    (180.0, 29.7),
+    (200.0, 37.1),
+    (250.0, 45.5),
+    (300.0, 53.6),
+    (350.0, 53.3),
+    (400.0, 58.5),
+    (450.0, 60.8),
+    (500.0, 63.8),
+    (600.0, 71.8),
+    (700.0, 88.7),
+    (800.0, 124.6),
+    (900.0, 181.0),
+    (1000.0, 268.0),
+];
+
+#[derive(Debug, Clone)]
+pub struct DecayEstimate {
+    pub element_epoch: DateTime<Utc>,
+    pub reentry: DateTime<Utc>,
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+    pub lifetime_days: f64,
+    pub perigee_alt_km: f64,
+}
+
+/// Estimates the re-entry epoch from the element set's mean motion derivative.
+/// Uses the King-Hele circular-orbit approximation: remaining lifetime is the density
+/// scale height at perigee divided by the current semi-major axis decay rate,
+/// `da/dt = -(2/3) a ndot / n`. `mean_motion_dot` is the TLE field (ndot / 2, rev/day^2).
+/// Returns `None` when the orbit is not decaying or the lifetime is too long to matter.
+pub fn estimate_reentry(elements: &Elements) -> Option<DecayEstimate> {
+    let ndot = 2.0 * elements.mean_motion_dot;
+    if ndot.is_nan() || ndot <= 0.0 || elements.mean_motion <= 0.0 {
+        return None;
+    }
+    let a = semi_major_axis_km(elements);
+    let (rp, _) = perigee_apogee_radius_km(elements);
+    let perigee_alt_km = rp - EARTH_RADIUS_KM;
+
+    let decay_km_per_day = 2.0 / 3.0 * a * ndot / elements.mean_motion;
+    let lifetime_days = scale_height_km(perigee_alt_km) / decay_km_per_day;
+    if !lifetime_days.is_finite() || lifetime_days > MAX_LIFETIME_DAYS {
+        return None;
+    }
+
+    let element_epoch = elements.datetime.and_utc();
+    let days = |d: f64| Duration::milliseconds((d * 86_400_000.0) as i64);
+    let reentry = element_epoch + days(lifetime_days);
+    let half_window = days(lifetime_days * UNCERTAINTY_FRACTION);
+    Some(DecayEstimate {
+        element_epoch,
+        reentry,
+        window_start: reentry - half_window,
+        window_end: reentry + half_window,
+        lifetime_days,
+        perigee_alt_km,
+    })
+}
+
+/// Ground track of the final `revolutions` before `end`, split into segments at the
+/// antimeridian. Points are `(lon, lat)` in degrees (GeoJSON order). Sampling stops
+/// early if SGP4 gives up on the decaying orbit.
+pub fn final_ground_track(elements: &Elements, end: DateTime<Utc>, revolutions: f64, step_seconds: i64) -> Vec<Vec<[f64; 2]>> {
+    let Ok(constants) = sgp4::Constants::from_elements(elements) else {
+        return Vec::new();
+    };
+    let period_s = 86_400.0 / elements.mean_motion;
+    let start = end - Duration::seconds((period_s * revolutions) as i64);
+
+    let mut segments: Vec<Vec<[f64; 2]>> = vec![Vec::new()];
+    let mut t = start;
+    while t <= end {
+        let Ok(pred) = constants.propagate(minutes_since_epoch(elements, t)) else {
+            break;
+        };
+        let (lat, lon) = subsatellite_point(&pred.position, t);
+        let wraps = segments
+            .last()
+            .and_then(|s| s.last())
+            .is_some_and(|prev| (lon - prev[0]).abs() > 180.0);
+        if wraps {
+            segments.push(Vec::new());
+        }
+        if let Some(seg) = segments.last_mut() {
+            seg.push([lon, lat]);
+        }
+        t += Duration::seconds(step_seconds.max(1));
+    }
+    segments.retain(|s| s.len() >= 2);
+    segments
+}
+
+fn scale_height_km(alt_km: f64) -> f64 {
+    let first = SCALE_HEIGHTS[0];
+    let last = SCALE_HEIGHTS[SCALE_HEIGHTS.len() - 1];
+    if alt_km <= first.0 {
+        return first.1;
+    }
+    if alt_km >= last.0 {
+        return last.1;
+    }
+    SCALE_HEIGHTS
+        .windows(2)
+        .find(|w| alt_km <= w[1].0)
+        .map(|w| w[0].1 + (w[1].1 - w[0].1) * (alt_km - w[0].0) / (w[1].0 - w[0].0))
+        .unwrap_or(last.1)
+}
@@ -0,0 +1,143 @@
+use chrono::{DateTime, Duration, Utc};
+use thiserror::Error;
+
+use crate::core::orbit::ric_components;
+
+#[derive(Debug, Error)]
+pub enum EphemerisError {
+    #[error("propagation failed at {time}: {source}")]
+    Propagation { time: DateTime<Utc>, source: sgp4::Error },
+    #[error("{time} is outside the ephemeris span")]
+    OutOfSpan { time: DateTime<Utc> },
+}
+
+/// Cartesian TEME state (km, km/s).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StateVector {
+    pub time: DateTime<Utc>,
+    pub position: [f64; 3],
+    pub velocity: [f64; 3],
+}
+
+/// Something that yields a state at any time within its span: an element set
+/// propagated with SGP4 (kept as its epoch and SGP4 constants), or a tabulated
+/// ephemeris interpolated between points.
+pub enum Ephemeris {
+    Elements(Box<(DateTime<Utc>, sgp4::Constants<'static>)>),
+    Table(Vec<StateVector>),
+}
+
+impl Ephemeris {
+    pub fn from_elements(el: &sgp4::Elements) -> Result<Ephemeris, sgp4::Error> {
+        let constants = sgp4::Constants::from_elements(el)?;
+        Ok(Ephemeris::Elements(Box::new((el.datetime.and_utc(), constants))))
+    }
+
+    /// Time span with data: the whole timeline for element sets.
+    pub fn span(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        match self {
+            Ephemeris::Elements(_) => None,
+            Ephemeris::Table(states) => Some((states.first()?.time, states.last()?.time)),
+        }
+    }
+
+    /// Epoch of an element set, or the first tabulated time.
+    pub fn epoch(&self) -> Option<DateTime<Utc>> {
+        match self {
+            Ephemeris::Elements(sgp4) => Some(sgp4.0),
+            Ephemeris::Table(states) => states.first().map(|s| s.time),
+        }
+    }
+
+    pub fn state_at(&self, time: DateTime<Utc>) -> Result<StateVector, EphemerisError> {
+        match self {
+            Ephemeris::Elements(sgp4) => {
+                let (epoch, constants) = sgp4.as_ref();
+                let pred = constants
+                    .propagate((time - *epoch).num_milliseconds() as f64 / 60_000.0)
+                    .map_err(|source| EphemerisError::Propagation { time, source })?;
+                Ok(StateVector { time, position: pred.position, velocity: pred.velocity })
+            }
+            Ephemeris::Table(states) => {
+                let after = states.partition_point(|s| s.time < time);
+                match (after.checked_sub(1).map(|i| &states[i]), states.get(after)) {
+                    (_, Some(s)) if s.time == time => Ok(*s),
+                    (Some(a), Some(b)) => Ok(hermite(a, b, time)),
+                    _ => Err(EphemerisError::OutOfSpan { time }),
+                }
+            }
+        }
+    }
+}
+
+/// Cubic Hermite interpolation between two states, using the velocities as the
+/// end-point derivatives.
+fn hermite(a: &StateVector, b: &StateVector, time: DateTime<Utc>) -> StateVector {
+    let h = (b.time - a.time).num_milliseconds() as f64 / 1000.0;
+    let s = (time - a.time).num_milliseconds() as f64 / 1000.0 / h;
+    let (s2, s3) = (s * s, s * s * s);
+    let (h00, h10, h01, h11) = (2.0 * s3 - 3.0 * s2 + 1.0, s3 - 2.0 * s2 + s, -2.0 * s3 + 3.0 * s2, s3 - s2);
+    let (d00, d10, d01, d11) = (6.0 * s2 - 6.0 * s, 3.0 * s2 - 4.0 * s + 1.0, -6.0 * s2 + 6.0 * s, 3.0 * s2 - 2.0 * s);
+    let mut out = StateVector { time, position: [0.0; 3], velocity: [0.0; 3] };
+    for i in 0..3 {
+        let (p0, v0, p1, v1) = (a.position[i], a.velocity[i] * h, b.position[i], b.velocity[i] * h);
+        out.position[i] = h00 * p0 + h10 * v0 + h01 * p1 + h11 * v1;
+        out.velocity[i] = (d00 * p0 + d10 * v0 + d01 * p1 + d11 * v1) / h;
+    }
+    out
+}
+
+/// Position of `other` relative to `reference` at one time, in the reference's
+/// radial / in-track / cross-track frame (km).
+#[derive(Debug, Clone, Copy)]
+pub struct RicDifference {
+    pub time: DateTime<Utc>,
+    pub radial_km: f64,
+    pub along_track_km: f64,
+    pub cross_track_km: f64,
+}
+
+impl RicDifference {
+    pub fn total_km(&self) -> f64 {
+        (self.radial_km.powi(2) + self.along_track_km.powi(2) + self.cross_track_km.powi(2)).sqrt()
+    }
+}
+
+/// Differences between two ephemerides every `step` over `[start, end]`.
+pub fn compare(reference: &Ephemeris, other: &Ephemeris, start: DateTime<Utc>, end: DateTime<Utc>, step: Duration) -> Result<Vec<RicDifference>, EphemerisError> {
+    let mut out = Vec::new();
+    let mut t = start;
+    while t <= end {
+        let r = reference.state_at(t)?;
+        let o = other.state_at(t)?;
+        let delta = [o.position[0] - r.position[0], o.position[1] - r.position[1], o.position[2] - r.position[2]];
+        let [radial_km, along_track_km, cross_track_km] = ric_components(&r.position, &r.velocity, &delta);
+        out.push(RicDifference { time: t, radial_km, along_track_km, cross_track_km });
+        t += step;
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tabulated_sgp4_states_interpolate_back_to_the_propagation() {
+        let el = sgp4::Elements::from_tle(
+            Some("ISS (ZARYA)".to_string()),
+            b"1 25544U 98067A   08264.51782528 -.00002182  00000-0 -11606-4 0  2927",
+            b"2 25544  51.6416 247.4627 0006703 130.5360 325.0288 15.72125391563537",
+        )
+        .unwrap();
+        let start = el.datetime.and_utc();
+        let sgp4 = Ephemeris::from_elements(&el).unwrap();
+        let table: Vec<StateVector> = (0..=120).map(|i| sgp4.state_at(start + Duration::seconds(60 * i)).unwrap()).collect();
+        let table = Ephemeris::Table(table);
+
+        let diffs = compare(&sgp4, &table, start + Duration::seconds(30), start + Duration::minutes(119), Duration::minutes(7)).unwrap();
+        assert_eq!(diffs.len(), 17);
+        assert!(diffs.iter().all(|d| d.total_km() < 0.01), "{:?}", diffs);
+        assert!(matches!(table.state_at(start + Duration::hours(3)), Err(EphemerisError::OutOfSpan { .. })));
+    }
+}
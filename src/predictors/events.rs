@@ -0,0 +1,146 @@
+use chrono::{DateTime, Duration, Utc};
+use sgp4::Elements;
+
+use crate::core::coords::{eci_to_ecef, ecef_to_geodetic_height, gmst};
+use crate::core::orbit::{dot, minutes_since_epoch};
+use crate::core::sun::shadow_margin_km;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrbitalEventKind {
+    Perigee,
+    Apogee,
+    AscendingNode,
+    DescendingNode,
+    EclipseEntry,
+    EclipseExit,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct OrbitalEvent {
+    pub kind: OrbitalEventKind,
+    pub time: DateTime<Utc>,
+    pub latitude_deg: f64,
+    pub longitude_deg: f64,
+    pub altitude_km: f64,
+}
+
+/// Samples per orbital period when bracketing events.
+const SAMPLES_PER_REV: f64 = 72.0;
+
+/// Apsis passages (radial velocity changes sign) and equator crossings (ECI z
+/// changes sign) between `start` and `end`, located by bisection to the millisecond.
+pub fn orbital_events(el: &Elements, start: DateTime<Utc>, end: DateTime<Utc>) -> sgp4::Result<Vec<OrbitalEvent>> {
+    let constants = sgp4::Constants::from_elements(el)?;
+    let state = |t: DateTime<Utc>| constants.propagate(minutes_since_epoch(el, t));
+    let radial = |t: DateTime<Utc>| state(t).map(|p| dot(&p.position, &p.velocity));
+    let height = |t: DateTime<Utc>| state(t).map(|p| p.position[2]);
+
+    let period_s = 86_400.0 / el.mean_motion.max(0.1);
+    let step = Duration::milliseconds(((period_s / SAMPLES_PER_REV) * 1000.0).max(1000.0) as i64);
+
+    let mut events = Vec::new();
+    let mut t0 = start;
+    let (mut r0, mut z0) = (radial(t0)?, height(t0)?);
+    while t0 < end {
+        let t1 = (t0 + step).min(end);
+        let (r1, z1) = (radial(t1)?, height(t1)?);
+        if r0 < 0.0 && r1 >= 0.0 {
+            events.push(describe(el, &constants, OrbitalEventKind::Perigee, bisect(&radial, t0, t1)?)?);
+        } else if r0 > 0.0 && r1 <= 0.0 {
+            events.push(describe(el, &constants, OrbitalEventKind::Apogee, bisect(&radial, t0, t1)?)?);
+        }
+        if z0 < 0.0 && z1 >= 0.0 {
+            events.push(describe(el, &constants, OrbitalEventKind::AscendingNode, bisect(&height, t0, t1)?)?);
+        } else if z0 > 0.0 && z1 <= 0.0 {
+            events.push(describe(el, &constants, OrbitalEventKind::DescendingNode, bisect(&height, t0, t1)?)?);
+        }
+        (t0, r0, z0) = (t1, r1, z1);
+    }
+    events.sort_by_key(|e| e.time);
+    Ok(events)
+}
+
+/// Entries into and exits from the Earth's (cylindrical) shadow between `start`
+/// and `end`, located like [`orbital_events`].
+pub fn eclipse_events(el: &Elements, start: DateTime<Utc>, end: DateTime<Utc>) -> sgp4::Result<Vec<OrbitalEvent>> {
+    let constants = sgp4::Constants::from_elements(el)?;
+    let margin = |t: DateTime<Utc>| constants.propagate(minutes_since_epoch(el, t)).map(|p| shadow_margin_km(&p.position, t));
+
+    let period_s = 86_400.0 / el.mean_motion.max(0.1);
+    let step = Duration::milliseconds(((period_s / SAMPLES_PER_REV) * 1000.0).max(1000.0) as i64);
+
+    let mut events = Vec::new();
+    let mut t0 = start;
+    let mut m0 = margin(t0)?;
+    while t0 < end {
+        let t1 = (t0 + step).min(end);
+        let m1 = margin(t1)?;
+        if m0 >= 0.0 && m1 < 0.0 {
+            events.push(describe(el, &constants, OrbitalEventKind::EclipseEntry, bisect(&margin, t0, t1)?)?);
+        } else if m0 < 0.0 && m1 >= 0.0 {
+            events.push(describe(el, &constants, OrbitalEventKind::EclipseExit, bisect(&margin, t0, t1)?)?);
+        }
+        (t0, m0) = (t1, m1);
+    }
+    Ok(events)
+}
+
+/// Root of `f` in [a, b], assuming a sign change.
+pub fn bisect(
+    f: &impl Fn(DateTime<Utc>) -> sgp4::Result<f64>,
+    mut a: DateTime<Utc>,
+    mut b: DateTime<Utc>,
+) -> sgp4::Result<DateTime<Utc>> {
+    let fa_negative = f(a)? < 0.0;
+    while (b - a).num_milliseconds() > 1 {
+        let mid = a + (b - a) / 2;
+        if (f(mid)? < 0.0) == fa_negative {
+            a = mid;
+        } else {
+            b = mid;
+        }
+    }
+    Ok(a + (b - a) / 2)
+}
+
+fn describe(el: &Elements, constants: &sgp4::Constants, kind: OrbitalEventKind, time: DateTime<Utc>) -> sgp4::Result<OrbitalEvent> {
+    let pred = constants.propagate(minutes_since_epoch(el, time))?;
+    let (x, y, z) = eci_to_ecef(&pred.position, gmst(time));
+    let (latitude_deg, longitude_deg, altitude_km) = ecef_to_geodetic_height(x, y, z);
+    Ok(OrbitalEvent { kind, time, latitude_deg, longitude_deg, altitude_km })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn finds_nodes_and_apsides_once_per_revolution() {
+        let el = Elements::from_tle(
+            Some("ISS (ZARYA)".to_string()),
+            b"1 25544U 98067A   08264.51782528 -.00002182  00000-0 -11606-4 0  2927",
+            b"2 25544  51.6416 247.4627 0006703 130.5360 325.0288 15.72125391563537",
+        )
+        .unwrap();
+        let start = Utc.with_ymd_and_hms(2008, 9, 20, 12, 0, 0).unwrap();
+        let events = orbital_events(&el, start, start + Duration::hours(24)).unwrap();
+        let count = |k: OrbitalEventKind| events.iter().filter(|e| e.kind == k).count();
+        for kind in [OrbitalEventKind::Perigee, OrbitalEventKind::Apogee, OrbitalEventKind::AscendingNode, OrbitalEventKind::DescendingNode] {
+            assert!((15..=16).contains(&count(kind)), "{:?}: {}", kind, count(kind));
+        }
+        for e in events.iter().filter(|e| e.kind == OrbitalEventKind::AscendingNode) {
+            assert!(e.latitude_deg.abs() < 0.01, "node latitude {}", e.latitude_deg);
+        }
+
+        let eclipses = eclipse_events(&el, start, start + Duration::hours(24)).unwrap();
+        assert!(eclipses.len() >= 30, "{} eclipse events", eclipses.len());
+        for pair in eclipses.windows(2) {
+            assert_ne!(pair[0].kind, pair[1].kind);
+            if pair[0].kind == OrbitalEventKind::EclipseEntry {
+                assert!((pair[1].time - pair[0].time) < Duration::minutes(40));
+            }
+        }
+    }
+}
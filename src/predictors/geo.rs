@@ -0,0 +1,34 @@
+use chrono::{DateTime, Utc};
+use sgp4::Elements;
+
+use crate::core::coords::subsatellite_point;
+use crate::core::orbit::minutes_since_epoch;
+
+/// Earth's rotation in revolutions per day (sidereal).
+pub const SIDEREAL_REV_PER_DAY: f64 = 1.002_737_909;
+
+/// Geosynchronous objects: one revolution per sidereal day within ±1%, on a
+/// near-circular orbit. Inclined and drifting objects are included; pass scans
+/// are meaningless for all of them.
+pub fn is_geosynchronous(el: &Elements) -> bool {
+    (el.mean_motion / SIDEREAL_REV_PER_DAY - 1.0).abs() < 0.01 && el.eccentricity < 0.02
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct GeoState {
+    pub longitude_deg: f64,
+    pub latitude_deg: f64,
+    /// Longitude drift relative to the rotating Earth, positive eastwards.
+    pub drift_deg_per_day: f64,
+}
+
+/// Subsatellite point and longitude drift of a geosynchronous object at `t`.
+pub fn geo_state(el: &Elements, t: DateTime<Utc>) -> sgp4::Result<GeoState> {
+    let pred = sgp4::Constants::from_elements(el)?.propagate(minutes_since_epoch(el, t))?;
+    let (latitude_deg, longitude_deg) = subsatellite_point(&pred.position, t);
+    Ok(GeoState {
+        longitude_deg,
+        latitude_deg,
+        drift_deg_per_day: (el.mean_motion - SIDEREAL_REV_PER_DAY) * 360.0,
+    })
+}
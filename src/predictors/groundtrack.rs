@@ -0,0 +1,141 @@
+use chrono::{DateTime, Duration, Utc};
+use sgp4::Elements;
+
+use crate::core::coords::{eci_to_ecef, ecef_to_geodetic_height, gmst};
+use crate::core::orbit::{minutes_since_epoch, EARTH_RADIUS_KM};
+
+#[derive(Debug, Clone, Copy)]
+pub struct GroundTrackPoint {
+    pub time: DateTime<Utc>,
+    pub lat_deg: f64,
+    pub lon_deg: f64,
+    pub alt_km: f64,
+}
+
+/// Subsatellite points from `start` to `end` every `step_seconds`.
+pub fn ground_track(el: &Elements, start: DateTime<Utc>, end: DateTime<Utc>, step_seconds: i64) -> sgp4::Result<Vec<GroundTrackPoint>> {
+    let constants = sgp4::Constants::from_elements(el)?;
+    let mut out = Vec::new();
+    let mut t = start;
+    while t <= end {
+        let pred = constants.propagate(minutes_since_epoch(el, t))?;
+        let (x, y, z) = eci_to_ecef(&pred.position, gmst(t));
+        let (lat_deg, lon_deg, alt_km) = ecef_to_geodetic_height(x, y, z);
+        out.push(GroundTrackPoint { time: t, lat_deg, lon_deg, alt_km });
+        t += Duration::seconds(step_seconds.max(1));
+    }
+    Ok(out)
+}
+
+/// Earth central angle (degrees) between nadir and the footprint edge of a
+/// nadir-pointing sensor with the given half-angle, on a spherical Earth. Capped
+/// at the horizon when the cone misses the Earth.
+pub fn swath_half_width_deg(alt_km: f64, half_angle_deg: f64) -> f64 {
+    let ratio = (EARTH_RADIUS_KM + alt_km) / EARTH_RADIUS_KM;
+    let eta = half_angle_deg.to_radians();
+    let sin_incidence = ratio * eta.sin();
+    if sin_incidence >= 1.0 {
+        return (1.0 / ratio).acos().to_degrees();
+    }
+    (sin_incidence.asin() - eta).to_degrees()
+}
+
+fn bearing_rad(from: &GroundTrackPoint, to: &GroundTrackPoint) -> f64 {
+    let (lat1, lat2) = (from.lat_deg.to_radians(), to.lat_deg.to_radians());
+    let dlon = (to.lon_deg - from.lon_deg).to_radians();
+    (dlon.sin() * lat2.cos()).atan2(lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * dlon.cos())
+}
+
+/// `(lon, lat)` reached from a point along a great circle.
+fn destination(lat_deg: f64, lon_deg: f64, bearing: f64, distance_deg: f64) -> [f64; 2] {
+    let (lat, d) = (lat_deg.to_radians(), distance_deg.to_radians());
+    let lat2 = (lat.sin() * d.cos() + lat.cos() * d.sin() * bearing.cos()).asin();
+    let lon2 = lon_deg.to_radians() + (bearing.sin() * d.sin() * lat.cos()).atan2(d.cos() - lat.sin() * lat2.sin());
+    [(lon2.to_degrees() + 540.0).rem_euclid(360.0) - 180.0, lat2.to_degrees()]
+}
+
+/// Left and right swath edges `(lon, lat)` for each track point, perpendicular
+/// to the local ground track heading.
+pub fn swath_edges(track: &[GroundTrackPoint], half_angle_deg: f64) -> Vec<([f64; 2], [f64; 2])> {
+    if track.len() < 2 {
+        return Vec::new();
+    }
+    (0..track.len())
+        .map(|i| {
+            let prev = &track[i.saturating_sub(1)];
+            let next = &track[(i + 1).min(track.len() - 1)];
+            let heading = bearing_rad(prev, next);
+            let p = &track[i];
+            let width = swath_half_width_deg(p.alt_km, half_angle_deg);
+            let half_pi = std::f64::consts::FRAC_PI_2;
+            (
+                destination(p.lat_deg, p.lon_deg, heading - half_pi, width),
+                destination(p.lat_deg, p.lon_deg, heading + half_pi, width),
+            )
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct SwathPolygon {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Closed ring of `(lon, lat)` points.
+    pub ring: Vec<[f64; 2]>,
+}
+
+/// Swath polygons along the track, split wherever the track or either edge
+/// crosses the antimeridian.
+pub fn swath_polygons(track: &[GroundTrackPoint], half_angle_deg: f64) -> Vec<SwathPolygon> {
+    let edges = swath_edges(track, half_angle_deg);
+    let mut chunks: Vec<Vec<usize>> = vec![Vec::new()];
+    for (i, edge) in edges.iter().enumerate() {
+        let wraps = i > 0 && {
+            let (l0, r0) = edges[i - 1];
+            (edge.0[0] - l0[0]).abs() > 180.0
+                || (edge.1[0] - r0[0]).abs() > 180.0
+                || (track[i].lon_deg - track[i - 1].lon_deg).abs() > 180.0
+        };
+        if wraps {
+            chunks.push(Vec::new());
+        }
+        if let Some(chunk) = chunks.last_mut() {
+            chunk.push(i);
+        }
+    }
+    chunks
+        .into_iter()
+        .filter(|c| c.len() >= 2)
+        .map(|c| {
+            let mut ring: Vec<[f64; 2]> = c.iter().map(|&i| edges[i].0).collect();
+            ring.extend(c.iter().rev().map(|&i| edges[i].1));
+            ring.push(ring[0]);
+            SwathPolygon { start: track[c[0]].time, end: track[c[c.len() - 1]].time, ring }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swath_width_matches_geometry() {
+        // 700 km altitude, 15° half-angle: about 190 km to each side.
+        let km = swath_half_width_deg(700.0, 15.0).to_radians() * EARTH_RADIUS_KM;
+        assert!((km - 190.0).abs() < 5.0, "{}", km);
+        // A cone wider than the Earth is capped at the horizon.
+        assert!((swath_half_width_deg(700.0, 89.0) - (EARTH_RADIUS_KM / (EARTH_RADIUS_KM + 700.0)).acos().to_degrees()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn edges_are_perpendicular_to_a_northbound_track() {
+        let t = Utc::now();
+        let track: Vec<GroundTrackPoint> = (0..3)
+            .map(|i| GroundTrackPoint { time: t, lat_deg: i as f64, lon_deg: 10.0, alt_km: 700.0 })
+            .collect();
+        let (left, right) = swath_edges(&track, 15.0)[1];
+        assert!((left[1] - 1.0).abs() < 0.01 && left[0] < 10.0);
+        assert!((right[1] - 1.0).abs() < 0.01 && right[0] > 10.0);
+    }
+}
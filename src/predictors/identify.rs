@@ -0,0 +1,154 @@
+use chrono::{DateTime, Utc};
+use sgp4::Elements;
+
+use crate::core::coords::gmst;
+use crate::core::orbit::{minutes_since_epoch, SPEED_OF_LIGHT_KM_S};
+use crate::predictors::passes::{topocentric_motion, ObserverPosition};
+
+/// What was measured in one sighting.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Measurement {
+    /// Pointing of an antenna or camera (degrees).
+    Angles { az_deg: f64, el_deg: f64 },
+    /// A received carrier against its nominal frequency (Hz).
+    Doppler { frequency_hz: f64, nominal_hz: f64 },
+}
+
+#[derive(Debug, Clone)]
+pub struct Sighting {
+    pub time: DateTime<Utc>,
+    pub observer: ObserverPosition,
+    pub measurement: Measurement,
+}
+
+/// Expected noise of each kind of measurement, which weighs them against each other.
+#[derive(Debug, Clone, Copy)]
+pub struct Weights {
+    pub angle_sigma_deg: f64,
+    pub range_rate_sigma_km_s: f64,
+    /// Remove the mean range-rate residual before scoring, absorbing the
+    /// transmitter's frequency offset from nominal.
+    pub fit_frequency_offset: bool,
+}
+
+/// How well one candidate's elements explain the sightings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CandidateFit {
+    pub norad_id: u64,
+    pub name: Option<String>,
+    /// Root mean square of the residuals in units of their sigmas; lower is better.
+    pub score: f64,
+    pub angle_rms_deg: Option<f64>,
+    pub range_rate_rms_km_s: Option<f64>,
+    /// Mean range-rate residual removed as the frequency offset.
+    pub range_rate_bias_km_s: Option<f64>,
+    /// Sightings made while the candidate was below the observer's horizon.
+    pub below_horizon: usize,
+}
+
+/// Range rate (km/s) implied by a received frequency, positive receding.
+pub fn range_rate_from_doppler(frequency_hz: f64, nominal_hz: f64) -> f64 {
+    SPEED_OF_LIGHT_KM_S * (1.0 - frequency_hz / nominal_hz)
+}
+
+/// Great-circle angle (degrees) between two az/el directions.
+fn separation_deg(az1: f64, el1: f64, az2: f64, el2: f64) -> f64 {
+    let (az1, el1, az2, el2) = (az1.to_radians(), el1.to_radians(), az2.to_radians(), el2.to_radians());
+    let cos = el1.sin() * el2.sin() + el1.cos() * el2.cos() * (az1 - az2).cos();
+    cos.clamp(-1.0, 1.0).acos().to_degrees()
+}
+
+fn rms(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| (values.iter().map(|v| v * v).sum::<f64>() / values.len() as f64).sqrt())
+}
+
+/// Residuals of `sightings` against `el`; `None` when SGP4 fails at any of them.
+pub fn fit_candidate(el: &Elements, name: Option<&str>, sightings: &[Sighting], weights: &Weights) -> Option<CandidateFit> {
+    let mut angles = Vec::new();
+    let mut range_rates = Vec::new();
+    let mut below_horizon = 0;
+    let constants = sgp4::Constants::from_elements(el).ok()?;
+    for s in sightings {
+        let state = constants.propagate(minutes_since_epoch(el, s.time)).ok()?;
+        let look = topocentric_motion(&state.position, &state.velocity, gmst(s.time), &s.observer);
+        if look.el_deg < 0.0 {
+            below_horizon += 1;
+        }
+        match s.measurement {
+            Measurement::Angles { az_deg, el_deg } => angles.push(separation_deg(az_deg, el_deg, look.az_deg, look.el_deg)),
+            Measurement::Doppler { frequency_hz, nominal_hz } => {
+                let (az, el) = (look.az_deg.to_radians(), look.el_deg.to_radians());
+                let line_of_sight = [az.sin() * el.cos(), az.cos() * el.cos(), el.sin()];
+                let predicted: f64 = line_of_sight.iter().zip(look.velocity_enu_km_s).map(|(a, b)| a * b).sum();
+                range_rates.push(range_rate_from_doppler(frequency_hz, nominal_hz) - predicted);
+            }
+        }
+    }
+    let bias = (weights.fit_frequency_offset && !range_rates.is_empty()).then(|| range_rates.iter().sum::<f64>() / range_rates.len() as f64);
+    if let Some(bias) = bias {
+        range_rates.iter_mut().for_each(|r| *r -= bias);
+    }
+    let normalized: Vec<f64> = angles
+        .iter()
+        .map(|a| a / weights.angle_sigma_deg)
+        .chain(range_rates.iter().map(|r| r / weights.range_rate_sigma_km_s))
+        .collect();
+    Some(CandidateFit {
+        norad_id: el.norad_id,
+        name: name.map(str::to_string),
+        score: rms(&normalized).unwrap_or(0.0),
+        angle_rms_deg: rms(&angles),
+        range_rate_rms_km_s: rms(&range_rates),
+        range_rate_bias_km_s: bias,
+        below_horizon,
+    })
+}
+
+/// Candidates ranked best first: fewest sightings below the horizon, then
+/// lowest score. Candidates, each with its name, that cannot be propagated are
+/// left out.
+pub fn rank_candidates(candidates: &[(&Elements, Option<&str>)], sightings: &[Sighting], weights: &Weights) -> Vec<CandidateFit> {
+    let mut fits: Vec<CandidateFit> = candidates.iter().filter_map(|&(el, name)| fit_candidate(el, name, sightings, weights)).collect();
+    fits.sort_by(|a, b| a.below_horizon.cmp(&b.below_horizon).then(a.score.total_cmp(&b.score)));
+    fits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::orbit::propagate_minutes;
+    use crate::predictors::passes::predict_passes;
+
+    #[test]
+    fn the_observed_object_ranks_first() {
+        let catalog = crate::testing::fixtures::catalog();
+        let (truth, other) = (&catalog[0], &catalog[1]);
+        let observer = crate::testing::fixtures::STATION;
+        let start = truth.datetime.and_utc();
+        let pass = predict_passes(truth, observer.lat_deg, observer.lon_deg, start, 1440, 15, 10.0).unwrap().remove(0);
+
+        // Angles and a 437 MHz beacon with a 2 kHz oscillator offset over one pass
+        let nominal_hz = 437e6;
+        let mut sightings = Vec::new();
+        let mut t = pass.start;
+        while t < pass.end {
+            let state = propagate_minutes(truth, minutes_since_epoch(truth, t)).unwrap();
+            let look = topocentric_motion(&state.position, &state.velocity, gmst(t), &observer);
+            let (az, el) = (look.az_deg.to_radians(), look.el_deg.to_radians());
+            let range_rate: f64 = [az.sin() * el.cos(), az.cos() * el.cos(), el.sin()].iter().zip(look.velocity_enu_km_s).map(|(a, b)| a * b).sum();
+            let frequency_hz = nominal_hz * (1.0 - range_rate / SPEED_OF_LIGHT_KM_S) + 2000.0;
+            sightings.push(Sighting { time: t, observer, measurement: Measurement::Doppler { frequency_hz, nominal_hz } });
+            sightings.push(Sighting { time: t, observer, measurement: Measurement::Angles { az_deg: look.az_deg + 0.3, el_deg: look.el_deg } });
+            t += chrono::Duration::seconds(30);
+        }
+
+        let weights = Weights { angle_sigma_deg: 1.0, range_rate_sigma_km_s: 0.1, fit_frequency_offset: true };
+        let ranked = rank_candidates(&[(other, other.object_name.as_deref()), (truth, Some("TRUTH"))], &sightings, &weights);
+        assert_eq!((ranked[0].norad_id, ranked[0].name.as_deref()), (truth.norad_id, Some("TRUTH")));
+        assert_eq!(ranked[0].below_horizon, 0);
+        assert!(ranked[0].score < 1.0 && ranked[1].score > 10.0);
+        // The offset shows up as the fitted bias: 2 kHz at 437 MHz is about -1.37 km/s
+        let bias = ranked[0].range_rate_bias_km_s.unwrap();
+        assert!((bias + 2000.0 / nominal_hz * SPEED_OF_LIGHT_KM_S).abs() < 1e-6);
+    }
+}
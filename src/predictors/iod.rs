@@ -0,0 +1,260 @@
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
+use crate::core::coords::{az_el_to_ecef_direction, ecef_to_eci, geodetic_to_ecef, gmst};
+use crate::core::catalog::Catalog;
+use crate::core::orbit::{cross, dot, osculating_elements, MU_EARTH_KM3_S2};
+use crate::core::tle::MeanElements;
+
+/// Catalog number assigned to element sets produced by IOD.
+pub const IOD_NORAD_ID: u64 = 99_999;
+
+#[derive(Debug, Error)]
+pub enum IodError {
+    #[error("at least three observations at distinct times are required")]
+    NotEnoughObservations,
+    #[error("lines of sight are coplanar; observations are too close together")]
+    Degenerate,
+    #[error("no physical root for the middle range")]
+    NoRoot,
+    #[error("solution is not a bound orbit")]
+    Unbound,
+}
+
+/// An azimuth/elevation sighting from a known site.
+#[derive(Debug, Clone)]
+pub struct AngleObservation {
+    pub time: DateTime<Utc>,
+    pub lat_deg: f64,
+    pub lon_deg: f64,
+    pub height_km: f64,
+    pub az_deg: f64,
+    pub el_deg: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct IodSolution {
+    /// Time of the middle observation, at which the state is estimated.
+    pub epoch: DateTime<Utc>,
+    pub position_km: [f64; 3],
+    pub velocity_km_s: [f64; 3],
+    pub elements: MeanElements,
+}
+
+#[derive(Debug, Clone)]
+pub struct CatalogMatch {
+    pub norad_id: u64,
+    pub name: Option<String>,
+    /// Distance between the catalog object and the IOD position at the solution epoch.
+    pub distance_km: f64,
+}
+
+/// Gauss angles-only initial orbit determination.
+/// Uses the first, middle and last observation (by time) and returns the estimated
+/// TEME state at the middle observation together with osculating elements packaged
+/// as an element set. The elements are two-body osculating values, good enough to
+/// compare against the catalog but not a substitute for a fitted TLE.
+pub fn gauss(observations: &[AngleObservation]) -> Result<IodSolution, IodError> {
+    let mut obs: Vec<&AngleObservation> = observations.iter().collect();
+    obs.sort_by_key(|o| o.time);
+    obs.dedup_by_key(|o| o.time);
+    if obs.len() < 3 {
+        return Err(IodError::NotEnoughObservations);
+    }
+    let picked = [obs[0], obs[obs.len() / 2], obs[obs.len() - 1]];
+
+    // Site positions and line-of-sight unit vectors in the inertial frame.
+    let mut site = [[0.0f64; 3]; 3];
+    let mut los = [[0.0f64; 3]; 3];
+    for (i, o) in picked.iter().enumerate() {
+        let theta = gmst(o.time);
+        site[i] = ecef_to_eci(&geodetic_to_ecef(o.lat_deg, o.lon_deg, o.height_km), theta);
+        los[i] = ecef_to_eci(&az_el_to_ecef_direction(o.lat_deg, o.lon_deg, o.az_deg, o.el_deg), theta);
+    }
+
+    let secs = |a: DateTime<Utc>, b: DateTime<Utc>| (a - b).num_milliseconds() as f64 / 1000.0;
+    let tau1 = secs(picked[0].time, picked[1].time);
+    let tau3 = secs(picked[2].time, picked[1].time);
+    let tau = tau3 - tau1;
+
+    let p1 = cross(&los[1], &los[2]);
+    let p2 = cross(&los[0], &los[2]);
+    let p3 = cross(&los[0], &los[1]);
+    let d0 = dot(&los[0], &p1);
+    if d0.abs() < 1e-12 {
+        return Err(IodError::Degenerate);
+    }
+    let d = |i: usize, p: &[f64; 3]| dot(&site[i], p);
+    let (d11, d12, d13) = (d(0, &p1), d(0, &p2), d(0, &p3));
+    let (d21, d22, d23) = (d(1, &p1), d(1, &p2), d(1, &p3));
+    let (d31, d32, d33) = (d(2, &p1), d(2, &p2), d(2, &p3));
+
+    let mu = MU_EARTH_KM3_S2;
+    let a = (-d12 * tau3 / tau + d22 + d32 * tau1 / tau) / d0;
+    let b = (d12 * (tau3 * tau3 - tau * tau) * tau3 / tau + d32 * (tau * tau - tau1 * tau1) * tau1 / tau) / (6.0 * d0);
+    let e = dot(&site[1], &los[1]);
+    let r2_site = dot(&site[1], &site[1]);
+
+    let ca = -(a * a + 2.0 * a * e + r2_site);
+    let cb = -2.0 * mu * b * (a + e);
+    let cc = -(mu * mu) * b * b;
+    let poly = |x: f64| x.powi(8) + ca * x.powi(6) + cb * x.powi(3) + cc;
+
+    let r2 = find_range_root(poly, |x| a + mu * b / x.powi(3) > 0.0).ok_or(IodError::NoRoot)?;
+    let r2_3 = r2.powi(3);
+
+    let rho1 = ((6.0 * (d31 * tau1 / tau3 + d21 * tau / tau3) * r2_3 + mu * d31 * (tau * tau - tau1 * tau1) * tau1 / tau3)
+        / (6.0 * r2_3 + mu * (tau * tau - tau3 * tau3))
+        - d11)
+        / d0;
+    let rho2 = a + mu * b / r2_3;
+    let rho3 = ((6.0 * (d13 * tau3 / tau1 - d23 * tau / tau1) * r2_3 + mu * d13 * (tau * tau - tau3 * tau3) * tau3 / tau1)
+        / (6.0 * r2_3 + mu * (tau * tau - tau1 * tau1))
+        - d33)
+        / d0;
+
+    let pos = |i: usize, rho: f64| [site[i][0] + rho * los[i][0], site[i][1] + rho * los[i][1], site[i][2] + rho * los[i][2]];
+    let r1 = pos(0, rho1);
+    let r2v = pos(1, rho2);
+    let r3 = pos(2, rho3);
+
+    // Lagrange coefficients truncated after the first perturbation term.
+    let f1 = 1.0 - mu * tau1 * tau1 / (2.0 * r2_3);
+    let f3 = 1.0 - mu * tau3 * tau3 / (2.0 * r2_3);
+    let g1 = tau1 - mu * tau1.powi(3) / (6.0 * r2_3);
+    let g3 = tau3 - mu * tau3.powi(3) / (6.0 * r2_3);
+    let den = f1 * g3 - f3 * g1;
+    let v2 = [
+        (-f3 * r1[0] + f1 * r3[0]) / den,
+        (-f3 * r1[1] + f1 * r3[1]) / den,
+        (-f3 * r1[2] + f1 * r3[2]) / den,
+    ];
+
+    let osc = osculating_elements(&r2v, &v2).ok_or(IodError::Unbound)?;
+    let epoch = picked[1].time;
+    Ok(IodSolution {
+        epoch,
+        position_km: r2v,
+        velocity_km_s: v2,
+        elements: MeanElements {
+            norad_id: IOD_NORAD_ID,
+            epoch: epoch.naive_utc(),
+            inclination_deg: osc.inclination_deg,
+            raan_deg: osc.raan_deg,
+            eccentricity: osc.eccentricity,
+            arg_perigee_deg: osc.arg_perigee_deg,
+            mean_anomaly_deg: osc.mean_anomaly_deg,
+            mean_motion_rev_day: osc.mean_motion_rev_day,
+            bstar: 0.0,
+        },
+    })
+}
+
+/// Ranks catalog objects by distance from the IOD position at the solution epoch.
+pub fn rank_catalog(solution: &IodSolution, catalog: &Catalog, limit: usize) -> Vec<CatalogMatch> {
+    let mut matches: Vec<CatalogMatch> = catalog
+        .named()
+        .enumerate()
+        .filter_map(|(i, (el, name))| {
+            let pred = catalog.propagate(i, solution.epoch).ok()?;
+            let dx = pred.position[0] - solution.position_km[0];
+            let dy = pred.position[1] - solution.position_km[1];
+            let dz = pred.position[2] - solution.position_km[2];
+            Some(CatalogMatch {
+                norad_id: el.norad_id,
+                name: name.map(str::to_string),
+                distance_km: (dx * dx + dy * dy + dz * dz).sqrt(),
+            })
+        })
+        .collect();
+    matches.sort_by(|a, b| a.distance_km.total_cmp(&b.distance_km));
+    matches.truncate(limit);
+    matches
+}
+
+/// Finds the smallest root of the Gauss eighth-degree polynomial above the Earth's
+/// surface that also satisfies `accept` (positive slant range).
+fn find_range_root(poly: impl Fn(f64) -> f64, accept: impl Fn(f64) -> bool) -> Option<f64> {
+    const MIN_R_KM: f64 = 6378.0;
+    const MAX_R_KM: f64 = 100_000.0;
+    const SCAN_STEP_KM: f64 = 10.0;
+
+    let mut lo = MIN_R_KM;
+    let mut f_lo = poly(lo);
+    while lo < MAX_R_KM {
+        let hi = lo + SCAN_STEP_KM;
+        let f_hi = poly(hi);
+        if f_lo.signum() != f_hi.signum() {
+            let (mut a, mut b, mut fa) = (lo, hi, f_lo);
+            for _ in 0..100 {
+                let mid = 0.5 * (a + b);
+                let fm = poly(mid);
+                if fm.signum() == fa.signum() {
+                    a = mid;
+                    fa = fm;
+                } else {
+                    b = mid;
+                }
+            }
+            let root = 0.5 * (a + b);
+            if accept(root) {
+                return Some(root);
+            }
+        }
+        lo = hi;
+        f_lo = f_hi;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::coords::{ecef_to_eci, eci_to_ecef, geodetic_to_ecef};
+    use chrono::TimeZone;
+
+    /// Builds an az/el observation of an inertial position from a ground site.
+    fn observe(t: DateTime<Utc>, lat: f64, lon: f64, sat_eci: [f64; 3]) -> AngleObservation {
+        let theta = gmst(t);
+        let site = ecef_to_eci(&geodetic_to_ecef(lat, lon, 0.0), theta);
+        let rel = [sat_eci[0] - site[0], sat_eci[1] - site[1], sat_eci[2] - site[2]];
+        let (x, y, z) = eci_to_ecef(&rel, theta);
+        let (sin_lat, cos_lat) = lat.to_radians().sin_cos();
+        let (sin_lon, cos_lon) = lon.to_radians().sin_cos();
+        let east = -sin_lon * x + cos_lon * y;
+        let north = -sin_lat * cos_lon * x - sin_lat * sin_lon * y + cos_lat * z;
+        let up = cos_lat * cos_lon * x + cos_lat * sin_lon * y + sin_lat * z;
+        let range = (east * east + north * north + up * up).sqrt();
+        AngleObservation {
+            time: t,
+            lat_deg: lat,
+            lon_deg: lon,
+            height_km: 0.0,
+            az_deg: east.atan2(north).to_degrees(),
+            el_deg: (up / range).asin().to_degrees(),
+        }
+    }
+
+    #[test]
+    fn gauss_recovers_circular_orbit_radius() {
+        // Circular equatorial-ish orbit at 7000 km observed from a site on the equator.
+        let r = 7000.0f64;
+        let n = (MU_EARTH_KM3_S2 / r.powi(3)).sqrt();
+        let inc = 10f64.to_radians();
+        let t0 = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+        let state = |dt: f64| {
+            let u = n * dt;
+            [r * u.cos(), r * u.sin() * inc.cos(), r * u.sin() * inc.sin()]
+        };
+        let lon = gmst(t0).to_degrees();
+        let obs: Vec<AngleObservation> = [-60.0, 0.0, 60.0]
+            .iter()
+            .map(|&dt| observe(t0 + chrono::Duration::seconds(dt as i64), 0.0, lon, state(dt)))
+            .collect();
+
+        let sol = gauss(&obs).unwrap();
+        let r_est = dot(&sol.position_km, &sol.position_km).sqrt();
+        assert!((r_est - r).abs() < 5.0, "radius {}", r_est);
+        assert!((sol.elements.inclination_deg - 10.0).abs() < 0.5, "inc {}", sol.elements.inclination_deg);
+    }
+}
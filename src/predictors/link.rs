@@ -0,0 +1,104 @@
+use chrono::{DateTime, Utc};
+use sgp4::Elements;
+
+use crate::core::coords::gmst;
+use crate::core::orbit::minutes_since_epoch;
+use crate::predictors::passes::{slant_range_km, ObserverPosition};
+
+/// Boltzmann's constant in dBW/(K·Hz).
+const BOLTZMANN_DBW_K_HZ: f64 = -228.6;
+
+/// Downlink parameters: the satellite's transmitter and the station's receiving
+/// chain.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinkParams {
+    pub frequency_mhz: f64,
+    pub tx_power_dbw: f64,
+    pub tx_gain_dbi: f64,
+    pub rx_gain_dbi: f64,
+    /// System noise temperature of the receiver (K).
+    pub noise_temp_k: f64,
+    pub bandwidth_hz: f64,
+    /// Pointing, polarisation, atmospheric and cable losses lumped together (dB).
+    pub misc_losses_db: f64,
+}
+
+impl LinkParams {
+    pub fn validate(&self) -> Result<(), String> {
+        let positive = |v: f64| v.is_finite() && v > 0.0;
+        if !positive(self.frequency_mhz) || !positive(self.noise_temp_k) || !positive(self.bandwidth_hz) {
+            return Err("frequency_mhz, noise_temp_k and bandwidth_hz must be positive".to_string());
+        }
+        Ok(())
+    }
+
+    /// Signal-to-noise ratio (dB) over `range_km`.
+    pub fn snr_db(&self, range_km: f64) -> f64 {
+        let eirp = self.tx_power_dbw + self.tx_gain_dbi;
+        let received = eirp + self.rx_gain_dbi - free_space_path_loss_db(range_km, self.frequency_mhz) - self.misc_losses_db;
+        let noise = BOLTZMANN_DBW_K_HZ + 10.0 * self.noise_temp_k.log10() + 10.0 * self.bandwidth_hz.log10();
+        received - noise
+    }
+}
+
+/// Free-space path loss (dB) over `range_km` at `frequency_mhz`.
+pub fn free_space_path_loss_db(range_km: f64, frequency_mhz: f64) -> f64 {
+    20.0 * range_km.log10() + 20.0 * frequency_mhz.log10() + 32.45
+}
+
+/// Link budget at one instant of a pass.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinkSample {
+    pub time: DateTime<Utc>,
+    pub range_km: f64,
+    pub path_loss_db: f64,
+    pub snr_db: f64,
+}
+
+/// Link budget from `observer` to the satellite at each of `times`.
+pub fn link_budget(
+    elements: &Elements,
+    observer: &ObserverPosition,
+    params: &LinkParams,
+    times: impl IntoIterator<Item = DateTime<Utc>>,
+) -> sgp4::Result<Vec<LinkSample>> {
+    let constants = sgp4::Constants::from_elements(elements)?;
+    times
+        .into_iter()
+        .map(|time| {
+            let pos = constants.propagate(minutes_since_epoch(elements, time))?.position;
+            let range_km = slant_range_km(&pos, gmst(time), observer);
+            Ok(LinkSample {
+                time,
+                range_km,
+                path_loss_db: free_space_path_loss_db(range_km, params.frequency_mhz),
+                snr_db: params.snr_db(range_km),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snr_follows_path_loss() {
+        // 2000 km at 437 MHz: about 151.3 dB.
+        assert!((free_space_path_loss_db(2000.0, 437.0) - 151.28).abs() < 0.01);
+        let params = LinkParams {
+            frequency_mhz: 437.0,
+            tx_power_dbw: 0.0,
+            tx_gain_dbi: 0.0,
+            rx_gain_dbi: 14.0,
+            noise_temp_k: 500.0,
+            bandwidth_hz: 10_000.0,
+            misc_losses_db: 3.0,
+        };
+        assert!(params.validate().is_ok());
+        // kTB = -228.6 + 27.0 + 40.0 = -161.6 dBW
+        assert!((params.snr_db(2000.0) - (14.0 - 151.28 - 3.0 + 161.6)).abs() < 0.02);
+        assert!((params.snr_db(1000.0) - params.snr_db(2000.0) - 6.02).abs() < 0.01);
+        assert!(LinkParams { bandwidth_hz: 0.0, ..params }.validate().is_err());
+    }
+}
@@ -1 +1,19 @@
-pub mod passes;
\ No newline at end of file
+pub mod passes;
+pub mod conjunctions;
+pub mod uncertainty;
+pub mod iod;
+pub mod identify;
+pub mod decay;
+pub mod geo;
+pub mod events;
+pub mod repeat;
+pub mod groundtrack;
+pub mod ephemeris;
+pub mod plane;
+pub mod link;
+pub mod access;
+pub mod revolution;
+pub mod network;
+pub mod mount;
+pub mod trains;
+pub mod saa;
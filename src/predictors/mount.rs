@@ -0,0 +1,118 @@
+use chrono::{DateTime, Utc};
+use sgp4::Elements;
+
+use crate::core::coords::{horizontal_to_equatorial, of_date_to_j2000};
+use crate::core::orbit::minutes_since_epoch;
+use crate::core::sun::{shadow_margin_km, sun_elevation_deg};
+use crate::predictors::passes::{ObserverPosition, PointingSample};
+
+/// Sun elevation (degrees) below which the sky counts as dark enough to see a
+/// sunlit satellite (end of civil twilight).
+pub const DARK_SKY_SUN_EL_DEG: f64 = -6.0;
+
+/// Length of a sidereal second in SI seconds, the time unit of ASCOM's
+/// `RightAscensionRate`.
+const SIDEREAL_SECOND_S: f64 = 0.997_269_566_3;
+
+/// One time-tagged pointing of an equatorial mount.
+#[derive(Debug, Clone, Copy)]
+pub struct MountSample {
+    pub time: DateTime<Utc>,
+    /// Topocentric right ascension of date (hours, [0, 24)).
+    pub ra_hours: f64,
+    /// Topocentric declination of date (degrees).
+    pub dec_deg: f64,
+    /// The same direction referred to J2000.0.
+    pub ra_j2000_hours: f64,
+    pub dec_j2000_deg: f64,
+    /// Offset from sidereal tracking in seconds of RA per sidereal second, as
+    /// ASCOM's `RightAscensionRate`.
+    pub ra_rate_s_per_sidereal_s: f64,
+    /// As ASCOM's `DeclinationRate` (arcseconds per second).
+    pub dec_rate_arcsec_s: f64,
+    pub az_deg: f64,
+    pub el_deg: f64,
+    /// Sunlit satellite above the horizon under a dark sky.
+    pub visible: bool,
+}
+
+/// Converts a pointing track into RA/Dec for an equatorial mount and marks the
+/// samples where the satellite can be seen. RA/Dec follow the track's
+/// elevations, so a refracted track gives apparent coordinates.
+pub fn mount_plan(elements: &Elements, observer: &ObserverPosition, track: &[PointingSample]) -> sgp4::Result<Vec<MountSample>> {
+    let constants = sgp4::Constants::from_elements(elements)?;
+    let mut samples = Vec::with_capacity(track.len());
+    for s in track {
+        let position = constants.propagate(minutes_since_epoch(elements, s.time))?.position;
+        let visible = s.el_deg >= 0.0
+            && shadow_margin_km(&position, s.time) >= 0.0
+            && sun_elevation_deg(s.time, observer.lat_deg, observer.lon_deg) < DARK_SKY_SUN_EL_DEG;
+        let (ra_deg, dec_deg) = horizontal_to_equatorial(observer.lat_deg, observer.lon_deg, s.az_deg, s.el_deg, s.time);
+        let (ra_j2000_deg, dec_j2000_deg) = of_date_to_j2000(ra_deg, dec_deg, s.time);
+        samples.push(MountSample {
+            time: s.time,
+            ra_hours: ra_deg / 15.0,
+            dec_deg,
+            ra_j2000_hours: ra_j2000_deg / 15.0,
+            dec_j2000_deg,
+            ra_rate_s_per_sidereal_s: 0.0,
+            dec_rate_arcsec_s: 0.0,
+            az_deg: s.az_deg,
+            el_deg: s.el_deg,
+            visible,
+        });
+    }
+    fill_rates(&mut samples);
+    Ok(samples)
+}
+
+/// Central differences inside the plan, one-sided at the ends; RA steps are
+/// taken the short way round 0h.
+fn fill_rates(samples: &mut [MountSample]) {
+    if samples.len() < 2 {
+        return;
+    }
+    for i in 0..samples.len() {
+        let (a, b) = (samples[i.saturating_sub(1)], samples[(i + 1).min(samples.len() - 1)]);
+        let dt = (b.time - a.time).num_milliseconds() as f64 / 1000.0;
+        let dra_hours = (b.ra_hours - a.ra_hours + 36.0).rem_euclid(24.0) - 12.0;
+        samples[i].ra_rate_s_per_sidereal_s = dra_hours * 3600.0 / dt * SIDEREAL_SECOND_S;
+        samples[i].dec_rate_arcsec_s = (b.dec_deg - a.dec_deg) * 3600.0 / dt;
+    }
+}
+
+/// The samples from the first to the last visible one; empty when the
+/// satellite is never visible.
+pub fn visible_part(samples: &[MountSample]) -> &[MountSample] {
+    match (samples.iter().position(|s| s.visible), samples.iter().rposition(|s| s.visible)) {
+        (Some(first), Some(last)) => &samples[first..=last],
+        _ => &[],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::predictors::passes::{pointing_track, predict_passes, LookOptions};
+
+    #[test]
+    fn plan_follows_the_track_with_consistent_rates() {
+        let el = &crate::testing::fixtures::catalog()[0];
+        let observer = crate::testing::fixtures::STATION;
+        let start = el.datetime.and_utc();
+        let pass = predict_passes(el, observer.lat_deg, observer.lon_deg, start, 1440, 15, 10.0).unwrap().remove(0);
+        let track = pointing_track(el, &observer, pass.start, pass.end, 1, &LookOptions::default()).unwrap();
+        let plan = mount_plan(el, &observer, &track).unwrap();
+
+        assert_eq!(plan.len(), track.len());
+        for s in &plan {
+            assert!((0.0..24.0).contains(&s.ra_hours) && (-90.0..=90.0).contains(&s.dec_deg));
+        }
+        // Integrating the rates reproduces the motion between neighbouring samples
+        let (a, b) = (&plan[plan.len() / 2 - 1], &plan[plan.len() / 2 + 1]);
+        let dec_step = (a.dec_rate_arcsec_s + b.dec_rate_arcsec_s) / 2.0 * 2.0 / 3600.0;
+        assert!((b.dec_deg - a.dec_deg - dec_step).abs() < 0.05);
+        let visible = visible_part(&plan);
+        assert!(visible.first().is_none_or(|s| s.visible) && visible.last().is_none_or(|s| s.visible));
+    }
+}
@@ -0,0 +1,170 @@
+use chrono::{DateTime, Duration, Utc};
+
+/// A time span, start first.
+pub type Interval = (DateTime<Utc>, DateTime<Utc>);
+
+/// A span during which the satellite is above the horizon of the same stations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommonWindow {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Indices into the per-station interval lists, ascending.
+    pub stations: Vec<usize>,
+}
+
+/// Spans in which the visibility intervals of at least `min_count` stations
+/// overlap, given one list of intervals per station. A span is split where a
+/// station joins or leaves, so each lists exactly the stations that see the
+/// satellite throughout it. Intervals that only touch do not overlap.
+pub fn common_windows(intervals: &[Vec<Interval>], min_count: usize) -> Vec<CommonWindow> {
+    // Ends sort before starts at the same instant
+    let mut events: Vec<(DateTime<Utc>, bool, usize)> = intervals
+        .iter()
+        .enumerate()
+        .flat_map(|(i, list)| list.iter().filter(|(s, e)| s < e).flat_map(move |&(s, e)| [(s, true, i), (e, false, i)]))
+        .collect();
+    events.sort();
+
+    let mut active = vec![0usize; intervals.len()];
+    let mut windows: Vec<CommonWindow> = Vec::new();
+    let mut since: Option<DateTime<Utc>> = None;
+    for (time, is_start, station) in events {
+        if let Some(from) = since.filter(|from| *from < time) {
+            let stations: Vec<usize> = (0..active.len()).filter(|&i| active[i] > 0).collect();
+            if stations.len() >= min_count.max(1) {
+                match windows.last_mut() {
+                    Some(last) if last.end == from && last.stations == stations => last.end = time,
+                    _ => windows.push(CommonWindow { start: from, end: time, stations }),
+                }
+            }
+        }
+        if is_start {
+            active[station] += 1;
+        } else {
+            active[station] = active[station].saturating_sub(1);
+        }
+        since = Some(time);
+    }
+    windows
+}
+
+/// One station's turn in a handover plan.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assignment {
+    /// Index into the per-station elevation columns.
+    pub station: usize,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub max_elevation_deg: f64,
+    /// Whether another station hands over at `start`, rather than the
+    /// satellite being acquired after a gap.
+    pub handover: bool,
+}
+
+/// Picks which station tracks at each sample: the plan maximises the summed
+/// elevation of the tracking station over time, while every acquisition costs
+/// `switch_penalty` degree-minutes, so a station only takes over when it gains
+/// that much. `elevations[k][s]` is station `s`'s elevation at `times[k]`, `None`
+/// when it cannot track then. Samples are taken to be `step_minutes` apart.
+pub fn plan_handover(times: &[DateTime<Utc>], elevations: &[Vec<Option<f64>>], step_minutes: f64, switch_penalty: f64) -> Vec<Assignment> {
+    let stations = elevations.first().map_or(0, Vec::len);
+    let idle = stations;
+    // Every tracked sample earns more than idling; the offset keeps negative
+    // elevations (a negative `min_el`) from making tracking a loss
+    let reward = |el: Option<f64>| el.map(|e| (e + 90.0) * step_minutes);
+
+    // Viterbi over states 0..stations (tracking) and `idle`
+    let mut score = vec![f64::NEG_INFINITY; stations + 1];
+    score[idle] = 0.0;
+    let mut from: Vec<Vec<usize>> = Vec::with_capacity(times.len());
+    for row in elevations {
+        let mut next = vec![f64::NEG_INFINITY; stations + 1];
+        let mut back = vec![idle; stations + 1];
+        for to in 0..=stations {
+            let gain = if to == idle { Some(0.0) } else { reward(row[to]) };
+            let Some(gain) = gain else { continue };
+            for (prev, &s) in score.iter().enumerate() {
+                let cost = if to != idle && prev != to { switch_penalty } else { 0.0 };
+                if s - cost + gain > next[to] {
+                    next[to] = s - cost + gain;
+                    back[to] = prev;
+                }
+            }
+        }
+        from.push(back);
+        score = next;
+    }
+    let Some(mut state) = (0..=stations).max_by(|a, b| score[*a].total_cmp(&score[*b])) else {
+        return Vec::new();
+    };
+    let mut path = vec![idle; times.len()];
+    for k in (0..times.len()).rev() {
+        path[k] = state;
+        state = from[k][state];
+    }
+
+    let mut plan: Vec<Assignment> = Vec::new();
+    for (k, &station) in path.iter().enumerate() {
+        let end = times.get(k + 1).copied().unwrap_or(times[k] + Duration::milliseconds((step_minutes * 60_000.0) as i64));
+        if station == idle {
+            continue;
+        }
+        let el = elevations[k][station].unwrap_or(f64::NEG_INFINITY);
+        match plan.last_mut() {
+            Some(last) if last.station == station && last.end == times[k] => {
+                last.end = end;
+                last.max_elevation_deg = last.max_elevation_deg.max(el);
+            }
+            _ => {
+                let handover = plan.last().is_some_and(|last| last.end == times[k]);
+                plan.push(Assignment { station, start: times[k], end, max_elevation_deg: el, handover });
+            }
+        }
+    }
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn splits_overlaps_where_stations_join_or_leave() {
+        let t0 = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+        let m = |minutes: i64| t0 + Duration::minutes(minutes);
+        let intervals = vec![
+            vec![(m(0), m(10)), (m(100), m(110))],
+            vec![(m(5), m(15)), (m(110), m(120))],
+            vec![(m(8), m(20))],
+        ];
+        let windows = common_windows(&intervals, 2);
+        let spans: Vec<_> = windows.iter().map(|w| (w.start, w.end, w.stations.clone())).collect();
+        assert_eq!(spans, vec![(m(5), m(8), vec![0, 1]), (m(8), m(10), vec![0, 1, 2]), (m(10), m(15), vec![1, 2])]);
+        assert_eq!(common_windows(&intervals, 3).len(), 1);
+        assert!(common_windows(&intervals[..1], 2).is_empty());
+    }
+
+    #[test]
+    fn hands_over_only_when_it_pays() {
+        let t0 = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+        let times: Vec<_> = (0..6).map(|k| t0 + Duration::minutes(k)).collect();
+        // Station 1 rises higher than station 0 from minute 2
+        let elevations = vec![
+            vec![Some(20.0), None],
+            vec![Some(25.0), Some(10.0)],
+            vec![Some(30.0), Some(35.0)],
+            vec![Some(25.0), Some(40.0)],
+            vec![Some(20.0), Some(45.0)],
+            vec![Some(20.0), Some(40.0)],
+        ];
+        let plan = plan_handover(&times, &elevations, 1.0, 10.0);
+        let turns: Vec<_> = plan.iter().map(|a| (a.station, a.start, a.end, a.handover)).collect();
+        assert_eq!(turns, vec![(0, times[0], times[2], false), (1, times[2], times[5] + Duration::minutes(1), true)]);
+        assert_eq!(plan[1].max_elevation_deg, 45.0);
+
+        // A switch that gains less than it costs is skipped
+        let plan = plan_handover(&times, &elevations, 1.0, 100.0);
+        assert_eq!(plan.iter().map(|a| (a.station, a.start)).collect::<Vec<_>>(), vec![(0, times[0])]);
+    }
+}
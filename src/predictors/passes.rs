@@ -1,11 +1,231 @@
-use chrono::{DateTime, Duration, NaiveDate, Utc};
+use chrono::{DateTime, Duration, Utc};
 use sgp4::Elements;
 
-#[derive(Debug, Clone)]
+use crate::core::coords::{geodetic_to_ecef, gmst, refraction_deg};
+use crate::core::orbit::{minutes_since_epoch, EARTH_ROTATION_RAD_S, SPEED_OF_LIGHT_KM_S};
+use crate::core::sun::sun_elevation_deg;
+use crate::core::terrain::HorizonMask;
+use crate::predictors::revolution::orbit_number_at;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct PassWindow {
     pub start: DateTime<Utc>,
     pub end: DateTime<Utc>,
+    /// Time of closest approach (sample with the highest elevation).
+    pub tca: DateTime<Utc>,
     pub max_elevation_deg: f64,
+    /// Revolution number at TCA.
+    pub orbit_number: i64,
+    /// Azimuths (degrees) at `start` and `end`.
+    pub aos_az_deg: f64,
+    pub los_az_deg: f64,
+    /// Parts of the pass spent in an excluded azimuth sector (annotate mode only).
+    pub blocked: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+}
+
+impl PassWindow {
+    pub fn duration_s(&self) -> i64 {
+        (self.end - self.start).num_seconds()
+    }
+
+    /// Quality score in [0, 1] for ranking passes: 70% maximum elevation (90° is
+    /// best), 30% duration (saturating at 15 minutes).
+    pub fn score(&self) -> f64 {
+        let el = (self.max_elevation_deg / 90.0).clamp(0.0, 1.0);
+        let duration = (self.duration_s() as f64 / 900.0).clamp(0.0, 1.0);
+        0.7 * el + 0.3 * duration
+    }
+
+    /// Where the satellite rises and sets, e.g. `NNW→SE`.
+    pub fn direction(&self) -> String {
+        format!("{}→{}", compass_point(self.aos_az_deg), compass_point(self.los_az_deg))
+    }
+}
+
+/// Nearest of the 16 compass points to an azimuth.
+pub fn compass_point(az_deg: f64) -> &'static str {
+    const POINTS: [&str; 16] = ["N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW", "NW", "NNW"];
+    POINTS[((az_deg.rem_euclid(360.0) / 22.5).round() as usize) % 16]
+}
+
+/// Sort key for pass lists.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PassSort {
+    #[default]
+    Start,
+    MaxEl,
+    Duration,
+    Score,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+/// Sorts passes by `by`; without an explicit order, start times ascend and the
+/// other keys descend (best first). The sort is stable in both orders, so
+/// passes with equal keys keep their relative (time) order.
+pub fn sort_passes(passes: &mut [PassWindow], by: PassSort, order: Option<SortOrder>) {
+    let compare = |a: &PassWindow, b: &PassWindow| match by {
+        PassSort::Start => a.start.cmp(&b.start),
+        PassSort::MaxEl => a.max_elevation_deg.total_cmp(&b.max_elevation_deg),
+        PassSort::Duration => a.duration_s().cmp(&b.duration_s()),
+        PassSort::Score => a.score().total_cmp(&b.score()),
+    };
+    let default = if by == PassSort::Start { SortOrder::Asc } else { SortOrder::Desc };
+    match order.unwrap_or(default) {
+        SortOrder::Asc => passes.sort_by(compare),
+        SortOrder::Desc => passes.sort_by(|a, b| compare(b, a)),
+    }
+}
+
+/// An unusable azimuth range, clockwise from `start_deg` to `end_deg`; it may
+/// wrap through north (e.g. 350° to 10°). A sector spanning 360° or more
+/// (0° to 360°) covers every azimuth.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AzimuthSector {
+    pub start_deg: f64,
+    pub end_deg: f64,
+}
+
+impl AzimuthSector {
+    pub fn contains(&self, az_deg: f64) -> bool {
+        if self.end_deg - self.start_deg >= 360.0 {
+            return true;
+        }
+        let az = az_deg.rem_euclid(360.0);
+        let (a, b) = (self.start_deg.rem_euclid(360.0), self.end_deg.rem_euclid(360.0));
+        if a <= b { (a..=b).contains(&az) } else { az >= a || az <= b }
+    }
+}
+
+/// How passes through excluded azimuth sectors are reported.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExclusionMode {
+    /// Blocked samples count as not visible, so a pass crossing a sector is split.
+    #[default]
+    Clip,
+    /// Passes are kept whole and the blocked intervals are listed.
+    Annotate,
+}
+
+/// Sun elevation (degrees) separating day from night: the end of civil twilight.
+pub const TWILIGHT_SUN_EL_DEG: f64 = -6.0;
+
+/// Lighting condition at the observer at the time of closest approach.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Lighting {
+    Day,
+    Night,
+}
+
+/// Criteria for dropping passes after prediction. `None` fields don't filter.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PassFilter {
+    pub min_duration_s: Option<i64>,
+    pub max_max_elevation_deg: Option<f64>,
+    pub lighting: Option<Lighting>,
+}
+
+impl PassFilter {
+    pub fn accepts(&self, pass: &PassWindow, observer: &ObserverPosition) -> bool {
+        if self.min_duration_s.is_some_and(|d| (pass.end - pass.start).num_seconds() < d) {
+            return false;
+        }
+        if self.max_max_elevation_deg.is_some_and(|m| pass.max_elevation_deg > m) {
+            return false;
+        }
+        match self.lighting {
+            Some(lighting) => {
+                let sun_el = sun_elevation_deg(pass.tca, observer.lat_deg, observer.lon_deg);
+                let day = sun_el > TWILIGHT_SUN_EL_DEG;
+                day == (lighting == Lighting::Day)
+            }
+            None => true,
+        }
+    }
+}
+
+/// Geodetic observer location (WGS84), altitude in km above the ellipsoid.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ObserverPosition {
+    pub lat_deg: f64,
+    pub lon_deg: f64,
+    pub alt_km: f64,
+}
+
+/// One time-tagged point of a moving observer's trajectory.
+#[derive(Debug, Clone, Copy)]
+pub struct TrackPoint {
+    pub time: DateTime<Utc>,
+    pub position: ObserverPosition,
+}
+
+/// Where the observer is during a prediction.
+#[derive(Debug, Clone, Copy)]
+pub enum Observer<'a> {
+    /// A fixed ground station.
+    Fixed(ObserverPosition),
+    /// A ship, vehicle or aircraft track, sorted by time. The observer only exists
+    /// between the first and last point; samples outside the track are not visible.
+    Track(&'a [TrackPoint]),
+}
+
+impl Observer<'_> {
+    /// Observer location at `t`, linearly interpolated along a track.
+    pub fn position_at(&self, t: DateTime<Utc>) -> Option<ObserverPosition> {
+        match self {
+            Observer::Fixed(p) => Some(*p),
+            Observer::Track(points) => interpolate_track(points, t),
+        }
+    }
+}
+
+/// Optional station constraints and corrections applied to look angles during prediction.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LookOptions<'a> {
+    /// Terrain horizon the satellite must clear in its azimuth direction.
+    pub horizon: Option<&'a HorizonMask>,
+    /// Report apparent (refracted) instead of geometric elevation.
+    pub refraction: bool,
+    /// Point where the received signal left the satellite (light-time) and
+    /// account for the observer's velocity due to Earth rotation (aberration).
+    pub light_time: bool,
+    /// Azimuth sectors the station cannot use.
+    pub exclusions: &'a [AzimuthSector],
+    pub exclusion_mode: ExclusionMode,
+}
+
+impl LookOptions<'_> {
+    fn apparent_elevation(&self, el_deg: f64) -> f64 {
+        if self.refraction { el_deg + refraction_deg(el_deg) } else { el_deg }
+    }
+
+    /// Elevation and azimuth (degrees) of the satellite seen from `observer` at `t`.
+    fn look_angles(
+        &self,
+        elements: &Elements,
+        constants: &sgp4::Constants,
+        observer: &ObserverPosition,
+        t: DateTime<Utc>,
+    ) -> sgp4::Result<(f64, f64)> {
+        let theta = gmst(t);
+        let mut pos = constants.propagate(minutes_since_epoch(elements, t))?.position;
+        if self.light_time {
+            // One iteration is enough: the satellite moves metres during the light-time error.
+            let [x, y, z] = relative_ecef(&pos, theta, observer);
+            let tau_s = (x * x + y * y + z * z).sqrt() / SPEED_OF_LIGHT_KM_S;
+            pos = constants.propagate(minutes_since_epoch(elements, t) - tau_s / 60.0)?.position;
+        }
+        let (el, az) = elevation_azimuth_deg(&pos, theta, observer, self.light_time);
+        Ok((self.apparent_elevation(el), az))
+    }
 }
 
 /// Predict simple visibility passes over a ground location using elevation threshold.
@@ -23,126 +243,573 @@ pub fn predict_passes(
     step_seconds: i64,
     min_elevation_deg: f64,
 ) -> sgp4::Result<Vec<PassWindow>> {
+    let observer = Observer::Fixed(ObserverPosition { lat_deg: ground_lat_deg, lon_deg: ground_lon_deg, alt_km: 0.0 });
+    predict_passes_for_observer(elements, &observer, start, duration_minutes, step_seconds, min_elevation_deg)
+}
+
+/// Same as [`predict_passes`] for a fixed or moving observer. For a track, the
+/// observer position is interpolated at every sample.
+pub fn predict_passes_for_observer(
+    elements: &Elements,
+    observer: &Observer<'_>,
+    start: DateTime<Utc>,
+    duration_minutes: i64,
+    step_seconds: i64,
+    min_elevation_deg: f64,
+) -> sgp4::Result<Vec<PassWindow>> {
+    predict_passes_with_options(elements, observer, &LookOptions::default(), start, duration_minutes, step_seconds, min_elevation_deg)
+}
+
+/// Same as [`predict_passes_for_observer`] with the station constraints and
+/// look-angle corrections in `options`.
+pub fn predict_passes_with_options(
+    elements: &Elements,
+    observer: &Observer<'_>,
+    options: &LookOptions<'_>,
+    start: DateTime<Utc>,
+    duration_minutes: i64,
+    step_seconds: i64,
+    min_elevation_deg: f64,
+) -> sgp4::Result<Vec<PassWindow>> {
+    let constants = sgp4::Constants::from_elements(elements)?;
     let mut windows: Vec<PassWindow> = Vec::new();
 
     let end = start + Duration::minutes(duration_minutes);
     let mut t = start;
 
-    let mut in_pass = false;
-    let mut current_start: Option<DateTime<Utc>> = None;
-    let mut max_el = f64::NEG_INFINITY;
+    let mut current: Option<PassWindow> = None;
+    let mut blocked_since: Option<DateTime<Utc>> = None;
+    // Azimuth of the latest sample with an observer position
+    let mut last_az = 0.0;
 
     while t <= end {
-        let minutes_since_epoch = minutes_since_elements_epoch(elements, t);
-        let pred = sgp4::Constants::from_elements(elements)?.propagate(minutes_since_epoch)?;
-
-        let gmst_rad = gmst(t);
-        let (el_deg, _az_deg) = elevation_azimuth_deg(
-            &pred.position,
-            gmst_rad,
-            ground_lat_deg,
-            ground_lon_deg,
-        );
-
-        if el_deg >= min_elevation_deg {
-            if !in_pass {
-                in_pass = true;
-                current_start = Some(t);
-                max_el = el_deg;
-            } else if el_deg > max_el {
-                max_el = el_deg;
+        let (el_deg, threshold, in_sector) = match observer.position_at(t) {
+            Some(obs) => {
+                let (el, az) = options.look_angles(elements, &constants, &obs, t)?;
+                last_az = az.rem_euclid(360.0);
+                let mask = options.horizon.map_or(f64::NEG_INFINITY, |h| h.elevation_at(az));
+                (el, min_elevation_deg.max(mask), options.exclusions.iter().any(|s| s.contains(az)))
             }
-        } else if in_pass {
-            // pass ended
-            in_pass = false;
-            windows.push(PassWindow {
-                start: current_start.unwrap(),
+            None => (f64::NEG_INFINITY, min_elevation_deg, false),
+        };
+        let visible = el_deg >= threshold && !(in_sector && options.exclusion_mode == ExclusionMode::Clip);
+
+        if visible {
+            let pass = current.get_or_insert_with(|| PassWindow {
+                start: t,
                 end: t,
-                max_elevation_deg: max_el,
+                tca: t,
+                max_elevation_deg: el_deg,
+                orbit_number: 0,
+                aos_az_deg: last_az,
+                los_az_deg: last_az,
+                blocked: Vec::new(),
             });
-            current_start = None;
-            max_el = f64::NEG_INFINITY;
+            if el_deg > pass.max_elevation_deg {
+                pass.max_elevation_deg = el_deg;
+                pass.tca = t;
+            }
+            match (in_sector, blocked_since) {
+                (true, None) => blocked_since = Some(t),
+                (false, Some(since)) => {
+                    pass.blocked.push((since, t));
+                    blocked_since = None;
+                }
+                _ => {}
+            }
+        } else if let Some(mut pass) = current.take() {
+            // pass ended
+            pass.end = t;
+            pass.los_az_deg = last_az;
+            if let Some(since) = blocked_since.take() {
+                pass.blocked.push((since, t));
+            }
+            windows.push(pass);
         }
 
-        t = t + Duration::seconds(step_seconds);
+        t += Duration::seconds(step_seconds.max(1));
     }
 
     // If still in pass at the end, close it
-    if in_pass {
-        windows.push(PassWindow {
-            start: current_start.unwrap(),
-            end,
-            max_elevation_deg: max_el,
-        });
+    if let Some(mut pass) = current {
+        pass.end = end;
+        pass.los_az_deg = last_az;
+        if let Some(since) = blocked_since {
+            pass.blocked.push((since, end));
+        }
+        windows.push(pass);
     }
 
+    for pass in &mut windows {
+        let pred = constants.propagate(minutes_since_epoch(elements, pass.tca))?;
+        pass.orbit_number = orbit_number_at(elements, pass.tca, &pred.position, &pred.velocity);
+    }
     Ok(windows)
 }
 
-fn minutes_since_elements_epoch(elements: &Elements, t: DateTime<Utc>) -> f64 {
-    let epoch = elements.datetime;
-    let t_naive = t.naive_utc();
-    let diff = t_naive - epoch;
-    diff.num_seconds() as f64 / 60.0
+/// Joins consecutive passes separated by at most `max_gap_s` seconds below the
+/// threshold, e.g. a brief dip behind a notch in the horizon mask, into one.
+/// The joined pass keeps the TCA and orbit number of the higher peak.
+pub fn merge_passes(passes: Vec<PassWindow>, max_gap_s: i64) -> Vec<PassWindow> {
+    let mut merged: Vec<PassWindow> = Vec::with_capacity(passes.len());
+    for pass in passes {
+        match merged.last_mut() {
+            Some(last) if (pass.start - last.end).num_seconds() <= max_gap_s => {
+                if pass.end > last.end {
+                    last.end = pass.end;
+                    last.los_az_deg = pass.los_az_deg;
+                }
+                last.blocked.extend(pass.blocked);
+                if pass.max_elevation_deg > last.max_elevation_deg {
+                    last.max_elevation_deg = pass.max_elevation_deg;
+                    last.tca = pass.tca;
+                    last.orbit_number = pass.orbit_number;
+                }
+            }
+            _ => merged.push(pass),
+        }
+    }
+    merged
 }
 
-/// Compute GMST (radians) from UTC time using a simplified expression.
-fn gmst(t: DateTime<Utc>) -> f64 {
-    // Seconds since J2000 (2000-01-01 12:00:00 UTC)
-    let j2000_naive = NaiveDate::from_ymd_opt(2000, 1, 1)
-        .unwrap()
-        .and_hms_opt(12, 0, 0)
-        .unwrap();
-    let secs = (t.naive_utc() - j2000_naive).num_seconds() as f64;
-    let days = secs / 86400.0;
-    let gmst_deg = 280.46061837 + 360.98564736629 * days;
-    let gmst_rad = (gmst_deg.rem_euclid(360.0)) * std::f64::consts::PI / 180.0;
-    gmst_rad
+/// One az/el pointing sample from an observer towards the satellite.
+#[derive(Debug, Clone, Copy)]
+pub struct PointingSample {
+    pub time: DateTime<Utc>,
+    /// Azimuth from north, clockwise, in [0, 360).
+    pub az_deg: f64,
+    pub el_deg: f64,
+    /// Angular rates (deg/s) from finite differences between neighbouring samples.
+    pub az_rate_deg_s: f64,
+    pub el_rate_deg_s: f64,
 }
 
-/// Convert satellite TEME/ECI position to elevation and azimuth from ground station.
-fn elevation_azimuth_deg(
-    pos_eci_km: &[f64; 3],
-    gmst_rad: f64,
-    ground_lat_deg: f64,
-    ground_lon_deg: f64,
-) -> (f64, f64) {
+/// Samples azimuth/elevation from a fixed observer between `start` and `end`
+/// (inclusive) every `step_seconds`, e.g. to preload a rotator with a pass.
+pub fn pointing_track(
+    elements: &Elements,
+    observer: &ObserverPosition,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    step_seconds: i64,
+    options: &LookOptions<'_>,
+) -> sgp4::Result<Vec<PointingSample>> {
+    let constants = sgp4::Constants::from_elements(elements)?;
+    let mut samples = Vec::new();
+    let mut t = start;
+    while t <= end {
+        let (el_deg, az_deg) = options.look_angles(elements, &constants, observer, t)?;
+        samples.push(PointingSample { time: t, az_deg: az_deg.rem_euclid(360.0), el_deg, az_rate_deg_s: 0.0, el_rate_deg_s: 0.0 });
+        t += Duration::seconds(step_seconds.max(1));
+    }
+    fill_rates(&mut samples);
+    Ok(samples)
+}
+
+/// Central differences inside the track, one-sided at the ends. Azimuth steps
+/// are taken the short way round so crossing north does not produce a spike.
+fn fill_rates(samples: &mut [PointingSample]) {
+    if samples.len() < 2 {
+        return;
+    }
+    for i in 0..samples.len() {
+        let (a, b) = (samples[i.saturating_sub(1)], samples[(i + 1).min(samples.len() - 1)]);
+        let dt = (b.time - a.time).num_milliseconds() as f64 / 1000.0;
+        let daz = (b.az_deg - a.az_deg + 540.0).rem_euclid(360.0) - 180.0;
+        samples[i].az_rate_deg_s = daz / dt;
+        samples[i].el_rate_deg_s = (b.el_deg - a.el_deg) / dt;
+    }
+}
+
+/// Intervals where the azimuth rate exceeds what an az-el rotator can slew,
+/// i.e. the zenith keyhole of a high pass. Empty when the rotator keeps up.
+pub fn keyhole_intervals(samples: &[PointingSample], max_az_rate_deg_s: f64) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut out = Vec::new();
+    let mut since: Option<DateTime<Utc>> = None;
+    for s in samples {
+        let fast = s.az_rate_deg_s.abs() > max_az_rate_deg_s;
+        match (fast, since) {
+            (true, None) => since = Some(s.time),
+            (false, Some(start)) => {
+                out.push((start, s.time));
+                since = None;
+            }
+            _ => {}
+        }
+    }
+    if let (Some(start), Some(last)) = (since, samples.last()) {
+        out.push((start, last.time));
+    }
+    out
+}
+
+/// Linear interpolation between the two track points bracketing `t`; longitude is
+/// interpolated along the shorter arc so antimeridian crossings stay continuous.
+fn interpolate_track(points: &[TrackPoint], t: DateTime<Utc>) -> Option<ObserverPosition> {
+    let first = points.first()?;
+    let last = points.last()?;
+    if t < first.time || t > last.time {
+        return None;
+    }
+    let idx = points.partition_point(|p| p.time <= t);
+    if idx == 0 || idx >= points.len() {
+        // `t` equals the last point (or the only point)
+        return Some(points[idx.saturating_sub(1)].position);
+    }
+    let (a, b) = (&points[idx - 1], &points[idx]);
+    let span = (b.time - a.time).num_milliseconds() as f64;
+    if span <= 0.0 {
+        return Some(a.position);
+    }
+    let f = (t - a.time).num_milliseconds() as f64 / span;
+    let dlon = (b.position.lon_deg - a.position.lon_deg + 540.0).rem_euclid(360.0) - 180.0;
+    Some(ObserverPosition {
+        lat_deg: a.position.lat_deg + f * (b.position.lat_deg - a.position.lat_deg),
+        lon_deg: (a.position.lon_deg + f * dlon + 540.0).rem_euclid(360.0) - 180.0,
+        alt_km: a.position.alt_km + f * (b.position.alt_km - a.position.alt_km),
+    })
+}
+
+/// Satellite position relative to the observer, in ECEF (km).
+fn relative_ecef(pos_eci_km: &[f64; 3], gmst_rad: f64, observer: &ObserverPosition) -> [f64; 3] {
     let (sin_t, cos_t) = gmst_rad.sin_cos();
     // Rotate ECI -> ECEF about Z by GMST
     let x_ecef = cos_t * pos_eci_km[0] + sin_t * pos_eci_km[1];
     let y_ecef = -sin_t * pos_eci_km[0] + cos_t * pos_eci_km[1];
     let z_ecef = pos_eci_km[2];
 
-    let lat = ground_lat_deg.to_radians();
-    let lon = ground_lon_deg.to_radians();
-
-    // WGS84 constants
-    let a = 6378.137; // km
-    let f = 1.0 / 298.257_223_563;
-    let e2 = f * (2.0 - f);
-    let sin_lat = lat.sin();
-    let cos_lat = lat.cos();
-    let sin_lon = lon.sin();
-    let cos_lon = lon.cos();
-
-    let n = a / (1.0 - e2 * sin_lat * sin_lat).sqrt();
-    let x_gs = n * cos_lat * cos_lon;
-    let y_gs = n * cos_lat * sin_lon;
-    let z_gs = n * (1.0 - e2) * sin_lat;
-
-    // Relative vector satellite - ground station in ECEF
-    let rx = x_ecef - x_gs;
-    let ry = y_ecef - y_gs;
-    let rz = z_ecef - z_gs;
-
-    // Transform to local ENU
-    let east = -sin_lon * rx + cos_lon * ry;
-    let north = -sin_lat * cos_lon * rx - sin_lat * sin_lon * ry + cos_lat * rz;
-    let up = cos_lat * cos_lon * rx + cos_lat * sin_lon * ry + sin_lat * rz;
+    let [x_gs, y_gs, z_gs] = geodetic_to_ecef(observer.lat_deg, observer.lon_deg, observer.alt_km);
+    [x_ecef - x_gs, y_ecef - y_gs, z_ecef - z_gs]
+}
+
+/// Geometric elevation and azimuth (degrees) of a TEME/ECI position seen by an observer.
+pub fn topocentric_look_deg(pos_eci_km: &[f64; 3], gmst_rad: f64, observer: &ObserverPosition) -> (f64, f64) {
+    elevation_azimuth_deg(pos_eci_km, gmst_rad, observer, false)
+}
+
+/// Look angle of a satellite together with its apparent motion across the sky.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SkyMotion {
+    pub az_deg: f64,
+    pub el_deg: f64,
+    /// Velocity relative to the observer in local east/north/up (km/s).
+    pub velocity_enu_km_s: [f64; 3],
+    pub az_rate_deg_s: f64,
+    pub el_rate_deg_s: f64,
+    /// Direction of apparent motion on the sky, clockwise from the direction
+    /// towards the zenith: 0° climbing, 90° along increasing azimuth, 180° setting.
+    pub position_angle_deg: f64,
+    /// Great-circle rate of apparent motion (deg/s).
+    pub angular_rate_deg_s: f64,
+}
+
+/// Geometric look angle and apparent motion of a TEME/ECI state seen by an
+/// observer fixed on the Earth. Rates are analytic, so unlike the finite
+/// differences of [`pointing_track`] they hold for a single instant.
+pub fn topocentric_motion(pos_eci_km: &[f64; 3], vel_eci_km_s: &[f64; 3], gmst_rad: f64, observer: &ObserverPosition) -> SkyMotion {
+    let [e, n, u] = ecef_to_enu(relative_ecef(pos_eci_km, gmst_rad, observer), observer);
+    // ECEF velocity: rotate by GMST, then remove the frame rotation omega x r
+    let (sin_t, cos_t) = gmst_rad.sin_cos();
+    let (x, y) = (cos_t * pos_eci_km[0] + sin_t * pos_eci_km[1], -sin_t * pos_eci_km[0] + cos_t * pos_eci_km[1]);
+    let v_ecef = [
+        cos_t * vel_eci_km_s[0] + sin_t * vel_eci_km_s[1] + EARTH_ROTATION_RAD_S * y,
+        -sin_t * vel_eci_km_s[0] + cos_t * vel_eci_km_s[1] - EARTH_ROTATION_RAD_S * x,
+        vel_eci_km_s[2],
+    ];
+    let [ve, vn, vu] = ecef_to_enu(v_ecef, observer);
+
+    let h2 = e * e + n * n;
+    let h = h2.sqrt();
+    let range2 = h2 + u * u;
+    let az_rate = (n * ve - e * vn) / h2;
+    let el_rate = (h * vu - u * (e * ve + n * vn) / h) / range2;
+    let el = u.atan2(h);
+    let across = az_rate * el.cos();
+    SkyMotion {
+        az_deg: e.atan2(n).to_degrees().rem_euclid(360.0),
+        el_deg: el.to_degrees(),
+        velocity_enu_km_s: [ve, vn, vu],
+        az_rate_deg_s: az_rate.to_degrees(),
+        el_rate_deg_s: el_rate.to_degrees(),
+        position_angle_deg: across.atan2(el_rate).to_degrees().rem_euclid(360.0),
+        angular_rate_deg_s: across.hypot(el_rate).to_degrees(),
+    }
+}
+
+/// Distance (km) from the observer to a TEME/ECI position.
+pub fn slant_range_km(pos_eci_km: &[f64; 3], gmst_rad: f64, observer: &ObserverPosition) -> f64 {
+    let [x, y, z] = relative_ecef(pos_eci_km, gmst_rad, observer);
+    (x * x + y * y + z * z).sqrt()
+}
+
+/// Rotates an ECEF vector into the observer's local east/north/up frame.
+fn ecef_to_enu([x, y, z]: [f64; 3], observer: &ObserverPosition) -> [f64; 3] {
+    let (sin_lat, cos_lat) = observer.lat_deg.to_radians().sin_cos();
+    let (sin_lon, cos_lon) = observer.lon_deg.to_radians().sin_cos();
+    [
+        -sin_lon * x + cos_lon * y,
+        -sin_lat * cos_lon * x - sin_lat * sin_lon * y + cos_lat * z,
+        cos_lat * cos_lon * x + cos_lat * sin_lon * y + sin_lat * z,
+    ]
+}
+
+/// Convert satellite TEME/ECI position to elevation and azimuth from an observer.
+/// With `aberration`, the line of sight is shifted by the observer's inertial
+/// velocity (Earth rotation) over the speed of light.
+fn elevation_azimuth_deg(
+    pos_eci_km: &[f64; 3],
+    gmst_rad: f64,
+    observer: &ObserverPosition,
+    aberration: bool,
+) -> (f64, f64) {
+    let [mut rx, mut ry, rz] = relative_ecef(pos_eci_km, gmst_rad, observer);
+
+    if aberration {
+        let range = (rx * rx + ry * ry + rz * rz).sqrt();
+        let [x_gs, y_gs, _] = geodetic_to_ecef(observer.lat_deg, observer.lon_deg, observer.alt_km);
+        // Observer velocity omega x r, expressed along the ECEF axes at this instant.
+        let (vx, vy) = (-EARTH_ROTATION_RAD_S * y_gs, EARTH_ROTATION_RAD_S * x_gs);
+        rx += range * vx / SPEED_OF_LIGHT_KM_S;
+        ry += range * vy / SPEED_OF_LIGHT_KM_S;
+    }
 
+    let [east, north, up] = ecef_to_enu([rx, ry, rz], observer);
     let range = (east * east + north * north + up * up).sqrt();
     let el = (up / range).asin();
     let az = east.atan2(north);
 
     (el.to_degrees(), az.to_degrees())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Timelike};
+    use crate::core::orbit::propagate_minutes;
+
+    #[test]
+    fn merges_passes_across_short_dips() {
+        let t0 = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let pass = |from: i64, to: i64, peak: f64| PassWindow {
+            start: t0 + Duration::seconds(from),
+            end: t0 + Duration::seconds(to),
+            tca: t0 + Duration::seconds((from + to) / 2),
+            max_elevation_deg: peak,
+            orbit_number: from,
+            aos_az_deg: from as f64 / 10.0,
+            los_az_deg: to as f64 / 10.0,
+            blocked: Vec::new(),
+        };
+        // A 30 s notch splits the first pass; the next one is an orbit later
+        let split = vec![pass(0, 200, 12.0), pass(230, 500, 35.0), pass(6000, 6300, 20.0)];
+        let merged = merge_passes(split.clone(), 60);
+        assert_eq!(merged.len(), 2);
+        assert_eq!((merged[0].start, merged[0].end), (t0, t0 + Duration::seconds(500)));
+        assert_eq!((merged[0].max_elevation_deg, merged[0].tca, merged[0].orbit_number), (35.0, t0 + Duration::seconds(365), 230));
+        assert_eq!((merged[0].aos_az_deg, merged[0].los_az_deg), (0.0, 50.0));
+        assert_eq!(merge_passes(split, 0).len(), 3);
+    }
+
+    fn window(start: DateTime<Utc>, seconds: i64, peak: f64) -> PassWindow {
+        PassWindow {
+            start,
+            end: start + Duration::seconds(seconds),
+            tca: start + Duration::seconds(seconds / 2),
+            max_elevation_deg: peak,
+            orbit_number: 0,
+            aos_az_deg: 0.0,
+            los_az_deg: 0.0,
+            blocked: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn pass_filter_drops_short_high_and_wrongly_lit_passes() {
+        let station = crate::testing::fixtures::STATION;
+        // Midsummer in Darmstadt: the Sun is high at noon UTC and well below
+        // civil twilight an hour before midnight
+        let noon = Utc.with_ymd_and_hms(2024, 6, 21, 12, 0, 0).unwrap();
+        let night = Utc.with_ymd_and_hms(2024, 6, 21, 23, 0, 0).unwrap();
+        assert!(sun_elevation_deg(noon, station.lat_deg, station.lon_deg) > 45.0);
+        assert!(sun_elevation_deg(night, station.lat_deg, station.lon_deg) < TWILIGHT_SUN_EL_DEG - 5.0);
+
+        let passes = [window(noon, 600, 30.0), window(night, 600, 30.0), window(noon, 299, 30.0), window(night, 300, 75.0)];
+        let accepted = |filter: PassFilter| passes.iter().map(|p| filter.accepts(p, &station)).collect::<Vec<_>>();
+        assert_eq!(accepted(PassFilter::default()), [true; 4]);
+        // Bounds are inclusive
+        assert_eq!(accepted(PassFilter { min_duration_s: Some(300), ..Default::default() }), [true, true, false, true]);
+        assert_eq!(accepted(PassFilter { max_max_elevation_deg: Some(30.0), ..Default::default() }), [true, true, true, false]);
+        assert_eq!(accepted(PassFilter { lighting: Some(Lighting::Day), ..Default::default() }), [true, false, true, false]);
+        assert_eq!(accepted(PassFilter { lighting: Some(Lighting::Night), ..Default::default() }), [false, true, false, true]);
+        let all = PassFilter { min_duration_s: Some(300), max_max_elevation_deg: Some(60.0), lighting: Some(Lighting::Night) };
+        assert_eq!(accepted(all), [false, true, false, false]);
+    }
+
+    #[test]
+    fn sorts_by_each_key_and_keeps_ties_in_time_order_both_ways() {
+        let t0 = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let at = |hours: i64, seconds: i64, peak: f64| window(t0 + Duration::hours(hours), seconds, peak);
+        let passes = vec![at(0, 300, 40.0), at(1, 600, 20.0), at(2, 300, 40.0), at(3, 450, 70.0), at(4, 600, 20.0)];
+        let sorted = |by: PassSort, order: Option<SortOrder>| {
+            let mut sorted = passes.clone();
+            sort_passes(&mut sorted, by, order);
+            sorted.iter().map(|p| (p.start - t0).num_hours()).collect::<Vec<_>>()
+        };
+        assert_eq!(sorted(PassSort::Start, None), [0, 1, 2, 3, 4]);
+        assert_eq!(sorted(PassSort::Start, Some(SortOrder::Desc)), [4, 3, 2, 1, 0]);
+        // Best first by default; equal keys stay earliest first
+        assert_eq!(sorted(PassSort::MaxEl, None), [3, 0, 2, 1, 4]);
+        assert_eq!(sorted(PassSort::MaxEl, Some(SortOrder::Asc)), [1, 4, 0, 2, 3]);
+        assert_eq!(sorted(PassSort::Duration, None), [1, 4, 3, 0, 2]);
+        assert_eq!(sorted(PassSort::Duration, Some(SortOrder::Asc)), [0, 2, 3, 1, 4]);
+        let by_score = sorted(PassSort::Score, None);
+        assert_eq!(by_score[0], 3);
+        assert!(by_score.windows(2).all(|w| passes[w[0] as usize].score() >= passes[w[1] as usize].score()));
+    }
+
+    fn point(min: i64, lat: f64, lon: f64) -> TrackPoint {
+        TrackPoint {
+            time: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::minutes(min),
+            position: ObserverPosition { lat_deg: lat, lon_deg: lon, alt_km: 0.0 },
+        }
+    }
+
+    #[test]
+    fn aberration_tilts_zenith_by_observer_speed_over_c() {
+        let observer = ObserverPosition { lat_deg: 0.0, lon_deg: 0.0, alt_km: 0.0 };
+        let overhead = [crate::core::coords::WGS84_A_KM + 1000.0, 0.0, 0.0];
+        let (el, _) = elevation_azimuth_deg(&overhead, 0.0, &observer, false);
+        assert!((el - 90.0).abs() < 1e-9);
+        let (el, az) = elevation_azimuth_deg(&overhead, 0.0, &observer, true);
+        let expected = (EARTH_ROTATION_RAD_S * crate::core::coords::WGS84_A_KM / SPEED_OF_LIGHT_KM_S).to_degrees();
+        assert!(((90.0 - el) - expected).abs() < 1e-7, "tilt {}", 90.0 - el);
+        // Tilted towards the east, the direction of rotation.
+        assert!((az - 90.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn look_angles_use_the_exact_time_since_epoch() {
+        // Near-Earth case 06251 of Vallado's SGP4 verification set, whose epoch
+        // falls 0.980 s into a second, at whole seconds. Positions from the
+        // independent SGP4 of scripts/golden_passes.py.
+        let text = "06251\n1 06251U 62025E   06176.82412014  .00008885  00000-0  12808-3 0  3985\n2 06251  58.0579  54.0425 0030035 139.1568 221.1854 15.56387291  6774\n";
+        let el = crate::core::tle::parse_tle_records(text)[0].to_elements().unwrap();
+        let constants = sgp4::Constants::from_elements(&el).unwrap();
+        let observer = crate::testing::fixtures::STATION;
+        let states = [
+            ("2006-06-25T21:46:43Z", [-3932.38796, 415.61269, 5473.23575]),
+            ("2006-06-25T23:46:43Z", [-1680.30399, -5684.77947, -3280.96480]),
+            ("2006-06-26T01:46:43Z", [4993.28297, 2884.95454, -3605.36904]),
+        ];
+        for (time, position) in states {
+            let t: DateTime<Utc> = time.parse().unwrap();
+            let (el_deg, az_deg) = LookOptions::default().look_angles(&el, &constants, &observer, t).unwrap();
+            let (want_el, want_az) = topocentric_look_deg(&position, gmst(t), &observer);
+            // Metres of error at thousands of kilometres; the 7 km travelled in
+            // the dropped 0.98 s would be a tenth of a degree
+            assert!((el_deg - want_el).abs() < 1e-3 && (az_deg - want_az).abs() < 1e-3, "{}: {} {}", time, el_deg - want_el, az_deg - want_az);
+        }
+    }
+
+    #[test]
+    fn light_time_shifts_look_angles_by_the_satellite_motion_during_tau() {
+        let el = crate::testing::fixtures::catalog().swap_remove(0);
+        let constants = sgp4::Constants::from_elements(&el).unwrap();
+        let observer = crate::testing::fixtures::STATION;
+        let geometric = LookOptions::default();
+        let corrected = LookOptions { light_time: true, ..Default::default() };
+        // Whole seconds from an epoch with a fractional second, so rounding the time since epoch would show
+        let t0 = el.datetime.and_utc().with_nanosecond(0).unwrap();
+        let overhead = (0..1440)
+            .map(|m| t0 + Duration::minutes(m))
+            .max_by(|a, b| {
+                let e = |t| geometric.look_angles(&el, &constants, &observer, t).unwrap().0;
+                e(*a).total_cmp(&e(*b))
+            })
+            .unwrap();
+        assert!(geometric.look_angles(&el, &constants, &observer, overhead).unwrap().0 > 10.0);
+        let (el0, az0) = geometric.look_angles(&el, &constants, &observer, overhead).unwrap();
+        let (el1, az1) = corrected.look_angles(&el, &constants, &observer, overhead).unwrap();
+        // ~7.7 km/s for a few ms at ~1000 km range: thousandths of a degree
+        assert!((el1 - el0).abs() < 0.005 && ((az1 - az0) * el0.to_radians().cos()).abs() < 0.005, "{} {}", el1 - el0, az1 - az0);
+        assert!(el1 != el0 || az1 != az0);
+    }
+
+    #[test]
+    fn rates_take_short_way_across_north_and_flag_keyhole() {
+        let t0 = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let sample = |s: i64, az: f64, el: f64| PointingSample {
+            time: t0 + Duration::seconds(s),
+            az_deg: az,
+            el_deg: el,
+            az_rate_deg_s: 0.0,
+            el_rate_deg_s: 0.0,
+        };
+        let mut samples = vec![sample(0, 358.0, 80.0), sample(1, 0.0, 85.0), sample(2, 2.0, 88.0), sample(3, 40.0, 89.0), sample(4, 42.0, 88.0)];
+        fill_rates(&mut samples);
+        assert!((samples[1].az_rate_deg_s - 2.0).abs() < 1e-9);
+        assert!((samples[0].el_rate_deg_s - 5.0).abs() < 1e-9);
+        let keyholes = keyhole_intervals(&samples, 5.0);
+        assert_eq!(keyholes, vec![(samples[2].time, samples[4].time)]);
+    }
+
+    #[test]
+    fn sky_motion_matches_finite_differences() {
+        let observer = ObserverPosition { lat_deg: 48.0, lon_deg: 11.0, alt_km: 0.5 };
+        let (pos, vel, theta) = ([4000.0, 1500.0, 5200.0], [-4.5, 5.8, 1.9], 0.3);
+        let m = topocentric_motion(&pos, &vel, theta, &observer);
+        let dt = 0.01;
+        let later = [pos[0] + vel[0] * dt, pos[1] + vel[1] * dt, pos[2] + vel[2] * dt];
+        let (el0, az0) = topocentric_look_deg(&pos, theta, &observer);
+        let (el1, az1) = topocentric_look_deg(&later, theta + EARTH_ROTATION_RAD_S * dt, &observer);
+        assert!((m.el_deg - el0).abs() < 1e-9 && (m.az_deg - az0.rem_euclid(360.0)).abs() < 1e-9);
+        assert!((m.el_rate_deg_s - (el1 - el0) / dt).abs() < 1e-4, "el rate {}", m.el_rate_deg_s);
+        assert!((m.az_rate_deg_s - (az1 - az0) / dt).abs() < 1e-4, "az rate {}", m.az_rate_deg_s);
+        let across = m.az_rate_deg_s * m.el_deg.to_radians().cos();
+        assert!((m.angular_rate_deg_s - across.hypot(m.el_rate_deg_s)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn passes_report_where_they_rise_and_set() {
+        assert_eq!([compass_point(0.0), compass_point(337.0), compass_point(134.0), compass_point(-5.0)], ["N", "NNW", "SE", "N"]);
+
+        let el = crate::testing::fixtures::catalog().swap_remove(0);
+        let observer = crate::testing::fixtures::STATION;
+        let passes = predict_passes_for_observer(&el, &Observer::Fixed(observer), el.datetime.and_utc(), 1440, 15, 10.0).unwrap();
+        let look_az = |t: DateTime<Utc>| topocentric_look_deg(&propagate_minutes(&el, minutes_since_epoch(&el, t)).unwrap().position, gmst(t), &observer).1.rem_euclid(360.0);
+        assert!(!passes.is_empty());
+        for pass in &passes {
+            assert!((pass.aos_az_deg - look_az(pass.start)).abs() < 1e-9);
+            assert!((pass.los_az_deg - look_az(pass.end)).abs() < 1e-9);
+            assert_eq!(pass.direction(), format!("{}→{}", compass_point(pass.aos_az_deg), compass_point(pass.los_az_deg)));
+        }
+    }
+
+    #[test]
+    fn azimuth_sector_wraps_through_north() {
+        let sector = AzimuthSector { start_deg: 350.0, end_deg: 10.0 };
+        assert!(sector.contains(355.0) && sector.contains(0.0) && sector.contains(-5.0));
+        assert!(!sector.contains(180.0));
+        assert!(AzimuthSector { start_deg: 90.0, end_deg: 120.0 }.contains(100.0));
+    }
+
+    #[test]
+    fn full_circle_sector_covers_every_azimuth() {
+        let full = AzimuthSector { start_deg: 0.0, end_deg: 360.0 };
+        assert!((0..720).map(|a| a as f64 * 0.5).all(|az| full.contains(az)));
+        assert!(full.contains(-0.25) && full.contains(360.0));
+        // Equal ends are a single azimuth, not the whole horizon
+        let point = AzimuthSector { start_deg: 90.0, end_deg: 90.0 };
+        assert!(point.contains(90.0) && !point.contains(90.5));
+    }
+
+    #[test]
+    fn track_interpolates_across_antimeridian() {
+        let track = [point(0, 10.0, 179.0), point(10, 20.0, -179.0)];
+        let mid = interpolate_track(&track, track[0].time + Duration::minutes(5)).unwrap();
+        assert!((mid.lat_deg - 15.0).abs() < 1e-9);
+        assert!((mid.lon_deg.abs() - 180.0).abs() < 1e-9);
+        assert!(interpolate_track(&track, track[1].time + Duration::seconds(1)).is_none());
+        assert_eq!(interpolate_track(&track, track[1].time).unwrap(), track[1].position);
+    }
+}
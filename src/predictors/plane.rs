@@ -0,0 +1,98 @@
+use chrono::{DateTime, Timelike, Utc};
+use sgp4::Elements;
+
+use crate::core::coords::gmst;
+use crate::core::orbit::minutes_since_epoch;
+use crate::predictors::repeat::secular_rates;
+
+/// Nodal regression that keeps the orbit plane fixed relative to the mean Sun:
+/// one turn per tropical year (deg/day).
+pub const SUN_SYNCHRONOUS_RATE_DEG_DAY: f64 = 360.0 / 365.242_19;
+/// Largest departure from that rate (deg/day) still counted as sun-synchronous;
+/// the local time of the node then drifts by less than half an hour a year.
+const SSO_TOLERANCE_DEG_DAY: f64 = 0.02;
+
+/// Orientation of an orbit plane relative to the Sun at some instant.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrbitPlane {
+    /// Right ascension of the ascending node, propagated from the epoch (degrees).
+    pub raan_deg: f64,
+    /// J2 nodal precession, negative for prograde orbits (deg/day).
+    pub raan_rate_deg_per_day: f64,
+    pub sun_synchronous: bool,
+    /// Local mean solar time at the ascending node (hours, `[0, 24)`).
+    pub ltan_hours: f64,
+}
+
+/// Nodal precession of `el` in degrees per day.
+pub fn raan_rate_deg_per_day(el: &Elements) -> f64 {
+    secular_rates(el).node.to_degrees() * 86_400.0
+}
+
+/// Orbit plane of `el` at `t`. The node is moved from the epoch at the secular
+/// rate, and its local time is the mean solar time at its longitude.
+pub fn orbit_plane(el: &Elements, t: DateTime<Utc>) -> OrbitPlane {
+    let rate = raan_rate_deg_per_day(el);
+    let raan_deg = (el.right_ascension + rate * minutes_since_epoch(el, t) / 1440.0).rem_euclid(360.0);
+    let ut_hours = t.num_seconds_from_midnight() as f64 / 3600.0;
+    let node_lon_deg = raan_deg - gmst(t).to_degrees();
+    OrbitPlane {
+        raan_deg,
+        raan_rate_deg_per_day: rate,
+        sun_synchronous: (rate - SUN_SYNCHRONOUS_RATE_DEG_DAY).abs() <= SSO_TOLERANCE_DEG_DAY,
+        ltan_hours: (ut_hours + node_lon_deg / 15.0).rem_euclid(24.0),
+    }
+}
+
+/// Separation of two local times of day in hours, across midnight.
+pub fn local_time_distance_hours(a: f64, b: f64) -> f64 {
+    let d = (a - b).rem_euclid(24.0);
+    d.min(24.0 - d)
+}
+
+/// `HH:MM` of a local time in hours.
+pub fn format_local_time(hours: f64) -> String {
+    let minutes = (hours * 60.0).round() as i64 % 1440;
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+/// Parses `HH:MM` or decimal hours within `[0, 24)`.
+pub fn parse_local_time(s: &str) -> Option<f64> {
+    let hours = match s.trim().split_once(':') {
+        Some((h, m)) => {
+            let (h, m) = (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?);
+            (m < 60).then_some(h as f64 + m as f64 / 60.0)?
+        }
+        None => s.trim().parse::<f64>().ok()?,
+    };
+    (0.0..24.0).contains(&hours).then_some(hours)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sun_synchronous_plane_keeps_its_local_time() {
+        let mut el = crate::testing::fixtures::catalog().swap_remove(0);
+        let iss = orbit_plane(&el, el.datetime.and_utc());
+        assert!(!iss.sun_synchronous && (iss.raan_rate_deg_per_day + 5.0).abs() < 0.2, "{:?}", iss);
+        assert!((iss.raan_deg - el.right_ascension).abs() < 1e-9);
+
+        el.inclination = 98.2;
+        el.eccentricity = 0.0001;
+        el.mean_motion = 14.5711;
+        let epoch = el.datetime.and_utc();
+        let now = orbit_plane(&el, epoch);
+        let later = orbit_plane(&el, epoch + chrono::Duration::days(90));
+        assert!(now.sun_synchronous, "{:?}", now);
+        assert!(local_time_distance_hours(now.ltan_hours, later.ltan_hours) < 0.05);
+
+        assert!((local_time_distance_hours(23.5, 0.25) - 0.75).abs() < 1e-12);
+        assert_eq!(format_local_time(10.5), "10:30");
+        assert_eq!(format_local_time(23.9999), "00:00");
+        assert_eq!(parse_local_time("22:30"), Some(22.5));
+        assert_eq!(parse_local_time("10.75"), Some(10.75));
+        assert!(parse_local_time("24:00").is_none() && parse_local_time("9:60").is_none());
+    }
+}
@@ -0,0 +1,111 @@
+use std::f64::consts::PI;
+
+use sgp4::Elements;
+
+use crate::core::orbit::{EARTH_RADIUS_KM, EARTH_ROTATION_RAD_S, MU_EARTH_KM3_S2};
+
+/// Second zonal harmonic (WGS72, as used by SGP4).
+const J2: f64 = 1.082_616e-3;
+/// Longest repeat cycle searched for, in days.
+const MAX_CYCLE_DAYS: u32 = 30;
+/// Largest equator-crossing shift (degrees of longitude) after one cycle for the
+/// orbit to count as repeating; about 5.5 km at the equator.
+const MAX_SHIFT_DEG: f64 = 0.05;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RepeatCycle {
+    /// Revolutions per cycle.
+    pub revolutions: u32,
+    /// Nodal days per cycle.
+    pub days: u32,
+    /// Eastward shift of the equator crossings after one cycle (degrees).
+    pub shift_deg: f64,
+}
+
+/// J2 secular rates of the mean elements (rad/s).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SecularRates {
+    /// Rate of the right ascension of the ascending node.
+    pub node: f64,
+    pub perigee: f64,
+    pub anomaly: f64,
+}
+
+/// J2 secular rates of `el`. The TLE (Kozai) mean motion is first converted to
+/// the Brouwer mean motion the way SGP4 does.
+pub fn secular_rates(el: &Elements) -> SecularRates {
+    let n_kozai = el.mean_motion * 2.0 * PI / 86_400.0;
+    let (sin_i, cos_i) = el.inclination.to_radians().sin_cos();
+    let e2 = el.eccentricity * el.eccentricity;
+    let beta = (1.0 - e2).sqrt();
+
+    let a1 = (MU_EARTH_KM3_S2 / (n_kozai * n_kozai)).cbrt() / EARTH_RADIUS_KM;
+    let d1 = 0.75 * J2 * (3.0 * cos_i * cos_i - 1.0) / (a1 * a1 * beta.powi(3));
+    let a0 = a1 * (1.0 - d1 / 3.0 - d1 * d1 - 134.0 / 81.0 * d1.powi(3));
+    let n = n_kozai / (1.0 + d1 * a1 * a1 / (a0 * a0));
+    let a = a1 * (n_kozai / n).powf(2.0 / 3.0);
+
+    let p = a * (1.0 - e2);
+    let k = 1.5 * J2 * n / (p * p);
+    SecularRates {
+        node: -k * cos_i,
+        perigee: k * (2.0 - 2.5 * sin_i * sin_i),
+        anomaly: n + k * beta * (1.0 - 1.5 * sin_i * sin_i),
+    }
+}
+
+/// Revolutions per nodal day, i.e. per rotation of the Earth relative to the
+/// regressing orbit plane, from the J2 secular rates.
+pub fn revolutions_per_nodal_day(el: &Elements) -> f64 {
+    let rates = secular_rates(el);
+    (rates.perigee + rates.anomaly) / (EARTH_ROTATION_RAD_S - rates.node)
+}
+
+/// Shortest cycle of whole revolutions in whole nodal days matching
+/// `revs_per_day`, if one exists within `MAX_CYCLE_DAYS`.
+pub fn repeat_cycle(revs_per_day: f64) -> Option<RepeatCycle> {
+    if !revs_per_day.is_finite() || revs_per_day <= 0.0 {
+        return None;
+    }
+    (1..=MAX_CYCLE_DAYS).find_map(|days| {
+        let revs = revs_per_day * days as f64;
+        let revolutions = revs.round();
+        // The residual fraction of a revolution moves the crossings by that
+        // fraction of the spacing between adjacent ground tracks.
+        let shift_deg = (revs - revolutions) * 360.0 / revs_per_day;
+        (revolutions >= 1.0 && shift_deg.abs() <= MAX_SHIFT_DEG).then_some(RepeatCycle {
+            revolutions: revolutions as u32,
+            days,
+            shift_deg,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_shortest_cycle() {
+        let landsat = repeat_cycle(233.0 / 16.0).unwrap();
+        assert_eq!((landsat.revolutions, landsat.days), (233, 16));
+        let daily = repeat_cycle(15.0 + 1e-5).unwrap();
+        assert_eq!((daily.revolutions, daily.days), (15, 1));
+        assert!(repeat_cycle(15.01).is_none_or(|c| c.days > 1));
+    }
+
+    #[test]
+    fn sun_synchronous_705km_is_near_233_in_16() {
+        let mut el = Elements::from_tle(
+            None,
+            b"1 25544U 98067A   08264.51782528 -.00002182  00000-0 -11606-4 0  2927",
+            b"2 25544  51.6416 247.4627 0006703 130.5360 325.0288 15.72125391563537",
+        )
+        .unwrap();
+        el.inclination = 98.2;
+        el.eccentricity = 0.0001;
+        el.mean_motion = 14.5711;
+        let q = revolutions_per_nodal_day(&el);
+        assert!((q - 233.0 / 16.0).abs() < 0.01, "{}", q);
+    }
+}
@@ -0,0 +1,78 @@
+use std::f64::consts::TAU;
+
+use chrono::{DateTime, Utc};
+use sgp4::Elements;
+
+use crate::core::orbit::{cross, dot, minutes_since_epoch, propagate_minutes};
+use crate::predictors::repeat::secular_rates;
+
+/// Revolutions since the epoch's last ascending node, from the mean argument of
+/// latitude and its J2 rate. Drifts slowly against the osculating orbit.
+fn mean_revolutions(el: &Elements, t: DateTime<Utc>) -> f64 {
+    let rates = secular_rates(el);
+    let since_node = (el.argument_of_perigee + el.mean_anomaly).rem_euclid(360.0) / 360.0;
+    since_node + (rates.perigee + rates.anomaly) * minutes_since_epoch(el, t) * 60.0 / TAU
+}
+
+/// Fraction of a revolution since the last ascending node, in [0, 1): the
+/// argument of latitude of an ECI state.
+pub fn node_fraction(position: &[f64; 3], velocity: &[f64; 3]) -> f64 {
+    let h = cross(position, velocity);
+    // Towards the ascending node: the equatorial plane crossed with the orbit plane
+    let node = [-h[1], h[0], 0.0];
+    let norm = |v: &[f64; 3]| dot(v, v).sqrt();
+    let cos_u = (dot(&node, position) / (norm(&node) * norm(position))).clamp(-1.0, 1.0);
+    let u = if position[2] >= 0.0 { cos_u.acos() } else { TAU - cos_u.acos() };
+    (u / TAU).rem_euclid(1.0)
+}
+
+/// Revolution (orbit) number at `t` for the propagated state there: the count in
+/// the element set plus the ascending node crossings since its epoch. The state
+/// locates the last crossing, and the mean motion only has to count whole
+/// revolutions to within half of one, so the number stays right over long spans.
+pub fn orbit_number_at(el: &Elements, t: DateTime<Utc>, position: &[f64; 3], velocity: &[f64; 3]) -> i64 {
+    let fraction = node_fraction(position, velocity);
+    el.revolution_number as i64 + (mean_revolutions(el, t) - fraction).round() as i64
+}
+
+/// [`orbit_number_at`], propagating the elements to `t`.
+pub fn orbit_number(el: &Elements, t: DateTime<Utc>) -> sgp4::Result<i64> {
+    let pred = propagate_minutes(el, minutes_since_epoch(el, t))?;
+    Ok(orbit_number_at(el, t, &pred.position, &pred.velocity))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::predictors::events::{orbital_events, OrbitalEventKind};
+    use chrono::Duration;
+
+    #[test]
+    fn increments_at_each_ascending_node() {
+        let el = crate::testing::fixtures::catalog().swap_remove(0);
+        let epoch = el.datetime.and_utc();
+        assert_eq!(orbit_number(&el, epoch).unwrap(), el.revolution_number as i64);
+
+        // A month out, the count steps by one across every ascending node
+        let start = epoch + Duration::days(30);
+        let nodes: Vec<_> = orbital_events(&el, start, start + Duration::hours(6))
+            .unwrap()
+            .into_iter()
+            .filter(|e| e.kind == OrbitalEventKind::AscendingNode)
+            .collect();
+        assert!(nodes.len() >= 3);
+        let second = Duration::seconds(1);
+        let mut previous = None;
+        for node in nodes {
+            let (before, after) = (orbit_number(&el, node.time - second).unwrap(), orbit_number(&el, node.time + second).unwrap());
+            assert_eq!(after, before + 1);
+            if let Some(p) = previous {
+                assert_eq!(after, p + 1);
+            }
+            previous = Some(after);
+        }
+        // About 15.7 revolutions a day
+        let elapsed = previous.unwrap() - el.revolution_number as i64;
+        assert!((470..=477).contains(&elapsed), "{}", elapsed);
+    }
+}
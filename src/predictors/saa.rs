@@ -0,0 +1,89 @@
+use chrono::{DateTime, Utc};
+use sgp4::Elements;
+
+use crate::predictors::groundtrack::{ground_track, GroundTrackPoint};
+
+/// Outline `(lon, lat)` of the South Atlantic Anomaly where trapped protons
+/// above 10 MeV reach low Earth orbit, roughly the AP-8 contour at 500 km. The
+/// anomaly drifts westwards by a few tenths of a degree per year; this is a
+/// planning aid, not a flux model.
+pub const SAA_BOUNDARY: [[f64; 2]; 15] = [
+    [-90.0, -20.0],
+    [-80.0, -8.0],
+    [-60.0, -2.0],
+    [-40.0, -2.0],
+    [-20.0, -6.0],
+    [0.0, -10.0],
+    [20.0, -18.0],
+    [35.0, -28.0],
+    [30.0, -40.0],
+    [10.0, -46.0],
+    [-20.0, -50.0],
+    [-50.0, -50.0],
+    [-75.0, -45.0],
+    [-88.0, -35.0],
+    [-90.0, -20.0],
+];
+/// Below this altitude (km) the anomaly's flux is negligible.
+pub const SAA_MIN_ALT_KM: f64 = 200.0;
+/// Above this altitude (km) the inner belt surrounds the whole orbit and the
+/// outline no longer applies.
+pub const SAA_MAX_ALT_KM: f64 = 2000.0;
+
+/// Whether a point lies inside the anomaly.
+pub fn in_saa(lat_deg: f64, lon_deg: f64, alt_km: f64) -> bool {
+    (SAA_MIN_ALT_KM..=SAA_MAX_ALT_KM).contains(&alt_km) && crate::core::aoi::contains(&SAA_BOUNDARY, lon_deg, lat_deg)
+}
+
+/// Intervals of a track spent inside the anomaly, from the first sample inside
+/// to the first one outside again.
+pub fn saa_crossings(track: &[GroundTrackPoint]) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut out = Vec::new();
+    let mut since: Option<DateTime<Utc>> = None;
+    for p in track {
+        let inside = in_saa(p.lat_deg, p.lon_deg, p.alt_km);
+        match (inside, since) {
+            (true, None) => since = Some(p.time),
+            (false, Some(start)) => {
+                out.push((start, p.time));
+                since = None;
+            }
+            _ => {}
+        }
+    }
+    if let (Some(start), Some(last)) = (since, track.last()) {
+        out.push((start, last.time));
+    }
+    out
+}
+
+/// Anomaly crossings of a satellite between `start` and `end`, sampled every
+/// `step_seconds`.
+pub fn saa_intervals(el: &Elements, start: DateTime<Utc>, end: DateTime<Utc>, step_seconds: i64) -> sgp4::Result<Vec<(DateTime<Utc>, DateTime<Utc>)>> {
+    Ok(saa_crossings(&ground_track(el, start, end, step_seconds)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn low_orbits_cross_the_anomaly_off_brazil() {
+        assert!(in_saa(-25.0, -45.0, 420.0));
+        assert!(!in_saa(-25.0, -45.0, 150.0) && !in_saa(-25.0, -45.0, 20_000.0));
+        assert!(!in_saa(50.0, 8.0, 420.0) && !in_saa(-25.0, 120.0, 420.0));
+
+        // The ISS passes through it on two runs of consecutive revolutions a day
+        let el = &crate::testing::fixtures::catalog()[0];
+        let start = el.datetime.and_utc();
+        let end = start + chrono::Duration::days(1);
+        let crossings = saa_intervals(el, start, end, 30).unwrap();
+        assert!((6..=16).contains(&crossings.len()));
+        let track = ground_track(el, start, end, 30).unwrap();
+        for (from, to) in &crossings {
+            assert!(from < to && (*to - *from).num_minutes() < 30);
+            let first = track.iter().find(|p| p.time == *from).unwrap();
+            assert!(in_saa(first.lat_deg, first.lon_deg, first.alt_km));
+        }
+    }
+}
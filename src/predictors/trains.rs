@@ -0,0 +1,169 @@
+use chrono::{DateTime, Duration, Utc};
+use sgp4::Elements;
+
+use crate::core::coords::gmst;
+use crate::core::orbit::minutes_since_epoch;
+use crate::core::sun::{shadow_margin_km, sun_elevation_deg};
+use crate::predictors::mount::DARK_SKY_SUN_EL_DEG;
+use crate::predictors::passes::{compass_point, predict_passes_for_observer, topocentric_look_deg, Observer, ObserverPosition};
+
+/// Sampling step (s) of the pass search.
+const PASS_STEP_S: i64 = 30;
+/// Sampling step (s) inside a pass when looking for the visible part.
+const VISIBILITY_STEP_S: i64 = 5;
+
+/// The part of one satellite's pass where it can be seen with the eye: above
+/// the elevation mask, sunlit, under a dark sky.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VisiblePass {
+    pub norad_id: u64,
+    pub name: Option<String>,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub max_elevation_deg: f64,
+    pub start_az_deg: f64,
+    pub end_az_deg: f64,
+}
+
+/// Satellites of one launch crossing the sky one after another.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Train {
+    /// In order of appearance.
+    pub satellites: Vec<VisiblePass>,
+}
+
+impl Train {
+    pub fn start(&self) -> DateTime<Utc> {
+        self.satellites[0].start
+    }
+
+    /// When the last satellite disappears.
+    pub fn end(&self) -> DateTime<Utc> {
+        self.satellites.iter().map(|s| s.end).max().unwrap_or_else(|| self.start())
+    }
+
+    pub fn max_elevation_deg(&self) -> f64 {
+        self.satellites.iter().map(|s| s.max_elevation_deg).fold(f64::MIN, f64::max)
+    }
+
+    /// Compass points where the first satellite appears and disappears, e.g. `WSW→NE`.
+    pub fn direction(&self) -> String {
+        let first = &self.satellites[0];
+        format!("{}→{}", compass_point(first.start_az_deg), compass_point(first.end_az_deg))
+    }
+}
+
+/// Visible parts of the passes of one satellite over `duration_min` from `start`.
+pub fn visible_passes(elements: &Elements, name: Option<&str>, observer: &ObserverPosition, start: DateTime<Utc>, duration_min: i64, min_el: f64) -> sgp4::Result<Vec<VisiblePass>> {
+    let windows = predict_passes_for_observer(elements, &Observer::Fixed(*observer), start, duration_min, PASS_STEP_S, min_el)?;
+    let constants = sgp4::Constants::from_elements(elements)?;
+    let mut visible = Vec::new();
+    for w in windows {
+        let mut part: Option<VisiblePass> = None;
+        let mut t = w.start;
+        while t <= w.end {
+            let position = constants.propagate(minutes_since_epoch(elements, t))?.position;
+            let (el_deg, az_deg) = topocentric_look_deg(&position, gmst(t), observer);
+            let seen = el_deg >= min_el
+                && shadow_margin_km(&position, t) >= 0.0
+                && sun_elevation_deg(t, observer.lat_deg, observer.lon_deg) < DARK_SKY_SUN_EL_DEG;
+            if seen {
+                let az_deg = az_deg.rem_euclid(360.0);
+                let p = part.get_or_insert_with(|| VisiblePass {
+                    norad_id: elements.norad_id,
+                    name: name.map(str::to_string),
+                    start: t,
+                    end: t,
+                    max_elevation_deg: el_deg,
+                    start_az_deg: az_deg,
+                    end_az_deg: az_deg,
+                });
+                p.end = t;
+                p.end_az_deg = az_deg;
+                p.max_elevation_deg = p.max_elevation_deg.max(el_deg);
+            }
+            t += Duration::seconds(VISIBILITY_STEP_S);
+        }
+        visible.extend(part);
+    }
+    Ok(visible)
+}
+
+/// Chains visible passes into trains: each satellite appears at most `max_gap`
+/// after the one before it, and a train has at least `min_count` satellites.
+pub fn find_trains(mut passes: Vec<VisiblePass>, max_gap: Duration, min_count: usize) -> Vec<Train> {
+    passes.sort_by_key(|p| p.start);
+    let mut trains = Vec::new();
+    let mut current: Vec<VisiblePass> = Vec::new();
+    for p in passes {
+        if current.last().is_some_and(|last| p.start - last.start > max_gap) {
+            let chain = std::mem::take(&mut current);
+            if chain.len() >= min_count.max(1) {
+                trains.push(Train { satellites: chain });
+            }
+        }
+        current.push(p);
+    }
+    if current.len() >= min_count.max(1) {
+        trains.push(Train { satellites: current });
+    }
+    trains
+}
+
+/// Visible trains of `elements`, each with its name, typically the pieces of
+/// one recent launch that still fly in a line. Element sets that cannot be
+/// propagated are left out.
+pub fn predict_trains(
+    elements: &[(&Elements, Option<&str>)],
+    observer: &ObserverPosition,
+    start: DateTime<Utc>,
+    duration_min: i64,
+    min_el: f64,
+    max_gap: Duration,
+    min_count: usize,
+) -> Vec<Train> {
+    let passes = elements.iter().filter_map(|&(el, name)| visible_passes(el, name, observer, start, duration_min, min_el).ok()).flatten().collect();
+    find_trains(passes, max_gap, min_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pass(norad_id: u64, start_s: i64) -> VisiblePass {
+        let t0 = DateTime::parse_from_rfc3339("2024-03-01T19:00:00Z").unwrap().with_timezone(&Utc);
+        VisiblePass {
+            norad_id,
+            name: None,
+            start: t0 + Duration::seconds(start_s),
+            end: t0 + Duration::seconds(start_s + 240),
+            max_elevation_deg: 30.0 + norad_id as f64,
+            start_az_deg: 250.0,
+            end_az_deg: 45.0,
+        }
+    }
+
+    #[test]
+    fn chains_satellites_that_follow_each_other_closely() {
+        // Three satellites 20-40 s apart, a straggler 10 min later, then a pair the next orbit
+        let passes = vec![pass(3, 60), pass(1, 0), pass(2, 20), pass(4, 660), pass(5, 5760), pass(6, 5790)];
+        let trains = find_trains(passes.clone(), Duration::seconds(60), 3);
+        assert_eq!(trains.len(), 1);
+        let ids: Vec<u64> = trains[0].satellites.iter().map(|s| s.norad_id).collect();
+        assert_eq!(ids, [1, 2, 3]);
+        assert_eq!((trains[0].end() - trains[0].start()).num_seconds(), 300);
+        assert_eq!((trains[0].max_elevation_deg(), trains[0].direction().as_str()), (33.0, "WSW→NE"));
+        assert_eq!(find_trains(passes, Duration::seconds(60), 2).len(), 2);
+    }
+
+    #[test]
+    fn visible_parts_lie_inside_dark_sunlit_passes() {
+        let el = &crate::testing::fixtures::catalog()[0];
+        let observer = crate::testing::fixtures::STATION;
+        let start = el.datetime.and_utc();
+        for p in visible_passes(el, None, &observer, start, 3 * 1440, 10.0).unwrap() {
+            assert!(p.start <= p.end && p.max_elevation_deg >= 10.0);
+            assert!(sun_elevation_deg(p.start, observer.lat_deg, observer.lon_deg) < DARK_SKY_SUN_EL_DEG);
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use rusqlite::Connection;
+use sgp4::Elements;
+use tracing::info;
+
+use crate::core::orbit::ric_components;
+use crate::utils::db::{self, DbError};
+
+/// Only element sets this close to the newest one contribute to the estimate.
+pub const HISTORY_WINDOW_DAYS: i64 = 30;
+
+/// One-sigma position uncertainty in the radial / along-track / cross-track frame.
+#[derive(Debug, Clone, Copy)]
+pub struct PositionSigma {
+    pub radial_km: f64,
+    pub along_track_km: f64,
+    pub cross_track_km: f64,
+    /// Number of older element sets compared against the reference.
+    pub samples: usize,
+    pub reference_epoch: DateTime<Utc>,
+}
+
+impl PositionSigma {
+    /// Root-sum-square of the three components.
+    pub fn total_km(&self) -> f64 {
+        (self.radial_km.powi(2) + self.along_track_km.powi(2) + self.cross_track_km.powi(2)).sqrt()
+    }
+}
+
+/// Estimates position uncertainty from the dispersion of historical element sets.
+/// The newest set is the reference; every older set is propagated to the reference
+/// epoch and its RIC offset from the reference state is accumulated as an RMS.
+/// Returns `None` with fewer than two usable sets.
+pub fn estimate_sigma(history: &[Elements]) -> Option<PositionSigma> {
+    let reference = history.iter().max_by_key(|e| e.datetime)?;
+    let ref_state = sgp4::Constants::from_elements(reference).ok()?.propagate(0.0).ok()?;
+
+    let mut sum_sq = [0.0f64; 3];
+    let mut samples = 0usize;
+    for el in history {
+        if el.datetime >= reference.datetime {
+            continue;
+        }
+        let minutes = (reference.datetime - el.datetime).num_milliseconds() as f64 / 60_000.0;
+        let Ok(pred) = sgp4::Constants::from_elements(el).and_then(|c| c.propagate(minutes)) else {
+            continue;
+        };
+        let delta = [
+            pred.position[0] - ref_state.position[0],
+            pred.position[1] - ref_state.position[1],
+            pred.position[2] - ref_state.position[2],
+        ];
+        let ric = ric_components(&ref_state.position, &ref_state.velocity, &delta);
+        for (acc, v) in sum_sq.iter_mut().zip(ric.iter()) {
+            *acc += v * v;
+        }
+        samples += 1;
+    }
+    if samples == 0 {
+        return None;
+    }
+
+    let n = samples as f64;
+    Some(PositionSigma {
+        radial_km: (sum_sq[0] / n).sqrt(),
+        along_track_km: (sum_sq[1] / n).sqrt(),
+        cross_track_km: (sum_sq[2] / n).sqrt(),
+        samples,
+        reference_epoch: reference.datetime.and_utc(),
+    })
+}
+
+/// Builds the per-satellite uncertainty table from the archived TLE history.
+pub fn estimate_from_history(conn: &Connection) -> Result<HashMap<u64, PositionSigma>, DbError> {
+    let since = (Utc::now() - chrono::Duration::days(HISTORY_WINDOW_DAYS)).to_rfc3339_opts(chrono::SecondsFormat::Micros, true);
+    let rows = db::list_tle_history_since(conn, &since)?;
+
+    let mut grouped: HashMap<u64, Vec<Elements>> = HashMap::new();
+    for row in rows {
+        if let Ok(el) = sgp4::Elements::from_tle(row.name.clone(), row.line1.as_bytes(), row.line2.as_bytes()) {
+            grouped.entry(row.norad_id).or_default().push(el);
+        }
+    }
+
+    let out: HashMap<u64, PositionSigma> = grouped
+        .into_iter()
+        .filter_map(|(norad, hist)| estimate_sigma(&hist).map(|s| (norad, s)))
+        .collect();
+    info!(count = out.len(), "Estimated position uncertainty from TLE history");
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::fixtures;
+
+    /// The ISS element set moved `days` earlier with its mean anomaly wound back
+    /// and a small mean motion error, so it drifts further from the reference
+    /// the longer it is propagated.
+    fn earlier_set(days: f64) -> Elements {
+        let mut el = fixtures::catalog().swap_remove(0);
+        let reference_mean_motion = el.mean_motion;
+        el.datetime -= chrono::Duration::milliseconds((days * 86_400_000.0) as i64);
+        el.mean_motion += 1e-5;
+        el.mean_anomaly = (el.mean_anomaly - 360.0 * reference_mean_motion * days).rem_euclid(360.0);
+        el
+    }
+
+    #[test]
+    fn sigma_grows_with_the_gap_between_element_sets() {
+        let sigma_for = |days: f64| {
+            let history = vec![fixtures::catalog().swap_remove(0), earlier_set(days)];
+            estimate_sigma(&history).unwrap()
+        };
+        let gaps = [0.5, 2.0, 7.0];
+        let totals: Vec<f64> = gaps.iter().map(|&d| sigma_for(d).total_km()).collect();
+        assert!(totals.windows(2).all(|w| w[0] < w[1]), "{:?}", totals);
+        let week = sigma_for(7.0);
+        assert_eq!(week.samples, 1);
+        assert_eq!(week.reference_epoch, fixtures::catalog()[0].datetime.and_utc());
+
+        assert!(estimate_sigma(&fixtures::catalog()[..1]).is_none());
+    }
+}
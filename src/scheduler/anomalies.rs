@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rusqlite::Connection;
+use tracing::{error, warn};
+
+use crate::analyzers::snapshot_anomalies::{check, Thresholds};
+use crate::scheduler::leader::Leadership;
+use crate::utils::db::{self, DbError, SnapshotAnomaly, SnapshotRow};
+use crate::utils::metrics;
+
+/// How often new snapshots are checked.
+pub const SCAN_PERIOD: Duration = Duration::from_secs(10 * 60);
+/// Snapshots read per query.
+const PAGE_SIZE: usize = 1000;
+/// Name of the scan's position in `analyzer_cursors`.
+const CURSOR: &str = "snapshot_anomalies";
+
+/// Spawns the periodic anomaly scan. The first run starts immediately; runs are
+/// skipped while this instance is not the leader.
+pub fn spawn(leadership: Arc<Leadership>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SCAN_PERIOD);
+        loop {
+            interval.tick().await;
+            if !leadership.is_leader() {
+                continue;
+            }
+            let result = tokio::task::spawn_blocking(|| db::open_or_init().and_then(|conn| scan(&conn, &Thresholds::default()))).await;
+            match result {
+                Ok(Ok(0)) => {}
+                Ok(Ok(found)) => warn!(found, "Flagged implausible snapshots"),
+                Ok(Err(e)) => error!(error = %e, "Snapshot anomaly scan failed"),
+                Err(e) => error!(error = %e, "Snapshot anomaly scan task panicked"),
+            }
+        }
+    })
+}
+
+/// Checks the snapshots written since the last scan, each against the previous
+/// snapshot of its satellite, and records what is flagged. Returns the number of
+/// new anomalies.
+pub fn scan(conn: &Connection, limits: &Thresholds) -> Result<usize, DbError> {
+    let mut after = db::analyzer_cursor(conn, CURSOR)?;
+    let mut previous: HashMap<u64, SnapshotRow> = HashMap::new();
+    let mut found = 0;
+    let detected_at = chrono::Utc::now().to_rfc3339();
+    loop {
+        let page = db::list_snapshots_page(conn, None, after, PAGE_SIZE)?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        let last_id = last.id;
+        for snapshot in page {
+            let before = match previous.remove(&snapshot.norad_id) {
+                Some(s) => Some(s),
+                None => db::previous_snapshot(conn, snapshot.norad_id, snapshot.id)?,
+            };
+            for anomaly in check(before.as_ref(), &snapshot, limits) {
+                let row = SnapshotAnomaly {
+                    id: 0,
+                    snapshot_id: snapshot.id,
+                    norad_id: snapshot.norad_id,
+                    timestamp: snapshot.timestamp.clone(),
+                    kind: anomaly.kind.as_str().to_string(),
+                    magnitude_km: anomaly.magnitude_km,
+                    detail: anomaly.detail,
+                    detected_at: detected_at.clone(),
+                };
+                if db::insert_snapshot_anomaly(conn, &row)? {
+                    metrics::record_snapshot_anomaly(anomaly.kind);
+                    found += 1;
+                }
+            }
+            previous.insert(snapshot.norad_id, snapshot);
+        }
+        after = last_id;
+        db::set_analyzer_cursor(conn, CURSOR, after)?;
+    }
+    Ok(found)
+}
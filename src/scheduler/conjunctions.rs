@@ -0,0 +1,101 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use arc_swap::ArcSwap;
+use tracing::{error, info, warn};
+
+use crate::core::catalog::Catalog;
+use crate::predictors::conjunctions::{screen_asset, ScreeningOptions};
+use crate::scheduler::leader::Leadership;
+use crate::utils::db::{self, DbError};
+use crate::utils::notify::{self, Alert};
+
+/// How often the protected assets are screened against the catalog.
+pub const SCREENING_PERIOD: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Spawns the daily screening job. The first run starts immediately; runs are
+/// skipped while this instance is not the leader. Each run screens the catalog
+/// current at the time.
+pub fn spawn_daily(elements: Arc<ArcSwap<Catalog>>, leadership: Arc<Leadership>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SCREENING_PERIOD);
+        loop {
+            interval.tick().await;
+            if leadership.is_leader() {
+                run_once(elements.load_full()).await;
+            } else {
+                info!("Not the leader, skipping conjunction screening");
+            }
+        }
+    })
+}
+
+/// Screens every protected asset once, stores the events and sends alerts for
+/// approaches closer than the asset's alert threshold.
+pub async fn run_once(elements: Arc<Catalog>) {
+    let result = tokio::task::spawn_blocking(move || screen_and_store(&elements)).await;
+    match result {
+        Ok(Ok((stored, alerts))) => {
+            info!(stored, alerts = alerts.len(), "Conjunction screening finished");
+            for alert in &alerts {
+                notify::send(alert).await;
+            }
+        }
+        Ok(Err(e)) => error!(error = %e, "Conjunction screening failed"),
+        Err(e) => error!(error = %e, "Conjunction screening task panicked"),
+    }
+}
+
+fn screen_and_store(elements: &Catalog) -> Result<(usize, Vec<Alert>), DbError> {
+    let conn = db::open_or_init()?;
+    // Tenants protecting the same satellite share one screening at the widest threshold
+    let mut assets: Vec<db::ProtectedAsset> = Vec::new();
+    for asset in db::list_protected_assets(&conn, None)? {
+        match assets.iter_mut().find(|a| a.norad_id == asset.norad_id) {
+            Some(a) => a.alert_threshold_km = a.alert_threshold_km.max(asset.alert_threshold_km),
+            None => assets.push(asset),
+        }
+    }
+    let now = chrono::Utc::now();
+    let screened_at = now.to_rfc3339();
+
+    let mut stored = 0usize;
+    let mut alerts = Vec::new();
+    for asset in assets {
+        let Some(asset_el) = elements.get(asset.norad_id) else {
+            warn!(norad = asset.norad_id, "Protected asset not in loaded TLEs, skipping");
+            continue;
+        };
+        let events = match screen_asset(asset_el, elements, now, ScreeningOptions::default()) {
+            Ok(ev) => ev,
+            Err(e) => {
+                warn!(error = %e, norad = asset.norad_id, "Screening failed for asset");
+                continue;
+            }
+        };
+        stored += db::replace_conjunctions(&conn, asset.norad_id, &screened_at, &events, &screened_at)?;
+        for ev in events {
+            if ev.miss_distance_km < asset.alert_threshold_km {
+                alerts.push(Alert {
+                    kind: "conjunction",
+                    message: format!(
+                        "Conjunction: {} vs {} at {} miss {:.3} km",
+                        ev.asset_norad_id,
+                        ev.secondary_norad_id,
+                        ev.tca.to_rfc3339(),
+                        ev.miss_distance_km
+                    ),
+                    payload: serde_json::json!({
+                        "asset_norad_id": ev.asset_norad_id,
+                        "secondary_norad_id": ev.secondary_norad_id,
+                        "secondary_name": ev.secondary_name,
+                        "tca": ev.tca,
+                        "miss_distance_km": ev.miss_distance_km,
+                        "relative_speed_km_s": ev.relative_speed_km_s,
+                    }),
+                });
+            }
+        }
+    }
+    Ok((stored, alerts))
+}
@@ -0,0 +1,142 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use redis::AsyncCommands;
+use tracing::{info, warn};
+
+/// Environment variable of the Redis shared by cooperating instances (the same
+/// one the response cache uses).
+const URL_ENV: &str = "STFCM_REDIS_URL";
+/// Optional stable name of this instance; defaults to `<hostname>-<pid>`.
+const INSTANCE_ENV: &str = "STFCM_INSTANCE_ID";
+const LEASE_KEY: &str = "stfcm:v1:leader";
+/// Lease lifetime; renewed every third of it, so a dead leader is replaced within it.
+pub const LEASE_TTL: Duration = Duration::from_secs(30);
+/// How long shared TLE sets stay readable for followers.
+const SHARED_TLE_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Extends the lease only if this instance still holds it.
+const RENEW_SCRIPT: &str = r#"
+if redis.call('get', KEYS[1]) == ARGV[1] then
+    return redis.call('pexpire', KEYS[1], ARGV[2])
+else
+    return 0
+end"#;
+
+/// Which instance fetches TLEs and runs the maintenance jobs. Without a shared
+/// Redis every campaigning instance is its own leader.
+pub struct Leadership {
+    instance_id: String,
+    conn: Option<redis::aio::ConnectionManager>,
+    leader: AtomicBool,
+}
+
+impl Leadership {
+    /// Joins the election on `STFCM_REDIS_URL` and tries to take the lease once
+    /// before returning; renewal then runs in the background. With `campaign`
+    /// false the instance stays a follower (read-only mode).
+    pub async fn from_env(campaign: bool) -> Arc<Leadership> {
+        let instance_id = std::env::var(INSTANCE_ENV).unwrap_or_else(|_| {
+            format!("{}-{}", std::env::var("HOSTNAME").unwrap_or_else(|_| "stfcm".to_string()), std::process::id())
+        });
+        let conn = match std::env::var(URL_ENV).ok().filter(|u| !u.is_empty()) {
+            None => None,
+            Some(url) => match redis::Client::open(url) {
+                Ok(client) => match client.get_connection_manager().await {
+                    Ok(conn) => Some(conn),
+                    Err(e) => {
+                        warn!(error = %e, "Failed to connect to Redis, running as a single instance");
+                        None
+                    }
+                },
+                Err(e) => {
+                    warn!(error = %e, "Invalid Redis URL, running as a single instance");
+                    None
+                }
+            },
+        };
+        let leadership = Arc::new(Leadership { instance_id, leader: AtomicBool::new(campaign && conn.is_none()), conn });
+        if campaign && leadership.conn.is_some() {
+            leadership.campaign().await;
+            let background = leadership.clone();
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(LEASE_TTL / 3);
+                interval.tick().await;
+                loop {
+                    interval.tick().await;
+                    background.campaign().await;
+                }
+            });
+        }
+        leadership
+    }
+
+    pub fn is_leader(&self) -> bool {
+        self.leader.load(Ordering::Relaxed)
+    }
+
+    /// Whether instances coordinate through Redis.
+    pub fn is_shared(&self) -> bool {
+        self.conn.is_some()
+    }
+
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
+    /// Renews the lease when held, otherwise tries to take it. Any Redis error
+    /// drops leadership so two instances never both act as leader.
+    async fn campaign(&self) {
+        let Some(mut conn) = self.conn.clone() else { return };
+        let ttl_ms = LEASE_TTL.as_millis() as u64;
+        let was_leader = self.is_leader();
+        let result: redis::RedisResult<bool> = if was_leader {
+            redis::Script::new(RENEW_SCRIPT)
+                .key(LEASE_KEY)
+                .arg(&self.instance_id)
+                .arg(ttl_ms)
+                .invoke_async::<i64>(&mut conn)
+                .await
+                .map(|renewed| renewed == 1)
+        } else {
+            redis::cmd("SET")
+                .arg(LEASE_KEY)
+                .arg(&self.instance_id)
+                .arg("NX")
+                .arg("PX")
+                .arg(ttl_ms)
+                .query_async::<Option<String>>(&mut conn)
+                .await
+                .map(|reply| reply.is_some())
+        };
+        let is_leader = result.unwrap_or_else(|e| {
+            warn!(error = %e, "Leader election failed");
+            false
+        });
+        self.leader.store(is_leader, Ordering::Relaxed);
+        if is_leader != was_leader {
+            info!(instance = %self.instance_id, leader = is_leader, "Leadership changed");
+        }
+    }
+
+    /// Publishes a fetched TLE set so followers can load it without downloading.
+    pub async fn share_tle(&self, group: &str, text: &str) {
+        let Some(mut conn) = self.conn.clone() else { return };
+        if let Err(e) = conn.set_ex::<_, _, ()>(format!("stfcm:v1:tle:{}", group), text, SHARED_TLE_TTL.as_secs()).await {
+            warn!(error = %e, group, "Failed to share TLE set");
+        }
+    }
+
+    /// TLE set last published by the leader for `group`, if any.
+    pub async fn shared_tle(&self, group: &str) -> Option<String> {
+        let mut conn = self.conn.clone()?;
+        match conn.get::<_, Option<String>>(format!("stfcm:v1:tle:{}", group)).await {
+            Ok(text) => text,
+            Err(e) => {
+                warn!(error = %e, group, "Failed to read shared TLE set");
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,8 @@
+// Periodic background jobs
+pub mod anomalies;
+pub mod conjunctions;
+pub mod leader;
+pub mod snapshot_recorder;
+pub mod snapshot_writer;
+pub mod tle_refresh;
+pub mod watchdog;
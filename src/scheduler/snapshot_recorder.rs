@@ -0,0 +1,64 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use arc_swap::ArcSwap;
+use chrono::{DateTime, Utc};
+use tracing::{debug, error};
+
+use crate::core::catalog::Catalog;
+use crate::scheduler::leader::Leadership;
+use crate::scheduler::snapshot_writer::SnapshotQueue;
+use crate::utils::db::NewSnapshot;
+
+/// Spawns the snapshot recorder: every `period` the leader propagates the whole
+/// current catalog on the blocking pool and queues one snapshot per satellite
+/// for the write-behind writer. The first run starts immediately.
+pub fn spawn(elements: Arc<ArcSwap<Catalog>>, queue: SnapshotQueue, leadership: Arc<Leadership>, period: Duration) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if !leadership.is_leader() {
+                continue;
+            }
+            let catalog = elements.load_full();
+            match tokio::task::spawn_blocking(move || snapshots(&catalog, Utc::now())).await {
+                Ok(snapshots) => {
+                    let count = snapshots.len();
+                    let queued = snapshots.into_iter().map(|s| queue.push(s)).filter(|&queued| queued).count();
+                    debug!(count, queued, "Recorded catalog snapshots");
+                }
+                Err(e) => error!(error = %e, "Snapshot recorder panicked"),
+            }
+        }
+    })
+}
+
+/// The state of every satellite in `catalog` at `t`; satellites SGP4 rejects
+/// are left out.
+pub fn snapshots(catalog: &Catalog, t: DateTime<Utc>) -> Vec<NewSnapshot> {
+    let timestamp = t.to_rfc3339();
+    (0..catalog.len())
+        .filter_map(|i| {
+            let pred = catalog.propagate(i, t).ok()?;
+            Some(NewSnapshot { norad_id: catalog[i].norad_id, timestamp: timestamp.clone(), position: pred.position, velocity: pred.velocity })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::fixtures;
+
+    #[test]
+    fn records_one_snapshot_per_satellite_at_the_tick() {
+        let catalog = Catalog::new(fixtures::catalog());
+        let t = fixtures::golden_start();
+        let recorded = snapshots(&catalog, t);
+        assert_eq!(recorded.iter().map(|s| s.norad_id).collect::<Vec<_>>(), [fixtures::ISS_NORAD_ID, fixtures::NOAA_18_NORAD_ID]);
+        assert!(recorded.iter().all(|s| s.timestamp == t.to_rfc3339()));
+        assert_eq!(recorded[0].position, catalog.propagate(0, t).unwrap().position);
+    }
+}
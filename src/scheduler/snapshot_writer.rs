@@ -0,0 +1,245 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use rusqlite::Connection;
+use tokio::sync::mpsc;
+use tracing::{debug, error, warn};
+
+use crate::utils::db::{self, NewSnapshot};
+use crate::utils::tsdb::TsdbSink;
+
+/// Thresholds of the write-behind snapshot queue.
+#[derive(Debug, Clone, Copy)]
+pub struct WriterConfig {
+    /// Snapshots buffered in the channel before new ones are dropped.
+    pub capacity: usize,
+    /// A batch is flushed as soon as it holds this many snapshots...
+    pub max_batch: usize,
+    /// ...or when this much time has passed since the last flush.
+    pub max_delay: Duration,
+    /// Written batches waiting to be mirrored before new ones are not mirrored.
+    pub mirror_backlog: usize,
+}
+
+impl Default for WriterConfig {
+    fn default() -> Self {
+        Self { capacity: 10_000, max_batch: 500, max_delay: Duration::from_secs(1), mirror_backlog: 16 }
+    }
+}
+
+/// Handle for queueing snapshots to the writer task. Cloning is cheap; the
+/// writer flushes what is left and stops once every handle is dropped.
+#[derive(Debug, Clone)]
+pub struct SnapshotQueue {
+    tx: mpsc::Sender<NewSnapshot>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl SnapshotQueue {
+    /// Queues a snapshot without waiting. Returns `false` (and counts the
+    /// snapshot as dropped) when the queue is full or the writer has stopped.
+    pub fn push(&self, snapshot: NewSnapshot) -> bool {
+        let queued = self.tx.try_send(snapshot).is_ok();
+        if !queued {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        queued
+    }
+}
+
+/// Spawns the writer task on `conn`. Snapshots are inserted in batches with
+/// `db::insert_snapshots` on the blocking pool, so producers never wait on SQLite,
+/// and each written batch is handed to a mirror task for `tsdb` when one is
+/// configured, so a slow time-series database never holds up the writer.
+pub fn spawn(conn: Connection, config: WriterConfig, tsdb: Option<Arc<TsdbSink>>) -> (SnapshotQueue, tokio::task::JoinHandle<()>) {
+    let (tx, rx) = mpsc::channel(config.capacity.max(1));
+    let dropped = Arc::new(AtomicU64::new(0));
+    let queue = SnapshotQueue { tx, dropped: dropped.clone() };
+    let mirror = tsdb.map(|sink| {
+        let (tx, rx) = mpsc::channel(config.mirror_backlog.max(1));
+        (tx, tokio::spawn(mirror(sink, rx)))
+    });
+    (queue, tokio::spawn(run(conn, rx, dropped, config, mirror)))
+}
+
+/// Mirrors written batches one after another until the writer stops.
+async fn mirror(sink: Arc<TsdbSink>, mut rx: mpsc::Receiver<Vec<NewSnapshot>>) {
+    while let Some(written) = rx.recv().await {
+        if let Err(e) = sink.write_snapshots(&written).await {
+            warn!(error = %e, count = written.len(), "Failed to mirror snapshots to time-series database");
+        }
+    }
+}
+
+async fn run(
+    conn: Connection,
+    mut rx: mpsc::Receiver<NewSnapshot>,
+    dropped: Arc<AtomicU64>,
+    config: WriterConfig,
+    mirror: Option<(mpsc::Sender<Vec<NewSnapshot>>, tokio::task::JoinHandle<()>)>,
+) {
+    let max_batch = config.max_batch.max(1);
+    let mut conn = Some(conn);
+    let mut batch = Vec::with_capacity(max_batch);
+    let mut ticker = tokio::time::interval(config.max_delay);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut reported_drops = 0;
+    loop {
+        let closed = tokio::select! {
+            msg = rx.recv() => match msg {
+                Some(snapshot) => {
+                    batch.push(snapshot);
+                    if batch.len() < max_batch {
+                        continue;
+                    }
+                    false
+                }
+                None => true,
+            },
+            _ = ticker.tick() => false,
+        };
+        if !batch.is_empty() {
+            let written;
+            (conn, written) = flush(conn, std::mem::replace(&mut batch, Vec::with_capacity(max_batch))).await;
+            if let (Some((mirror, _)), Some(written)) = (&mirror, written) {
+                if let Err(e) = mirror.try_send(written) {
+                    warn!(count = e.into_inner().len(), "Time-series mirror is behind, snapshot batch not mirrored");
+                }
+            }
+            ticker.reset();
+        }
+        let total = dropped.load(Ordering::Relaxed);
+        if total > reported_drops {
+            warn!(dropped = total - reported_drops, "Snapshot queue full, snapshots dropped");
+            reported_drops = total;
+        }
+        if closed {
+            break;
+        }
+    }
+    // Let the mirror finish the batches it already has
+    if let Some((tx, task)) = mirror {
+        drop(tx);
+        let _ = task.await;
+    }
+}
+
+/// Writes one batch and hands the connection back, with the batch when it was
+/// stored; a lost connection is reopened for the next batch.
+async fn flush(conn: Option<Connection>, batch: Vec<NewSnapshot>) -> (Option<Connection>, Option<Vec<NewSnapshot>>) {
+    let Some(conn) = conn.or_else(|| db::open_or_init().map(db::PooledConnection::into_inner).map_err(|e| error!(error = %e, "Failed to reopen database")).ok()) else {
+        warn!(count = batch.len(), "No database connection, snapshot batch discarded");
+        return (None, None);
+    };
+    let count = batch.len();
+    match tokio::task::spawn_blocking(move || {
+        let result = db::insert_snapshots(&conn, &batch);
+        (conn, batch, result)
+    })
+    .await
+    {
+        Ok((conn, batch, Ok(n))) => {
+            debug!(count = n, "Flushed snapshot batch");
+            (Some(conn), Some(batch))
+        }
+        Ok((conn, _, Err(e))) => {
+            error!(error = %e, count, "Failed to write snapshot batch");
+            (Some(conn), None)
+        }
+        Err(e) => {
+            error!(error = %e, count, "Snapshot writer panicked");
+            (None, None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(norad_id: u64) -> NewSnapshot {
+        NewSnapshot { norad_id, timestamp: "2024-01-01T00:00:00Z".to_string(), position: [7000.0, 0.0, 0.0], velocity: [0.0, 7.5, 0.0] }
+    }
+
+    fn open(path: &std::path::Path) -> Connection {
+        let conn = Connection::open(path).unwrap();
+        db::init_schema(&conn).unwrap();
+        db::upsert_satellites(&conn, &[(1, None)]).unwrap();
+        conn
+    }
+
+    fn count(path: &std::path::Path) -> i64 {
+        Connection::open(path).unwrap().query_row("SELECT COUNT(*) FROM snapshots", [], |r| r.get(0)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn flushes_on_delay_and_drains_on_close() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("queue.sqlite");
+        let config = WriterConfig { capacity: 100, max_batch: 1_000, max_delay: Duration::from_millis(20), ..Default::default() };
+        let (queue, writer) = spawn(open(&path), config, None);
+        for _ in 0..3 {
+            assert!(queue.push(snapshot(1)));
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(count(&path), 3);
+
+        for _ in 0..25 {
+            queue.push(snapshot(1));
+        }
+        drop(queue);
+        writer.await.unwrap();
+        assert_eq!(count(&path), 28);
+    }
+
+    #[tokio::test]
+    async fn full_batches_are_flushed_without_waiting_for_the_delay() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("batches.sqlite");
+        let config = WriterConfig { capacity: 100, max_batch: 10, max_delay: Duration::from_secs(3600), ..Default::default() };
+        let (queue, writer) = spawn(open(&path), config, None);
+        for _ in 0..25 {
+            assert!(queue.push(snapshot(1)));
+        }
+        let mut flushed = 0;
+        for _ in 0..100 {
+            flushed = count(&path);
+            if flushed == 20 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(flushed, 20);
+
+        drop(queue);
+        writer.await.unwrap();
+        assert_eq!(count(&path), 25);
+    }
+
+    #[tokio::test]
+    async fn a_stalled_mirror_does_not_hold_up_the_writer() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mirror.sqlite");
+        // Accepts connections but never answers
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = reqwest::Client::builder().timeout(Duration::from_millis(300)).build().unwrap();
+        let sink = TsdbSink::Influx { client, url: format!("http://{}/write", listener.local_addr().unwrap()), token: None };
+        let config = WriterConfig { capacity: 100, max_batch: 1, max_delay: Duration::from_secs(3600), mirror_backlog: 1 };
+        let (queue, writer) = spawn(open(&path), config, Some(Arc::new(sink)));
+        for _ in 0..5 {
+            assert!(queue.push(snapshot(1)));
+        }
+        let mut flushed = 0;
+        for _ in 0..20 {
+            flushed = count(&path);
+            if flushed == 5 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(flushed, 5);
+        drop(queue);
+        writer.await.unwrap();
+    }
+}
@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tracing::{info, warn};
+
+use crate::api::server::AppState;
+use crate::core::catalog::Catalog;
+use crate::core::screening::{ScreenedElement, ScreeningLimits};
+use crate::predictors::uncertainty::PositionSigma;
+
+/// What one load of the TLEs gives the API.
+pub struct LoadedCatalog {
+    pub catalog: Catalog,
+    pub uncertainty: HashMap<u64, PositionSigma>,
+    /// The limits applied and the element sets that failed them.
+    pub screening: (ScreeningLimits, Vec<ScreenedElement>),
+}
+
+impl LoadedCatalog {
+    /// Swaps the load into `state`. Requests that already hold the previous
+    /// catalog finish with it.
+    pub fn install(self, state: &AppState) {
+        state.pass_cache.retain_current(&self.catalog);
+        state.uncertainty.store(Arc::new(self.uncertainty));
+        state.screening.store(Arc::new(self.screening));
+        state.elements.store(Arc::new(self.catalog));
+    }
+}
+
+/// Spawns the periodic TLE refresh: every `period`, starting one period from
+/// now, `load` fetches and parses the TLEs again and the result replaces the
+/// catalog of `state`. A failed load keeps the current catalog until the next
+/// run.
+pub fn spawn<F, Fut>(period: Duration, state: AppState, load: F) -> tokio::task::JoinHandle<()>
+where
+    F: Fn() -> Fut + Send + 'static,
+    Fut: Future<Output = Option<LoadedCatalog>> + Send,
+{
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            match load().await {
+                Some(loaded) => {
+                    let count = loaded.catalog.len();
+                    loaded.install(&state);
+                    info!(count, "Refreshed TLEs");
+                }
+                None => warn!("TLE refresh failed; keeping the current catalog"),
+            }
+        }
+    })
+}
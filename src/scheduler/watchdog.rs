@@ -0,0 +1,17 @@
+use tracing::info;
+
+use crate::utils::sd_notify;
+
+/// Pings the systemd watchdog at half its timeout when `WatchdogSec=` is set. The
+/// pings run on the shared runtime, so a wedged runtime lets systemd restart us.
+pub fn spawn() -> Option<tokio::task::JoinHandle<()>> {
+    let timeout = sd_notify::watchdog_interval()?;
+    info!(timeout_s = timeout.as_secs_f64(), "systemd watchdog enabled");
+    Some(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(timeout / 2);
+        loop {
+            interval.tick().await;
+            sd_notify::notify("WATCHDOG=1");
+        }
+    }))
+}
@@ -0,0 +1,37 @@
+use chrono::{DateTime, TimeZone, Utc};
+
+use crate::predictors::passes::ObserverPosition;
+
+/// ISS element set from 20 Sep 2008 (the widely used SGP4 example).
+pub const ISS_TLE: &str = "ISS (ZARYA)
+1 25544U 98067A   08264.51782528 -.00002182  00000-0 -11606-4 0  2927
+2 25544  51.6416 247.4627 0006703 130.5360 325.0288 15.72125391563537";
+
+/// Sun-synchronous weather satellite with the same epoch day as [`ISS_TLE`].
+pub const NOAA_18_TLE: &str = "NOAA 18
+1 28654U 05018A   08264.50000000  .00000089  00000-0  71614-4 0  9994
+2 28654  98.8712 215.5342 0014081 112.4470 247.8193 14.11563102173411";
+
+pub const ISS_NORAD_ID: u64 = 25544;
+pub const NOAA_18_NORAD_ID: u64 = 28654;
+
+/// Both fixtures as one 3-line TLE file, as served by Celestrak.
+pub fn catalog_text() -> String {
+    format!("{}\n{}\n", ISS_TLE, NOAA_18_TLE)
+}
+
+/// The fixture catalog parsed into element sets.
+pub fn catalog() -> Vec<sgp4::Elements> {
+    crate::core::tle::parse_tle_records(&catalog_text())
+        .iter()
+        .map(|r| r.to_elements().expect("fixture TLEs are valid"))
+        .collect()
+}
+
+/// Ground station the golden passes are predicted for (ESOC, Darmstadt).
+pub const STATION: ObserverPosition = ObserverPosition { lat_deg: 49.8711, lon_deg: 8.6224, alt_km: 0.144 };
+
+/// Start of the golden prediction window.
+pub fn golden_start() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2008, 9, 20, 12, 0, 0).unwrap()
+}
@@ -0,0 +1,6 @@
+//! Offline test helpers for code built on STfCM: canned element sets, a clock
+//! that only moves when told to, and golden pass windows to check predictions
+//! against. Enabled for this crate's own tests and by the `testing` feature.
+pub mod clock;
+pub mod fixtures;
+pub mod golden;
@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+use redis::AsyncCommands;
+use tracing::{info, warn};
+
+/// Environment variable holding an optional Redis URL (`redis://host:6379/0`) used
+/// to cache expensive API responses across instances.
+const URL_ENV: &str = "STFCM_REDIS_URL";
+/// Prefix of every key written, so the cache can share a Redis database.
+const KEY_PREFIX: &str = "stfcm:v1";
+
+/// Shared cache of serialized responses. Errors are logged and treated as misses,
+/// so an unavailable Redis only costs the recomputation.
+#[derive(Clone)]
+pub struct ResponseCache {
+    conn: redis::aio::ConnectionManager,
+}
+
+impl ResponseCache {
+    /// Connects to the Redis named by `STFCM_REDIS_URL`; `None` when unset or
+    /// unreachable (logged).
+    pub async fn from_env() -> Option<ResponseCache> {
+        let url = std::env::var(URL_ENV).ok().filter(|u| !u.is_empty())?;
+        let client = match redis::Client::open(url) {
+            Ok(c) => c,
+            Err(e) => {
+                warn!(error = %e, "Invalid Redis URL, response cache disabled");
+                return None;
+            }
+        };
+        match client.get_connection_manager().await {
+            Ok(conn) => {
+                info!("Caching API responses in Redis");
+                Some(ResponseCache { conn })
+            }
+            Err(e) => {
+                warn!(error = %e, "Failed to connect to Redis, response cache disabled");
+                None
+            }
+        }
+    }
+
+    pub async fn get(&self, key: &str) -> Option<Vec<u8>> {
+        let mut conn = self.conn.clone();
+        match conn.get::<_, Option<Vec<u8>>>(format!("{}:{}", KEY_PREFIX, key)).await {
+            Ok(v) => v,
+            Err(e) => {
+                warn!(error = %e, "Redis read failed");
+                None
+            }
+        }
+    }
+
+    pub async fn put(&self, key: &str, value: &[u8], ttl: Duration) {
+        let mut conn = self.conn.clone();
+        let ttl_s = ttl.as_secs().max(1);
+        if let Err(e) = conn.set_ex::<_, _, ()>(format!("{}:{}", KEY_PREFIX, key), value, ttl_s).await {
+            warn!(error = %e, "Redis write failed");
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[cfg(unix)]
+use tokio::signal::unix::{signal, Signal, SignalKind};
+
+/// Command-line options for running as a long-lived service.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// Detach from the terminal (`--daemon`).
+    pub daemon: bool,
+    /// Write the process ID here while running (`--pid-file <path>`).
+    pub pid_file: Option<PathBuf>,
+    /// Append stdout/stderr here once detached (`--log-file <path>`).
+    pub log_file: Option<PathBuf>,
+}
+
+/// Default PID file when `--daemon` is given without `--pid-file`.
+const DEFAULT_PID_FILE: &str = "data/stfcm.pid";
+
+impl Options {
+    /// A daemon without a PID file gets [`DEFAULT_PID_FILE`].
+    pub fn new(daemon: bool, pid_file: Option<PathBuf>, log_file: Option<PathBuf>) -> Options {
+        let pid_file = pid_file.or_else(|| daemon.then(|| DEFAULT_PID_FILE.into()));
+        Options { daemon, pid_file, log_file }
+    }
+}
+
+/// Detaches from the controlling terminal: forks (the parent exits), starts a new
+/// session and points stdin at `/dev/null` and stdout/stderr at `log_file` (or
+/// `/dev/null`). Must run before any thread, i.e. before the Tokio runtime starts.
+#[cfg(unix)]
+pub fn daemonize(log_file: Option<&Path>) -> io::Result<()> {
+    // SAFETY: called from `main` before any other thread exists, so the child
+    // starts with a consistent copy of the process.
+    match unsafe { libc::fork() } {
+        -1 => return Err(io::Error::last_os_error()),
+        0 => {}
+        _ => std::process::exit(0),
+    }
+    // SAFETY: plain syscall without pointers.
+    if unsafe { libc::setsid() } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    let null = fs::OpenOptions::new().read(true).write(true).open("/dev/null")?;
+    let out = match log_file {
+        Some(path) => fs::OpenOptions::new().create(true).append(true).open(path)?,
+        None => null.try_clone()?,
+    };
+    redirect(&null, libc::STDIN_FILENO)?;
+    redirect(&out, libc::STDOUT_FILENO)?;
+    redirect(&out, libc::STDERR_FILENO)?;
+    Ok(())
+}
+
+/// Without fork there is no detaching; run in the foreground or as a service.
+#[cfg(not(unix))]
+pub fn daemonize(_log_file: Option<&Path>) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "--daemon needs a Unix system"))
+}
+
+#[cfg(unix)]
+fn redirect(file: &fs::File, fd: libc::c_int) -> io::Result<()> {
+    use std::os::fd::AsRawFd;
+    // SAFETY: both descriptors are valid for the duration of the call.
+    if unsafe { libc::dup2(file.as_raw_fd(), fd) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Whether a process with this PID exists. Signal 0 only runs the permission and
+/// existence checks; EPERM means it exists but belongs to another user.
+#[cfg(unix)]
+fn is_running(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else { return false };
+    // SAFETY: plain syscall without pointers; signal 0 delivers nothing.
+    let alive = unsafe { libc::kill(pid, 0) } == 0;
+    alive || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// No portable check elsewhere: a left-over PID file is always treated as stale.
+#[cfg(not(unix))]
+fn is_running(_pid: u32) -> bool {
+    false
+}
+
+/// PID file held for the life of the process and removed on drop.
+#[derive(Debug)]
+pub struct PidFile {
+    path: PathBuf,
+}
+
+impl PidFile {
+    /// Writes the current PID to `path`. Fails if the file names a process that
+    /// is still running (checked on Unix only); a stale file is replaced.
+    pub fn create(path: &Path) -> io::Result<PidFile> {
+        if let Some(pid) = fs::read_to_string(path).ok().and_then(|s| s.trim().parse::<u32>().ok()) {
+            if pid != std::process::id() && is_running(pid) {
+                return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("already running with PID {}", pid)));
+            }
+        }
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, format!("{}\n", std::process::id()))?;
+        Ok(PidFile { path: path.to_path_buf() })
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// What a received signal asks the service to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    /// SIGHUP: reload configuration and TLEs.
+    #[cfg_attr(not(unix), allow(dead_code))]
+    Reload,
+    /// SIGTERM or SIGINT: stop gracefully.
+    Shutdown,
+}
+
+#[cfg(unix)]
+pub struct Signals {
+    hangup: Signal,
+    terminate: Signal,
+    interrupt: Signal,
+}
+
+#[cfg(unix)]
+impl Signals {
+    pub fn install() -> io::Result<Signals> {
+        Ok(Signals {
+            hangup: signal(SignalKind::hangup())?,
+            terminate: signal(SignalKind::terminate())?,
+            interrupt: signal(SignalKind::interrupt())?,
+        })
+    }
+
+    pub async fn recv(&mut self) -> Control {
+        tokio::select! {
+            _ = self.hangup.recv() => Control::Reload,
+            _ = self.terminate.recv() => Control::Shutdown,
+            _ = self.interrupt.recv() => Control::Shutdown,
+        }
+    }
+}
+
+/// Elsewhere only Ctrl-C is available; it stops the service.
+#[cfg(not(unix))]
+pub struct Signals;
+
+#[cfg(not(unix))]
+impl Signals {
+    pub fn install() -> io::Result<Signals> {
+        Ok(Signals)
+    }
+
+    pub async fn recv(&mut self) -> Control {
+        match tokio::signal::ctrl_c().await {
+            Ok(()) => Control::Shutdown,
+            Err(_) => std::future::pending().await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn daemons_get_a_default_pid_file() {
+        assert_eq!(Options::new(false, None, None), Options::default());
+        let o = Options::new(true, None, Some("/var/log/stfcm.log".into()));
+        assert_eq!(o.pid_file.as_deref(), Some(Path::new(DEFAULT_PID_FILE)));
+        assert_eq!(o.log_file.as_deref(), Some(Path::new("/var/log/stfcm.log")));
+        assert_eq!(Options::new(true, Some("/run/stfcm.pid".into()), None).pid_file.as_deref(), Some(Path::new("/run/stfcm.pid")));
+    }
+
+    #[test]
+    fn pid_file_is_removed_on_drop() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run/stfcm.pid");
+        let pid_file = PidFile::create(&path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap().trim(), std::process::id().to_string());
+        drop(pid_file);
+        assert!(!path.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn running_pid_blocks_a_second_start() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("stfcm.pid");
+        // PID 1 always exists; a PID above any pid_max never does.
+        fs::write(&path, "1\n").unwrap();
+        assert_eq!(PidFile::create(&path).unwrap_err().kind(), io::ErrorKind::AlreadyExists);
+        fs::write(&path, format!("{}\n", u32::MAX)).unwrap();
+        assert!(PidFile::create(&path).is_ok());
+    }
+}
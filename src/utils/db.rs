@@ -1,138 +1,2281 @@
-use rusqlite::{params, Connection};
-use std::fs;
-use std::path::PathBuf;
-use thiserror::Error;
-
-#[derive(Debug, Error)]
-pub enum DbError {
-    #[error("sqlite error: {0}")]
-    Sql(#[from] rusqlite::Error),
-    #[error("io error: {0}")]
-    Io(#[from] std::io::Error),
-}
-
-pub fn open_or_init() -> Result<Connection, DbError> {
-    let dir = PathBuf::from("data/db");
-    fs::create_dir_all(&dir)?;
-    let path = dir.join("tracker.sqlite");
-    let conn = Connection::open(path)?;
-    conn.execute_batch(
-        r#"
-        PRAGMA journal_mode=WAL;
-        CREATE TABLE IF NOT EXISTS satellites (
-            norad_id INTEGER PRIMARY KEY,
-            name TEXT
-        );
-        CREATE TABLE IF NOT EXISTS snapshots (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            norad_id INTEGER NOT NULL,
-            timestamp TEXT NOT NULL,
-            pos_x REAL NOT NULL,
-            pos_y REAL NOT NULL,
-            pos_z REAL NOT NULL,
-            vel_x REAL NOT NULL,
-            vel_y REAL NOT NULL,
-            vel_z REAL NOT NULL,
-            FOREIGN KEY(norad_id) REFERENCES satellites(norad_id)
-        );
-        CREATE TABLE IF NOT EXISTS stations (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            name TEXT,
-            lat REAL NOT NULL,
-            lon REAL NOT NULL
-        );
-        CREATE UNIQUE INDEX IF NOT EXISTS stations_name_unique ON stations(name) WHERE name IS NOT NULL;
-        "#,
-    )?;
-    Ok(conn)
-}
-
-pub fn upsert_satellite(conn: &Connection, norad_id: u64, name: Option<&str>) -> Result<(), DbError> {
-    conn.execute(
-        "INSERT INTO satellites (norad_id, name) VALUES (?1, ?2)
-         ON CONFLICT(norad_id) DO UPDATE SET name=excluded.name",
-        params![norad_id as i64, name.unwrap_or("")],
-    )?;
-    Ok(())
-}
-
-pub fn insert_snapshot(
-    conn: &Connection,
-    norad_id: u64,
-    timestamp: &str,
-    prediction: &sgp4::Prediction,
-) -> Result<(), DbError> {
-    conn.execute(
-        "INSERT INTO snapshots (norad_id, timestamp, pos_x, pos_y, pos_z, vel_x, vel_y, vel_z)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-        params![
-            norad_id as i64,
-            timestamp,
-            prediction.position[0],
-            prediction.position[1],
-            prediction.position[2],
-            prediction.velocity[0],
-            prediction.velocity[1],
-            prediction.velocity[2],
-        ],
-    )?;
-    Ok(())
-}
-
-#[derive(Debug, Clone)]
-pub struct Station {
-    pub id: i64,
-    pub name: Option<String>,
-    pub lat: f64,
-    pub lon: f64,
-}
-
-pub fn insert_station(conn: &Connection, name: Option<&str>, lat: f64, lon: f64) -> Result<i64, DbError> {
-    conn.execute(
-        "INSERT INTO stations (name, lat, lon) VALUES (?1, ?2, ?3)",
-        params![name, lat, lon],
-    )?;
-    let id = conn.last_insert_rowid();
-    Ok(id)
-}
-
-pub fn list_stations(conn: &Connection) -> Result<Vec<Station>, DbError> {
-    let mut stmt = conn.prepare("SELECT id, name, lat, lon FROM stations ORDER BY id")?;
-    let iter = stmt.query_map([], |row| {
-        Ok(Station {
-            id: row.get::<_, i64>(0)?,
-            name: row.get::<_, String>(1).ok(),
-            lat: row.get::<_, f64>(2)?,
-            lon: row.get::<_, f64>(3)?,
-        })
-    })?;
-    Ok(iter.filter_map(Result::ok).collect())
-}
-
-pub fn get_station(conn: &Connection, id: i64) -> Result<Station, DbError> {
-    let mut stmt = conn.prepare("SELECT id, name, lat, lon FROM stations WHERE id = ?1")?;
-    let mut rows = stmt.query(params![id])?;
-    if let Some(row) = rows.next()? {
-        Ok(Station {
-            id: row.get::<_, i64>(0)?,
-            name: row.get::<_, String>(1).ok(),
-            lat: row.get::<_, f64>(2)?,
-            lon: row.get::<_, f64>(3)?,
-        })
-    } else {
-        Err(rusqlite::Error::QueryReturnedNoRows.into())
-    }
-}
-
-pub fn update_station(conn: &Connection, id: i64, name: Option<&str>, lat: f64, lon: f64) -> Result<(), DbError> {
-    conn.execute(
-        "UPDATE stations SET name = ?1, lat = ?2, lon = ?3 WHERE id = ?4",
-        params![name, lat, lon, id],
-    )?;
-    Ok(())
-}
-
-pub fn delete_station(conn: &Connection, id: i64) -> Result<(), DbError> {
-    conn.execute("DELETE FROM stations WHERE id = ?1", params![id])?;
-    Ok(())
-}
\ No newline at end of file
+use rusqlite::{params, Connection, OpenFlags};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum DbError {
+    #[error("sqlite error: {0}")]
+    Sql(#[from] rusqlite::Error),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Statements kept prepared per connection; covers every write path.
+const STATEMENT_CACHE_CAPACITY: usize = 64;
+
+/// Environment variable that, when `1` or `true`, serves an existing database
+/// read-only (e.g. a replica) and disables every write.
+const READ_ONLY_ENV: &str = "STFCM_READ_ONLY";
+
+pub fn read_only() -> bool {
+    std::env::var(READ_ONLY_ENV).is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+}
+
+/// Idle connections kept for reuse, each with its cache of prepared statements.
+const POOL_CAPACITY: usize = 16;
+
+/// Connections to one database file. Each keeps its prepared statements, so
+/// reusing one skips preparing them again; the first connection a pool opens
+/// creates and migrates the schema, later ones skip that. In read-only mode
+/// the file must exist and is opened without write access.
+pub struct Pool {
+    path: PathBuf,
+    /// Connections handed back by dropped [`PooledConnection`]s.
+    idle: Mutex<Vec<Connection>>,
+    /// Whether `init_schema` already ran on `path` through this pool.
+    schema_ready: Mutex<bool>,
+}
+
+impl Pool {
+    pub fn new(path: PathBuf) -> Pool {
+        Pool { path, idle: Mutex::new(Vec::new()), schema_ready: Mutex::new(false) }
+    }
+
+    /// Hands out an idle connection, opening a new one when there is none.
+    pub fn get(&self) -> Result<PooledConnection<'_>, DbError> {
+        let idle = self.idle.lock().unwrap_or_else(|e| e.into_inner()).pop();
+        let conn = match idle {
+            Some(conn) => conn,
+            None => self.open()?,
+        };
+        Ok(PooledConnection { conn: Some(conn), pool: self })
+    }
+
+    fn open(&self) -> Result<Connection, DbError> {
+        let path = &self.path;
+        if read_only() {
+            let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)?;
+            conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+            return Ok(conn);
+        }
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let conn = Connection::open(path)?;
+        conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+        // Per connection, unlike WAL mode which the file keeps
+        conn.execute_batch("PRAGMA synchronous=NORMAL;")?;
+        let mut schema_ready = self.schema_ready.lock().unwrap_or_else(|e| e.into_inner());
+        if !*schema_ready {
+            init_schema(&conn)?;
+            *schema_ready = true;
+        }
+        Ok(conn)
+    }
+}
+
+/// A connection from a [`Pool`]. It derefs to the `rusqlite::Connection` and
+/// goes back to its pool when dropped.
+pub struct PooledConnection<'a> {
+    conn: Option<Connection>,
+    pool: &'a Pool,
+}
+
+impl PooledConnection<'_> {
+    /// Takes the connection out of the pool for good, e.g. for a long-lived task.
+    pub fn into_inner(mut self) -> Connection {
+        self.conn.take().expect("connection is only taken once")
+    }
+}
+
+impl std::ops::Deref for PooledConnection<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn.as_ref().expect("connection is only taken once")
+    }
+}
+
+impl Drop for PooledConnection<'_> {
+    fn drop(&mut self) {
+        // A connection left inside a transaction is closed rather than reused
+        let Some(conn) = self.conn.take().filter(|c| c.is_autocommit()) else {
+            return;
+        };
+        let mut idle = self.pool.idle.lock().unwrap_or_else(|e| e.into_inner());
+        if idle.len() < POOL_CAPACITY {
+            idle.push(conn);
+        }
+    }
+}
+
+/// The process's pool on the configured database file.
+static DEFAULT_POOL: OnceLock<Pool> = OnceLock::new();
+
+/// Sets the file `open_or_init` opens (default `data/db/tracker.sqlite`). Only
+/// the first call counts, so it takes effect on restart rather than reload.
+pub fn set_path(path: PathBuf) {
+    let _ = DEFAULT_POOL.set(Pool::new(path));
+}
+
+fn default_pool() -> &'static Pool {
+    DEFAULT_POOL.get_or_init(|| Pool::new(crate::config::DataConfig::default().db_path))
+}
+
+/// A connection from the process's pool on the tracker database.
+pub fn open_or_init() -> Result<PooledConnection<'static>, DbError> {
+    default_pool().get()
+}
+
+/// Creates missing tables and migrates older schemas. WAL with `synchronous=NORMAL`
+/// keeps commits cheap; a crash can lose the last transactions but not corrupt the file.
+pub fn init_schema(conn: &Connection) -> Result<(), DbError> {
+    conn.execute_batch(
+        r#"
+        PRAGMA journal_mode=WAL;
+        PRAGMA synchronous=NORMAL;
+        CREATE TABLE IF NOT EXISTS satellites (
+            norad_id INTEGER PRIMARY KEY,
+            name TEXT
+        );
+        CREATE TABLE IF NOT EXISTS snapshots (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            norad_id INTEGER NOT NULL,
+            timestamp TEXT NOT NULL,
+            pos_x REAL NOT NULL,
+            pos_y REAL NOT NULL,
+            pos_z REAL NOT NULL,
+            vel_x REAL NOT NULL,
+            vel_y REAL NOT NULL,
+            vel_z REAL NOT NULL,
+            FOREIGN KEY(norad_id) REFERENCES satellites(norad_id)
+        );
+        CREATE TABLE IF NOT EXISTS stations (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT,
+            lat REAL NOT NULL,
+            lon REAL NOT NULL,
+            alt_m REAL NOT NULL DEFAULT 0,
+            tenant TEXT NOT NULL DEFAULT '',
+            version INTEGER NOT NULL DEFAULT 0
+        );
+        CREATE TABLE IF NOT EXISTS protected_assets (
+            norad_id INTEGER NOT NULL,
+            tenant TEXT NOT NULL DEFAULT '',
+            alert_threshold_km REAL NOT NULL,
+            PRIMARY KEY(norad_id, tenant)
+        );
+        CREATE TABLE IF NOT EXISTS conjunctions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            asset_norad_id INTEGER NOT NULL,
+            secondary_norad_id INTEGER NOT NULL,
+            secondary_name TEXT,
+            tca TEXT NOT NULL,
+            miss_distance_km REAL NOT NULL,
+            relative_speed_km_s REAL NOT NULL,
+            screened_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS conjunctions_asset_tca ON conjunctions(asset_norad_id, tca);
+        CREATE TABLE IF NOT EXISTS tle_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            norad_id INTEGER NOT NULL,
+            epoch TEXT NOT NULL,
+            name TEXT,
+            line1 TEXT NOT NULL,
+            line2 TEXT NOT NULL,
+            fetched_at TEXT NOT NULL,
+            UNIQUE(norad_id, epoch)
+        );
+        CREATE TABLE IF NOT EXISTS satellite_tags (
+            norad_id INTEGER NOT NULL,
+            tag TEXT NOT NULL,
+            tenant TEXT NOT NULL DEFAULT '',
+            created_at TEXT NOT NULL,
+            PRIMARY KEY(norad_id, tag, tenant)
+        );
+        CREATE TABLE IF NOT EXISTS observations (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            station_id INTEGER NOT NULL,
+            norad_id INTEGER,
+            observed_at TEXT NOT NULL,
+            az_deg REAL NOT NULL,
+            el_deg REAL NOT NULL,
+            FOREIGN KEY(station_id) REFERENCES stations(id)
+        );
+        CREATE TABLE IF NOT EXISTS station_exclusions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            station_id INTEGER NOT NULL,
+            start_deg REAL NOT NULL,
+            end_deg REAL NOT NULL,
+            FOREIGN KEY(station_id) REFERENCES stations(id)
+        );
+        CREATE TABLE IF NOT EXISTS fetch_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            fetched_at TEXT NOT NULL,
+            source TEXT NOT NULL,
+            records INTEGER NOT NULL,
+            error TEXT
+        );
+        CREATE TABLE IF NOT EXISTS station_horizons (
+            station_id INTEGER PRIMARY KEY,
+            profile TEXT NOT NULL,
+            computed_at TEXT NOT NULL,
+            FOREIGN KEY(station_id) REFERENCES stations(id)
+        );
+        CREATE TABLE IF NOT EXISTS custom_elements (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT,
+            tle TEXT,
+            omm TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS element_overrides (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            norad_id INTEGER NOT NULL,
+            source TEXT NOT NULL,
+            tle TEXT,
+            omm TEXT,
+            reason TEXT,
+            created_at TEXT NOT NULL,
+            reverted_at TEXT
+        );
+        CREATE INDEX IF NOT EXISTS element_overrides_norad ON element_overrides(norad_id, id);
+        CREATE TABLE IF NOT EXISTS audit_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            at TEXT NOT NULL,
+            actor TEXT NOT NULL,
+            method TEXT NOT NULL,
+            path TEXT NOT NULL,
+            status INTEGER NOT NULL,
+            payload TEXT,
+            diff TEXT
+        );
+        CREATE TABLE IF NOT EXISTS device_tokens (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            station_id INTEGER NOT NULL,
+            name TEXT,
+            token_sha256 TEXT NOT NULL UNIQUE,
+            created_at TEXT NOT NULL,
+            revoked_at TEXT,
+            FOREIGN KEY(station_id) REFERENCES stations(id)
+        );
+        CREATE TABLE IF NOT EXISTS station_telemetry (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            station_id INTEGER NOT NULL,
+            norad_id INTEGER,
+            time TEXT NOT NULL,
+            az_deg REAL NOT NULL,
+            el_deg REAL NOT NULL,
+            signal_dbm REAL,
+            received_at TEXT NOT NULL,
+            FOREIGN KEY(station_id) REFERENCES stations(id)
+        );
+        CREATE TABLE IF NOT EXISTS station_favorites (
+            station_id INTEGER NOT NULL,
+            norad_id INTEGER NOT NULL,
+            PRIMARY KEY(station_id, norad_id),
+            FOREIGN KEY(station_id) REFERENCES stations(id)
+        );
+        CREATE TABLE IF NOT EXISTS satellite_aliases (
+            norad_id INTEGER NOT NULL,
+            alias TEXT NOT NULL,
+            display INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY(norad_id, alias)
+        );
+        CREATE TABLE IF NOT EXISTS satcat (
+            norad_id INTEGER PRIMARY KEY,
+            object_id TEXT,
+            object_type TEXT NOT NULL,
+            owner TEXT NOT NULL,
+            launch_date TEXT,
+            decay_date TEXT,
+            fetched_at TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS catalog_members (
+            norad_id INTEGER PRIMARY KEY,
+            name TEXT,
+            epoch TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS catalog_changes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            changed_at TEXT NOT NULL,
+            norad_id INTEGER NOT NULL,
+            kind TEXT NOT NULL,
+            name TEXT,
+            previous_epoch TEXT,
+            epoch TEXT
+        );
+        CREATE INDEX IF NOT EXISTS catalog_changes_at ON catalog_changes(changed_at, norad_id);
+        CREATE TABLE IF NOT EXISTS aois (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            geometry TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS pass_cache (
+            key TEXT PRIMARY KEY,
+            norad_id INTEGER NOT NULL,
+            tle_epoch INTEGER NOT NULL,
+            start TEXT NOT NULL,
+            windows TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS snapshot_anomalies (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            snapshot_id INTEGER NOT NULL,
+            norad_id INTEGER NOT NULL,
+            timestamp TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            magnitude_km REAL NOT NULL,
+            detail TEXT NOT NULL,
+            detected_at TEXT NOT NULL,
+            UNIQUE(snapshot_id, kind)
+        );
+        CREATE TABLE IF NOT EXISTS tle_accuracy (
+            norad_id INTEGER NOT NULL,
+            epoch TEXT NOT NULL,
+            previous_epoch TEXT NOT NULL,
+            gap_hours REAL NOT NULL,
+            error_km REAL NOT NULL,
+            radial_km REAL NOT NULL,
+            along_track_km REAL NOT NULL,
+            cross_track_km REAL NOT NULL,
+            recorded_at TEXT NOT NULL,
+            PRIMARY KEY(norad_id, epoch)
+        );
+        CREATE TABLE IF NOT EXISTS shell_counts (
+            fetched_at TEXT NOT NULL,
+            shell_km INTEGER NOT NULL,
+            width_km INTEGER NOT NULL,
+            count INTEGER NOT NULL,
+            PRIMARY KEY(fetched_at, shell_km)
+        ) WITHOUT ROWID;
+        CREATE TABLE IF NOT EXISTS analyzer_cursors (
+            name TEXT PRIMARY KEY,
+            last_id INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS dynamic_groups (
+            name TEXT NOT NULL,
+            tenant TEXT NOT NULL DEFAULT '',
+            filter TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            PRIMARY KEY(tenant, name)
+        );
+        "#,
+    )?;
+    add_column_if_missing(conn, "stations", "alt_m", "REAL NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "fetch_log", "rejected", "TEXT")?;
+    // Station names are unique per tenant ('' when there is none)
+    add_column_if_missing(conn, "stations", "tenant", "TEXT NOT NULL DEFAULT ''")?;
+    add_column_if_missing(conn, "stations", "version", "INTEGER NOT NULL DEFAULT 0")?;
+    conn.execute_batch(
+        "DROP INDEX IF EXISTS stations_name_unique;
+         CREATE UNIQUE INDEX IF NOT EXISTS stations_tenant_name_unique ON stations(tenant, name) WHERE name IS NOT NULL;",
+    )?;
+    if add_column_if_missing(conn, "protected_assets", "tenant", "TEXT NOT NULL DEFAULT ''")? {
+        // Older tables are keyed by NORAD ID alone; several tenants may protect the same satellite
+        conn.execute_batch(
+            "ALTER TABLE protected_assets RENAME TO protected_assets_old;
+             CREATE TABLE protected_assets (
+                 norad_id INTEGER NOT NULL,
+                 tenant TEXT NOT NULL DEFAULT '',
+                 alert_threshold_km REAL NOT NULL,
+                 PRIMARY KEY(norad_id, tenant)
+             );
+             INSERT INTO protected_assets (norad_id, tenant, alert_threshold_km) SELECT norad_id, tenant, alert_threshold_km FROM protected_assets_old;
+             DROP TABLE protected_assets_old;",
+        )?;
+    }
+    if add_column_if_missing(conn, "satellite_tags", "tenant", "TEXT NOT NULL DEFAULT ''")? {
+        // Each tenant keeps its own tags and groups under the same names
+        conn.execute_batch(
+            "ALTER TABLE satellite_tags RENAME TO satellite_tags_old;
+             CREATE TABLE satellite_tags (
+                 norad_id INTEGER NOT NULL,
+                 tag TEXT NOT NULL,
+                 tenant TEXT NOT NULL DEFAULT '',
+                 created_at TEXT NOT NULL,
+                 PRIMARY KEY(norad_id, tag, tenant)
+             );
+             INSERT INTO satellite_tags (norad_id, tag, tenant, created_at) SELECT norad_id, tag, tenant, created_at FROM satellite_tags_old;
+             DROP TABLE satellite_tags_old;",
+        )?;
+    }
+    if add_column_if_missing(conn, "dynamic_groups", "tenant", "TEXT NOT NULL DEFAULT ''")? {
+        conn.execute_batch(
+            "ALTER TABLE dynamic_groups RENAME TO dynamic_groups_old;
+             CREATE TABLE dynamic_groups (
+                 name TEXT NOT NULL,
+                 tenant TEXT NOT NULL DEFAULT '',
+                 filter TEXT NOT NULL,
+                 created_at TEXT NOT NULL,
+                 updated_at TEXT NOT NULL,
+                 PRIMARY KEY(tenant, name)
+             );
+             INSERT INTO dynamic_groups (name, tenant, filter, created_at, updated_at) SELECT name, tenant, filter, created_at, updated_at FROM dynamic_groups_old;
+             DROP TABLE dynamic_groups_old;",
+        )?;
+    }
+    let epoch_added = add_column_if_missing(conn, "snapshots", "epoch_s", "INTEGER")?;
+    conn.execute_batch(
+        r#"
+        CREATE INDEX IF NOT EXISTS snapshots_norad_epoch ON snapshots(norad_id, epoch_s);
+        CREATE TABLE IF NOT EXISTS snapshot_rollups (
+            resolution_s INTEGER NOT NULL,
+            norad_id INTEGER NOT NULL,
+            bucket_s INTEGER NOT NULL,
+            samples INTEGER NOT NULL,
+            epoch_s INTEGER NOT NULL,
+            timestamp TEXT NOT NULL,
+            pos_x REAL NOT NULL,
+            pos_y REAL NOT NULL,
+            pos_z REAL NOT NULL,
+            vel_x REAL NOT NULL,
+            vel_y REAL NOT NULL,
+            vel_z REAL NOT NULL,
+            PRIMARY KEY(resolution_s, norad_id, bucket_s)
+        ) WITHOUT ROWID;
+        "#,
+    )?;
+    if epoch_added {
+        conn.execute_batch("UPDATE snapshots SET epoch_s = CAST(strftime('%s', timestamp) AS INTEGER)")?;
+        rebuild_rollups(conn)?;
+    }
+    Ok(())
+}
+
+/// Runs a single statement through the connection's prepared-statement cache.
+fn execute_cached<P: rusqlite::Params>(conn: &Connection, sql: &str, params: P) -> Result<usize, DbError> {
+    Ok(conn.prepare_cached(sql)?.execute(params)?)
+}
+
+/// Runs `f` in a transaction of its own, or in the caller's when one is open, so
+/// helpers can be combined into one atomic change.
+fn in_transaction<T>(conn: &Connection, f: impl FnOnce(&Connection) -> Result<T, DbError>) -> Result<T, DbError> {
+    if !conn.is_autocommit() {
+        return f(conn);
+    }
+    let tx = conn.unchecked_transaction()?;
+    let out = f(&tx)?;
+    tx.commit()?;
+    Ok(out)
+}
+
+/// Adds a column to a table created by an older version of the schema; returns
+/// whether it was added.
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, decl: &str) -> Result<bool, DbError> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(Result::ok)
+        .any(|name| name == column);
+    if !exists {
+        conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, decl))?;
+    }
+    Ok(!exists)
+}
+
+/// Inserts or renames satellites `(norad_id, name)` in one transaction; returns
+/// the number of rows written.
+pub fn upsert_satellites(conn: &Connection, satellites: &[(u64, Option<&str>)]) -> Result<usize, DbError> {
+    let tx = conn.unchecked_transaction()?;
+    let mut written = 0usize;
+    {
+        let mut stmt = tx.prepare_cached(
+            "INSERT INTO satellites (norad_id, name) VALUES (?1, ?2)
+             ON CONFLICT(norad_id) DO UPDATE SET name=excluded.name",
+        )?;
+        for (norad_id, name) in satellites {
+            written += stmt.execute(params![*norad_id as i64, name.unwrap_or("")])?;
+        }
+    }
+    tx.commit()?;
+    Ok(written)
+}
+
+#[derive(Debug, Clone)]
+pub struct NewSnapshot {
+    pub norad_id: u64,
+    pub timestamp: String,
+    pub position: [f64; 3],
+    pub velocity: [f64; 3],
+}
+
+const INSERT_SNAPSHOT: &str = "INSERT INTO snapshots (norad_id, timestamp, pos_x, pos_y, pos_z, vel_x, vel_y, vel_z, epoch_s)
+     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)";
+
+/// Bucket widths (seconds) of the snapshot rollups, finest first.
+pub const ROLLUP_RESOLUTIONS: [i64; 2] = [60, 3600];
+
+/// Each rollup bucket keeps its first snapshot as the representative sample and
+/// counts the snapshots that fell into it.
+const UPSERT_ROLLUP: &str = "INSERT INTO snapshot_rollups
+     (resolution_s, norad_id, bucket_s, samples, epoch_s, timestamp, pos_x, pos_y, pos_z, vel_x, vel_y, vel_z)
+     VALUES (?1, ?2, ?3, 1, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+     ON CONFLICT(resolution_s, norad_id, bucket_s) DO UPDATE SET samples = samples + 1";
+
+/// Inserts a burst of snapshots and updates their rollups in one transaction with
+/// cached statements; returns the number of snapshots written. Snapshots whose
+/// timestamp is not RFC 3339 are stored but left out of the rollups.
+pub fn insert_snapshots(conn: &Connection, snapshots: &[NewSnapshot]) -> Result<usize, DbError> {
+    let tx = conn.unchecked_transaction()?;
+    let mut written = 0usize;
+    {
+        let mut insert = tx.prepare_cached(INSERT_SNAPSHOT)?;
+        let mut rollup = tx.prepare_cached(UPSERT_ROLLUP)?;
+        for s in snapshots {
+            let epoch_s = chrono::DateTime::parse_from_rfc3339(&s.timestamp).ok().map(|t| t.timestamp());
+            let [px, py, pz] = s.position;
+            let [vx, vy, vz] = s.velocity;
+            written += insert.execute(params![s.norad_id as i64, s.timestamp, px, py, pz, vx, vy, vz, epoch_s])?;
+            if let Some(epoch_s) = epoch_s {
+                for res in ROLLUP_RESOLUTIONS {
+                    let bucket = epoch_s.div_euclid(res) * res;
+                    rollup.execute(params![res, s.norad_id as i64, bucket, epoch_s, s.timestamp, px, py, pz, vx, vy, vz])?;
+                }
+            }
+        }
+    }
+    tx.commit()?;
+    Ok(written)
+}
+
+/// Recomputes every rollup from the raw snapshots (used when migrating a
+/// database that predates them).
+pub fn rebuild_rollups(conn: &Connection) -> Result<(), DbError> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("DELETE FROM snapshot_rollups", [])?;
+    for res in ROLLUP_RESOLUTIONS {
+        tx.execute(
+            "INSERT INTO snapshot_rollups
+             (resolution_s, norad_id, bucket_s, samples, epoch_s, timestamp, pos_x, pos_y, pos_z, vel_x, vel_y, vel_z)
+             SELECT ?1, s.norad_id, b.bucket_s, b.samples, s.epoch_s, s.timestamp, s.pos_x, s.pos_y, s.pos_z, s.vel_x, s.vel_y, s.vel_z
+             FROM (SELECT MIN(id) AS first_id, (epoch_s / ?1) * ?1 AS bucket_s, COUNT(*) AS samples
+                   FROM snapshots WHERE epoch_s IS NOT NULL GROUP BY norad_id, bucket_s) b
+             JOIN snapshots s ON s.id = b.first_id",
+            params![res],
+        )?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// One point of a position history: a raw snapshot, or the representative
+/// snapshot of a rollup bucket holding `samples` snapshots.
+#[derive(Debug, Clone)]
+pub struct PositionSample {
+    pub timestamp: String,
+    pub samples: u64,
+    pub position: [f64; 3],
+    pub velocity: [f64; 3],
+}
+
+/// Number of raw snapshots and an upper bound on the number of 1-minute buckets
+/// of a satellite in `[start_s, end_s]`, read from the hourly rollups.
+pub fn history_density(conn: &Connection, norad_id: u64, start_s: i64, end_s: i64) -> Result<(u64, u64), DbError> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT COALESCE(SUM(samples), 0), COALESCE(SUM(MIN(samples, 60)), 0) FROM snapshot_rollups
+         WHERE resolution_s = 3600 AND norad_id = ?1 AND bucket_s BETWEEN ?2 AND ?3",
+    )?;
+    // Hourly buckets partially overlapping the range are counted whole.
+    let row = stmt.query_row(params![norad_id as i64, start_s.div_euclid(3600) * 3600, end_s], |row| {
+        Ok((row.get::<_, i64>(0)? as u64, row.get::<_, i64>(1)? as u64))
+    })?;
+    Ok(row)
+}
+
+/// Up to `limit` points of a satellite's history in `[start_s, end_s]`, oldest
+/// first: raw snapshots when `resolution_s` is `None`, otherwise the rollup of
+/// that resolution (one of `ROLLUP_RESOLUTIONS`).
+pub fn list_position_history(
+    conn: &Connection,
+    norad_id: u64,
+    start_s: i64,
+    end_s: i64,
+    resolution_s: Option<i64>,
+    limit: usize,
+) -> Result<Vec<PositionSample>, DbError> {
+    let map = |row: &rusqlite::Row<'_>| {
+        Ok(PositionSample {
+            timestamp: row.get(0)?,
+            samples: row.get::<_, i64>(1)? as u64,
+            position: [row.get(2)?, row.get(3)?, row.get(4)?],
+            velocity: [row.get(5)?, row.get(6)?, row.get(7)?],
+        })
+    };
+    let rows = match resolution_s {
+        None => {
+            let mut stmt = conn.prepare_cached(
+                "SELECT timestamp, 1, pos_x, pos_y, pos_z, vel_x, vel_y, vel_z FROM snapshots
+                 WHERE norad_id = ?1 AND epoch_s BETWEEN ?2 AND ?3 ORDER BY epoch_s, id LIMIT ?4",
+            )?;
+            let rows = stmt.query_map(params![norad_id as i64, start_s, end_s, limit as i64], map)?;
+            rows.filter_map(Result::ok).collect()
+        }
+        Some(res) => {
+            let mut stmt = conn.prepare_cached(
+                "SELECT timestamp, samples, pos_x, pos_y, pos_z, vel_x, vel_y, vel_z FROM snapshot_rollups
+                 WHERE resolution_s = ?1 AND norad_id = ?2 AND bucket_s BETWEEN ?3 AND ?4 ORDER BY bucket_s LIMIT ?5",
+            )?;
+            let rows = stmt.query_map(params![res, norad_id as i64, start_s.div_euclid(res) * res, end_s, limit as i64], map)?;
+            rows.filter_map(Result::ok).collect()
+        }
+    };
+    Ok(rows)
+}
+
+#[derive(Debug, Clone)]
+pub struct SnapshotRow {
+    pub id: i64,
+    pub norad_id: u64,
+    pub timestamp: String,
+    pub position: [f64; 3],
+    pub velocity: [f64; 3],
+}
+
+/// Up to `limit` snapshots with a row ID above `after_id`, oldest first,
+/// optionally for one satellite. Row IDs only grow, so paging by them never
+/// skips or repeats rows written between calls.
+pub fn list_snapshots_page(conn: &Connection, norad_id: Option<u64>, after_id: i64, limit: usize) -> Result<Vec<SnapshotRow>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, norad_id, timestamp, pos_x, pos_y, pos_z, vel_x, vel_y, vel_z FROM snapshots
+         WHERE id > ?1 AND (?2 IS NULL OR norad_id = ?2)
+         ORDER BY id LIMIT ?3",
+    )?;
+    let iter = stmt.query_map(params![after_id, norad_id.map(|n| n as i64), limit as i64], snapshot_from_row)?;
+    Ok(iter.filter_map(Result::ok).collect())
+}
+
+fn snapshot_from_row(row: &rusqlite::Row) -> rusqlite::Result<SnapshotRow> {
+    Ok(SnapshotRow {
+        id: row.get(0)?,
+        norad_id: row.get::<_, i64>(1)? as u64,
+        timestamp: row.get(2)?,
+        position: [row.get(3)?, row.get(4)?, row.get(5)?],
+        velocity: [row.get(6)?, row.get(7)?, row.get(8)?],
+    })
+}
+
+/// The satellite's last snapshot written before row `before_id`.
+pub fn previous_snapshot(conn: &Connection, norad_id: u64, before_id: i64) -> Result<Option<SnapshotRow>, DbError> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, norad_id, timestamp, pos_x, pos_y, pos_z, vel_x, vel_y, vel_z FROM snapshots
+         WHERE norad_id = ?1 AND id < ?2 ORDER BY id DESC LIMIT 1",
+    )?;
+    let mut rows = stmt.query(params![norad_id as i64, before_id])?;
+    match rows.next()? {
+        Some(row) => Ok(Some(snapshot_from_row(row)?)),
+        None => Ok(None),
+    }
+}
+
+/// A snapshot flagged by the anomaly analyzer.
+#[derive(Debug, Clone)]
+pub struct SnapshotAnomaly {
+    pub id: i64,
+    pub snapshot_id: i64,
+    pub norad_id: u64,
+    /// Time of the snapshot.
+    pub timestamp: String,
+    pub kind: String,
+    pub magnitude_km: f64,
+    pub detail: String,
+    pub detected_at: String,
+}
+
+/// Records an anomaly; returns `false` when the snapshot was already flagged
+/// for the same kind.
+pub fn insert_snapshot_anomaly(conn: &Connection, a: &SnapshotAnomaly) -> Result<bool, DbError> {
+    let inserted = execute_cached(
+        conn,
+        "INSERT OR IGNORE INTO snapshot_anomalies (snapshot_id, norad_id, timestamp, kind, magnitude_km, detail, detected_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![a.snapshot_id, a.norad_id as i64, a.timestamp, a.kind, a.magnitude_km, a.detail, a.detected_at],
+    )?;
+    Ok(inserted > 0)
+}
+
+/// Up to `limit` anomalies with a row ID above `after_id`, oldest first,
+/// optionally for one satellite.
+pub fn list_snapshot_anomalies_page(conn: &Connection, norad_id: Option<u64>, after_id: i64, limit: usize) -> Result<Vec<SnapshotAnomaly>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, snapshot_id, norad_id, timestamp, kind, magnitude_km, detail, detected_at FROM snapshot_anomalies
+         WHERE id > ?1 AND (?2 IS NULL OR norad_id = ?2)
+         ORDER BY id LIMIT ?3",
+    )?;
+    let iter = stmt.query_map(params![after_id, norad_id.map(|n| n as i64), limit as i64], |row| {
+        Ok(SnapshotAnomaly {
+            id: row.get(0)?,
+            snapshot_id: row.get(1)?,
+            norad_id: row.get::<_, i64>(2)? as u64,
+            timestamp: row.get(3)?,
+            kind: row.get(4)?,
+            magnitude_km: row.get(5)?,
+            detail: row.get(6)?,
+            detected_at: row.get(7)?,
+        })
+    })?;
+    Ok(iter.filter_map(Result::ok).collect())
+}
+
+/// Last row ID a background analyzer has processed (0 before its first run).
+pub fn analyzer_cursor(conn: &Connection, name: &str) -> Result<i64, DbError> {
+    let mut stmt = conn.prepare_cached("SELECT last_id FROM analyzer_cursors WHERE name = ?1")?;
+    let mut rows = stmt.query(params![name])?;
+    match rows.next()? {
+        Some(row) => Ok(row.get(0)?),
+        None => Ok(0),
+    }
+}
+
+pub fn set_analyzer_cursor(conn: &Connection, name: &str, last_id: i64) -> Result<(), DbError> {
+    execute_cached(
+        conn,
+        "INSERT INTO analyzer_cursors (name, last_id) VALUES (?1, ?2) ON CONFLICT(name) DO UPDATE SET last_id = excluded.last_id",
+        params![name, last_id],
+    )?;
+    Ok(())
+}
+
+/// Tenant column value: the stored `''` stands for "no tenant".
+fn tenant_from_column(tenant: String) -> Option<String> {
+    Some(tenant).filter(|t| !t.is_empty())
+}
+
+#[derive(Debug, Clone)]
+pub struct Station {
+    pub id: i64,
+    pub name: Option<String>,
+    pub lat: f64,
+    pub lon: f64,
+    /// Height above the WGS84 ellipsoid (m).
+    pub alt_m: f64,
+    /// Tenant owning the station; `None` on single-tenant deployments.
+    pub tenant: Option<String>,
+}
+
+fn station_from_row(row: &rusqlite::Row) -> rusqlite::Result<Station> {
+    Ok(Station {
+        id: row.get::<_, i64>(0)?,
+        name: row.get::<_, String>(1).ok(),
+        lat: row.get::<_, f64>(2)?,
+        lon: row.get::<_, f64>(3)?,
+        alt_m: row.get::<_, f64>(4)?,
+        tenant: tenant_from_column(row.get::<_, String>(5)?),
+    })
+}
+
+pub fn insert_station(conn: &Connection, name: Option<&str>, lat: f64, lon: f64, alt_m: f64, tenant: Option<&str>) -> Result<i64, DbError> {
+    execute_cached(
+        conn,
+        "INSERT INTO stations (name, lat, lon, alt_m, tenant) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![name, lat, lon, alt_m, tenant.unwrap_or_default()],
+    )?;
+    let id = conn.last_insert_rowid();
+    Ok(id)
+}
+
+pub fn list_stations(conn: &Connection) -> Result<Vec<Station>, DbError> {
+    let mut stmt = conn.prepare("SELECT id, name, lat, lon, alt_m, tenant FROM stations ORDER BY id")?;
+    let iter = stmt.query_map([], station_from_row)?;
+    Ok(iter.filter_map(Result::ok).collect())
+}
+
+pub fn get_station(conn: &Connection, id: i64) -> Result<Station, DbError> {
+    let mut stmt = conn.prepare("SELECT id, name, lat, lon, alt_m, tenant FROM stations WHERE id = ?1")?;
+    Ok(stmt.query_row(params![id], station_from_row)?)
+}
+
+/// Version of what predictions for a station depend on: its position, horizon
+/// and excluded sectors. Each change to one of them increments it.
+pub fn station_version(conn: &Connection, id: i64) -> Result<Option<i64>, DbError> {
+    let mut stmt = conn.prepare_cached("SELECT version FROM stations WHERE id = ?1")?;
+    let mut rows = stmt.query(params![id])?;
+    Ok(rows.next()?.map(|row| row.get(0)).transpose()?)
+}
+
+fn bump_station_version(conn: &Connection, id: i64) -> Result<(), DbError> {
+    execute_cached(conn, "UPDATE stations SET version = version + 1 WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+pub fn update_station(conn: &Connection, id: i64, name: Option<&str>, lat: f64, lon: f64, alt_m: f64) -> Result<(), DbError> {
+    in_transaction(conn, |tx| {
+        tx.execute(
+            "UPDATE stations SET name = ?1, lat = ?2, lon = ?3, alt_m = ?4, version = version + 1 WHERE id = ?5",
+            params![name, lat, lon, alt_m, id],
+        )?;
+        // A moved station needs a new horizon profile.
+        tx.execute("DELETE FROM station_horizons WHERE station_id = ?1", params![id])?;
+        Ok(())
+    })
+}
+
+pub fn delete_station(conn: &Connection, id: i64) -> Result<(), DbError> {
+    in_transaction(conn, |tx| {
+        tx.execute("DELETE FROM station_horizons WHERE station_id = ?1", params![id])?;
+        tx.execute("DELETE FROM station_exclusions WHERE station_id = ?1", params![id])?;
+        tx.execute("DELETE FROM device_tokens WHERE station_id = ?1", params![id])?;
+        tx.execute("DELETE FROM station_telemetry WHERE station_id = ?1", params![id])?;
+        tx.execute("DELETE FROM station_favorites WHERE station_id = ?1", params![id])?;
+        tx.execute("DELETE FROM stations WHERE id = ?1", params![id])?;
+        Ok(())
+    })
+}
+
+#[derive(Debug, Clone)]
+pub struct StationHorizon {
+    pub station_id: i64,
+    /// Terrain elevation (degrees) per 1° azimuth bin, starting at north.
+    pub profile: Vec<f64>,
+    pub computed_at: String,
+}
+
+/// Stores (or replaces) the horizon profile of a station as a JSON array.
+pub fn upsert_station_horizon(conn: &Connection, station_id: i64, profile: &[f64], computed_at: &str) -> Result<(), DbError> {
+    let json = serde_json::to_string(profile).unwrap_or_else(|_| "[]".to_string());
+    in_transaction(conn, |tx| {
+        execute_cached(
+            tx,
+            "INSERT INTO station_horizons (station_id, profile, computed_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(station_id) DO UPDATE SET profile=excluded.profile, computed_at=excluded.computed_at",
+            params![station_id, json, computed_at],
+        )?;
+        bump_station_version(tx, station_id)
+    })
+}
+
+pub fn get_station_horizon(conn: &Connection, station_id: i64) -> Result<Option<StationHorizon>, DbError> {
+    let mut stmt = conn.prepare("SELECT station_id, profile, computed_at FROM station_horizons WHERE station_id = ?1")?;
+    let mut rows = stmt.query(params![station_id])?;
+    if let Some(row) = rows.next()? {
+        let profile: String = row.get(1)?;
+        Ok(Some(StationHorizon {
+            station_id: row.get::<_, i64>(0)?,
+            profile: serde_json::from_str(&profile).unwrap_or_default(),
+            computed_at: row.get::<_, String>(2)?,
+        }))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Replaces the excluded azimuth sectors of a station, given as (start, end) degrees.
+pub fn set_station_exclusions(conn: &Connection, station_id: i64, sectors: &[(f64, f64)]) -> Result<(), DbError> {
+    in_transaction(conn, |tx| {
+        tx.execute("DELETE FROM station_exclusions WHERE station_id = ?1", params![station_id])?;
+        let mut stmt = tx.prepare_cached("INSERT INTO station_exclusions (station_id, start_deg, end_deg) VALUES (?1, ?2, ?3)")?;
+        for (start, end) in sectors {
+            stmt.execute(params![station_id, start, end])?;
+        }
+        bump_station_version(tx, station_id)
+    })
+}
+
+pub fn list_station_exclusions(conn: &Connection, station_id: i64) -> Result<Vec<(f64, f64)>, DbError> {
+    let mut stmt = conn.prepare("SELECT start_deg, end_deg FROM station_exclusions WHERE station_id = ?1 ORDER BY id")?;
+    let iter = stmt.query_map(params![station_id], |row| Ok((row.get::<_, f64>(0)?, row.get::<_, f64>(1)?)))?;
+    Ok(iter.filter_map(Result::ok).collect())
+}
+
+/// Replaces the satellites a station's dashboard follows.
+pub fn set_station_favorites(conn: &Connection, station_id: i64, norad_ids: &[u64]) -> Result<(), DbError> {
+    in_transaction(conn, |tx| {
+        tx.execute("DELETE FROM station_favorites WHERE station_id = ?1", params![station_id])?;
+        let mut stmt = tx.prepare_cached("INSERT OR IGNORE INTO station_favorites (station_id, norad_id) VALUES (?1, ?2)")?;
+        for norad_id in norad_ids {
+            stmt.execute(params![station_id, *norad_id as i64])?;
+        }
+        Ok(())
+    })
+}
+
+pub fn list_station_favorites(conn: &Connection, station_id: i64) -> Result<Vec<u64>, DbError> {
+    let mut stmt = conn.prepare("SELECT norad_id FROM station_favorites WHERE station_id = ?1 ORDER BY norad_id")?;
+    let iter = stmt.query_map(params![station_id], |row| Ok(row.get::<_, i64>(0)? as u64))?;
+    Ok(iter.filter_map(Result::ok).collect())
+}
+
+/// A conjunction alert subscription. Each tenant keeps its own threshold for a satellite.
+#[derive(Debug, Clone)]
+pub struct ProtectedAsset {
+    pub norad_id: u64,
+    pub alert_threshold_km: f64,
+    pub tenant: Option<String>,
+}
+
+pub fn upsert_protected_asset(conn: &Connection, norad_id: u64, alert_threshold_km: f64, tenant: Option<&str>) -> Result<(), DbError> {
+    execute_cached(
+        conn,
+        "INSERT INTO protected_assets (norad_id, tenant, alert_threshold_km) VALUES (?1, ?2, ?3)
+         ON CONFLICT(norad_id, tenant) DO UPDATE SET alert_threshold_km=excluded.alert_threshold_km",
+        params![norad_id as i64, tenant.unwrap_or_default(), alert_threshold_km],
+    )?;
+    Ok(())
+}
+
+/// Protected assets of one tenant, or of every tenant when `scope` is `None`.
+pub fn list_protected_assets(conn: &Connection, scope: Option<&str>) -> Result<Vec<ProtectedAsset>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT norad_id, alert_threshold_km, tenant FROM protected_assets WHERE (?1 IS NULL OR tenant = ?1) ORDER BY norad_id, tenant",
+    )?;
+    let iter = stmt.query_map(params![scope], |row| {
+        Ok(ProtectedAsset {
+            norad_id: row.get::<_, i64>(0)? as u64,
+            alert_threshold_km: row.get::<_, f64>(1)?,
+            tenant: tenant_from_column(row.get::<_, String>(2)?),
+        })
+    })?;
+    Ok(iter.filter_map(Result::ok).collect())
+}
+
+/// Removes one tenant's subscription to a satellite, or everyone's when `scope` is `None`.
+pub fn delete_protected_asset(conn: &Connection, norad_id: u64, scope: Option<&str>) -> Result<(), DbError> {
+    conn.execute("DELETE FROM protected_assets WHERE norad_id = ?1 AND (?2 IS NULL OR tenant = ?2)", params![norad_id as i64, scope])?;
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+pub struct ConjunctionRecord {
+    pub id: i64,
+    pub asset_norad_id: u64,
+    pub secondary_norad_id: u64,
+    pub secondary_name: Option<String>,
+    pub tca: String,
+    pub miss_distance_km: f64,
+    pub relative_speed_km_s: f64,
+    pub screened_at: String,
+}
+
+pub fn insert_conjunction(
+    conn: &Connection,
+    event: &crate::predictors::conjunctions::ConjunctionEvent,
+    screened_at: &str,
+) -> Result<i64, DbError> {
+    execute_cached(
+        conn,
+        "INSERT INTO conjunctions (asset_norad_id, secondary_norad_id, secondary_name, tca, miss_distance_km, relative_speed_km_s, screened_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            event.asset_norad_id as i64,
+            event.secondary_norad_id as i64,
+            event.secondary_name,
+            event.tca.to_rfc3339(),
+            event.miss_distance_km,
+            event.relative_speed_km_s,
+            screened_at,
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Replaces an asset's upcoming conjunctions (TCA at or after `from`) with a new
+/// screening in one transaction, so repeated runs over the same window don't pile
+/// up duplicates. Past events are kept as history. Returns the number stored.
+pub fn replace_conjunctions(
+    conn: &Connection,
+    asset_norad_id: u64,
+    from: &str,
+    events: &[crate::predictors::conjunctions::ConjunctionEvent],
+    screened_at: &str,
+) -> Result<usize, DbError> {
+    in_transaction(conn, |conn| {
+        execute_cached(
+            conn,
+            "DELETE FROM conjunctions WHERE asset_norad_id = ?1 AND tca >= ?2",
+            params![asset_norad_id as i64, from],
+        )?;
+        for event in events {
+            insert_conjunction(conn, event, screened_at)?;
+        }
+        Ok(events.len())
+    })
+}
+
+/// Lists stored conjunctions, latest TCA first, optionally restricted to one asset.
+/// Screened conjunctions, newest TCA first; with a `scope` only those of the
+/// tenant's protected assets.
+pub fn list_conjunctions(conn: &Connection, asset_norad_id: Option<u64>, scope: Option<&str>, limit: usize) -> Result<Vec<ConjunctionRecord>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, asset_norad_id, secondary_norad_id, secondary_name, tca, miss_distance_km, relative_speed_km_s, screened_at
+         FROM conjunctions
+         WHERE (?1 IS NULL OR asset_norad_id = ?1)
+           AND (?3 IS NULL OR asset_norad_id IN (SELECT norad_id FROM protected_assets WHERE tenant = ?3))
+         ORDER BY tca DESC
+         LIMIT ?2",
+    )?;
+    let iter = stmt.query_map(params![asset_norad_id.map(|n| n as i64), limit as i64, scope], |row| {
+        Ok(ConjunctionRecord {
+            id: row.get::<_, i64>(0)?,
+            asset_norad_id: row.get::<_, i64>(1)? as u64,
+            secondary_norad_id: row.get::<_, i64>(2)? as u64,
+            secondary_name: row.get::<_, String>(3).ok(),
+            tca: row.get::<_, String>(4)?,
+            miss_distance_km: row.get::<_, f64>(5)?,
+            relative_speed_km_s: row.get::<_, f64>(6)?,
+            screened_at: row.get::<_, String>(7)?,
+        })
+    })?;
+    Ok(iter.filter_map(Result::ok).collect())
+}
+
+#[derive(Debug, Clone)]
+pub struct TleHistoryRecord {
+    pub norad_id: u64,
+    pub name: Option<String>,
+    pub line1: String,
+    pub line2: String,
+}
+
+/// Archives TLE records in one transaction. Records already stored for the same
+/// NORAD ID and epoch are ignored; returns the number of new rows.
+pub fn insert_tle_history(conn: &Connection, records: &[crate::core::tle::TleRecord], fetched_at: &str) -> Result<usize, DbError> {
+    let tx = conn.unchecked_transaction()?;
+    let mut inserted = 0usize;
+    {
+        let mut stmt = tx.prepare_cached(
+            "INSERT OR IGNORE INTO tle_history (norad_id, epoch, name, line1, line2, fetched_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+        for rec in records {
+            let Ok(el) = rec.to_elements() else { continue };
+            inserted += stmt.execute(params![
+                el.norad_id as i64,
+                el.datetime.and_utc().to_rfc3339_opts(chrono::SecondsFormat::Micros, true),
+                rec.name,
+                rec.line1,
+                rec.line2,
+                fetched_at,
+            ])?;
+        }
+    }
+    tx.commit()?;
+    Ok(inserted)
+}
+
+/// Lists archived TLEs with an epoch at or after `since` (RFC 3339), ordered by NORAD ID then epoch.
+pub fn list_tle_history_since(conn: &Connection, since: &str) -> Result<Vec<TleHistoryRecord>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT norad_id, name, line1, line2 FROM tle_history
+         WHERE epoch >= ?1 ORDER BY norad_id, epoch",
+    )?;
+    let iter = stmt.query_map(params![since], map_tle_history_row)?;
+    Ok(iter.filter_map(Result::ok).collect())
+}
+
+/// Archived TLE with the epoch nearest `as_of` (RFC 3339) for every satellite, or
+/// only for `norad_id`, ordered by NORAD ID.
+pub fn list_tle_nearest(conn: &Connection, as_of: &str, norad_id: Option<u64>) -> Result<Vec<TleHistoryRecord>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT norad_id, name, line1, line2 FROM (
+             SELECT norad_id, name, line1, line2, ROW_NUMBER() OVER (
+                 PARTITION BY norad_id ORDER BY ABS(julianday(epoch) - julianday(?1))
+             ) AS rank
+             FROM tle_history WHERE ?2 IS NULL OR norad_id = ?2
+         ) WHERE rank = 1 ORDER BY norad_id",
+    )?;
+    let iter = stmt.query_map(params![as_of, norad_id.map(|n| n as i64)], map_tle_history_row)?;
+    Ok(iter.filter_map(Result::ok).collect())
+}
+
+fn map_tle_history_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<TleHistoryRecord> {
+    Ok(TleHistoryRecord {
+        norad_id: row.get::<_, i64>(0)? as u64,
+        name: row.get::<_, String>(1).ok(),
+        line1: row.get::<_, String>(2)?,
+        line2: row.get::<_, String>(3)?,
+    })
+}
+
+#[derive(Debug, Clone)]
+pub struct TleHistoryEntry {
+    pub id: i64,
+    pub norad_id: u64,
+    pub epoch: String,
+    pub name: Option<String>,
+    pub line1: String,
+    pub line2: String,
+    pub fetched_at: String,
+}
+
+fn tle_history_entry_from_row(row: &rusqlite::Row) -> rusqlite::Result<TleHistoryEntry> {
+    Ok(TleHistoryEntry {
+        id: row.get(0)?,
+        norad_id: row.get::<_, i64>(1)? as u64,
+        epoch: row.get(2)?,
+        name: row.get::<_, String>(3).ok(),
+        line1: row.get(4)?,
+        line2: row.get(5)?,
+        fetched_at: row.get(6)?,
+    })
+}
+
+/// Up to `limit` archived TLEs with a row ID above `after_id`, in archive order,
+/// optionally for one satellite.
+pub fn list_tle_history_page(conn: &Connection, norad_id: Option<u64>, after_id: i64, limit: usize) -> Result<Vec<TleHistoryEntry>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, norad_id, epoch, name, line1, line2, fetched_at FROM tle_history
+         WHERE id > ?1 AND (?2 IS NULL OR norad_id = ?2)
+         ORDER BY id LIMIT ?3",
+    )?;
+    let iter = stmt.query_map(params![after_id, norad_id.map(|n| n as i64), limit as i64], tle_history_entry_from_row)?;
+    Ok(iter.filter_map(Result::ok).collect())
+}
+
+/// Every archived TLE of a satellite with an epoch in `[from, to]` (RFC 3339 as
+/// archived; either bound may be open), oldest epoch first.
+pub fn list_tle_history_range(conn: &Connection, norad_id: u64, from: Option<&str>, to: Option<&str>) -> Result<Vec<TleHistoryEntry>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, norad_id, epoch, name, line1, line2, fetched_at FROM tle_history
+         WHERE norad_id = ?1 AND (?2 IS NULL OR epoch >= ?2) AND (?3 IS NULL OR epoch <= ?3)
+         ORDER BY epoch",
+    )?;
+    let iter = stmt.query_map(params![norad_id as i64, from, to], tle_history_entry_from_row)?;
+    Ok(iter.filter_map(Result::ok).collect())
+}
+
+/// The satellite's newest archived TLE with an epoch at or before `epoch`
+/// (RFC 3339 as archived).
+pub fn latest_tle_until(conn: &Connection, norad_id: u64, epoch: &str) -> Result<Option<TleHistoryEntry>, DbError> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, norad_id, epoch, name, line1, line2, fetched_at FROM tle_history
+         WHERE norad_id = ?1 AND epoch <= ?2 ORDER BY epoch DESC LIMIT 1",
+    )?;
+    let mut rows = stmt.query(params![norad_id as i64, epoch])?;
+    match rows.next()? {
+        Some(row) => Ok(Some(tle_history_entry_from_row(row)?)),
+        None => Ok(None),
+    }
+}
+
+/// How far an element set's prediction was from its successor at the
+/// successor's epoch, in the successor's radial / along-track / cross-track frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TleAccuracy {
+    pub norad_id: u64,
+    pub epoch: String,
+    pub previous_epoch: String,
+    pub gap_hours: f64,
+    pub error_km: f64,
+    pub radial_km: f64,
+    pub along_track_km: f64,
+    pub cross_track_km: f64,
+    pub recorded_at: String,
+}
+
+/// Stores accuracy samples in one transaction, ignoring epochs already
+/// recorded; returns the number of new rows.
+pub fn insert_tle_accuracy(conn: &Connection, samples: &[TleAccuracy]) -> Result<usize, DbError> {
+    in_transaction(conn, |tx| {
+        let mut stmt = tx.prepare_cached(
+            "INSERT OR IGNORE INTO tle_accuracy
+             (norad_id, epoch, previous_epoch, gap_hours, error_km, radial_km, along_track_km, cross_track_km, recorded_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        )?;
+        let mut inserted = 0;
+        for s in samples {
+            inserted += stmt.execute(params![
+                s.norad_id as i64,
+                s.epoch,
+                s.previous_epoch,
+                s.gap_hours,
+                s.error_km,
+                s.radial_km,
+                s.along_track_km,
+                s.cross_track_km,
+                s.recorded_at,
+            ])?;
+        }
+        Ok(inserted)
+    })
+}
+
+/// Accuracy samples with an epoch at or after `since`, optionally for one
+/// satellite, ordered by NORAD ID then epoch.
+pub fn list_tle_accuracy(conn: &Connection, norad_id: Option<u64>, since: &str) -> Result<Vec<TleAccuracy>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT norad_id, epoch, previous_epoch, gap_hours, error_km, radial_km, along_track_km, cross_track_km, recorded_at
+         FROM tle_accuracy WHERE epoch >= ?1 AND (?2 IS NULL OR norad_id = ?2) ORDER BY norad_id, epoch",
+    )?;
+    let iter = stmt.query_map(params![since, norad_id.map(|n| n as i64)], |row| {
+        Ok(TleAccuracy {
+            norad_id: row.get::<_, i64>(0)? as u64,
+            epoch: row.get(1)?,
+            previous_epoch: row.get(2)?,
+            gap_hours: row.get(3)?,
+            error_km: row.get(4)?,
+            radial_km: row.get(5)?,
+            along_track_km: row.get(6)?,
+            cross_track_km: row.get(7)?,
+            recorded_at: row.get(8)?,
+        })
+    })?;
+    Ok(iter.filter_map(Result::ok).collect())
+}
+
+/// Objects in one altitude shell of a fetched catalog.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShellCount {
+    pub fetched_at: String,
+    /// Lower edge of the shell (km).
+    pub shell_km: u32,
+    pub width_km: u32,
+    pub count: u64,
+}
+
+/// Stores the counts of consecutive `width_km` shells from 0 km for one fetch,
+/// replacing any stored for the same fetch time.
+pub fn insert_shell_counts(conn: &Connection, fetched_at: &str, width_km: u32, counts: &[u64]) -> Result<(), DbError> {
+    in_transaction(conn, |tx| {
+        let mut stmt = tx.prepare_cached("INSERT OR REPLACE INTO shell_counts (fetched_at, shell_km, width_km, count) VALUES (?1, ?2, ?3, ?4)")?;
+        for (i, count) in counts.iter().enumerate() {
+            stmt.execute(params![fetched_at, i as i64 * width_km as i64, width_km, *count as i64])?;
+        }
+        Ok(())
+    })
+}
+
+/// Shell counts of the fetches at or after `since`, ordered by fetch time then
+/// altitude.
+pub fn list_shell_counts(conn: &Connection, since: &str) -> Result<Vec<ShellCount>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT fetched_at, shell_km, width_km, count FROM shell_counts WHERE fetched_at >= ?1 ORDER BY fetched_at, shell_km",
+    )?;
+    let iter = stmt.query_map(params![since], |row| {
+        Ok(ShellCount {
+            fetched_at: row.get(0)?,
+            shell_km: row.get(1)?,
+            width_km: row.get(2)?,
+            count: row.get::<_, i64>(3)? as u64,
+        })
+    })?;
+    Ok(iter.filter_map(Result::ok).collect())
+}
+
+#[derive(Debug, Clone)]
+pub struct FetchLogEntry {
+    pub id: i64,
+    pub fetched_at: String,
+    /// Where the elements came from, e.g. `celestrak:active` or `upload`.
+    pub source: String,
+    pub records: i64,
+    /// TLE entries skipped while parsing the fetched text.
+    pub rejected: Vec<crate::core::tle::RejectedTle>,
+    pub error: Option<String>,
+}
+
+/// Records one catalog fetch or upload; `error` is set when it failed. Rejected
+/// TLE entries are stored as a JSON array (NULL when none).
+pub fn insert_fetch_log(conn: &Connection, fetched_at: &str, source: &str, records: usize, rejected: &[crate::core::tle::RejectedTle], error: Option<&str>) -> Result<(), DbError> {
+    let rejected = (!rejected.is_empty()).then(|| serde_json::to_string(rejected).unwrap_or_else(|_| "[]".to_string()));
+    execute_cached(
+        conn,
+        "INSERT INTO fetch_log (fetched_at, source, records, rejected, error) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![fetched_at, source, records as i64, rejected, error],
+    )?;
+    Ok(())
+}
+
+/// Up to `limit` fetch log entries with a row ID above `after_id`, oldest first.
+pub fn list_fetch_log_page(conn: &Connection, after_id: i64, limit: usize) -> Result<Vec<FetchLogEntry>, DbError> {
+    let mut stmt = conn.prepare("SELECT id, fetched_at, source, records, rejected, error FROM fetch_log WHERE id > ?1 ORDER BY id LIMIT ?2")?;
+    let iter = stmt.query_map(params![after_id, limit as i64], |row| {
+        let rejected: Option<String> = row.get(4)?;
+        Ok(FetchLogEntry {
+            id: row.get(0)?,
+            fetched_at: row.get(1)?,
+            source: row.get(2)?,
+            records: row.get(3)?,
+            rejected: rejected.and_then(|r| serde_json::from_str(&r).ok()).unwrap_or_default(),
+            error: row.get(5)?,
+        })
+    })?;
+    Ok(iter.filter_map(Result::ok).collect())
+}
+
+#[derive(Debug, Clone)]
+pub struct Observation {
+    pub id: i64,
+    pub station_id: i64,
+    pub norad_id: Option<u64>,
+    pub observed_at: String,
+    pub az_deg: f64,
+    pub el_deg: f64,
+}
+
+pub fn insert_observation(
+    conn: &Connection,
+    station_id: i64,
+    norad_id: Option<u64>,
+    observed_at: &str,
+    az_deg: f64,
+    el_deg: f64,
+) -> Result<i64, DbError> {
+    execute_cached(
+        conn,
+        "INSERT INTO observations (station_id, norad_id, observed_at, az_deg, el_deg) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![station_id, norad_id.map(|n| n as i64), observed_at, az_deg, el_deg],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Lists observations ordered by time, optionally filtered by station and/or NORAD ID.
+/// With a `scope` only observations from the tenant's stations are listed.
+pub fn list_observations(
+    conn: &Connection,
+    station_id: Option<i64>,
+    norad_id: Option<u64>,
+    scope: Option<&str>,
+    limit: usize,
+) -> Result<Vec<Observation>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, station_id, norad_id, observed_at, az_deg, el_deg FROM observations
+         WHERE (?1 IS NULL OR station_id = ?1) AND (?2 IS NULL OR norad_id = ?2)
+           AND (?4 IS NULL OR station_id IN (SELECT id FROM stations WHERE tenant = ?4))
+         ORDER BY observed_at LIMIT ?3",
+    )?;
+    let iter = stmt.query_map(params![station_id, norad_id.map(|n| n as i64), limit as i64, scope], map_observation_row)?;
+    Ok(iter.filter_map(Result::ok).collect())
+}
+
+pub fn get_observations_by_ids(conn: &Connection, ids: &[i64]) -> Result<Vec<Observation>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, station_id, norad_id, observed_at, az_deg, el_deg FROM observations WHERE id = ?1",
+    )?;
+    let mut out = Vec::with_capacity(ids.len());
+    for id in ids {
+        let mut rows = stmt.query_map(params![id], map_observation_row)?;
+        match rows.next() {
+            Some(row) => out.push(row?),
+            None => return Err(rusqlite::Error::QueryReturnedNoRows.into()),
+        }
+    }
+    Ok(out)
+}
+
+pub fn delete_observation(conn: &Connection, id: i64) -> Result<(), DbError> {
+    conn.execute("DELETE FROM observations WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+fn map_observation_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Observation> {
+    Ok(Observation {
+        id: row.get::<_, i64>(0)?,
+        station_id: row.get::<_, i64>(1)?,
+        norad_id: row.get::<_, Option<i64>>(2)?.map(|n| n as u64),
+        observed_at: row.get::<_, String>(3)?,
+        az_deg: row.get::<_, f64>(4)?,
+        el_deg: row.get::<_, f64>(5)?,
+    })
+}
+
+/// NORAD IDs that have at least one archived element set.
+pub fn known_norad_ids(conn: &Connection) -> Result<HashSet<u64>, DbError> {
+    let mut stmt = conn.prepare("SELECT DISTINCT norad_id FROM tle_history")?;
+    let iter = stmt.query_map([], |row| Ok(row.get::<_, i64>(0)? as u64))?;
+    Ok(iter.filter_map(Result::ok).collect())
+}
+
+/// Applies a tenant's tag (`None`: a shared one) to each NORAD ID in one
+/// transaction; existing tags keep their original timestamp.
+pub fn tag_satellites(conn: &Connection, norad_ids: &[u64], tag: &str, tenant: Option<&str>, created_at: &str) -> Result<(), DbError> {
+    in_transaction(conn, |tx| {
+        let mut stmt = tx.prepare_cached("INSERT OR IGNORE INTO satellite_tags (norad_id, tag, tenant, created_at) VALUES (?1, ?2, ?3, ?4)")?;
+        for id in norad_ids {
+            stmt.execute(params![*id as i64, tag, tenant.unwrap_or_default(), created_at])?;
+        }
+        Ok(())
+    })
+}
+
+#[derive(Debug, Clone)]
+pub struct TaggedSatellite {
+    pub norad_id: u64,
+    pub name: Option<String>,
+    pub tagged_at: String,
+}
+
+/// Lists satellites carrying a tenant's `tag` that were tagged at or after
+/// `since`. Without `since` only the most recent tagging batch is returned.
+pub fn list_tagged(conn: &Connection, tag: &str, tenant: Option<&str>, since: Option<&str>) -> Result<Vec<TaggedSatellite>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT t.norad_id, t.created_at,
+                (SELECT name FROM tle_history h WHERE h.norad_id = t.norad_id ORDER BY epoch DESC LIMIT 1)
+         FROM satellite_tags t
+         WHERE t.tag = ?1 AND t.tenant = ?2
+           AND t.created_at >= COALESCE(?3, (SELECT MAX(created_at) FROM satellite_tags WHERE tag = ?1 AND tenant = ?2))
+         ORDER BY t.created_at DESC, t.norad_id",
+    )?;
+    let iter = stmt.query_map(params![tag, tenant.unwrap_or_default(), since], |row| {
+        Ok(TaggedSatellite {
+            norad_id: row.get::<_, i64>(0)? as u64,
+            tagged_at: row.get::<_, String>(1)?,
+            name: row.get::<_, String>(2).ok(),
+        })
+    })?;
+    Ok(iter.filter_map(Result::ok).collect())
+}
+
+/// One tag on one satellite.
+#[derive(Debug, Clone)]
+pub struct SatelliteTag {
+    pub norad_id: u64,
+    pub tag: String,
+    /// Tenant the tag belongs to; `None` for shared tags such as `new`.
+    pub tenant: Option<String>,
+    pub created_at: String,
+}
+
+/// Every tag applied to any satellite, of every tenant.
+pub fn list_all_tags(conn: &Connection) -> Result<Vec<SatelliteTag>, DbError> {
+    let mut stmt = conn.prepare("SELECT norad_id, tag, tenant, created_at FROM satellite_tags ORDER BY tenant, tag, norad_id")?;
+    let iter = stmt.query_map([], |row| {
+        Ok(SatelliteTag {
+            norad_id: row.get::<_, i64>(0)? as u64,
+            tag: row.get(1)?,
+            tenant: tenant_from_column(row.get::<_, String>(2)?),
+            created_at: row.get(3)?,
+        })
+    })?;
+    Ok(iter.filter_map(Result::ok).collect())
+}
+
+/// NORAD IDs of every satellite carrying a tenant's `tag`, whenever it was applied.
+pub fn tagged_norad_ids(conn: &Connection, tag: &str, tenant: Option<&str>) -> Result<Vec<u64>, DbError> {
+    let mut stmt = conn.prepare("SELECT norad_id FROM satellite_tags WHERE tag = ?1 AND tenant = ?2 ORDER BY norad_id")?;
+    let iter = stmt.query_map(params![tag, tenant.unwrap_or_default()], |row| Ok(row.get::<_, i64>(0)? as u64))?;
+    Ok(iter.filter_map(Result::ok).collect())
+}
+
+/// Makes `norad_ids` the members of a tenant's `tag`: others lose it,
+/// newcomers get it with `created_at` and remaining members keep their
+/// original timestamp.
+pub fn replace_tag_members(conn: &Connection, tag: &str, tenant: Option<&str>, norad_ids: &[u64], created_at: &str) -> Result<(), DbError> {
+    let members: HashSet<u64> = norad_ids.iter().copied().collect();
+    let tenant = tenant.unwrap_or_default();
+    in_transaction(conn, |tx| {
+        for id in tagged_norad_ids(tx, tag, Some(tenant))? {
+            if !members.contains(&id) {
+                tx.execute("DELETE FROM satellite_tags WHERE norad_id = ?1 AND tag = ?2 AND tenant = ?3", params![id as i64, tag, tenant])?;
+            }
+        }
+        let mut stmt = tx.prepare_cached("INSERT OR IGNORE INTO satellite_tags (norad_id, tag, tenant, created_at) VALUES (?1, ?2, ?3, ?4)")?;
+        for id in &members {
+            stmt.execute(params![*id as i64, tag, tenant, created_at])?;
+        }
+        Ok(())
+    })
+}
+
+/// Number of satellites carrying each tag as `(tenant, tag, count)`. With a
+/// `scope` only that tenant's tags and the shared ones are counted.
+pub fn tag_counts(conn: &Connection, scope: Option<&str>) -> Result<Vec<(Option<String>, String, usize)>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT tenant, tag, COUNT(*) FROM satellite_tags WHERE (?1 IS NULL OR tenant IN (?1, '')) GROUP BY tenant, tag ORDER BY tag, tenant",
+    )?;
+    let iter = stmt.query_map(params![scope], |row| {
+        Ok((tenant_from_column(row.get::<_, String>(0)?), row.get::<_, String>(1)?, row.get::<_, i64>(2)? as usize))
+    })?;
+    Ok(iter.filter_map(Result::ok).collect())
+}
+
+/// Whose `tag` a reader in `tenant` gets: the tenant's own when it has a tag or
+/// dynamic group of that name, the shared one otherwise.
+pub fn tag_owner(conn: &Connection, tag: &str, tenant: Option<&str>) -> Result<Option<String>, DbError> {
+    let Some(tenant) = tenant.filter(|t| !t.is_empty()) else {
+        return Ok(None);
+    };
+    let owns: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM satellite_tags WHERE tag = ?1 AND tenant = ?2)
+             OR EXISTS(SELECT 1 FROM dynamic_groups WHERE name = ?1 AND tenant = ?2)",
+        params![tag, tenant],
+        |row| row.get(0),
+    )?;
+    Ok(owns.then(|| tenant.to_string()))
+}
+
+/// A group whose members are the catalogued objects matching `filter`,
+/// stored as satellite tags named after the group and updated on each load.
+/// Names are unique per tenant.
+#[derive(Debug, Clone)]
+pub struct DynamicGroup {
+    pub name: String,
+    /// Tenant owning the group and its tags; `None` for shared groups.
+    pub tenant: Option<String>,
+    pub filter: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+fn dynamic_group_from_row(row: &rusqlite::Row) -> rusqlite::Result<DynamicGroup> {
+    Ok(DynamicGroup {
+        name: row.get(0)?,
+        tenant: tenant_from_column(row.get::<_, String>(1)?),
+        filter: row.get(2)?,
+        created_at: row.get(3)?,
+        updated_at: row.get(4)?,
+    })
+}
+
+/// Defines a tenant's dynamic group or replaces its filter; returns whether it is new.
+pub fn upsert_dynamic_group(conn: &Connection, name: &str, tenant: Option<&str>, filter: &str, now: &str) -> Result<bool, DbError> {
+    let tenant = tenant.unwrap_or_default();
+    let updated = conn.execute("UPDATE dynamic_groups SET filter = ?3, updated_at = ?4 WHERE name = ?1 AND tenant = ?2", params![name, tenant, filter, now])?;
+    if updated > 0 {
+        return Ok(false);
+    }
+    conn.execute(
+        "INSERT INTO dynamic_groups (name, tenant, filter, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?4)",
+        params![name, tenant, filter, now],
+    )?;
+    Ok(true)
+}
+
+pub fn get_dynamic_group(conn: &Connection, name: &str, tenant: Option<&str>) -> Result<Option<DynamicGroup>, DbError> {
+    let mut stmt = conn.prepare("SELECT name, tenant, filter, created_at, updated_at FROM dynamic_groups WHERE name = ?1 AND tenant = ?2")?;
+    let mut rows = stmt.query(params![name, tenant.unwrap_or_default()])?;
+    match rows.next()? {
+        Some(row) => Ok(Some(dynamic_group_from_row(row)?)),
+        None => Ok(None),
+    }
+}
+
+/// Dynamic groups of every tenant, or with a `scope` that tenant's and the shared ones.
+pub fn list_dynamic_groups(conn: &Connection, scope: Option<&str>) -> Result<Vec<DynamicGroup>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT name, tenant, filter, created_at, updated_at FROM dynamic_groups WHERE (?1 IS NULL OR tenant IN (?1, '')) ORDER BY name, tenant",
+    )?;
+    let iter = stmt.query_map(params![scope], dynamic_group_from_row)?;
+    Ok(iter.filter_map(Result::ok).collect())
+}
+
+/// Deletes a tenant's dynamic group and its tags; returns whether it existed.
+pub fn delete_dynamic_group(conn: &Connection, name: &str, tenant: Option<&str>) -> Result<bool, DbError> {
+    let tenant = tenant.unwrap_or_default();
+    in_transaction(conn, |tx| {
+        if tx.execute("DELETE FROM dynamic_groups WHERE name = ?1 AND tenant = ?2", params![name, tenant])? == 0 {
+            return Ok(false);
+        }
+        tx.execute("DELETE FROM satellite_tags WHERE tag = ?1 AND tenant = ?2", params![name, tenant])?;
+        Ok(true)
+    })
+}
+
+/// Latest archived TLE for every satellite carrying `tag`, whichever tenant tagged it.
+pub fn list_latest_records_with_tag(conn: &Connection, tag: &str) -> Result<Vec<crate::core::tle::TleRecord>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT h.name, h.line1, h.line2 FROM tle_history h
+         WHERE h.norad_id IN (SELECT norad_id FROM satellite_tags WHERE tag = ?1)
+           AND h.epoch = (SELECT MAX(epoch) FROM tle_history WHERE norad_id = h.norad_id)",
+    )?;
+    let iter = stmt.query_map(params![tag], |row| {
+        Ok(crate::core::tle::TleRecord {
+            name: row.get::<_, String>(0).ok(),
+            line1: row.get::<_, String>(1)?,
+            line2: row.get::<_, String>(2)?,
+        })
+    })?;
+    Ok(iter.filter_map(Result::ok).collect())
+}
+
+/// User-assigned names of a satellite: an optional display name shown instead of
+/// the catalog name, and further aliases to search by.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SatelliteAliases {
+    pub display_name: Option<String>,
+    pub aliases: Vec<String>,
+}
+
+impl SatelliteAliases {
+    fn push(&mut self, alias: String, display: bool) {
+        if display {
+            self.display_name = Some(alias);
+        } else {
+            self.aliases.push(alias);
+        }
+    }
+
+    /// Whether the display name or an alias contains `needle` (already lowercased).
+    pub fn matches(&self, needle: &str) -> bool {
+        self.display_name.iter().chain(&self.aliases).any(|a| a.to_lowercase().contains(needle))
+    }
+}
+
+/// Replaces the display name and aliases of a satellite.
+pub fn set_satellite_aliases(conn: &Connection, norad_id: u64, names: &SatelliteAliases) -> Result<(), DbError> {
+    in_transaction(conn, |tx| {
+        tx.execute("DELETE FROM satellite_aliases WHERE norad_id = ?1", params![norad_id as i64])?;
+        let mut stmt = tx.prepare_cached("INSERT OR REPLACE INTO satellite_aliases (norad_id, alias, display) VALUES (?1, ?2, ?3)")?;
+        for alias in &names.aliases {
+            stmt.execute(params![norad_id as i64, alias, false])?;
+        }
+        if let Some(display_name) = &names.display_name {
+            stmt.execute(params![norad_id as i64, display_name, true])?;
+        }
+        Ok(())
+    })
+}
+
+/// User-assigned names of every satellite that has any, keyed by NORAD ID.
+pub fn list_satellite_aliases(conn: &Connection) -> Result<HashMap<u64, SatelliteAliases>, DbError> {
+    let mut stmt = conn.prepare("SELECT norad_id, alias, display FROM satellite_aliases ORDER BY norad_id, alias")?;
+    let iter = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)? as u64, row.get::<_, String>(1)?, row.get::<_, bool>(2)?)))?;
+    let mut out: HashMap<u64, SatelliteAliases> = HashMap::new();
+    for (norad_id, alias, display) in iter.filter_map(Result::ok) {
+        out.entry(norad_id).or_default().push(alias, display);
+    }
+    Ok(out)
+}
+
+pub fn get_satellite_aliases(conn: &Connection, norad_id: u64) -> Result<SatelliteAliases, DbError> {
+    let mut stmt = conn.prepare("SELECT alias, display FROM satellite_aliases WHERE norad_id = ?1 ORDER BY alias")?;
+    let iter = stmt.query_map(params![norad_id as i64], |row| Ok((row.get::<_, String>(0)?, row.get::<_, bool>(1)?)))?;
+    let mut names = SatelliteAliases::default();
+    for (alias, display) in iter.filter_map(Result::ok) {
+        names.push(alias, display);
+    }
+    Ok(names)
+}
+
+/// Replaces the stored SATCAT with a fresh download.
+pub fn replace_satcat(conn: &Connection, entries: &[crate::collectors::satcat::SatcatEntry], fetched_at: &str) -> Result<usize, DbError> {
+    in_transaction(conn, |tx| {
+        tx.execute("DELETE FROM satcat", [])?;
+        let mut stmt = tx.prepare_cached(
+            "INSERT OR REPLACE INTO satcat (norad_id, object_id, object_type, owner, launch_date, decay_date, fetched_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        )?;
+        for e in entries {
+            stmt.execute(params![e.norad_id as i64, e.object_id, e.object_type, e.owner, e.launch_date, e.decay_date, fetched_at])?;
+        }
+        Ok(entries.len())
+    })
+}
+
+/// When the stored SATCAT was downloaded, if it ever was.
+pub fn satcat_fetched_at(conn: &Connection) -> Result<Option<String>, DbError> {
+    Ok(conn.query_row("SELECT MAX(fetched_at) FROM satcat", [], |row| row.get(0))?)
+}
+
+/// Stored SATCAT metadata keyed by NORAD ID.
+pub fn satcat_map(conn: &Connection) -> Result<HashMap<u64, crate::collectors::satcat::SatcatEntry>, DbError> {
+    let mut stmt = conn.prepare("SELECT norad_id, object_id, object_type, owner, launch_date, decay_date FROM satcat")?;
+    let iter = stmt.query_map([], |row| {
+        Ok(crate::collectors::satcat::SatcatEntry {
+            norad_id: row.get::<_, i64>(0)? as u64,
+            object_id: row.get(1)?,
+            object_type: row.get(2)?,
+            owner: row.get(3)?,
+            launch_date: row.get(4)?,
+            decay_date: row.get(5)?,
+        })
+    })?;
+    Ok(iter.filter_map(Result::ok).map(|e| (e.norad_id, e)).collect())
+}
+
+/// A user-defined element set for an object missing from public catalogs. Exactly
+/// one of `tle` (raw text) and `omm` (JSON) is set.
+#[derive(Debug, Clone)]
+pub struct CustomElements {
+    pub id: i64,
+    pub name: Option<String>,
+    pub tle: Option<String>,
+    pub omm: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+fn custom_elements_from_row(row: &rusqlite::Row) -> rusqlite::Result<CustomElements> {
+    Ok(CustomElements {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        tle: row.get(2)?,
+        omm: row.get(3)?,
+        created_at: row.get(4)?,
+        updated_at: row.get(5)?,
+    })
+}
+
+pub fn insert_custom_elements(conn: &Connection, name: Option<&str>, tle: Option<&str>, omm: Option<&str>, now: &str) -> Result<i64, DbError> {
+    execute_cached(
+        conn,
+        "INSERT INTO custom_elements (name, tle, omm, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?4)",
+        params![name, tle, omm, now],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn list_custom_elements(conn: &Connection) -> Result<Vec<CustomElements>, DbError> {
+    let mut stmt = conn.prepare("SELECT id, name, tle, omm, created_at, updated_at FROM custom_elements ORDER BY id")?;
+    let iter = stmt.query_map([], custom_elements_from_row)?;
+    Ok(iter.filter_map(Result::ok).collect())
+}
+
+pub fn get_custom_elements(conn: &Connection, id: i64) -> Result<CustomElements, DbError> {
+    let mut stmt = conn.prepare("SELECT id, name, tle, omm, created_at, updated_at FROM custom_elements WHERE id = ?1")?;
+    Ok(stmt.query_row(params![id], custom_elements_from_row)?)
+}
+
+/// Replaces the elements of a custom satellite; returns whether it existed.
+pub fn update_custom_elements(conn: &Connection, id: i64, name: Option<&str>, tle: Option<&str>, omm: Option<&str>, now: &str) -> Result<bool, DbError> {
+    let n = conn.execute(
+        "UPDATE custom_elements SET name = ?1, tle = ?2, omm = ?3, updated_at = ?4 WHERE id = ?5",
+        params![name, tle, omm, now, id],
+    )?;
+    Ok(n > 0)
+}
+
+/// Deletes a custom satellite; returns whether it existed.
+pub fn delete_custom_elements(conn: &Connection, id: i64) -> Result<bool, DbError> {
+    Ok(conn.execute("DELETE FROM custom_elements WHERE id = ?1", params![id])? > 0)
+}
+
+/// An object of the catalog as of the previous load.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatalogMember {
+    pub norad_id: u64,
+    pub name: Option<String>,
+    /// Element epoch, RFC 3339.
+    pub epoch: String,
+}
+
+pub fn list_catalog_members(conn: &Connection) -> Result<Vec<CatalogMember>, DbError> {
+    let mut stmt = conn.prepare("SELECT norad_id, name, epoch FROM catalog_members ORDER BY norad_id")?;
+    let iter = stmt.query_map([], |row| Ok(CatalogMember { norad_id: row.get::<_, i64>(0)? as u64, name: row.get(1)?, epoch: row.get(2)? }))?;
+    Ok(iter.filter_map(Result::ok).collect())
+}
+
+/// One entry of the catalog change feed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatalogChangeRow {
+    pub changed_at: String,
+    pub norad_id: u64,
+    /// `added`, `removed` or `updated`.
+    pub kind: String,
+    pub name: Option<String>,
+    pub previous_epoch: Option<String>,
+    pub epoch: Option<String>,
+}
+
+/// Appends `changes` to the feed and makes `members` the catalog the next load
+/// is compared against, in one transaction.
+pub fn record_catalog_changes(conn: &Connection, changes: &[CatalogChangeRow], members: &[CatalogMember]) -> Result<(), DbError> {
+    in_transaction(conn, |tx| {
+        let mut insert = tx.prepare_cached(
+            "INSERT INTO catalog_changes (changed_at, norad_id, kind, name, previous_epoch, epoch) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+        for c in changes {
+            insert.execute(params![c.changed_at, c.norad_id as i64, c.kind, c.name, c.previous_epoch, c.epoch])?;
+        }
+        tx.execute("DELETE FROM catalog_members", [])?;
+        let mut member = tx.prepare_cached("INSERT INTO catalog_members (norad_id, name, epoch) VALUES (?1, ?2, ?3)")?;
+        for m in members {
+            member.execute(params![m.norad_id as i64, m.name, m.epoch])?;
+        }
+        Ok(())
+    })
+}
+
+/// Changes recorded at or after `since`, or those of the latest load without
+/// it; optionally of one kind only.
+pub fn list_catalog_changes(conn: &Connection, since: Option<&str>, kind: Option<&str>) -> Result<Vec<CatalogChangeRow>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT changed_at, norad_id, kind, name, previous_epoch, epoch
+         FROM catalog_changes
+         WHERE changed_at >= COALESCE(?1, (SELECT MAX(changed_at) FROM catalog_changes))
+           AND (?2 IS NULL OR kind = ?2)
+         ORDER BY changed_at, kind, norad_id",
+    )?;
+    let iter = stmt.query_map(params![since, kind], |row| {
+        Ok(CatalogChangeRow {
+            changed_at: row.get(0)?,
+            norad_id: row.get::<_, i64>(1)? as u64,
+            kind: row.get(2)?,
+            name: row.get(3)?,
+            previous_epoch: row.get(4)?,
+            epoch: row.get(5)?,
+        })
+    })?;
+    Ok(iter.filter_map(Result::ok).collect())
+}
+
+/// A named area of interest; `geometry` is GeoJSON.
+#[derive(Debug, Clone)]
+pub struct Aoi {
+    pub id: i64,
+    pub name: String,
+    pub geometry: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+fn aoi_from_row(row: &rusqlite::Row) -> rusqlite::Result<Aoi> {
+    Ok(Aoi { id: row.get(0)?, name: row.get(1)?, geometry: row.get(2)?, created_at: row.get(3)?, updated_at: row.get(4)? })
+}
+
+pub fn insert_aoi(conn: &Connection, name: &str, geometry: &str, now: &str) -> Result<i64, DbError> {
+    execute_cached(conn, "INSERT INTO aois (name, geometry, created_at, updated_at) VALUES (?1, ?2, ?3, ?3)", params![name, geometry, now])?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn get_aoi(conn: &Connection, id: i64) -> Result<Aoi, DbError> {
+    let mut stmt = conn.prepare("SELECT id, name, geometry, created_at, updated_at FROM aois WHERE id = ?1")?;
+    Ok(stmt.query_row(params![id], aoi_from_row)?)
+}
+
+pub fn list_aois(conn: &Connection) -> Result<Vec<Aoi>, DbError> {
+    let mut stmt = conn.prepare("SELECT id, name, geometry, created_at, updated_at FROM aois ORDER BY id")?;
+    let iter = stmt.query_map([], aoi_from_row)?;
+    Ok(iter.filter_map(Result::ok).collect())
+}
+
+/// Replaces the name and geometry of an area; returns whether it existed.
+pub fn update_aoi(conn: &Connection, id: i64, name: &str, geometry: &str, now: &str) -> Result<bool, DbError> {
+    let n = conn.execute("UPDATE aois SET name = ?1, geometry = ?2, updated_at = ?3 WHERE id = ?4", params![name, geometry, now, id])?;
+    Ok(n > 0)
+}
+
+/// Deletes an area; returns whether it existed.
+pub fn delete_aoi(conn: &Connection, id: i64) -> Result<bool, DbError> {
+    Ok(conn.execute("DELETE FROM aois WHERE id = ?1", params![id])? > 0)
+}
+
+/// Start (RFC 3339) and JSON windows of the pass prediction cached under `key`.
+pub fn get_cached_passes(conn: &Connection, key: &str) -> Result<Option<(String, String)>, DbError> {
+    let mut stmt = conn.prepare("SELECT start, windows FROM pass_cache WHERE key = ?1")?;
+    let mut rows = stmt.query(params![key])?;
+    match rows.next()? {
+        Some(row) => Ok(Some((row.get(0)?, row.get(1)?))),
+        None => Ok(None),
+    }
+}
+
+/// Stores a pass prediction, replacing an older one for the same key.
+pub fn put_cached_passes(conn: &Connection, key: &str, norad_id: u64, tle_epoch: i64, start: &str, windows: &str, now: &str) -> Result<(), DbError> {
+    conn.execute(
+        "INSERT OR REPLACE INTO pass_cache (key, norad_id, tle_epoch, start, windows, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![key, norad_id as i64, tle_epoch, start, windows, now],
+    )?;
+    Ok(())
+}
+
+/// Deletes cached predictions whose element set (NORAD ID and epoch in Unix
+/// seconds) is not in `current`; returns how many.
+pub fn prune_pass_cache(conn: &Connection, current: &HashSet<(u64, i64)>) -> Result<usize, DbError> {
+    let mut stmt = conn.prepare("SELECT DISTINCT norad_id, tle_epoch FROM pass_cache")?;
+    let stored = stmt
+        .query_map([], |row| Ok((row.get::<_, i64>(0)? as u64, row.get::<_, i64>(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+    in_transaction(conn, |tx| {
+        let mut delete = tx.prepare_cached("DELETE FROM pass_cache WHERE norad_id = ?1 AND tle_epoch = ?2")?;
+        let mut removed = 0;
+        for (norad_id, epoch) in stored.iter().filter(|s| !current.contains(s)) {
+            removed += delete.execute(params![*norad_id as i64, epoch])?;
+        }
+        Ok(removed)
+    })
+}
+
+/// Deletes every station (with what hangs off it), tag, dynamic group, alias,
+/// protected asset and custom element set, ahead of restoring a configuration
+/// bundle. Recorded data (snapshots, TLE history, observations, conjunctions) is kept.
+pub fn clear_configuration(conn: &Connection) -> Result<(), DbError> {
+    in_transaction(conn, |tx| {
+        for station in list_stations(tx)? {
+            delete_station(tx, station.id)?;
+        }
+        tx.execute_batch(
+            "DELETE FROM satellite_tags;
+             DELETE FROM dynamic_groups;
+             DELETE FROM satellite_aliases;
+             DELETE FROM protected_assets;
+             DELETE FROM custom_elements;",
+        )?;
+        Ok(())
+    })
+}
+
+/// One version of a manually set element set for a catalogued satellite. The
+/// newest version that has not been reverted replaces the fetched elements.
+#[derive(Debug, Clone)]
+pub struct ElementOverride {
+    pub id: i64,
+    pub norad_id: u64,
+    /// How the elements were given: `tle`, `omm`, `state` (converted state vector)
+    /// or `pin` (an archived TLE).
+    pub source: String,
+    pub tle: Option<String>,
+    pub omm: Option<String>,
+    pub reason: Option<String>,
+    pub created_at: String,
+    pub reverted_at: Option<String>,
+}
+
+fn element_override_from_row(row: &rusqlite::Row) -> rusqlite::Result<ElementOverride> {
+    Ok(ElementOverride {
+        id: row.get(0)?,
+        norad_id: row.get::<_, i64>(1)? as u64,
+        source: row.get(2)?,
+        tle: row.get(3)?,
+        omm: row.get(4)?,
+        reason: row.get(5)?,
+        created_at: row.get(6)?,
+        reverted_at: row.get(7)?,
+    })
+}
+
+pub fn insert_element_override(conn: &Connection, o: &ElementOverride) -> Result<i64, DbError> {
+    execute_cached(
+        conn,
+        "INSERT INTO element_overrides (norad_id, source, tle, omm, reason, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![o.norad_id as i64, o.source, o.tle, o.omm, o.reason, o.created_at],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Every override version of a satellite, newest first.
+pub fn list_element_overrides(conn: &Connection, norad_id: u64) -> Result<Vec<ElementOverride>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, norad_id, source, tle, omm, reason, created_at, reverted_at FROM element_overrides
+         WHERE norad_id = ?1 ORDER BY id DESC",
+    )?;
+    let iter = stmt.query_map(params![norad_id as i64], element_override_from_row)?;
+    Ok(iter.filter_map(Result::ok).collect())
+}
+
+/// The override in effect for each satellite that has one.
+pub fn active_element_overrides(conn: &Connection) -> Result<Vec<ElementOverride>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, norad_id, source, tle, omm, reason, created_at, reverted_at FROM element_overrides
+         WHERE id IN (SELECT MAX(id) FROM element_overrides WHERE reverted_at IS NULL GROUP BY norad_id)
+         ORDER BY norad_id",
+    )?;
+    let iter = stmt.query_map([], element_override_from_row)?;
+    Ok(iter.filter_map(Result::ok).collect())
+}
+
+/// Marks the override in effect for a satellite as reverted, so the previous
+/// version (or the fetched elements) applies again; returns its ID.
+pub fn revert_element_override(conn: &Connection, norad_id: u64, now: &str) -> Result<Option<i64>, DbError> {
+    let tx = conn.unchecked_transaction()?;
+    let active: Option<i64> = tx.query_row(
+        "SELECT MAX(id) FROM element_overrides WHERE norad_id = ?1 AND reverted_at IS NULL",
+        params![norad_id as i64],
+        |row| row.get(0),
+    )?;
+    if let Some(id) = active {
+        tx.execute("UPDATE element_overrides SET reverted_at = ?1 WHERE id = ?2", params![now, id])?;
+    }
+    tx.commit()?;
+    Ok(active)
+}
+
+/// One mutating API request. `payload` and `diff` are JSON text.
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub at: String,
+    /// `role:key fingerprint`, or `anonymous` without access control.
+    pub actor: String,
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+    pub payload: Option<String>,
+    pub diff: Option<String>,
+}
+
+pub fn insert_audit_entry(conn: &Connection, e: &AuditEntry) -> Result<(), DbError> {
+    execute_cached(
+        conn,
+        "INSERT INTO audit_log (at, actor, method, path, status, payload, diff) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![e.at, e.actor, e.method, e.path, e.status, e.payload, e.diff],
+    )?;
+    Ok(())
+}
+
+/// Up to `limit` audit entries with a row ID above `after_id`, oldest first.
+pub fn list_audit_log_page(conn: &Connection, after_id: i64, limit: usize) -> Result<Vec<AuditEntry>, DbError> {
+    let mut stmt = conn.prepare("SELECT id, at, actor, method, path, status, payload, diff FROM audit_log WHERE id > ?1 ORDER BY id LIMIT ?2")?;
+    let iter = stmt.query_map(params![after_id, limit as i64], |row| {
+        Ok(AuditEntry {
+            id: row.get(0)?,
+            at: row.get(1)?,
+            actor: row.get(2)?,
+            method: row.get(3)?,
+            path: row.get(4)?,
+            status: row.get(5)?,
+            payload: row.get(6)?,
+            diff: row.get(7)?,
+        })
+    })?;
+    Ok(iter.filter_map(Result::ok).collect())
+}
+
+/// A token issued to a tracking client in the field; only its SHA-256 is stored.
+#[derive(Debug, Clone)]
+pub struct DeviceToken {
+    pub id: i64,
+    pub station_id: i64,
+    pub name: Option<String>,
+    pub created_at: String,
+    pub revoked_at: Option<String>,
+}
+
+pub fn insert_device_token(conn: &Connection, station_id: i64, name: Option<&str>, token_sha256: &str, created_at: &str) -> Result<i64, DbError> {
+    execute_cached(
+        conn,
+        "INSERT INTO device_tokens (station_id, name, token_sha256, created_at) VALUES (?1, ?2, ?3, ?4)",
+        params![station_id, name, token_sha256, created_at],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn list_device_tokens(conn: &Connection, station_id: i64) -> Result<Vec<DeviceToken>, DbError> {
+    let mut stmt = conn.prepare("SELECT id, station_id, name, created_at, revoked_at FROM device_tokens WHERE station_id = ?1 ORDER BY id")?;
+    let iter = stmt.query_map(params![station_id], |row| {
+        Ok(DeviceToken {
+            id: row.get(0)?,
+            station_id: row.get(1)?,
+            name: row.get(2)?,
+            created_at: row.get(3)?,
+            revoked_at: row.get(4)?,
+        })
+    })?;
+    Ok(iter.filter_map(Result::ok).collect())
+}
+
+/// Revokes a token of a station; returns whether an active one was found.
+pub fn revoke_device_token(conn: &Connection, station_id: i64, id: i64, now: &str) -> Result<bool, DbError> {
+    let n = conn.execute(
+        "UPDATE device_tokens SET revoked_at = ?1 WHERE id = ?2 AND station_id = ?3 AND revoked_at IS NULL",
+        params![now, id, station_id],
+    )?;
+    Ok(n > 0)
+}
+
+/// Station an unrevoked token is bound to.
+pub fn device_token_station(conn: &Connection, token_sha256: &str) -> Result<Option<i64>, DbError> {
+    let mut stmt = conn.prepare_cached("SELECT station_id FROM device_tokens WHERE token_sha256 = ?1 AND revoked_at IS NULL")?;
+    let mut rows = stmt.query(params![token_sha256])?;
+    Ok(match rows.next()? {
+        Some(row) => Some(row.get(0)?),
+        None => None,
+    })
+}
+
+/// Antenna pointing reported by a station's tracking client.
+#[derive(Debug, Clone)]
+pub struct TelemetrySample {
+    pub id: i64,
+    pub norad_id: Option<u64>,
+    pub time: String,
+    pub az_deg: f64,
+    pub el_deg: f64,
+    pub signal_dbm: Option<f64>,
+    pub received_at: String,
+}
+
+/// Stores telemetry samples of a station in one transaction; returns the count.
+pub fn insert_telemetry(conn: &Connection, station_id: i64, samples: &[TelemetrySample]) -> Result<usize, DbError> {
+    let tx = conn.unchecked_transaction()?;
+    {
+        let mut stmt = tx.prepare_cached(
+            "INSERT INTO station_telemetry (station_id, norad_id, time, az_deg, el_deg, signal_dbm, received_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        )?;
+        for s in samples {
+            stmt.execute(params![station_id, s.norad_id.map(|n| n as i64), s.time, s.az_deg, s.el_deg, s.signal_dbm, s.received_at])?;
+        }
+    }
+    tx.commit()?;
+    Ok(samples.len())
+}
+
+/// Up to `limit` telemetry samples of a station with a row ID above `after_id`,
+/// oldest first, optionally only for `norad_id`.
+pub fn list_telemetry_page(conn: &Connection, station_id: i64, norad_id: Option<u64>, after_id: i64, limit: usize) -> Result<Vec<TelemetrySample>, DbError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, norad_id, time, az_deg, el_deg, signal_dbm, received_at FROM station_telemetry
+         WHERE station_id = ?1 AND (?2 IS NULL OR norad_id = ?2) AND id > ?3 ORDER BY id LIMIT ?4",
+    )?;
+    let iter = stmt.query_map(params![station_id, norad_id.map(|n| n as i64), after_id, limit as i64], |row| {
+        Ok(TelemetrySample {
+            id: row.get(0)?,
+            norad_id: row.get::<_, Option<i64>>(1)?.map(|n| n as u64),
+            time: row.get(2)?,
+            az_deg: row.get(3)?,
+            el_deg: row.get(4)?,
+            signal_dbm: row.get(5)?,
+            received_at: row.get(6)?,
+        })
+    })?;
+    Ok(iter.filter_map(Result::ok).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn snapshots(n: usize) -> Vec<NewSnapshot> {
+        (0..n)
+            .map(|i| NewSnapshot {
+                norad_id: 25544 + (i % 50) as u64,
+                timestamp: format!("2024-01-01T00:00:{:02}Z", i % 60),
+                position: [6778.0, i as f64, 0.0],
+                velocity: [0.0, 7.67, 0.0],
+            })
+            .collect()
+    }
+
+    fn seed_catalog(conn: &Connection) {
+        let ids: Vec<(u64, Option<&str>)> = (0..50).map(|i| (25544 + i, None)).collect();
+        upsert_satellites(conn, &ids).unwrap();
+    }
+
+    #[test]
+    fn batched_snapshots_page_in_insertion_order() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        seed_catalog(&conn);
+        assert_eq!(insert_snapshots(&conn, &snapshots(120)).unwrap(), 120);
+        let first = list_snapshots_page(&conn, None, 0, 100).unwrap();
+        let rest = list_snapshots_page(&conn, None, first.last().unwrap().id, 100).unwrap();
+        assert_eq!((first.len(), rest.len()), (100, 20));
+        assert_eq!(rest[0].position[1], 100.0);
+        assert_eq!(list_snapshots_page(&conn, Some(25544), 0, 1000).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn rollups_count_snapshots_per_bucket() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        seed_catalog(&conn);
+        let rows: Vec<NewSnapshot> = ["2024-01-01T00:00:10Z", "2024-01-01T00:00:50+00:00", "2024-01-01T00:01:30.5Z"]
+            .iter()
+            .map(|t| NewSnapshot { norad_id: 25544, timestamp: t.to_string(), position: [7000.0, 0.0, 0.0], velocity: [0.0, 7.5, 0.0] })
+            .collect();
+        insert_snapshots(&conn, &rows).unwrap();
+        let (start, end) = (1_704_067_200, 1_704_067_200 + 3600);
+        let check = |conn: &Connection| {
+            let minutes = list_position_history(conn, 25544, start, end, Some(60), 10).unwrap();
+            assert_eq!(minutes.iter().map(|p| p.samples).collect::<Vec<_>>(), vec![2, 1]);
+            assert_eq!(minutes[0].timestamp, "2024-01-01T00:00:10Z");
+            assert_eq!(list_position_history(conn, 25544, start, end, Some(3600), 10).unwrap()[0].samples, 3);
+            assert_eq!(list_position_history(conn, 25544, start + 30, end, None, 10).unwrap().len(), 2);
+            assert_eq!(history_density(conn, 25544, start, end).unwrap(), (3, 3));
+        };
+        check(&conn);
+        rebuild_rollups(&conn).unwrap();
+        check(&conn);
+    }
+
+    #[test]
+    fn dropped_connections_are_reused() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = Pool::new(dir.path().join("pool.sqlite"));
+        let conn = pool.get().unwrap();
+        conn.execute_batch("CREATE TEMP TABLE marker (x INTEGER)").unwrap();
+        drop(conn);
+        // Temporary tables live only as long as the connection that made them
+        let conn = pool.get().unwrap();
+        assert!(conn.prepare("SELECT x FROM temp.marker").is_ok());
+        assert!(conn.prepare("SELECT norad_id FROM satellites").is_ok());
+        // A second connection open at the same time is a fresh one
+        let other = pool.get().unwrap();
+        assert!(other.prepare("SELECT x FROM temp.marker").is_err());
+    }
+
+    #[test]
+    fn station_edits_bump_its_version() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        let id = insert_station(&conn, Some("roof"), 52.0, 4.0, 10.0, None).unwrap();
+        assert_eq!(station_version(&conn, id).unwrap(), Some(0));
+        update_station(&conn, id, Some("roof"), 52.1, 4.0, 10.0).unwrap();
+        set_station_exclusions(&conn, id, &[(350.0, 10.0)]).unwrap();
+        upsert_station_horizon(&conn, id, &[1.0; 360], "2024-01-01T00:00:00Z").unwrap();
+        assert_eq!(station_version(&conn, id).unwrap(), Some(3));
+        // Favorites do not change predictions
+        set_station_favorites(&conn, id, &[25544]).unwrap();
+        assert_eq!(station_version(&conn, id).unwrap(), Some(3));
+        assert_eq!(station_version(&conn, id + 1).unwrap(), None);
+    }
+
+    #[test]
+    fn rescreening_replaces_upcoming_conjunctions() {
+        use crate::predictors::conjunctions::ConjunctionEvent;
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        let event = |secondary: u64, tca: &str| ConjunctionEvent {
+            asset_norad_id: 25544,
+            secondary_norad_id: secondary,
+            secondary_name: None,
+            tca: tca.parse().unwrap(),
+            miss_distance_km: 1.0,
+            relative_speed_km_s: 10.0,
+        };
+        let first = [event(1, "2024-01-01T06:00:00Z"), event(2, "2024-01-02T06:00:00Z")];
+        replace_conjunctions(&conn, 25544, "2024-01-01T00:00:00+00:00", &first, "2024-01-01T00:00:00+00:00").unwrap();
+        let second = [event(2, "2024-01-02T06:00:00Z"), event(3, "2024-01-03T06:00:00Z")];
+        replace_conjunctions(&conn, 25544, "2024-01-01T12:00:00+00:00", &second, "2024-01-01T12:00:00+00:00").unwrap();
+        replace_conjunctions(&conn, 25544, "2024-01-01T12:00:00+00:00", &second, "2024-01-01T12:00:00+00:00").unwrap();
+        let stored: Vec<u64> = list_conjunctions(&conn, Some(25544), None, 10).unwrap().iter().map(|c| c.secondary_norad_id).collect();
+        assert_eq!(stored, vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn nearest_tle_is_picked_per_satellite() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        for (norad_id, epoch) in [(25544, "2023-01-10T00:00:00.000000Z"), (25544, "2023-01-14T12:00:00.000000Z"), (25544, "2023-01-20T00:00:00.000000Z"), (43013, "2022-12-01T00:00:00.000000Z")] {
+            conn.execute(
+                "INSERT INTO tle_history (norad_id, epoch, line1, line2, fetched_at) VALUES (?1, ?2, ?2, '', ?2)",
+                params![norad_id, epoch],
+            )
+            .unwrap();
+        }
+        let nearest = list_tle_nearest(&conn, "2023-01-15T00:00:00.000000Z", None).unwrap();
+        let lines: Vec<(u64, &str)> = nearest.iter().map(|r| (r.norad_id, r.line1.as_str())).collect();
+        assert_eq!(lines, vec![(25544, "2023-01-14T12:00:00.000000Z"), (43013, "2022-12-01T00:00:00.000000Z")]);
+        assert_eq!(list_tle_nearest(&conn, "2023-01-19T00:00:00Z", Some(25544)).unwrap()[0].line1, "2023-01-20T00:00:00.000000Z");
+    }
+
+    /// Insert throughput of the old autocommit path against batched inserts on an
+    /// on-disk WAL database. Run with `cargo test -- --ignored --nocapture snapshot_insert_throughput`.
+    #[test]
+    #[ignore]
+    fn snapshot_insert_throughput() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = Connection::open(dir.path().join("bench.sqlite")).unwrap();
+        init_schema(&conn).unwrap();
+        seed_catalog(&conn);
+        let rows = snapshots(2_000);
+
+        let start = Instant::now();
+        for s in &rows {
+            conn.execute(
+                INSERT_SNAPSHOT,
+                params![s.norad_id as i64, s.timestamp, s.position[0], s.position[1], s.position[2], s.velocity[0], s.velocity[1], s.velocity[2], None::<i64>],
+            )
+            .unwrap();
+        }
+        let row_by_row = start.elapsed();
+
+        let start = Instant::now();
+        for chunk in rows.chunks(500) {
+            insert_snapshots(&conn, chunk).unwrap();
+        }
+        let batched = start.elapsed();
+
+        let rate = |d: std::time::Duration| rows.len() as f64 / d.as_secs_f64();
+        println!("row-by-row: {:.0} rows/s, batched: {:.0} rows/s", rate(row_by_row), rate(batched));
+        assert!(batched < row_by_row);
+    }
+}
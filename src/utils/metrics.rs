@@ -0,0 +1,92 @@
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::analyzers::snapshot_anomalies::AnomalyKind;
+
+/// Where a failed propagation was requested.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropagationSource {
+    /// `GET /satellites/positions`.
+    Positions,
+    /// The `/ws/positions` stream.
+    Stream,
+}
+
+impl PropagationSource {
+    const ALL: [PropagationSource; 2] = [PropagationSource::Positions, PropagationSource::Stream];
+
+    fn label(self) -> &'static str {
+        match self {
+            PropagationSource::Positions => "positions",
+            PropagationSource::Stream => "stream",
+        }
+    }
+}
+
+/// Where a pass prediction came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PassCacheResult {
+    Memory,
+    Database,
+    /// Predicted anew.
+    Miss,
+}
+
+impl PassCacheResult {
+    const ALL: [PassCacheResult; 3] = [PassCacheResult::Memory, PassCacheResult::Database, PassCacheResult::Miss];
+
+    fn label(self) -> &'static str {
+        match self {
+            PassCacheResult::Memory => "memory",
+            PassCacheResult::Database => "db",
+            PassCacheResult::Miss => "miss",
+        }
+    }
+}
+
+static PROPAGATION_ERRORS: [AtomicU64; 2] = [AtomicU64::new(0), AtomicU64::new(0)];
+
+static PASS_CACHE_LOOKUPS: [AtomicU64; 3] = [AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0)];
+
+static SNAPSHOT_ANOMALIES: [AtomicU64; 3] = [AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0)];
+
+/// Counts one newly flagged snapshot anomaly.
+pub fn record_snapshot_anomaly(kind: AnomalyKind) {
+    SNAPSHOT_ANOMALIES[kind as usize].fetch_add(1, Ordering::Relaxed);
+}
+
+/// Counts one pass cache lookup.
+pub fn record_pass_cache(result: PassCacheResult) {
+    PASS_CACHE_LOOKUPS[result as usize].fetch_add(1, Ordering::Relaxed);
+}
+
+/// Counts satellites left out of a response because SGP4 rejected them.
+pub fn record_propagation_errors(source: PropagationSource, count: usize) {
+    if count > 0 {
+        PROPAGATION_ERRORS[source as usize].fetch_add(count as u64, Ordering::Relaxed);
+    }
+}
+
+/// The counters in the Prometheus text exposition format.
+pub fn render() -> String {
+    let mut out = String::new();
+    out.push_str("# HELP stfcm_propagation_errors_total Satellites dropped from responses because propagation failed.\n");
+    out.push_str("# TYPE stfcm_propagation_errors_total counter\n");
+    for source in PropagationSource::ALL {
+        let count = PROPAGATION_ERRORS[source as usize].load(Ordering::Relaxed);
+        let _ = writeln!(out, "stfcm_propagation_errors_total{{source=\"{}\"}} {}", source.label(), count);
+    }
+    out.push_str("# HELP stfcm_pass_cache_lookups_total Pass predictions served from the cache or predicted anew.\n");
+    out.push_str("# TYPE stfcm_pass_cache_lookups_total counter\n");
+    for result in PassCacheResult::ALL {
+        let count = PASS_CACHE_LOOKUPS[result as usize].load(Ordering::Relaxed);
+        let _ = writeln!(out, "stfcm_pass_cache_lookups_total{{result=\"{}\"}} {}", result.label(), count);
+    }
+    out.push_str("# HELP stfcm_snapshot_anomalies_total Recorded snapshots flagged as implausible since start.\n");
+    out.push_str("# TYPE stfcm_snapshot_anomalies_total counter\n");
+    for kind in AnomalyKind::ALL {
+        let count = SNAPSHOT_ANOMALIES[kind as usize].load(Ordering::Relaxed);
+        let _ = writeln!(out, "stfcm_snapshot_anomalies_total{{kind=\"{}\"}} {}", kind.as_str(), count);
+    }
+    out
+}
@@ -1,4 +1,11 @@
 pub mod logging;
 
 // Common helpers will be added here as the project grows.
-pub mod db;
\ No newline at end of file
+pub mod cache;
+pub mod daemon;
+pub mod db;
+pub mod metrics;
+pub mod notify;
+pub mod parquet;
+pub mod sd_notify;
+pub mod tsdb;
@@ -0,0 +1,33 @@
+use serde::Serialize;
+use tracing::{info, warn};
+
+/// Environment variable holding an optional webhook URL that receives alerts as JSON POSTs.
+const WEBHOOK_ENV: &str = "STFCM_ALERT_WEBHOOK";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Alert {
+    /// Short machine-readable category, e.g. `conjunction`.
+    pub kind: &'static str,
+    pub message: String,
+    pub payload: serde_json::Value,
+}
+
+/// Whether alerts are forwarded anywhere beyond the log.
+pub fn webhook_configured() -> bool {
+    std::env::var(WEBHOOK_ENV).is_ok_and(|url| !url.is_empty())
+}
+
+/// Delivers an alert: always logged, and forwarded to the webhook when one is configured.
+/// Delivery failures are logged and otherwise ignored.
+pub async fn send(alert: &Alert) {
+    warn!(kind = alert.kind, "{}", alert.message);
+
+    let Ok(url) = std::env::var(WEBHOOK_ENV) else {
+        return;
+    };
+    match reqwest::Client::new().post(&url).json(alert).send().await {
+        Ok(resp) if resp.status().is_success() => info!(kind = alert.kind, "Alert delivered to webhook"),
+        Ok(resp) => warn!(status = ?resp.status(), "Webhook rejected alert"),
+        Err(e) => warn!(error = %e, "Failed to deliver alert to webhook"),
+    }
+}
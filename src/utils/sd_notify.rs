@@ -0,0 +1,101 @@
+#[cfg(target_os = "linux")]
+use std::io;
+#[cfg(target_os = "linux")]
+use std::os::unix::net::UnixDatagram;
+use std::time::Duration;
+
+#[cfg(target_os = "linux")]
+use tracing::debug;
+
+/// Sends a state string (`READY=1`, `WATCHDOG=1`, …) to systemd. A no-op
+/// returning `false` when not started by systemd with `Type=notify`.
+#[cfg(target_os = "linux")]
+pub fn notify(state: &str) -> bool {
+    let Ok(socket) = std::env::var("NOTIFY_SOCKET") else {
+        return false;
+    };
+    match send(&socket, state) {
+        Ok(()) => true,
+        Err(e) => {
+            debug!(error = %e, "sd_notify failed");
+            false
+        }
+    }
+}
+
+/// There is no systemd outside Linux.
+#[cfg(not(target_os = "linux"))]
+pub fn notify(_state: &str) -> bool {
+    false
+}
+
+#[cfg(target_os = "linux")]
+fn send(socket: &str, state: &str) -> io::Result<()> {
+    let sock = UnixDatagram::unbound()?;
+    match socket.strip_prefix('@') {
+        // Abstract namespace socket
+        Some(name) => {
+            use std::os::linux::net::SocketAddrExt;
+            let addr = std::os::unix::net::SocketAddr::from_abstract_name(name.as_bytes())?;
+            sock.send_to_addr(state.as_bytes(), &addr)?;
+        }
+        None => {
+            sock.send_to(state.as_bytes(), socket)?;
+        }
+    }
+    Ok(())
+}
+
+/// The listener is bound; with `STFCM_WAIT_FOR_CATALOG=1`, the elements are
+/// loaded as well.
+pub fn ready() {
+    notify("READY=1");
+}
+
+/// A reload (SIGHUP) has started; `READY=1` follows once serving again.
+#[cfg(target_os = "linux")]
+pub fn reloading() {
+    notify(&format!("RELOADING=1\nMONOTONIC_USEC={}", monotonic_usec()));
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn reloading() {}
+
+pub fn stopping() {
+    notify("STOPPING=1");
+}
+
+/// Watchdog timeout configured for this process (`WatchdogSec=`), if any.
+pub fn watchdog_interval() -> Option<Duration> {
+    let usec: u64 = std::env::var("WATCHDOG_USEC").ok()?.parse().ok()?;
+    if let Some(pid) = std::env::var("WATCHDOG_PID").ok().and_then(|p| p.parse::<u32>().ok()) {
+        if pid != std::process::id() {
+            return None;
+        }
+    }
+    (usec > 0).then(|| Duration::from_micros(usec))
+}
+
+#[cfg(target_os = "linux")]
+fn monotonic_usec() -> u64 {
+    let mut ts = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    // SAFETY: `ts` is a valid, writable timespec.
+    unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut ts) };
+    ts.tv_sec as u64 * 1_000_000 + ts.tv_nsec as u64 / 1_000
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sends_datagram_to_path_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notify.sock");
+        let server = UnixDatagram::bind(&path).unwrap();
+        send(path.to_str().unwrap(), "READY=1").unwrap();
+        let mut buf = [0u8; 64];
+        let n = server.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"READY=1");
+    }
+}
@@ -0,0 +1,288 @@
+use std::fmt::Write as _;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tokio_postgres::types::ToSql;
+use tracing::{info, warn};
+
+use crate::utils::db::NewSnapshot;
+
+/// Environment variable naming an optional time-series database to mirror
+/// snapshots and passes to: an InfluxDB write URL (`http(s)://…/api/v2/write?org=…&bucket=…`)
+/// or a PostgreSQL/TimescaleDB connection string (`postgres://…`).
+const URL_ENV: &str = "STFCM_TSDB_URL";
+/// API token sent as `Authorization: Token …` to InfluxDB.
+const TOKEN_ENV: &str = "STFCM_TSDB_TOKEN";
+/// Limits of one InfluxDB write, so an unreachable server cannot hold up mirroring.
+const INFLUX_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const INFLUX_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+const TIMESCALE_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS satellite_positions (
+        time TIMESTAMPTZ NOT NULL,
+        norad_id BIGINT NOT NULL,
+        x_km DOUBLE PRECISION NOT NULL,
+        y_km DOUBLE PRECISION NOT NULL,
+        z_km DOUBLE PRECISION NOT NULL,
+        vx_km_s DOUBLE PRECISION NOT NULL,
+        vy_km_s DOUBLE PRECISION NOT NULL,
+        vz_km_s DOUBLE PRECISION NOT NULL,
+        UNIQUE (norad_id, time)
+    );
+    SELECT create_hypertable('satellite_positions', 'time', if_not_exists => TRUE);
+    CREATE TABLE IF NOT EXISTS satellite_passes (
+        start_time TIMESTAMPTZ NOT NULL,
+        norad_id BIGINT NOT NULL,
+        station_id BIGINT NOT NULL,
+        tca TIMESTAMPTZ NOT NULL,
+        end_time TIMESTAMPTZ NOT NULL,
+        max_elevation_deg DOUBLE PRECISION NOT NULL,
+        duration_s BIGINT NOT NULL,
+        UNIQUE (norad_id, station_id, start_time)
+    );
+    SELECT create_hypertable('satellite_passes', 'start_time', if_not_exists => TRUE);
+"#;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SinkError {
+    #[error("http error: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("influxdb rejected write: {0}")]
+    Rejected(reqwest::StatusCode),
+    #[error("postgres error: {0}")]
+    Postgres(#[from] tokio_postgres::Error),
+}
+
+/// A predicted pass of a satellite over a stored station.
+#[derive(Debug, Clone)]
+pub struct PassEvent {
+    pub norad_id: u64,
+    pub station_id: i64,
+    pub start: DateTime<Utc>,
+    pub tca: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub max_elevation_deg: f64,
+}
+
+/// Time-series database that mirrors what is written to SQLite, so dashboards
+/// (e.g. Grafana) can chart it directly.
+pub enum TsdbSink {
+    /// InfluxDB (v2 write API, or v1 `/write?db=…`) fed with line protocol.
+    Influx { client: reqwest::Client, url: String, token: Option<String> },
+    /// TimescaleDB hypertables `satellite_positions` and `satellite_passes`. A
+    /// closed connection is dropped and opened again by the next write.
+    Timescale { url: String, client: tokio::sync::Mutex<Option<tokio_postgres::Client>> },
+}
+
+impl TsdbSink {
+    /// Connects the sink named by `STFCM_TSDB_URL`; `None` when unset or when the
+    /// connection fails (logged).
+    pub async fn from_env() -> Option<TsdbSink> {
+        let url = std::env::var(URL_ENV).ok().filter(|u| !u.is_empty())?;
+        let sink = if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+            match Self::connect_timescale(&url).await {
+                Ok(client) => TsdbSink::Timescale { url, client: tokio::sync::Mutex::new(Some(client)) },
+                Err(e) => {
+                    warn!(error = %e, "Failed to connect to TimescaleDB, mirroring disabled");
+                    return None;
+                }
+            }
+        } else {
+            let client = match reqwest::Client::builder().connect_timeout(INFLUX_CONNECT_TIMEOUT).timeout(INFLUX_REQUEST_TIMEOUT).build() {
+                Ok(client) => client,
+                Err(e) => {
+                    warn!(error = %e, "Failed to set up InfluxDB client, mirroring disabled");
+                    return None;
+                }
+            };
+            TsdbSink::Influx { client, url, token: std::env::var(TOKEN_ENV).ok() }
+        };
+        info!(kind = sink.kind(), "Mirroring snapshots and passes to time-series database");
+        Some(sink)
+    }
+
+    async fn connect_timescale(url: &str) -> Result<tokio_postgres::Client, SinkError> {
+        let (client, connection) = tokio_postgres::connect(url, tokio_postgres::NoTls).await?;
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                warn!(error = %e, "TimescaleDB connection closed");
+            }
+        });
+        client.batch_execute(TIMESCALE_SCHEMA).await?;
+        Ok(client)
+    }
+
+    /// Runs one statement on TimescaleDB. Without an open connection one is made
+    /// first; when the connection turns out closed, the statement is retried once
+    /// on a new one.
+    async fn timescale_execute(&self, sql: &str, params: &[&(dyn ToSql + Sync)]) -> Result<(), SinkError> {
+        let TsdbSink::Timescale { url, client } = self else {
+            return Ok(());
+        };
+        let mut client = client.lock().await;
+        for retry in [false, true] {
+            let conn = match client.as_ref().filter(|c| !c.is_closed()) {
+                Some(conn) => conn,
+                None => {
+                    if !retry {
+                        info!("Reconnecting to TimescaleDB");
+                    }
+                    client.insert(Self::connect_timescale(url).await?)
+                }
+            };
+            match conn.execute(sql, params).await {
+                Ok(_) => return Ok(()),
+                Err(e) if !retry && conn.is_closed() => {
+                    warn!(error = %e, "TimescaleDB connection lost, reconnecting");
+                    *client = None;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+        unreachable!("the retry returns")
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            TsdbSink::Influx { .. } => "influxdb",
+            TsdbSink::Timescale { .. } => "timescaledb",
+        }
+    }
+
+    /// Mirrors snapshots; ones whose timestamp is not RFC 3339 are skipped.
+    pub async fn write_snapshots(&self, snapshots: &[NewSnapshot]) -> Result<(), SinkError> {
+        let rows: Vec<(DateTime<Utc>, &NewSnapshot)> = snapshots
+            .iter()
+            .filter_map(|s| Some((DateTime::parse_from_rfc3339(&s.timestamp).ok()?.with_timezone(&Utc), s)))
+            .collect();
+        if rows.is_empty() {
+            return Ok(());
+        }
+        match self {
+            TsdbSink::Influx { .. } => self.write_lines(snapshot_lines(&rows)).await,
+            TsdbSink::Timescale { .. } => {
+                let column = |f: fn(&NewSnapshot) -> f64| rows.iter().map(|(_, s)| f(s)).collect::<Vec<f64>>();
+                let times: Vec<DateTime<Utc>> = rows.iter().map(|(t, _)| *t).collect();
+                let ids: Vec<i64> = rows.iter().map(|(_, s)| s.norad_id as i64).collect();
+                self.timescale_execute(
+                    "INSERT INTO satellite_positions (time, norad_id, x_km, y_km, z_km, vx_km_s, vy_km_s, vz_km_s)
+                     SELECT * FROM UNNEST($1::timestamptz[], $2::bigint[], $3::float8[], $4::float8[], $5::float8[], $6::float8[], $7::float8[], $8::float8[])
+                     ON CONFLICT DO NOTHING",
+                    &[
+                        &times,
+                        &ids,
+                        &column(|s| s.position[0]),
+                        &column(|s| s.position[1]),
+                        &column(|s| s.position[2]),
+                        &column(|s| s.velocity[0]),
+                        &column(|s| s.velocity[1]),
+                        &column(|s| s.velocity[2]),
+                    ],
+                )
+                .await
+            }
+        }
+    }
+
+    /// Mirrors pass events; re-sending a pass overwrites (InfluxDB) or is ignored (TimescaleDB).
+    pub async fn write_passes(&self, passes: &[PassEvent]) -> Result<(), SinkError> {
+        if passes.is_empty() {
+            return Ok(());
+        }
+        match self {
+            TsdbSink::Influx { .. } => self.write_lines(pass_lines(passes)).await,
+            TsdbSink::Timescale { .. } => {
+                let starts: Vec<DateTime<Utc>> = passes.iter().map(|p| p.start).collect();
+                let ids: Vec<i64> = passes.iter().map(|p| p.norad_id as i64).collect();
+                let stations: Vec<i64> = passes.iter().map(|p| p.station_id).collect();
+                let tcas: Vec<DateTime<Utc>> = passes.iter().map(|p| p.tca).collect();
+                let ends: Vec<DateTime<Utc>> = passes.iter().map(|p| p.end).collect();
+                let max_els: Vec<f64> = passes.iter().map(|p| p.max_elevation_deg).collect();
+                let durations: Vec<i64> = passes.iter().map(|p| (p.end - p.start).num_seconds()).collect();
+                self.timescale_execute(
+                    "INSERT INTO satellite_passes (start_time, norad_id, station_id, tca, end_time, max_elevation_deg, duration_s)
+                     SELECT * FROM UNNEST($1::timestamptz[], $2::bigint[], $3::bigint[], $4::timestamptz[], $5::timestamptz[], $6::float8[], $7::bigint[])
+                     ON CONFLICT DO NOTHING",
+                    &[&starts, &ids, &stations, &tcas, &ends, &max_els, &durations],
+                )
+                .await
+            }
+        }
+    }
+
+    async fn write_lines(&self, body: String) -> Result<(), SinkError> {
+        let TsdbSink::Influx { client, url, token } = self else {
+            return Ok(());
+        };
+        let mut request = client.post(url).query(&[("precision", "ms")]).body(body);
+        if let Some(token) = token {
+            request = request.header(reqwest::header::AUTHORIZATION, format!("Token {}", token));
+        }
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            return Err(SinkError::Rejected(response.status()));
+        }
+        Ok(())
+    }
+}
+
+/// InfluxDB line protocol, one `satellite_position` point per snapshot.
+fn snapshot_lines(rows: &[(DateTime<Utc>, &NewSnapshot)]) -> String {
+    let mut out = String::new();
+    for (t, s) in rows {
+        let [x, y, z] = s.position;
+        let [vx, vy, vz] = s.velocity;
+        let _ = writeln!(
+            out,
+            "satellite_position,norad_id={} x_km={},y_km={},z_km={},vx_km_s={},vy_km_s={},vz_km_s={} {}",
+            s.norad_id,
+            x,
+            y,
+            z,
+            vx,
+            vy,
+            vz,
+            t.timestamp_millis()
+        );
+    }
+    out
+}
+
+/// InfluxDB line protocol, one `satellite_pass` point per pass stamped with its start.
+fn pass_lines(passes: &[PassEvent]) -> String {
+    let mut out = String::new();
+    for p in passes {
+        let _ = writeln!(
+            out,
+            "satellite_pass,norad_id={},station_id={} max_elevation_deg={},duration_s={}i,tca_ms={}i,end_ms={}i {}",
+            p.norad_id,
+            p.station_id,
+            p.max_elevation_deg,
+            (p.end - p.start).num_seconds(),
+            p.tca.timestamp_millis(),
+            p.end.timestamp_millis(),
+            p.start.timestamp_millis()
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_line_protocol() {
+        let s = NewSnapshot { norad_id: 25544, timestamp: String::new(), position: [1.5, 2.0, 3.0], velocity: [0.0, 7.5, 0.0] };
+        let t = DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z").unwrap().with_timezone(&Utc);
+        assert_eq!(
+            snapshot_lines(&[(t, &s)]),
+            "satellite_position,norad_id=25544 x_km=1.5,y_km=2,z_km=3,vx_km_s=0,vy_km_s=7.5,vz_km_s=0 1704067200000\n"
+        );
+        let pass = PassEvent { norad_id: 25544, station_id: 3, start: t, tca: t + chrono::Duration::seconds(300), end: t + chrono::Duration::seconds(600), max_elevation_deg: 45.0 };
+        assert_eq!(
+            pass_lines(&[pass]),
+            "satellite_pass,norad_id=25544,station_id=3 max_elevation_deg=45,duration_s=600i,tca_ms=1704067500000i,end_ms=1704067800000i 1704067200000\n"
+        );
+    }
+}
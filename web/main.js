@@ -54,7 +54,7 @@ const stepEl = document.getElementById('step');
 const minEl = document.getElementById('minel');
 const predictBtnEl = document.getElementById('predict-btn');
 
-const api = axios.create({ baseURL: '' }); // relative to same origin
+const api = axios.create({ baseURL: '/api/v1' }); // relative to same origin
 const navGlobeBtn = document.getElementById('nav-globe');
 const navStationsBtn = document.getElementById('nav-stations');
 const tabGlobe = document.getElementById('tab-globe');